@@ -7,6 +7,7 @@
 .global halt_cpu           # Rust'tan çağrılabilir yap
 .global read_csr           # Rust'tan çağrılabilir yap
 .global write_csr          # Rust'tan çağrılabilir yap
+.global thread_exit_trampoline # Task::new tarafından ra'ya yazılır
 
 # void disable_interrupts(void);
 # Makine kesmelerini (MIE biti) devre dışı bırakır.
@@ -51,6 +52,15 @@ write_csr:
     csrw a0, a1           # a0'daki CSR adresine a1'deki değeri yaz
     ret                   # Fonksiyondan dön
 
+# __attribute__((noreturn)) void thread_exit_trampoline(void);
+# Her görevin/iş parçacığının ra registerı buraya işaret eder.
+# Giriş fonksiyonu döndüğünde dönüş değeri zaten a0'dadır;
+# SYSCALL_THREAD_EXIT (15) ile iş parçacığı bu değerle sonlandırılır.
+thread_exit_trampoline:
+    li a7, 15             # SYSCALL_THREAD_EXIT (sahne64::arch ile aynı olmalı)
+    ecall                 # Çekirdeğe geç, a0 = çıkış kodu
+1:  j 1b                  # sys_exit geri dönmez, yine de güvenlik için döngü
+
 # Diğer genel yardımcı fonksiyonlar buraya eklenebilir
 # Örneğin, bellek bariyerleri (fences) veya atomik operasyonlar için.
 # Ancak rs_io.S dosyası I/O odaklı bariyerleri içerecek.
\ No newline at end of file
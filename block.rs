@@ -0,0 +1,361 @@
+// main_kernel/block.rs
+// Blok Cihaz Soyutlaması
+// eMMC, SD kart ve virtio-blk sürücüleri aynı BlockDevice trait'ini uygular; üst katmanlar (bölüm tablosu,
+// kaynak yöneticisi, blok önbelleği) cihazın türünü bilmez. Cihazlar blok blok okunur/yazılır;
+// read_at/write_at kaynakların bayt konumlu okuma/yazmalarını bloklara çevirir.
+//
+// Donanımdan bağımsızdır; host testleri bellekteki bir diskle çalışır.
+
+use alloc::vec::Vec;
+
+use crate::sahne64::SahneError;
+
+/// Blok tabanlı depolama cihazı. Aynı anda birden fazla görevden kullanılabileceği için
+/// uygulamalar kendi iç durumlarını kilitlemelidir.
+pub trait BlockDevice {
+    /// Blok boyutu (bayt). 512 veya 2'nin daha büyük bir kuvvetidir.
+    fn block_size(&self) -> usize;
+
+    /// Toplam blok sayısı (cihaz kullanılamaz hale geldiyse 0).
+    fn block_count(&self) -> u64;
+
+    /// `first` bloğundan başlayarak `buffer.len() / block_size()` blok okur.
+    /// Tampon boyutu blok boyutunun katı olmalı, aralık cihazın içinde kalmalıdır (yoksa InvalidParameter).
+    fn read_blocks(&self, first: u64, buffer: &mut [u8]) -> Result<(), SahneError>;
+
+    /// `first` bloğundan başlayarak bloklar yazar. Kurallar read_blocks ile aynıdır;
+    /// salt okunur cihazlar PermissionDenied döner.
+    fn write_blocks(&self, first: u64, buffer: &[u8]) -> Result<(), SahneError>;
+
+    /// `first` bloğundan başlayan ardışık blokları sırayla `segments` parçalarına okur (scatter list).
+    /// Her parça blok boyutunun katı olmalıdır. Çok bloklu komut veya DMA destekleyen sürücüler bunu tek
+    /// aktarımla yapar; varsayılan uygulama parçaları tek tek read_blocks ile okur.
+    fn read_blocks_scatter(&self, first: u64, segments: &mut [&mut [u8]]) -> Result<(), SahneError> {
+        check_segments(self, first, segments.iter().map(|segment| segment.len()))?;
+        let mut block = first;
+        for segment in segments.iter_mut() {
+            self.read_blocks(block, segment)?;
+            block += (segment.len() / self.block_size()) as u64;
+        }
+        Ok(())
+    }
+
+    /// `segments` parçalarını `first` bloğundan başlayarak ardışık bloklara yazar (gather list).
+    /// Kurallar read_blocks_scatter ile aynıdır.
+    fn write_blocks_gather(&self, first: u64, segments: &[&[u8]]) -> Result<(), SahneError> {
+        check_segments(self, first, segments.iter().map(|segment| segment.len()))?;
+        let mut block = first;
+        for segment in segments {
+            self.write_blocks(block, segment)?;
+            block += (segment.len() / self.block_size()) as u64;
+        }
+        Ok(())
+    }
+
+    /// Cihazın yazma önbelleğini kalıcı depolamaya yazdırır.
+    fn flush(&self) -> Result<(), SahneError> {
+        Ok(())
+    }
+
+    /// Cihaz salt okunur mu (yazma korumalı SD kart, salt okunur imaj ...)?
+    fn is_read_only(&self) -> bool {
+        false
+    }
+}
+
+impl<T: BlockDevice + ?Sized> BlockDevice for &T {
+    fn block_size(&self) -> usize {
+        (**self).block_size()
+    }
+
+    fn block_count(&self) -> u64 {
+        (**self).block_count()
+    }
+
+    fn read_blocks(&self, first: u64, buffer: &mut [u8]) -> Result<(), SahneError> {
+        (**self).read_blocks(first, buffer)
+    }
+
+    fn write_blocks(&self, first: u64, buffer: &[u8]) -> Result<(), SahneError> {
+        (**self).write_blocks(first, buffer)
+    }
+
+    fn read_blocks_scatter(&self, first: u64, segments: &mut [&mut [u8]]) -> Result<(), SahneError> {
+        (**self).read_blocks_scatter(first, segments)
+    }
+
+    fn write_blocks_gather(&self, first: u64, segments: &[&[u8]]) -> Result<(), SahneError> {
+        (**self).write_blocks_gather(first, segments)
+    }
+
+    fn flush(&self) -> Result<(), SahneError> {
+        (**self).flush()
+    }
+
+    fn is_read_only(&self) -> bool {
+        (**self).is_read_only()
+    }
+}
+
+/// `first` ve `buffer` cihazın sınırları içinde mi? Sürücüler read/write_blocks başında kullanır.
+pub fn check_range<D: BlockDevice + ?Sized>(device: &D, first: u64, len: usize) -> Result<(), SahneError> {
+    let block_size = device.block_size();
+    if !len.is_multiple_of(block_size) {
+        return Err(SahneError::InvalidParameter);
+    }
+    let end = first.checked_add((len / block_size) as u64).ok_or(SahneError::InvalidParameter)?;
+    if end > device.block_count() {
+        return Err(SahneError::InvalidParameter);
+    }
+    Ok(())
+}
+
+/// Scatter/gather listesi için check_range: her parça blok boyutunun katı olmalı, toplam aralık cihazın
+/// içinde kalmalıdır. Toplam blok sayısını döndürür.
+pub fn check_segments<D: BlockDevice + ?Sized>(
+    device: &D,
+    first: u64,
+    lengths: impl Iterator<Item = usize>,
+) -> Result<usize, SahneError> {
+    let block_size = device.block_size();
+    let mut total = 0usize;
+    for len in lengths {
+        if !len.is_multiple_of(block_size) {
+            return Err(SahneError::InvalidParameter);
+        }
+        total = total.checked_add(len).ok_or(SahneError::InvalidParameter)?;
+    }
+    check_range(device, first, total)?;
+    Ok(total / block_size)
+}
+
+/// Cihazın bayt cinsinden boyutu.
+pub fn size_bytes<D: BlockDevice + ?Sized>(device: &D) -> u64 {
+    device.block_count().saturating_mul(device.block_size() as u64)
+}
+
+// Kısmi bloklar için tek bloğluk geçici tampon. Bellek yetersizse panik yerine OutOfMemory döner.
+fn scratch_block(block_size: usize) -> Result<Vec<u8>, SahneError> {
+    let mut block = Vec::new();
+    block.try_reserve_exact(block_size).map_err(|_| SahneError::OutOfMemory)?;
+    block.resize(block_size, 0);
+    Ok(block)
+}
+
+/// Cihazın `offset` baytından okur. Cihazın sonunda kısa okuma yapar; sonrasında 0 döner.
+/// Tam bloklar doğrudan `buffer`a okunur, baştaki ve sondaki kısmi bloklar geçici tampondan geçer.
+pub fn read_at<D: BlockDevice + ?Sized>(device: &D, offset: u64, buffer: &mut [u8]) -> Result<usize, SahneError> {
+    let size = size_bytes(device);
+    if offset >= size {
+        return Ok(0);
+    }
+    let len = (buffer.len() as u64).min(size - offset) as usize;
+    let block_size = device.block_size();
+    let mut done = 0;
+    while done < len {
+        let position = offset + done as u64;
+        let block = position / block_size as u64;
+        let skip = (position % block_size as u64) as usize;
+        let remaining = len - done;
+        if skip == 0 && remaining >= block_size {
+            let whole = remaining - remaining % block_size;
+            device.read_blocks(block, &mut buffer[done..done + whole])?;
+            done += whole;
+        } else {
+            let chunk = remaining.min(block_size - skip);
+            let mut scratch = scratch_block(block_size)?;
+            device.read_blocks(block, &mut scratch)?;
+            buffer[done..done + chunk].copy_from_slice(&scratch[skip..skip + chunk]);
+            done += chunk;
+        }
+    }
+    Ok(len)
+}
+
+/// Cihazın `offset` baytına yazar. Kısmi bloklar önce okunur (read-modify-write).
+/// Cihazın sonunda kısa yazma yapar; tamamen cihazın dışındaki yazma InvalidParameter döner.
+pub fn write_at<D: BlockDevice + ?Sized>(device: &D, offset: u64, buffer: &[u8]) -> Result<usize, SahneError> {
+    if device.is_read_only() {
+        return Err(SahneError::PermissionDenied);
+    }
+    if buffer.is_empty() {
+        return Ok(0);
+    }
+    let size = size_bytes(device);
+    if offset >= size {
+        return Err(SahneError::InvalidParameter);
+    }
+    let len = (buffer.len() as u64).min(size - offset) as usize;
+    let block_size = device.block_size();
+    let mut done = 0;
+    while done < len {
+        let position = offset + done as u64;
+        let block = position / block_size as u64;
+        let skip = (position % block_size as u64) as usize;
+        let remaining = len - done;
+        if skip == 0 && remaining >= block_size {
+            let whole = remaining - remaining % block_size;
+            device.write_blocks(block, &buffer[done..done + whole])?;
+            done += whole;
+        } else {
+            let chunk = remaining.min(block_size - skip);
+            let mut scratch = scratch_block(block_size)?;
+            device.read_blocks(block, &mut scratch)?;
+            scratch[skip..skip + chunk].copy_from_slice(&buffer[done..done + chunk]);
+            device.write_blocks(block, &scratch)?;
+            done += chunk;
+        }
+    }
+    Ok(len)
+}
+
+/// Bir cihazın ardışık bloklarından oluşan bölüm (partition). Blok numaraları bölümün başına göredir.
+pub struct Partition<D: BlockDevice> {
+    device: D,
+    first_block: u64,
+    block_count: u64,
+}
+
+impl<D: BlockDevice> Partition<D> {
+    /// Aralık cihazın dışına taşıyorsa InvalidParameter döner.
+    pub fn new(device: D, first_block: u64, block_count: u64) -> Result<Self, SahneError> {
+        let end = first_block.checked_add(block_count).ok_or(SahneError::InvalidParameter)?;
+        if block_count == 0 || end > device.block_count() {
+            return Err(SahneError::InvalidParameter);
+        }
+        Ok(Partition { device, first_block, block_count })
+    }
+
+    pub fn first_block(&self) -> u64 {
+        self.first_block
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for Partition<D> {
+    fn block_size(&self) -> usize {
+        self.device.block_size()
+    }
+
+    fn block_count(&self) -> u64 {
+        self.block_count
+    }
+
+    fn read_blocks(&self, first: u64, buffer: &mut [u8]) -> Result<(), SahneError> {
+        check_range(self, first, buffer.len())?;
+        self.device.read_blocks(self.first_block + first, buffer)
+    }
+
+    fn write_blocks(&self, first: u64, buffer: &[u8]) -> Result<(), SahneError> {
+        check_range(self, first, buffer.len())?;
+        self.device.write_blocks(self.first_block + first, buffer)
+    }
+
+    fn read_blocks_scatter(&self, first: u64, segments: &mut [&mut [u8]]) -> Result<(), SahneError> {
+        check_segments(self, first, segments.iter().map(|segment| segment.len()))?;
+        self.device.read_blocks_scatter(self.first_block + first, segments)
+    }
+
+    fn write_blocks_gather(&self, first: u64, segments: &[&[u8]]) -> Result<(), SahneError> {
+        check_segments(self, first, segments.iter().map(|segment| segment.len()))?;
+        self.device.write_blocks_gather(self.first_block + first, segments)
+    }
+
+    fn flush(&self) -> Result<(), SahneError> {
+        self.device.flush()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.device.is_read_only()
+    }
+}
+
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use core::cell::RefCell;
+
+    /// Testler için bellekteki disk (partition.rs testleri de kullanır).
+    pub(crate) struct RamDisk {
+        pub(crate) data: RefCell<Vec<u8>>,
+        pub(crate) block_size: usize,
+    }
+
+    impl RamDisk {
+        pub(crate) fn new(blocks: usize, block_size: usize) -> Self {
+            RamDisk { data: RefCell::new(alloc::vec![0; blocks * block_size]), block_size }
+        }
+    }
+
+    impl BlockDevice for RamDisk {
+        fn block_size(&self) -> usize {
+            self.block_size
+        }
+
+        fn block_count(&self) -> u64 {
+            (self.data.borrow().len() / self.block_size) as u64
+        }
+
+        fn read_blocks(&self, first: u64, buffer: &mut [u8]) -> Result<(), SahneError> {
+            check_range(self, first, buffer.len())?;
+            let start = first as usize * self.block_size;
+            buffer.copy_from_slice(&self.data.borrow()[start..start + buffer.len()]);
+            Ok(())
+        }
+
+        fn write_blocks(&self, first: u64, buffer: &[u8]) -> Result<(), SahneError> {
+            check_range(self, first, buffer.len())?;
+            let start = first as usize * self.block_size;
+            self.data.borrow_mut()[start..start + buffer.len()].copy_from_slice(buffer);
+            Ok(())
+        }
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn byte_access_handles_partial_blocks_and_device_end() {
+        let disk = RamDisk::new(8, 512);
+        let pattern: Vec<u8> = (0..1500u32).map(|i| (i % 251) as u8).collect();
+        assert_eq!(write_at(&disk, 300, &pattern), Ok(1500));
+        assert_eq!(disk.data.borrow()[299], 0); // Yazılan alanın öncesi ve sonrası bozulmamalı
+        assert_eq!(disk.data.borrow()[1800], 0);
+
+        let mut read_back = alloc::vec![0u8; 1500];
+        assert_eq!(read_at(&disk, 300, &mut read_back), Ok(1500));
+        assert_eq!(read_back, pattern);
+
+        let mut tail = [0u8; 16];
+        assert_eq!(read_at(&disk, 8 * 512 - 4, &mut tail), Ok(4));
+        assert_eq!(read_at(&disk, 8 * 512, &mut tail), Ok(0));
+        assert_eq!(write_at(&disk, 8 * 512 - 4, &tail), Ok(4));
+        assert_eq!(write_at(&disk, 8 * 512, &tail), Err(SahneError::InvalidParameter));
+        assert_eq!(disk.read_blocks(7, &mut [0u8; 1024]), Err(SahneError::InvalidParameter));
+        assert_eq!(disk.read_blocks(0, &mut [0u8; 100]), Err(SahneError::InvalidParameter));
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn partition_offsets_and_bounds_accesses() {
+        let disk = RamDisk::new(16, 512);
+        assert!(Partition::new(&disk, 10, 7).is_err()); // Cihazın dışına taşıyor
+        assert!(Partition::new(&disk, 4, 0).is_err());
+
+        let partition = Partition::new(&disk, 4, 6).unwrap();
+        assert_eq!(partition.block_count(), 6);
+        assert_eq!(write_at(&partition, 0, &[0xAB; 512]), Ok(512));
+        assert_eq!(disk.data.borrow()[4 * 512], 0xAB);
+        assert_eq!(disk.data.borrow()[4 * 512 - 1], 0);
+
+        // Bölümün sonundan sonrası, cihazda yer olsa da erişilemez.
+        assert_eq!(partition.write_blocks(6, &[0; 512]), Err(SahneError::InvalidParameter));
+        assert_eq!(write_at(&partition, 6 * 512 - 2, &[1; 8]), Ok(2));
+        assert_eq!(disk.data.borrow()[10 * 512], 0);
+
+        // Scatter list bölümün başına göre okunur ve parçaların toplamı sınırlara uymalıdır.
+        let (mut first, mut second) = ([0u8; 512], [0u8; 1024]);
+        assert_eq!(partition.read_blocks_scatter(0, &mut [&mut first, &mut second]), Ok(()));
+        assert_eq!((first[0], second[0]), (0xAB, 0));
+        assert_eq!(partition.read_blocks_scatter(4, &mut [&mut first, &mut second]), Err(SahneError::InvalidParameter));
+        assert_eq!(partition.write_blocks_gather(0, &[&[0; 100]]), Err(SahneError::InvalidParameter));
+    }
+}
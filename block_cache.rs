@@ -0,0 +1,419 @@
+// main_kernel/block_cache.rs
+// Blok Önbelleği (LRU, write-back)
+// Dosya sistemi aynı süper blok, grup tanımlayıcı ve i-node tablosu bloklarını tekrar tekrar okur;
+// eMMC'nin PIO yolu yavaş olduğu için bloklar bellekte tutulur. Yazmalar önbellekte kirli (dirty)
+// olarak işaretlenir ve flush ile (sync sistem çağrısı, periyodik boşaltma, kapatma kancası) aygıta yazılır.
+//
+// Tek bir önbellek tüm cihazlar arasında paylaşılır; bellek bütçesi bayt cinsindendir. Bütçe dolunca
+// en uzun süredir kullanılmayan blok çıkarılır (kirliyse önce aygıta yazılır). Bellek yetersizse
+// blok önbelleğe alınmadan doğrudan aygıta gider.
+//
+// Donanımdan bağımsızdır; host testleri bellekteki disklerle çalışır.
+
+use alloc::vec::Vec;
+
+use crate::block::{check_segments, BlockDevice};
+use crate::sahne64::SahneError;
+
+/// Bu kadar veya daha fazla blokluk aktarımlar önbelleği atlar (imaj kopyalama, büyük dosya okuma):
+/// sık kullanılan meta veri bloklarını önbellekten atmazlar ve sürücüye tek komut olarak giderler.
+pub const DIRECT_TRANSFER_BLOCKS: usize = 16;
+
+/// Bir cihazın önbellek sayaçları.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,       // Önbellekten karşılanan blok okumaları
+    pub misses: u64,     // Aygıttan okunan bloklar
+    pub writebacks: u64, // Aygıta yazılan kirli bloklar
+    pub evictions: u64,  // Yer açmak için çıkarılan bloklar
+}
+
+struct Entry {
+    device: usize,
+    block: u64,
+    data: Vec<u8>,
+    dirty: bool,
+    last_used: u64, // LRU sayacı
+}
+
+// Kayıtlı bir cihaz ve sayaçları. Kaldırılan cihazların yuvası boş kalır (kimlikler yeniden kullanılmaz).
+struct DeviceSlot<D> {
+    device: D,
+    stats: CacheStats,
+}
+
+/// Cihazlar arasında paylaşılan blok önbelleği. Cihazlar add_device ile eklenir ve verilen kimlikle kullanılır.
+pub struct BlockCache<D: BlockDevice> {
+    devices: Vec<Option<DeviceSlot<D>>>,
+    entries: Vec<Entry>,
+    budget: usize, // Blok verileri için en fazla bayt
+    used: usize,
+    clock: u64,
+}
+
+impl<D: BlockDevice> BlockCache<D> {
+    pub const fn new(budget: usize) -> Self {
+        BlockCache { devices: Vec::new(), entries: Vec::new(), budget, used: 0, clock: 0 }
+    }
+
+    /// Cihazı ekler ve önbellek kimliğini döndürür.
+    pub fn add_device(&mut self, device: D) -> Result<usize, SahneError> {
+        self.devices.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+        self.devices.push(Some(DeviceSlot { device, stats: CacheStats::default() }));
+        Ok(self.devices.len() - 1)
+    }
+
+    /// Cihazı kaldırır (örn. SD kart çıkarıldı). Kirli bloklar yazılmadan atılır; sayıları döndürülür.
+    /// Kimlik yeniden kullanılmaz: kaldırılan cihazın kimliğiyle yapılan çağrılar ResourceNotFound döner.
+    pub fn remove_device(&mut self, id: usize) -> usize {
+        let lost = self.entries.iter().filter(|entry| entry.device == id && entry.dirty).count();
+        self.invalidate(id);
+        if let Some(slot) = self.devices.get_mut(id) {
+            *slot = None;
+        }
+        lost
+    }
+
+    fn slot(&self, id: usize) -> Result<&DeviceSlot<D>, SahneError> {
+        self.devices.get(id).and_then(|slot| slot.as_ref()).ok_or(SahneError::ResourceNotFound)
+    }
+
+    fn stats_mut(&mut self, id: usize) -> &mut CacheStats {
+        // Çağıranlar kimliği önceden doğrular.
+        &mut self.devices[id].as_mut().unwrap().stats
+    }
+
+    pub fn stats(&self, id: usize) -> Result<CacheStats, SahneError> {
+        self.slot(id).map(|slot| slot.stats)
+    }
+
+    /// Önbellekteki blok sayısı ve kirli olanların sayısı.
+    pub fn usage(&self) -> (usize, usize) {
+        (self.entries.len(), self.entries.iter().filter(|entry| entry.dirty).count())
+    }
+
+    fn touch(&mut self, index: usize) {
+        self.clock += 1;
+        self.entries[index].last_used = self.clock;
+    }
+
+    fn find(&self, id: usize, block: u64) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.device == id && entry.block == block)
+    }
+
+    // Kirli bir girdiyi aygıtına yazar.
+    fn write_back(&mut self, index: usize) -> Result<(), SahneError> {
+        let entry = &self.entries[index];
+        if !entry.dirty {
+            return Ok(());
+        }
+        let id = entry.device;
+        self.slot(id)?.device.write_blocks(entry.block, &entry.data)?;
+        self.entries[index].dirty = false;
+        self.stats_mut(id).writebacks += 1;
+        Ok(())
+    }
+
+    // Yeni bir blok için girdi hazırlar (verisi geçersiz) ve indeksini döndürür. Bütçe doluysa en eski
+    // blok çıkarılır. Bellek ayrılamazsa None döner; çağıran bloğu önbelleğe almadan aktarır.
+    fn allocate(&mut self, id: usize, block: u64, block_size: usize) -> Result<Option<usize>, SahneError> {
+        while self.used + block_size > self.budget {
+            let Some(victim) = (0..self.entries.len()).min_by_key(|&index| self.entries[index].last_used) else {
+                return Ok(None); // Bütçe tek bloğa bile yetmiyor
+            };
+            // Aynı boyuttaki girdi yeniden kullanılır; kirliyse veri kaybolmasın diye önce yazılır.
+            self.write_back(victim)?;
+            let victim_device = self.entries[victim].device;
+            if let Some(slot) = self.devices[victim_device].as_mut() {
+                slot.stats.evictions += 1;
+            }
+            if self.entries[victim].data.len() == block_size {
+                let entry = &mut self.entries[victim];
+                entry.device = id;
+                entry.block = block;
+                return Ok(Some(victim));
+            }
+            let removed = self.entries.swap_remove(victim);
+            self.used -= removed.data.len();
+        }
+
+        let mut data = Vec::new();
+        if data.try_reserve_exact(block_size).is_err() || self.entries.try_reserve(1).is_err() {
+            return Ok(None);
+        }
+        data.resize(block_size, 0);
+        self.used += block_size;
+        self.entries.push(Entry { device: id, block, data, dirty: false, last_used: 0 });
+        Ok(Some(self.entries.len() - 1))
+    }
+
+    fn forget(&mut self, index: usize) {
+        let removed = self.entries.swap_remove(index);
+        self.used -= removed.data.len();
+    }
+
+    /// `first` bloğundan başlayarak `buffer`ı okur (BlockDevice::read_blocks kuralları).
+    pub fn read(&mut self, id: usize, first: u64, buffer: &mut [u8]) -> Result<(), SahneError> {
+        self.read_scatter(id, first, &mut [buffer])
+    }
+
+    /// Blokları önbelleğe kirli olarak yazar; aygıta flush sırasında veya blok çıkarılırken yazılır.
+    pub fn write(&mut self, id: usize, first: u64, buffer: &[u8]) -> Result<(), SahneError> {
+        self.write_gather(id, first, &[buffer])
+    }
+
+    /// Ardışık blokları `segments` parçalarına okur (BlockDevice::read_blocks_scatter kuralları).
+    /// DIRECT_TRANSFER_BLOCKS veya daha uzun okumalar önbelleği atlayıp aygıttan tek aktarımla okunur.
+    pub fn read_scatter(&mut self, id: usize, first: u64, segments: &mut [&mut [u8]]) -> Result<(), SahneError> {
+        let (block_size, blocks) = {
+            let device = &self.slot(id)?.device;
+            (device.block_size(), check_segments(device, first, segments.iter().map(|segment| segment.len()))?)
+        };
+        if blocks >= DIRECT_TRANSFER_BLOCKS {
+            return self.read_direct(id, first, blocks, segments);
+        }
+        let mut block = first;
+        for segment in segments.iter_mut() {
+            for chunk in segment.chunks_exact_mut(block_size) {
+                self.read_block(id, block, chunk)?;
+                block += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// `segments` parçalarını ardışık bloklara yazar. Kısa yazmalar önbellekte kirli kalır;
+    /// DIRECT_TRANSFER_BLOCKS veya daha uzun yazmalar doğrudan aygıta gider.
+    pub fn write_gather(&mut self, id: usize, first: u64, segments: &[&[u8]]) -> Result<(), SahneError> {
+        let (block_size, blocks) = {
+            let device = &self.slot(id)?.device;
+            if device.is_read_only() {
+                return Err(SahneError::PermissionDenied);
+            }
+            (device.block_size(), check_segments(device, first, segments.iter().map(|segment| segment.len()))?)
+        };
+        if blocks >= DIRECT_TRANSFER_BLOCKS {
+            return self.write_direct(id, first, blocks, segments);
+        }
+        let mut block = first;
+        for segment in segments {
+            for chunk in segment.chunks_exact(block_size) {
+                self.write_block(id, block, chunk)?;
+                block += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_block(&mut self, id: usize, block: u64, chunk: &mut [u8]) -> Result<(), SahneError> {
+        if let Some(index) = self.find(id, block) {
+            chunk.copy_from_slice(&self.entries[index].data);
+            self.touch(index);
+            self.stats_mut(id).hits += 1;
+            return Ok(());
+        }
+        self.stats_mut(id).misses += 1;
+        match self.allocate(id, block, chunk.len())? {
+            Some(index) => {
+                let device = &self.devices[id].as_ref().unwrap().device;
+                if let Err(err) = device.read_blocks(block, &mut self.entries[index].data) {
+                    self.forget(index);
+                    return Err(err);
+                }
+                self.entries[index].dirty = false;
+                chunk.copy_from_slice(&self.entries[index].data);
+                self.touch(index);
+                Ok(())
+            }
+            None => self.slot(id)?.device.read_blocks(block, chunk),
+        }
+    }
+
+    fn write_block(&mut self, id: usize, block: u64, chunk: &[u8]) -> Result<(), SahneError> {
+        let index = match self.find(id, block) {
+            Some(index) => Some(index),
+            None => self.allocate(id, block, chunk.len())?,
+        };
+        match index {
+            Some(index) => {
+                self.entries[index].data.copy_from_slice(chunk);
+                self.entries[index].dirty = true;
+                self.touch(index);
+                Ok(())
+            }
+            None => self.slot(id)?.device.write_blocks(block, chunk),
+        }
+    }
+
+    // Aygıttan doğrudan okur; önbellekteki kirli bloklar aygıttakinden yeni olduğu için üzerine kopyalanır.
+    fn read_direct(&mut self, id: usize, first: u64, blocks: usize, segments: &mut [&mut [u8]]) -> Result<(), SahneError> {
+        self.slot(id)?.device.read_blocks_scatter(first, segments)?;
+        self.stats_mut(id).misses += blocks as u64;
+        for entry in self.entries.iter().filter(|entry| entry.dirty && in_range(entry, id, first, blocks)) {
+            let (segment, offset) = locate(segments.iter().map(|segment| segment.len()), block_offset(entry, first));
+            segments[segment][offset..offset + entry.data.len()].copy_from_slice(&entry.data);
+        }
+        Ok(())
+    }
+
+    // Aygıta doğrudan yazar; aralıktaki önbellek kopyaları yeni veriyle güncellenir ve temiz sayılır.
+    fn write_direct(&mut self, id: usize, first: u64, blocks: usize, segments: &[&[u8]]) -> Result<(), SahneError> {
+        self.slot(id)?.device.write_blocks_gather(first, segments)?;
+        for entry in self.entries.iter_mut().filter(|entry| in_range(entry, id, first, blocks)) {
+            let (segment, offset) = locate(segments.iter().map(|segment| segment.len()), block_offset(entry, first));
+            let len = entry.data.len();
+            entry.data.copy_from_slice(&segments[segment][offset..offset + len]);
+            entry.dirty = false;
+        }
+        Ok(())
+    }
+
+    /// Cihazın kirli bloklarını blok sırasıyla yazar ve aygıtın kendi önbelleğini boşaltır.
+    pub fn flush(&mut self, id: usize) -> Result<(), SahneError> {
+        self.slot(id)?;
+        loop {
+            // Her turda en küçük numaralı kirli blok yazılır (aygıtta sıralı erişim).
+            let next = (0..self.entries.len())
+                .filter(|&index| self.entries[index].device == id && self.entries[index].dirty)
+                .min_by_key(|&index| self.entries[index].block);
+            match next {
+                Some(index) => self.write_back(index)?,
+                None => break,
+            }
+        }
+        self.slot(id)?.device.flush()
+    }
+
+    /// Tüm cihazları boşaltır. Hata olsa da diğer cihazlara devam edilir; ilk hata döner.
+    pub fn flush_all(&mut self) -> Result<(), SahneError> {
+        let mut result = Ok(());
+        for id in 0..self.devices.len() {
+            if self.devices[id].is_none() {
+                continue;
+            }
+            if let Err(err) = self.flush(id) {
+                result = result.and(Err(err));
+            }
+        }
+        result
+    }
+
+    /// Cihazın tüm bloklarını (kirli olanlar dahil) önbellekten atar.
+    pub fn invalidate(&mut self, id: usize) {
+        let mut index = 0;
+        while index < self.entries.len() {
+            if self.entries[index].device == id {
+                self.forget(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+fn in_range(entry: &Entry, id: usize, first: u64, blocks: usize) -> bool {
+    entry.device == id && entry.block >= first && entry.block - first < blocks as u64
+}
+
+// Girdinin aktarımın başından itibaren bayt konumu.
+fn block_offset(entry: &Entry, first: u64) -> usize {
+    (entry.block - first) as usize * entry.data.len()
+}
+
+// Aktarımdaki bayt konumunu (parça, parça içi konum) çiftine çevirir. Konum aralık içinde olmalıdır.
+fn locate(lengths: impl Iterator<Item = usize>, mut offset: usize) -> (usize, usize) {
+    for (segment, len) in lengths.enumerate() {
+        if offset < len {
+            return (segment, offset);
+        }
+        offset -= len;
+    }
+    unreachable!("locate: konum aktarımın dışında");
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::tests::RamDisk;
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn repeated_reads_hit_and_writes_stay_dirty_until_flush() {
+        let disk = RamDisk::new(16, 512);
+        disk.data.borrow_mut()[512..1024].fill(7);
+        let mut cache = BlockCache::new(4 * 512);
+        let id = cache.add_device(&disk).unwrap();
+
+        let mut buffer = [0u8; 512];
+        cache.read(id, 1, &mut buffer).unwrap();
+        cache.read(id, 1, &mut buffer).unwrap();
+        assert_eq!(buffer, [7; 512]);
+        let stats = cache.stats(id).unwrap();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+
+        cache.write(id, 2, &[9; 1024]).unwrap();
+        assert_eq!(disk.data.borrow()[2 * 512], 0); // Henüz aygıta yazılmadı
+        assert_eq!(cache.usage(), (3, 2));
+        let mut read_back = [0u8; 1024];
+        cache.read(id, 2, &mut read_back).unwrap();
+        assert_eq!(read_back, [9; 1024]);
+
+        cache.flush(id).unwrap();
+        assert_eq!(disk.data.borrow()[2 * 512..4 * 512], [9; 1024]);
+        assert_eq!(cache.usage(), (3, 0));
+        assert_eq!(cache.stats(id).unwrap().writebacks, 2);
+        assert_eq!(cache.read(id, 15, &mut [0u8; 1024]), Err(SahneError::InvalidParameter));
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn least_recently_used_block_is_written_back_on_eviction() {
+        let first = RamDisk::new(8, 512);
+        let second = RamDisk::new(8, 512);
+        let mut cache = BlockCache::new(2 * 512);
+        let a = cache.add_device(&first).unwrap();
+        let b = cache.add_device(&second).unwrap();
+
+        cache.write(a, 0, &[1; 512]).unwrap();
+        cache.read(b, 0, &mut [0u8; 512]).unwrap();
+        cache.read(b, 0, &mut [0u8; 512]).unwrap(); // b:0 son kullanılan; a:0 en eski
+        cache.read(b, 1, &mut [0u8; 512]).unwrap(); // a:0 çıkarılır ve önce yazılır
+        assert_eq!(first.data.borrow()[..512], [1; 512]);
+        assert_eq!(cache.stats(a).unwrap().evictions, 1);
+        assert_eq!(cache.usage(), (2, 0));
+
+        // Geçersiz kılınan cihazın kirli blokları atılır; kaldırılan cihaz kullanılamaz.
+        cache.write(b, 3, &[5; 512]).unwrap();
+        assert_eq!(cache.remove_device(b), 1);
+        assert_eq!(second.data.borrow()[3 * 512], 0);
+        assert_eq!(cache.read(b, 0, &mut [0u8; 512]), Err(SahneError::ResourceNotFound));
+        assert_eq!(cache.flush_all(), Ok(()));
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn large_transfers_bypass_cache_and_stay_coherent() {
+        let disk = RamDisk::new(64, 512);
+        let mut cache = BlockCache::new(8 * 512);
+        let id = cache.add_device(&disk).unwrap();
+
+        cache.write(id, 5, &[3; 512]).unwrap(); // Kirli, henüz aygıtta değil
+        let mut head = alloc::vec![0u8; 4 * 512];
+        let mut tail = alloc::vec![0u8; 12 * 512];
+        cache.read_scatter(id, 2, &mut [&mut head, &mut tail]).unwrap();
+        assert_eq!(head[3 * 512..], [3; 512]); // Kirli blok okunanın üzerine kopyalanır
+        assert_eq!(tail, [0; 12 * 512]);
+        assert_eq!(cache.usage(), (1, 1)); // Doğrudan okunan bloklar önbelleğe alınmaz
+
+        // Doğrudan yazma aygıta gider ve aralıktaki önbellek kopyasını günceller.
+        let data = alloc::vec![8u8; DIRECT_TRANSFER_BLOCKS * 512];
+        cache.write_gather(id, 4, &[&data[..512], &data[512..]]).unwrap();
+        assert_eq!(disk.data.borrow()[4 * 512..(4 + DIRECT_TRANSFER_BLOCKS) * 512], data[..]);
+        assert_eq!(cache.usage(), (1, 0));
+        let mut block = [0u8; 512];
+        cache.read(id, 5, &mut block).unwrap();
+        assert_eq!(block, [8; 512]);
+        assert_eq!(cache.read_scatter(id, 60, &mut [&mut tail]), Err(SahneError::InvalidParameter));
+    }
+}
@@ -0,0 +1,211 @@
+// main_kernel/calendar.rs
+// Takvim Hesapları (Gregoryen, UTC)
+// Unix zamanı (1970-01-01 00:00:00 UTC'den beri geçen saniye) ile tarih/saat arasında dönüşüm yapar.
+// Donanıma bağımlı değildir; RTC sürücüsü, duvar saati ve kullanıcı alanı aynı hesapları kullanır.
+// Saat dilimi ve artık saniye (leap second) desteklenmez.
+
+use crate::sahne64::SahneError;
+
+pub const SECONDS_PER_MINUTE: u64 = 60;
+pub const SECONDS_PER_HOUR: u64 = 60 * SECONDS_PER_MINUTE;
+pub const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+
+// Desteklenen yıl aralığı. 1970 öncesi Unix zamanı negatif olacağından reddedilir.
+pub const MIN_YEAR: u32 = 1970;
+pub const MAX_YEAR: u32 = 9999;
+// MAX_YEAR'ın son saniyesi (9999-12-31 23:59:59).
+pub const MAX_UNIX_SECONDS: u64 = 253_402_300_799;
+
+/// Takvim tarihi ve günün saati (UTC). Ay ve gün 1'den başlar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: u32,
+    pub month: u8,  // 1..=12
+    pub day: u8,    // 1..=days_in_month
+    pub hour: u8,   // 0..=23
+    pub minute: u8, // 0..=59
+    pub second: u8, // 0..=59
+}
+
+/// Artık yıl mı? 4'e bölünen yıllar artıktır; 100'e bölünenler değildir, 400'e bölünenler yine artıktır.
+pub const fn is_leap_year(year: u32) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+/// Ayın gün sayısı. Geçersiz ay için 0 döner.
+pub const fn days_in_month(year: u32, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+// 1970-01-01'den beri geçen gün sayısını (y, m, d) tarihine çevirir.
+// Yıl 1 Mart'ta başlatılarak şubatın artık günü yılın sonuna alınır; 400 yıllık döngüler (era)
+// 146097 gündür. (H. Hinnant, "chrono-Compatible Low-Level Date Algorithms")
+fn civil_from_days(days: u64) -> (u32, u8, u8) {
+    let z = days + 719_468; // 0000-03-01'den itibaren gün
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153; // Mart = 0
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year as u32, month, day)
+}
+
+// civil_from_days'in tersi. Tarihin MIN_YEAR veya sonrası ve geçerli olduğu varsayılır.
+fn days_from_civil(year: u32, month: u8, day: u8) -> u64 {
+    let year = year as u64 - if month <= 2 { 1 } else { 0 };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let mp = (month as u64 + 9) % 12; // Mart = 0
+    let day_of_year = (153 * mp + 2) / 5 + day as u64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+impl DateTime {
+    /// Unix zamanını (saniye) tarihe çevirir. MAX_YEAR'dan sonraki değerler MAX_YEAR'ın son saniyesine sabitlenir.
+    pub fn from_unix(seconds: u64) -> DateTime {
+        let seconds = seconds.min(MAX_UNIX_SECONDS);
+        let (year, month, day) = civil_from_days(seconds / SECONDS_PER_DAY);
+        let second_of_day = seconds % SECONDS_PER_DAY;
+        DateTime {
+            year,
+            month,
+            day,
+            hour: (second_of_day / SECONDS_PER_HOUR) as u8,
+            minute: (second_of_day % SECONDS_PER_HOUR / SECONDS_PER_MINUTE) as u8,
+            second: (second_of_day % SECONDS_PER_MINUTE) as u8,
+        }
+    }
+
+    /// Tarihi Unix zamanına (saniye) çevirir. Alanlar doğrulanır;
+    /// 2023-02-29 veya 24:00:00 gibi var olmayan bir zaman InvalidParameter ile reddedilir.
+    pub fn to_unix(&self) -> Result<u64, SahneError> {
+        if !self.is_valid() {
+            return Err(SahneError::InvalidParameter);
+        }
+        Ok(days_from_civil(self.year, self.month, self.day) * SECONDS_PER_DAY
+            + self.hour as u64 * SECONDS_PER_HOUR
+            + self.minute as u64 * SECONDS_PER_MINUTE
+            + self.second as u64)
+    }
+
+    /// Tüm alanlar desteklenen aralıkta ve tarih takvimde var mı?
+    pub fn is_valid(&self) -> bool {
+        (MIN_YEAR..=MAX_YEAR).contains(&self.year)
+            && (1..=12).contains(&self.month)
+            && self.day >= 1
+            && self.day <= days_in_month(self.year, self.month)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+    }
+
+    /// Haftanın günü: 0 = Pazartesi ... 6 = Pazar (1970-01-01 bir Perşembe'dir).
+    pub fn weekday(&self) -> u8 {
+        match self.to_unix() {
+            Ok(seconds) => ((seconds / SECONDS_PER_DAY + 3) % 7) as u8,
+            Err(_) => 0,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: u32, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> DateTime {
+        DateTime { year, month, day, hour, minute, second }
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn leap_years() {
+        assert!(is_leap_year(2024));
+        assert!(is_leap_year(2000)); // 400'e bölünür
+        assert!(!is_leap_year(2100)); // 100'e bölünür, 400'e bölünmez
+        assert!(!is_leap_year(2023));
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+        assert_eq!(days_in_month(2023, 13), 0);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn known_dates() {
+        assert_eq!(DateTime::from_unix(0), date(1970, 1, 1, 0, 0, 0));
+        assert_eq!(DateTime::from_unix(951_782_400), date(2000, 2, 29, 0, 0, 0));
+        assert_eq!(DateTime::from_unix(1_709_251_199), date(2024, 2, 29, 23, 59, 59));
+        assert_eq!(DateTime::from_unix(4_107_542_400), date(2100, 3, 1, 0, 0, 0));
+        assert_eq!(DateTime::from_unix(MAX_UNIX_SECONDS), date(9999, 12, 31, 23, 59, 59));
+        assert_eq!(DateTime::from_unix(u64::MAX), date(9999, 12, 31, 23, 59, 59));
+        assert_eq!(date(2100, 3, 1, 0, 0, 0).to_unix(), Ok(4_107_542_400));
+        assert_eq!(date(1970, 1, 1, 0, 0, 0).weekday(), 3); // Perşembe
+        assert_eq!(date(2024, 2, 29, 12, 0, 0).weekday(), 3); // Perşembe
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn rejects_invalid_dates() {
+        assert_eq!(date(2023, 2, 29, 0, 0, 0).to_unix(), Err(SahneError::InvalidParameter));
+        assert_eq!(date(2100, 2, 29, 0, 0, 0).to_unix(), Err(SahneError::InvalidParameter));
+        assert_eq!(date(2024, 4, 31, 0, 0, 0).to_unix(), Err(SahneError::InvalidParameter));
+        assert_eq!(date(2024, 1, 1, 24, 0, 0).to_unix(), Err(SahneError::InvalidParameter));
+        assert_eq!(date(2024, 0, 1, 0, 0, 0).to_unix(), Err(SahneError::InvalidParameter));
+        assert_eq!(date(1969, 12, 31, 23, 59, 59).to_unix(), Err(SahneError::InvalidParameter));
+        assert_eq!(date(10_000, 1, 1, 0, 0, 0).to_unix(), Err(SahneError::InvalidParameter));
+    }
+
+    // Her gün için ardışık dönüşüm: tarih bir gün ilerler ve geri dönüşüm aynı saniyeyi verir.
+    // Tam bir 400 yıllık döngü (ve 2000, 2100 gibi özel yıllar) kapsanır.
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn day_by_day_round_trip() {
+        let mut expected = date(1970, 1, 1, 0, 0, 0);
+        let mut days = 0;
+        while expected.year < MIN_YEAR + 401 {
+            let seconds = days * SECONDS_PER_DAY;
+            let current = DateTime::from_unix(seconds);
+            assert_eq!(current, expected);
+            assert_eq!(current.to_unix(), Ok(seconds));
+
+            expected.day += 1;
+            if expected.day > days_in_month(expected.year, expected.month) {
+                expected.day = 1;
+                expected.month += 1;
+                if expected.month > 12 {
+                    expected.month = 1;
+                    expected.year += 1;
+                }
+            }
+            days += 1;
+        }
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn random_seconds_round_trip() {
+        let mut state: u64 = 0x5EC0_4D5F_CA1E_4DA2;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..10_000 {
+            let seconds = next() % (MAX_UNIX_SECONDS + 1);
+            let datetime = DateTime::from_unix(seconds);
+            assert!(datetime.is_valid());
+            assert_eq!(datetime.to_unix(), Ok(seconds));
+        }
+    }
+}
@@ -1,20 +1,62 @@
 // main_kernel/console.rs
 // Çekirdek konsol çıktıları için soyutlama katmanı.
-// Başlangıçta seri portu kullanır, gelecekte grafik ekranı destekleyebilir.
+// Başlangıçta seri portu kullanır; ekran sürücüsü hazır olduğunda açılışta seçilen moda göre
+// framebuffer metin konsoluna (fbcon.rs) da yazabilir.
+// Uygulamalar konsola "console" kaynağı üzerinden erişir; girdi satır disiplininden (tty_io.rs) geçer.
 
 use core::fmt;
-use spin::Mutex; // Mutex için 'spin' crate'ini kullanacağız.
-use crate::serial; // Seri port sürücüsünü içeri aktar
+use spin::{Mutex, MutexGuard}; // Mutex için 'spin' crate'ini kullanacağız.
+use crate::drivers::uart; // Seri port (16550 UART) sürücüsü
+use crate::fbcon::{self, FbConsole};
+use crate::drivers::display;
+use crate::printk;
+use crate::resource_manager::{self, KernelResource};
+use crate::sahne64::resource::{CONTROL_TTY_GET_MODE, CONTROL_TTY_SET_MODE, MODE_NONBLOCK, MODE_READ, MODE_WRITE};
+use crate::sahne64::SahneError;
+use crate::sched;
+use crate::tty_io::LineDiscipline;
 
-// Konsol çıktı cihazını temsil eden enum (Gelecekte grafik ekran eklenebilir)
+/// Konsol çıktısının nereye gideceği. Açılışta bootargs'taki "console=" ile seçilir
+/// (console=serial, console=fb, console=both). Buzdolabında UART dışarıya açık olmadığı için varsayılan Both'tur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleMode {
+    Serial,
+    Framebuffer,
+    Both,
+}
+
+impl ConsoleMode {
+    /// Çekirdek komut satırındaki son "console=" değerini okur. Yoksa veya tanınmıyorsa None döner.
+    pub fn from_bootargs(bootargs: &str) -> Option<ConsoleMode> {
+        bootargs
+            .split_ascii_whitespace()
+            .rev()
+            .find_map(|arg| arg.strip_prefix("console="))
+            .and_then(|value| match value {
+                "serial" | "ttyS0" => Some(ConsoleMode::Serial),
+                "fb" | "tty0" => Some(ConsoleMode::Framebuffer),
+                "both" => Some(ConsoleMode::Both),
+                _ => None,
+            })
+    }
+}
+
+// Konsol çıktı cihazını temsil eden enum
 enum ConsoleDevice {
     Serial,
-    // Framebuffer(FramebufferWriter), // TODO: Grafik ekran sürücüsü eklendiğinde
+    Framebuffer(FbConsole<'static>),
+    Both(FbConsole<'static>), // Seri port + ekran
 }
 
-struct Console {
+// Ekran seçilmeden önce yazılan mesajlar burada tutulur ve ekran açıldığında yeniden çizilir;
+// böylece ilk açılış mesajları da ekranda görünür. Dolarsa sonraki mesajlar sadece seri porta gider.
+const EARLY_LOG_SIZE: usize = 2048;
+
+pub struct Console {
     device: ConsoleDevice,
-    // Gelecekte imleç pozisyonu, renk ayarları vb. eklenebilir
+    early_log: [u8; EARLY_LOG_SIZE],
+    early_log_len: usize,
+    recording: bool, // Mod seçilene kadar true
 }
 
 impl Console {
@@ -23,34 +65,68 @@ impl Console {
         // Başlangıçta seri portu kullan
         Console {
             device: ConsoleDevice::Serial,
+            early_log: [0; EARLY_LOG_SIZE],
+            early_log_len: 0,
+            recording: true,
         }
     }
 
     // Konsola bir karakter yazar.
     pub fn putc(&mut self, byte: u8) {
+        if self.recording && self.early_log_len < EARLY_LOG_SIZE {
+            self.early_log[self.early_log_len] = byte;
+            self.early_log_len += 1;
+        }
         match self.device {
             ConsoleDevice::Serial => {
-                serial::writer().putc(byte);
+                uart::putc(byte);
+            }
+            ConsoleDevice::Framebuffer(ref mut fb_writer) => {
+                fb_writer.write_byte(byte);
+            }
+            ConsoleDevice::Both(ref mut fb_writer) => {
+                uart::putc(byte);
+                fb_writer.write_byte(byte);
             }
-             ConsoleDevice::Framebuffer(ref mut fb_writer) => {
-                 fb_writer.putc(byte); // TODO: Framebuffer yazıcısını kullan
-             }
         }
     }
 
-    // Konsoldan bir karakter okur.
-    // TODO: Eğer girdi gerekiyorsa bu fonksiyonu tamamlayın.
-    #[allow(dead_code)] // Kullanılmıyorsa uyarı vermemesi için
+    // Konsoldan bir karakter okur (bekleyen yoksa None).
     pub fn getc(&mut self) -> Option<u8> {
         match self.device {
-            ConsoleDevice::Serial => {
-                serial::writer().getc()
+            ConsoleDevice::Serial | ConsoleDevice::Both(_) => {
+                uart::getc()
             }
-             ConsoleDevice::Framebuffer(ref mut fb_writer) => {
-            //    // Grafik ekrandan girdi (dokunmatik klavye?)
-                 None // Veya girdi mekanizmasını kullan
-             }
+            ConsoleDevice::Framebuffer(_) => {
+                // Grafik ekrandan girdi (dokunmatik klavye?) henüz yok
+                None
+            }
+        }
+    }
+
+    // Çıkış modunu değiştirir. Framebuffer kullanılacaksa ekran temizlenir ve erken mesajlar yeniden çizilir.
+    fn select(&mut self, mode: ConsoleMode) -> Result<(), SahneError> {
+        self.recording = false;
+        if mode == ConsoleMode::Serial {
+            self.device = ConsoleDevice::Serial;
+            return Ok(());
+        }
+
+        if display::pixel_size() != fbcon::BYTES_PER_PIXEL {
+            return Err(SahneError::NotSupported);
         }
+        let pixels = display::framebuffer().ok_or(SahneError::ResourceNotFound)?;
+        let (width, height) = display::resolution();
+        let mut fb_writer = FbConsole::new(pixels, width, height, width * fbcon::BYTES_PER_PIXEL)?;
+        fb_writer.clear();
+        for &byte in &self.early_log[..self.early_log_len] {
+            fb_writer.write_byte(byte);
+        }
+        self.device = match mode {
+            ConsoleMode::Framebuffer => ConsoleDevice::Framebuffer(fb_writer),
+            _ => ConsoleDevice::Both(fb_writer),
+        };
+        Ok(())
     }
 }
 
@@ -58,13 +134,29 @@ impl Console {
 static CONSOLE: Mutex<Console> = Mutex::new(Console::new());
 
 // Konsolu başlatmak için genel fonksiyon
-// Bu fonksiyon, donanım initsiyalizasyonları (serial::init gibi) yapıldıktan sonra çağrılmalıdır.
+// Bu fonksiyon, donanım initsiyalizasyonları (uart::init gibi) yapıldıktan sonra çağrılmalıdır.
 pub fn init() {
     // Şu anda sadece seri port initsiyalize edildiği için burada ek bir şey yapmıyoruz.
-     serial::init(); // Eğer seri port initsiyalizasyonu burada yapılıyorsa
+     uart::init(); // Eğer seri port initsiyalizasyonu burada yapılıyorsa
     printk!("Konsol başlatıldı (Seri port yönlendirmeli).\n"); // printk'i kullanmak için bu init fonksiyonu printk_init'ten sonra çağrılmalı
 }
 
+/// Konsol çıkışını seçer. Ekran sürücüsü (drivers::display) başlatıldıktan sonra çağrılmalıdır.
+/// Framebuffer kullanılamazsa konsol seri portta kalır ve hata döner.
+pub fn select(mode: ConsoleMode) -> Result<(), SahneError> {
+    CONSOLE.lock().select(mode)
+}
+
+/// Panik işleyicisi için: konsolu tutan kod paniklediyse kilidi zorla açar, böylece panik mesajı yazılabilir.
+///
+/// # Safety
+/// Sadece panik sırasında, başka hiçbir yürütme bağlamı konsolu kullanmayacakken çağrılmalıdır.
+pub unsafe fn force_unlock() {
+    if CONSOLE.is_locked() {
+        CONSOLE.force_unlock();
+    }
+}
+
 
 // fmt::Write trait'ini global Console örneği için implemente et
 // Bu, printk! gibi makroların Console katmanını kullanmasını sağlar.
@@ -81,7 +173,142 @@ impl fmt::Write for Console {
     }
 }
 
+/// Kilitli konsol; printk! gibi makrolar bunun üzerinden yazar.
+/// Konsol kilitliyse (örn. konsola yazan kod bir kesmeyle bölündüyse) None: çıktı beklemeden
+/// sadece seri porta gider, böylece kesme bağlamındaki printk! takılmaz.
+pub struct ConsoleWriter(Option<MutexGuard<'static, Console>>);
+
+impl fmt::Write for ConsoleWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.0 {
+            Some(ref mut console) => console.write_str(s),
+            None => {
+                for byte in s.bytes() {
+                    if byte == b'\n' {
+                        uart::putc(b'\r');
+                    }
+                    uart::putc(byte);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 // printk! gibi makrolar tarafından kullanılan konsol yazıcısı nesnesi döner.
-pub fn writer() -> impl fmt::Write {
-     CONSOLE.lock() // MutexGuard<Console> döner, bu da fmt::Write'ı implemente eder
+pub fn writer() -> ConsoleWriter {
+    ConsoleWriter(CONSOLE.try_lock())
+}
+
+
+// Konsol girdisinin satır disiplini. Kilit sırası: TTY -> CONSOLE (yankı konsola yazılır).
+static TTY: Mutex<LineDiscipline> = Mutex::new(LineDiscipline::new());
+
+// Girdi beklenirken seri port bu aralıkla yoklanır.
+const INPUT_POLL_MS: u64 = 10;
+// Kullanıcı yazmaları bu boyutta parçalarla, seri portun TX halkasında yer açıldıkça yazılır.
+const WRITE_CHUNK_SIZE: usize = 256;
+
+// Seri porttan bekleyen baytları satır disiplinine aktarır; yankı doğrudan konsola yazılır.
+fn pump_input() {
+    let mut tty = TTY.lock();
+    while let Some(byte) = CONSOLE.lock().getc() {
+        tty.input(byte, |echo| {
+            let mut console = CONSOLE.lock();
+            for &echo_byte in echo {
+                console.putc(echo_byte);
+            }
+        });
+    }
+}
+
+// "console" kaynağı: Okuma satır disiplininden geçer (kanonik modda satır satır), yazma '\n'i CR+LF yapar.
+// Mod resource::control ile CONTROL_TTY_GET_MODE / CONTROL_TTY_SET_MODE istekleriyle okunur ve değiştirilir.
+struct ConsoleResource;
+
+impl KernelResource for ConsoleResource {
+    fn modes(&self) -> u32 {
+        MODE_READ | MODE_WRITE
+    }
+
+    // Girdi yoksa bekler; Handle MODE_NONBLOCK ile edinildiyse hemen ResourceBusy döner.
+    fn read(&self, buffer: &mut [u8], _offset: u64, mode: u32) -> Result<usize, SahneError> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            pump_input();
+            match TTY.lock().read(buffer) {
+                Err(SahneError::ResourceBusy) if mode & MODE_NONBLOCK == 0 => sched::sleep_interruptible(INPUT_POLL_MS)?,
+                result => return result,
+            }
+        }
+    }
+
+    fn write(&self, buffer: &[u8], _offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        for chunk in buffer.chunks(WRITE_CHUNK_SIZE) {
+            // Seri port yazması beklemez; yer yoksa baytlar atılacağı için önce beklenir ('\n' iki bayt olur).
+            uart::wait_for_tx_space(2 * chunk.len());
+            let mut console = CONSOLE.lock();
+            for &byte in chunk {
+                if byte == b'\n' {
+                    console.putc(b'\r');
+                }
+                console.putc(byte);
+            }
+        }
+        Ok(buffer.len())
+    }
+
+    fn control(&self, request: u32, arg: u64, _mode: u32) -> Result<u64, SahneError> {
+        match request {
+            CONTROL_TTY_GET_MODE => Ok(TTY.lock().mode() as u64),
+            CONTROL_TTY_SET_MODE => {
+                let mode = u32::try_from(arg).map_err(|_| SahneError::InvalidParameter)?;
+                TTY.lock().set_mode(mode)?;
+                Ok(0)
+            }
+            _ => Err(SahneError::NotSupported),
+        }
+    }
+}
+
+static CONSOLE_RESOURCE: ConsoleResource = ConsoleResource;
+
+/// "console" kaynağını kaydeder. resource_manager::init'ten sonra çağrılmalıdır.
+pub fn register_tty() {
+    if let Err(err) = resource_manager::register("console", &CONSOLE_RESOURCE) {
+        printk!("Konsol: \"console\" kaynağı kaydedilemedi: {:?}\n", err);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn bootargs_select_console_mode() {
+        assert_eq!(ConsoleMode::from_bootargs(""), None);
+        assert_eq!(ConsoleMode::from_bootargs("root=/dev/emmc0 console=fb"), Some(ConsoleMode::Framebuffer));
+        assert_eq!(ConsoleMode::from_bootargs("console=fb quiet console=serial"), Some(ConsoleMode::Serial));
+        assert_eq!(ConsoleMode::from_bootargs("console=both"), Some(ConsoleMode::Both));
+        assert_eq!(ConsoleMode::from_bootargs("console=lcd"), None);
+    }
+
+    #[test_case]
+    fn console_resource_controls_tty_mode() {
+        use crate::sahne64::resource::{TTY_CANONICAL, TTY_ECHO};
+        use crate::tty_io::DEFAULT_MODE;
+
+        let handle = resource_manager::acquire("console", MODE_READ | MODE_NONBLOCK).unwrap();
+        assert_eq!(resource_manager::control(handle, CONTROL_TTY_GET_MODE, 0), Ok(DEFAULT_MODE as u64));
+        assert_eq!(resource_manager::control(handle, CONTROL_TTY_SET_MODE, (TTY_CANONICAL | TTY_ECHO) as u64), Ok(0));
+        assert_eq!(resource_manager::control(handle, CONTROL_TTY_GET_MODE, 0), Ok((TTY_CANONICAL | TTY_ECHO) as u64));
+        assert_eq!(resource_manager::control(handle, CONTROL_TTY_SET_MODE, u64::MAX), Err(SahneError::InvalidParameter));
+        assert_eq!(resource_manager::control(handle, 99, 0), Err(SahneError::NotSupported));
+        assert_eq!(resource_manager::read(handle, &mut [0u8; 16], None), Err(SahneError::ResourceBusy));
+        assert_eq!(resource_manager::control(handle, CONTROL_TTY_SET_MODE, DEFAULT_MODE as u64), Ok(0));
+        assert_eq!(resource_manager::release(handle), Ok(()));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,259 @@
+// main_kernel/csprng.rs
+// ChaCha20 Tabanlı Entropi Havuzu ve Kriptografik Rastgele Sayı Üreteci
+// Donanıma bağımlı değildir; entropi kaynakları ve kilitleme entropy.rs'dedir.
+//
+// - EntropyPool: Gürültü kaynaklarından gelen baytları ChaCha permütasyonu üzerine kurulu bir
+//   sünger (sponge) ile karıştırır ve tahmini entropi miktarını bit olarak sayar.
+// - ChaChaRng: Havuzdan çekilen 256 bitlik anahtarla ChaCha20 akışı üretir. Her blokta anahtar
+//   yenilenir (fast key erasure); çekirdek belleği sonradan okunsa bile önceki çıktılar bulunamaz.
+
+// "expand 32-byte k" sabitleri (RFC 8439)
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646E, 0x7962_2D32, 0x6B20_6574];
+
+pub const KEY_SIZE: usize = 32;
+pub const BLOCK_SIZE: usize = 64;
+
+#[inline(always)]
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+// 20 tur (10 çift tur) ChaCha permütasyonu, ileri besleme (feed-forward) olmadan.
+fn permute(state: &mut [u32; 16]) {
+    for _ in 0..10 {
+        quarter_round(state, 0, 4, 8, 12);
+        quarter_round(state, 1, 5, 9, 13);
+        quarter_round(state, 2, 6, 10, 14);
+        quarter_round(state, 3, 7, 11, 15);
+        quarter_round(state, 0, 5, 10, 15);
+        quarter_round(state, 1, 6, 11, 12);
+        quarter_round(state, 2, 7, 8, 13);
+        quarter_round(state, 3, 4, 9, 14);
+    }
+}
+
+fn words(bytes: &[u8; KEY_SIZE]) -> [u32; 8] {
+    let mut words = [0u32; 8];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    words
+}
+
+/// ChaCha20 blok fonksiyonu (RFC 8439, 32-bit sayaç + 96-bit nonce).
+pub fn chacha20_block(key: &[u8; KEY_SIZE], counter: u32, nonce: &[u8; 12]) -> [u8; BLOCK_SIZE] {
+    let mut input = [0u32; 16];
+    input[..4].copy_from_slice(&CONSTANTS);
+    input[4..12].copy_from_slice(&words(key));
+    input[12] = counter;
+    for (index, chunk) in nonce.chunks_exact(4).enumerate() {
+        input[13 + index] = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+
+    let mut state = input;
+    permute(&mut state);
+    let mut output = [0u8; BLOCK_SIZE];
+    for (index, chunk) in output.chunks_exact_mut(4).enumerate() {
+        chunk.copy_from_slice(&state[index].wrapping_add(input[index]).to_le_bytes());
+    }
+    output
+}
+
+/// Entropi havuzu. 512 bitlik durumun ilk 256 biti "rate" (girdinin eklendiği kısım),
+/// kalan 256 bit "capacity"dir; capacity dışarıya hiçbir zaman doğrudan verilmez.
+pub struct EntropyPool {
+    state: [u32; 16],
+    position: usize, // rate içinde bir sonraki girdi baytının konumu
+    entropy_bits: u32,
+}
+
+const RATE_BYTES: usize = 32;
+// Havuz 4096 bitten fazla entropi saymaz; daha fazlası tahmini güçlendirmez.
+const MAX_ENTROPY_BITS: u32 = 4096;
+
+impl EntropyPool {
+    pub const fn new() -> Self {
+        EntropyPool { state: [0; 16], position: 0, entropy_bits: 0 }
+    }
+
+    /// Gürültü baytlarını havuza karıştırır. `credit_bits` girdinin içerdiği tahmini entropidir;
+    /// emin olunamayan kaynaklar 0 vermelidir (karıştırılır ama sayılmaz).
+    pub fn add(&mut self, data: &[u8], credit_bits: u32) {
+        for &byte in data {
+            let word = self.position / 4;
+            self.state[word] ^= (byte as u32) << ((self.position % 4) * 8);
+            self.position += 1;
+            if self.position == RATE_BYTES {
+                permute(&mut self.state);
+                self.position = 0;
+            }
+        }
+        self.entropy_bits = self.entropy_bits.saturating_add(credit_bits).min(MAX_ENTROPY_BITS);
+    }
+
+    /// Tahmini biriken entropi (bit).
+    pub fn entropy_bits(&self) -> u32 {
+        self.entropy_bits
+    }
+
+    /// Havuzdan 256 bitlik bir tohum (seed) çeker ve entropi sayacını sıfırlar.
+    /// Çıktıdan sonra rate kısmı silinir; aynı tohum iki kez üretilemez ve tohumdan havuz geri hesaplanamaz.
+    pub fn extract(&mut self) -> [u8; KEY_SIZE] {
+        // Girdinin sonunu işaretle (padding), böylece "abc" + çıkarma ile "abc\0" + çıkarma farklı olur.
+        self.state[15] ^= 0x8000_0000 | self.position as u32;
+        permute(&mut self.state);
+        let mut seed = [0u8; KEY_SIZE];
+        for (index, chunk) in seed.chunks_exact_mut(4).enumerate() {
+            chunk.copy_from_slice(&self.state[index].to_le_bytes());
+        }
+        self.state[..8].fill(0);
+        permute(&mut self.state);
+        self.position = 0;
+        self.entropy_bits = 0;
+        seed
+    }
+}
+
+impl Default for EntropyPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// ChaCha20 akış üreteci.
+pub struct ChaChaRng {
+    key: [u8; KEY_SIZE],
+    counter: u64,
+    buffer: [u8; BLOCK_SIZE - KEY_SIZE],
+    available: usize, // buffer'ın sonundaki henüz verilmemiş bayt sayısı
+}
+
+impl ChaChaRng {
+    pub const fn new() -> Self {
+        ChaChaRng { key: [0; KEY_SIZE], counter: 0, buffer: [0; BLOCK_SIZE - KEY_SIZE], available: 0 }
+    }
+
+    /// Yeni tohumu mevcut anahtarla birleştirir. Önceki anahtar tahmin edilebilir olsa bile
+    /// tohum gizliyse sonuç da gizlidir; tohum zayıfsa önceki anahtarın gücü korunur.
+    pub fn reseed(&mut self, seed: &[u8; KEY_SIZE]) {
+        for (key_byte, seed_byte) in self.key.iter_mut().zip(seed) {
+            *key_byte ^= seed_byte;
+        }
+        self.refill();
+        self.available = 0; // Eski anahtarla ilişkili çıktıyı at
+    }
+
+    // Yeni bir blok üretir: ilk 32 bayt bir sonraki anahtar, kalan 32 bayt çıktı olur.
+    fn refill(&mut self) {
+        let mut nonce = [0u8; 12];
+        nonce[..8].copy_from_slice(&self.counter.to_le_bytes());
+        self.counter = self.counter.wrapping_add(1);
+        let block = chacha20_block(&self.key, 0, &nonce);
+        self.key.copy_from_slice(&block[..KEY_SIZE]);
+        self.buffer.copy_from_slice(&block[KEY_SIZE..]);
+        self.available = self.buffer.len();
+    }
+
+    /// Tamponu rastgele baytlarla doldurur. Verilen baytlar üreteçte tutulmaz.
+    pub fn fill(&mut self, out: &mut [u8]) {
+        let mut written = 0;
+        while written < out.len() {
+            if self.available == 0 {
+                self.refill();
+            }
+            let start = self.buffer.len() - self.available;
+            let count = self.available.min(out.len() - written);
+            out[written..written + count].copy_from_slice(&self.buffer[start..start + count]);
+            self.buffer[start..start + count].fill(0);
+            self.available -= count;
+            written += count;
+        }
+    }
+}
+
+impl Default for ChaChaRng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8439, bölüm 2.3.2 test vektörü.
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn chacha20_block_matches_rfc8439() {
+        let mut key = [0u8; KEY_SIZE];
+        for (index, byte) in key.iter_mut().enumerate() {
+            *byte = index as u8;
+        }
+        let nonce = [0, 0, 0, 0x09, 0, 0, 0, 0x4A, 0, 0, 0, 0];
+        let block = chacha20_block(&key, 1, &nonce);
+        let expected: [u8; BLOCK_SIZE] = [
+            0x10, 0xF1, 0xE7, 0xE4, 0xD1, 0x3B, 0x59, 0x15, 0x50, 0x0F, 0xDD, 0x1F, 0xA3, 0x20, 0x71, 0xC4,
+            0xC7, 0xD1, 0xF4, 0xC7, 0x33, 0xC0, 0x68, 0x03, 0x04, 0x22, 0xAA, 0x9A, 0xC3, 0xD4, 0x6C, 0x4E,
+            0xD2, 0x82, 0x64, 0x46, 0x07, 0x9F, 0xAA, 0x09, 0x14, 0xC2, 0xD7, 0x05, 0xD9, 0x8B, 0x02, 0xA2,
+            0xB5, 0x12, 0x9C, 0xD1, 0xDE, 0x16, 0x4E, 0xB9, 0xCB, 0xD0, 0x83, 0xE8, 0xA2, 0x50, 0x3C, 0x4E,
+        ];
+        assert_eq!(block, expected);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn pool_counts_entropy_and_depends_on_every_input() {
+        let mut a = EntropyPool::new();
+        let mut b = EntropyPool::new();
+        a.add(b"zamanlayici titresimi", 3);
+        b.add(b"zamanlayici titresimi", 3);
+        b.add(&[0], 0); // Sıfır bayt bile sonucu değiştirmeli
+        assert_eq!(a.entropy_bits(), 3);
+
+        let seed_a = a.extract();
+        assert_ne!(seed_a, b.extract());
+        assert_eq!(a.entropy_bits(), 0);
+        assert_ne!(seed_a, a.extract()); // Aynı tohum iki kez çıkmaz
+
+        let mut full = EntropyPool::new();
+        full.add(&[1], u32::MAX);
+        assert_eq!(full.entropy_bits(), MAX_ENTROPY_BITS);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn rng_output_is_independent_of_read_sizes() {
+        let seed = [7u8; KEY_SIZE];
+        let mut one = ChaChaRng::new();
+        let mut two = ChaChaRng::new();
+        one.reseed(&seed);
+        two.reseed(&seed);
+
+        let mut whole = [0u8; 200];
+        one.fill(&mut whole);
+        let mut pieces = [0u8; 200];
+        let mut offset = 0;
+        for size in [1, 31, 0, 33, 64, 71] {
+            two.fill(&mut pieces[offset..offset + size]);
+            offset += size;
+        }
+        assert_eq!(offset, pieces.len());
+        assert_eq!(whole, pieces);
+
+        // Farklı tohum farklı akış üretir; akış sabit bir bayt dizisi değildir.
+        let mut other = ChaChaRng::new();
+        other.reseed(&[8u8; KEY_SIZE]);
+        let mut other_output = [0u8; 200];
+        other.fill(&mut other_output);
+        assert_ne!(whole, other_output);
+        assert!(whole.iter().any(|&byte| byte != whole[0]));
+    }
+}
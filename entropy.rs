@@ -0,0 +1,291 @@
+// main_kernel/entropy.rs
+// Çekirdek Entropi Havuzu ve "random" Kaynağı
+// Rastgelelik kaynakları havuzda (csprng::EntropyPool) toplanır, yeterli entropi biriktiğinde
+// ChaCha20 üreteci (csprng::ChaChaRng) yeniden tohumlanır. Kaynaklar:
+// - Zamanlayıcı titremesi (timer jitter): açılışta ve tohum beklenirken ölçülür.
+// - Kesme zamanlaması: traps.rs her kesmede add_interrupt_timing'i çağırır.
+// - Donanım RNG: Device Tree'de virtio-rng varsa (QEMU) tam kredi ile kullanılır.
+//
+// Kullanıcı alanı "random" kaynağını resource::read ile okur. Üreteç henüz tohumlanmadıysa okuma
+// bekler; kaynak MODE_NONBLOCK ile edinildiyse hemen ResourceBusy döner.
+// Kaynağa yazılan veriler havuza karıştırılır ama entropi olarak sayılmaz.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use spin::Mutex;
+
+use crate::csprng::{ChaChaRng, EntropyPool, KEY_SIZE};
+use crate::drivers::{timer, virtio_rng};
+use crate::fdt::Fdt;
+use crate::printk;
+use crate::resource_manager::{self, KernelResource};
+use crate::sahne64::resource::{MODE_NONBLOCK, MODE_READ, MODE_WRITE};
+use crate::sahne64::SahneError;
+use crate::sched;
+
+// Üreteç ilk kez ve sonraki her seferde en az bu kadar entropi ile tohumlanır.
+const RESEED_BITS: u32 = (KEY_SIZE * 8) as u32;
+// Tohumlandıktan sonra yeniden tohumlama en erken bu aralıkla yapılır (saldırganın tahmin
+// edebileceği küçük girdilerle durumu adım adım takip etmesini zorlaştırır).
+const RESEED_INTERVAL_MS: u64 = 60_000;
+// Açılışta ölçülen zamanlayıcı örneği sayısı.
+const BOOT_JITTER_SAMPLES: usize = 4096;
+// Tohum beklenirken her turda ölçülen örnek sayısı ve turlar arası bekleme.
+const WAIT_JITTER_SAMPLES: usize = 256;
+const WAIT_POLL_MS: u64 = 10;
+// Zamanlayıcı titremesi için her 16 değişken ölçüme 1 bit kredi verilir (ihtiyatlı tahmin).
+const JITTER_SAMPLES_PER_BIT: u32 = 16;
+// Bir ölçümün değişken sayılması için ikinci derece farkının görülmemiş olması gereken son ölçüm sayısı.
+const JITTER_HISTORY: usize = 8;
+// Kesme zamanlaması için her 64 kesmeye 1 bit kredi verilir.
+const INTERRUPTS_PER_BIT: u32 = 64;
+// Donanım RNG'den tohumlama başına okunan bayt sayısı.
+const HARDWARE_SEED_BYTES: usize = 64;
+// Tek bir okuma çağrısında verilen en fazla bayt; büyük istekler parça parça okunur.
+pub const MAX_READ_SIZE: usize = 4096;
+
+struct EntropyState {
+    pool: EntropyPool,
+    rng: ChaChaRng,
+    last_reseed_ticks: u64,
+    interrupt_count: u32,
+}
+
+static STATE: Mutex<EntropyState> = Mutex::new(EntropyState {
+    pool: EntropyPool::new(),
+    rng: ChaChaRng::new(),
+    last_reseed_ticks: 0,
+    interrupt_count: 0,
+});
+
+// Üreteç en az bir kez RESEED_BITS entropi ile tohumlandı mı? Kilitsiz okunabilsin diye ayrı tutulur.
+static SEEDED: AtomicBool = AtomicBool::new(false);
+
+/// Havuzu başlatır ve "random" kaynağını kaydeder. Zamanlayıcıdan ve resource_manager::init'ten sonra çağrılmalıdır.
+pub fn init(fdt: Option<&Fdt>) {
+    collect_timer_jitter(BOOT_JITTER_SAMPLES);
+    if fdt.is_some_and(virtio_rng::init) {
+        add_hardware_entropy();
+    }
+    try_reseed(&mut STATE.lock());
+
+    if let Err(err) = resource_manager::register("random", &RANDOM_RESOURCE) {
+        printk!("Entropi: \"random\" kaynağı kaydedilemedi: {:?}\n", err);
+    }
+    if is_seeded() {
+        printk!("Entropi: Üreteç tohumlandı.\n");
+    } else {
+        printk!("Entropi: Yeterli entropi yok, \"random\" okumaları tohumlanana kadar bekleyecek.\n");
+    }
+}
+
+/// Üreteç tohumlandı mı?
+pub fn is_seeded() -> bool {
+    SEEDED.load(Ordering::Acquire)
+}
+
+// Zamanlayıcı titremesine verilecek krediyi ihtiyatlı tahmin eder. Bir ölçüm, ham farkın (now - last)
+// ikinci derece farkı (art arda iki farkın farkı) son JITTER_HISTORY ölçümde görülmemişse sayılır.
+// Sabit hızlı bir zamanlayıcıda (emülatör) farklar sabit veya kısa periyotla tekrar ettiği için
+// ikinci derece farklar da tekrar eder ve kredi verilmez.
+struct JitterEstimator {
+    previous_delta: u64,
+    history: [u64; JITTER_HISTORY],
+    next: usize,
+}
+
+impl JitterEstimator {
+    const fn new() -> Self {
+        JitterEstimator { previous_delta: 0, history: [0; JITTER_HISTORY], next: 0 }
+    }
+
+    // Yeni bir ham farkı kaydeder; ölçüm krediye sayılacaksa true döner.
+    fn record(&mut self, delta: u64) -> bool {
+        let second_order = delta.wrapping_sub(self.previous_delta);
+        self.previous_delta = delta;
+        let novel = !self.history.contains(&second_order);
+        self.history[self.next] = second_order;
+        self.next = (self.next + 1) % JITTER_HISTORY;
+        novel
+    }
+}
+
+// Art arda zamanlayıcı okumaları arasındaki farkları havuza ekler. Farklar önbellek, bellek yolu
+// ve kesme zamanlamasına bağlı olarak değişir; kredi JitterEstimator ile ham farklardan hesaplanır.
+fn collect_timer_jitter(samples: usize) {
+    let mut state = STATE.lock();
+    let mut estimator = JitterEstimator::new();
+    let mut last = timer::now_ticks();
+    let mut credited = 0u32;
+    for sample in 0..samples {
+        // Süresi değişken küçük bir iş; derleyicinin döngüyü silmemesi için black_box kullanılır.
+        let mut work = sample as u64;
+        for _ in 0..(sample % 7) + 1 {
+            work = core::hint::black_box(work.wrapping_mul(0x9E37_79B9_7F4A_7C15).rotate_left(7));
+        }
+        let now = timer::now_ticks();
+        let delta = now.wrapping_sub(last);
+        last = now;
+        if estimator.record(delta) {
+            credited += 1;
+        }
+        // İşin sonucu sadece karıştırılır; krediyi etkilemez.
+        state.pool.add(&(delta ^ work).to_le_bytes(), 0);
+    }
+    state.pool.add(&[], credited / JITTER_SAMPLES_PER_BIT);
+}
+
+// Donanım RNG'den okunan baytları tam kredi ile havuza ekler.
+// Cihaz sorgulanırken havuz kilitli tutulmaz (kesme zamanlaması beklemeden eklenebilsin).
+fn add_hardware_entropy() {
+    let mut seed = [0u8; HARDWARE_SEED_BYTES];
+    match virtio_rng::read(&mut seed) {
+        Ok(count) => STATE.lock().pool.add(&seed[..count], (count * 8) as u32),
+        Err(err) => printk!("Entropi: Donanım RNG okunamadı: {:?}\n", err),
+    }
+    seed.fill(0);
+}
+
+// Havuzda yeterli entropi varsa (ve tohumlandıysa yeniden tohumlama aralığı dolduysa) üreteci tohumlar.
+fn try_reseed(state: &mut EntropyState) {
+    if state.pool.entropy_bits() < RESEED_BITS {
+        return;
+    }
+    let now = timer::now_ticks();
+    if is_seeded() && now.wrapping_sub(state.last_reseed_ticks) < timer::ms_to_ticks(RESEED_INTERVAL_MS) {
+        return;
+    }
+    let mut seed = state.pool.extract();
+    state.rng.reseed(&seed);
+    seed.fill(0);
+    state.last_reseed_ticks = now;
+    SEEDED.store(true, Ordering::Release);
+}
+
+// Yeniden tohumlama zamanı geldi mi? (Donanım RNG'yi gereksiz yere sorgulamamak için.)
+fn reseed_due() -> bool {
+    let state = STATE.lock();
+    timer::now_ticks().wrapping_sub(state.last_reseed_ticks) >= timer::ms_to_ticks(RESEED_INTERVAL_MS)
+}
+
+/// Kesme işleyicisinden çağrılır: kesmenin geldiği anı havuza karıştırır.
+/// Havuz o an kilitliyse (kesilen kod havuzu kullanıyorsa) örnek atlanır; kesme içinde beklenmez.
+pub fn add_interrupt_timing(cause: usize) {
+    let Some(mut state) = STATE.try_lock() else {
+        return;
+    };
+    let sample = timer::now_ticks() ^ ((cause as u64) << 56);
+    state.interrupt_count = state.interrupt_count.wrapping_add(1);
+    let credit = if state.interrupt_count.is_multiple_of(INTERRUPTS_PER_BIT) { 1 } else { 0 };
+    state.pool.add(&sample.to_le_bytes(), credit);
+    try_reseed(&mut state);
+}
+
+/// Verilen veriyi havuza karıştırır; entropi olarak sayılmaz.
+pub fn mix(data: &[u8]) {
+    STATE.lock().pool.add(data, 0);
+}
+
+/// Tamponu rastgele baytlarla doldurur ve yazılan bayt sayısını döndürür (en fazla MAX_READ_SIZE).
+/// Üreteç tohumlanmadıysa: `nonblocking` ise ResourceBusy döner, değilse tohumlanana kadar bekler.
+pub fn get_random(buffer: &mut [u8], nonblocking: bool) -> Result<usize, SahneError> {
+    while !is_seeded() {
+        if nonblocking {
+            return Err(SahneError::ResourceBusy);
+        }
+        // Beklerken de titreşim topla; kesme gelmeyen bir sistemde de tohumlama ilerlesin.
+        collect_timer_jitter(WAIT_JITTER_SAMPLES);
+        try_reseed(&mut STATE.lock());
+        if !is_seeded() {
+            sched::sleep(WAIT_POLL_MS);
+        }
+    }
+
+    if virtio_rng::is_present() && reseed_due() {
+        add_hardware_entropy();
+    }
+    let count = buffer.len().min(MAX_READ_SIZE);
+    let mut state = STATE.lock();
+    try_reseed(&mut state);
+    state.rng.fill(&mut buffer[..count]);
+    Ok(count)
+}
+
+
+// "random" kaynağı: Okuma rastgele bayt verir, yazma havuza karıştırır.
+struct RandomResource;
+
+impl KernelResource for RandomResource {
+    fn modes(&self) -> u32 {
+        MODE_READ | MODE_WRITE
+    }
+
+    fn read(&self, buffer: &mut [u8], _offset: u64, mode: u32) -> Result<usize, SahneError> {
+        get_random(buffer, mode & MODE_NONBLOCK != 0)
+    }
+
+    fn write(&self, buffer: &[u8], _offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        mix(buffer);
+        Ok(buffer.len())
+    }
+}
+
+static RANDOM_RESOURCE: RandomResource = RandomResource;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test çekirdeği QEMU virt'te virtio-rng ile çalışır (bkz. Makefile); init tohumlamış olmalı.
+    #[test_case]
+    fn random_resource_returns_distinct_output() {
+        assert!(is_seeded());
+        let handle = resource_manager::acquire("random", MODE_READ | MODE_NONBLOCK).unwrap();
+        let mut first = [0u8; 32];
+        let mut second = [0u8; 32];
+        assert_eq!(resource_manager::read(handle, &mut first, None), Ok(32));
+        assert_eq!(resource_manager::read(handle, &mut second, None), Ok(32));
+        assert_ne!(first, second);
+        assert_ne!(first, [0u8; 32]);
+        assert_eq!(resource_manager::release(handle), Ok(()));
+    }
+
+    #[test_case]
+    fn reads_are_capped_and_interrupts_feed_the_pool() {
+        let mut large = alloc::vec![0u8; MAX_READ_SIZE + 100];
+        assert_eq!(get_random(&mut large, true), Ok(MAX_READ_SIZE));
+        assert_eq!(get_random(&mut [], true), Ok(0));
+
+        let before = STATE.lock().interrupt_count;
+        add_interrupt_timing(5);
+        assert_eq!(STATE.lock().interrupt_count, before.wrapping_add(1));
+    }
+
+    // Verilen ham farklardan kaç ölçümün krediye sayıldığını döndürür.
+    fn credited(deltas: impl Iterator<Item = u64>) -> usize {
+        let mut estimator = JitterEstimator::new();
+        deltas.filter(|&delta| estimator.record(delta)).count()
+    }
+
+    #[test_case]
+    fn steady_timer_earns_no_jitter_credit() {
+        // Sabit fark ve iş süresinden gelen 7'lik periyot: en fazla ilk tur sayılır.
+        assert!(credited(core::iter::repeat(40).take(BOOT_JITTER_SAMPLES)) <= 1);
+        let periodic = (0..BOOT_JITTER_SAMPLES).map(|sample| 40 + (sample % 7) as u64 * 3);
+        assert!(credited(periodic) <= JITTER_HISTORY);
+        // Zamanlayıcı döngüden yavaşsa farklar 0/1 arasında düzenli gidip gelir.
+        assert!(credited((0..BOOT_JITTER_SAMPLES).map(|sample| (sample % 3 == 0) as u64)) <= JITTER_HISTORY);
+    }
+
+    #[test_case]
+    fn irregular_deltas_earn_credit() {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let irregular = (0..BOOT_JITTER_SAMPLES).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            30 + state % 64
+        });
+        assert!(credited(irregular) > BOOT_JITTER_SAMPLES / 2);
+    }
+}
@@ -22,12 +22,25 @@ pub extern "C" fn sys_exit(status: i32) {
         }
     };
 
+    // Heap dolu olsa bile görevin temizliği tamamlanabilsin diye acil durum rezervini aç.
+    // schedule() bu görevin bağlamına geri dönmeyeceği için izin ondan önce bırakılır.
+    let reserve = crate::mm::emergency_reserve();
+
     // Görevin durumunu Exited olarak işaretle
     let mut current_task = current_task_arc.lock();
     current_task.state = TaskState::Exited;
     // TODO: Dönüş kodunu (status) bir yere kaydetmek gerekebilir (örn. parent process'in wait çağrısı için).
      current_task.exit_status = status;
 
+    // İş parçacığını görevin kaynak hesabından düş. Son iş parçacığıysa görevin bellek
+    // tahsisleri ve açık Handle'ları da iade edilir (görev kilidi bırakıldıktan sonra).
+    let resources = current_task.resources();
+    let last_thread = {
+        let mut account = resources.lock();
+        account.release_thread();
+        account.threads() == 0
+    };
+
     // TODO: Görev için ayrılan yığın belleğini serbest bırak.
     // Bu, görev yapısındaki stack alanını alıp, kullanılan bellek yöneticisi
     // aracılığıyla belleği iade etmeyi gerektirir.
@@ -49,6 +62,12 @@ pub extern "C" fn sys_exit(status: i32) {
     // MutexGuard'ı serbest bırak
     drop(current_task);
     drop(current_task_arc); // Arc'ın referans sayısını düşür
+    if last_thread {
+        crate::mm::release_task_allocations(&resources);
+        crate::resource_manager::release_task_handles(&resources);
+    }
+    drop(resources);
+    drop(reserve);
 
     // Zamanlayıcıyı çağır. Çalışmaya hazır bir sonraki göreve geçilir.
     // Bu fonksiyondan asla dönülmez, çünkü geçerli görev sonlanmıştır.
@@ -60,10 +79,5 @@ pub extern "C" fn sys_exit(status: i32) {
     loop {}
 }
 
-// TODO: Görevler bittiğinde otomatik olarak çağrılacak bir wrapper fonksiyonu gerekebilir.
-// Task::new fonksiyonunda context.ra alanına bu wrapper'ın adresi yazılır.
-// Görev fonksiyonu normal şekilde tamamlandığında bu wrapper'a döner ve wrapper sys_exit'i çağırır.
- #[no_mangle]
- extern "C" fn task_exit_wrapper(exit_code: i32) { // Görev fonksiyonundan gelen dönüş kodu parametre olabilir
-     sys_exit(exit_code);
- }
\ No newline at end of file
+// Görev fonksiyonu normal şekilde döndüğünde ra, asm.s'deki thread_exit_trampoline'e işaret eder
+// (Task::new). Trampolin dönüş değeriyle SYSCALL_THREAD_EXIT çağırır ve sonunda sys_exit çalışır.
\ No newline at end of file
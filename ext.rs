@@ -1,6 +1,11 @@
 // filesystem/ext.rs
-// Minimal EXT2 Dosya Sistemi Sürücüsü (Sadece Okuma)
+// Minimal EXT2 Dosya Sistemi Sürücüsü
 // Çekirdekten block device Handle'ını kullanarak çalışır.
+// Yazma desteği: grup bitmap'lerinden blok/i-node ayırma, dosya oluşturma, yazma/ekleme/kısaltma.
+// Süper blok ve grup tanımlayıcılarındaki serbest sayılar her ayırmada diske yazılır; bilinmeyen
+// (incompat/ro_compat) özellikleri olan dosya sistemlerine yazılmaz.
+// İlk yazmada süper blok "temiz değil" işaretlenir (s_state) ve bağlama sayısı artar; unmount temizler.
+// Tutarlılık denetimi fsck alt modülündedir.
 
 #![no_std] // Standart kütüphaneye ihtiyaç duymuyoruz (Kullanıcı alanı kütüphanesi)
 #![allow(dead_code)] // Henüz kullanılmayan kodlar için uyarı vermesin
@@ -8,32 +13,157 @@
 
 extern crate alloc; // Heap tahsisi için alloc crate'ini kullan
 
-use alloc::boxed::Box;
-use alloc::string::String;
 use alloc::vec::Vec;
+use core::cell::RefCell;
 use core::mem;
-use core::str;
 
 // Çekirdek API'mızı içeri aktarıyoruz
-use crate::sahne64::{self, resource, memory, SahneError, Handle};
+use crate::sahne64::{kernel, resource, SahneError, Handle};
+use crate::sahne64::buffer::zeroed_buffer;
+
+#[path = "fsck.rs"]
+pub mod fsck;
+
+/// Dosya sisteminin üzerinde çalıştığı blok cihaz.
+/// SahneBox'ta bir cihaz Handle'ı (resource::read_at), testlerde bellekteki bir imajdır.
+pub trait BlockDevice {
+    /// Cihazın başından `offset` bayt ilerisinden `buffer.len()` bayt okur; okunan bayt sayısını döner.
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<usize, SahneError>;
+
+    /// Cihazın başından `offset` bayt ilerisine `buffer`ı yazar; yazılan bayt sayısını döner.
+    /// Salt okunur cihazlar PermissionDenied döner.
+    fn write_at(&mut self, _offset: u64, _buffer: &[u8]) -> Result<usize, SahneError> {
+        Err(SahneError::PermissionDenied)
+    }
+
+    /// Dosya sisteminin "temiz değil" işaretlenerek yazılabilir bağlandığını (`true`) veya düzgünce
+    /// ayrıldığını (`false`) bildirir. SahneBox çekirdeği kapatırken, ayrılmadan bırakılan birimleri bu
+    /// bilgiyle temiz işaretler. Varsayılan uygulama bir şey yapmaz.
+    fn set_mounted(&mut self, _mounted: bool) -> Result<(), SahneError> {
+        Ok(())
+    }
+}
+
+// Çekirdeğin verdiği blok cihaz Handle'ı (örn. "emmc0", "sdcard1").
+// Yazmalar için Handle MODE_WRITE ile edinilmiş olmalı.
+impl BlockDevice for Handle {
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<usize, SahneError> {
+        resource::read_at(*self, buffer, offset)
+    }
+
+    fn write_at(&mut self, offset: u64, buffer: &[u8]) -> Result<usize, SahneError> {
+        resource::write_at(*self, buffer, offset)
+    }
+
+    fn set_mounted(&mut self, mounted: bool) -> Result<(), SahneError> {
+        resource::control(*self, resource::CONTROL_BLOCK_SET_MOUNTED, mounted as u64).map(|_| ())
+    }
+}
+
+// Bellekteki bir imaj (RAM disk veya include_bytes! ile gömülü test imajı).
+impl BlockDevice for &[u8] {
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<usize, SahneError> {
+        let start = usize::try_from(offset).map_err(|_| SahneError::InvalidParameter)?;
+        if start >= self.len() {
+            return Ok(0);
+        }
+        let len = core::cmp::min(buffer.len(), self.len() - start);
+        buffer[..len].copy_from_slice(&self[start..start + len]);
+        Ok(len)
+    }
+}
 
+// Yazılabilir RAM disk (boyutu sabittir; sonunu aşan yazma kısa kalır).
+impl BlockDevice for Vec<u8> {
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<usize, SahneError> {
+        self.as_slice().read_at(offset, buffer)
+    }
+
+    fn write_at(&mut self, offset: u64, buffer: &[u8]) -> Result<usize, SahneError> {
+        let start = usize::try_from(offset).map_err(|_| SahneError::InvalidParameter)?;
+        if start >= self.len() {
+            return Ok(0);
+        }
+        let len = core::cmp::min(buffer.len(), self.len() - start);
+        self[start..start + len].copy_from_slice(&buffer[..len]);
+        Ok(len)
+    }
+}
 
 // EXT2 Sabitleri
-const EXT2_SUPERBLOCK_BLOCK: u64 = 1; // Süper blok genellikle 1. blokta başlar (1024 bayt bloklar için)
-const EXT2_SUPERBLOCK_OFFSET: usize = 1024; // 512 bayt sektörlerde 1024 offset (yani 2. sektör)
+const EXT2_SUPERBLOCK_OFFSET: u64 = 1024; // Süper blok, blok boyutundan bağımsız olarak cihazın 1024. baytında başlar
+const EXT2_SUPERBLOCK_SIZE: usize = 1024;
 const EXT2_MIN_BLOCK_SIZE: u32 = 1024; // EXT2 minimum blok boyutu 1KB (2^10)
+const EXT2_MAX_LOG_BLOCK_SIZE: u32 = 6; // En büyük blok boyutu 64KB
 const EXT2_SUPERBLOCK_MAGIC: u16 = 0xEF53; // Süper blok sihirli sayısı
-
-// EXT2 i-node modları (Basit örnekler)
+const EXT2_GOOD_OLD_INODE_SIZE: u16 = 128; // Revizyon 0'daki sabit i-node boyutu
+pub const EXT2_ROOT_INODE: u32 = 2; // Kök dizinin i-node numarası
+const EXT2_GOOD_OLD_FIRST_INO: u32 = 11; // Revizyon 0'da kullanıcıya açık ilk i-node
+const EXT2_GOOD_OLD_REV: u32 = 0;
+
+// Süper bloktaki serbest blok/i-node sayılarının (s_free_blocks_count, s_free_inodes_count) konumu
+const SUPERBLOCK_FREE_COUNTS_OFFSET: u64 = EXT2_SUPERBLOCK_OFFSET + 12;
+// Bağlama durumu alanlarının konumları (s_mnt_count, s_state, s_lastcheck)
+const SUPERBLOCK_MOUNT_COUNT_OFFSET: u64 = EXT2_SUPERBLOCK_OFFSET + 52;
+const SUPERBLOCK_STATE_OFFSET: u64 = EXT2_SUPERBLOCK_OFFSET + 58;
+const SUPERBLOCK_LAST_CHECK_OFFSET: u64 = EXT2_SUPERBLOCK_OFFSET + 64;
+// s_reserved_gdt_blocks (resize_inode için ayrılmış grup tanımlayıcı blokları); Superblock yapısının dışında
+const SUPERBLOCK_RESERVED_GDT_OFFSET: usize = 206;
+
+// s_state bayrakları
+const EXT2_VALID_FS: u16 = 0x0001; // Temiz kapatıldı
+const EXT2_ERROR_FS: u16 = 0x0002; // Hata bulundu (denetim gerekli)
+
+// Yazarken desteklenen özellikler. Bunların dışında bir incompat/ro_compat özelliği varsa yazılmaz.
+const EXT2_FEATURE_INCOMPAT_FILETYPE: u32 = 0x0002; // Dizin girişlerinde dosya türü baytı
+const EXT2_FEATURE_RO_COMPAT_SPARSE_SUPER: u32 = 0x0001;
+const EXT2_FEATURE_RO_COMPAT_LARGE_FILE: u32 = 0x0002; // i_size 2 GB'ı aşabilir
+const EXT2_INDEX_FL: u32 = 0x1000; // Dizin htree ile indekslenmiş (dir_index)
+
+// EXT2 i-node modları (i_mode'un üst 4 biti dosya türüdür)
+const S_IFMT: u16 = 0xF000; // Dosya türü maskesi
 const S_IFREG: u16 = 0x8000; // Normal dosya
 const S_IFDIR: u16 = 0x4000; // Dizin
+const S_IFLNK: u16 = 0xA000; // Sembolik bağlantı
+const S_IFCHR: u16 = 0x2000; // Karakter aygıtı
+const S_IFBLK: u16 = 0x6000; // Blok aygıtı
+const S_IFIFO: u16 = 0x1000; // FIFO
+const S_IFSOCK: u16 = 0xC000; // Soket
+const NEW_FILE_MODE: u16 = S_IFREG | 0o644; // create_file ile oluşturulan dosyalar (rw-r--r--)
+const NEW_DIR_MODE: u16 = S_IFDIR | 0o755; // mkdir ile oluşturulan dizinler (rwxr-xr-x)
+const EXT2_LINK_MAX: u16 = 32000; // Bir i-node'un en fazla bağlantı sayısı
+const NEW_SYMLINK_MODE: u16 = S_IFLNK | 0o777; // Sembolik bağlantıların izinleri kullanılmaz
+
+// Sembolik bağlantılar: hedefi i_block'a sığanlar (60 bayttan kısa) "hızlı" bağlantıdır ve blok kullanmaz;
+// daha uzun hedefler tek bir veri bloğunda NUL ile doldurulmuş olarak tutulur.
+const EXT2_FAST_SYMLINK_SIZE: usize = 60; // i_block alanının bayt boyutu
+const EXT2_MAX_SYMLINK_FOLLOWS: u32 = 40; // Bir yol çözümünde izlenen en fazla bağlantı (döngü sınırı)
+
+// Dizin girişlerindeki dosya türleri (filetype özelliği)
+const EXT2_FT_UNKNOWN: u8 = 0;
+const EXT2_FT_REG_FILE: u8 = 1;
+const EXT2_FT_DIR: u8 = 2;
+const EXT2_FT_CHRDEV: u8 = 3;
+const EXT2_FT_BLKDEV: u8 = 4;
+const EXT2_FT_FIFO: u8 = 5;
+const EXT2_FT_SOCK: u8 = 6;
+const EXT2_FT_SYMLINK: u8 = 7;
+
+// Dizin girişinin sabit başlık boyutu (inode + rec_len + name_len + file_type)
+const DIR_ENTRY_HEADER_SIZE: usize = 8;
+
+// i_block işaretçileri: 0-11 doğrudan, 12 tek dolaylı, 13 çift dolaylı, 14 üç dolaylı.
+const EXT2_NDIR_BLOCKS: u32 = 12; // Doğrudan işaretçi sayısı
+const EXT2_IND_BLOCK: usize = 12; // İlk dolaylı işaretçinin i_block'taki yeri
+const EXT2_MAX_INDIRECTION: usize = 3; // Üç dolaylıya kadar
 
 // On-disk EXT2 yapıları için Rust temsilleri
 // Bunlar doğrudan blok cihazdan okunacak bayt yapısına karşılık gelmeli.
 // Alan boyutları ve offsetleri EXT2 spesifikasyonuna göre ayarlanmalıdır.
-// Little-endian varsayılmıştır. #[repr(C)] ve #[packed] gerekebilir.
+// Little-endian varsayılmıştır; okuma read_unaligned ile yapılır.
 
 #[repr(C, packed)] // C uyumluluğu ve sıkı paketleme (alanlar arasında boşluk olmamalı)
+#[derive(Clone, Copy)]
 struct Superblock {
     s_inodes_count: u32, // i-node sayısı
     s_blocks_count: u32, // Blok sayısı
@@ -60,11 +190,21 @@ struct Superblock {
     s_rev_level: u32, // Revizyon seviyesi
     s_def_resuid: u16, // Varsayılan kullanıcı id'si
     s_def_resgid: u16, // Varsayılan grup id'si
+    // Sadece revizyon 1 (EXT2_DYNAMIC_REV) ve sonrasında geçerli alanlar
+    s_first_ino: u32, // Kullanıcıya açık ilk i-node (rev 0'da 11)
+    s_inode_size: u16, // i-node boyutu (rev 0'da 128)
+    s_block_group_nr: u16, // Bu süper bloğun bulunduğu blok grubu
+    s_feature_compat: u32, // Uyumlu özellikler
+    s_feature_incompat: u32, // Desteklenmiyorsa bağlanmaması gereken özellikler
+    s_feature_ro_compat: u32, // Desteklenmiyorsa sadece okunabilir bağlanması gereken özellikler
+    s_uuid: [u8; 16], // Dosya sistemi UUID'si
+    s_volume_name: [u8; 16], // Birim adı
     // ... Süper bloğun geri kalanı (toplam 1024 bayt)
-    // UUID, Volume Name, Last Mounted, vb.
+    // Last Mounted, sıkıştırma, journal alanları vb.
 }
 
 #[repr(C, packed)]
+#[derive(Clone, Copy)]
 struct GroupDescriptor {
     bg_block_bitmap: u32, // Blok bitmap bloğunun konumu
     bg_inode_bitmap: u32, // i-node bitmap bloğunun konumu
@@ -76,44 +216,105 @@ struct GroupDescriptor {
     bg_reserved: [u32; 3], // Gelecek kullanım için
 }
 
+/// Diskteki i-node'un ilk 128 baytı (tüm revizyonlarda ortak kısım).
 #[repr(C, packed)]
-struct Inode {
-    i_mode: u16, // Dosya tipi ve izinler
-    i_uid: u16, // Sahibi kullanıcı id'si
-    i_size: u32, // Dosya boyutu (düşük 32 bit)
-    i_atime: u32, // Erişim zamanı
-    i_ctime: u32, // Oluşturma zamanı
-    i_mtime: u32, // Değiştirme zamanı
-    i_dtime: u32, // Silme zamanı
-    i_gid: u16, // Sahibi grup id'si
-    i_links_count: u16, // Bağlantı (hard link) sayısı
-    i_blocks: u32, // Tahsis edilen blok sayısı (512 bayt biriminde!)
-    i_flags: u32, // Bayraklar
-    // OSPTR1 (işletim sistemine özel)
-    // i_block: [u32; 15], // Blok işaretçileri (0-11 doğrudan, 12 tek dolaylı, 13 çift dolaylı, 14 üç dolaylı)
-    i_block: [u32; 15], // Minimal için sadece doğrudan işaretçilerle ilgileneceğiz
-    i_generation: u32, // Dosya sürümü (NFS için)
-    i_file_acl: u32, // Dosya ACL'si (yüksek 32 bit i_size'ın parçası olabilir EXT2 revizyona göre)
-    i_dir_acl: u32, // Dizin ACL'si (yüksek 32 bit i_size'ın parçası olabilir)
-    i_faddr: u32, // Fragment adresi
-    // OSPTR2 (işletim sistemine özel)
-    // i_size_high (yüksek 32 bit, 4GB'tan büyük dosyalar için)
-    // i_author (yüksek 32 bit i_uid/i_gid olabilir)
+#[derive(Clone, Copy)]
+pub struct Inode {
+    pub i_mode: u16, // Dosya tipi ve izinler
+    pub i_uid: u16, // Sahibi kullanıcı id'si
+    pub i_size: u32, // Dosya boyutu (düşük 32 bit)
+    pub i_atime: u32, // Erişim zamanı
+    pub i_ctime: u32, // Oluşturma zamanı
+    pub i_mtime: u32, // Değiştirme zamanı
+    pub i_dtime: u32, // Silme zamanı
+    pub i_gid: u16, // Sahibi grup id'si
+    pub i_links_count: u16, // Bağlantı (hard link) sayısı
+    pub i_blocks: u32, // Tahsis edilen blok sayısı (512 bayt biriminde!)
+    pub i_flags: u32, // Bayraklar
+    pub i_osd1: u32, // OSD1 (işletim sistemine özel)
+    pub i_block: [u32; 15], // Blok işaretçileri (0-11 doğrudan, 12 tek dolaylı, 13 çift dolaylı, 14 üç dolaylı)
+    pub i_generation: u32, // Dosya sürümü (NFS için)
+    pub i_file_acl: u32, // Dosya ACL'si (genişletilmiş öznitelik bloğu)
+    pub i_dir_acl: u32, // Dizin ACL'si / normal dosyalarda i_size'ın yüksek 32 biti
+    pub i_faddr: u32, // Fragment adresi
+    pub i_osd2: [u8; 12], // OSD2 (işletim sistemine özel; i_uid/i_gid yüksek bitleri vb.)
 }
 
-#[repr(C, packed)]
-struct DirectoryEntry {
-    inode: u32, // i-node numarası
-    rec_len: u16, // Kayıt uzunluğu
-    name_len: u8, // İsim uzunluğu (EXT2 revizyon 0'da u8, revizyon 1'de 16384 >> s_log_block_size)
-    file_type: u8, // Dosya tipi (EXT2 revizyon 1 ve sonrası)
-    name: [u8; 255], // Dosya ismi (maksimum 255 bayt, rec_len'e göre değişir)
+impl Inode {
+    /// i-node bir dizin mi?
+    pub fn is_dir(&self) -> bool {
+        self.i_mode & S_IFMT == S_IFDIR
+    }
+
+    /// i-node normal bir dosya mı?
+    pub fn is_file(&self) -> bool {
+        self.i_mode & S_IFMT == S_IFREG
+    }
+
+    /// i-node bir sembolik bağlantı mı?
+    pub fn is_symlink(&self) -> bool {
+        self.i_mode & S_IFMT == S_IFLNK
+    }
+
+    // Hedefi i_block içinde tutulan (blok kullanmayan) bir bağlantı mı? e2fsprogs gibi boyuta bakılır.
+    fn is_fast_symlink(&self) -> bool {
+        self.is_symlink() && (self.i_size as usize) < EXT2_FAST_SYMLINK_SIZE
+    }
+}
+
+/// Dizin girişi. Diskte değişken uzunlukludur; burada ismi sabit boyutlu tampona kopyalanmış hali tutulur.
+#[derive(Clone, Copy)]
+pub struct DirectoryEntry {
+    pub inode: u32, // i-node numarası
+    pub rec_len: u16, // Kayıt uzunluğu
+    pub name_len: u8, // İsim uzunluğu (filetype özelliğiyle u8; revizyon 0'da u16'nın düşük baytı)
+    pub file_type: u8, // Dosya tipi (EXT2 revizyon 1 ve sonrası)
+    pub name: [u8; 255], // Dosya ismi (ilk name_len baytı geçerli)
+}
+
+impl DirectoryEntry {
+    /// Girişin ismini bayt dizisi olarak döndürür.
+    pub fn name_bytes(&self) -> &[u8] {
+        &self.name[..self.name_len as usize]
+    }
+}
+
+// Paketlenmiş bir yapıyı tamponun verilen offsetinden okur (sınır kontrolüyle).
+fn read_struct<T: Copy>(buffer: &[u8], offset: usize) -> Result<T, SahneError> {
+    let end = offset.checked_add(mem::size_of::<T>()).ok_or(SahneError::InvalidParameter)?;
+    if end > buffer.len() {
+        return Err(SahneError::InvalidParameter);
+    }
+    Ok(unsafe { (buffer.as_ptr().add(offset) as *const T).read_unaligned() })
 }
 
 
+// Blok eşlemesinde son okunan dolaylı bloklar (ağaçtaki her derinlik için bir tane).
+// Sıralı okumada ardışık dosya blokları aynı dolaylı bloklardan geçer; işaretçi blokları her parça için
+// cihazdan yeniden okunmaz. Anahtar fiziksel blok numarasıdır; 0 boş yuvadır (0 hiçbir zaman geçerli bir
+// dolaylı blok değildir, seyrek alt ağacı gösterir).
+struct BlockMapCache {
+    levels: [(u32, Vec<u8>); EXT2_MAX_INDIRECTION],
+}
+
+impl BlockMapCache {
+    const fn new() -> Self {
+        BlockMapCache { levels: [(0, Vec::new()), (0, Vec::new()), (0, Vec::new())] }
+    }
+}
+
+// Bir dizin girişinin diskteki yeri (remove_entry / set_entry_inode için).
+struct EntryLocation {
+    block: u32,                      // Girişin bulunduğu dizin bloğu
+    offset: usize,                   // Blok içindeki ofset
+    rec_len: usize,
+    previous: Option<(usize, usize)>, // Aynı bloktaki önceki kayıt (ofset, rec_len)
+}
+
 // Dosya Sistemi Ana Yapısı
-pub struct ExtFilesystem {
-    device_handle: Handle, // Blok cihaza erişim için çekirdek Handle'ı
+// Varsayılan cihaz türü çekirdek Handle'ıdır; testler bellekteki imajları kullanır.
+pub struct ExtFilesystem<D: BlockDevice = Handle> {
+    device: D, // Blok cihaza erişim (çekirdek Handle'ı veya bellekteki imaj)
     block_size: u32, // Dosya sistemi blok boyutu (genellikle 1024, 2048, 4096)
     inode_size: u16, // i-node boyutu (128 veya 256)
     blocks_per_group: u32,
@@ -123,103 +324,92 @@ pub struct ExtFilesystem {
     total_block_count: u32,
     group_count: u32,
     group_descriptors: Vec<GroupDescriptor>, // Tüm blok gruplarının tanımlayıcıları
+    block_map: RefCell<BlockMapCache>, // Dolaylı blok önbelleği (read_file/list_directory &self ile çalışır)
+    free_blocks_count: u32, // Süper bloktaki serbest sayılar (ayırma/serbest bırakmada güncellenir)
+    free_inodes_count: u32,
+    first_inode: u32, // Ayrılabilecek ilk i-node (öncekiler dosya sistemine ayrılmış)
+    feature_incompat: u32,
+    feature_ro_compat: u32,
+    bitmap: (u32, Vec<u8>), // Son okunan bitmap bloğu (blok numarası, içerik); 0 boş
+    reserved_gdt_blocks: u32, // Her süper blok kopyasından sonra ayrılmış grup tanımlayıcı blokları
+    state: u16, // s_state (EXT2_VALID_FS / EXT2_ERROR_FS)
+    mount_count: u16,
+    max_mount_count: i16, // Negatifse bağlama sayısıyla denetim kapalı
+    last_check: u32,
+    check_interval: u32, // Saniye; 0 kapalı
+    dirty: bool, // Süper blok bu bağlamada "temiz değil" işaretlendi mi?
     // Diğer süper blok bilgileri eklenebilir
 }
 
-impl ExtFilesystem {
-    /// Belirtilen blok cihazı Handle'ından EXT2 dosya sistemini bağlar (mount).
-    /// Sadece okuma amaçlıdır.
-    pub fn mount(device_handle: Handle) -> Result<Self, SahneError> {
-        if !device_handle.is_valid() {
-            return Err(SahneError::InvalidHandle);
-        }
-
-        // Süper bloğu oku (genellikle blok 1, offset 1024)
-        // Blok 1, sektör 2'ye denk gelir (sektör boyutu 512 varsayımıyla).
-        let mut super_block_buffer = alloc::vec![0u8; 1024]; // Süper blok boyutu 1024
-        // Blok cihazdan 2. sektörden (offset 1024) 1024 bayt oku
-        let bytes_read = resource::read(device_handle, &mut super_block_buffer, EXT2_SUPERBLOCK_OFFSET, super_block_buffer.len())?;
-        if bytes_read != super_block_buffer.len() {
-             return Err(SahneError::InvalidOperation); // Yeterli veri okunamadı
-        }
+impl<D: BlockDevice> ExtFilesystem<D> {
+    /// Belirtilen blok cihazdaki EXT2 dosya sistemini bağlar (mount).
+    /// Süper bloktaki değerler doğrulanır; bozuk bir imaj panik yerine InvalidParameter ile reddedilir.
+    /// Yazma işlemleri cihaz yazılabilirse ve dosya sisteminin özellikleri destekleniyorsa çalışır.
+    pub fn mount(device: D) -> Result<Self, SahneError> {
+        // Süper bloğu oku (cihazın 1024. baytından itibaren 1024 bayt)
+        let mut super_block_buffer = zeroed_buffer(EXT2_SUPERBLOCK_SIZE)?;
+        read_exact_at(&device, EXT2_SUPERBLOCK_OFFSET, &mut super_block_buffer)?;
 
         // Süper bloğu parse et
-        // Okunan baytları Superblock yapısına pointer casting ile eriş
-        let superblock: &Superblock = unsafe {
-            // Süper bloğun başlangıç adresini al ve Superblock pointer'a dönüştür
-            let ptr = super_block_buffer.as_ptr() as *const Superblock;
-            // Dereference et
-            &*ptr
-        };
+        let superblock: Superblock = read_struct(&super_block_buffer, 0)?;
 
         // Süper blok sihirli sayısını kontrol et
         if superblock.s_magic != EXT2_SUPERBLOCK_MAGIC {
-            sahne64::printk!("EXT: Geçersiz sihirli sayı: {:#x}\n", superblock.s_magic);
             return Err(SahneError::InvalidParameter); // Veya NamingError / InvalidFormat
         }
+        if superblock.s_log_block_size > EXT2_MAX_LOG_BLOCK_SIZE {
+            return Err(SahneError::InvalidParameter);
+        }
 
         let block_size = EXT2_MIN_BLOCK_SIZE << superblock.s_log_block_size;
-        let inode_size = if superblock.s_rev_level >= 1 { superblock.s_inode_size } else { 128 }; // Rev 0 ise 128
-
-        sahne64::printk!("EXT: Dosya sistemi bulundu! Blok boyutu: {}, i-node boyutu: {}\n", block_size, inode_size);
-         printk!("EXT: Toplam i-node: {}, Toplam blok: {}\n", superblock.s_inodes_count, superblock.s_blocks_count);
-
+        let inode_size = if superblock.s_rev_level >= 1 { superblock.s_inode_size } else { EXT2_GOOD_OLD_INODE_SIZE }; // Rev 0 ise 128
+        if (inode_size as usize) < mem::size_of::<Inode>() || inode_size as u32 > block_size || !inode_size.is_power_of_two() {
+            return Err(SahneError::InvalidParameter);
+        }
 
-        // Blok grubunun kaç blok içerdiğini hesapla (Süper bloktan s_blocks_per_group kullanılır)
+        // Blok grubunun kaç blok/i-node içerdiği süper bloktan alınır
         let blocks_per_group = superblock.s_blocks_per_group;
         let inodes_per_group = superblock.s_inodes_per_group;
         let first_data_block = superblock.s_first_data_block;
         let total_inode_count = superblock.s_inodes_count;
         let total_block_count = superblock.s_blocks_count;
+        if blocks_per_group == 0 || inodes_per_group == 0 || first_data_block >= total_block_count {
+            return Err(SahneError::InvalidParameter);
+        }
+        let (first_inode, feature_incompat, feature_ro_compat, reserved_gdt_blocks) = if superblock.s_rev_level == EXT2_GOOD_OLD_REV {
+            (EXT2_GOOD_OLD_FIRST_INO, 0, 0, 0)
+        } else {
+            let reserved = &super_block_buffer[SUPERBLOCK_RESERVED_GDT_OFFSET..SUPERBLOCK_RESERVED_GDT_OFFSET + 2];
+            let reserved_gdt_blocks = u16::from_le_bytes([reserved[0], reserved[1]]) as u32;
+            (superblock.s_first_ino, superblock.s_feature_incompat, superblock.s_feature_ro_compat, reserved_gdt_blocks)
+        };
 
-        // Blok grubu sayısını hesapla
-        let group_count = (total_block_count + blocks_per_group - 1) / blocks_per_group;
-         printk!("EXT: Blok grubu sayısı: {}\n", group_count);
+        // Blok grubu sayısını hesapla (ilk veri bloğundan önceki bloklar gruplara dahil değildir)
+        let group_count = (total_block_count - first_data_block).div_ceil(blocks_per_group);
+        if (group_count as u64) * (inodes_per_group as u64) < total_inode_count as u64 {
+            return Err(SahneError::InvalidParameter); // i-node sayısı gruplara sığmıyor
+        }
 
         // Grup Tanımlayıcı Tablosunu oku
-        // Grup tanımlayıcı tablosu, ilk veri bloğundan sonra başlar.
-        // 1024 bayt bloklar için genellikle blok 2'de başlar.
-        // 2048/4096 bayt bloklar için genellikle blok 1'de başlar.
-        let group_desc_block = superblock.s_first_data_block + 1; // Genellikle blok 2
+        // Tablo, süper bloğu içeren bloktan hemen sonraki bloktadır:
+        // 1024 bayt bloklar için blok 2, daha büyük bloklar için blok 1.
+        let group_desc_block = first_data_block + 1;
         let group_desc_size = mem::size_of::<GroupDescriptor>();
-        let group_desc_table_size = (group_count as usize) * group_desc_size;
-        let mut group_desc_buffer = alloc::vec![0u8; group_desc_table_size];
-
-        // Blok cihazdan Grup Tanımlayıcı Tablosu'nu oku
-        // EXT2 dosya sistemi bloklarını cihaz bloklarına çevirmek gerek!
-        // EXT2 blok X -> Cihaz bloğu (X * block_size) / device_block_size
-        // Basitlik için cihaz blok boyutu 512 bayt varsayalım.
-        let device_block_size = 512; // TODO: Kernelden veya donanımdan öğrenilmeli!
-        let group_desc_device_block_start = (group_desc_block as u64 * block_size as u64) / device_block_size as u64;
-        let group_desc_offset_in_device_block = (group_desc_block as u64 * block_size as u64) % device_block_size as u64;
-
-        let bytes_read = resource::read(
-             device_handle,
-             &mut group_desc_buffer,
-             group_desc_device_block_start as usize * device_block_size + group_desc_offset_in_device_block as usize, // Okuma offseti
-             group_desc_buffer.len() // Okunacak uzunluk
-        )?;
-
-         if bytes_read != group_desc_buffer.len() {
-             return Err(SahneError::InvalidOperation); // Yeterli veri okunamadı
-         }
-
+        let group_desc_table_size = (group_count as usize)
+            .checked_mul(group_desc_size)
+            .ok_or(SahneError::InvalidParameter)?;
+        let mut group_desc_buffer = zeroed_buffer(group_desc_table_size)?;
+        read_exact_at(&device, group_desc_block as u64 * block_size as u64, &mut group_desc_buffer)?;
 
         // Grup tanımlayıcılarını parse et
-        let group_descriptors: Vec<GroupDescriptor> = (0..group_count)
-            .map(|i| {
-                let offset = i as usize * group_desc_size;
-                let ptr = unsafe { group_desc_buffer.as_ptr().add(offset) as *const GroupDescriptor };
-                unsafe { ptr.read_unaligned() } // Paketlenmiş yapıları okurken unaligned read gerekebilir
-            })
-            .collect();
-
-         printk!("EXT: {} blok grubu tanımlayıcısı okundu.\n", group_descriptors.len());
-         printk!("EXT: İlk grup i-node tablosu: Blok {}\n", group_descriptors[0].bg_inode_table);
-
+        let mut group_descriptors: Vec<GroupDescriptor> = Vec::new();
+        group_descriptors.try_reserve_exact(group_count as usize).map_err(|_| SahneError::OutOfMemory)?;
+        for i in 0..group_count as usize {
+            group_descriptors.push(read_struct(&group_desc_buffer, i * group_desc_size)?);
+        }
 
         Ok(ExtFilesystem {
-            device_handle,
+            device,
             block_size,
             inode_size,
             blocks_per_group,
@@ -229,25 +419,129 @@ impl ExtFilesystem {
             total_block_count,
             group_count,
             group_descriptors,
+            block_map: RefCell::new(BlockMapCache::new()),
+            free_blocks_count: superblock.s_free_blocks_count,
+            free_inodes_count: superblock.s_free_inodes_count,
+            first_inode,
+            feature_incompat,
+            feature_ro_compat,
+            bitmap: (0, Vec::new()),
+            reserved_gdt_blocks,
+            state: superblock.s_state,
+            mount_count: superblock.s_mnt_count,
+            max_mount_count: superblock.s_max_mnt_count as i16,
+            last_check: superblock.s_lastcheck,
+            check_interval: superblock.s_checkinterval,
+            dirty: false,
         })
     }
 
-    /// Blok numaralarını cihaz bloklarına çeviren yardımcı fonksiyon.
-    /// EXT2 blokları -> cihaz offseti (bayt cinsinden)
-    fn fs_block_to_device_offset(&self, fs_block: u32) -> usize {
-         let device_block_size = 512; // TODO: Kernelden veya donanımdan öğrenilmeli!
-         (fs_block as u64 * self.block_size as u64) as usize
-         // Eğer cihazın blok boyutu farklıysa burası değişir.
-         // Örneğin, cihaz 512 bayt, FS 4096 bayt blok kullanıyorsa:
-         // (fs_block * 4096) / 512 = fs_block * 8 cihaz bloğu
-         // Yani offset = fs_block * 8 * 512
-         // En basiti: offset = fs_block * self.block_size
+    /// Dosya sistemini ayırır ve cihazı geri verir. Bu bağlamada yazıldıysa süper blok yeniden "temiz"
+    /// işaretlenir (bir sonraki açılışta denetim gerekmez).
+    pub fn unmount(mut self) -> Result<D, SahneError> {
+        if self.dirty {
+            self.state |= EXT2_VALID_FS;
+            self.write_mount_state()?;
+            self.device.set_mounted(false)?;
+        }
+        Ok(self.device)
+    }
+
+    /// Dosya sistemi blok boyutu (bayt).
+    pub fn block_size(&self) -> u32 {
+        self.block_size
+    }
+
+    /// Dosya sistemi blok numarasını cihaz offsetine (bayt) çevirir.
+    /// Offset bayt cinsinden olduğu için cihazın sektör boyutundan bağımsızdır.
+    fn fs_block_to_device_offset(&self, fs_block: u32) -> u64 {
+        fs_block as u64 * self.block_size as u64
+    }
+
+    /// Bir dosya sistemi bloğunu okur. Blok numarası dosya sistemi dışındaysa InvalidParameter döner.
+    fn read_block(&self, fs_block: u32) -> Result<Vec<u8>, SahneError> {
+        let mut block_buffer = zeroed_buffer(self.block_size as usize)?;
+        self.read_block_part(fs_block, 0, &mut block_buffer)?;
+        Ok(block_buffer)
+    }
+
+    /// Bloğun `offset_in_block` baytından itibaren `buffer.len()` baytı okur (blok sınırını aşmamalı).
+    fn read_block_part(&self, fs_block: u32, offset_in_block: usize, buffer: &mut [u8]) -> Result<(), SahneError> {
+        if fs_block >= self.total_block_count || offset_in_block + buffer.len() > self.block_size as usize {
+            return Err(SahneError::InvalidParameter);
+        }
+        let offset = self.fs_block_to_device_offset(fs_block) + offset_in_block as u64;
+        read_exact_at(&self.device, offset, buffer)
+    }
+
+    /// Dosyanın `index`. bloğunun dosya sistemi blok numarasını döndürür; 0 ayrılmamış (seyrek) bloktur.
+    fn map_block(&self, inode: &Inode, index: u32) -> Result<u32, SahneError> {
+        let (slot, depth, index) = self.locate_block(index)?;
+        self.walk_indirect(inode.i_block[slot], depth, index)
+    }
+
+    // Dosya blok indeksinin yerini bulur: (i_block yuvası, dolaylılık derinliği, ağaç içindeki indeks).
+    // Doğrudan bloklarda derinlik 0'dır.
+    fn locate_block(&self, index: u32) -> Result<(usize, usize, u64), SahneError> {
+        if index < EXT2_NDIR_BLOCKS {
+            return Ok((index as usize, 0, 0));
+        }
+        let pointers_per_block = (self.block_size / 4) as u64;
+        let mut index = (index - EXT2_NDIR_BLOCKS) as u64;
+        let mut span = 1; // Bu derinlikteki ağacın kapsadığı veri bloğu sayısı
+        for depth in 1..=EXT2_MAX_INDIRECTION {
+            span *= pointers_per_block;
+            if index < span {
+                return Ok((EXT2_IND_BLOCK + depth - 1, depth, index));
+            }
+            index -= span;
+        }
+        Err(SahneError::InvalidParameter) // Üç dolaylı ağacın da ötesinde
+    }
+
+    // `root`tan başlayan `depth` derinlikli dolaylı ağaçta `index`. veri bloğunu bulur.
+    fn walk_indirect(&self, root: u32, depth: usize, index: u64) -> Result<u32, SahneError> {
+        let pointers_per_block = (self.block_size / 4) as u64;
+        let mut block = root;
+        for level in 0..depth {
+            if block == 0 {
+                return Ok(0); // Alt ağaç ayrılmamış
+            }
+            let span = pointers_per_block.pow((depth - 1 - level) as u32);
+            block = self.indirect_pointer(level, block, (index / span % pointers_per_block) as usize)?;
+        }
+        Ok(block)
+    }
+
+    // Dolaylı bloktaki `slot`. işaretçiyi döndürür. Blok önbellekteki `level` yuvasında değilse okunur.
+    fn indirect_pointer(&self, level: usize, block: u32, slot: usize) -> Result<u32, SahneError> {
+        let mut cache = self.block_map.borrow_mut();
+        let (cached_block, data) = &mut cache.levels[level];
+        if *cached_block != block {
+            if data.is_empty() {
+                *data = zeroed_buffer(self.block_size as usize)?;
+            }
+            *cached_block = 0; // Okuma başarısız olursa yuva boş kalır
+            self.read_block_part(block, 0, data)?;
+            *cached_block = block;
+        }
+        let offset = slot * 4;
+        Ok(u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]))
     }
 
 
     /// i-node numarasından i-node yapısını okur.
     /// Root i-node genellikle 2 numaralı i-node'dur.
     pub fn read_inode(&self, inode_number: u32) -> Result<Inode, SahneError> {
+        let (inode_fs_block, inode_offset_in_block) = self.inode_location(inode_number)?;
+
+        // i-node bloğunu cihazdan oku ve yapıyı buffer'dan al
+        let block_buffer = self.read_block(inode_fs_block)?;
+        read_struct(&block_buffer, inode_offset_in_block)
+    }
+
+    // i-node'un bulunduğu dosya sistemi bloğunu ve blok içindeki ofsetini hesaplar.
+    fn inode_location(&self, inode_number: u32) -> Result<(u32, usize), SahneError> {
         if inode_number == 0 || inode_number > self.total_inode_count {
             return Err(SahneError::InvalidParameter); // Geçersiz i-node numarası
         }
@@ -261,131 +555,169 @@ impl ExtFilesystem {
             return Err(SahneError::InvalidParameter); // Hesaplanan grup geçersiz
         }
 
-        // Grup tanımlayıcısını al
-        let group_desc = &self.group_descriptors[group_index as usize];
-
-        // i-node tablosunun başlangıç bloğunu al
-        let inode_table_start_block = group_desc.bg_inode_table;
+        // Grup tanımlayıcısından i-node tablosunun başlangıç bloğunu al
+        let inode_table_start_block = self.group_descriptors[group_index as usize].bg_inode_table;
 
         // i-node'un tablo içindeki ofsetini hesapla (bayt cinsinden)
-        let inode_offset_in_table = inode_index_in_group as usize * self.inode_size as usize;
-
-        // i-node'un bulunduğu dosya sistemi bloğunu hesapla
-        let inode_fs_block = inode_table_start_block + (inode_offset_in_table as u32 / self.block_size);
+        let inode_offset_in_table = inode_index_in_group as u64 * self.inode_size as u64;
 
-        // i-node'un bulunduğu blok içindeki ofsetini hesapla (bayt cinsinden)
-        let inode_offset_in_block = inode_offset_in_table % self.block_size as usize;
-
-        // i-node bloğunu cihazdan oku
-        let mut block_buffer = alloc::vec![0u8; self.block_size as usize];
-        let device_offset = self.fs_block_to_device_offset(inode_fs_block);
-
-        let bytes_read = resource::read(self.device_handle, &mut block_buffer, device_offset, self.block_size as usize)?;
-        if bytes_read != self.block_size as usize {
-            return Err(SahneError::InvalidOperation); // Blok okunamadı
-        }
-
-        // i-node yapısını buffer'dan al
-        let inode: Inode = unsafe {
-            let ptr = block_buffer.as_ptr().add(inode_offset_in_block) as *const Inode;
-            ptr.read_unaligned() // Paketlenmiş yapıları okurken unaligned read gerekebilir
-        };
-
-        Ok(inode)
+        // i-node'un bulunduğu dosya sistemi bloğu ve blok içindeki ofseti
+        let inode_fs_block = inode_table_start_block as u64 + inode_offset_in_table / self.block_size as u64;
+        let inode_offset_in_block = (inode_offset_in_table % self.block_size as u64) as usize;
+        let inode_fs_block = u32::try_from(inode_fs_block).map_err(|_| SahneError::InvalidParameter)?;
+        Ok((inode_fs_block, inode_offset_in_block))
     }
 
     /// Bir dizinin içeriğini listeler (alt dosya ve dizin isimleri).
     /// Sadece EXT2 revizyon 0 veya 1'deki temel dizin girişlerini destekler.
     pub fn list_directory(&self, dir_inode: &Inode) -> Result<Vec<DirectoryEntry>, SahneError> {
-        if (dir_inode.i_mode & S_IFDIR) == 0 {
+        if !dir_inode.is_dir() {
             return Err(SahneError::InvalidOperation); // Bu bir dizin değil
         }
 
-        // Dizin içeriği i_block işaretçilerindeki veri bloklarında saklanır.
-        // Sadece doğrudan (direct) blok işaretçilerini destekliyoruz (i_block[0] - i_block[11]).
+        // Dizin içeriği i_size kadar veri bloğunda saklanır (doğrudan ve dolaylı işaretçiler).
         let mut entries = Vec::new();
-        let mut current_offset_in_dir = 0;
+        let block_count = dir_inode.i_size.div_ceil(self.block_size);
+        let mut block_buffer = zeroed_buffer(self.block_size as usize)?;
+        for index in 0..block_count {
+            let data_block_number = self.map_block(dir_inode, index)?;
+            if data_block_number == 0 {
+                continue; // Ayrılmamış blok: giriş yok
+            }
+            self.read_block_part(data_block_number, 0, &mut block_buffer)?;
+            parse_directory_block(&block_buffer, &mut entries)?;
+        }
+        Ok(entries)
+    }
 
-        // TODO: Dolaylı (indirect) blok işaretçileri (i_block[12], [13], [14]) implemente edilmeli
-        // eğer dizin içeriği birden fazla doğrudan bloktan büyükse.
-        // Bu implementasyon sadece i_block[0]'daki veriyi okur.
-        let data_block_number = dir_inode.i_block[0]; // İlk doğrudan blok
 
-        if data_block_number == 0 {
-             return Ok(entries); // Boş dizin
-        }
+    /// `dir_number` dizinindeki `name` girişinin i-node numarasını döndürür (bulunamazsa ResourceNotFound).
+    pub fn lookup(&self, dir_number: u32, name: &str) -> Result<u32, SahneError> {
+        let dir = self.read_dir_inode(dir_number)?;
+        self.find_entry(&dir, name.as_bytes())?.ok_or(SahneError::ResourceNotFound)
+    }
 
-        let mut block_buffer = alloc::vec![0u8; self.block_size as usize];
-        let device_offset = self.fs_block_to_device_offset(data_block_number);
+    /// `path` yolunu i-node numarasına çözer. Göreli yollar `cwd` dizininden başlar; "/" ile başlayanlar
+    /// kökten. ".", ".." ve art arda gelen eğik çizgiler desteklenir; yoldaki tüm sembolik bağlantılar
+    /// (son bileşen dahil) izlenir. Bileşen bulunamazsa ResourceNotFound, dizin olmayan bir bileşenin
+    /// altına inilirse InvalidOperation, bağlantı döngüsünde (40'tan fazla bağlantı) NamingError döner.
+    pub fn resolve(&self, path: &str, cwd: u32) -> Result<u32, SahneError> {
+        self.walk_path(path.as_bytes(), cwd)
+    }
 
-        let bytes_read = resource::read(self.device_handle, &mut block_buffer, device_offset, self.block_size as usize)?;
-        if bytes_read != self.block_size as usize {
-            return Err(SahneError::InvalidOperation); // Dizin bloğu okunamadı
+    /// Yolun son bileşeni dışındakileri çözer: (üst dizin, son bileşenin adı). Son bileşen izlenmez;
+    /// böylece bir bağlantının kendisi üzerinde işlem yapılabilir (unlink, readlink, rename).
+    /// Son bileşen yoksa ("/", "") InvalidParameter döner.
+    pub fn resolve_parent<'a>(&self, path: &'a str, cwd: u32) -> Result<(u32, &'a str), SahneError> {
+        let trimmed = path.trim_end_matches('/');
+        let (dir_path, name) = match trimmed.rfind('/') {
+            Some(0) => ("/", &trimmed[1..]),
+            Some(slash) => (&trimmed[..slash], &trimmed[slash + 1..]),
+            None => (".", trimmed),
+        };
+        if name.is_empty() {
+            return Err(SahneError::InvalidParameter);
         }
+        let dir = self.walk_path(dir_path.as_bytes(), cwd)?;
+        self.read_dir_inode(dir)?;
+        Ok((dir, name))
+    }
 
-        // Blok içindeki dizin girişlerini parse et
-        while current_offset_in_dir < self.block_size as usize {
-            let entry: &DirectoryEntry = unsafe {
-                let ptr = block_buffer.as_ptr().add(current_offset_in_dir) as *const DirectoryEntry;
-                &*ptr // Dereference
-                // read_unaligned() gerekebilir eğer packed struct kullanıyorsak?
-                 ptr.read_unaligned()
-            };
-
-            // Geçersiz giriş (inode 0 ise) veya son giriş (rec_len çok büyükse)
-            if entry.inode == 0 || entry.rec_len == 0 {
-                 // Bu girişi atla, genellikle son giriş veya silinmiş girişler böyle olur
-                 // Ama döngüyü kırmamak için rec_len kadar ilerlemek önemli.
-                 if entry.rec_len > 0 {
-                      current_offset_in_dir += entry.rec_len as usize;
-                      continue;
-                 } else {
-                      // rec_len 0 ise sonsuz döngüyü önle
-                      break;
-                 }
+    /// Sembolik bağlantının hedefini döndürür (bağlantı değilse InvalidOperation).
+    pub fn readlink(&self, inode_number: u32) -> Result<Vec<u8>, SahneError> {
+        let inode = self.read_inode(inode_number)?;
+        if !inode.is_symlink() {
+            return Err(SahneError::InvalidOperation);
+        }
+        let len = inode.i_size as usize;
+        if len == 0 || len >= self.block_size as usize {
+            return Err(SahneError::InvalidParameter); // Bozuk bağlantı
+        }
+        let mut target = zeroed_buffer(len)?;
+        if inode.is_fast_symlink() {
+            let i_block = inode.i_block;
+            for (chunk, pointer) in target.chunks_mut(4).zip(i_block.iter()) {
+                chunk.copy_from_slice(&pointer.to_le_bytes()[..chunk.len()]);
             }
-
-
-            // İsim uzunluğunu kontrol et (name_len 255'ten büyük olamaz)
-            let name_len = entry.name_len as usize;
-            if name_len > 255 || (current_offset_in_dir + entry.rec_len as usize) > self.block_size as usize {
-                  printk!("EXT: Hata: Geçersiz dizin girişi veya rec_len! Offset: {}, rec_len: {}\n", current_offset_in_dir, entry.rec_len);
-                 break; // Geçersiz yapı, döngüyü kır
+        } else {
+            let block = self.map_block(&inode, 0)?;
+            if block == 0 {
+                return Err(SahneError::InvalidParameter);
             }
+            self.read_block_part(block, 0, &mut target)?;
+        }
+        Ok(target)
+    }
 
-            // İsim slice'ını al ve String'e dönüştür
-            let name_slice = &entry.name[0..name_len];
-            let name = str::from_utf8(name_slice).unwrap_or("<geçersiz isim>"); // UTF8 değilse placeholder
-
-             printk!("EXT: Dizin Girişi: i-node {}, rec_len {}, name_len {}, name: {}\n", entry.inode, entry.rec_len, entry.name_len, name);
-
-
-            // Yeni bir DirectoryEntry struct oluştur ve listeye ekle
-            // Kendi hafıza alanına kopyalamak daha güvenlidir.
-            let mut owned_entry = DirectoryEntry {
-                inode: entry.inode,
-                rec_len: entry.rec_len,
-                name_len: entry.name_len,
-                name: [0u8; 255], // Varsayılan sıfırlarla doldur
-                file_type: entry.file_type, // Rev 1+ için
-            };
-            owned_entry.name[0..name_len].copy_from_slice(name_slice);
-
-            entries.push(owned_entry);
-
+    // Yol çözümü. Bağlantı izlendiğinde hedefi yolun kalanının önüne eklenir ve çözüm bağlantının
+    // bulunduğu dizinden (hedef mutlaksa kökten) sürer.
+    fn walk_path(&self, path: &[u8], cwd: u32) -> Result<u32, SahneError> {
+        if path.is_empty() {
+            return Err(SahneError::InvalidParameter);
+        }
+        let mut remaining = Vec::new();
+        remaining.try_reserve_exact(path.len()).map_err(|_| SahneError::OutOfMemory)?;
+        remaining.extend_from_slice(path);
+        let mut current = if path[0] == b'/' { EXT2_ROOT_INODE } else { cwd };
+        let mut position = 0;
+        let mut followed = 0;
+        loop {
+            while remaining.get(position) == Some(&b'/') {
+                position += 1;
+            }
+            if position == remaining.len() {
+                break;
+            }
+            let end = remaining[position..].iter().position(|&byte| byte == b'/').map_or(remaining.len(), |i| position + i);
+            let dir = self.read_dir_inode(current)?;
+            let next = self.find_entry(&dir, &remaining[position..end])?.ok_or(SahneError::ResourceNotFound)?;
+            if !self.read_inode(next)?.is_symlink() {
+                current = next;
+                position = end;
+                continue;
+            }
 
-            // Bir sonraki girişe geç
-            current_offset_in_dir += entry.rec_len as usize;
+            followed += 1;
+            if followed > EXT2_MAX_SYMLINK_FOLLOWS {
+                return Err(SahneError::NamingError);
+            }
+            let target = self.readlink(next)?;
+            let rest = &remaining[end..];
+            let mut expanded = Vec::new();
+            expanded.try_reserve_exact(target.len() + rest.len()).map_err(|_| SahneError::OutOfMemory)?;
+            expanded.extend_from_slice(&target);
+            expanded.extend_from_slice(rest);
+            if target[0] == b'/' {
+                current = EXT2_ROOT_INODE;
+            }
+            remaining = expanded;
+            position = 0;
+        }
+        // "dosya/" gibi eğik çizgiyle biten yollar bir dizin göstermeli.
+        if remaining.last() == Some(&b'/') && !self.read_inode(current)?.is_dir() {
+            return Err(SahneError::InvalidOperation);
         }
+        Ok(current)
+    }
 
-        Ok(entries)
+    // Dizin i-node'unu okur; dizin değilse InvalidOperation döner.
+    fn read_dir_inode(&self, dir_number: u32) -> Result<Inode, SahneError> {
+        let dir = self.read_inode(dir_number)?;
+        if !dir.is_dir() {
+            return Err(SahneError::InvalidOperation);
+        }
+        Ok(dir)
     }
 
+    // Dizinde adı verilen girişin i-node numarasını arar.
+    fn find_entry(&self, dir: &Inode, name: &[u8]) -> Result<Option<u32>, SahneError> {
+        Ok(self.list_directory(dir)?.iter().find(|entry| entry.name_bytes() == name).map(|entry| entry.inode))
+    }
 
-    /// Bir dosyadan veri okur.
-    /// Sadece doğrudan (direct) blok işaretçilerini destekler ve dosya boyutuyla sınırlıdır.
+    /// Bir dosyadan veri okur (doğrudan, tek/çift/üç dolaylı bloklar); dosya boyutuyla sınırlıdır.
+    /// Seyrek dosyalardaki ayrılmamış bloklar sıfır olarak okunur.
     pub fn read_file(&self, file_inode: &Inode, buffer: &mut [u8], offset: usize) -> Result<usize, SahneError> {
-        if (file_inode.i_mode & S_IFREG) == 0 {
+        if !file_inode.is_file() {
             return Err(SahneError::InvalidOperation); // Bu bir dosya değil
         }
 
@@ -404,10 +736,6 @@ impl ExtFilesystem {
 
         let initial_bytes_to_read = bytes_to_read;
 
-        // TODO: Dolaylı (indirect) blok işaretçileri (i_block[12], [13], [14]) implemente edilmeli
-        // eğer dosya boyutu birden fazla doğrudan bloktan büyükse.
-        // Bu implementasyon sadece i_block[0] - i_block[11]'deki verilere doğrudan erişir.
-
         while bytes_to_read > 0 && current_offset_in_file < file_size {
             // Hangi dosya sistemi bloğunda olduğumuzu hesapla
             let fs_block_index_in_file = current_offset_in_file / self.block_size as usize;
@@ -415,33 +743,16 @@ impl ExtFilesystem {
             let bytes_left_in_block = self.block_size as usize - offset_in_fs_block;
             let read_len_in_block = core::cmp::min(bytes_to_read, bytes_left_in_block);
 
-            // İlgili blok numarasını al (sadece doğrudan işaretçiler)
-            if fs_block_index_in_file >= file_inode.i_block.len() {
-                 printk!("EXT: Hata: Dosya boyutu doğrudan blokları aştı ama dolaylı bloklar implemente edilmedi.\n");
-                break; // Desteklenmeyen dosya yapısı
-            }
-            let data_fs_block = file_inode.i_block[fs_block_index_in_file];
+            // İlgili blok numarasını al (dolaylı bloklar önbellekten çözülür)
+            let data_fs_block = self.map_block(file_inode, fs_block_index_in_file as u32)?;
+            let destination = &mut buffer[buffer_write_offset..buffer_write_offset + read_len_in_block];
 
             if data_fs_block == 0 {
-                  printk!("EXT: Hata: Okunmaya çalışılan blok (index {}) boş (0). Dosya sparse olabilir veya hata var.\n", fs_block_index_in_file);
                  // Boş bloktan okunuyorsa (sparse dosya), sıfır bayt döndürülür.
-                 // Buffer'ın ilgili kısmını sıfırla
-                 for i in 0..read_len_in_block {
-                     buffer[buffer_write_offset + i] = 0;
-                 }
+                 destination.fill(0);
             } else {
-                 // Veri bloğunu cihazdan oku
-                 let device_offset = self.fs_block_to_device_offset(data_fs_block);
-                 let mut block_buffer = alloc::vec![0u8; self.block_size as usize]; // Geçici buffer
-                 let bytes_read_from_block = resource::read(self.device_handle, &mut block_buffer, device_offset, self.block_size as usize)?;
-
-                 if bytes_read_from_block != self.block_size as usize {
-                     return Err(SahneError::InvalidOperation); // Blok okunamadı
-                 }
-
-                 // Okunan veriyi hedef buffera kopyala
-                 buffer[buffer_write_offset .. buffer_write_offset + read_len_in_block]
-                    .copy_from_slice(&block_buffer[offset_in_fs_block .. offset_in_fs_block + read_len_in_block]);
+                 // Veri bloğunun istenen kısmı doğrudan hedef buffera okunur
+                 self.read_block_part(data_fs_block, offset_in_fs_block, destination)?;
             }
 
 
@@ -456,85 +767,1562 @@ impl ExtFilesystem {
 
 
     // TODO: Başka temel dosya sistemi fonksiyonları (read_link, stat, vb.) eklenebilir.
-    // TODO: Dolaylı (indirect) blok işaretçileri için yardımcı fonksiyonlar yazılmalı.
-     pub fn resolve_indirect_block(&self, block_number: u32, indirect_level: u32) -> Result<Vec<u32>, SahneError> { ... }
 
+    // Root dizinine erişmek için kolaylık fonksiyonu
+    pub fn root_directory(&self) -> Result<Inode, SahneError> {
+        self.read_inode(EXT2_ROOT_INODE) // EXT2'de root i-node her zaman 2'dir.
+    }
 }
 
+// Yazma işlemleri. Cihaz MODE_WRITE ile edinilmiş olmalı; dosya sistemi tek bir görev tarafından
+// bağlanmış varsayılır (ayırma bilgileri bellekte tutulur).
+impl<D: BlockDevice> ExtFilesystem<D> {
+    /// `dir_number` dizininde `name` adlı boş bir dosya oluşturur ve i-node numarasını döndürür.
+    /// Ad zaten varsa NamingError, disk veya i-node tablosu doluysa OutOfMemory döner.
+    pub fn create_file(&mut self, dir_number: u32, name: &str) -> Result<u32, SahneError> {
+        self.check_writable()?;
+        self.create_inode(dir_number, name, NEW_FILE_MODE, |_, _, _| Ok(()))
+    }
+
+    /// `parent` dizininde `name` adlı boş bir dizin oluşturur ("." ve ".." girişleriyle) ve i-node
+    /// numarasını döndürür. Yeni dizinin bağlantı sayısı 2'dir; ".." nedeniyle üst dizininki bir artar.
+    pub fn mkdir(&mut self, parent: u32, name: &str) -> Result<u32, SahneError> {
+        self.check_writable()?;
+        let number = self.create_inode(parent, name, NEW_DIR_MODE, |fs, number, inode| {
+            let goal = fs.first_data_block + (number - 1) / fs.inodes_per_group * fs.blocks_per_group;
+            let (block, _) = fs.map_block_alloc(inode, 0, goal)?;
+            let mut buffer = zeroed_buffer(fs.block_size as usize)?;
+            let dot_len = dir_entry_len(1);
+            fs.encode_entry_at(&mut buffer, 0, dot_len, b".", number, EXT2_FT_DIR);
+            fs.encode_entry_at(&mut buffer, dot_len, fs.block_size as usize - dot_len, b"..", parent, EXT2_FT_DIR);
+            fs.write_block_part(block, 0, &buffer)?;
+            inode.i_size = fs.block_size;
+            inode.i_links_count = 2;
+            Ok(())
+        })?;
+        self.adjust_links(parent, 1)?;
+        Ok(number)
+    }
+
+    /// `parent` dizinindeki boş `name` dizinini siler.
+    /// Dizin boş değilse ResourceBusy, dizin değilse InvalidOperation döner.
+    pub fn rmdir(&mut self, parent: u32, name: &str) -> Result<(), SahneError> {
+        self.check_writable()?;
+        check_entry_name(name)?;
+        let number = self.lookup(parent, name)?;
+        let mut inode = self.read_inode(number)?;
+        if !inode.is_dir() {
+            return Err(SahneError::InvalidOperation);
+        }
+        if number == EXT2_ROOT_INODE {
+            return Err(SahneError::PermissionDenied);
+        }
+        if !self.is_empty_dir(&inode)? {
+            return Err(SahneError::ResourceBusy);
+        }
+        self.remove_entry(parent, name.as_bytes())?;
+        self.adjust_links(parent, -1)?; // Silinen dizinin ".." girişi
+        self.release_inode(number, &mut inode)
+    }
+
+    /// `parent` dizinindeki `name` girişini siler. Dosyanın bağlantı sayısı sıfıra inerse i-node'u ve
+    /// blokları serbest bırakılır. Dizinler için rmdir kullanılmalıdır (InvalidOperation).
+    pub fn unlink(&mut self, parent: u32, name: &str) -> Result<(), SahneError> {
+        self.check_writable()?;
+        check_entry_name(name)?;
+        let number = self.lookup(parent, name)?;
+        if self.read_inode(number)?.is_dir() {
+            return Err(SahneError::InvalidOperation);
+        }
+        self.remove_entry(parent, name.as_bytes())?;
+        self.drop_link(number)
+    }
+
+    /// `target` i-node'una `parent` dizininde `name` adlı yeni bir sabit bağlantı oluşturur.
+    /// Dizinlere bağlantı oluşturulamaz (InvalidOperation).
+    pub fn link(&mut self, target: u32, parent: u32, name: &str) -> Result<(), SahneError> {
+        self.check_writable()?;
+        check_entry_name(name)?;
+        let inode = self.read_inode(target)?;
+        if inode.is_dir() {
+            return Err(SahneError::InvalidOperation);
+        }
+        if inode.i_links_count >= EXT2_LINK_MAX {
+            return Err(SahneError::ResourceBusy);
+        }
+        if self.find_entry(&self.read_dir_inode(parent)?, name.as_bytes())?.is_some() {
+            return Err(SahneError::NamingError);
+        }
+        self.add_entry(parent, name.as_bytes(), target, file_type_of(&inode))?;
+        self.adjust_links(target, 1)
+    }
+
+    /// `parent` dizininde `target` yolunu gösteren `name` adlı bir sembolik bağlantı oluşturur ve i-node
+    /// numarasını döndürür. Hedef denetlenmez (var olmayan bir yolu da gösterebilir); boş olamaz ve
+    /// blok boyutundan kısa olmalıdır (InvalidParameter).
+    pub fn symlink(&mut self, parent: u32, name: &str, target: &str) -> Result<u32, SahneError> {
+        self.check_writable()?;
+        let target = target.as_bytes();
+        if target.is_empty() || target.len() >= self.block_size as usize || target.contains(&0) {
+            return Err(SahneError::InvalidParameter);
+        }
+        self.create_inode(parent, name, NEW_SYMLINK_MODE, |fs, number, inode| {
+            inode.i_size = target.len() as u32;
+            if target.len() < EXT2_FAST_SYMLINK_SIZE {
+                let mut i_block = [0u32; 15];
+                for (pointer, chunk) in i_block.iter_mut().zip(target.chunks(4)) {
+                    let mut bytes = [0u8; 4];
+                    bytes[..chunk.len()].copy_from_slice(chunk);
+                    *pointer = u32::from_le_bytes(bytes);
+                }
+                inode.i_block = i_block;
+                return Ok(());
+            }
+            // Yeni blok write_data tarafından sıfırla doldurulur (hedef NUL ile biter).
+            fs.write_data(number, inode, 0, target).map(|_| ())
+        })
+    }
+
+    /// `old_parent` dizinindeki `old_name` girişini `new_parent` dizininde `new_name` olarak taşır.
+    /// Hedef ad varsa yerine geçilir (dosya dosyanın, boş dizin dizinin yerine). Hedef girişin i-node'u
+    /// yerinde değiştirilir; böylece yarıda kesilen bir rename'den sonra hedef ad her zaman eski ya da
+    /// yeni dosyayı gösterir. Dizin kendi alt dizinine taşınamaz (InvalidParameter).
+    pub fn rename(&mut self, old_parent: u32, old_name: &str, new_parent: u32, new_name: &str) -> Result<(), SahneError> {
+        self.check_writable()?;
+        check_entry_name(old_name)?;
+        check_entry_name(new_name)?;
+        let source_number = self.lookup(old_parent, old_name)?;
+        let source = self.read_inode(source_number)?;
+        let new_dir = self.read_dir_inode(new_parent)?;
+        let moves_dir = source.is_dir() && old_parent != new_parent;
+        if moves_dir {
+            self.check_not_ancestor(source_number, new_parent)?;
+        }
+
+        let file_type = file_type_of(&source);
+        let replaced = match self.find_entry(&new_dir, new_name.as_bytes())? {
+            Some(existing) if existing == source_number => return Ok(()), // Aynı dosyanın iki adı
+            Some(existing) => {
+                let replaced = self.read_inode(existing)?;
+                if replaced.is_dir() != source.is_dir() {
+                    return Err(SahneError::InvalidOperation);
+                }
+                if replaced.is_dir() && !self.is_empty_dir(&replaced)? {
+                    return Err(SahneError::ResourceBusy);
+                }
+                Some((existing, replaced))
+            }
+            None => None,
+        };
+        if source.i_links_count >= EXT2_LINK_MAX {
+            return Err(SahneError::ResourceBusy);
+        }
 
-// Helper fonksiyon: resource::read için offset ekleyen versiyon
-// Bu, çekirdeğin resource::read API'sinde offset parametresi olduğunu varsayar.
-// Eğer yoksa, çekirdek API'si güncellenmeli veya dosya sistemi blokları
-// doğrudan cihazın sektörlerine (örn. 512 bayt) dönüştürülmeli.
-impl resource::resource_impl { // resource modülü içinde bir impl bloğu varsayalım
-    // resource::read fonksiyonunu offset parametresi ile genişleten wrapper
-    // Offset cihazın başlangıcına göredir.
-    pub fn read(handle: Handle, buffer: &mut [u8], offset: usize, len: usize) -> Result<usize, SahneError> {
-        // sahne64::syscall(arch::SYSCALL_RESOURCE_READ, handle.raw(), buf_ptr, buf_len, offset as u64, 0) gibi olmalı
-        // Mevcut Sahne64 resource::read API'sinde offset yok.
-        // API ya güncellenmeli ya da dosya sistemi offseti cihaza özgü sektörlere çevirmeli.
-        // Şimdilik, mevcut API'yi kullanıp, offset'i arg4 olarak geçirecek şekilde syscall'ı doğrudan çağıralım.
-        // BU, sahne64::resource::read'in iç implementasyonunu 'hacklemek' demektir!
-        // İdeal olan sahne64::resource::read API'sinin offset parametresi içermesidir.
-
-        // Sahne64 API'sini bu senaryo için genişletilmiş varsayalım:
-          pub fn read_at(handle: Handle, buffer: &mut [u8], offset: usize) -> Result<usize, SahneError>
-
-        // Eğer resource::read API'sinde offset yoksa, aşağıdaki gibi doğrudan syscall çağrılabilir:
-        let buffer_ptr = buffer.as_mut_ptr() as u64;
-        let buffer_len = buffer.len() as u64;
-        let result = unsafe {
-            sahne64::syscall(
-                sahne64::arch::SYSCALL_RESOURCE_READ,
-                handle.raw(), // arg1: handle
-                buffer_ptr,   // arg2: buffer ptr
-                buffer_len,   // arg3: buffer len
-                offset as u64,// arg4: offset (yeni argüman)
-                len as u64    // arg5: len (yeni argüman) - Veya len buffer_len ile aynıysa arg3 yeterli
-            )
+        // Yarıda kalan bir yeniden adlandırmada bağlantı sayısı hiçbir i-node için girişlerinin altına
+        // düşmemeli (aksi halde i-node hâlâ kullanılırken serbest bırakılabilir). Sayılar yeni giriş
+        // eklenmeden önce artırılır, eski giriş kaldırıldıktan sonra azaltılır.
+        self.adjust_links(source_number, 1)?;
+        if moves_dir {
+            self.adjust_links(new_parent, 1)?; // Kaynağın yeni ".." girişi
+        }
+        let linked = match replaced {
+            Some(_) => self.set_entry_inode(new_parent, new_name.as_bytes(), source_number, file_type),
+            None => self.add_entry(new_parent, new_name.as_bytes(), source_number, file_type),
         };
+        if let Err(err) = linked {
+            if moves_dir {
+                self.adjust_links(new_parent, -1)?;
+            }
+            self.adjust_links(source_number, -1)?;
+            return Err(err);
+        }
+        self.remove_entry(old_parent, old_name.as_bytes())?;
+        self.adjust_links(source_number, -1)?;
 
-        if result < 0 {
-             Err(sahne64::map_kernel_error(result))
-        } else {
-             Ok(result as usize)
-        }
-
-        // TODO: resource::read API'si çekirdek tarafında offset'i destekleyecek şekilde güncellenmeli.
-        // O zaman bu helper fonksiyonun yerine doğrudan resource::read_at çağrılabilir.
-    }
-
-    // resource::write fonksiyonunu offset ekleyen versiyon (read ile benzer)
-     #[allow(dead_code)]
-     pub fn write(handle: Handle, buffer: &[u8], offset: usize, len: usize) -> Result<usize, SahneError> {
-         let buffer_ptr = buffer.as_ptr() as u64;
-         let buffer_len = buffer.len() as u64; // len argümanını kullanmıyoruz, buffer.len() kullanıyoruz.
-         let result = unsafe {
-             sahne64::syscall(
-                 sahne64::arch::SYSCALL_RESOURCE_WRITE,
-                 handle.raw(), // arg1: handle
-                 buffer_ptr,   // arg2: buffer ptr
-                 buffer_len,   // arg3: buffer len
-                 offset as u64,// arg4: offset (yeni argüman)
-                  len as u64 // arg5: len (isteğe bağlı)
-                 0 // arg5 kullanılmıyorsa 0
-             )
-         };
-
-        if result < 0 {
-             Err(sahne64::map_kernel_error(result))
+        if moves_dir {
+            self.set_entry_inode(source_number, b"..", new_parent, EXT2_FT_DIR)?;
+            self.adjust_links(old_parent, -1)?;
+        }
+        // Yerine geçilen i-node'un girişi artık kaynağı gösteriyor; bağlantısı şimdi düşülür.
+        match replaced {
+            Some((existing, mut replaced)) if replaced.is_dir() => {
+                self.release_inode(existing, &mut replaced)?;
+                self.adjust_links(new_parent, -1)?; // Yerine geçilen dizinin ".." girişi
+            }
+            Some((existing, _)) => self.drop_link(existing)?,
+            None => {}
+        }
+        let mut source = self.read_inode(source_number)?;
+        source.i_ctime = unix_time();
+        self.write_inode(source_number, &source)
+    }
+
+    /// Dosyanın `offset` baytından itibaren `data`yı yazar; gerekirse bloklar ayrılır ve dosya büyür.
+    /// Yazılan bayt sayısını döndürür. Disk dolarsa sığan kadarı yazılır (kısa yazma); hiç yer yoksa
+    /// OutOfMemory döner.
+    pub fn write_file(&mut self, inode_number: u32, offset: u64, data: &[u8]) -> Result<usize, SahneError> {
+        self.check_writable()?;
+        let mut inode = self.read_inode(inode_number)?;
+        if !inode.is_file() {
+            return Err(SahneError::InvalidOperation);
+        }
+        let end = offset.checked_add(data.len() as u64).ok_or(SahneError::InvalidParameter)?;
+        self.check_file_size(end)?;
+
+        // Ayrılan bloklar hata durumunda da i-node'a kaydedilmeli; i-node her durumda yazılır.
+        let result = self.write_data(inode_number, &mut inode, offset, data);
+        let written = *result.as_ref().unwrap_or(&0);
+        if written > 0 {
+            let now = unix_time();
+            inode.i_mtime = now;
+            inode.i_ctime = now;
+            inode.i_size = inode.i_size.max((offset + written as u64) as u32);
+        }
+        self.write_inode(inode_number, &inode)?;
+        result
+    }
+
+    /// `data`yı dosyanın sonuna ekler.
+    pub fn append_file(&mut self, inode_number: u32, data: &[u8]) -> Result<usize, SahneError> {
+        let size = self.read_inode(inode_number)?.i_size;
+        self.write_file(inode_number, size as u64, data)
+    }
+
+    /// Dosyanın boyutunu `size` yapar. Kısaltılırken artık kullanılmayan veri ve dolaylı bloklar
+    /// serbest bırakılır; büyütülürken yeni kısım seyrek (sıfır) kalır.
+    pub fn truncate_file(&mut self, inode_number: u32, size: u64) -> Result<(), SahneError> {
+        self.check_writable()?;
+        let mut inode = self.read_inode(inode_number)?;
+        if !inode.is_file() {
+            return Err(SahneError::InvalidOperation);
+        }
+        self.check_file_size(size)?;
+        let result = if size < inode.i_size as u64 { self.free_blocks_from(&mut inode, size) } else { Ok(()) };
+        if result.is_ok() {
+            inode.i_size = size as u32;
+        }
+        let now = unix_time();
+        inode.i_mtime = now;
+        inode.i_ctime = now;
+        self.write_inode(inode_number, &inode)?;
+        result
+    }
+
+    // Dosya sisteminin özellikleri yazmaya uygun mu? (ext3/ext4 özellikleri, bilinmeyen bayraklar: hayır)
+    // Uygunsa ilk yazmadan önce süper blok "temiz değil" işaretlenir ve bağlama sayısı artırılır;
+    // yarıda kalan bir yazma böylece bir sonraki açılışta fsck'i tetikler.
+    fn check_writable(&mut self) -> Result<(), SahneError> {
+        let ro_compat_supported = EXT2_FEATURE_RO_COMPAT_SPARSE_SUPER | EXT2_FEATURE_RO_COMPAT_LARGE_FILE;
+        if self.feature_incompat & !EXT2_FEATURE_INCOMPAT_FILETYPE != 0 || self.feature_ro_compat & !ro_compat_supported != 0 {
+            return Err(SahneError::NotSupported);
+        }
+        if !self.dirty {
+            self.device.set_mounted(true)?;
+            self.state &= !EXT2_VALID_FS;
+            self.mount_count = self.mount_count.wrapping_add(1);
+            self.write_mount_state()?;
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    // Süper bloğa bağlama durumunu yazar (s_mnt_count, s_state, s_lastcheck).
+    fn write_mount_state(&mut self) -> Result<(), SahneError> {
+        write_exact_at(&mut self.device, SUPERBLOCK_MOUNT_COUNT_OFFSET, &self.mount_count.to_le_bytes())?;
+        write_exact_at(&mut self.device, SUPERBLOCK_STATE_OFFSET, &self.state.to_le_bytes())?;
+        write_exact_at(&mut self.device, SUPERBLOCK_LAST_CHECK_OFFSET, &self.last_check.to_le_bytes())
+    }
+
+    // i_size 32 bittir; 2 GB üstü large_file özelliği ister.
+    fn check_file_size(&self, size: u64) -> Result<(), SahneError> {
+        let limit = if self.feature_ro_compat & EXT2_FEATURE_RO_COMPAT_LARGE_FILE != 0 { u32::MAX as u64 } else { i32::MAX as u64 };
+        if size > limit {
+            return Err(SahneError::InvalidParameter);
+        }
+        Ok(())
+    }
+
+    // Veriyi bloklara yazar (i-node'u güncellemez, çağıran yazar). Yazılan bayt sayısını döndürür.
+    fn write_data(&mut self, inode_number: u32, inode: &mut Inode, offset: u64, data: &[u8]) -> Result<usize, SahneError> {
+        let block_size = self.block_size as usize;
+        let group = (inode_number - 1) / self.inodes_per_group;
+        let mut goal = self.first_data_block + group * self.blocks_per_group; // İlk blok i-node'un grubunda
+        let mut written = 0;
+        while written < data.len() {
+            let position = offset + written as u64;
+            let index = (position / block_size as u64) as u32;
+            let offset_in_block = (position % block_size as u64) as usize;
+            let len = core::cmp::min(data.len() - written, block_size - offset_in_block);
+            let part = &data[written..written + len];
+
+            let (block, created) = match self.map_block_alloc(inode, index, goal) {
+                Ok(mapped) => mapped,
+                Err(err) if written > 0 && err == SahneError::OutOfMemory => return Ok(written),
+                Err(err) => return Err(err),
+            };
+            if created && len < block_size {
+                // Yeni bloğun yazılmayan kısmı sıfırlanır (eski verisi görünmemeli).
+                let mut buffer = zeroed_buffer(block_size)?;
+                buffer[offset_in_block..offset_in_block + len].copy_from_slice(part);
+                self.write_block_part(block, 0, &buffer)?;
+            } else {
+                self.write_block_part(block, offset_in_block, part)?;
+            }
+            goal = block + 1;
+            written += len;
+        }
+        Ok(written)
+    }
+
+    // Dosyanın `index`. bloğunu döndürür; ayrılmamışsa (gerekirse yoldaki dolaylı bloklarla birlikte) ayırır.
+    // İkinci değer veri bloğunun yeni ayrıldığını bildirir. i_block ve i_blocks `inode`da güncellenir.
+    fn map_block_alloc(&mut self, inode: &mut Inode, index: u32, goal: u32) -> Result<(u32, bool), SahneError> {
+        let (slot, depth, index) = self.locate_block(index)?;
+        let pointers_per_block = (self.block_size / 4) as u64;
+        let sectors_per_block = self.block_size / 512;
+
+        let mut i_block = inode.i_block;
+        let mut block = i_block[slot];
+        let mut created = false;
+        if block == 0 {
+            block = self.allocate_block(goal)?;
+            inode.i_blocks += sectors_per_block;
+            i_block[slot] = block;
+            inode.i_block = i_block;
+            if depth > 0 {
+                self.zero_block(block)?;
+            }
+            created = depth == 0;
+        }
+        for level in 0..depth {
+            let span = pointers_per_block.pow((depth - 1 - level) as u32);
+            let entry = (index / span % pointers_per_block) as usize;
+            let mut next = self.indirect_pointer(level, block, entry)?;
+            if next == 0 {
+                let data_block = level + 1 == depth;
+                next = self.allocate_block(if data_block { goal } else { block + 1 })?;
+                inode.i_blocks += sectors_per_block;
+                if !data_block {
+                    self.zero_block(next)?;
+                }
+                self.set_indirect_pointer(level, block, entry, next)?;
+                created = data_block;
+            }
+            block = next;
+        }
+        Ok((block, created))
+    }
+
+    // Dolaylı bloğa işaretçi yazar; blok önbellekteyse önbellek de güncellenir.
+    fn set_indirect_pointer(&mut self, level: usize, block: u32, slot: usize, value: u32) -> Result<(), SahneError> {
+        self.write_block_part(block, slot * 4, &value.to_le_bytes())?;
+        let (cached_block, data) = &mut self.block_map.get_mut().levels[level];
+        if *cached_block == block {
+            data[slot * 4..slot * 4 + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    // `size` baytından sonraki blokları serbest bırakır ve son kısmi bloğun kalanını sıfırlar.
+    fn free_blocks_from(&mut self, inode: &mut Inode, size: u64) -> Result<(), SahneError> {
+        let block_size = self.block_size as u64;
+        let first_freed = size.div_ceil(block_size); // Serbest bırakılacak ilk dosya bloğu
+        let mut freed = 0u32;
+        let mut i_block = inode.i_block;
+        let result = self.free_pointers_from(&mut i_block, first_freed, &mut freed);
+        inode.i_block = i_block;
+        // Bozuk bir imajda i_blocks eşlenen bloklardan az olabilir (fsck düzeltir); sayı sıfırın altına inmez.
+        inode.i_blocks = inode.i_blocks.saturating_sub(freed * (self.block_size / 512));
+        *self.block_map.get_mut() = BlockMapCache::new(); // Serbest kalan dolaylı bloklar önbellekte kalmasın
+        result?;
+
+        let offset_in_block = (size % block_size) as usize;
+        if offset_in_block != 0 {
+            let block = self.map_block(inode, (size / block_size) as u32)?;
+            if block != 0 {
+                let zeros = zeroed_buffer(self.block_size as usize - offset_in_block)?;
+                self.write_block_part(block, offset_in_block, &zeros)?;
+            }
+        }
+        Ok(())
+    }
+
+    // i_block'taki `first_freed`. ve sonraki dosya bloklarını serbest bırakır; `freed` serbest kalan blok
+    // sayısını (dolaylı bloklar dahil) toplar. Hata olursa o ana kadar yapılanlar `i_block`ta kalır.
+    fn free_pointers_from(&mut self, i_block: &mut [u32; 15], first_freed: u64, freed: &mut u32) -> Result<(), SahneError> {
+        let first_direct = first_freed.min(EXT2_NDIR_BLOCKS as u64) as usize;
+        for pointer in &mut i_block[first_direct..EXT2_NDIR_BLOCKS as usize] {
+            if *pointer != 0 {
+                self.free_block(*pointer)?;
+                *pointer = 0;
+                *freed += 1;
+            }
+        }
+        let pointers_per_block = self.block_size as u64 / 4;
+        let mut start = EXT2_NDIR_BLOCKS as u64; // Ağacın kapsadığı ilk dosya bloğu
+        let mut span = 1;
+        for depth in 1..=EXT2_MAX_INDIRECTION {
+            span *= pointers_per_block;
+            let slot = EXT2_IND_BLOCK + depth - 1;
+            if i_block[slot] != 0
+                && first_freed < start + span
+                && self.free_tree(i_block[slot], depth, first_freed.saturating_sub(start), freed)?
+            {
+                self.free_block(i_block[slot])?;
+                i_block[slot] = 0;
+                *freed += 1;
+            }
+            start += span;
+        }
+        Ok(())
+    }
+
+    // `block` kökündeki `depth` derinlikli ağaçta `from`. ve sonraki veri bloklarını (ve boşalan alt
+    // ağaçların dolaylı bloklarını) serbest bırakır. Ağaç tamamen boşaldıysa true döner; kökü çağıran
+    // serbest bırakır.
+    fn free_tree(&mut self, block: u32, depth: usize, from: u64, freed: &mut u32) -> Result<bool, SahneError> {
+        let mut pointers = self.read_block(block)?;
+        let span = (self.block_size as u64 / 4).pow(depth as u32 - 1);
+        let first_entry = (from / span) as usize;
+        let mut changed = false;
+        for entry in first_entry..pointers.len() / 4 {
+            let bytes = &mut pointers[entry * 4..entry * 4 + 4];
+            let child = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            if child == 0 {
+                continue;
+            }
+            let child_from = if entry == first_entry { from % span } else { 0 };
+            if depth == 1 || self.free_tree(child, depth - 1, child_from, freed)? {
+                self.free_block(child)?;
+                *freed += 1;
+                pointers[entry * 4..entry * 4 + 4].fill(0);
+                changed = true;
+            }
+        }
+        let empty = pointers.iter().all(|&byte| byte == 0);
+        if changed && !empty {
+            self.write_block_part(block, 0, &pointers)?;
+        }
+        Ok(empty)
+    }
+
+    // --- Dizin girişleri ---
+
+    // Dizine giriş ekler: boş yeri olan bir kayıt bölünür, yer yoksa dizine yeni blok eklenir.
+    fn add_entry(&mut self, dir_number: u32, name: &[u8], inode_number: u32, file_type: u8) -> Result<(), SahneError> {
+        let mut dir = self.read_dir_inode(dir_number)?;
+        let needed = dir_entry_len(name.len());
+        let block_size = self.block_size as usize;
+        let block_count = dir.i_size.div_ceil(self.block_size);
+        let mut buffer = zeroed_buffer(block_size)?;
+        let mut placed = false;
+        for index in 0..block_count {
+            let block = self.map_block(&dir, index)?;
+            if block == 0 {
+                continue;
+            }
+            self.read_block_part(block, 0, &mut buffer)?;
+            if let Some(offset) = find_free_slot(&buffer, needed)? {
+                let written = self.encode_entry(&mut buffer, offset, name, inode_number, file_type);
+                self.write_block_part(block, offset, &buffer[offset..offset + written])?;
+                placed = true;
+                break;
+            }
+        }
+        if !placed {
+            // Yeni blok: tek giriş bloğun tamamını kaplar.
+            let group = (dir_number - 1) / self.inodes_per_group;
+            let goal = self.first_data_block + group * self.blocks_per_group;
+            let (block, _) = self.map_block_alloc(&mut dir, block_count, goal)?;
+            buffer.fill(0);
+            self.encode_entry_at(&mut buffer, 0, block_size, name, inode_number, file_type);
+            self.write_block_part(block, 0, &buffer)?;
+            dir.i_size += self.block_size;
+        }
+        self.touch_dir(dir_number, &mut dir)
+    }
+
+    // Dizinden girişi siler: bloktaki önceki kayıt silinenin yerini de kaplayacak şekilde uzatılır;
+    // bloğun ilk kaydıysa i-node alanı sıfırlanır.
+    fn remove_entry(&mut self, dir_number: u32, name: &[u8]) -> Result<(), SahneError> {
+        let mut dir = self.read_dir_inode(dir_number)?;
+        let location = self.locate_entry(&dir, name)?.ok_or(SahneError::ResourceNotFound)?;
+        match location.previous {
+            Some((previous, previous_len)) => {
+                let merged = (previous_len + location.rec_len) as u16;
+                self.write_block_part(location.block, previous + 4, &merged.to_le_bytes())?;
+            }
+            None => self.write_block_part(location.block, location.offset, &0u32.to_le_bytes())?,
+        }
+        self.touch_dir(dir_number, &mut dir)
+    }
+
+    // Var olan girişin gösterdiği i-node'u (ve dosya türünü) yerinde değiştirir.
+    fn set_entry_inode(&mut self, dir_number: u32, name: &[u8], inode_number: u32, file_type: u8) -> Result<(), SahneError> {
+        let mut dir = self.read_dir_inode(dir_number)?;
+        let location = self.locate_entry(&dir, name)?.ok_or(SahneError::ResourceNotFound)?;
+        self.write_block_part(location.block, location.offset, &inode_number.to_le_bytes())?;
+        if self.feature_incompat & EXT2_FEATURE_INCOMPAT_FILETYPE != 0 {
+            self.write_block_part(location.block, location.offset + 7, &[file_type])?;
+        }
+        self.touch_dir(dir_number, &mut dir)
+    }
+
+    // Değişen dizinin zamanlarını günceller ve i-node'unu yazar.
+    fn touch_dir(&mut self, dir_number: u32, dir: &mut Inode) -> Result<(), SahneError> {
+        // Girişler doğrusal değiştirildiği için htree indeksi geçersiz olur; bayrak kaldırılır (e2fsck yeniden kurar).
+        dir.i_flags &= !EXT2_INDEX_FL;
+        let now = unix_time();
+        dir.i_mtime = now;
+        dir.i_ctime = now;
+        self.write_inode(dir_number, dir)
+    }
+
+    // Girişin bulunduğu bloğu ve kaydı bulur. Kayıt zinciri bozuksa InvalidParameter döner.
+    fn locate_entry(&self, dir: &Inode, name: &[u8]) -> Result<Option<EntryLocation>, SahneError> {
+        let block_size = self.block_size as usize;
+        let mut buffer = zeroed_buffer(block_size)?;
+        for index in 0..dir.i_size.div_ceil(self.block_size) {
+            let block = self.map_block(dir, index)?;
+            if block == 0 {
+                continue;
+            }
+            self.read_block_part(block, 0, &mut buffer)?;
+            let mut offset = 0;
+            let mut previous = None;
+            while offset < block_size {
+                let (inode, rec_len, name_len) = entry_header(&buffer, offset)?;
+                let entry_name = buffer.get(offset + DIR_ENTRY_HEADER_SIZE..offset + DIR_ENTRY_HEADER_SIZE + name_len);
+                if inode != 0 && entry_name == Some(name) {
+                    return Ok(Some(EntryLocation { block, offset, rec_len, previous }));
+                }
+                previous = Some((offset, rec_len));
+                offset += rec_len;
+            }
+        }
+        Ok(None)
+    }
+
+    // `offset`teki kaydı (find_free_slot'un bulduğu) yeni girişle doldurur. Kayıt kullanılıyorsa
+    // bölünür: eski giriş kendi uzunluğuna iner, yenisi kalan yeri alır. Değişen bayt sayısını döndürür.
+    fn encode_entry(&self, block: &mut [u8], offset: usize, name: &[u8], inode_number: u32, file_type: u8) -> usize {
+        let header = &block[offset..offset + DIR_ENTRY_HEADER_SIZE];
+        let existing_inode = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let rec_len = u16::from_le_bytes([header[4], header[5]]) as usize;
+        let (start, new_len) = if existing_inode == 0 {
+            (offset, rec_len)
         } else {
-             Ok(result as usize)
+            let used = dir_entry_len(block[offset + 6] as usize);
+            block[offset + 4..offset + 6].copy_from_slice(&(used as u16).to_le_bytes());
+            (offset + used, rec_len - used)
+        };
+        self.encode_entry_at(block, start, new_len, name, inode_number, file_type);
+        start + DIR_ENTRY_HEADER_SIZE + name.len() - offset
+    }
+
+    // `offset`e `rec_len` uzunluğunda bir giriş yazar.
+    fn encode_entry_at(&self, block: &mut [u8], offset: usize, rec_len: usize, name: &[u8], inode_number: u32, file_type: u8) {
+        let file_type = if self.feature_incompat & EXT2_FEATURE_INCOMPAT_FILETYPE != 0 { file_type } else { 0 };
+        block[offset..offset + 4].copy_from_slice(&inode_number.to_le_bytes());
+        block[offset + 4..offset + 6].copy_from_slice(&(rec_len as u16).to_le_bytes());
+        block[offset + 6] = name.len() as u8;
+        block[offset + 7] = file_type;
+        block[offset + DIR_ENTRY_HEADER_SIZE..offset + DIR_ENTRY_HEADER_SIZE + name.len()].copy_from_slice(name);
+    }
+
+    // Dizinde sadece "." ve ".." var mı?
+    fn is_empty_dir(&self, dir: &Inode) -> Result<bool, SahneError> {
+        Ok(self.list_directory(dir)?.iter().all(|entry| matches!(entry.name_bytes(), b"." | b"..")))
+    }
+
+    // `dir` veya üst dizinlerinden biri `ancestor` ise InvalidParameter döner (dizin kendi altına taşınamaz).
+    fn check_not_ancestor(&self, ancestor: u32, dir: u32) -> Result<(), SahneError> {
+        let mut current = dir;
+        // Bozuk bir dizin ağacındaki ".." döngüsü sınırlı adımda yakalanır.
+        for _ in 0..self.total_inode_count {
+            if current == ancestor {
+                return Err(SahneError::InvalidParameter);
+            }
+            if current == EXT2_ROOT_INODE {
+                return Ok(());
+            }
+            current = self.lookup(current, "..")?;
         }
-        // TODO: resource::write API'si çekirdek tarafında offset'i destekleyecek şekilde güncellenmeli.
-     }
+        Err(SahneError::InvalidParameter)
+    }
+
+    // --- i-node'lar ---
 
+    // i-node'un ilk 128 baytını (Inode) yazar; genişletilmiş alanlar korunur.
+    fn write_inode(&mut self, inode_number: u32, inode: &Inode) -> Result<(), SahneError> {
+        let (block, offset) = self.inode_location(inode_number)?;
+        self.write_block_part(block, offset, struct_bytes(inode))
+    }
+
+    // Yeni ayrılmış i-node'u yazar; i-node boyutunun geri kalanı sıfırlanır.
+    fn write_new_inode(&mut self, inode_number: u32, inode: &Inode) -> Result<(), SahneError> {
+        let (block, offset) = self.inode_location(inode_number)?;
+        let mut buffer = zeroed_buffer(self.inode_size as usize)?;
+        buffer[..mem::size_of::<Inode>()].copy_from_slice(struct_bytes(inode));
+        self.write_block_part(block, offset, &buffer)
+    }
+
+    // Yeni bir i-node oluşturup `parent` dizinine `name` adıyla bağlar. `init` i-node yazılmadan önce
+    // içeriğini hazırlar (örn. dizin bloğu). Hata olursa ayrılan her şey geri verilir.
+    fn create_inode<F>(&mut self, parent: u32, name: &str, mode: u16, init: F) -> Result<u32, SahneError>
+    where
+        F: FnOnce(&mut Self, u32, &mut Inode) -> Result<(), SahneError>,
+    {
+        check_entry_name(name)?;
+        let dir = self.read_dir_inode(parent)?;
+        if self.find_entry(&dir, name.as_bytes())?.is_some() {
+            return Err(SahneError::NamingError);
+        }
+
+        let number = self.allocate_inode(parent, mode & S_IFMT == S_IFDIR)?;
+        let now = unix_time();
+        let mut inode = empty_inode();
+        inode.i_mode = mode;
+        inode.i_links_count = 1;
+        inode.i_atime = now;
+        inode.i_ctime = now;
+        inode.i_mtime = now;
+        let result = init(self, number, &mut inode)
+            .and_then(|()| self.write_new_inode(number, &inode))
+            .and_then(|()| self.add_entry(parent, name.as_bytes(), number, file_type_of(&inode)));
+        if let Err(err) = result {
+            let _ = self.release_inode(number, &mut inode);
+            return Err(err);
+        }
+        Ok(number)
+    }
+
+    // i-node'un bağlantı sayısını `delta` kadar değiştirir.
+    fn adjust_links(&mut self, inode_number: u32, delta: i32) -> Result<(), SahneError> {
+        let mut inode = self.read_inode(inode_number)?;
+        inode.i_links_count = (inode.i_links_count as i32 + delta).clamp(0, EXT2_LINK_MAX as i32) as u16;
+        inode.i_ctime = unix_time();
+        self.write_inode(inode_number, &inode)
+    }
+
+    // Silinen bir girişin bağlantısını düşer; son bağlantıysa i-node serbest bırakılır.
+    fn drop_link(&mut self, inode_number: u32) -> Result<(), SahneError> {
+        let mut inode = self.read_inode(inode_number)?;
+        inode.i_links_count = inode.i_links_count.saturating_sub(1);
+        if inode.i_links_count == 0 {
+            return self.release_inode(inode_number, &mut inode);
+        }
+        inode.i_ctime = unix_time();
+        self.write_inode(inode_number, &inode)
+    }
+
+    // i-node'un bloklarını ve kendisini serbest bırakır (silme zamanı yazılır).
+    fn release_inode(&mut self, inode_number: u32, inode: &mut Inode) -> Result<(), SahneError> {
+        if !inode.is_fast_symlink() {
+            // Hızlı bağlantının i_block'u blok işaretçisi değil, hedefin kendisidir.
+            self.free_blocks_from(inode, 0)?;
+        }
+        inode.i_links_count = 0;
+        inode.i_size = 0;
+        // Silinmiş i-node'u dtime belirtir; i-node sayısından küçük bir değer e2fsck'e yetim listesi
+        // bağlantısı gibi görünür (saat okunamadığında bile).
+        inode.i_dtime = unix_time().max(self.total_inode_count);
+        self.write_inode(inode_number, inode)?;
+        self.free_inode(inode_number, inode.is_dir())
+    }
+
+    // --- Ayırma (bitmap'ler ve serbest sayılar) ---
+
+    // `goal` bloğundan başlayarak boş bir blok ayırır (bulunamazsa sonraki gruplara bakılır).
+    fn allocate_block(&mut self, goal: u32) -> Result<u32, SahneError> {
+        if self.free_blocks_count == 0 {
+            return Err(SahneError::OutOfMemory); // Disk dolu
+        }
+        let goal = goal.clamp(self.first_data_block, self.total_block_count - 1) - self.first_data_block;
+        let goal_group = goal / self.blocks_per_group;
+        for step in 0..=self.group_count {
+            let group = (goal_group + step) % self.group_count;
+            if self.group_descriptors[group as usize].bg_free_blocks_count == 0 {
+                continue;
+            }
+            // İlk turda hedefin kendisinden, sonra grubun başından aranır.
+            let start = if step == 0 { goal % self.blocks_per_group } else { 0 };
+            let bitmap = self.group_descriptors[group as usize].bg_block_bitmap;
+            if let Some(bit) = self.take_free_bit(bitmap, start, self.blocks_in_group(group))? {
+                let descriptor = &mut self.group_descriptors[group as usize];
+                descriptor.bg_free_blocks_count -= 1;
+                self.free_blocks_count -= 1;
+                self.write_group_counts(group)?;
+                return Ok(self.first_data_block + group * self.blocks_per_group + bit);
+            }
+        }
+        Err(SahneError::OutOfMemory)
+    }
+
+    fn free_block(&mut self, block: u32) -> Result<(), SahneError> {
+        if block < self.first_data_block || block >= self.total_block_count {
+            return Err(SahneError::InvalidParameter);
+        }
+        let relative = block - self.first_data_block;
+        let group = relative / self.blocks_per_group;
+        let bitmap = self.group_descriptors[group as usize].bg_block_bitmap;
+        self.clear_bit(bitmap, relative % self.blocks_per_group)?;
+        let descriptor = &mut self.group_descriptors[group as usize];
+        descriptor.bg_free_blocks_count += 1;
+        self.free_blocks_count += 1;
+        self.write_group_counts(group)
+    }
+
+    // Yeni bir i-node ayırır; önce `parent` i-node'unun grubuna bakılır.
+    fn allocate_inode(&mut self, parent: u32, directory: bool) -> Result<u32, SahneError> {
+        if self.free_inodes_count == 0 {
+            return Err(SahneError::OutOfMemory);
+        }
+        let parent_group = (parent - 1) / self.inodes_per_group;
+        for step in 0..self.group_count {
+            let group = (parent_group + step) % self.group_count;
+            if self.group_descriptors[group as usize].bg_free_inodes_count == 0 {
+                continue;
+            }
+            // Ayrılmış i-node'lar (first_inode'dan küçükler) atlanır.
+            let first_in_group = group * self.inodes_per_group + 1;
+            let start = self.first_inode.saturating_sub(first_in_group);
+            if start >= self.inodes_per_group {
+                continue;
+            }
+            let bitmap = self.group_descriptors[group as usize].bg_inode_bitmap;
+            if let Some(bit) = self.take_free_bit(bitmap, start, self.inodes_per_group)? {
+                let descriptor = &mut self.group_descriptors[group as usize];
+                descriptor.bg_free_inodes_count -= 1;
+                if directory {
+                    descriptor.bg_used_dirs_count += 1;
+                }
+                self.free_inodes_count -= 1;
+                self.write_group_counts(group)?;
+                return Ok(first_in_group + bit);
+            }
+        }
+        Err(SahneError::OutOfMemory)
+    }
+
+    fn free_inode(&mut self, inode_number: u32, directory: bool) -> Result<(), SahneError> {
+        if inode_number < self.first_inode || inode_number > self.total_inode_count {
+            return Err(SahneError::InvalidParameter);
+        }
+        let group = (inode_number - 1) / self.inodes_per_group;
+        let bitmap = self.group_descriptors[group as usize].bg_inode_bitmap;
+        self.clear_bit(bitmap, (inode_number - 1) % self.inodes_per_group)?;
+        let descriptor = &mut self.group_descriptors[group as usize];
+        descriptor.bg_free_inodes_count += 1;
+        if directory {
+            descriptor.bg_used_dirs_count -= 1;
+        }
+        self.free_inodes_count += 1;
+        self.write_group_counts(group)
+    }
+
+    // Gruptaki blok sayısı (son grup kısa olabilir).
+    fn blocks_in_group(&self, group: u32) -> u32 {
+        let start = group * self.blocks_per_group;
+        (self.total_block_count - self.first_data_block - start).min(self.blocks_per_group)
+    }
+
+    // Bitmap bloğunu önbelleğe alır (zaten oradaysa okumaz).
+    fn load_bitmap(&mut self, bitmap_block: u32) -> Result<(), SahneError> {
+        if self.bitmap.0 != bitmap_block {
+            let data = self.read_block(bitmap_block)?;
+            self.bitmap = (bitmap_block, data);
+        }
+        Ok(())
+    }
+
+    // Bitmap'te [start, limit) aralığında boş bit arar; bulursa işaretler ve diske yazar.
+    fn take_free_bit(&mut self, bitmap_block: u32, start: u32, limit: u32) -> Result<Option<u32>, SahneError> {
+        self.load_bitmap(bitmap_block)?;
+        let limit = limit.min(self.block_size * 8);
+        let Some(bit) = (start..limit).find(|&bit| self.bitmap.1[bit as usize / 8] & (1 << (bit % 8)) == 0) else {
+            return Ok(None);
+        };
+        let byte = bit as usize / 8;
+        let value = self.bitmap.1[byte] | 1 << (bit % 8);
+        self.write_block_part(bitmap_block, byte, &[value])?;
+        self.bitmap.1[byte] = value;
+        Ok(Some(bit))
+    }
+
+    fn clear_bit(&mut self, bitmap_block: u32, bit: u32) -> Result<(), SahneError> {
+        self.load_bitmap(bitmap_block)?;
+        let byte = bit as usize / 8;
+        if byte >= self.bitmap.1.len() || self.bitmap.1[byte] & (1 << (bit % 8)) == 0 {
+            return Err(SahneError::InvalidParameter); // Zaten boş: çift serbest bırakma
+        }
+        let value = self.bitmap.1[byte] & !(1 << (bit % 8));
+        self.write_block_part(bitmap_block, byte, &[value])?;
+        self.bitmap.1[byte] = value;
+        Ok(())
+    }
+
+    // Grup tanımlayıcısını ve süper bloktaki serbest sayıları diske yazar.
+    fn write_group_counts(&mut self, group: u32) -> Result<(), SahneError> {
+        let descriptor = self.group_descriptors[group as usize];
+        let descriptor_size = mem::size_of::<GroupDescriptor>();
+        // Tablo birden fazla bloğa yayılabilir; tanımlayıcılar blok sınırını aşmaz.
+        let offset = group as usize * descriptor_size;
+        let block = self.first_data_block + 1 + (offset / self.block_size as usize) as u32;
+        self.write_block_part(block, offset % self.block_size as usize, struct_bytes(&descriptor))?;
+        let mut counts = [0u8; 8];
+        counts[..4].copy_from_slice(&self.free_blocks_count.to_le_bytes());
+        counts[4..].copy_from_slice(&self.free_inodes_count.to_le_bytes());
+        write_exact_at(&mut self.device, SUPERBLOCK_FREE_COUNTS_OFFSET, &counts)
+    }
+
+    // --- Blok yazma ---
+
+    /// Bloğun `offset_in_block` baytından itibaren `buffer`ı yazar (blok sınırını aşmamalı).
+    fn write_block_part(&mut self, fs_block: u32, offset_in_block: usize, buffer: &[u8]) -> Result<(), SahneError> {
+        if fs_block >= self.total_block_count || offset_in_block + buffer.len() > self.block_size as usize {
+            return Err(SahneError::InvalidParameter);
+        }
+        let offset = self.fs_block_to_device_offset(fs_block) + offset_in_block as u64;
+        write_exact_at(&mut self.device, offset, buffer)
+    }
+
+    fn zero_block(&mut self, fs_block: u32) -> Result<(), SahneError> {
+        let zeros = zeroed_buffer(self.block_size as usize)?;
+        self.write_block_part(fs_block, 0, &zeros)
+    }
 }
 
+// Cihazdan tam olarak buffer.len() bayt okur; eksik okuma InvalidOperation'dır.
+fn read_exact_at<D: BlockDevice>(device: &D, offset: u64, buffer: &mut [u8]) -> Result<(), SahneError> {
+    let bytes_read = device.read_at(offset, buffer)?;
+    if bytes_read != buffer.len() {
+        return Err(SahneError::InvalidOperation); // Yeterli veri okunamadı
+    }
+    Ok(())
+}
+
+// Cihaza tam olarak buffer.len() bayt yazar; eksik yazma InvalidOperation'dır.
+fn write_exact_at<D: BlockDevice>(device: &mut D, offset: u64, buffer: &[u8]) -> Result<(), SahneError> {
+    if device.write_at(offset, buffer)? != buffer.len() {
+        return Err(SahneError::InvalidOperation);
+    }
+    Ok(())
+}
+
+// Paketlenmiş bir yapının bayt görünümü (diske yazmak için).
+fn struct_bytes<T: Copy>(value: &T) -> &[u8] {
+    unsafe { core::slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>()) }
+}
+
+// Tüm alanları sıfır olan bir i-node.
+fn empty_inode() -> Inode {
+    // Inode sadece tamsayı alanlarından oluşur; sıfır geçerli bir değerdir.
+    unsafe { mem::zeroed() }
+}
+
+// Duvar saati (Unix zamanı, saniye). Saat okunamazsa 0.
+fn unix_time() -> u32 {
+    kernel::get_wall_time().map_or(0, |ns| (ns / 1_000_000_000) as u32)
+}
+
+// Dizin girişi adı geçerli mi? (boş değil, en fazla 255 bayt, '/' ve NUL içermez, "." ve ".." değil)
+fn check_entry_name(name: &str) -> Result<(), SahneError> {
+    let bytes = name.as_bytes();
+    if bytes.is_empty() || bytes.len() > 255 || bytes.contains(&b'/') || bytes.contains(&0) || name == "." || name == ".." {
+        return Err(SahneError::InvalidParameter);
+    }
+    Ok(())
+}
 
-// Root dizinine erişmek için kolaylık fonksiyonu
-impl ExtFilesystem {
-     pub fn root_directory(&self) -> Result<Inode, SahneError> {
-         self.read_inode(2) // EXT2'de root i-node her zaman 2'dir.
-     }
-}
\ No newline at end of file
+// i-node türüne karşılık gelen dizin girişi dosya türü.
+fn file_type_of(inode: &Inode) -> u8 {
+    match inode.i_mode & S_IFMT {
+        S_IFREG => EXT2_FT_REG_FILE,
+        S_IFDIR => EXT2_FT_DIR,
+        S_IFCHR => EXT2_FT_CHRDEV,
+        S_IFBLK => EXT2_FT_BLKDEV,
+        S_IFIFO => EXT2_FT_FIFO,
+        S_IFSOCK => EXT2_FT_SOCK,
+        S_IFLNK => EXT2_FT_SYMLINK,
+        _ => EXT2_FT_UNKNOWN,
+    }
+}
+
+// Adı `name_len` bayt olan bir dizin girişinin kapladığı en az yer (4 bayta hizalı).
+fn dir_entry_len(name_len: usize) -> usize {
+    (DIR_ENTRY_HEADER_SIZE + name_len + 3) & !3
+}
+
+// Dizin bloğunda `needed` baytlık yeni giriş için yeri olan kaydın ofsetini bulur: ya boş bir kayıt
+// (inode 0) ya da sonunda yeterli boşluk olan bir kayıt. Bozuk kayıt zincirinde InvalidParameter döner
+// (bloğa yazılmaz).
+fn find_free_slot(block: &[u8], needed: usize) -> Result<Option<usize>, SahneError> {
+    let mut offset = 0;
+    while offset < block.len() {
+        let (inode, rec_len, name_len) = entry_header(block, offset)?;
+        let used = if inode == 0 { 0 } else { dir_entry_len(name_len) };
+        if rec_len - used >= needed {
+            return Ok(Some(offset));
+        }
+        offset += rec_len;
+    }
+    Ok(None)
+}
+
+// `offset`teki kaydın başlığını doğrulayarak okur: (inode, rec_len, name_len).
+fn entry_header(block: &[u8], offset: usize) -> Result<(u32, usize, usize), SahneError> {
+    let header = block.get(offset..offset + DIR_ENTRY_HEADER_SIZE).ok_or(SahneError::InvalidParameter)?;
+    let inode = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    let rec_len = u16::from_le_bytes([header[4], header[5]]) as usize;
+    let name_len = header[6] as usize;
+    if rec_len < DIR_ENTRY_HEADER_SIZE
+        || !rec_len.is_multiple_of(4)
+        || offset + rec_len > block.len()
+        || (inode != 0 && dir_entry_len(name_len) > rec_len)
+    {
+        return Err(SahneError::InvalidParameter);
+    }
+    Ok((inode, rec_len, name_len))
+}
+
+// Bir dizin bloğundaki girişleri parse eder.
+// Girişler diskten geldiği için her alan blok sınırlarına göre doğrulanır.
+fn parse_directory_block(block: &[u8], entries: &mut Vec<DirectoryEntry>) -> Result<(), SahneError> {
+    let mut offset = 0;
+    while offset + DIR_ENTRY_HEADER_SIZE <= block.len() {
+        let header = &block[offset..offset + DIR_ENTRY_HEADER_SIZE];
+        let inode = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let rec_len = u16::from_le_bytes([header[4], header[5]]);
+        let name_len = header[6];
+        let file_type = header[7];
+
+        // rec_len 0 ise sonsuz döngüyü önle; blok dışına taşan kayıt bozuk yapıdır.
+        if (rec_len as usize) < DIR_ENTRY_HEADER_SIZE || offset + rec_len as usize > block.len() {
+            break;
+        }
+        // Silinmiş girişler (inode 0) atlanır ama rec_len kadar ilerlenir.
+        if inode != 0 && DIR_ENTRY_HEADER_SIZE + name_len as usize <= rec_len as usize {
+            let name_start = offset + DIR_ENTRY_HEADER_SIZE;
+            let mut entry = DirectoryEntry {
+                inode,
+                rec_len,
+                name_len,
+                file_type,
+                name: [0u8; 255],
+            };
+            entry.name[..name_len as usize].copy_from_slice(&block[name_start..name_start + name_len as usize]);
+
+            entries.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+            entries.push(entry);
+        }
+
+        // Bir sonraki girişe geç
+        offset += rec_len as usize;
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    // fixtures/make_fixtures.sh ile üretilen 1 KB bloklu EXT2 imajı.
+    pub(super) static IMAGE: &[u8] = include_bytes!("fixtures/ext2_small.img");
+    static SBXE: &[u8] = include_bytes!("fixtures/hello.sbxe");
+
+    fn mount_image() -> ExtFilesystem<&'static [u8]> {
+        ExtFilesystem::mount(IMAGE).expect("test imajı bağlanamadı")
+    }
+
+    fn find<D: BlockDevice>(fs: &ExtFilesystem<D>, dir: &Inode, name: &str) -> Option<u32> {
+        fs.list_directory(dir)
+            .unwrap()
+            .iter()
+            .find(|entry| entry.name_bytes() == name.as_bytes())
+            .map(|entry| entry.inode)
+    }
+
+    #[test]
+    fn mounts_fixture_image() {
+        let fs = mount_image();
+        assert_eq!(fs.block_size(), 1024);
+        assert_eq!(fs.inode_size, 256);
+        assert_eq!(fs.group_count, 1);
+    }
+
+    #[test]
+    fn root_directory_lists_entries() {
+        let fs = mount_image();
+        let root = fs.root_directory().unwrap();
+        assert!(root.is_dir());
+        let entries = fs.list_directory(&root).unwrap();
+        let names: Vec<&[u8]> = entries.iter().map(|entry| entry.name_bytes()).collect();
+        for expected in [&b"."[..], b"..", b"lost+found", b"bin", b"etc", b"hello.txt"] {
+            assert!(names.contains(&expected), "{:?} bulunamadı", core::str::from_utf8(expected));
+        }
+    }
+
+    #[test]
+    fn reads_small_file() {
+        let fs = mount_image();
+        let root = fs.root_directory().unwrap();
+        let inode = fs.read_inode(find(&fs, &root, "hello.txt").unwrap()).unwrap();
+        assert!(inode.is_file());
+        let mut buffer = [0u8; 64];
+        let read = fs.read_file(&inode, &mut buffer, 0).unwrap();
+        assert_eq!(&buffer[..read], b"Merhaba SahneBox!\n");
+
+        // Dosya ortasından ve sonundan okuma
+        assert_eq!(fs.read_file(&inode, &mut buffer[..7], 8).unwrap(), 7);
+        assert_eq!(&buffer[..7], b"SahneBo");
+        assert_eq!(fs.read_file(&inode, &mut buffer, 100).unwrap(), 0);
+    }
+
+    #[test]
+    fn reads_file_in_subdirectory() {
+        let fs = mount_image();
+        let root = fs.root_directory().unwrap();
+        let bin = fs.read_inode(find(&fs, &root, "bin").unwrap()).unwrap();
+        let inode = fs.read_inode(find(&fs, &bin, "hello.sbxe").unwrap()).unwrap();
+        let mut buffer = vec![0u8; inode.i_size as usize];
+        assert_eq!(fs.read_file(&inode, &mut buffer, 0).unwrap(), SBXE.len());
+        assert_eq!(&buffer[..], SBXE);
+    }
+
+    // fixtures/make_fixtures.sh: sparse.bin'in dolu blokları ve bayt deseni.
+    fn sparse_block(block: usize) -> Vec<u8> {
+        (0..1024).map(|i| ((block * 7 + i) % 251) as u8).collect()
+    }
+
+    #[test]
+    fn reads_sparse_file_through_indirect_blocks() {
+        let fs = mount_image();
+        let root = fs.root_directory().unwrap();
+        let inode = fs.read_inode(find(&fs, &root, "sparse.bin").unwrap()).unwrap();
+        assert_eq!({ inode.i_size }, 65811 * 1024);
+
+        let mut buffer = vec![0u8; 20 * 1024];
+        assert_eq!(fs.read_file(&inode, &mut buffer, 0).unwrap(), buffer.len());
+        for block in 0..20 {
+            assert_eq!(buffer[block * 1024..(block + 1) * 1024], sparse_block(block)[..], "blok {}", block);
+        }
+
+        // Çift ve üç dolaylı bloklar; bloklar arasındaki boşluklar sıfır okunur.
+        let mut block = vec![0u8; 1024];
+        for (index, filled) in [(300, true), (65810, true), (20, false), (299, false), (5000, false), (65809, false)] {
+            assert_eq!(fs.read_file(&inode, &mut block, index * 1024).unwrap(), 1024);
+            let expected = if filled { sparse_block(index) } else { vec![0u8; 1024] };
+            assert_eq!(block, expected, "blok {}", index);
+        }
+        assert_eq!(fs.read_file(&inode, &mut block, 65811 * 1024).unwrap(), 0);
+    }
+
+    // Okuma isteklerini sayan bellek imajı.
+    struct CountingDevice {
+        image: &'static [u8],
+        reads: core::cell::Cell<usize>,
+    }
+
+    impl BlockDevice for CountingDevice {
+        fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<usize, SahneError> {
+            self.reads.set(self.reads.get() + 1);
+            self.image.read_at(offset, buffer)
+        }
+    }
+
+    #[test]
+    fn sequential_reads_reuse_cached_indirect_blocks() {
+        let fs = ExtFilesystem::mount(CountingDevice { image: IMAGE, reads: core::cell::Cell::new(0) }).unwrap();
+        let root = fs.root_directory().unwrap();
+        let inode = fs.read_inode(find(&fs, &root, "sparse.bin").unwrap()).unwrap();
+
+        // Tek dolaylı bloklar (12-19), 256 baytlık parçalarla: her parça bir okuma, dolaylı blok bir kez.
+        fs.device.reads.set(0);
+        let mut chunk = [0u8; 256];
+        for offset in (12 * 1024..20 * 1024).step_by(chunk.len()) {
+            assert_eq!(fs.read_file(&inode, &mut chunk, offset).unwrap(), chunk.len());
+            assert_eq!(chunk[..], sparse_block(offset / 1024)[offset % 1024..offset % 1024 + 256]);
+        }
+        assert_eq!(fs.device.reads.get(), 32 + 1);
+
+        // Üç dolaylı blok: ilk okumada üç işaretçi bloğu, sonrakilerde sadece veri.
+        fs.device.reads.set(0);
+        for offset in (65810 * 1024..65811 * 1024).step_by(chunk.len()) {
+            fs.read_file(&inode, &mut chunk, offset).unwrap();
+        }
+        assert_eq!(fs.device.reads.get(), 4 + 3);
+    }
+
+    #[test]
+    fn lists_directory_spanning_indirect_block() {
+        let fs = mount_image();
+        let root = fs.root_directory().unwrap();
+        let links = fs.read_inode(find(&fs, &root, "links").unwrap()).unwrap();
+        assert!({ links.i_size } > 12 * 1024); // Dizin dolaylı bloğa taşıyor
+        let hello = find(&fs, &root, "hello.txt").unwrap();
+        let entries = fs.list_directory(&links).unwrap();
+        assert_eq!(entries.len(), 2 + 64);
+        for i in 1..=64 {
+            let name = alloc::format!("link{:02}-{:0200}", i, 0);
+            assert!(entries.iter().any(|entry| entry.name_bytes() == name.as_bytes() && entry.inode == hello), "{} bulunamadı", i);
+        }
+    }
+
+    // İmajı host'taki `e2fsck -fn` ile denetler. Host testleri e2fsprogs gerektirir; e2fsck
+    // bulunamazsa test başarısız olur (denetim sessizce atlanmaz).
+    pub(super) fn assert_fsck_clean(image: &[u8], tag: &str) {
+        let path = std::env::temp_dir().join(alloc::format!("sahnebox-ext2-{}-{}.img", std::process::id(), tag));
+        std::fs::write(&path, image).unwrap();
+        let output = ["e2fsck", "/sbin/e2fsck", "/usr/sbin/e2fsck"]
+            .iter()
+            .find_map(|program| std::process::Command::new(program).arg("-fn").arg(&path).output().ok());
+        let _ = std::fs::remove_file(&path);
+        let output = output.unwrap_or_else(|| panic!("e2fsck bulunamadı; {} imajını denetlemek için e2fsprogs kurulmalı", tag));
+        // -n ile özet sayılarındaki hatalar çıkış kodunu değiştirmez; her soru bir hatadır.
+        let report = std::string::String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success() && !report.contains("Fix? no"), "e2fsck hata buldu ({}):\n{}", tag, report);
+    }
+
+    pub(super) fn mount_writable() -> ExtFilesystem<Vec<u8>> {
+        ExtFilesystem::mount(IMAGE.to_vec()).unwrap()
+    }
+
+    fn read_all<D: BlockDevice>(fs: &ExtFilesystem<D>, inode_number: u32) -> Vec<u8> {
+        let inode = fs.read_inode(inode_number).unwrap();
+        let mut data = vec![0u8; inode.i_size as usize];
+        assert_eq!(fs.read_file(&inode, &mut data, 0).unwrap(), data.len());
+        data
+    }
+
+    #[test]
+    fn creates_writes_and_appends_files() {
+        let mut fs = mount_writable();
+        let free_blocks = fs.free_blocks_count;
+        let free_inodes = fs.free_inodes_count;
+        let root = fs.root_directory().unwrap();
+        let etc = find(&fs, &root, "etc").unwrap();
+
+        let list = fs.create_file(etc, "installed.list").unwrap();
+        assert_eq!(fs.create_file(etc, "installed.list"), Err(SahneError::NamingError));
+        assert_eq!(fs.create_file(etc, "a/b"), Err(SahneError::InvalidParameter));
+        assert_eq!(fs.create_file(list, "x"), Err(SahneError::InvalidOperation)); // Dizin değil
+        assert_eq!(fs.append_file(list, b"hello 1.0.0\n").unwrap(), 12);
+        assert_eq!(fs.append_file(list, b"editor 0.3.1\n").unwrap(), 13);
+        assert_eq!(read_all(&fs, list), b"hello 1.0.0\neditor 0.3.1\n");
+        let etc_inode = fs.read_inode(etc).unwrap();
+        assert_eq!(find(&fs, &etc_inode, "installed.list"), Some(list));
+
+        // Çift dolaylı bloğa uzanan büyük dosya: 12 doğrudan + 256 tek dolaylı + 2 çift dolaylı blok.
+        let big = fs.create_file(EXT2_ROOT_INODE, "big.bin").unwrap();
+        let data: Vec<u8> = (0..270 * 1024).map(|i| (i % 253) as u8).collect();
+        assert_eq!(fs.write_file(big, 0, &data[..100 * 1024]), Ok(100 * 1024));
+        assert_eq!(read_all(&fs, big), &data[..100 * 1024]);
+        // Orta bloktan başlayan üzerine yazma
+        assert_eq!(fs.write_file(big, 5000, &[0xAA; 3000]), Ok(3000));
+        let content = read_all(&fs, big);
+        assert!(content[5000..8000].iter().all(|&byte| byte == 0xAA));
+        assert_eq!(content[8000..], data[8000..100 * 1024]);
+
+        // i_blocks 512 baytlık birimlerdir: 100 veri bloğu + 1 tek dolaylı blok.
+        let inode = fs.read_inode(big).unwrap();
+        assert_eq!({ inode.i_blocks }, (100 + 1) * 2);
+        assert_eq!(fs.free_blocks_count, free_blocks - 1 - 101); // installed.list için bir blok
+        assert_eq!(fs.free_inodes_count, free_inodes - 2);
+
+        // Seyrek yazma: üç dolaylı bloğa tek blok, aradaki boşluk sıfır okunur.
+        let far = 70_000 * 1024;
+        assert_eq!(fs.write_file(big, far, b"son"), Ok(3));
+        let inode = fs.read_inode(big).unwrap();
+        assert_eq!({ inode.i_size }, far as u32 + 3);
+        let mut buffer = [0xFFu8; 8];
+        assert_eq!(fs.read_file(&inode, &mut buffer, far as usize - 5).unwrap(), 8);
+        assert_eq!(&buffer, b"\0\0\0\0\0son");
+
+        // Bağlama bilgileri diske yazıldı: yeniden bağlanan imaj aynı sayıları görür.
+        let image = fs.device;
+        let remounted = ExtFilesystem::mount(&image[..]).unwrap();
+        assert_eq!(remounted.free_blocks_count, free_blocks - 102 - 4); // + 1 veri, 3 dolaylı blok
+        assert_eq!(read_all(&remounted, list), b"hello 1.0.0\neditor 0.3.1\n");
+        assert_fsck_clean(&image, "create");
+    }
+
+    #[test]
+    fn truncate_frees_indirect_blocks() {
+        let mut fs = mount_writable();
+        let root = fs.root_directory().unwrap();
+        let sparse = find(&fs, &root, "sparse.bin").unwrap();
+        let free_blocks = fs.free_blocks_count;
+
+        // sparse.bin: 22 veri + 6 dolaylı blok. 10 KB + 100 bayta kısaltınca 11 veri bloğu kalır.
+        fs.truncate_file(sparse, 10 * 1024 + 100).unwrap();
+        assert_eq!(fs.free_blocks_count, free_blocks + 11 + 6);
+        let inode = fs.read_inode(sparse).unwrap();
+        assert_eq!({ inode.i_blocks }, 11 * 2);
+        let content = read_all(&fs, sparse);
+        assert_eq!(content[..1024], sparse_block(0)[..]);
+        assert_eq!(content[10 * 1024..], sparse_block(10)[..100]);
+
+        // Büyütme seyrek kalır; kısaltmada kesilen kuyruk yeniden görünmez.
+        fs.truncate_file(sparse, 20 * 1024).unwrap();
+        assert_eq!(fs.free_blocks_count, free_blocks + 17);
+        let content = read_all(&fs, sparse);
+        assert!(content[10 * 1024 + 100..].iter().all(|&byte| byte == 0));
+
+        // Tek dolaylı ağacın bir kısmını bırakan kısaltma
+        fs.write_file(sparse, 30 * 1024, &[1; 2048]).unwrap();
+        fs.truncate_file(sparse, 31 * 1024).unwrap();
+        assert_eq!(read_all(&fs, sparse)[30 * 1024..], [1; 1024]);
+        fs.truncate_file(sparse, 0).unwrap();
+        assert_eq!(fs.free_blocks_count, free_blocks + 28);
+        assert_eq!({ fs.read_inode(sparse).unwrap().i_blocks }, 0);
+        assert_fsck_clean(&fs.device, "truncate");
+    }
+
+    #[test]
+    fn truncate_tolerates_understated_block_count() {
+        let mut fs = mount_writable();
+        let hello = fs.lookup(EXT2_ROOT_INODE, "hello.txt").unwrap();
+        let free_blocks = fs.free_blocks_count;
+        let mut inode = fs.read_inode(hello).unwrap();
+        inode.i_blocks = 0; // Bozuk imaj: eşlenen bir blok var ama sayılmamış
+        fs.write_inode(hello, &inode).unwrap();
+
+        fs.truncate_file(hello, 0).unwrap();
+        assert_eq!({ fs.read_inode(hello).unwrap().i_blocks }, 0);
+        assert_eq!(fs.free_blocks_count, free_blocks + 1);
+    }
+
+    #[test]
+    fn full_disk_and_read_only_devices_are_reported() {
+        let mut fs = mount_writable();
+        let file = fs.create_file(EXT2_ROOT_INODE, "fill").unwrap();
+        let free = fs.free_blocks_count as usize;
+        let data = vec![7u8; (free + 8) * 1024];
+        // Dolaylı bloklar da yer kaplar; sığan kadarı yazılır.
+        let written = fs.write_file(file, 0, &data).unwrap();
+        assert!(written < data.len() && written >= (free - 2) * 1024, "{} bayt yazıldı", written);
+        assert_eq!(fs.free_blocks_count, 0);
+        assert_eq!(fs.write_file(file, written as u64, b"x"), Err(SahneError::OutOfMemory));
+        assert_eq!(read_all(&fs, file).len(), written);
+        assert_fsck_clean(&fs.device, "full");
+
+        let mut read_only = mount_image();
+        assert_eq!(read_only.create_file(EXT2_ROOT_INODE, "x"), Err(SahneError::PermissionDenied));
+    }
+
+    // Bağlama durumu bildirimlerini kaydeden yazılabilir imaj.
+    struct MountTrackingDevice {
+        image: Vec<u8>,
+        mounted: Vec<bool>,
+    }
+
+    impl BlockDevice for MountTrackingDevice {
+        fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<usize, SahneError> {
+            self.image.read_at(offset, buffer)
+        }
+
+        fn write_at(&mut self, offset: u64, buffer: &[u8]) -> Result<usize, SahneError> {
+            self.image.write_at(offset, buffer)
+        }
+
+        fn set_mounted(&mut self, mounted: bool) -> Result<(), SahneError> {
+            self.mounted.push(mounted);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn first_write_and_unmount_are_reported_to_device() {
+        let mut fs = ExtFilesystem::mount(MountTrackingDevice { image: IMAGE.to_vec(), mounted: Vec::new() }).unwrap();
+        fs.create_file(EXT2_ROOT_INODE, "a").unwrap();
+        fs.create_file(EXT2_ROOT_INODE, "b").unwrap();
+        assert_eq!(fs.device.mounted, [true]);
+        let device = fs.unmount().unwrap();
+        assert_eq!(device.mounted, [true, false]);
+
+        // Hiç yazılmayan bağlama bildirilmez.
+        let fs = ExtFilesystem::mount(MountTrackingDevice { image: device.image, mounted: Vec::new() }).unwrap();
+        assert!(fs.unmount().unwrap().mounted.is_empty());
+    }
+
+    // fixtures/ext2_groups.blocks'u bellekte tam imaja açar (35 grup, tanımlayıcı tablosu iki blok).
+    fn groups_image() -> Vec<u8> {
+        let packed: &[u8] = include_bytes!("fixtures/ext2_groups.blocks");
+        let block_count = u32::from_le_bytes(packed[..4].try_into().unwrap()) as usize;
+        let mut image = vec![0u8; block_count * 1024];
+        for record in packed[4..].chunks_exact(4 + 1024) {
+            let block = u32::from_le_bytes(record[..4].try_into().unwrap()) as usize;
+            image[block * 1024..(block + 1) * 1024].copy_from_slice(&record[4..]);
+        }
+        image
+    }
+
+    #[test]
+    fn writes_descriptors_past_first_table_block() {
+        let original = groups_image();
+        let mut fs = ExtFilesystem::mount(original.clone()).unwrap();
+        assert_eq!(fs.group_count, 35);
+        let last = fs.group_count - 1;
+        let free_in_last = fs.group_descriptors[last as usize].bg_free_blocks_count;
+        // Son grubun tanımlayıcısı ikinci tablo bloğunda, grup 2'ninkiyle aynı offset'tedir.
+        let group2 = fs.group_descriptors[2];
+        let goal = fs.first_data_block + last * fs.blocks_per_group;
+        let block = fs.allocate_block(goal).unwrap();
+        assert!(block >= goal);
+
+        let mut fs = ExtFilesystem::mount(fs.device).unwrap();
+        assert_eq!({ fs.group_descriptors[last as usize].bg_free_blocks_count }, free_in_last - 1);
+        assert_eq!(struct_bytes(&fs.group_descriptors[2]), struct_bytes(&group2));
+        fs.free_block(block).unwrap();
+        assert_eq!(fs.device, original);
+        assert_fsck_clean(&fs.device, "groups");
+    }
+
+    #[test]
+    fn mkdir_and_rmdir_maintain_link_counts() {
+        let mut fs = mount_writable();
+        let free_blocks = fs.free_blocks_count;
+        let free_inodes = fs.free_inodes_count;
+        let root_links = fs.root_directory().unwrap().i_links_count;
+
+        let usr = fs.mkdir(EXT2_ROOT_INODE, "usr").unwrap();
+        let share = fs.mkdir(usr, "share").unwrap();
+        assert_eq!(fs.mkdir(EXT2_ROOT_INODE, "usr"), Err(SahneError::NamingError));
+        assert_eq!({ fs.root_directory().unwrap().i_links_count }, root_links + 1);
+        assert_eq!({ fs.read_inode(usr).unwrap().i_links_count }, 3); // Üst dizindeki giriş, "." ve share/..
+        assert_eq!({ fs.read_inode(share).unwrap().i_links_count }, 2);
+        assert_eq!(fs.lookup(share, "."), Ok(share));
+        assert_eq!(fs.lookup(share, ".."), Ok(usr));
+        assert_eq!(fs.free_blocks_count, free_blocks - 2);
+        assert_eq!(fs.free_inodes_count, free_inodes - 2);
+
+        fs.create_file(share, "doc").unwrap();
+        assert_eq!(fs.rmdir(usr, "share"), Err(SahneError::ResourceBusy));
+        assert_eq!(fs.rmdir(share, "doc"), Err(SahneError::InvalidOperation));
+        assert_eq!(fs.unlink(EXT2_ROOT_INODE, "usr"), Err(SahneError::InvalidOperation));
+        assert_eq!(fs.rmdir(usr, ".."), Err(SahneError::InvalidParameter));
+        assert_fsck_clean(&fs.device, "mkdir");
+
+        fs.unlink(share, "doc").unwrap();
+        fs.rmdir(usr, "share").unwrap();
+        fs.rmdir(EXT2_ROOT_INODE, "usr").unwrap();
+        assert_eq!(fs.lookup(EXT2_ROOT_INODE, "usr"), Err(SahneError::ResourceNotFound));
+        assert_eq!({ fs.root_directory().unwrap().i_links_count }, root_links);
+        assert_eq!(fs.free_blocks_count, free_blocks);
+        assert_eq!(fs.free_inodes_count, free_inodes);
+        assert_fsck_clean(&fs.device, "rmdir");
+    }
+
+    #[test]
+    fn unlink_frees_inode_with_last_link() {
+        let mut fs = mount_writable();
+        let free_blocks = fs.free_blocks_count;
+        let free_inodes = fs.free_inodes_count;
+        let links = fs.lookup(EXT2_ROOT_INODE, "links").unwrap();
+        let hello = fs.lookup(EXT2_ROOT_INODE, "hello.txt").unwrap();
+
+        // links dizininde hem bloğun ilk girişleri (i-node alanı sıfırlanır) hem de önceki kayda
+        // katılan girişler silinir.
+        for i in 1..=64 {
+            fs.unlink(links, &alloc::format!("link{:02}-{:0200}", i, 0)).unwrap();
+        }
+        assert_eq!({ fs.read_inode(hello).unwrap().i_links_count }, 1);
+        assert_eq!(fs.list_directory(&fs.read_inode(links).unwrap()).unwrap().len(), 2);
+        assert_fsck_clean(&fs.device, "unlink-links");
+
+        fs.link(hello, EXT2_ROOT_INODE, "merhaba.txt").unwrap();
+        assert_eq!(fs.link(hello, EXT2_ROOT_INODE, "merhaba.txt"), Err(SahneError::NamingError));
+        assert_eq!(fs.link(links, EXT2_ROOT_INODE, "x"), Err(SahneError::InvalidOperation));
+        assert_eq!({ fs.read_inode(hello).unwrap().i_links_count }, 2);
+
+        // İlk ad silinince veri diğer addan okunmaya devam eder; son ad silinince i-node ve bloğu serbest kalır.
+        fs.unlink(EXT2_ROOT_INODE, "hello.txt").unwrap();
+        assert_eq!(read_all(&fs, hello), b"Merhaba SahneBox!\n");
+        fs.unlink(EXT2_ROOT_INODE, "merhaba.txt").unwrap();
+        assert_eq!(fs.unlink(EXT2_ROOT_INODE, "hello.txt"), Err(SahneError::ResourceNotFound));
+        assert_eq!(fs.free_blocks_count, free_blocks + 1);
+        assert_eq!(fs.free_inodes_count, free_inodes + 1);
+        assert_fsck_clean(&fs.device, "unlink");
+    }
+
+    #[test]
+    fn rename_replaces_and_moves_entries() {
+        let mut fs = mount_writable();
+        let root = EXT2_ROOT_INODE;
+        let etc = fs.lookup(root, "etc").unwrap();
+        let bin = fs.lookup(root, "bin").unwrap();
+        let hello = fs.lookup(root, "hello.txt").unwrap();
+
+        let hello_links = fs.read_inode(hello).unwrap().i_links_count;
+        fs.rename(root, "hello.txt", root, "merhaba.txt").unwrap();
+        assert_eq!(fs.lookup(root, "hello.txt"), Err(SahneError::ResourceNotFound));
+        assert_eq!(fs.lookup(root, "merhaba.txt"), Ok(hello));
+        assert_eq!({ fs.read_inode(hello).unwrap().i_links_count }, hello_links);
+
+        // Var olan dosyanın yerine geçilir; eski dosya son bağlantısıyla birlikte serbest kalır.
+        let conf = fs.create_file(etc, "hello.conf").unwrap();
+        fs.append_file(conf, b"eski").unwrap();
+        let free_blocks = fs.free_blocks_count;
+        fs.rename(root, "merhaba.txt", etc, "hello.conf").unwrap();
+        assert_eq!(fs.lookup(etc, "hello.conf"), Ok(hello));
+        assert_eq!({ fs.read_inode(conf).unwrap().i_links_count }, 0);
+        assert_eq!(fs.free_blocks_count, free_blocks + 1);
+
+        // Dizin taşınınca ".." ve üst dizinlerin bağlantı sayıları güncellenir.
+        let root_links = fs.root_directory().unwrap().i_links_count;
+        let etc_links = fs.read_inode(etc).unwrap().i_links_count;
+        fs.rename(root, "bin", etc, "bin").unwrap();
+        assert_eq!(fs.lookup(bin, ".."), Ok(etc));
+        assert_eq!({ fs.root_directory().unwrap().i_links_count }, root_links - 1);
+        assert_eq!({ fs.read_inode(etc).unwrap().i_links_count }, etc_links + 1);
+        assert_eq!(read_all(&fs, fs.lookup(bin, "hello.sbxe").unwrap()).len(), 120);
+
+        assert_eq!(fs.rename(root, "etc", bin, "etc"), Err(SahneError::InvalidParameter)); // Kendi altına
+        assert_eq!(fs.rename(root, "etc", etc, "x"), Err(SahneError::InvalidParameter));
+        assert_eq!(fs.rename(etc, "bin", etc, "hello.conf"), Err(SahneError::InvalidOperation));
+        assert_eq!(fs.rename(root, "yok", root, "x"), Err(SahneError::ResourceNotFound));
+        assert_fsck_clean(&fs.device, "rename-move");
+
+        // Boş bir dizinin yerine geçme
+        let empty = fs.mkdir(root, "bos").unwrap();
+        fs.rename(etc, "bin", root, "bos").unwrap();
+        assert_eq!(fs.lookup(root, "bos"), Ok(bin));
+        assert_eq!(fs.lookup(bin, ".."), Ok(root));
+        assert_eq!({ fs.read_inode(empty).unwrap().i_links_count }, 0);
+        assert_eq!({ fs.root_directory().unwrap().i_links_count }, root_links);
+        assert_eq!({ fs.read_inode(etc).unwrap().i_links_count }, etc_links);
+        assert_fsck_clean(&fs.device, "rename-replace-dir");
+    }
+
+    #[test]
+    fn resolves_paths_through_symlinks() {
+        let fs = mount_image();
+        let root = EXT2_ROOT_INODE;
+        let bin = fs.lookup(root, "bin").unwrap();
+        let etc = fs.lookup(root, "etc").unwrap();
+        let hello = fs.lookup(root, "hello.txt").unwrap();
+        let sbxe = fs.lookup(bin, "hello.sbxe").unwrap();
+
+        assert_eq!(fs.resolve("/", bin), Ok(root));
+        assert_eq!(fs.resolve("//bin///hello.sbxe", etc), Ok(sbxe));
+        assert_eq!(fs.resolve("hello.sbxe", bin), Ok(sbxe));
+        assert_eq!(fs.resolve("../hello.txt", bin), Ok(hello));
+        assert_eq!(fs.resolve("/../..", bin), Ok(root)); // Kökün üstü yine kök
+        assert_eq!(fs.resolve("./bin/./../etc/", root), Ok(etc));
+
+        // Hızlı bağlantı (hedef i-node'da) ve yavaş bağlantı (hedef veri bloğunda)
+        let sh = fs.lookup(bin, "sh").unwrap();
+        assert_eq!({ fs.read_inode(sh).unwrap().i_blocks }, 0);
+        assert_eq!(fs.readlink(sh).unwrap(), b"hello.sbxe");
+        assert_eq!(fs.resolve("/bin/sh", root), Ok(sbxe));
+        let motd = fs.lookup(etc, "motd").unwrap();
+        assert_eq!(fs.readlink(motd).unwrap().len(), 90);
+        assert_eq!(fs.resolve("motd", etc), Ok(hello));
+
+        // Son bileşen izlenmez
+        assert_eq!(fs.resolve_parent("/bin/sh", root), Ok((bin, "sh")));
+        assert_eq!(fs.resolve_parent("sh", bin), Ok((bin, "sh")));
+        assert_eq!(fs.resolve_parent("/etc//motd/", bin), Ok((etc, "motd")));
+        assert_eq!(fs.resolve_parent("/", root), Err(SahneError::InvalidParameter));
+
+        assert_eq!(fs.resolve("", root), Err(SahneError::InvalidParameter));
+        assert_eq!(fs.resolve("/yok", root), Err(SahneError::ResourceNotFound));
+        assert_eq!(fs.resolve("/hello.txt/x", root), Err(SahneError::InvalidOperation));
+        assert_eq!(fs.resolve("/hello.txt/", root), Err(SahneError::InvalidOperation));
+        assert_eq!(fs.readlink(hello), Err(SahneError::InvalidOperation));
+    }
+
+    #[test]
+    fn creates_symlinks_and_detects_loops() {
+        let mut fs = mount_writable();
+        let root = EXT2_ROOT_INODE;
+        let free_blocks = fs.free_blocks_count;
+        let free_inodes = fs.free_inodes_count;
+        let etc = fs.lookup(root, "etc").unwrap();
+        let hello = fs.lookup(root, "hello.txt").unwrap();
+        let sbxe = fs.resolve("/bin/hello.sbxe", root).unwrap();
+
+        // Bağlantı zinciri: /kisa -> bin/sh -> hello.sbxe
+        let short = fs.symlink(root, "kisa", "bin/sh").unwrap();
+        assert_eq!({ fs.read_inode(short).unwrap().i_blocks }, 0);
+        assert_eq!(fs.resolve("/kisa", etc), Ok(sbxe));
+        // 60 bayt ve üzeri hedef veri bloğuna yazılır
+        let target = alloc::format!("{}etc/motd", "./".repeat(30));
+        let long = fs.symlink(root, "uzun", &target).unwrap();
+        assert_eq!(fs.readlink(long).unwrap(), target.as_bytes());
+        assert_eq!(fs.resolve("uzun", root), Ok(hello));
+        assert_eq!(fs.free_blocks_count, free_blocks - 1);
+
+        // Göreli hedef bağlantının bulunduğu dizinden çözülür; ".." gerçek üst dizine çıkar.
+        fs.symlink(etc, "yukari", "../bin").unwrap();
+        assert_eq!(fs.resolve("/etc/yukari/sh", root), Ok(sbxe));
+        assert_eq!(fs.resolve("/etc/yukari/..", root), Ok(root));
+
+        fs.symlink(root, "a", "b").unwrap();
+        fs.symlink(root, "b", "/a").unwrap();
+        assert_eq!(fs.resolve("/a", root), Err(SahneError::NamingError));
+        assert_eq!(fs.resolve_parent("/a", root), Ok((root, "a")));
+        fs.symlink(root, "kopuk", "yok").unwrap();
+        assert_eq!(fs.resolve("kopuk", root), Err(SahneError::ResourceNotFound));
+
+        assert_eq!(fs.symlink(root, "x", ""), Err(SahneError::InvalidParameter));
+        assert_eq!(fs.symlink(root, "x", &"a".repeat(1024)), Err(SahneError::InvalidParameter));
+        assert_eq!(fs.symlink(root, "kisa", "z"), Err(SahneError::NamingError));
+        assert_fsck_clean(&fs.device, "symlink");
+
+        for name in ["kisa", "uzun", "a", "b", "kopuk"] {
+            fs.unlink(root, name).unwrap();
+        }
+        fs.unlink(etc, "yukari").unwrap();
+        assert_eq!(fs.free_blocks_count, free_blocks);
+        assert_eq!(fs.free_inodes_count, free_inodes);
+        assert_fsck_clean(&fs.device, "symlink-unlink");
+    }
+
+    #[test]
+    fn rejects_wrong_inode_kinds_and_numbers() {
+        let fs = mount_image();
+        let root = fs.root_directory().unwrap();
+        let mut buffer = [0u8; 8];
+        assert_eq!(fs.read_file(&root, &mut buffer, 0).unwrap_err(), SahneError::InvalidOperation);
+        assert_eq!(fs.read_inode(0).err(), Some(SahneError::InvalidParameter));
+        assert_eq!(fs.read_inode(u32::MAX).err(), Some(SahneError::InvalidParameter));
+    }
+
+    #[test]
+    fn rejects_non_ext2_device() {
+        let blank = vec![0u8; 4096];
+        assert_eq!(ExtFilesystem::mount(&blank[..]).err(), Some(SahneError::InvalidParameter));
+        let short: &[u8] = &IMAGE[..1500];
+        assert_eq!(ExtFilesystem::mount(short).err(), Some(SahneError::InvalidOperation));
+    }
+
+    // Süper bloğu ve ilk metadata bloklarını rastgele bozarak bağlama ve listelemenin
+    // panik etmediğini doğrular (basit, bağımlılıksız bir fuzz testi).
+    #[test]
+    fn corrupted_images_never_panic() {
+        let mut state: u64 = 0x5EED_5A4E_4542_4F58;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..500 {
+            let mut image = IMAGE.to_vec();
+            for _ in 0..8 {
+                let position = 1024 + (next() % 8192) as usize;
+                image[position] = next() as u8;
+            }
+            if let Ok(fs) = ExtFilesystem::mount(&image[..]) {
+                if let Ok(root) = fs.root_directory() {
+                    let _ = fs.list_directory(&root);
+                }
+                let _ = fs.read_inode((next() % 64) as u32);
+            }
+        }
+    }
+}
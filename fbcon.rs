@@ -0,0 +1,490 @@
+// main_kernel/fbcon.rs
+// Framebuffer Metin Konsolu
+// Buzdolabının UART'ı dışarıya açık olmadığından açılış mesajları ve panikler 800x600 ekrana da yazılır.
+// Bu modül donanıma bağımlı değildir: verilen piksel belleğine (32-bit XRGB8888) yerleşik yazı tipiyle
+// metin çizer, ekran dolunca kaydırır ve temel ANSI kaçış dizilerini (renk, imleç konumu, silme) işler.
+// Hangi çıktının kullanılacağı console.rs'de, açılışta seçilir.
+
+use core::fmt;
+
+use crate::font::{self, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::sahne64::SahneError;
+
+pub const BYTES_PER_PIXEL: usize = 4;
+// Yazı tipi ekranda 2 kat büyütülür: 800x600'de 66x30 karakter.
+pub const FONT_SCALE: usize = 2;
+// Karakterler arasında 1 piksel (büyütmeden önce) boşluk bırakılır.
+pub const CELL_WIDTH: usize = (GLYPH_WIDTH + 1) * FONT_SCALE;
+pub const CELL_HEIGHT: usize = GLYPH_HEIGHT * FONT_SCALE;
+
+const TAB_WIDTH: usize = 8;
+const MAX_PARAMS: usize = 4;
+// İmleç, karakter hücresinin en altındaki bu kadar piksel satırında çizilen bir alt çizgidir.
+const CURSOR_HEIGHT: usize = FONT_SCALE;
+
+// Standart 16 renkli VGA paleti (0x00RRGGBB). 0-7 normal, 8-15 parlak renkler.
+const PALETTE: [u32; 16] = [
+    0x00_0000, 0xAA_0000, 0x00_AA00, 0xAA_5500, 0x00_00AA, 0xAA_00AA, 0x00_AAAA, 0xAA_AAAA,
+    0x55_5555, 0xFF_5555, 0x55_FF55, 0xFF_FF55, 0x55_55FF, 0xFF_55FF, 0x55_FFFF, 0xFF_FFFF,
+];
+const DEFAULT_FG: u8 = 7;
+const DEFAULT_BG: u8 = 0;
+
+// ANSI kaçış dizisi ayrıştırıcısının durumu
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    Esc,              // ESC alındı
+    Csi { private: bool }, // ESC [ alındı; private: '?' ile başlayan dizi (örn. ESC[?25l)
+}
+
+pub struct FbConsole<'a> {
+    pixels: &'a mut [u8],
+    stride: usize, // Bir piksel satırının bayt uzunluğu
+    columns: usize,
+    rows: usize,
+    cursor_column: usize, // columns'a eşitse satır sonundayız; bir sonraki karakter alt satıra geçer
+    cursor_row: usize,
+    fg: u8,
+    bg: u8,
+    bold: bool,
+    cursor_visible: bool,
+    cursor_drawn: bool,
+    escape: Escape,
+    params: [u16; MAX_PARAMS],
+    param_count: usize,
+    utf8_char: u32,      // Çözülmekte olan UTF-8 karakteri
+    utf8_remaining: u8,  // Beklenen devam baytı sayısı
+}
+
+impl<'a> FbConsole<'a> {
+    /// `pixels` en az `stride * height` bayt olmalı, her satır `width` adet 32-bit pikselden oluşur.
+    /// Ekran tek bir karakter hücresinden küçükse InvalidParameter döner.
+    pub fn new(pixels: &'a mut [u8], width: usize, height: usize, stride: usize) -> Result<FbConsole<'a>, SahneError> {
+        let columns = width / CELL_WIDTH;
+        let rows = height / CELL_HEIGHT;
+        let needed = stride.checked_mul(height).ok_or(SahneError::InvalidParameter)?;
+        if columns == 0 || rows == 0 || stride < width * BYTES_PER_PIXEL || pixels.len() < needed {
+            return Err(SahneError::InvalidParameter);
+        }
+        Ok(FbConsole {
+            pixels,
+            stride,
+            columns,
+            rows,
+            cursor_column: 0,
+            cursor_row: 0,
+            fg: DEFAULT_FG,
+            bg: DEFAULT_BG,
+            bold: false,
+            cursor_visible: true,
+            cursor_drawn: false,
+            escape: Escape::None,
+            params: [0; MAX_PARAMS],
+            param_count: 0,
+            utf8_char: 0,
+            utf8_remaining: 0,
+        })
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// İmlecin konumu (sütun, satır), 0'dan başlar.
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.cursor_column.min(self.columns - 1), self.cursor_row)
+    }
+
+    /// Ekranı arka plan rengiyle temizler ve imleci sol üste taşır.
+    pub fn clear(&mut self) {
+        self.hide_cursor();
+        self.fill_rows(0, self.rows);
+        self.cursor_column = 0;
+        self.cursor_row = 0;
+        self.show_cursor();
+    }
+
+    /// Bir bayt yazar. Çok baytlı UTF-8 karakterler ve kaçış dizileri çağrılar arasında bölünebilir.
+    pub fn write_byte(&mut self, byte: u8) {
+        self.hide_cursor();
+        if let Some(ch) = self.decode_utf8(byte) {
+            self.handle_char(ch);
+        }
+        self.show_cursor();
+    }
+
+    // UTF-8 çözücü. Geçersiz diziler yerine kutu karakteri (U+FFFD) çizilir.
+    fn decode_utf8(&mut self, byte: u8) -> Option<char> {
+        if self.utf8_remaining > 0 {
+            if byte & 0xC0 == 0x80 {
+                self.utf8_char = (self.utf8_char << 6) | (byte & 0x3F) as u32;
+                self.utf8_remaining -= 1;
+                if self.utf8_remaining > 0 {
+                    return None;
+                }
+                return Some(char::from_u32(self.utf8_char).unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            // Yarım kalan karakter: onu kutu olarak çiz, yeni baytı ayrıca işle.
+            self.utf8_remaining = 0;
+            self.handle_char(char::REPLACEMENT_CHARACTER);
+        }
+        match byte {
+            0x00..=0x7F => Some(byte as char),
+            0xC2..=0xDF => self.begin_utf8(byte & 0x1F, 1),
+            0xE0..=0xEF => self.begin_utf8(byte & 0x0F, 2),
+            0xF0..=0xF4 => self.begin_utf8(byte & 0x07, 3),
+            _ => Some(char::REPLACEMENT_CHARACTER),
+        }
+    }
+
+    fn begin_utf8(&mut self, bits: u8, remaining: u8) -> Option<char> {
+        self.utf8_char = bits as u32;
+        self.utf8_remaining = remaining;
+        None
+    }
+
+    fn handle_char(&mut self, ch: char) {
+        match self.escape {
+            Escape::Esc => {
+                self.escape = if ch == '[' { Escape::Csi { private: false } } else { Escape::None };
+                self.params = [0; MAX_PARAMS];
+                self.param_count = 0;
+                return;
+            }
+            Escape::Csi { private } => {
+                self.handle_csi(ch, private);
+                return;
+            }
+            Escape::None => {}
+        }
+
+        match ch {
+            '\x1B' => self.escape = Escape::Esc,
+            '\n' => self.new_line(),
+            '\r' => self.cursor_column = 0,
+            '\t' => {
+                let next = (self.cursor_column / TAB_WIDTH + 1) * TAB_WIDTH;
+                self.cursor_column = next.min(self.columns - 1);
+            }
+            '\x08' => self.cursor_column = self.cursor_column.min(self.columns - 1).saturating_sub(1),
+            ch if ch.is_control() => {} // Zil (BEL) vb. yok sayılır
+            ch => {
+                if self.cursor_column >= self.columns {
+                    self.new_line();
+                }
+                self.draw_glyph(self.cursor_column, self.cursor_row, ch);
+                self.cursor_column += 1;
+            }
+        }
+    }
+
+    // CSI (ESC [) dizisinin parametrelerini toplar ve son karakterde komutu uygular.
+    fn handle_csi(&mut self, ch: char, private: bool) {
+        match ch {
+            '0'..='9' => {
+                if self.param_count == 0 {
+                    self.param_count = 1;
+                }
+                if let Some(param) = self.params.get_mut(self.param_count - 1) {
+                    *param = param.saturating_mul(10).saturating_add(ch as u16 - b'0' as u16);
+                }
+            }
+            ';' => self.param_count = (self.param_count.max(1) + 1).min(MAX_PARAMS + 1),
+            '?' if self.param_count == 0 => self.escape = Escape::Csi { private: true },
+            '\x40'..='\x7E' => {
+                self.escape = Escape::None;
+                self.execute_csi(ch, private);
+            }
+            _ => self.escape = Escape::None, // Bozuk dizi: yok say
+        }
+    }
+
+    // Verilmeyen veya 0 olan parametre için varsayılan değer (çoğu komutta 1).
+    fn param(&self, index: usize, default: usize) -> usize {
+        match self.params.get(index) {
+            Some(&value) if index < self.param_count && value != 0 => value as usize,
+            _ => default,
+        }
+    }
+
+    fn execute_csi(&mut self, command: char, private: bool) {
+        let last_column = self.columns - 1;
+        let column = self.cursor_column.min(last_column);
+        match (command, private) {
+            ('m', false) => self.select_graphic_rendition(),
+            ('H', false) | ('f', false) => {
+                self.cursor_row = (self.param(0, 1) - 1).min(self.rows - 1);
+                self.cursor_column = (self.param(1, 1) - 1).min(last_column);
+            }
+            ('A', false) => self.cursor_row = self.cursor_row.saturating_sub(self.param(0, 1)),
+            ('B', false) => self.cursor_row = (self.cursor_row + self.param(0, 1)).min(self.rows - 1),
+            ('C', false) => self.cursor_column = (column + self.param(0, 1)).min(last_column),
+            ('D', false) => self.cursor_column = column.saturating_sub(self.param(0, 1)),
+            ('J', false) => match self.param(0, 0) {
+                0 => {
+                    self.clear_cells(self.cursor_row, column, self.columns);
+                    self.fill_rows(self.cursor_row + 1, self.rows);
+                }
+                1 => {
+                    self.fill_rows(0, self.cursor_row);
+                    self.clear_cells(self.cursor_row, 0, column + 1);
+                }
+                _ => self.fill_rows(0, self.rows),
+            },
+            ('K', false) => match self.param(0, 0) {
+                0 => self.clear_cells(self.cursor_row, column, self.columns),
+                1 => self.clear_cells(self.cursor_row, 0, column + 1),
+                _ => self.clear_cells(self.cursor_row, 0, self.columns),
+            },
+            ('h', true) | ('l', true) if self.param(0, 0) == 25 => self.cursor_visible = command == 'h',
+            _ => {} // Desteklenmeyen komut
+        }
+    }
+
+    // ESC [ ... m: renk ve yazı stili
+    fn select_graphic_rendition(&mut self) {
+        for index in 0..self.param_count.clamp(1, MAX_PARAMS) {
+            match self.params[index] {
+                0 => {
+                    self.fg = DEFAULT_FG;
+                    self.bg = DEFAULT_BG;
+                    self.bold = false;
+                }
+                1 => self.bold = true,
+                22 => self.bold = false,
+                code @ 30..=37 => self.fg = (code - 30) as u8,
+                39 => self.fg = DEFAULT_FG,
+                code @ 40..=47 => self.bg = (code - 40) as u8,
+                49 => self.bg = DEFAULT_BG,
+                code @ 90..=97 => self.fg = (code - 90) as u8 + 8,
+                code @ 100..=107 => self.bg = (code - 100) as u8 + 8,
+                _ => {}
+            }
+        }
+    }
+
+    fn foreground(&self) -> u32 {
+        // Kalın yazı, normal renklerin parlak karşılığıyla gösterilir.
+        let index = if self.bold && self.fg < 8 { self.fg + 8 } else { self.fg };
+        PALETTE[index as usize]
+    }
+
+    fn background(&self) -> u32 {
+        PALETTE[self.bg as usize]
+    }
+
+    fn new_line(&mut self) {
+        self.cursor_column = 0;
+        if self.cursor_row + 1 < self.rows {
+            self.cursor_row += 1;
+        } else {
+            self.scroll_up();
+        }
+    }
+
+    // Tüm metni bir satır yukarı kaydırır ve en alt satırı temizler.
+    fn scroll_up(&mut self) {
+        let row_bytes = CELL_HEIGHT * self.stride;
+        let text_bytes = self.rows * row_bytes;
+        self.pixels.copy_within(row_bytes..text_bytes, 0);
+        self.fill_rows(self.rows - 1, self.rows);
+    }
+
+    fn put_pixel(&mut self, x: usize, y: usize, color: u32) {
+        let offset = y * self.stride + x * BYTES_PER_PIXEL;
+        self.pixels[offset..offset + BYTES_PER_PIXEL].copy_from_slice(&color.to_le_bytes());
+    }
+
+    fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u32) {
+        for line in y..y + height {
+            for column in x..x + width {
+                self.put_pixel(column, line, color);
+            }
+        }
+    }
+
+    // [first, last) aralığındaki metin satırlarını arka plan rengiyle doldurur.
+    fn fill_rows(&mut self, first: usize, last: usize) {
+        if first < last {
+            let color = self.background();
+            self.fill_rect(0, first * CELL_HEIGHT, self.columns * CELL_WIDTH, (last - first) * CELL_HEIGHT, color);
+        }
+    }
+
+    // Bir satırdaki [first, last) sütunlarını temizler.
+    fn clear_cells(&mut self, row: usize, first: usize, last: usize) {
+        if first < last {
+            let color = self.background();
+            self.fill_rect(first * CELL_WIDTH, row * CELL_HEIGHT, (last - first) * CELL_WIDTH, CELL_HEIGHT, color);
+        }
+    }
+
+    fn draw_glyph(&mut self, column: usize, row: usize, ch: char) {
+        let glyph = font::glyph(ch);
+        let (fg, bg) = (self.foreground(), self.background());
+        let (x, y) = (column * CELL_WIDTH, row * CELL_HEIGHT);
+        for (line, bits) in glyph.iter().enumerate() {
+            for dot in 0..=GLYPH_WIDTH {
+                // Son sütun karakterler arası boşluktur (bit yok).
+                let lit = dot < GLYPH_WIDTH && bits & (1 << (GLYPH_WIDTH - 1 - dot)) != 0;
+                let color = if lit { fg } else { bg };
+                self.fill_rect(x + dot * FONT_SCALE, y + line * FONT_SCALE, FONT_SCALE, FONT_SCALE, color);
+            }
+        }
+    }
+
+    // İmleç, hücrenin alt kısmındaki piksellerin tersine çevrilmesiyle çizilir; aynı işlem
+    // tekrarlanınca altındaki içerik geri gelir.
+    fn toggle_cursor(&mut self) {
+        let (column, row) = self.cursor();
+        let y = row * CELL_HEIGHT + CELL_HEIGHT - CURSOR_HEIGHT;
+        for line in y..y + CURSOR_HEIGHT {
+            for x in column * CELL_WIDTH..(column + 1) * CELL_WIDTH {
+                let offset = line * self.stride + x * BYTES_PER_PIXEL;
+                for byte in &mut self.pixels[offset..offset + 3] {
+                    *byte ^= 0xFF;
+                }
+            }
+        }
+        self.cursor_drawn = !self.cursor_drawn;
+    }
+
+    fn hide_cursor(&mut self) {
+        if self.cursor_drawn {
+            self.toggle_cursor();
+        }
+    }
+
+    fn show_cursor(&mut self) {
+        if self.cursor_visible && !self.cursor_drawn {
+            self.toggle_cursor();
+        }
+    }
+}
+
+impl fmt::Write for FbConsole<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            self.write_byte(byte);
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    const COLUMNS: usize = 4;
+    const ROWS: usize = 3;
+    const WIDTH: usize = COLUMNS * CELL_WIDTH + 5; // Hücreye sığmayan kenar boşluğu
+    const HEIGHT: usize = ROWS * CELL_HEIGHT;
+    const STRIDE: usize = WIDTH * BYTES_PER_PIXEL;
+
+    fn pixel(pixels: &[u8], x: usize, y: usize) -> u32 {
+        let offset = y * STRIDE + x * BYTES_PER_PIXEL;
+        u32::from_le_bytes([pixels[offset], pixels[offset + 1], pixels[offset + 2], pixels[offset + 3]])
+    }
+
+    // Hücredeki pikselleri, karakterin verilen renklerle çizilmiş haliyle karşılaştırır (imleç hariç).
+    fn cell_shows(pixels: &[u8], column: usize, row: usize, ch: char, fg: u32, bg: u32) -> bool {
+        let glyph = font::glyph(ch);
+        (0..CELL_HEIGHT - CURSOR_HEIGHT).all(|dy| {
+            (0..CELL_WIDTH).all(|dx| {
+                let (dot, line) = (dx / FONT_SCALE, dy / FONT_SCALE);
+                let lit = dot < GLYPH_WIDTH && glyph[line] & (1 << (GLYPH_WIDTH - 1 - dot)) != 0;
+                pixel(pixels, column * CELL_WIDTH + dx, row * CELL_HEIGHT + dy) == if lit { fg } else { bg }
+            })
+        })
+    }
+
+    fn render(text: &[u8]) -> (Vec<u8>, (usize, usize)) {
+        let mut pixels = vec![0xEEu8; STRIDE * HEIGHT];
+        let cursor = {
+            let mut console = FbConsole::new(&mut pixels, WIDTH, HEIGHT, STRIDE).unwrap();
+            console.clear();
+            for &byte in text {
+                console.write_byte(byte);
+            }
+            console.cursor()
+        };
+        (pixels, cursor)
+    }
+
+    const WHITE: u32 = PALETTE[DEFAULT_FG as usize];
+    const BLACK: u32 = PALETTE[DEFAULT_BG as usize];
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn draws_ascii_and_utf8_and_wraps_lines() {
+        assert!(FbConsole::new(&mut [0u8; 16], 2, 2, 8).is_err());
+        assert!(FbConsole::new(&mut vec![0u8; STRIDE * HEIGHT - 1], WIDTH, HEIGHT, STRIDE).is_err());
+
+        // "ş" iki bayttır; geçersiz 0xFF kutu olarak çizilir. 4 sütunluk satır dolunca alt satıra geçilir.
+        let mut text = "Aşb\u{7}\x1B[?25l".as_bytes().to_vec();
+        text.extend_from_slice(b"\xFFZ");
+        let (pixels, cursor) = render(&text);
+        assert!(cell_shows(&pixels, 0, 0, 'A', WHITE, BLACK));
+        assert!(cell_shows(&pixels, 1, 0, 'ş', WHITE, BLACK));
+        assert!(cell_shows(&pixels, 2, 0, 'b', WHITE, BLACK));
+        assert!(cell_shows(&pixels, 3, 0, char::REPLACEMENT_CHARACTER, WHITE, BLACK));
+        assert!(cell_shows(&pixels, 0, 1, 'Z', WHITE, BLACK));
+        assert_eq!(cursor, (1, 1));
+        // Hücrelerin dışındaki kenar boşluğuna dokunulmaz.
+        assert_eq!(pixel(&pixels, WIDTH - 1, 0), 0xEEEE_EEEE);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn scrolls_when_the_screen_is_full() {
+        let (pixels, cursor) = render(b"\x1B[?25l1\r\n2\r\n3\r\n4\n5");
+        assert!(cell_shows(&pixels, 0, 0, '3', WHITE, BLACK));
+        assert!(cell_shows(&pixels, 0, 1, '4', WHITE, BLACK));
+        assert!(cell_shows(&pixels, 0, 2, '5', WHITE, BLACK));
+        assert!(cell_shows(&pixels, 1, 2, ' ', WHITE, BLACK));
+        assert_eq!(cursor, (1, 2));
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn handles_ansi_colors_cursor_and_erase() {
+        let red = PALETTE[1];
+        let bright_red = PALETTE[9];
+        let blue = PALETTE[4];
+        let (pixels, cursor) = render(b"\x1B[?25lxxxx\x1B[31mR\x1B[1;44mB\x1B[0mn\x1B[3;2HQ\x1B[1;3H\x1B[K\x1B[99zy");
+        assert!(cell_shows(&pixels, 0, 0, 'x', WHITE, BLACK));
+        assert!(cell_shows(&pixels, 1, 0, 'x', WHITE, BLACK));
+        assert!(cell_shows(&pixels, 2, 0, 'y', WHITE, BLACK)); // ESC[K satır sonunu sildi, bilinmeyen ESC[99z yok sayıldı
+        assert!(cell_shows(&pixels, 3, 0, ' ', WHITE, BLACK));
+        assert!(cell_shows(&pixels, 0, 1, 'R', red, BLACK));
+        assert!(cell_shows(&pixels, 1, 1, 'B', bright_red, blue));
+        assert!(cell_shows(&pixels, 2, 1, 'n', WHITE, BLACK));
+        assert!(cell_shows(&pixels, 1, 2, 'Q', WHITE, BLACK));
+        assert_eq!(cursor, (3, 0));
+
+        let (cleared, cursor) = render(b"abc\x1B[2J\x1B[H");
+        assert!((0..COLUMNS).all(|column| cell_shows(&cleared, column, 0, ' ', WHITE, BLACK)));
+        assert_eq!(cursor, (0, 0));
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn cursor_is_drawn_and_restored() {
+        let (with_cursor, _) = render(b"a");
+        let (without_cursor, _) = render(b"a\x1B[?25l");
+        let y = CELL_HEIGHT - 1;
+        let x = CELL_WIDTH; // İmleç ikinci hücrede
+        assert_eq!(pixel(&with_cursor, x, y) & 0x00FF_FFFF, !BLACK & 0x00FF_FFFF);
+        assert_eq!(pixel(&without_cursor, x, y), BLACK);
+        // İmleç kaldırıldığında altındaki karakter bozulmaz.
+        assert!(cell_shows(&without_cursor, 0, 0, 'a', WHITE, BLACK));
+    }
+}
@@ -0,0 +1,508 @@
+// main_kernel/fdt.rs
+// Flattened Device Tree (DTB) Okuyucu
+// Firmware (OpenSBI / QEMU) çekirdeğe a1 register'ında bir DTB adresi verir. Bu modül DTB'yi
+// kopyalamadan, heap kullanmadan okur ve sürücülerin cihazlarını "compatible" değeriyle bulmasını sağlar.
+// Donanıma bağımlı değildir; DTB güvenilmeyen veri gibi ele alınır, bozuk bir DTB panik yerine
+// hata veya eksik sonuç üretir.
+//
+// DTB yerleşimi (big-endian): başlık, bellek rezervasyon tablosu, yapı bloğu (token akışı), dizgi bloğu.
+
+use crate::sahne64::SahneError;
+
+pub const FDT_MAGIC: u32 = 0xD00D_FEED;
+const HEADER_SIZE: usize = 40;
+// Bu okuyucunun anladığı en son sürümle uyumlu olması gereken sürüm (last_comp_version).
+const SUPPORTED_COMP_VERSION: u32 = 16;
+
+const FDT_BEGIN_NODE: u32 = 0x1;
+const FDT_END_NODE: u32 = 0x2;
+const FDT_PROP: u32 = 0x3;
+const FDT_NOP: u32 = 0x4;
+const FDT_END: u32 = 0x9;
+
+// Desteklenen en fazla düğüm derinliği (kök = 0). QEMU virt ağacı 3-4 seviyedir.
+const MAX_DEPTH: usize = 16;
+
+// #address-cells / #size-cells verilmemişse geçerli varsayılanlar (DT spesifikasyonu).
+const DEFAULT_ADDRESS_CELLS: u32 = 2;
+const DEFAULT_SIZE_CELLS: u32 = 1;
+
+fn be32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// 4 bayt hizalamaya yukarı yuvarlar.
+fn align4(value: usize) -> Option<usize> {
+    Some(value.checked_add(3)? & !3)
+}
+
+// NUL ile biten dizgiyi döndürür (NUL hariç).
+fn c_str(data: &[u8], offset: usize) -> Option<&[u8]> {
+    let rest = data.get(offset..)?;
+    let len = rest.iter().position(|&b| b == 0)?;
+    Some(&rest[..len])
+}
+
+/// Bellekteki bir DTB.
+#[derive(Clone, Copy)]
+pub struct Fdt<'a> {
+    structure: &'a [u8],
+    strings: &'a [u8],
+}
+
+/// Ağaçtaki bir düğüm. Özellikleri (property) ve adres biçimi (üst düğümün #address-cells /
+/// #size-cells değerleri) ile birlikte döner.
+#[derive(Clone, Copy)]
+pub struct Node<'a> {
+    fdt: Fdt<'a>,
+    name: &'a [u8],
+    depth: usize,
+    properties: usize, // Yapı bloğunda ilk özelliğin (veya alt düğümün) offseti
+    address_cells: u32,
+    size_cells: u32,
+}
+
+impl<'a> Fdt<'a> {
+    /// DTB başlığını doğrular. `data` en az başlıktaki toplam boyut kadar olmalıdır.
+    pub fn parse(data: &'a [u8]) -> Result<Fdt<'a>, SahneError> {
+        let field = |index: usize| be32(data, index * 4).ok_or(SahneError::InvalidParameter);
+        if field(0)? != FDT_MAGIC {
+            return Err(SahneError::InvalidParameter);
+        }
+        let total_size = field(1)? as usize;
+        let off_struct = field(2)? as usize;
+        let off_strings = field(3)? as usize;
+        let last_comp_version = field(6)?;
+        let size_strings = field(8)? as usize;
+        let size_struct = field(9)? as usize;
+
+        if last_comp_version > SUPPORTED_COMP_VERSION {
+            return Err(SahneError::NotSupported);
+        }
+        if total_size < HEADER_SIZE || total_size > data.len() {
+            return Err(SahneError::InvalidParameter);
+        }
+        let data = &data[..total_size];
+        let structure = off_struct
+            .checked_add(size_struct)
+            .and_then(|end| data.get(off_struct..end))
+            .ok_or(SahneError::InvalidParameter)?;
+        let strings = off_strings
+            .checked_add(size_strings)
+            .and_then(|end| data.get(off_strings..end))
+            .ok_or(SahneError::InvalidParameter)?;
+        Ok(Fdt { structure, strings })
+    }
+
+    /// Firmware'ın verdiği adresteki DTB'yi okur. Boyut başlıktan alınır.
+    ///
+    /// # Safety
+    /// `address` geçerli, çekirdek çalıştığı sürece değişmeyecek bir DTB'yi göstermelidir.
+    pub unsafe fn from_address(address: usize) -> Result<Fdt<'static>, SahneError> {
+        if address == 0 || !address.is_multiple_of(4) {
+            return Err(SahneError::InvalidAddress);
+        }
+        let header = core::slice::from_raw_parts(address as *const u8, HEADER_SIZE);
+        if be32(header, 0) != Some(FDT_MAGIC) {
+            return Err(SahneError::InvalidParameter);
+        }
+        let total_size = be32(header, 4).ok_or(SahneError::InvalidParameter)? as usize;
+        Fdt::parse(core::slice::from_raw_parts(address as *const u8, total_size.max(HEADER_SIZE)))
+    }
+
+    /// Tüm düğümleri ağaç sırasıyla (önce derinlik) dolaşır.
+    pub fn nodes(&self) -> Nodes<'a> {
+        Nodes {
+            fdt: *self,
+            offset: 0,
+            depth: 0,
+            cells: [(DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS); MAX_DEPTH + 1],
+            finished: false,
+        }
+    }
+
+    /// "compatible" listesinde verilen değer bulunan düğümler.
+    pub fn compatible_nodes(&self, compatible: &'a str) -> impl Iterator<Item = Node<'a>> + 'a {
+        self.nodes().filter(move |node| node.is_compatible(compatible))
+    }
+
+    /// Yolu verilen düğüm (örn. "/chosen"). Birim adresi (@...) yolda verilmezse yok sayılır.
+    pub fn find_node(&self, path: &str) -> Option<Node<'a>> {
+        let mut wanted = path.split('/').filter(|part| !part.is_empty());
+        let mut target = wanted.next();
+        let mut matched_depth = 0;
+        for node in self.nodes() {
+            if node.depth == 0 {
+                if target.is_none() {
+                    return Some(node); // "/"
+                }
+                continue;
+            }
+            if node.depth <= matched_depth {
+                return None; // Eşleşen düğümün alt ağacı bitti
+            }
+            if node.depth == matched_depth + 1 && target.is_some_and(|part| node.name_matches(part)) {
+                matched_depth += 1;
+                target = wanted.next();
+                if target.is_none() {
+                    return Some(node);
+                }
+            }
+        }
+        None
+    }
+
+    fn token(&self, offset: usize) -> Option<u32> {
+        be32(self.structure, offset)
+    }
+
+    fn string(&self, offset: usize) -> Option<&'a [u8]> {
+        c_str(self.strings, offset)
+    }
+
+    // offset'teki FDT_PROP'u okur: (ad, değer, sonraki token offseti).
+    fn property_at(&self, offset: usize) -> Option<(&'a [u8], &'a [u8], usize)> {
+        let len = self.token(offset + 4)? as usize;
+        let name_offset = self.token(offset + 8)? as usize;
+        let value_start = offset + 12;
+        let value = self.structure.get(value_start..value_start.checked_add(len)?)?;
+        Some((self.string(name_offset)?, value, align4(value_start + len)?))
+    }
+}
+
+/// Fdt::nodes() dolaşıcısı.
+pub struct Nodes<'a> {
+    fdt: Fdt<'a>,
+    offset: usize,
+    depth: usize,
+    // Her derinlik için alt düğümlerin kullanacağı (#address-cells, #size-cells).
+    cells: [(u32, u32); MAX_DEPTH + 1],
+    finished: bool,
+}
+
+impl<'a> Iterator for Nodes<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        let node = self.advance();
+        if node.is_none() {
+            self.finished = true;
+        }
+        node
+    }
+}
+
+impl<'a> Nodes<'a> {
+    // Bir sonraki FDT_BEGIN_NODE'a kadar ilerler. Bozuk veri dolaşmayı bitirir.
+    fn advance(&mut self) -> Option<Node<'a>> {
+        if self.finished {
+            return None;
+        }
+        let fdt = self.fdt;
+        loop {
+            match fdt.token(self.offset)? {
+                FDT_BEGIN_NODE => {
+                    if self.depth > MAX_DEPTH {
+                        return None;
+                    }
+                    let name = c_str(fdt.structure, self.offset + 4)?;
+                    let properties = align4(self.offset + 4 + name.len() + 1)?;
+                    let (address_cells, size_cells) = self.cells[self.depth];
+                    let node = Node { fdt, name, depth: self.depth, properties, address_cells, size_cells };
+
+                    // Alt düğümler bu düğümün #address-cells / #size-cells değerlerini kullanır.
+                    let mut child_cells = (DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS);
+                    let mut offset = properties;
+                    loop {
+                        match fdt.token(offset)? {
+                            FDT_PROP => {
+                                let (prop_name, value, next) = fdt.property_at(offset)?;
+                                match prop_name {
+                                    b"#address-cells" => child_cells.0 = be32(value, 0)?,
+                                    b"#size-cells" => child_cells.1 = be32(value, 0)?,
+                                    _ => {}
+                                }
+                                offset = next;
+                            }
+                            FDT_NOP => offset += 4,
+                            _ => break,
+                        }
+                    }
+                    self.depth += 1;
+                    if self.depth <= MAX_DEPTH {
+                        self.cells[self.depth] = child_cells;
+                    }
+                    self.offset = offset;
+                    return Some(node);
+                }
+                FDT_END_NODE => {
+                    self.depth = self.depth.checked_sub(1)?;
+                    self.offset += 4;
+                }
+                FDT_PROP => {
+                    self.offset = fdt.property_at(self.offset)?.2;
+                }
+                FDT_NOP => self.offset += 4,
+                FDT_END => return None,
+                _ => return None, // Bilinmeyen token
+            }
+        }
+    }
+}
+
+impl<'a> Node<'a> {
+    /// Düğüm adı (birim adresi dahil, örn. "virtio_mmio@10001000"). Kök düğümün adı boştur.
+    pub fn name(&self) -> &'a str {
+        core::str::from_utf8(self.name).unwrap_or("")
+    }
+
+    /// Kökten itibaren derinlik (kök = 0).
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    // Yol parçası ile eşleşiyor mu? "uart" hem "uart" hem "uart@1000" ile eşleşir.
+    fn name_matches(&self, part: &str) -> bool {
+        let name = self.name();
+        name == part || (!part.contains('@') && name.split('@').next() == Some(part))
+    }
+
+    /// Adı verilen özelliğin ham değeri.
+    pub fn property(&self, name: &str) -> Option<&'a [u8]> {
+        let mut offset = self.properties;
+        loop {
+            match self.fdt.token(offset)? {
+                FDT_PROP => {
+                    let (prop_name, value, next) = self.fdt.property_at(offset)?;
+                    if prop_name == name.as_bytes() {
+                        return Some(value);
+                    }
+                    offset = next;
+                }
+                FDT_NOP => offset += 4,
+                _ => return None,
+            }
+        }
+    }
+
+    /// Tek bir u32 hücresinden oluşan özellik.
+    pub fn property_u32(&self, name: &str) -> Option<u32> {
+        be32(self.property(name)?, 0)
+    }
+
+    /// "compatible" listesi (NUL ile ayrılmış dizgiler) verilen değeri içeriyor mu?
+    pub fn is_compatible(&self, compatible: &str) -> bool {
+        self.property("compatible").is_some_and(|list| {
+            list.split(|&b| b == 0).any(|entry| entry == compatible.as_bytes())
+        })
+    }
+
+    /// "reg" özelliğindeki index'inci (adres, boyut) çifti. Hücre sayıları üst düğümden alınır.
+    pub fn reg(&self, index: usize) -> Option<(u64, u64)> {
+        let reg = self.property("reg")?;
+        let (address_cells, size_cells) = (self.address_cells as usize, self.size_cells as usize);
+        if address_cells > 2 || size_cells > 2 {
+            return None; // 64 bitten geniş adresler desteklenmez
+        }
+        let entry_size = (address_cells + size_cells) * 4;
+        let start = index.checked_mul(entry_size)?;
+        let entry = reg.get(start..start.checked_add(entry_size)?)?;
+        let read_cells = |cells: &[u8]| cells.chunks(4).fold(0u64, |acc, cell| (acc << 32) | be32(cell, 0).unwrap_or(0) as u64);
+        let (address, size) = entry.split_at(address_cells * 4);
+        Some((read_cells(address), read_cells(size)))
+    }
+
+    /// "interrupts" özelliğinin ilk hücresi (PLIC kaynak numarası).
+    pub fn interrupt(&self) -> Option<u32> {
+        self.property_u32("interrupts")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    // Testler için küçük bir DTB üreticisi (dtc gerektirmez).
+    struct Builder {
+        structure: Vec<u8>,
+        strings: Vec<u8>,
+    }
+
+    impl Builder {
+        fn new() -> Self {
+            Builder { structure: Vec::new(), strings: Vec::new() }
+        }
+
+        fn token(&mut self, value: u32) -> &mut Self {
+            self.structure.extend_from_slice(&value.to_be_bytes());
+            self
+        }
+
+        fn pad(&mut self) {
+            while !self.structure.len().is_multiple_of(4) {
+                self.structure.push(0);
+            }
+        }
+
+        fn begin(&mut self, name: &str) -> &mut Self {
+            self.token(FDT_BEGIN_NODE);
+            self.structure.extend_from_slice(name.as_bytes());
+            self.structure.push(0);
+            self.pad();
+            self
+        }
+
+        fn end(&mut self) -> &mut Self {
+            self.token(FDT_END_NODE)
+        }
+
+        fn prop(&mut self, name: &str, value: &[u8]) -> &mut Self {
+            let name_offset = self.strings.len() as u32;
+            self.strings.extend_from_slice(name.as_bytes());
+            self.strings.push(0);
+            self.token(FDT_PROP).token(value.len() as u32).token(name_offset);
+            self.structure.extend_from_slice(value);
+            self.pad();
+            self
+        }
+
+        fn prop_cells(&mut self, name: &str, cells: &[u32]) -> &mut Self {
+            let value: Vec<u8> = cells.iter().flat_map(|cell| cell.to_be_bytes()).collect();
+            self.prop(name, &value)
+        }
+
+        fn finish(&mut self) -> Vec<u8> {
+            self.token(FDT_END);
+            let off_struct = HEADER_SIZE + 16; // Başlık + boş rezervasyon tablosu
+            let off_strings = off_struct + self.structure.len();
+            let total = off_strings + self.strings.len();
+            let header = [
+                FDT_MAGIC, total as u32, off_struct as u32, off_strings as u32, HEADER_SIZE as u32,
+                17, 16, 0, self.strings.len() as u32, self.structure.len() as u32,
+            ];
+            let mut blob: Vec<u8> = header.iter().flat_map(|field| field.to_be_bytes()).collect();
+            blob.extend_from_slice(&[0; 16]);
+            blob.extend_from_slice(&self.structure);
+            blob.extend_from_slice(&self.strings);
+            blob
+        }
+    }
+
+    // QEMU virt ağacının küçük bir kopyası.
+    fn virt_tree() -> Vec<u8> {
+        let mut builder = Builder::new();
+        builder
+            .begin("")
+            .prop_cells("#address-cells", &[2])
+            .prop_cells("#size-cells", &[2])
+            .begin("chosen").prop("bootargs", b"console=fb\0").end()
+            .begin("soc")
+                .prop_cells("#address-cells", &[2])
+                .prop_cells("#size-cells", &[2])
+                .begin("rtc@101000")
+                    .prop("compatible", b"google,goldfish-rtc\0")
+                    .prop_cells("reg", &[0, 0x101000, 0, 0x1000])
+                .end()
+                .begin("virtio_mmio@10008000")
+                    .prop("compatible", b"virtio,mmio\0")
+                    .prop_cells("reg", &[0, 0x1000_8000, 0, 0x1000])
+                    .prop_cells("interrupts", &[8])
+                .end()
+                .begin("virtio_mmio@10007000")
+                    .prop("compatible", b"virtio,mmio\0")
+                    .prop_cells("reg", &[0, 0x1000_7000, 0, 0x1000])
+                    .prop_cells("interrupts", &[7])
+                .end()
+            .end()
+            .begin("legacy")
+                .prop_cells("#address-cells", &[1])
+                .prop_cells("#size-cells", &[1])
+                .begin("uart@3000")
+                    .prop("compatible", b"vendor,uart\0ns16550a\0")
+                    .prop_cells("reg", &[0x3000, 0x100])
+                .end()
+            .end()
+            .end();
+        builder.finish()
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn finds_compatible_devices_with_parent_cells() {
+        let blob = virt_tree();
+        let fdt = Fdt::parse(&blob).unwrap();
+
+        let virtio: Vec<(u64, u64, Option<u32>)> = fdt
+            .compatible_nodes("virtio,mmio")
+            .map(|node| {
+                let (address, size) = node.reg(0).unwrap();
+                (address, size, node.interrupt())
+            })
+            .collect();
+        assert_eq!(virtio, [(0x1000_8000, 0x1000, Some(8)), (0x1000_7000, 0x1000, Some(7))]);
+
+        // Aynı compatible listesindeki ikinci değer de eşleşir; 1 hücrelik adresler okunur.
+        let uart = fdt.compatible_nodes("ns16550a").next().unwrap();
+        assert_eq!(uart.name(), "uart@3000");
+        assert_eq!(uart.reg(0), Some((0x3000, 0x100)));
+        assert_eq!(uart.reg(1), None);
+        assert!(fdt.compatible_nodes("ns16550").next().is_none()); // Önek eşleşmesi yok
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn finds_nodes_by_path() {
+        let blob = virt_tree();
+        let fdt = Fdt::parse(&blob).unwrap();
+        assert_eq!(fdt.find_node("/").map(|node| node.depth()), Some(0));
+        assert_eq!(fdt.find_node("/chosen").and_then(|node| node.property("bootargs")), Some(&b"console=fb\0"[..]));
+        assert_eq!(fdt.find_node("/soc/rtc").map(|node| node.name()), Some("rtc@101000"));
+        assert_eq!(fdt.find_node("/soc/virtio_mmio@10007000").and_then(|node| node.interrupt()), Some(7));
+        assert!(fdt.find_node("/rtc").is_none()); // Sadece doğru seviyede aranır
+        assert!(fdt.find_node("/soc/missing").is_none());
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn rejects_bad_headers() {
+        let blob = virt_tree();
+        let mut bad_magic = blob.clone();
+        bad_magic[0] = 0;
+        assert_eq!(Fdt::parse(&bad_magic).err(), Some(SahneError::InvalidParameter));
+        assert_eq!(Fdt::parse(&blob[..blob.len() - 1]).err(), Some(SahneError::InvalidParameter));
+
+        let mut future = blob.clone();
+        future[24..28].copy_from_slice(&18u32.to_be_bytes()); // last_comp_version
+        assert_eq!(Fdt::parse(&future).err(), Some(SahneError::NotSupported));
+    }
+
+    // Rastgele bozulmuş ağaçlarda dolaşmanın panik etmediğini ve bittiğini doğrular.
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn corrupted_trees_never_panic() {
+        let blob = virt_tree();
+        let mut state: u64 = 0x00FD_7B10_B5EE_D123;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..3000 {
+            let mut corrupted = blob.clone();
+            for _ in 0..1 + next() % 4 {
+                let position = (next() % corrupted.len() as u64) as usize;
+                corrupted[position] = next() as u8;
+            }
+            if let Ok(fdt) = Fdt::parse(&corrupted) {
+                for node in fdt.nodes().take(1000) {
+                    let _ = (node.name(), node.reg(0), node.interrupt(), node.is_compatible("virtio,mmio"));
+                }
+                let _ = fdt.find_node("/soc/rtc");
+            }
+        }
+    }
+}
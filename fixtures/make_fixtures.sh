@@ -0,0 +1,112 @@
+#!/bin/sh
+# fixtures/make_fixtures.sh
+# Host birim testlerinin kullandığı örnek dosyaları yeniden üretir.
+# Gerekenler: e2fsprogs (mke2fs, debugfs) ve python3.
+#
+#   ext2_small.img : 1 KB bloklu, 256 KB'lık küçük bir EXT2 imajı
+#                    /hello.txt, /bin/hello.sbxe ve boş /etc dizinini içerir.
+#                    /sparse.bin tek, çift ve üç dolaylı blok işaretçilerini kullanan seyrek bir dosyadır;
+#                    /links dizini 12 bloktan büyüktür (hello.txt'ye 64 sabit bağlantı);
+#                    /bin/sh ve /etc/motd sembolik bağlantılardır.
+#   ext2_groups.blocks : 256 bloklu 35 gruptan oluşan boş bir EXT2 imajının sıfır olmayan blokları;
+#                    grup tanımlayıcı tablosu iki bloğa yayılır (testler imajı bellekte açar).
+#   hello.sbxe     : text + data + bss bölümlü küçük bir SBXE yürütülebilir dosyası
+#   hello.spk      : iki dosyalı örnek bir .spk paketi
+
+set -e
+cd "$(dirname "$0")"
+
+# Tekrarlanabilir çıktı için sabit zaman ve UUID
+export E2FSPROGS_FAKE_TIME=1700000000
+
+python3 - <<'PY'
+import struct
+
+# --- hello.sbxe ---
+# Dosya başlığı: magic, architecture, header_size, entry_point_offset, num_sections, section_header_offset
+text = bytes([0x13, 0x00, 0x00, 0x00] * 4)          # 4 adet RISC-V 'nop'
+data = b"SahneBox\x00\x00\x00\x00"
+header_size = 20
+section_header_offset = header_size
+num_sections = 3
+sections_size = num_sections * 24
+text_offset = section_header_offset + sections_size
+data_offset = text_offset + len(text)
+sections = [
+    # type, flags, offset_in_file, size_in_file, size_in_memory, load_address_offset
+    (1, 0x5, text_offset, len(text), len(text), 0x000),  # TEXT (r-x)
+    (2, 0x3, data_offset, len(data), 32, 0x100),         # DATA (rw-), kalan 20 bayt sıfırlanır
+    (3, 0x3, 0, 0, 64, 0x200),                            # BSS  (rw-)
+]
+out = struct.pack("<IHHIII", 0x45584253, 1, header_size, 0x4, num_sections, section_header_offset)
+for s in sections:
+    out += struct.pack("<IIIIII", *s)
+out += text + data
+open("hello.sbxe", "wb").write(out)
+
+# --- hello.spk ---
+# Başlık: magic "SSPK", format sürümü, dosya sayısı, isim/sürüm uzunlukları ve metinleri,
+# ardından dosya girişleri (yol uzunluğu, offset, boyut, yol) ve dosya verileri.
+name, version = b"hello", b"1.0.0"
+files = [(b"/bin/hello", b"\x13\x00\x00\x00" * 8), (b"/etc/hello.conf", b"greeting=Merhaba\n")]
+head = struct.pack("<IHHBB", 0x4B505353, 1, len(files), len(name), len(version)) + name + version
+entries_size = sum(10 + len(p) for p, _ in files)
+offset = len(head) + entries_size
+entries, blobs = b"", b""
+for path, blob in files:
+    entries += struct.pack("<HII", len(path), offset, len(blob)) + path
+    blobs += blob
+    offset += len(blob)
+open("hello.spk", "wb").write(head + entries + blobs)
+
+# --- sparse.bin ---
+# Dolu bloklar: 0-19 (doğrudan + tek dolaylı), 300 (çift dolaylı), 65810 (üç dolaylı, son blok).
+# Bloğun i. baytı (blok * 7 + i) % 251'dir; aradaki bloklar boştur (seyrek).
+with open("sparse.bin", "wb") as f:
+    for block in list(range(20)) + [300, 65810]:
+        f.seek(block * 1024)
+        f.write(bytes((block * 7 + i) % 251 for i in range(1024)))
+PY
+
+printf 'Merhaba SahneBox!\n' > hello.txt
+
+rm -f ext2_small.img
+mke2fs -q -F -t ext2 -b 1024 -N 32 -m 0 -U 5a4e4542-4f58-4000-8000-000000000001 \
+    -E hash_seed=5a4e4542-4f58-4000-8000-000000000002 -L sahnetest ext2_small.img 256
+{
+    echo "mkdir bin"
+    echo "mkdir etc"
+    echo "mkdir links"
+    echo "write hello.txt hello.txt"
+    echo "write hello.sbxe bin/hello.sbxe"
+    echo "write sparse.bin sparse.bin"
+    # Uzun adlı girişler: blok başına 4 giriş, 64 giriş 16 blok tutar (debugfs dizini kendisi büyütmez).
+    for i in $(seq 1 15); do
+        echo "expand_dir links"
+    done
+    for i in $(seq -w 1 64); do
+        echo "ln hello.txt links/$(printf 'link%s-%0200d' "$i" 0)"
+    done
+    echo "sif hello.txt links_count 65"
+    # Sembolik bağlantılar: /bin/sh hızlı (hedef i-node'da), /etc/motd yavaş (90 baytlık hedef veri bloğunda).
+    echo "symlink bin/sh hello.sbxe"
+    echo "symlink etc/motd /$(printf './%.0s' $(seq 1 40))hello.txt"
+} | debugfs -w ext2_small.img >/dev/null
+
+rm -f hello.txt sparse.bin
+
+# Biçim: toplam blok sayısı (u32), ardından her sıfır olmayan blok için numarası (u32) ve 1024 baytı.
+rm -f ext2_groups.img
+mke2fs -q -F -t ext2 -b 1024 -g 256 -N 280 -m 0 -O ^resize_inode -U 5a4e4542-4f58-4000-8000-000000000003 \
+    -E hash_seed=5a4e4542-4f58-4000-8000-000000000004 -L sahnegroups ext2_groups.img 8960
+python3 - <<'PY'
+import struct
+image = open("ext2_groups.img", "rb").read()
+out = struct.pack("<I", len(image) // 1024)
+for block in range(len(image) // 1024):
+    data = image[block * 1024:(block + 1) * 1024]
+    if any(data):
+        out += struct.pack("<I", block) + data
+open("ext2_groups.blocks", "wb").write(out)
+PY
+rm -f ext2_groups.img
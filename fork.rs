@@ -4,6 +4,8 @@
 use alloc::sync::Arc; // Arc kullanılıyorsa
 use spin::Mutex; // Mutex kullanılıyorsa
 use crate::sched::{self, Task, TaskState, TaskContext}; // scheduler modülünü içeri aktar
+use crate::limits::TaskResources;
+use crate::sahne64::SahneError;
 
 // TODO: fork sistem çağrısı handler'ı buradan çağırabilir.
 // Sistem çağrı mekanizması (traps.rs, sys.rs) üzerinden erişilecektir.
@@ -29,7 +31,7 @@ pub extern "C" fn sys_fork() -> usize {
 
     // Yeni görev için yığın boyutu (mevcut görevle aynı olabilir veya farklı boyutta tahsis edilebilir)
     // TODO: Yığın boyutu stratejisini belirleyin.
-    let new_stack_size = 4096; // Örnek yığın boyutu (4KB)
+    let new_stack_size = 4096; // Örnek yığın boyutu (4KB); taşma koruma alanı Task::new tarafından ayrıca eklenir
 
     // Yeni bir görev yapısı oluştur
     // Yeni görevin başlayacağı adres...
@@ -42,13 +44,27 @@ pub extern "C" fn sys_fork() -> usize {
          Err(_) => {
              // Hata: Bellek tahsisi başarısız oldu.
               printk!("fork hatası: Bellek tahsisi başarısız!\n");
+             current_task.context.a0 = usize::MAX; // Parent'a hata döndür
              return usize::MAX; // Hata kodu
          }
     };
 
     // Parent görev bağlamını yeni görevin bağlamına kopyala (shallow copy)
-    // Bu, register durumunu kopyalar.
-    new_task.context = current_task.context;
+    // Bu, register durumunu kopyalar. Çocuğun tp'si kendi TLS bloğunda kalır.
+    new_task.copy_context_from(&current_task);
+
+    // Çocuk, ebeveynin kaynak sınırlarını ve ayrıcalığını devralır.
+    // Kullanım sayaçları çocuk için sıfırdan başlar (kendi hesabı olur).
+    let child_resources = TaskResources::inherit(&current_task.resources().lock());
+    match Arc::try_new(Mutex::new(child_resources)) {
+        Ok(resources) => new_task.set_resources(resources),
+        Err(_) => {
+            current_task.context.a0 = usize::MAX; // Bellek yetersiz; parent'a hata döndür
+            return usize::MAX;
+        }
+    }
+    new_task.set_privileged(current_task.is_privileged());
+    new_task.set_name(current_task.name());
 
     // Çocuk görev için a0 registerını 0 olarak ayarla (fork dönüş değeri convention)
     new_task.context.a0 = 0;
@@ -61,7 +77,13 @@ pub extern "C" fn sys_fork() -> usize {
     new_task.state = TaskState::Runnable;
 
     // Yeni görevi zamanlayıcının görev listesine ekle
-    let new_task_id_returned = sched::add_task(new_task);
+    let new_task_id_returned = match sched::add_task(new_task) {
+        Ok(id) => id,
+        Err(_) => {
+            current_task.context.a0 = usize::MAX; // Parent'a hata döndür
+            return usize::MAX;
+        }
+    };
 
     // TODO: Yığın belleğini kopyala?
     // Unix fork'un "copy-on-write" veya tam kopya semantiği burada çok zor.
@@ -84,7 +106,7 @@ pub extern "C" fn sys_fork() -> usize {
 // Doğrudan bir entry point fonksiyonunu alır ve yeni görev olarak başlatır.
 // TODO: fork yerine başlangıçta bu daha kullanışlı olabilir.
 #[allow(dead_code)] // Kullanılmıyorsa uyarı vermemesi için
-pub fn create_new_task(entry_point: fn(), stack_size: usize) -> Result<usize, &'static str> {
+pub fn create_new_task(name: &str, entry_point: fn(), stack_size: usize) -> Result<usize, SahneError> {
      // Yeni görev için bir ID ata
     let new_task_id = {
         let tasks_lock = sched::TASKS.lock();
@@ -95,10 +117,13 @@ pub fn create_new_task(entry_point: fn(), stack_size: usize) -> Result<usize, &'
     // entry_point fonksiyonunun adresini al
     let entry_address = entry_point as *const () as usize;
 
-    let new_task = Task::new(new_task_id, entry_address, stack_size)?;
+    let mut new_task = Task::new(new_task_id, entry_address, stack_size)?;
+    new_task.set_name(name);
+    // Çekirdek görevleri ayrıcalıklıdır.
+    new_task.set_privileged(true);
 
     // Yeni görevi zamanlayıcının görev listesine ekle
-    let new_task_id_returned = sched::add_task(new_task);
+    let new_task_id_returned = sched::add_task(new_task)?;
 
     Ok(new_task_id_returned)
 }
@@ -0,0 +1,302 @@
+// mm/free_list.rs
+// Serbest Liste Tahsis Edicisi
+// Çekirdek heap'inin altındaki saf tahsis mantığı. Donanıma veya çekirdek API'sine
+// bağımlı olmadığı için host üzerinde de derlenip test edilebilir.
+
+use core::alloc::Layout;
+use core::ptr;
+
+// Serbest bellek bloğunu temsil eden yapı.
+// Her serbest blok, bir sonraki serbest bloğa işaret eder.
+pub struct FreeListNode {
+    size: usize,
+    next: Option<*mut FreeListNode>,
+}
+
+// Basit Serbest Liste Tahsis Edici Yapısı.
+// Bir Mutex içinde tutulur çünkü GlobalAlloc trait'i Sync gerektirir.
+pub struct FreeListAllocator {
+    head: Option<*mut FreeListNode>, // Serbest listesinin başı
+}
+
+unsafe impl Sync for FreeListAllocator {} // Bu basit tahsis edici Sync'tir (Mutex ile korunur)
+
+impl Default for FreeListAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FreeListAllocator {
+    // Boş bir tahsis edici oluşturur. init() ile bellek eklenmelidir.
+    pub const fn new() -> Self {
+        FreeListAllocator { head: None }
+    }
+
+    /// Tahsis ediciye bellek ekler (heap alanını başlatır).
+    /// İlk başta tüm boş alanı tek bir blok olarak listeye ekler.
+    ///
+    /// # Safety
+    /// `[heap_start_addr, heap_start_addr + heap_size)` yazılabilir, başka hiçbir şeyin kullanmadığı
+    /// ve tahsis edici yaşadığı sürece geçerli kalan bir bellek alanı olmalıdır.
+    pub unsafe fn init(&mut self, heap_start_addr: usize, heap_size: usize) {
+        if heap_size == 0 {
+            return; // Boş heap
+        }
+
+        // Bellek alanının layout gereksinimlerini karşıladığından emin olun.
+        // Serbest liste düğümü kadar veya daha büyük ve hizalanmış olmalı.
+        if heap_size < core::mem::size_of::<FreeListNode>() || !heap_start_addr.is_multiple_of(core::mem::align_of::<FreeListNode>()) {
+              // printk!("Bellek alanı tahsis edici için uygun değil! Başlangıç: {:#x}, Boyut: {}\n", heap_start_addr, heap_size);
+             // Hata durumunda init yapılamaz.
+             return;
+        }
+
+
+        // Tüm alanı tek bir serbest blok olarak ekle
+        let initial_node = heap_start_addr as *mut FreeListNode;
+        ptr::write_volatile(initial_node, FreeListNode { size: heap_size, next: None });
+        self.head = Some(initial_node);
+
+         // printk!("Heap başlatıldı: Başlangıç {:#x}, Boyut {}\n", heap_start_addr, heap_size);
+    }
+
+    /// Bellek tahsis etme (Layout'a uygun boyutta ve hizalamada). Yer yoksa null döner.
+    ///
+    /// # Safety
+    /// Tahsis edici `init` ile geçerli bir alan üzerinde başlatılmış olmalıdır.
+    pub unsafe fn allocate(&mut self, layout: Layout) -> *mut u8 {
+         // printk!("Bellek tahsis isteği: Boyut {}, Hizalama {}\n", layout.size(), layout.align());
+
+        let mut current = &mut self.head;
+        while let Some(node_ptr) = *current {
+            let node = &mut *node_ptr;
+            let node_start_addr = node_ptr as usize;
+
+            // Bu blok, istenen Layout'u karşılamak için kullanılabilir mi kontrol et.
+            // Hem boyut (layout.size() kadar yer olmalı) hem de hizalama (blok içinde hizalanmış bir adres bulunmalı) kontrol edilir.
+            let mut allocation_start_offset_in_node = (node_ptr as *const u8).align_offset(layout.align());
+            // Önde kalan serbest kısım düğüm başlığını (FreeListNode) tutamayacak kadar küçükse
+            // başlık tahsis edilen alanla çakışır; bir sonraki hizalı adrese kaydırılır.
+            while allocation_start_offset_in_node != 0 && allocation_start_offset_in_node < core::mem::size_of::<FreeListNode>() {
+                allocation_start_offset_in_node += layout.align();
+            }
+            let allocation_start_addr = node_start_addr + allocation_start_offset_in_node;
+            let allocation_end_addr = allocation_start_addr + layout.size();
+
+            if allocation_end_addr <= node_start_addr + node.size {
+                // Bu blok yeterli alana sahip ve içinde hizalanmış bir yer var.
+                // Bloğu kullanacağız.
+
+                // Bloğun başından tahsis edilen yerden önceki kısım (varsa)
+                let prev_free_size = allocation_start_addr - node_start_addr;
+                // Tahsis edilen yerden sonraki kısım (varsa)
+                let next_free_size = (node_start_addr + node.size) - allocation_end_addr;
+
+                if prev_free_size > 0 && next_free_size > 0 {
+                    // Blok üç parçaya ayrılıyor: önceki serbest, tahsis edilen, sonraki serbest
+                    // Önceki serbest bloğu güncelle
+                    node.size = prev_free_size;
+                    // Sonraki serbest bloğu listeye ekle
+                    let next_free_node_ptr = allocation_end_addr as *mut FreeListNode;
+                     if next_free_size >= core::mem::size_of::<FreeListNode>() && allocation_end_addr.is_multiple_of(core::mem::align_of::<FreeListNode>()) {
+                         ptr::write_volatile(next_free_node_ptr, FreeListNode { size: next_free_size, next: node.next });
+                         node.next = Some(next_free_node_ptr); // Önceki bloğun next'i yeni bloğa işaret etsin
+                     } else {
+                           // printk!("Hata: Kalan küçük blok FreeListNode için yeterli değil/hizalanmış değil. Kayıp bellek.\n");
+                          // Bu küçük kalan alan kaybolur (fragmentasyon).
+                          // node.next değişmez.
+                     }
+
+
+                } else if prev_free_size > 0 {
+                    // Blok ikiye ayrılıyor: önceki serbest, tahsis edilen
+                    // Önceki serbest bloğun boyutunu güncelle. next'i aynı kalır.
+                    node.size = prev_free_size;
+                } else if next_free_size > 0 {
+                    // Blok ikiye ayrılıyor: tahsis edilen, sonraki serbest
+                    // Bu durumda mevcut düğüm tahsis edildiği için listeden çıkarılmalı.
+                    // Sonraki serbest blok düğümü, tahsis edilen alanın hemen arkasından başlar.
+                    let next_free_node_ptr = allocation_end_addr as *mut FreeListNode;
+                     if next_free_size >= core::mem::size_of::<FreeListNode>() && allocation_end_addr.is_multiple_of(core::mem::align_of::<FreeListNode>()) {
+                        ptr::write_volatile(next_free_node_ptr, FreeListNode { size: next_free_size, next: node.next });
+                        // Mevcut düğümü listeden çıkar
+                        *current = Some(next_free_node_ptr); // current.head veya current.next bir sonraki bloğa işaret etsin
+                     } else {
+                         // printk!("Hata: Kalan küçük blok FreeListNode için yeterli değil/hizalanmış değil. Kayıp bellek.\n");
+                         // Mevcut düğümü listeden çıkar. next'i aynı kalır.
+                         *current = node.next;
+                     }
+
+                } else {
+                    // Tüm blok tahsis ediliyor. Bu düğüm listeden çıkarılmalı.
+                    *current = node.next;
+                }
+
+                // Tahsis edilen alanın başlangıç adresini döndür.
+                 // printk!("Tahsis edildi: {:#x}, Boyut: {}\n", allocation_start_addr, layout.size());
+                return allocation_start_addr as *mut u8;
+            } else {
+                // Bu blok uygun değil, bir sonraki bloğa geç
+                current = &mut node.next;
+            }
+        }
+
+        // Uygun serbest blok bulunamadı
+         // printk!("Tahsis hatası: Yeterli serbest bellek yok.\n");
+        ptr::null_mut() // Başarısızlık durumunda null pointer dön
+    }
+
+    /// Belleği serbest bırakma.
+    /// TODO: Birleştirme (coalescing) implementasyonu eklenmeli.
+    /// Serbest bırakılan bloğu, serbest listedeki bitişik bloklarla birleştirmek fragmentasyonu azaltır.
+    ///
+    /// # Safety
+    /// `ptr` bu tahsis ediciden aynı `layout` ile alınmış ve henüz serbest bırakılmamış olmalıdır.
+    /// Blok bir `FreeListNode` tutacak kadar büyük ve onun hizasında olmalıdır.
+    pub unsafe fn deallocate(&mut self, ptr: *mut u8, layout: Layout) {
+         // printk!("Bellek serbest bırakma: {:#p}, Boyut: {}\n", ptr, layout.size());
+
+        if ptr.is_null() { return; } // Null pointer serbest bırakılamaz
+
+        // Serbest bırakılan bloğun bilgisi (geçici olarak FreeListNode gibi davranır)
+        // Layout'tan gelen boyut, tahsis edilen gerçek boyutu temsil etmeyebilir
+        // Tahsis edicide kaydedilen gerçek boyutu bilmek gerekir (karmaşık)
+        // Basitlik için, Layout.size()'ı kullanıyoruz, ama bu doğru birleştirme için yeterli değil.
+        // Doğru birleştirme için tahsis edilen bloğun boyutunu bir şekilde takip etmek gerekir.
+
+        // Serbest bırakılan bloğu FreeListNode olarak yaz (geçici olarak)
+        let freed_node_ptr = ptr as *mut FreeListNode;
+        // Boyut bilgisi burada doğru olmayabilir!
+        ptr::write_volatile(freed_node_ptr, FreeListNode { size: layout.size(), next: self.head }); // Listenin başına ekle (basit)
+
+        self.head = Some(freed_node_ptr);
+
+        // TODO: Burada bitişik serbest blokları birleştirme (coalescing) mantığı eklenmeli.
+        // Serbest bırakılan bloğun hem önceki hem de sonraki bellek adreslerindeki serbest bloklarla
+        // adreslerine göre sıralı listede tutularak birleştirilmesi gerekir.
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARENA_SIZE: usize = 1024;
+
+    // Tahsis edici testleri için global heap'ten bağımsız, hizalı bir bellek alanı.
+    // Host'ta testler paralel çalıştığı için her test kendi alanını yığında tutar.
+    #[repr(align(16))]
+    struct Arena([u8; ARENA_SIZE]);
+
+    // Verilen alan üzerinde sıfırdan başlatılmış bir tahsis edici döndürür.
+    fn fresh_allocator(arena: &mut Arena) -> (FreeListAllocator, usize) {
+        let start = arena.0.as_mut_ptr() as usize;
+        let mut allocator = FreeListAllocator::new();
+        unsafe { allocator.init(start, ARENA_SIZE); }
+        (allocator, start)
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn allocation_is_aligned_and_inside_heap() {
+        let mut arena = Arena([0; ARENA_SIZE]);
+        let (mut allocator, start) = fresh_allocator(&mut arena);
+        let layout = Layout::from_size_align(100, 64).unwrap();
+        let ptr = unsafe { allocator.allocate(layout) } as usize;
+        assert!(ptr != 0);
+        assert_eq!(ptr % 64, 0);
+        assert!(ptr >= start && ptr + 100 <= start + ARENA_SIZE);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn allocations_do_not_overlap() {
+        let mut arena = Arena([0; ARENA_SIZE]);
+        let (mut allocator, _) = fresh_allocator(&mut arena);
+        let layout = Layout::from_size_align(64, 8).unwrap();
+        let first = unsafe { allocator.allocate(layout) } as usize;
+        let second = unsafe { allocator.allocate(layout) } as usize;
+        assert!(first != 0 && second != 0);
+        assert!(first + 64 <= second || second + 64 <= first);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn oversized_allocation_returns_null() {
+        let mut arena = Arena([0; ARENA_SIZE]);
+        let (mut allocator, _) = fresh_allocator(&mut arena);
+        let layout = Layout::from_size_align(ARENA_SIZE * 2, 8).unwrap();
+        assert!(unsafe { allocator.allocate(layout) }.is_null());
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn freed_block_is_reused() {
+        let mut arena = Arena([0; ARENA_SIZE]);
+        let (mut allocator, _) = fresh_allocator(&mut arena);
+        let layout = Layout::from_size_align(256, 8).unwrap();
+        let first = unsafe { allocator.allocate(layout) };
+        unsafe { allocator.deallocate(first, layout); }
+        let second = unsafe { allocator.allocate(layout) };
+        assert_eq!(first, second);
+    }
+
+    // Testler için küçük, tekrarlanabilir sözde rastgele üreteç (xorshift64).
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+    }
+
+    // Özellik testi: rastgele tahsis/iade dizilerinde her canlı blok hizalı ve alanın içinde olmalı,
+    // diğer canlı bloklarla çakışmamalı ve içeriği sonraki tahsis/iadelerle bozulmamalıdır.
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn random_sequences_keep_live_blocks_intact() {
+        const SLOTS: usize = 12;
+        for seed in 1..=64u64 {
+            let mut arena = Arena([0; ARENA_SIZE]);
+            let (mut allocator, start) = fresh_allocator(&mut arena);
+            let mut rng = XorShift(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let mut live: [Option<(usize, Layout, u8)>; SLOTS] = [None; SLOTS];
+
+            for step in 0..512usize {
+                let slot = rng.below(SLOTS);
+                if let Some((addr, layout, fill)) = live[slot].take() {
+                    let block = unsafe { core::slice::from_raw_parts(addr as *const u8, layout.size()) };
+                    assert!(block.iter().all(|&byte| byte == fill), "seed {}: blok {:#x} bozuldu", seed, addr);
+                    unsafe { allocator.deallocate(addr as *mut u8, layout); }
+                    continue;
+                }
+
+                // İade edilen blok bir FreeListNode'a dönüşür; çekirdek de en az bu boyutu ister.
+                let size = core::mem::size_of::<FreeListNode>() + 8 * rng.below(10);
+                let align = 8 << rng.below(4);
+                let layout = Layout::from_size_align(size, align).unwrap();
+                let addr = unsafe { allocator.allocate(layout) } as usize;
+                if addr == 0 {
+                    continue; // Parçalanma nedeniyle yer kalmamış olabilir
+                }
+                assert!(addr.is_multiple_of(align), "seed {}: hizasız blok", seed);
+                assert!(addr >= start && addr + size <= start + ARENA_SIZE, "seed {}: alan dışı blok", seed);
+                for (other, other_layout, _) in live.iter().flatten() {
+                    assert!(addr + size <= *other || other + other_layout.size() <= addr, "seed {}: çakışan bloklar", seed);
+                }
+                let fill = (step % 251) as u8 + 1;
+                unsafe { ptr::write_bytes(addr as *mut u8, fill, size); }
+                live[slot] = Some((addr, layout, fill));
+            }
+        }
+    }
+}
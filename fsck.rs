@@ -0,0 +1,1216 @@
+// filesystem/ext/fsck.rs
+// EXT2 Tutarlılık Denetleyicisi (fsck)
+// i-node'ları, dizinleri ve bitmap'leri tarar: geçersiz ve çift ayrılmış bloklar, dizin kayıtları,
+// ".." girişleri, bağlantı sayıları, yetim i-node'lar, grup ve süper blok serbest sayıları.
+//
+// Rapor kipinde diske hiç yazılmaz; bağlı (başka görevlerce okunan) bir dosya sisteminde de çalışır.
+// Onarım kipinde sorunlar e2fsck'in seçtiği yoldan düzeltilir; o sırada dosya sistemine başka bir görev
+// yazmamalıdır. Açılışta check_at_boot, süper bloğa (s_state, bağlama sayısı, denetim aralığı) bakıp
+// gerekiyorsa onarım kipinde çalışır.
+//
+// Geçişler: 1) i-node'lar ve blok ağaçları, 1B) çift ayrılmış blokların kopyalanması, 2) dizin girişleri,
+// 5) bitmap'ler ve sayılar, 3) dizin bağlantısı, 4) bağlantı sayıları ve yetim i-node'lar.
+// 5. geçiş 3 ve 4'ten önce çalışır: lost+found'a bağlarken yapılan ayırmalar doğru bitmap'leri kullanır.
+
+use alloc::format;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::{
+    entry_header, file_type_of, read_struct, unix_time, BlockDevice, BlockMapCache, ExtFilesystem, Inode,
+    DIR_ENTRY_HEADER_SIZE, EXT2_ERROR_FS, EXT2_FEATURE_INCOMPAT_FILETYPE, EXT2_FEATURE_RO_COMPAT_SPARSE_SUPER,
+    EXT2_FT_DIR, EXT2_FT_REG_FILE, EXT2_FT_SYMLINK, EXT2_FT_UNKNOWN, EXT2_IND_BLOCK, EXT2_ROOT_INODE, EXT2_VALID_FS,
+};
+use crate::sahne64::buffer::zeroed_buffer;
+use crate::sahne64::SahneError;
+
+const EXT2_RESIZE_INODE: u32 = 7; // resize_inode: ayrılmış grup tanımlayıcı bloklarını tutar
+const LOST_AND_FOUND: &str = "lost+found";
+
+/// Denetim kipi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Sadece rapor: diske yazılmaz.
+    Report,
+    /// Bulunan sorunlar onarılır (cihaz yazılabilir olmalı).
+    Repair,
+}
+
+/// Denetimde bulunan bir sorun.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    /// Kök dizin yok veya dizin değil (onarılamaz; denetim durur).
+    RootMissing,
+    /// Türü bilinmeyen ama kullanımda görünen i-node (silinir).
+    BadInode { inode: u32 },
+    /// Dosya sistemi dışını veya meta veriyi (bitmap, i-node tablosu...) gösteren blok işaretçisi (silinir).
+    IllegalBlock { inode: u32, block: u32 },
+    /// Blok başka bir i-node'a (veya aynı i-node'da iki kez) ayrılmış; bu i-node'a kopyası verilir.
+    DuplicateBlock { inode: u32, block: u32 },
+    /// i_blocks sayılan bloklarla uyuşmuyor (512 baytlık birimler).
+    BlockCount { inode: u32, stored: u32, counted: u32 },
+    /// Dizin bloğunun kayıt zinciri bozuk; blok boşaltılır, içindeki i-node'lar yetim kalır.
+    BadDirectoryBlock { dir: u32, block: u32 },
+    /// Dizinin ilk iki girişi "." ve ".." değil.
+    BadDotEntries { dir: u32 },
+    /// Giriş geçersiz veya kullanılmayan bir i-node'u gösteriyor (giriş silinir).
+    BadEntry { dir: u32, inode: u32 },
+    /// Girişteki dosya türü i-node'un türüyle uyuşmuyor.
+    FileType { dir: u32, inode: u32 },
+    /// Dizin ikinci kez bağlanmış (dizinlere sabit bağlantı olamaz; giriş silinir).
+    ExtraDirLink { dir: u32, inode: u32 },
+    /// Dizinin ".." girişi bağlı olduğu dizini göstermiyor.
+    BadParent { dir: u32, parent: u32 },
+    /// Kökten ulaşılamayan i-node; lost+found'a "#<i-node>" adıyla bağlanır (boş dosyalar silinir).
+    Unattached { inode: u32 },
+    /// i_links_count sayılan dizin girişleriyle uyuşmuyor.
+    LinkCount { inode: u32, stored: u16, counted: u16 },
+    /// Blok bitmap'i kullanılan bloklarla uyuşmuyor (farklı bit sayısı).
+    BlockBitmap { group: u32, differences: u32 },
+    /// i-node bitmap'i kullanılan i-node'larla uyuşmuyor.
+    InodeBitmap { group: u32, differences: u32 },
+    /// Grup tanımlayıcısındaki serbest blok/i-node veya dizin sayısı yanlış.
+    GroupCounts { group: u32 },
+    /// Süper bloktaki serbest blok/i-node sayısı yanlış.
+    SuperblockCounts,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Problem::RootMissing => write!(f, "kök dizin yok"),
+            Problem::BadInode { inode } => write!(f, "i-node {}: türü geçersiz", inode),
+            Problem::IllegalBlock { inode, block } => write!(f, "i-node {}: geçersiz blok {}", inode, block),
+            Problem::DuplicateBlock { inode, block } => write!(f, "i-node {}: blok {} başka bir i-node'da da kullanılıyor", inode, block),
+            Problem::BlockCount { inode, stored, counted } => write!(f, "i-node {}: i_blocks {}, sayılan {}", inode, stored, counted),
+            Problem::BadDirectoryBlock { dir, block } => write!(f, "dizin {}: blok {} bozuk", dir, block),
+            Problem::BadDotEntries { dir } => write!(f, "dizin {}: \".\" veya \"..\" girişi eksik", dir),
+            Problem::BadEntry { dir, inode } => write!(f, "dizin {}: giriş kullanılmayan i-node {}'u gösteriyor", dir, inode),
+            Problem::FileType { dir, inode } => write!(f, "dizin {}: i-node {} girişinin dosya türü yanlış", dir, inode),
+            Problem::ExtraDirLink { dir, inode } => write!(f, "dizin {}: dizin {} ikinci kez bağlanmış", dir, inode),
+            Problem::BadParent { dir, parent } => write!(f, "dizin {}: \"..\" üst dizin {}'i göstermiyor", dir, parent),
+            Problem::Unattached { inode } => write!(f, "i-node {}: hiçbir dizinde bağlı değil", inode),
+            Problem::LinkCount { inode, stored, counted } => write!(f, "i-node {}: bağlantı sayısı {}, sayılan {}", inode, stored, counted),
+            Problem::BlockBitmap { group, differences } => write!(f, "grup {}: blok bitmap'inde {} fark", group, differences),
+            Problem::InodeBitmap { group, differences } => write!(f, "grup {}: i-node bitmap'inde {} fark", group, differences),
+            Problem::GroupCounts { group } => write!(f, "grup {}: serbest sayılar yanlış", group),
+            Problem::SuperblockCounts => write!(f, "süper blok: serbest sayılar yanlış"),
+        }
+    }
+}
+
+/// Bir sorun ve onarılıp onarılmadığı.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    pub problem: Problem,
+    pub fixed: bool,
+}
+
+/// Denetim sonucu.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub findings: Vec<Finding>,
+    pub used_inodes: u32,
+    pub used_blocks: u32,
+}
+
+impl Report {
+    /// Hiç sorun bulunmadı mı?
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Onarılmadan kalan sorun sayısı.
+    pub fn unfixed(&self) -> usize {
+        self.findings.iter().filter(|finding| !finding.fixed).count()
+    }
+}
+
+/// Açılışta denetim gerekiyor mu? Dosya sistemi temiz kapatılmamışsa (s_state), hatalı işaretliyse,
+/// bağlama sayısı s_max_mnt_count'a ulaştıysa veya denetim aralığı dolduysa true döner.
+pub fn needs_check<D: BlockDevice>(fs: &ExtFilesystem<D>) -> bool {
+    if fs.state & EXT2_VALID_FS == 0 || fs.state & EXT2_ERROR_FS != 0 {
+        return true;
+    }
+    if fs.max_mount_count > 0 && fs.mount_count >= fs.max_mount_count as u16 {
+        return true;
+    }
+    let now = unix_time();
+    fs.check_interval != 0 && now != 0 && now >= fs.last_check.saturating_add(fs.check_interval)
+}
+
+/// Açılış denetimi: gerekiyorsa dosya sistemini onarım kipinde denetler, gerekmiyorsa None döner.
+pub fn check_at_boot<D: BlockDevice>(fs: &mut ExtFilesystem<D>) -> Result<Option<Report>, SahneError> {
+    if !needs_check(fs) {
+        return Ok(None);
+    }
+    check(fs, Mode::Repair).map(Some)
+}
+
+/// Dosya sistemini denetler; onarım kipinde bulunan sorunları düzeltir. Onarımdan sonra sorun kalmadıysa
+/// süper blok temiz işaretlenir ve bağlama sayısı sıfırlanır; kaldıysa hatalı işaretlenir.
+/// Grup tanımlayıcıları dosya sistemi dışını gösteriyorsa InvalidParameter döner (onarılamaz).
+pub fn check<D: BlockDevice>(fs: &mut ExtFilesystem<D>, mode: Mode) -> Result<Report, SahneError> {
+    if mode == Mode::Repair {
+        fs.check_writable()?;
+    }
+    let mut checker = Checker::new(fs, mode == Mode::Repair)?;
+    checker.run()?;
+    let report = checker.report;
+
+    if mode == Mode::Repair {
+        if report.unfixed() == 0 {
+            fs.state = (fs.state | EXT2_VALID_FS) & !EXT2_ERROR_FS;
+            fs.mount_count = 0;
+        } else {
+            fs.state = (fs.state & !EXT2_VALID_FS) | EXT2_ERROR_FS;
+        }
+        fs.last_check = unix_time();
+        fs.write_mount_state()?;
+        fs.dirty = false; // Sonraki yazma süper bloğu yeniden "temiz değil" işaretler
+    }
+    Ok(report)
+}
+
+// Bir dizin i-node'unun bağlantı bilgisi.
+#[derive(Clone, Copy)]
+struct DirInfo {
+    inode: u32,
+    parent: u32, // Dizini içeren (ilk) dizin; 0 bilinmiyor
+    dotdot: u32, // ".." girişinin gösterdiği i-node; 0 yok
+}
+
+// Bir i-node'un (geçiş 1'de) zaten sahiplenilmiş bir bloğu gösteren işaretçisi.
+struct DuplicateClaim {
+    inode: u32,
+    block: u32,
+    finding: usize, // Report.findings içindeki sıra
+}
+
+struct Checker<'a, D: BlockDevice> {
+    fs: &'a mut ExtFilesystem<D>,
+    repair: bool,
+    report: Report,
+    metadata: Vec<u8>,    // Süper blok kopyaları, tanımlayıcılar, bitmap'ler ve i-node tabloları (blok başına bit)
+    used_blocks: Vec<u8>, // i-node'ların sahiplendiği bloklar (blok başına bit)
+    types: Vec<u8>,       // i-node başına dosya türü (EXT2_FT_*); EXT2_FT_UNKNOWN kullanılmıyor demek
+    refs: Vec<u16>,       // i-node başına sayılan dizin girişi ("." ve ".." dahil)
+    dirs: Vec<DirInfo>,   // Kullanılan dizinler (i-node numarasına göre sıralı)
+    duplicates: Vec<DuplicateClaim>,
+    ea_blocks: Vec<u32>,  // Genişletilmiş öznitelik blokları (i-node'lar arasında paylaşılabilir)
+    lost_and_found: u32,  // 0: henüz aranmadı
+    next_free: u32,       // Blok kopyalarken aramanın başlayacağı blok
+}
+
+impl<'a, D: BlockDevice> Checker<'a, D> {
+    fn new(fs: &'a mut ExtFilesystem<D>, repair: bool) -> Result<Self, SahneError> {
+        let inode_slots = fs.total_inode_count as usize + 1; // i-node numaraları 1'den başlar
+        let mut types = Vec::new();
+        types.try_reserve_exact(inode_slots).map_err(|_| SahneError::OutOfMemory)?;
+        types.resize(inode_slots, EXT2_FT_UNKNOWN);
+        let mut refs = Vec::new();
+        refs.try_reserve_exact(inode_slots).map_err(|_| SahneError::OutOfMemory)?;
+        refs.resize(inode_slots, 0);
+        let bitmap_len = (fs.total_block_count as usize).div_ceil(8);
+        let next_free = fs.first_data_block;
+        let mut checker = Checker {
+            metadata: zeroed_buffer(bitmap_len)?,
+            used_blocks: zeroed_buffer(bitmap_len)?,
+            fs,
+            repair,
+            report: Report::default(),
+            types,
+            refs,
+            dirs: Vec::new(),
+            duplicates: Vec::new(),
+            ea_blocks: Vec::new(),
+            lost_and_found: 0,
+            next_free,
+        };
+        checker.mark_metadata()?;
+        Ok(checker)
+    }
+
+    fn run(&mut self) -> Result<(), SahneError> {
+        self.pass1()?;
+        if self.types[EXT2_ROOT_INODE as usize] != EXT2_FT_DIR {
+            return self.record(Problem::RootMissing, false);
+        }
+        self.pass1b()?;
+        // Geçiş 1 ve 1B blok işaretçilerini değiştirmiş olabilir.
+        *self.fs.block_map.get_mut() = BlockMapCache::new();
+        self.pass2()?;
+        self.pass5()?;
+        self.pass3()?;
+        self.pass4()
+    }
+
+    fn record(&mut self, problem: Problem, fixed: bool) -> Result<(), SahneError> {
+        self.report.findings.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+        self.report.findings.push(Finding { problem, fixed });
+        Ok(())
+    }
+
+    // Her grubun meta veri bloklarını işaretler. Tanımlayıcılar grup dışını gösteriyorsa InvalidParameter.
+    fn mark_metadata(&mut self) -> Result<(), SahneError> {
+        let fs = &*self.fs;
+        let descriptor_blocks = (fs.group_count as usize * core::mem::size_of::<super::GroupDescriptor>()).div_ceil(fs.block_size as usize) as u32;
+        let table_blocks = (fs.inodes_per_group as usize * fs.inode_size as usize).div_ceil(fs.block_size as usize) as u32;
+        let sparse = fs.feature_ro_compat & EXT2_FEATURE_RO_COMPAT_SPARSE_SUPER != 0;
+        for group in 0..fs.group_count {
+            let start = fs.first_data_block + group * fs.blocks_per_group;
+            let end = start + fs.blocks_in_group(group);
+            if !sparse || has_superblock_copy(group) {
+                let copy = 1 + descriptor_blocks + fs.reserved_gdt_blocks;
+                for block in start..(start + copy).min(end) {
+                    set_bit(&mut self.metadata, block);
+                }
+            }
+            let descriptor = fs.group_descriptors[group as usize];
+            let table = descriptor.bg_inode_table;
+            let table_end = table.checked_add(table_blocks).ok_or(SahneError::InvalidParameter)?;
+            for block in [descriptor.bg_block_bitmap, descriptor.bg_inode_bitmap] {
+                if block < start || block >= end {
+                    return Err(SahneError::InvalidParameter);
+                }
+                set_bit(&mut self.metadata, block);
+            }
+            if table < start || table_end > end {
+                return Err(SahneError::InvalidParameter);
+            }
+            for block in table..table_end {
+                set_bit(&mut self.metadata, block);
+            }
+        }
+        Ok(())
+    }
+
+    // --- Geçiş 1: i-node'lar ve blok ağaçları ---
+
+    // i-node tabloları blok blok okunur: her tablo bloğu bir kez okunur ve içindeki i-node'lar sırayla
+    // denetlenir (i-node başına ayrı blok okuması yapılmaz).
+    fn pass1(&mut self) -> Result<(), SahneError> {
+        let inode_size = self.fs.inode_size as usize;
+        let inodes_per_block = self.fs.block_size as usize / inode_size;
+        for group in 0..self.fs.group_count {
+            let first = group * self.fs.inodes_per_group + 1;
+            let last = (first + self.fs.inodes_per_group - 1).min(self.fs.total_inode_count);
+            let mut block = self.fs.group_descriptors[group as usize].bg_inode_table;
+            let mut number = first;
+            while number <= last {
+                let data = self.fs.read_block(block)?;
+                for offset in (0..inodes_per_block).map(|slot| slot * inode_size) {
+                    if number > last {
+                        break;
+                    }
+                    self.check_inode(number, read_struct(&data, offset)?)?;
+                    number += 1;
+                }
+                block += 1;
+            }
+        }
+        Ok(())
+    }
+
+    // Tek bir i-node'u denetler: blok ağacını sahiplenir, türünü ve blok sayısını doğrular.
+    fn check_inode(&mut self, number: u32, mut inode: Inode) -> Result<(), SahneError> {
+        let sectors_per_block = self.fs.block_size / 512;
+        let reserved = number < self.fs.first_inode && number != EXT2_ROOT_INODE;
+        if reserved {
+            // Ayrılmış i-node'ların blokları sahiplenilir; içerikleri denetlenmez.
+            if number == EXT2_RESIZE_INODE {
+                // Çift dolaylı bloğun gösterdiği bloklar zaten meta veri (ayrılmış tanımlayıcı blokları).
+                let i_block = inode.i_block;
+                if i_block[EXT2_IND_BLOCK + 1] != 0 {
+                    self.claim(number, i_block[EXT2_IND_BLOCK + 1])?;
+                }
+            } else if inode.i_links_count > 0 || inode.i_blocks > 0 {
+                let mut changed = false;
+                self.walk_inode(number, &mut inode, &mut changed)?;
+                if changed {
+                    self.fs.write_inode(number, &inode)?;
+                }
+            }
+            return Ok(());
+        }
+        if inode.i_links_count == 0 {
+            return Ok(()); // Serbest (veya silinmiş) i-node
+        }
+
+        let file_type = file_type_of(&inode);
+        if file_type == EXT2_FT_UNKNOWN {
+            if self.repair {
+                inode.i_links_count = 0;
+                inode.i_dtime = unix_time().max(self.fs.total_inode_count);
+                self.fs.write_inode(number, &inode)?;
+            }
+            self.record(Problem::BadInode { inode: number }, self.repair)?;
+            return Ok(());
+        }
+        self.types[number as usize] = file_type;
+        if file_type == EXT2_FT_DIR {
+            self.dirs.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+            self.dirs.push(DirInfo { inode: number, parent: 0, dotdot: 0 });
+        }
+
+        // Aygıt, FIFO, soket ve hızlı bağlantılarda i_block blok işaretçisi değildir.
+        let has_blocks = file_type == EXT2_FT_REG_FILE
+            || file_type == EXT2_FT_DIR
+            || (file_type == EXT2_FT_SYMLINK && !inode.is_fast_symlink());
+        let mut changed = false;
+        let mut counted = if has_blocks { self.walk_inode(number, &mut inode, &mut changed)? } else { 0 };
+        let acl = inode.i_file_acl;
+        if acl != 0 {
+            if self.ea_blocks.contains(&acl) {
+                counted += 1;
+            } else if self.claim(number, acl)? {
+                self.ea_blocks.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+                self.ea_blocks.push(acl);
+                counted += 1;
+            } else if self.repair {
+                inode.i_file_acl = 0;
+                changed = true;
+            }
+        }
+
+        let counted = counted * sectors_per_block;
+        let stored = inode.i_blocks;
+        if stored != counted {
+            if self.repair {
+                inode.i_blocks = counted;
+                changed = true;
+            }
+            self.record(Problem::BlockCount { inode: number, stored, counted }, self.repair)?;
+        }
+        if changed {
+            self.fs.write_inode(number, &inode)?;
+        }
+        Ok(())
+    }
+
+    // i-node'un blok ağacını dolaşır ve sahiplenir; sayılan blok sayısını (dolaylı bloklar dahil) döndürür.
+    // Onarım kipinde geçersiz işaretçiler silinir ve `changed` işaretlenir.
+    fn walk_inode(&mut self, number: u32, inode: &mut Inode, changed: &mut bool) -> Result<u32, SahneError> {
+        let mut i_block = inode.i_block;
+        let mut counted = 0;
+        for (slot, pointer) in i_block.iter_mut().enumerate() {
+            if *pointer == 0 {
+                continue;
+            }
+            if !self.claim(number, *pointer)? {
+                if self.repair {
+                    *pointer = 0;
+                    *changed = true;
+                }
+                continue;
+            }
+            counted += 1;
+            if slot >= EXT2_IND_BLOCK {
+                counted += self.walk_indirect(number, *pointer, slot - EXT2_IND_BLOCK + 1)?;
+            }
+        }
+        inode.i_block = i_block;
+        Ok(counted)
+    }
+
+    fn walk_indirect(&mut self, number: u32, block: u32, depth: usize) -> Result<u32, SahneError> {
+        let mut data = self.fs.read_block(block)?;
+        let mut counted = 0;
+        let mut changed = false;
+        for offset in (0..data.len()).step_by(4) {
+            let pointer = read_u32(&data, offset);
+            if pointer == 0 {
+                continue;
+            }
+            if !self.claim(number, pointer)? {
+                if self.repair {
+                    data[offset..offset + 4].fill(0);
+                    changed = true;
+                }
+                continue;
+            }
+            counted += 1;
+            if depth > 1 {
+                counted += self.walk_indirect(number, pointer, depth - 1)?;
+            }
+        }
+        if changed {
+            self.fs.write_block_part(block, 0, &data)?;
+        }
+        Ok(counted)
+    }
+
+    // Bloğu i-node'a sahiplendirir. Geçersizse false döner (işaretçi silinmeli); zaten sahiplenilmişse
+    // çift ayırma kaydedilir (1B'de kopyalanır).
+    fn claim(&mut self, number: u32, block: u32) -> Result<bool, SahneError> {
+        if block < self.fs.first_data_block || block >= self.fs.total_block_count || test_bit(&self.metadata, block) {
+            self.record(Problem::IllegalBlock { inode: number, block }, self.repair)?;
+            return Ok(false);
+        }
+        if test_bit(&self.used_blocks, block) {
+            self.duplicates.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+            self.duplicates.push(DuplicateClaim { inode: number, block, finding: self.report.findings.len() });
+            self.record(Problem::DuplicateBlock { inode: number, block }, false)?;
+        } else {
+            set_bit(&mut self.used_blocks, block);
+        }
+        Ok(true)
+    }
+
+    // --- Geçiş 1B: çift ayrılmış bloklar ---
+
+    // Bloğu ikinci sahiplenen i-node'a bloğun bir kopyası verilir. Kopyalanan dolaylı bloğun çocukları da
+    // çift ayrılmış sayıldığından ağaç yeni kopya üzerinden dolaşılarak onlar da kopyalanır.
+    fn pass1b(&mut self) -> Result<(), SahneError> {
+        if !self.repair {
+            return Ok(());
+        }
+        let mut owners: Vec<u32> = Vec::new();
+        for claim in &self.duplicates {
+            if !owners.contains(&claim.inode) {
+                owners.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+                owners.push(claim.inode);
+            }
+        }
+        for number in owners {
+            let mut inode = self.fs.read_inode(number)?;
+            let mut i_block = inode.i_block;
+            for (slot, pointer) in i_block.iter_mut().enumerate() {
+                if *pointer == 0 {
+                    continue;
+                }
+                if let Some(copy) = self.clone_if_duplicate(number, *pointer)? {
+                    *pointer = copy;
+                }
+                if slot >= EXT2_IND_BLOCK {
+                    self.clone_in_indirect(number, *pointer, slot - EXT2_IND_BLOCK + 1)?;
+                }
+            }
+            inode.i_block = i_block;
+            self.fs.write_inode(number, &inode)?;
+        }
+        Ok(())
+    }
+
+    fn clone_in_indirect(&mut self, number: u32, block: u32, depth: usize) -> Result<(), SahneError> {
+        let mut data = self.fs.read_block(block)?;
+        let mut changed = false;
+        for offset in (0..data.len()).step_by(4) {
+            let mut pointer = read_u32(&data, offset);
+            if pointer == 0 {
+                continue;
+            }
+            if let Some(copy) = self.clone_if_duplicate(number, pointer)? {
+                data[offset..offset + 4].copy_from_slice(&copy.to_le_bytes());
+                pointer = copy;
+                changed = true;
+            }
+            if depth > 1 {
+                self.clone_in_indirect(number, pointer, depth - 1)?;
+            }
+        }
+        if changed {
+            self.fs.write_block_part(block, 0, &data)?;
+        }
+        Ok(())
+    }
+
+    // `block` bu i-node'un çift ayrılmış bir işaretçisiyse kopyalar ve kopyanın numarasını döndürür.
+    // Boş blok kalmadıysa sorun onarılmamış kalır.
+    fn clone_if_duplicate(&mut self, number: u32, block: u32) -> Result<Option<u32>, SahneError> {
+        let Some(position) = self.duplicates.iter().position(|claim| claim.inode == number && claim.block == block) else {
+            return Ok(None);
+        };
+        let finding = self.duplicates.remove(position).finding;
+        let Some(copy) = self.find_free_block() else {
+            return Ok(None);
+        };
+        let data = self.fs.read_block(block)?;
+        self.fs.write_block_part(copy, 0, &data)?;
+        set_bit(&mut self.used_blocks, copy);
+        self.report.findings[finding].fixed = true;
+        Ok(Some(copy))
+    }
+
+    // Ne meta veri ne de sahiplenilmiş olan bir blok (bitmap'ler 5. geçişte yeniden yazılır).
+    fn find_free_block(&mut self) -> Option<u32> {
+        let block = (self.next_free..self.fs.total_block_count)
+            .find(|&block| !test_bit(&self.metadata, block) && !test_bit(&self.used_blocks, block))?;
+        self.next_free = block + 1;
+        Some(block)
+    }
+
+    // --- Geçiş 2: dizin girişleri ---
+
+    fn pass2(&mut self) -> Result<(), SahneError> {
+        for index in 0..self.dirs.len() {
+            let dir_number = self.dirs[index].inode;
+            let dir = self.fs.read_inode(dir_number)?;
+            for block_index in 0..dir.i_size.div_ceil(self.fs.block_size) {
+                // Rapor kipinde geçersiz işaretçiler yerinde durur; onlar 1. geçişte raporlandı.
+                let block = match self.fs.map_block(&dir, block_index) {
+                    Ok(block) if self.is_data_block(block) => block,
+                    _ => continue,
+                };
+                let mut data = self.fs.read_block(block)?;
+                let changed = if chain_is_valid(&data) {
+                    self.check_entries(index, block_index == 0, &mut data)?
+                } else {
+                    self.record(Problem::BadDirectoryBlock { dir: dir_number, block }, self.repair)?;
+                    self.repair && self.reset_directory_block(index, block_index == 0, &mut data)
+                };
+                if changed {
+                    self.fs.write_block_part(block, 0, &data)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_data_block(&self, block: u32) -> bool {
+        block >= self.fs.first_data_block && block < self.fs.total_block_count && !test_bit(&self.metadata, block)
+    }
+
+    // Kayıt zinciri geçerli bir dizin bloğunun girişlerini denetler ve sayar. Blok değiştiyse true döner.
+    fn check_entries(&mut self, index: usize, first_block: bool, data: &mut [u8]) -> Result<bool, SahneError> {
+        let dir_number = self.dirs[index].inode;
+        let check_file_type = self.fs.feature_incompat & EXT2_FEATURE_INCOMPAT_FILETYPE != 0;
+        let mut changed = false;
+        let mut offset = 0;
+        let mut position = 0;
+        while offset < data.len() {
+            let (inode, rec_len, name_len) = entry_header(data, offset)?;
+            let entry = offset;
+            offset += rec_len;
+            position += 1;
+            let name = &data[entry + DIR_ENTRY_HEADER_SIZE..entry + DIR_ENTRY_HEADER_SIZE + name_len];
+
+            // İlk bloğun ilk iki girişi "." ve ".." olmalı.
+            if first_block && position <= 2 {
+                let expected: &[u8] = if position == 1 { b"." } else { b".." };
+                if name != expected {
+                    self.record(Problem::BadDotEntries { dir: dir_number }, false)?;
+                    continue;
+                }
+                if position == 1 {
+                    if inode != dir_number {
+                        if self.repair {
+                            data[entry..entry + 4].copy_from_slice(&dir_number.to_le_bytes());
+                            changed = true;
+                        }
+                        self.record(Problem::BadDotEntries { dir: dir_number }, self.repair)?;
+                    }
+                    self.add_ref(dir_number);
+                } else {
+                    // Geçerliliği 3. geçişte, gerçek üst dizinle birlikte denetlenir.
+                    self.dirs[index].dotdot = inode;
+                    if self.is_dir(inode) {
+                        self.add_ref(inode);
+                    }
+                }
+                continue;
+            }
+            if inode == 0 {
+                continue;
+            }
+
+            let valid = inode <= self.fs.total_inode_count
+                && self.types[inode as usize] != EXT2_FT_UNKNOWN
+                && name_len > 0
+                && name != b"."
+                && name != b"..";
+            let problem = if !valid {
+                Some(Problem::BadEntry { dir: dir_number, inode })
+            } else if self.types[inode as usize] == EXT2_FT_DIR {
+                let child = self.dir_index(inode);
+                match child {
+                    Some(child) if inode != EXT2_ROOT_INODE && self.dirs[child].parent == 0 => {
+                        self.dirs[child].parent = dir_number;
+                        None
+                    }
+                    _ => Some(Problem::ExtraDirLink { dir: dir_number, inode }),
+                }
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                // Giriş i-node'u sıfırlanarak silinir (kayıt zinciri değişmez).
+                if self.repair {
+                    data[entry..entry + 4].fill(0);
+                    changed = true;
+                }
+                self.record(problem, self.repair)?;
+                continue;
+            }
+            self.add_ref(inode);
+
+            let file_type = self.types[inode as usize];
+            if check_file_type && data[entry + 7] != file_type {
+                if self.repair {
+                    data[entry + 7] = file_type;
+                    changed = true;
+                }
+                self.record(Problem::FileType { dir: dir_number, inode }, self.repair)?;
+            }
+        }
+        Ok(changed)
+    }
+
+    // Bozuk dizin bloğunu tek bir boş kayda (ilk blok için "." ve "..") çevirir. ".." şimdilik kökü
+    // gösterir; 3. geçiş gerçek üst dizine düzeltir.
+    fn reset_directory_block(&mut self, index: usize, first_block: bool, data: &mut [u8]) -> bool {
+        data.fill(0);
+        let block_size = data.len();
+        if first_block {
+            let dir_number = self.dirs[index].inode;
+            let dot_len = super::dir_entry_len(1);
+            self.fs.encode_entry_at(data, 0, dot_len, b".", dir_number, EXT2_FT_DIR);
+            self.fs.encode_entry_at(data, dot_len, block_size - dot_len, b"..", EXT2_ROOT_INODE, EXT2_FT_DIR);
+            self.dirs[index].dotdot = EXT2_ROOT_INODE;
+            self.add_ref(dir_number);
+            self.add_ref(EXT2_ROOT_INODE);
+        } else {
+            data[4..6].copy_from_slice(&(block_size as u16).to_le_bytes());
+        }
+        true
+    }
+
+    fn add_ref(&mut self, inode: u32) {
+        let count = &mut self.refs[inode as usize];
+        *count = count.saturating_add(1);
+    }
+
+    fn is_dir(&self, inode: u32) -> bool {
+        inode != 0 && inode <= self.fs.total_inode_count && self.types[inode as usize] == EXT2_FT_DIR
+    }
+
+    fn dir_index(&self, inode: u32) -> Option<usize> {
+        self.dirs.binary_search_by_key(&inode, |info| info.inode).ok()
+    }
+
+    // --- Geçiş 5: bitmap'ler ve serbest sayılar ---
+
+    fn pass5(&mut self) -> Result<(), SahneError> {
+        let block_size_bits = self.fs.block_size * 8;
+        let mut free_blocks = 0;
+        let mut free_inodes = 0;
+        for group in 0..self.fs.group_count {
+            let descriptor = self.fs.group_descriptors[group as usize];
+
+            let first_block = self.fs.first_data_block + group * self.fs.blocks_per_group;
+            let mut bitmap = self.fs.read_block(descriptor.bg_block_bitmap)?;
+            let mut differences = 0;
+            let mut group_free_blocks = 0;
+            for bit in 0..block_size_bits {
+                let block = first_block + bit;
+                // Son grubun dosya sistemi dışında kalan bitleri (dolgu) işaretli olmalı.
+                let used = bit >= self.fs.blocks_in_group(group) || test_bit(&self.metadata, block) || test_bit(&self.used_blocks, block);
+                if !used {
+                    group_free_blocks += 1;
+                }
+                if test_bit(&bitmap, bit) != used {
+                    assign_bit(&mut bitmap, bit, used);
+                    differences += 1;
+                }
+            }
+            if differences > 0 {
+                if self.repair {
+                    self.fs.write_block_part(descriptor.bg_block_bitmap, 0, &bitmap)?;
+                }
+                self.record(Problem::BlockBitmap { group, differences }, self.repair)?;
+            }
+
+            let first_inode = group * self.fs.inodes_per_group + 1;
+            let mut bitmap = self.fs.read_block(descriptor.bg_inode_bitmap)?;
+            let mut differences = 0;
+            let mut group_free_inodes = 0;
+            let mut group_dirs = 0;
+            for bit in 0..block_size_bits {
+                let number = first_inode + bit;
+                let used = if bit >= self.fs.inodes_per_group {
+                    true // Dolgu
+                } else if number < self.fs.first_inode {
+                    number <= self.fs.total_inode_count // Ayrılmış i-node'lar hep kullanımda
+                } else {
+                    number <= self.fs.total_inode_count && self.types[number as usize] != EXT2_FT_UNKNOWN
+                };
+                if bit < self.fs.inodes_per_group {
+                    if !used {
+                        group_free_inodes += 1;
+                    } else if self.is_dir(number) {
+                        group_dirs += 1;
+                    }
+                }
+                if test_bit(&bitmap, bit) != used {
+                    assign_bit(&mut bitmap, bit, used);
+                    differences += 1;
+                }
+            }
+            if differences > 0 {
+                if self.repair {
+                    self.fs.write_block_part(descriptor.bg_inode_bitmap, 0, &bitmap)?;
+                }
+                self.record(Problem::InodeBitmap { group, differences }, self.repair)?;
+            }
+
+            free_blocks += group_free_blocks;
+            free_inodes += group_free_inodes;
+            if descriptor.bg_free_blocks_count as u32 != group_free_blocks
+                || descriptor.bg_free_inodes_count as u32 != group_free_inodes
+                || descriptor.bg_used_dirs_count as u32 != group_dirs
+            {
+                if self.repair {
+                    let descriptor = &mut self.fs.group_descriptors[group as usize];
+                    descriptor.bg_free_blocks_count = group_free_blocks as u16;
+                    descriptor.bg_free_inodes_count = group_free_inodes as u16;
+                    descriptor.bg_used_dirs_count = group_dirs as u16;
+                    self.fs.write_group_counts(group)?;
+                }
+                self.record(Problem::GroupCounts { group }, self.repair)?;
+            }
+        }
+
+        if self.fs.free_blocks_count != free_blocks || self.fs.free_inodes_count != free_inodes {
+            if self.repair {
+                self.fs.free_blocks_count = free_blocks;
+                self.fs.free_inodes_count = free_inodes;
+                self.fs.write_group_counts(0)?;
+            }
+            self.record(Problem::SuperblockCounts, self.repair)?;
+        }
+        if self.repair {
+            self.fs.bitmap = (0, Vec::new()); // Önbellekteki bitmap eskidi
+        }
+        self.report.used_inodes = self.fs.total_inode_count - free_inodes;
+        self.report.used_blocks = self.fs.total_block_count - self.fs.first_data_block - free_blocks;
+        Ok(())
+    }
+
+    // --- Geçiş 3: dizin bağlantısı ---
+
+    fn pass3(&mut self) -> Result<(), SahneError> {
+        if let Some(root) = self.dir_index(EXT2_ROOT_INODE) {
+            self.dirs[root].parent = EXT2_ROOT_INODE; // Kökün ".." girişi kendisini gösterir
+        }
+        for index in 0..self.dirs.len() {
+            let number = self.dirs[index].inode;
+            if !self.reaches_root(number) {
+                // Üst dizini yok ya da kökten kopuk bir döngünün parçası.
+                let fixed = self.repair && self.detach_and_reconnect(index)?;
+                self.record(Problem::Unattached { inode: number }, fixed)?;
+            }
+            let info = self.dirs[index];
+            if info.parent != 0 && info.dotdot != info.parent {
+                let fixed = self.repair && self.fs.set_entry_inode(number, b"..", info.parent, EXT2_FT_DIR).is_ok();
+                if fixed {
+                    if self.is_dir(info.dotdot) {
+                        self.refs[info.dotdot as usize] -= 1;
+                    }
+                    self.add_ref(info.parent);
+                    self.dirs[index].dotdot = info.parent;
+                }
+                self.record(Problem::BadParent { dir: number, parent: info.parent }, fixed)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Dizin üst dizinleri üzerinden köke ulaşıyor mu? (Döngüler dizin sayısıyla sınırlanır.)
+    fn reaches_root(&self, number: u32) -> bool {
+        let mut current = number;
+        for _ in 0..=self.dirs.len() {
+            if current == EXT2_ROOT_INODE {
+                return true;
+            }
+            match self.dir_index(current) {
+                Some(index) if self.dirs[index].parent != 0 => current = self.dirs[index].parent,
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    // Kopuk dizini (bir döngüdeyse önce eski girişinden ayırarak) lost+found'a bağlar.
+    fn detach_and_reconnect(&mut self, index: usize) -> Result<bool, SahneError> {
+        let info = self.dirs[index];
+        if info.parent != 0 {
+            self.clear_entries_to(info.parent, info.inode)?;
+            self.refs[info.inode as usize] -= 1;
+            self.dirs[index].parent = 0;
+        }
+        self.reconnect(info.inode)
+    }
+
+    // `dir` dizininde `target`ı gösteren ("." ve ".." dışındaki) girişleri siler.
+    fn clear_entries_to(&mut self, dir_number: u32, target: u32) -> Result<(), SahneError> {
+        let dir = self.fs.read_inode(dir_number)?;
+        for block_index in 0..dir.i_size.div_ceil(self.fs.block_size) {
+            let block = self.fs.map_block(&dir, block_index)?;
+            if block == 0 {
+                continue;
+            }
+            let mut data = self.fs.read_block(block)?;
+            let mut changed = false;
+            let mut offset = 0;
+            while offset < data.len() {
+                let (inode, rec_len, name_len) = entry_header(&data, offset)?;
+                let name = &data[offset + DIR_ENTRY_HEADER_SIZE..offset + DIR_ENTRY_HEADER_SIZE + name_len];
+                if inode == target && name != b"." && name != b".." {
+                    data[offset..offset + 4].fill(0);
+                    changed = true;
+                }
+                offset += rec_len;
+            }
+            if changed {
+                self.fs.write_block_part(block, 0, &data)?;
+            }
+        }
+        Ok(())
+    }
+
+    // i-node'u lost+found'a "#<numara>" adıyla bağlar (gerekirse lost+found oluşturulur). Dizinlerin
+    // ".." girişi lost+found'u gösterecek şekilde düzeltilir. Bağlanamazsa false döner.
+    fn reconnect(&mut self, number: u32) -> Result<bool, SahneError> {
+        let Some(lost_and_found) = self.lost_and_found()? else {
+            return Ok(false);
+        };
+        let name = format!("#{}", number);
+        let file_type = self.types[number as usize];
+        if self.fs.add_entry(lost_and_found, name.as_bytes(), number, file_type).is_err() {
+            return Ok(false);
+        }
+        self.add_ref(number);
+        if let Some(index) = self.dir_index(number) {
+            let old = self.dirs[index].dotdot;
+            self.dirs[index].parent = lost_and_found;
+            if old != lost_and_found && self.fs.set_entry_inode(number, b"..", lost_and_found, EXT2_FT_DIR).is_ok() {
+                if self.is_dir(old) {
+                    self.refs[old as usize] -= 1;
+                }
+                self.add_ref(lost_and_found);
+                self.dirs[index].dotdot = lost_and_found;
+            }
+        }
+        Ok(true)
+    }
+
+    // lost+found dizinini bulur; yoksa kökte oluşturur.
+    fn lost_and_found(&mut self) -> Result<Option<u32>, SahneError> {
+        if self.lost_and_found != 0 {
+            return Ok(Some(self.lost_and_found));
+        }
+        if let Ok(number) = self.fs.lookup(EXT2_ROOT_INODE, LOST_AND_FOUND) {
+            if self.is_dir(number) && self.reaches_root(number) {
+                self.lost_and_found = number;
+                return Ok(Some(number));
+            }
+            return Ok(None); // Ad başka bir şeyce kullanılıyor
+        }
+        let Ok(number) = self.fs.mkdir(EXT2_ROOT_INODE, LOST_AND_FOUND) else {
+            return Ok(None);
+        };
+        // mkdir bağlantı sayılarını diske yazdı; sayımlar da aynı girişleri içermeli.
+        self.types[number as usize] = EXT2_FT_DIR;
+        self.refs[number as usize] = 2; // Kökteki giriş ve "."
+        self.add_ref(EXT2_ROOT_INODE); // Yeni dizinin ".." girişi
+        let index = self.dirs.partition_point(|info| info.inode < number);
+        self.dirs.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+        self.dirs.insert(index, DirInfo { inode: number, parent: EXT2_ROOT_INODE, dotdot: EXT2_ROOT_INODE });
+        self.lost_and_found = number;
+        Ok(Some(number))
+    }
+
+    // --- Geçiş 4: bağlantı sayıları ---
+
+    fn pass4(&mut self) -> Result<(), SahneError> {
+        for number in EXT2_ROOT_INODE..=self.fs.total_inode_count {
+            let file_type = self.types[number as usize];
+            if file_type == EXT2_FT_UNKNOWN {
+                continue;
+            }
+            let mut inode = self.fs.read_inode(number)?;
+            if self.refs[number as usize] == 0 && file_type != EXT2_FT_DIR {
+                // Bağlanmamış i-node: boş normal dosyalar (örn. yarıda kalan bir oluşturma) silinir,
+                // diğerleri lost+found'a bağlanır.
+                let fixed = if !self.repair {
+                    false
+                } else if file_type == EXT2_FT_REG_FILE && inode.i_size == 0 {
+                    self.fs.release_inode(number, &mut inode)?;
+                    self.types[number as usize] = EXT2_FT_UNKNOWN;
+                    true
+                } else {
+                    self.reconnect(number)?
+                };
+                self.record(Problem::Unattached { inode: number }, fixed)?;
+                if self.refs[number as usize] == 0 {
+                    continue; // Silindi veya bağlanamadı; bağlantı sayısı ayrıca raporlanmaz
+                }
+                inode = self.fs.read_inode(number)?;
+            }
+
+            let counted = self.refs[number as usize];
+            let stored = inode.i_links_count;
+            if stored != counted {
+                if self.repair {
+                    inode.i_links_count = counted;
+                    self.fs.write_inode(number, &inode)?;
+                }
+                self.record(Problem::LinkCount { inode: number, stored, counted }, self.repair)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Bu grupta süper blok ve tanımlayıcı kopyası var mı? (sparse_super: 0, 1 ve 3, 5, 7'nin kuvvetleri)
+fn has_superblock_copy(group: u32) -> bool {
+    if group <= 1 {
+        return true;
+    }
+    [3, 5, 7].iter().any(|&base| {
+        let mut power = base;
+        while power < group {
+            power *= base;
+        }
+        power == group
+    })
+}
+
+// Dizin bloğunun kayıt zinciri baştan sona geçerli mi?
+fn chain_is_valid(data: &[u8]) -> bool {
+    let mut offset = 0;
+    while offset < data.len() {
+        match entry_header(data, offset) {
+            Ok((_, rec_len, _)) => offset += rec_len,
+            Err(_) => return false,
+        }
+    }
+    true
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+fn test_bit(bits: &[u8], bit: u32) -> bool {
+    bits.get(bit as usize / 8).is_some_and(|byte| byte & (1 << (bit % 8)) != 0)
+}
+
+fn set_bit(bits: &mut [u8], bit: u32) {
+    assign_bit(bits, bit, true);
+}
+
+fn assign_bit(bits: &mut [u8], bit: u32, value: bool) {
+    if let Some(byte) = bits.get_mut(bit as usize / 8) {
+        if value {
+            *byte |= 1 << (bit % 8);
+        } else {
+            *byte &= !(1 << (bit % 8));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{assert_fsck_clean, mount_writable, IMAGE};
+    use super::*;
+    use crate::filesystem::ext::SUPERBLOCK_MOUNT_COUNT_OFFSET;
+
+    fn problems(report: &Report) -> Vec<Problem> {
+        report.findings.iter().map(|finding| finding.problem).collect()
+    }
+
+    // Okunan bayt ofsetlerini kaydeden bellek imajı.
+    struct RecordingDevice {
+        image: &'static [u8],
+        reads: core::cell::RefCell<Vec<u64>>,
+    }
+
+    impl BlockDevice for RecordingDevice {
+        fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<usize, SahneError> {
+            self.reads.borrow_mut().push(offset);
+            self.image.read_at(offset, buffer)
+        }
+    }
+
+    #[test]
+    fn pass1_reads_each_inode_table_block_once() {
+        let mut fs = ExtFilesystem::mount(RecordingDevice { image: IMAGE, reads: Default::default() }).unwrap();
+        let block_size = fs.block_size as u64;
+        let table_blocks = (fs.inodes_per_group as u64 * fs.inode_size as u64).div_ceil(block_size);
+        let tables: Vec<u64> = fs.group_descriptors.iter().map(|descriptor| descriptor.bg_inode_table as u64).collect();
+
+        let mut checker = Checker::new(&mut fs, false).unwrap();
+        checker.fs.device.reads.borrow_mut().clear();
+        checker.pass1().unwrap();
+        let reads = checker.fs.device.reads.take();
+        for block in tables.iter().flat_map(|&table| table..table + table_blocks) {
+            let count = reads.iter().filter(|&&offset| offset / block_size == block).count();
+            assert_eq!(count, 1, "i-node tablosu bloğu {}", block);
+        }
+    }
+
+    #[test]
+    fn fixture_image_is_clean() {
+        let mut fs = ExtFilesystem::mount(IMAGE).unwrap();
+        let report = check(&mut fs, Mode::Report).unwrap();
+        assert!(report.is_clean(), "{:?}", report.findings);
+        assert_eq!(report.used_inodes, fs.total_inode_count - fs.free_inodes_count);
+        assert_eq!(report.used_blocks, fs.total_block_count - fs.first_data_block - fs.free_blocks_count);
+        assert!(!needs_check(&fs));
+    }
+
+    #[test]
+    fn repairs_orphans_and_link_counts() {
+        let mut fs = mount_writable();
+        let lost_and_found = fs.lookup(EXT2_ROOT_INODE, LOST_AND_FOUND).unwrap();
+        let hello = fs.lookup(EXT2_ROOT_INODE, "hello.txt").unwrap();
+        // Girişi silinmiş ama i-node'u serbest bırakılmamış dosyalar ve dizin (yarıda kalan unlink).
+        let orphan = fs.create_file(EXT2_ROOT_INODE, "a").unwrap();
+        fs.write_file(orphan, 0, b"kayip veri").unwrap();
+        fs.remove_entry(EXT2_ROOT_INODE, b"a").unwrap();
+        let empty = fs.create_file(EXT2_ROOT_INODE, "b").unwrap();
+        fs.remove_entry(EXT2_ROOT_INODE, b"b").unwrap();
+        let dir = fs.mkdir(EXT2_ROOT_INODE, "d").unwrap();
+        fs.remove_entry(EXT2_ROOT_INODE, b"d").unwrap();
+        let mut inode = fs.read_inode(hello).unwrap();
+        inode.i_links_count = 3;
+        fs.write_inode(hello, &inode).unwrap();
+
+        // Rapor kipi diske yazmaz.
+        let before = fs.device.clone();
+        let report = check(&mut fs, Mode::Report).unwrap();
+        assert_eq!(fs.device, before);
+        assert_eq!(report.unfixed(), report.findings.len());
+        let found = problems(&report);
+        for problem in [
+            Problem::Unattached { inode: orphan },
+            Problem::Unattached { inode: empty },
+            Problem::Unattached { inode: dir },
+            Problem::LinkCount { inode: hello, stored: 3, counted: 65 },
+        ] {
+            assert!(found.contains(&problem), "{:?} yok: {:?}", problem, found);
+        }
+
+        let report = check(&mut fs, Mode::Repair).unwrap();
+        assert!(!report.is_clean());
+        assert_eq!(report.unfixed(), 0, "{:?}", report.findings);
+        assert_eq!(fs.lookup(lost_and_found, &alloc::format!("#{}", orphan)), Ok(orphan));
+        assert_eq!(fs.lookup(lost_and_found, &alloc::format!("#{}", dir)), Ok(dir));
+        assert_eq!(fs.lookup(dir, ".."), Ok(lost_and_found));
+        assert_eq!({ fs.read_inode(empty).unwrap().i_links_count }, 0);
+        assert_eq!({ fs.read_inode(hello).unwrap().i_links_count }, 65);
+        let mut data = [0u8; 10];
+        fs.read_file(&fs.read_inode(orphan).unwrap(), &mut data, 0).unwrap();
+        assert_eq!(&data, b"kayip veri");
+
+        assert!(check(&mut fs, Mode::Report).unwrap().is_clean());
+        assert_fsck_clean(&fs.device, "fsck-orphans");
+    }
+
+    #[test]
+    fn repairs_blocks_bitmaps_and_entries() {
+        let mut fs = mount_writable();
+        let hello = fs.lookup(EXT2_ROOT_INODE, "hello.txt").unwrap();
+        let sparse = fs.lookup(EXT2_ROOT_INODE, "sparse.bin").unwrap();
+        let bin = fs.lookup(EXT2_ROOT_INODE, "bin").unwrap();
+        let sbxe = fs.lookup(bin, "hello.sbxe").unwrap();
+        let etc = fs.lookup(EXT2_ROOT_INODE, "etc").unwrap();
+        let hello_block = fs.read_inode(hello).unwrap().i_block[0];
+        let block_bitmap = fs.group_descriptors[0].bg_block_bitmap;
+
+        // sparse.bin'in ilk bloğu hello.txt'ninkiyle paylaşılır; hello.sbxe bitmap bloğunu gösterir.
+        let mut inode = fs.read_inode(sparse).unwrap();
+        inode.i_block[0] = hello_block;
+        fs.write_inode(sparse, &inode).unwrap();
+        let mut inode = fs.read_inode(sbxe).unwrap();
+        inode.i_block[1] = block_bitmap;
+        fs.write_inode(sbxe, &inode).unwrap();
+        // Kullanılmayan i-node'u gösteren giriş, silinmiş bitmap bitleri ve yanlış sayılar
+        fs.add_entry(etc, b"hayalet", 30, EXT2_FT_REG_FILE).unwrap();
+        let inode_bitmap = fs.group_descriptors[0].bg_inode_bitmap;
+        let mut bitmap = fs.read_block(inode_bitmap).unwrap();
+        assign_bit(&mut bitmap, hello - 1, false);
+        fs.write_block_part(inode_bitmap, 0, &bitmap).unwrap();
+        let mut bitmap = fs.read_block(block_bitmap).unwrap();
+        assign_bit(&mut bitmap, hello_block - fs.first_data_block, false);
+        fs.write_block_part(block_bitmap, 0, &bitmap).unwrap();
+        fs.free_blocks_count += 5;
+        fs.group_descriptors[0].bg_used_dirs_count += 1;
+        fs.write_group_counts(0).unwrap();
+
+        let report = check(&mut fs, Mode::Repair).unwrap();
+        assert_eq!(report.unfixed(), 0, "{:?}", report.findings);
+        let found = problems(&report);
+        for problem in [
+            Problem::DuplicateBlock { inode: hello.max(sparse), block: hello_block },
+            Problem::IllegalBlock { inode: sbxe, block: block_bitmap },
+            Problem::BadEntry { dir: etc, inode: 30 },
+            Problem::InodeBitmap { group: 0, differences: 1 },
+            Problem::GroupCounts { group: 0 },
+            Problem::SuperblockCounts,
+        ] {
+            assert!(found.contains(&problem), "{:?} yok: {:?}", problem, found);
+        }
+        assert!(found.iter().any(|problem| matches!(problem, Problem::BlockBitmap { group: 0, .. })));
+
+        assert_ne!({ fs.read_inode(sparse).unwrap().i_block }[0], hello_block);
+        let mut data = [0u8; 18];
+        fs.read_file(&fs.read_inode(hello).unwrap(), &mut data, 0).unwrap();
+        assert_eq!(&data, b"Merhaba SahneBox!\n");
+        assert_eq!({ fs.read_inode(sbxe).unwrap().i_block[1] }, 0);
+        assert_eq!(fs.lookup(etc, "hayalet"), Err(SahneError::ResourceNotFound));
+
+        assert!(check(&mut fs, Mode::Report).unwrap().is_clean());
+        assert_fsck_clean(&fs.device, "fsck-blocks");
+    }
+
+    #[test]
+    fn rebuilds_corrupted_directory_block() {
+        let mut fs = mount_writable();
+        let lost_and_found = fs.lookup(EXT2_ROOT_INODE, LOST_AND_FOUND).unwrap();
+        let bin = fs.lookup(EXT2_ROOT_INODE, "bin").unwrap();
+        let sbxe = fs.lookup(bin, "hello.sbxe").unwrap();
+        let block = fs.map_block(&fs.read_inode(bin).unwrap(), 0).unwrap();
+        fs.write_block_part(block, 4, &[3, 0]).unwrap(); // "." girişinin rec_len'i geçersiz
+
+        let report = check(&mut fs, Mode::Repair).unwrap();
+        assert_eq!(report.unfixed(), 0, "{:?}", report.findings);
+        assert!(problems(&report).contains(&Problem::BadDirectoryBlock { dir: bin, block }));
+        assert_eq!(fs.lookup(bin, "."), Ok(bin));
+        assert_eq!(fs.lookup(bin, ".."), Ok(EXT2_ROOT_INODE));
+        assert_eq!(fs.lookup(bin, "hello.sbxe"), Err(SahneError::ResourceNotFound));
+        assert_eq!(fs.lookup(lost_and_found, &alloc::format!("#{}", sbxe)), Ok(sbxe));
+
+        assert!(check(&mut fs, Mode::Report).unwrap().is_clean());
+        assert_fsck_clean(&fs.device, "fsck-dirblock");
+    }
+
+    #[test]
+    fn boot_check_follows_mount_state() {
+        // Temiz kapatılan dosya sistemi denetim gerektirmez.
+        let mut fs = mount_writable();
+        fs.create_file(EXT2_ROOT_INODE, "a").unwrap();
+        let device = fs.unmount().unwrap();
+        let mut fs = ExtFilesystem::mount(device).unwrap();
+        assert!(!needs_check(&fs));
+        assert_eq!(fs.mount_count, 1);
+
+        // Yazma sırasında elektrik kesilirse (unmount yok) denetim gerekir.
+        fs.create_file(EXT2_ROOT_INODE, "b").unwrap();
+        let mut fs = ExtFilesystem::mount(fs.device.clone()).unwrap();
+        assert!(needs_check(&fs));
+        let report = check_at_boot(&mut fs).unwrap().unwrap();
+        assert_eq!(report.unfixed(), 0);
+        assert_eq!(fs.mount_count, 0);
+        let mut fs = ExtFilesystem::mount(fs.device.clone()).unwrap();
+        assert!(!needs_check(&fs));
+        assert!(check_at_boot(&mut fs).unwrap().is_none());
+
+        // s_max_mnt_count'a ulaşıldı
+        let mut image = IMAGE.to_vec();
+        let offset = SUPERBLOCK_MOUNT_COUNT_OFFSET as usize;
+        image[offset..offset + 4].copy_from_slice(&[1, 0, 1, 0]);
+        assert!(needs_check(&ExtFilesystem::mount(image).unwrap()));
+    }
+}
@@ -0,0 +1,114 @@
+// main_kernel/futex.rs
+// Adres Üzerinde Bekleme (Futex Benzeri) Sistem Çağrıları
+// Kullanıcı alanı kilitleri çekişme yokken tamamen atomik işlemlerle çalışır;
+// çekirdeğe sadece beklemek (wait_on_address) veya bekleyeni uyandırmak (wake_address) için gelinir.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+use spin::Mutex;
+use crate::sahne64::SahneError;
+use crate::sched;
+use crate::drivers::timer;
+
+// Zaman aşımı olmadan beklemeyi belirten değer (sahne64::sync ile aynı).
+pub const NO_TIMEOUT: u64 = u64::MAX;
+
+// Bir adreste bekleyen görev.
+struct Waiter {
+    addr: usize,
+    task_id: usize,
+}
+
+// Tüm bekleyenler tek bir listede tutulur. Görev sayısı az olduğu için
+// doğrusal arama yeterlidir; eklenme sırası korunur (FIFO uyandırma).
+static WAITERS: Mutex<Vec<Waiter>> = Mutex::new(Vec::new());
+
+// Adresin çağıran görevin belleğinde 4 bayt hizalı bir u32'yi gösterdiğini doğrular.
+// Çekirdek belleğini veya başka bir görevin belleğini gösteren adresler reddedilir.
+fn check_address(addr: usize) -> Result<(), SahneError> {
+    if addr == 0 || addr % core::mem::align_of::<AtomicU32>() != 0 {
+        return Err(SahneError::InvalidAddress);
+    }
+    crate::mm::check_user_range(addr, core::mem::size_of::<AtomicU32>())
+}
+
+// SYSCALL_WAIT_ON_ADDRESS: *addr hâlâ expected ise görevi uyandırılana veya süre dolana kadar bloklar.
+// Değer farklıysa hemen ResourceBusy döner (çağıran kilidi yeniden denemeli).
+// Süre dolarsa TimedOut, kapatma bildirimi gelirse Interrupted döner.
+pub fn sys_wait_on_address(addr: usize, expected: u32, timeout_ms: u64) -> Result<(), SahneError> {
+    check_address(addr)?;
+    let task_id = sched::current_task_id().ok_or(SahneError::InvalidOperation)?;
+    let deadline = if timeout_ms == NO_TIMEOUT {
+        None
+    } else {
+        Some(timer::now_ticks().saturating_add(timer::ms_to_ticks(timeout_ms)))
+    };
+
+    {
+        // Değer kontrolü, kuyruğa ekleme ve bloklanma aynı kilit altında yapılır;
+        // böylece kontrol ile bloklanma arasında gelen bir wake_address kaybolmaz.
+        let mut waiters = WAITERS.lock();
+        let current = unsafe { (*(addr as *const AtomicU32)).load(Ordering::SeqCst) };
+        if current != expected {
+            return Err(SahneError::ResourceBusy);
+        }
+        waiters.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+        waiters.push(Waiter { addr, task_id });
+        if let Err(err) = sched::prepare_to_block_interruptible(deadline) {
+            waiters.pop();
+            return Err(err);
+        }
+    }
+
+    sched::wait_while_blocked();
+
+    // wake_address bizi listeden çıkarmıştır; hâlâ listedeysek süre dolmuştur
+    // veya kapatma bildirimiyle uyandırıldık.
+    let mut waiters = WAITERS.lock();
+    match waiters.iter().position(|waiter| waiter.task_id == task_id) {
+        Some(index) => {
+            waiters.remove(index);
+            Err(if sched::termination_requested() { SahneError::Interrupted } else { SahneError::TimedOut })
+        }
+        None => Ok(()),
+    }
+}
+
+// SYSCALL_WAKE_ADDRESS: addr üzerinde bekleyen en fazla count görevi (bekleme sırasıyla) uyandırır.
+// Uyandırılan görev sayısını döndürür.
+pub fn sys_wake_address(addr: usize, count: usize) -> Result<usize, SahneError> {
+    check_address(addr)?;
+    let mut waiters = WAITERS.lock();
+    let mut woken = 0;
+    let mut index = 0;
+    while index < waiters.len() && woken < count {
+        if waiters[index].addr == addr {
+            let waiter = waiters.remove(index);
+            sched::wake_task(waiter.task_id);
+            woken += 1;
+        } else {
+            index += 1;
+        }
+    }
+    Ok(woken)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static KERNEL_WORD: AtomicU32 = AtomicU32::new(7);
+
+    #[test_case]
+    fn addresses_outside_the_callers_memory_are_rejected() {
+        let addr = &KERNEL_WORD as *const AtomicU32 as usize;
+        assert_eq!(check_address(0), Err(SahneError::InvalidAddress));
+        assert_eq!(check_address(addr + 1), Err(SahneError::InvalidAddress)); // Hizasız
+        sched::run_as_test_task(|| {
+            // Değer eşleşse de çekirdek değişkeni okunmaz.
+            assert_eq!(sys_wait_on_address(addr, 7, 0), Err(SahneError::InvalidAddress));
+            assert_eq!(sys_wake_address(addr, 1), Err(SahneError::InvalidAddress));
+        });
+    }
+}
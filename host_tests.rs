@@ -0,0 +1,84 @@
+// tests/host_tests.rs
+// Host Birim Testleri
+// Donanıma bağımlı olmayan (taşınabilir) modülleri geliştirme makinesinde derleyip
+// normal #[test]'lerle çalıştırır. Çekirdek ve QEMU gerekmez: `make host-test`.
+//
+// sahne64 host'ta derlenirken syscall'lar NotSupported döndüren bir taslağa bağlanır;
+// testler çekirdeğe değil, BlockDevice / ProgramMemory gibi trait'lerin bellek içi
+// implementasyonlarına dayanır. Test verileri fixtures/ altındadır.
+
+#![no_std]
+// Modüller kendi crate'lerinin kök özniteliklerini (#![no_std] vb.) taşır; burada etkisizdirler.
+#![allow(unused_attributes)]
+
+extern crate alloc;
+#[cfg(test)]
+extern crate std;
+
+#[path = "sahne64.rs"]
+pub mod sahne64;
+
+// loader.rs ext2 koduna crate::filesystem::ext yolundan erişir.
+#[path = "."]
+pub mod filesystem {
+    #[path = "ext.rs"]
+    pub mod ext;
+}
+
+#[path = "loader.rs"]
+pub mod loader;
+
+#[path = "spk.rs"]
+pub mod spk;
+
+#[path = "layout.rs"]
+pub mod layout;
+
+#[path = "free_list.rs"]
+pub mod free_list;
+
+#[path = "calendar.rs"]
+pub mod calendar;
+
+#[path = "fdt.rs"]
+pub mod fdt;
+
+#[path = "csprng.rs"]
+pub mod csprng;
+
+#[path = "font.rs"]
+pub mod font;
+
+#[path = "fbcon.rs"]
+pub mod fbcon;
+
+#[path = "tty_io.rs"]
+pub mod tty_io;
+
+#[path = "ring_buffer.rs"]
+pub mod ring_buffer;
+
+#[path = "block.rs"]
+pub mod block;
+
+#[path = "partition.rs"]
+pub mod partition;
+
+#[path = "block_cache.rs"]
+pub mod block_cache;
+
+#[path = "media_events.rs"]
+pub mod media_events;
+
+// Sürücülerin donanımdan bağımsız kısımları
+#[path = "."]
+pub mod drivers {
+    #[path = "virtqueue.rs"]
+    pub mod virtqueue;
+
+    #[path = "sdhci.rs"]
+    pub mod sdhci;
+
+    #[path = "mmc.rs"]
+    pub mod mmc;
+}
@@ -16,10 +16,10 @@ use core::ptr;
 
 
 // SahneBox Çekirdek API'sini içeri aktar
-use crate::sahne64::{self, resource, memory, task, kernel, SahneError, Handle};
+use crate::sahne64::{self, resource, memory, task, kernel, media, SahneError, Handle};
 
 // Minimal EXT2 dosya sistemi kütüphanesi (Sadece kaynak imaj dosyasını okumak için)
-use crate::filesystem::ext::ExtFilesystem; // ext.rs dosyasını filesystem modülü altında varsayalım
+use crate::filesystem::ext::{ExtFilesystem, EXT2_ROOT_INODE}; // ext.rs dosyasını filesystem modülü altında varsayalım
 
 
 // TODO: resource::write üzerine yazıcı wrapper'ı (Diğer uygulamalardan kopyalandı)
@@ -32,62 +32,33 @@ impl core::fmt::Write for ConsoleWriter {
     }
 }
 
-// TODO: resource::read üzerine okuyucu wrapper'ı ve temel satır düzenleme (Shell'den kopyalandı)
+// Çekirdeğin kanonik konsol satırı en fazla 256 bayt + satır sonudur (tty_io.rs LINE_MAX).
+const LINE_BUFFER_SIZE: usize = 257;
+
+// İmaj kopyalanırken tek seferde aktarılan blok sayısı (512 baytlık bloklarla 16 KB).
+const COPY_BLOCKS: usize = 32;
+
+// Konsoldan satır okuyucu (Shell ile aynı). Satır düzenleme ve yankı çekirdeğin TTY katmanında yapılır.
+// Konsol MODE_NONBLOCK ile edinildiği için satır hazır değilse Err(SahneError::ResourceBusy) döner;
+// böylece beklerken dokunmatik ekran da yoklanabilir.
 struct ConsoleReader {
      handle: Handle,
-     buffer: Vec<u8>, // Satır tamponu
-     // TODO: buffer_pos alanı kaldırıldı, buffer clear ediliyor.
+     buffer: [u8; LINE_BUFFER_SIZE],
 }
 
 impl ConsoleReader {
-    fn new(handle: Handle, buffer_capacity: usize) -> Self {
-        ConsoleReader {
-            handle,
-            buffer: Vec::with_capacity(buffer_capacity),
-        }
+    fn new(handle: Handle) -> Self {
+        ConsoleReader { handle, buffer: [0; LINE_BUFFER_SIZE] }
     }
 
-    // Tek bir karakter okumaya çalışır (pollemeye dayalı).
-    fn read_char(&mut self) -> Option<u8> {
-        let mut byte = [0u8; 1];
-        match resource::read(self.handle, &mut byte, 0, 1) { // resource::read(handle, buf, offset, len)
-            Ok(1) => Some(byte[0]),
-            _ => None, // Veri yok veya hata
-        }
-    }
-
-    // Bir satır okur (Enter'a kadar). Temel satır düzenleme (backspace) yapar.
-    fn read_line(&mut self, console: &mut ConsoleWriter) -> Result<String, SahneError> {
-        self.buffer.clear();
-
-        loop {
-            let byte = loop {
-                if let Some(b) = self.read_char() {
-                    break b;
-                }
-                task::yield_now().unwrap_or_else(|_| { core::hint::spin_loop(); }); // Scheduler varsa yield
-            };
-
-            match byte {
-                b'\n' | b'\r' => { // Enter tuşu
-                    writeln!(console, "").unwrap();
-                    let line = String::from_utf8(self.buffer.clone()).unwrap_or(String::new());
-                    return Ok(line);
-                }
-                0x7f | b'\x08' => { // Backspace (ASCII 127 veya 8)
-                    if !self.buffer.is_empty() {
-                        self.buffer.pop();
-                        write!(console, "\x08 \x08").unwrap();
-                    }
-                }
-                _ => { // Diğer karakterler
-                    if self.buffer.len() < self.buffer.capacity() {
-                         write!(console, "{}", byte as char).unwrap();
-                         self.buffer.push(byte);
-                    }
-                }
-            }
+    // Hazır bir satırı satır sonu olmadan döndürür. Ctrl-D ile dosya sonunda None döner.
+    fn read_line(&mut self) -> Result<Option<String>, SahneError> {
+        let count = resource::read(self.handle, &mut self.buffer)?;
+        if count == 0 {
+            return Ok(None);
         }
+        let line = self.buffer[..count].strip_suffix(b"\n").unwrap_or(&self.buffer[..count]);
+        Ok(Some(String::from_utf8(line.to_vec()).unwrap_or(String::new())))
     }
 }
 
@@ -110,9 +81,9 @@ fn wait_for_touch_or_enter(console: &mut ConsoleWriter, touchscreen_handle: Hand
              }
          }
 
-         // UART konsoldan input oku (Enter'ı bekle)
-         match console_reader.read_line(console) {
-             Ok(line) => {
+         // Konsoldan hazır bir satır var mı bak (beklemez)
+         match console_reader.read_line() {
+             Ok(Some(line)) => {
                  if line.as_str() == "Y" || line.as_str() == "y" {
                       writeln!(console, "Onay alındı.").unwrap();
                       break;
@@ -120,13 +91,35 @@ fn wait_for_touch_or_enter(console: &mut ConsoleWriter, touchscreen_handle: Hand
                       writeln!(console, "Geçersiz giriş. 'Y' yazıp Enter'a basın veya ekrana dokunun.").unwrap();
                  }
              }
-             Err(_) => { /* Hata, ignore et */ }
+             Ok(None) | Err(SahneError::Interrupted) => {
+                 // Ctrl-D / Ctrl-C: kurulum iptal edilir
+                 writeln!(console, "Kurulum iptal edildi.").unwrap();
+                 task::exit(-1);
+             }
+             Err(_) => { /* Satır henüz hazır değil (ResourceBusy) veya hata */ }
          }
 
          task::yield_now().unwrap_or_else(|_| { core::hint::spin_loop(); }); // Scheduler varsa yield
      }
 }
 
+// SD kart takılana kadar bekler ve "sdcard1"i edinir. Abonelik acquire denemesinden önce açılır;
+// böylece arada takılan kartın olayı kaçırılmaz.
+fn wait_for_sd_card() -> Result<Handle, SahneError> {
+    let mut watcher = media::MediaWatcher::new(false)?;
+    loop {
+        if let Ok(handle) = resource::acquire("sdcard1", resource::MODE_READ) {
+            return Ok(handle);
+        }
+        loop {
+            let event = watcher.next_event()?;
+            if event.kind == media::MEDIA_INSERTED && event.device() == "sdcard1" {
+                break;
+            }
+        }
+    }
+}
+
 
 // Kurulum Uygulamasının Ana Giriş Noktası
 #[no_mangle] // Kernel tarafından çağrılabilmesi için isim bozulmamalı
@@ -137,9 +130,9 @@ pub extern "C" fn main(_argc: usize, _argv: *const *const u8) -> ! {
     // kullanılabilir olduğunu varsayıyoruz.
 
     // Konsol kaynağını edin (UART veya Grafik Ekran Metin Modu)
-    let console_handle = resource::acquire("console", resource::MODE_WRITE | resource::MODE_READ).unwrap_or_else(|_| {loop{core::hint::spin_loop();}});
+    let console_handle = resource::acquire("console", resource::MODE_WRITE | resource::MODE_READ | resource::MODE_NONBLOCK).unwrap_or_else(|_| {loop{core::hint::spin_loop();}});
     let mut console_writer = ConsoleWriter { handle: console_handle };
-    let mut console_reader = ConsoleReader::new(console_handle, 64); // Konsol girdisi için buffer
+    let mut console_reader = ConsoleReader::new(console_handle);
 
     writeln!(console_writer, "SahneBox Kurulum Sihirbazı Başlıyor (İmaj Kopyalama)...").unwrap();
 
@@ -151,13 +144,16 @@ pub extern "C" fn main(_argc: usize, _argv: *const *const u8) -> ! {
 
     // Kurulum medyası (Kaynak) olarak SD Kart'ı varsayalım.
     // Hedef cihaz olarak Dahili eMMC'yi varsayalım.
-    let source_device_handle = resource::acquire("sdcard1", resource::MODE_READ).unwrap_or(Handle::invalid());
+    let mut source_device_handle = resource::acquire("sdcard1", resource::MODE_READ).unwrap_or(Handle::invalid());
     let target_device_handle = resource::acquire("emmc0", resource::MODE_READ | resource::MODE_WRITE).unwrap_or(Handle::invalid());
 
     if !source_device_handle.is_valid() {
-        writeln!(console_writer, "Hata: Kurulum medyası (sdcard1) bulunamadı!").unwrap();
-        writeln!(console_writer, "Lütfen kurulum imajını içeren SD kartı taktığınızdan emin olun.").unwrap();
-        task::exit(-1); // Hata durumu
+        writeln!(console_writer, "Kurulum medyası (sdcard1) bulunamadı.").unwrap();
+        writeln!(console_writer, "Lütfen kurulum imajını içeren SD kartı takın...").unwrap();
+        source_device_handle = wait_for_sd_card().unwrap_or_else(|err| {
+            writeln!(console_writer, "Hata: SD kart beklenemedi: {:?}", err).unwrap();
+            task::exit(-1); // Hata durumu
+        });
     }
      writeln!(console_writer, "Kaynak Cihaz Algılandı: SD Kart (sdcard1)").unwrap();
 
@@ -182,13 +178,16 @@ pub extern "C" fn main(_argc: usize, _argv: *const *const u8) -> ! {
     };
     writeln!(console_writer, "Kaynak dosya sistemi bağlandı.").unwrap();
 
-    // Kurulum imaj dosyasını bul (Örn: "/sahnebox.img")
+    // Kurulum imaj dosyasını bul (Örn: "/sahnebox.img"; sembolik bağlantı da olabilir)
     let image_file_path = "/sahnebox.img"; // Kurulum imaj dosyası adı
-    let root_dir_inode = source_fs.root_directory().unwrap_or_else(|e| {
-         writeln!(console_writer, "Hata: Kaynak FS root dizini okunamadı: {:?}", e).unwrap();
-         task::exit(-4);
-    }); // Root dizininin i-node'unu al
-    let image_file_inode_num = source_fs.lookup(root_dir_inode.inode, image_file_path).unwrap_or(0);
+    let image_file_inode_num = match source_fs.resolve(image_file_path, EXT2_ROOT_INODE) {
+        Ok(inode_number) => inode_number,
+        Err(SahneError::ResourceNotFound) => 0,
+        Err(e) => {
+            writeln!(console_writer, "Hata: Kaynak dosya sisteminde '{}' aranamadı: {:?}", image_file_path, e).unwrap();
+            task::exit(-4);
+        }
+    };
 
     if image_file_inode_num == 0 {
         writeln!(console_writer, "Hata: Kurulum imaj dosyası '{}' kaynak medyada bulunamadı!", image_file_path).unwrap();
@@ -209,9 +208,15 @@ pub extern "C" fn main(_argc: usize, _argv: *const *const u8) -> ! {
     writeln!(console_writer, "İmaj Boyutu: {} bayt", image_size).unwrap();
 
 
-    // TODO: Hedef cihazın kapasitesini kontrol et. İmaj boyutu hedef cihaza sığmalı.
-    // Şu an kaynak resource API'sinde kapasite bilgisi yok. Kernel resource manager güncellenmeli.
-     let target_capacity_blocks = resource::get_capacity(target_device_handle)?; // Varsayımsal
+    // Hedef cihazın kapasitesini kontrol et. İmaj boyutu hedef cihaza sığmalı.
+    let target_capacity = resource::control(target_device_handle, resource::CONTROL_BLOCK_GET_SIZE, 0).unwrap_or_else(|e| {
+         writeln!(console_writer, "Hata: Hedef cihazın boyutu okunamadı: {:?}", e).unwrap();
+         task::exit(-12);
+    });
+    if image_size as u64 > target_capacity {
+         writeln!(console_writer, "Hata: İmaj ({} bayt) hedef cihaza ({} bayt) sığmıyor!", image_size, target_capacity).unwrap();
+         task::exit(-13);
+    }
 
 
     // --- 4. Aşama: Biçimlendirme Uyarısı ve Onay ---
@@ -226,15 +231,18 @@ pub extern "C" fn main(_argc: usize, _argv: *const *const u8) -> ! {
 
     writeln!(console_writer, "Kurulum imajı hedef cihaza (emmc0) kopyalanıyor...").unwrap();
 
-    let device_block_size = 512; // Çoğu blok cihazın sektör boyutu 512 bayttır.
-    let mut copy_buffer = alloc::vec![0u8; device_block_size]; // Kopyalama buffer'ı (bir blok boyutunda)
+    // Kopyalama hedef cihazın blok boyutunun katlarıyla yapılır; kısmi blok yazmaları çekirdekte ek okuma gerektirir.
+    // Birden fazla blok tek yazmada gönderilir: çekirdek bunları önbelleği atlayıp tek çok bloklu komutla yazar.
+    let device_block_size = resource::control(target_device_handle, resource::CONTROL_BLOCK_GET_BLOCK_SIZE, 0).unwrap_or(512) as usize;
+    let copy_size = device_block_size * COPY_BLOCKS;
+    let mut copy_buffer = alloc::vec![0u8; copy_size]; // Kopyalama buffer'ı (COPY_BLOCKS blok)
     let total_blocks_to_copy = (image_size + device_block_size - 1) / device_block_size;
 
     let mut bytes_copied = 0;
 
     while bytes_copied < image_size {
         let bytes_left = image_size - bytes_copied;
-        let bytes_to_read_this_iter = core::cmp::min(bytes_left, device_block_size);
+        let bytes_to_read_this_iter = core::cmp::min(bytes_left, copy_size);
 
         // Kaynak imaj dosyasından bloğu oku
         let file_offset = bytes_copied;
@@ -254,7 +262,7 @@ pub extern "C" fn main(_argc: usize, _argv: *const *const u8) -> ! {
 
         // Hedef cihaza bloğu yaz
         let device_offset = bytes_copied;
-         match resource::write(target_device_handle, &copy_buffer[0..bytes_to_read_this_iter], device_offset, bytes_to_read_this_iter) { // resource::write'ın offset'li versiyonu kullanılıyor
+        match resource::write_at(target_device_handle, &copy_buffer[0..bytes_to_read_this_iter], device_offset as u64) {
              Ok(bytes_written) if bytes_written == bytes_to_read_this_iter => {
                   // Başarıyla yazıldı
              }
@@ -270,13 +278,18 @@ pub extern "C" fn main(_argc: usize, _argv: *const *const u8) -> ! {
 
         bytes_copied += bytes_to_read_this_iter;
 
-        // İlerleme göstergesi
-        let blocks_copied = bytes_copied / device_block_size;
-        if blocks_copied % 100 == 0 {
+        // İlerleme göstergesi (yaklaşık her 256 KB'ta bir nokta)
+        if (bytes_copied / copy_size) % 16 == 0 {
             write!(console_writer, ".").unwrap();
         }
     }
 
+    // Cihazın yazma önbelleği boşaltılır; yeniden başlatma başarısız olup güç kesilirse imaj yarım kalmasın.
+    if let Err(e) = resource::control(target_device_handle, resource::CONTROL_BLOCK_FLUSH, 0) {
+         writeln!(console_writer, "\nHata: Hedef cihaz önbelleği boşaltılamadı: {:?}", e).unwrap();
+         task::exit(-14);
+    }
+
     writeln!(console_writer, "\nİmaj kopyalama tamamlandı ({} bayt).", bytes_copied).unwrap();
 
 
@@ -290,15 +303,16 @@ pub extern "C" fn main(_argc: usize, _argv: *const *const u8) -> ! {
     // --- 7. Aşama: Kurulum Tamamlandı ve Yeniden Başlatma ---
 
     writeln!(console_writer, "SahneBox kurulumu başarıyla tamamlandı!").unwrap();
-    writeln!(console_writer, "Lütfen cihazı yeniden başlatın (Reset veya Güç Kesme).").unwrap();
-
-    // TODO: İsteğe bağlı: Yeniden başlatma syscall'ı varsa kullan.
-     kernel::reboot().unwrap_or_else(|e| { writeln!(console_writer, "Yeniden başlatma syscall hatası: {:?}", e).unwrap(); });
-    // Reboot syscall yoksa veya başarısız olursa, kullanıcı gücü kesmeli.
+    writeln!(console_writer, "Cihaz yeniden başlatılıyor...").unwrap();
 
+    // Çekirdek diğer görevleri durdurur, önbellekleri diske yazar ve cihazı sıfırlar.
+    // Başarılı olursa geri dönülmez; kurulum sihirbazı ayrıcalıklı değilse PermissionDenied döner.
+    if let Err(e) = kernel::reboot() {
+        writeln!(console_writer, "Yeniden başlatma başarısız: {:?}", e).unwrap();
+        writeln!(console_writer, "Lütfen cihazı yeniden başlatın (Reset veya Güç Kesme).").unwrap();
+    }
 
-    // Kurulum uygulaması burada biter. task::exit ile çıkar.
-    // Eğer reboot syscall yoksa veya başarısız olursa, burada sonsuz döngüye girer.
+    // Yeniden başlatılamazsa kurulum burada bekler; kullanıcı gücü kesmeli.
     loop { core::hint::spin_loop(); } // Kurulum bitti, bekle
 
     // Kaynak Handle'ları (console, touchscreen, source_device, target_device)
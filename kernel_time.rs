@@ -0,0 +1,99 @@
+// main_kernel/kernel_time.rs
+// Duvar Saati (Wall-Clock)
+// Monoton zamanlayıcı (drivers::timer) açılıştan beri geçen süreyi verir ve asla geri gitmez.
+// Duvar saati bunun üzerine "açılış anının Unix zamanı" ofsetini ekler: ofset açılışta RTC'den
+// okunur, set_time ile değiştirilir. Uyku süreleri ve zaman aşımları her zaman monoton saati kullanır;
+// saatin ayarlanması bekleyen görevleri etkilemez.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::calendar::DateTime;
+use crate::drivers::{rtc, timer};
+use crate::printk;
+use crate::sahne64::SahneError;
+use crate::sched;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+// Sistem çağrısı dönüş değeri i64 olduğundan (negatif değerler hata kodudur) duvar saati
+// i64::MAX nanosaniyeyi (2262 yılı) geçemez.
+const MAX_WALL_TIME_NS: u64 = i64::MAX as u64;
+
+// Monoton saatin sıfır anına (açılış) karşılık gelen Unix zamanı (nanosaniye).
+static BOOT_WALL_TIME_NS: AtomicU64 = AtomicU64::new(0);
+
+// Duvar saatini RTC'den başlatır. timer::init'ten sonra çağrılmalıdır.
+pub fn init() {
+    let unix_ns = rtc::read_time_ns().min(MAX_WALL_TIME_NS);
+    BOOT_WALL_TIME_NS.store(unix_ns.saturating_sub(timer::now_ns()), Ordering::Relaxed);
+
+    let now = DateTime::from_unix(unix_ns / NANOS_PER_SECOND);
+    printk!("Saat: {:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC\n",
+        now.year, now.month, now.day, now.hour, now.minute, now.second);
+}
+
+// Şu anki Unix zamanı (nanosaniye).
+pub fn wall_time_ns() -> u64 {
+    BOOT_WALL_TIME_NS.load(Ordering::Relaxed)
+        .saturating_add(timer::now_ns())
+        .min(MAX_WALL_TIME_NS)
+}
+
+// Şu anki tarih ve saat (UTC).
+pub fn now() -> DateTime {
+    DateTime::from_unix(wall_time_ns() / NANOS_PER_SECOND)
+}
+
+// Duvar saatini ayarlar ve değeri kalıcı olması için RTC'ye de yazar.
+// Monoton saatin açılıştan beri geçen süresinden küçük bir zaman (yani açılışı 1970'ten önceye
+// taşıyacak bir değer) temsil edilemez ve reddedilir.
+pub fn set_wall_time_ns(unix_ns: u64) -> Result<(), SahneError> {
+    if unix_ns > MAX_WALL_TIME_NS {
+        return Err(SahneError::InvalidParameter);
+    }
+    let boot_wall_time = unix_ns.checked_sub(timer::now_ns()).ok_or(SahneError::InvalidParameter)?;
+    BOOT_WALL_TIME_NS.store(boot_wall_time, Ordering::Relaxed);
+    rtc::write_time_ns(unix_ns);
+    Ok(())
+}
+
+// SYSCALL_SET_TIME: Duvar saatini ayarlar. Sadece ayrıcalıklı görevler çağırabilir.
+pub fn sys_set_time(unix_ns: u64) -> Result<(), SahneError> {
+    let caller = sched::current_task().ok_or(SahneError::InvalidOperation)?;
+    if !caller.lock().is_privileged() {
+        return Err(SahneError::PermissionDenied);
+    }
+    drop(caller);
+    set_wall_time_ns(unix_ns)
+}
+
+// SYSCALL_GET_WALL_TIME: Şu anki Unix zamanı (nanosaniye).
+pub fn sys_get_wall_time() -> u64 {
+    wall_time_ns()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn wall_clock_follows_monotonic_clock() {
+        let saved = BOOT_WALL_TIME_NS.load(Ordering::Relaxed);
+        BOOT_WALL_TIME_NS.store(1_709_251_199 * NANOS_PER_SECOND, Ordering::Relaxed);
+
+        let first = wall_time_ns();
+        let second = wall_time_ns();
+        assert!(second >= first);
+        assert!(first >= 1_709_251_199 * NANOS_PER_SECOND);
+        assert_eq!(now().year, 2024);
+
+        BOOT_WALL_TIME_NS.store(saved, Ordering::Relaxed);
+    }
+
+    #[test_case]
+    fn set_time_rejects_unrepresentable_values() {
+        assert_eq!(set_wall_time_ns(0), Err(SahneError::InvalidParameter)); // Açılıştan önce
+        assert_eq!(set_wall_time_ns(u64::MAX), Err(SahneError::InvalidParameter));
+    }
+}
@@ -0,0 +1,136 @@
+// minimal_gtk4/src/layout.rs
+// Widget Yerleşim (Layout) Hesapları
+// Sadece boyut ve koordinat aritmetiği içerir; çizim veya pencereleme sistemine bağımlı değildir.
+
+use alloc::vec::Vec;
+
+/// Ekran koordinatlarında bir dikdörtgen (sol üst köşe + boyut).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Rect { x, y, width, height }
+    }
+
+    /// Nokta dikdörtgenin içinde mi? Sol ve üst kenar dahil, sağ ve alt kenar hariçtir.
+    pub fn contains(&self, px: i32, py: i32) -> bool {
+        // i64 ile hesaplanır; büyük boyutlarda i32 taşması olmaz.
+        let (px, py) = (px as i64, py as i64);
+        px >= self.x as i64
+            && py >= self.y as i64
+            && px < self.x as i64 + self.width as i64
+            && py < self.y as i64 + self.height as i64
+    }
+}
+
+/// `width` x `height` boyutundaki bir kutuyu `outer` içinde ortalar.
+/// Kutu dış dikdörtgenden büyükse taşan kısım iki yana eşit dağıtılır (negatif kaydırma).
+pub fn center_in(outer: Rect, width: u32, height: u32) -> Rect {
+    let dx = (outer.width as i64 - width as i64) / 2;
+    let dy = (outer.height as i64 - height as i64) / 2;
+    Rect::new(saturate(outer.x as i64 + dx), saturate(outer.y as i64 + dy), width, height)
+}
+
+/// Dikey kutunun (VBox) tercih ettiği boyut: en geniş çocuğun genişliği ve
+/// çocukların yükseklikleri ile aralarındaki boşlukların toplamı.
+pub fn vbox_preferred_size<I>(child_sizes: I, padding: u32) -> (u32, u32)
+where
+    I: IntoIterator<Item = (u32, u32)>,
+{
+    let mut max_width: u32 = 0;
+    let mut total_height: u32 = 0;
+    for (index, (width, height)) in child_sizes.into_iter().enumerate() {
+        max_width = max_width.max(width);
+        if index > 0 {
+            total_height = total_height.saturating_add(padding); // Son elemandan sonra boşluk yok
+        }
+        total_height = total_height.saturating_add(height);
+    }
+    (max_width, total_height)
+}
+
+/// Çocukları (x, y) noktasından başlayarak alt alta yerleştirir.
+/// Her çocuk tercih ettiği boyutu alır; dönen dikdörtgenler çocuklarla aynı sıradadır.
+pub fn vbox_layout<I>(x: i32, y: i32, child_sizes: I, padding: u32) -> Vec<Rect>
+where
+    I: IntoIterator<Item = (u32, u32)>,
+{
+    let mut rects = Vec::new();
+    let mut current_y = y as i64;
+    for (width, height) in child_sizes {
+        rects.push(Rect::new(x, saturate(current_y), width, height));
+        current_y += height as i64 + padding as i64;
+    }
+    rects
+}
+
+// i64 koordinatı i32 aralığına sıkıştırır.
+fn saturate(value: i64) -> i32 {
+    value.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_excludes_right_and_bottom_edges() {
+        let rect = Rect::new(10, 20, 5, 3);
+        assert!(rect.contains(10, 20));
+        assert!(rect.contains(14, 22));
+        assert!(!rect.contains(15, 22));
+        assert!(!rect.contains(14, 23));
+        assert!(!rect.contains(9, 20));
+        assert!(!Rect::new(0, 0, 0, 0).contains(0, 0));
+        // Taşma olmadan ekranın sağ alt köşesine uzanan dikdörtgen
+        assert!(Rect::new(i32::MAX - 1, 0, u32::MAX, 1).contains(i32::MAX, 0));
+    }
+
+    #[test]
+    fn centers_smaller_and_larger_boxes() {
+        let outer = Rect::new(100, 50, 40, 20);
+        assert_eq!(center_in(outer, 20, 10), Rect::new(110, 55, 20, 10));
+        assert_eq!(center_in(outer, 60, 20), Rect::new(90, 50, 60, 20));
+    }
+
+    #[test]
+    fn vbox_size_and_positions() {
+        let sizes = [(40, 16), (70, 26), (10, 0)];
+        assert_eq!(vbox_preferred_size(sizes, 5), (70, 16 + 5 + 26 + 5)); // Son çocuğun yüksekliği 0
+        assert_eq!(vbox_preferred_size([], 5), (0, 0));
+
+        let rects = vbox_layout(3, 7, sizes, 5);
+        assert_eq!(rects, [Rect::new(3, 7, 40, 16), Rect::new(3, 28, 70, 26), Rect::new(3, 59, 10, 0)]);
+    }
+
+    // Yerleşim, tercih edilen boyutla her zaman tutarlı olmalı:
+    // son çocuğun alt kenarı VBox yüksekliğine, en geniş çocuk VBox genişliğine eşittir.
+    #[test]
+    fn layout_matches_preferred_size() {
+        let mut state: u32 = 0x1234_5678;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        };
+        for _ in 0..1000 {
+            let count = (next() % 8) as usize;
+            let sizes: Vec<(u32, u32)> = (0..count).map(|_| (next() % 500, next() % 500)).collect();
+            let padding = next() % 20;
+            let (width, height) = vbox_preferred_size(sizes.iter().copied(), padding);
+            let rects = vbox_layout(0, 0, sizes.iter().copied(), padding);
+            assert_eq!(rects.len(), count);
+            assert_eq!(rects.iter().map(|r| r.width).max().unwrap_or(0), width);
+            let bottom = rects.last().map(|r| r.y as u32 + r.height).unwrap_or(0);
+            assert_eq!(bottom, height);
+        }
+    }
+}
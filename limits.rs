@@ -0,0 +1,209 @@
+// main_kernel/limits.rs
+// Görev Başına Kaynak Sınırları (Kotalar)
+// Bellek, Handle, iş parçacığı ve paylaşımlı bellek kullanımını görev bazında sınırlar.
+// Aynı görevin iş parçacıkları tek bir hesabı paylaşır; fork ile oluşan çocuklar
+// ebeveynin sınırlarını devralır ama kendi kullanım sayaçlarıyla başlar.
+
+use alloc::sync::Arc;
+use spin::Mutex;
+use crate::sahne64::{ResourceLimits, SahneError};
+use crate::sched;
+
+// Varsayılan sınırlar. 2 MB RAM'li bir cihazda tek bir görevin çekirdeği
+// aç bırakmaması için bilinçli olarak küçük tutuldu.
+pub const DEFAULT_LIMITS: ResourceLimits = ResourceLimits {
+    memory_bytes: 512 * 1024,   // Görev başına en fazla 512 KB heap
+    handles: 32,                // Aynı anda açık en fazla 32 Handle
+    threads: 8,                 // Ana iş parçacığı dahil en fazla 8 iş parçacığı
+    shared_mem_bytes: 64 * 1024, // Toplam 64 KB paylaşımlı bellek
+};
+
+// Bir görevin (ve tüm iş parçacıklarının) kaynak hesabı.
+#[derive(Debug)]
+pub struct TaskResources {
+    limits: ResourceLimits,
+    memory_bytes: usize,
+    handles: usize,
+    threads: usize,
+    shared_mem_bytes: usize,
+}
+
+// İş parçacıkları arasında paylaşılan hesap türü.
+pub type SharedResources = Arc<Mutex<TaskResources>>;
+
+// Kullanım + istenen miktar sınırı aşıyor mu?
+fn exceeds(used: usize, amount: usize, limit: u64) -> bool {
+    match used.checked_add(amount) {
+        Some(total) => total as u64 > limit,
+        None => true,
+    }
+}
+
+impl TaskResources {
+    // Yeni bir hesap oluşturur. Hesabı oluşturan ana iş parçacığı baştan sayılır.
+    pub const fn new(limits: ResourceLimits) -> Self {
+        TaskResources {
+            limits,
+            memory_bytes: 0,
+            handles: 0,
+            threads: 1,
+            shared_mem_bytes: 0,
+        }
+    }
+
+    // Çocuk görev için hesap: sınırlar devralınır, kullanım sıfırdan başlar.
+    pub fn inherit(parent: &TaskResources) -> Self {
+        TaskResources::new(parent.limits)
+    }
+
+    pub fn limits(&self) -> ResourceLimits {
+        self.limits
+    }
+
+    // Yeni sınırlar mevcut kullanımdan küçük olabilir; bu durumda yalnızca
+    // yeni talepler reddedilir, mevcut kaynaklar geri alınmaz.
+    pub fn set_limits(&mut self, limits: ResourceLimits) {
+        self.limits = limits;
+    }
+
+    pub fn charge_memory(&mut self, size: usize) -> Result<(), SahneError> {
+        if exceeds(self.memory_bytes, size, self.limits.memory_bytes) {
+            return Err(SahneError::OutOfMemory);
+        }
+        self.memory_bytes += size;
+        Ok(())
+    }
+
+    pub fn release_memory(&mut self, size: usize) {
+        self.memory_bytes = self.memory_bytes.saturating_sub(size);
+    }
+
+    pub fn charge_handle(&mut self) -> Result<(), SahneError> {
+        if exceeds(self.handles, 1, self.limits.handles) {
+            return Err(SahneError::HandleLimitExceeded);
+        }
+        self.handles += 1;
+        Ok(())
+    }
+
+    pub fn release_handle(&mut self) {
+        self.handles = self.handles.saturating_sub(1);
+    }
+
+    // İş parçacığı sınırı aşılırsa thread oluşturma TaskCreationFailed yerine
+    // açıkça kaynak yetersizliği olarak raporlanır.
+    pub fn charge_thread(&mut self) -> Result<(), SahneError> {
+        if exceeds(self.threads, 1, self.limits.threads) {
+            return Err(SahneError::OutOfMemory);
+        }
+        self.threads += 1;
+        Ok(())
+    }
+
+    pub fn release_thread(&mut self) {
+        self.threads = self.threads.saturating_sub(1);
+    }
+
+    // Hesabı paylaşan canlı iş parçacığı sayısı.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn charge_shared_mem(&mut self, size: usize) -> Result<(), SahneError> {
+        if exceeds(self.shared_mem_bytes, size, self.limits.shared_mem_bytes) {
+            return Err(SahneError::OutOfMemory);
+        }
+        self.shared_mem_bytes += size;
+        Ok(())
+    }
+
+    pub fn release_shared_mem(&mut self, size: usize) {
+        self.shared_mem_bytes = self.shared_mem_bytes.saturating_sub(size);
+    }
+}
+
+// Geçerli görevin hesabı üzerinde işlem yapar.
+// Henüz çalışan bir görev yoksa (erken açılış) işlem çekirdek adına yapılmıştır ve sınırlanmaz.
+fn with_current<F>(f: F) -> Result<(), SahneError>
+where
+    F: FnOnce(&mut TaskResources) -> Result<(), SahneError>,
+{
+    let task_arc = match sched::current_task() {
+        Some(task) => task,
+        None => return Ok(()),
+    };
+    // Görev kilidini hesap kilidinden önce bırak (kilit sırası: Task -> TaskResources).
+    let resources = task_arc.lock().resources();
+    let mut account = resources.lock();
+    f(&mut account)
+}
+
+pub fn charge_memory(size: usize) -> Result<(), SahneError> {
+    with_current(|r| r.charge_memory(size))
+}
+
+pub fn release_memory(size: usize) {
+    let _ = with_current(|r| { r.release_memory(size); Ok(()) });
+}
+
+pub fn charge_handle() -> Result<(), SahneError> {
+    with_current(|r| r.charge_handle())
+}
+
+pub fn release_handle() {
+    let _ = with_current(|r| { r.release_handle(); Ok(()) });
+}
+
+pub fn charge_shared_mem(size: usize) -> Result<(), SahneError> {
+    with_current(|r| r.charge_shared_mem(size))
+}
+
+pub fn release_shared_mem(size: usize) {
+    let _ = with_current(|r| { r.release_shared_mem(size); Ok(()) });
+}
+
+// Sistem çağrısındaki görev ID'sini göreve çevirir.
+// sahne64::task::CURRENT_TASK (u64::MAX) çağıran görevi belirtir.
+fn target_task(task_id: u64) -> Result<Arc<Mutex<sched::Task>>, SahneError> {
+    if task_id == u64::MAX {
+        sched::current_task().ok_or(SahneError::InvalidOperation)
+    } else {
+        sched::task_by_id(task_id as usize).ok_or(SahneError::ResourceNotFound)
+    }
+}
+
+// Kullanıcının verdiği ResourceLimits işaretçisi hizalı ve çağıranın belleğinde olmalı.
+fn check_limits_ptr(ptr: *const ResourceLimits) -> Result<(), SahneError> {
+    if !ptr.is_aligned() {
+        return Err(SahneError::InvalidAddress);
+    }
+    crate::mm::check_user_range(ptr as usize, core::mem::size_of::<ResourceLimits>())
+}
+
+// SYSCALL_GET_LIMITS: Bir görevin sınırlarını kullanıcı tamponuna yazar.
+// Sınırları okumak için ayrıcalık gerekmez.
+pub fn sys_get_limits(task_id: u64, out_ptr: *mut ResourceLimits) -> Result<(), SahneError> {
+    check_limits_ptr(out_ptr)?;
+    let target = target_task(task_id)?;
+    let resources = target.lock().resources();
+    let limits = resources.lock().limits();
+    unsafe { core::ptr::write(out_ptr, limits); }
+    Ok(())
+}
+
+// SYSCALL_SET_LIMITS: Bir görevin sınırlarını değiştirir. Sadece ayrıcalıklı görevler çağırabilir.
+// Sınırlar görevin tüm iş parçacıklarına aynı anda uygulanır.
+pub fn sys_set_limits(task_id: u64, limits_ptr: *const ResourceLimits) -> Result<(), SahneError> {
+    check_limits_ptr(limits_ptr)?;
+    let caller = sched::current_task().ok_or(SahneError::InvalidOperation)?;
+    if !caller.lock().is_privileged() {
+        return Err(SahneError::PermissionDenied);
+    }
+    drop(caller);
+
+    let new_limits = unsafe { core::ptr::read(limits_ptr) };
+    let target = target_task(task_id)?;
+    let resources = target.lock().resources();
+    resources.lock().set_limits(new_limits);
+    Ok(())
+}
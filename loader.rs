@@ -6,19 +6,39 @@
 
 extern crate alloc;
 
-use alloc::boxed::Box;
-use alloc::string::String;
 use alloc::vec::Vec;
 use core::slice;
 use core::ptr;
 use core::mem;
-use core::any::Any; // Argüman belleğini Box içinde tutmak için
 
 // SahneBox Çekirdek API'si
-use crate::sahne64::{self, memory, task, SahneError};
+use crate::sahne64::{memory, SahneError};
 
 // Minimal Dosya Sistemi Kütüphanesi
-use crate::filesystem::ext::ExtFilesystem; // ext.rs dosyasını kullanacak
+use crate::filesystem::ext::{BlockDevice, ExtFilesystem}; // ext.rs dosyasını kullanacak
+
+
+/// Yükleyicinin program ve argüman belleğini aldığı yer.
+/// SahneBox'ta sahne64::memory syscall'larıdır; testlerde host heap'i kullanılır.
+pub trait ProgramMemory {
+    /// En az 8 bayt hizalı `size` baytlık bir blok ayırır.
+    fn allocate(&self, size: usize) -> Result<*mut u8, SahneError>;
+    /// allocate ile alınmış bloğu aynı boyutla geri verir.
+    fn release(&self, ptr: *mut u8, size: usize) -> Result<(), SahneError>;
+}
+
+/// Çekirdeğin bellek syscall'ları üzerinden çalışan varsayılan sağlayıcı.
+pub struct SahneMemory;
+
+impl ProgramMemory for SahneMemory {
+    fn allocate(&self, size: usize) -> Result<*mut u8, SahneError> {
+        memory::allocate(size)
+    }
+
+    fn release(&self, ptr: *mut u8, size: usize) -> Result<(), SahneError> {
+        memory::release(ptr, size)
+    }
+}
 
 
 // SBXE Yürütülebilir Dosya Formatı Yapıları (On-Disk Format)
@@ -52,32 +72,65 @@ const SBXE_SECTION_TYPE_BSS: u32 = 3; // Uninitialized Data
 
 // Yüklenmiş Program Bilgisi
 // Loader tarafından program belleğe yüklendikten sonra döndürülür.
+// Bellek otomatik olarak serbest bırakılmaz: program sonlandığında release çağrılmalıdır.
 pub struct LoadedProgram {
     pub entry_point: usize, // Programın başlayacağı adres
-    // TODO: Ayrılan bellek bloklarına işaretçiler ve boyutlar (program sonlandığında serbest bırakmak için)
-    // Şu an sadece bir ana bellek bloğu varsayalım.
-    program_memory_block: Box<dyn Any>, // Ayrılan belleği tutan Box (serbest bırakmak için)
-    program_memory_ptr: *mut u8,
+    program_memory_ptr: *mut u8, // Tüm bölümleri içeren tek bellek bloğu
     program_memory_size: usize,
-    // TODO: Argüman belleği (eğer program argüman alıyorsa)
-    arg_memory_block: Option<Box<dyn Any>>,
 }
 
 impl LoadedProgram {
-     // Program sonlandığında belleği serbest bırakmak için çağrılmalı.
-     // Kernel, task sonlandığında bu struct'ı alıp drop etmeli mi?
-     // Veya task exit syscall'ı belleği serbest bırakma bilgisi içermeli mi?
-     // En basiti: Loader sadece allocate etsin, serbest bırakma sorumluluğu kernel veya task bitiş handlerında olsun.
-     // Eğer Box kullanılıyorsa, Box drop edildiğinde bellek serbest bırakılır (GlobalAlloc sayesinde).
-     // Ama Box'ı kernelin drop etmesi için Box'ı kernel struct'larına eklemek lazım.
-     // Veya loader, serbest bırakılacak ptr ve size bilgisini dönmeli.
+    /// Programın yüklendiği bellek bloğunun başlangıç adresi.
+    pub fn base_address(&self) -> usize {
+        self.program_memory_ptr as usize
+    }
+
+    /// Programın bellekteki toplam boyutu (BSS dahil).
+    pub fn memory_size(&self) -> usize {
+        self.program_memory_size
+    }
+
+    /// Program belleğini, ayrıldığı sağlayıcıya geri verir.
+    pub fn release<M: ProgramMemory>(self, memory: &M) -> Result<(), SahneError> {
+        memory.release(self.program_memory_ptr, self.program_memory_size)
+    }
+}
+
+/// prepare_program_args tarafından ayrılan argüman belleği.
+pub struct ArgMemory {
+    ptr: *mut u8,
+    size: usize,
+}
+
+impl ArgMemory {
+    /// Argüman belleğini geri verir. Argüman yoksa bir şey yapmaz.
+    pub fn release<M: ProgramMemory>(self, memory: &M) -> Result<(), SahneError> {
+        if self.size == 0 {
+            return Ok(());
+        }
+        memory.release(self.ptr, self.size)
+    }
+}
+
+// Dosyadaki [offset, offset + len) aralığının dosya içinde kaldığını doğrular.
+fn file_range(offset: u32, len: u32, file_size: usize) -> Result<core::ops::Range<usize>, SahneError> {
+    let start = offset as usize;
+    let end = start.checked_add(len as usize).ok_or(SahneError::InvalidParameter)?;
+    if end > file_size {
+        return Err(SahneError::InvalidParameter);
+    }
+    Ok(start..end)
 }
 
 
 /// Belirtilen i-node'a sahip SBXE yürütülebilir dosyasını okur, parse eder ve belleğe yükler.
 /// Başarılı olursa, programın giriş noktası adresini ve bellek bilgilerini döndürür.
-pub fn load_executable(fs: &ExtFilesystem, inode_number: u32) -> Result<LoadedProgram, SahneError> {
-    // 1. Yürütülebilir dosyayı oku
+pub fn load_executable<D: BlockDevice, M: ProgramMemory>(
+    fs: &ExtFilesystem<D>,
+    inode_number: u32,
+    memory: &M,
+) -> Result<LoadedProgram, SahneError> {
+    // Yürütülebilir dosyayı oku
     let program_inode = fs.read_inode(inode_number)?;
     let file_size = program_inode.i_size as usize;
 
@@ -85,161 +138,163 @@ pub fn load_executable(fs: &ExtFilesystem, inode_number: u32) -> Result<LoadedPr
         return Err(SahneError::InvalidParameter); // Dosya çok kısa
     }
 
-    let mut program_data = alloc::vec![0u8; file_size];
-    fs.read_file(&program_inode, &mut program_data, 0)?; // Dosyanın tamamını oku
+    // Dosya boyutu diskten geldiği için tahsis panik etmemeli.
+    let mut program_data = crate::sahne64::buffer::zeroed_buffer(file_size)?;
+    if fs.read_file(&program_inode, &mut program_data, 0)? != file_size { // Dosyanın tamamını oku
+        return Err(SahneError::InvalidOperation);
+    }
 
-    // 2. Dosya Başlığını Parse Et
-    let file_header: &SbxeFileHeader = unsafe {
-        let ptr = program_data.as_ptr() as *const SbxeFileHeader;
-        &*ptr // Dereference
-        // read_unaligned() gerekebilir eğer packed struct kullanıyorsak
-         ptr.read_unaligned()
-    };
+    load_image(&program_data, memory)
+}
+
+/// Bellekteki bir SBXE imajını doğrular ve belleğe yükler.
+/// Tüm başlıklar bellek ayrılmadan önce doğrulanır; bozuk bir dosya panik yerine
+/// InvalidParameter (veya NotSupported) ile reddedilir.
+pub fn load_image<M: ProgramMemory>(program_data: &[u8], memory: &M) -> Result<LoadedProgram, SahneError> {
+    let file_size = program_data.len();
+    if file_size < mem::size_of::<SbxeFileHeader>() {
+        return Err(SahneError::InvalidParameter); // Dosya çok kısa
+    }
+
+    // 1. Dosya Başlığını Parse Et (packed yapı olduğu için read_unaligned)
+    let file_header = unsafe { (program_data.as_ptr() as *const SbxeFileHeader).read_unaligned() };
 
     if file_header.magic != SBXE_MAGIC {
-        // printk!("Hata: Geçersiz SBXE sihirli sayısı: {:#x}\n", file_header.magic);
         return Err(SahneError::InvalidParameter); // Geçersiz format
     }
     if file_header.architecture != 1 { // RISC-V 64 (varsayım)
-         printk!("Hata: Desteklenmeyen mimari: {}\n", file_header.architecture);
         return Err(SahneError::NotSupported); // Yanlış mimari
     }
-    if (file_header.section_header_offset as usize) < file_header.header_size as usize ||
-       (file_header.section_header_offset as usize) + (file_header.num_sections as usize * mem::size_of::<SbxeSectionHeader>()) > file_size
+    let section_table_size = (file_header.num_sections as usize)
+        .checked_mul(mem::size_of::<SbxeSectionHeader>())
+        .ok_or(SahneError::InvalidParameter)?;
+    if section_table_size > u32::MAX as usize
+        || (file_header.section_header_offset as usize) < file_header.header_size as usize
     {
-          printk!("Hata: Bölüm başlıkları dosya sınırları dışında.\n");
-         return Err(SahneError::InvalidParameter); // Bölüm başlıkları geçersiz konumda
+        return Err(SahneError::InvalidParameter); // Bölüm başlıkları geçersiz konumda
     }
+    let table_range = file_range(file_header.section_header_offset, section_table_size as u32, file_size)?;
 
+    // 2. Bölüm başlıklarını kopyala ve doğrula; toplam bellek boyutunu hesapla
+    let mut section_headers: Vec<SbxeSectionHeader> = Vec::new();
+    section_headers.try_reserve_exact(file_header.num_sections as usize).map_err(|_| SahneError::OutOfMemory)?;
+    let mut max_load_address: usize = 0;
 
-    // 3. Toplam Bellek Boyutunu Hesapla ve Ayır
-    let section_headers_ptr = unsafe { program_data.as_ptr().add(file_header.section_header_offset as usize) as *const SbxeSectionHeader };
-    let section_headers_slice = unsafe { slice::from_raw_parts(section_headers_ptr, file_header.num_sections as usize) };
-
-    let mut total_memory_size: usize = 0;
-    let mut max_load_address = 0;
+    for i in 0..file_header.num_sections as usize {
+        let header_offset = table_range.start + i * mem::size_of::<SbxeSectionHeader>();
+        let section_header = unsafe {
+            (program_data.as_ptr().add(header_offset) as *const SbxeSectionHeader).read_unaligned()
+        };
 
-    for section_header in section_headers_slice {
-        // Bölümlerin en yüksek yükleneceği adresi bul (toplam bellek bloğunun boyutu için)
-        let section_end_offset = section_header.load_address_offset + section_header.size_in_memory;
-        if section_end_offset > max_load_address {
-            max_load_address = section_end_offset;
+        match section_header.type_ {
+            SBXE_SECTION_TYPE_TEXT | SBXE_SECTION_TYPE_DATA => {
+                // Bölüm verisi dosya sınırları içinde olmalı ve bellekteki alana sığmalı
+                file_range(section_header.offset_in_file, section_header.size_in_file, file_size)?;
+                if section_header.size_in_file > section_header.size_in_memory {
+                    return Err(SahneError::InvalidParameter);
+                }
+            }
+            SBXE_SECTION_TYPE_BSS => {}
+            _ => return Err(SahneError::InvalidParameter), // Bilinmeyen bölüm türü
         }
-
-        // TODO: Bölüm verisinin dosyada sınırları içinde olup olmadığını kontrol et.
-         if section_header.offset_in_file + section_header.size_in_file > file_size { ... }
-
         // TODO: Bölüm bayraklarını kontrol et (okunabilir, yazılabilir, çalıştırılabilir).
+
+        // Bölümlerin en yüksek yükleneceği adresi bul (toplam bellek bloğunun boyutu için)
+        let section_end_offset = (section_header.load_address_offset as usize)
+            .checked_add(section_header.size_in_memory as usize)
+            .ok_or(SahneError::InvalidParameter)?;
+        max_load_address = max_load_address.max(section_end_offset);
+        section_headers.push(section_header);
     }
 
-    total_memory_size = max_load_address as usize;
+    let total_memory_size = max_load_address;
     if total_memory_size == 0 {
-         // printk!("Hata: Program bölümleri tanımlı değil veya boyut 0.\n");
-         return Err(SahneError::InvalidParameter); // Programın bellekte boyutu 0
+        return Err(SahneError::InvalidParameter); // Programın bellekte boyutu 0
+    }
+    // Giriş noktası program belleğinin içinde olmalı
+    if file_header.entry_point_offset as usize >= total_memory_size {
+        return Err(SahneError::InvalidParameter);
     }
 
-    // Program için bellekte tek bir bitişik blok ayır
+    // 3. Program için bellekte tek bir bitişik blok ayır
     // Bu blok, tüm bölümleri (text, data, bss) içerecektir.
-    // Align gereksinimleri olabilir, şimdilik 8 bayt hizalama varsayalım.
-    let program_memory = memory::allocate(total_memory_size)?; // sahne64::memory::allocate kullan
-
+    let program_memory = memory.allocate(total_memory_size)?;
     if program_memory.is_null() {
-          printk!("Hata: Program belleği tahsis edilemedi ({} bayt).\n", total_memory_size);
-         return Err(SahneError::OutOfMemory);
+        return Err(SahneError::OutOfMemory);
     }
-    let program_memory_block = unsafe { Box::from_raw(program_memory as *mut u8) }; // Belleği Box'a sarmala
-
 
     // 4. Bölümleri Belleğe Yükle ve BSS'i Sıfırla
-    let program_base_address = program_memory as usize;
-
-    for section_header in section_headers_slice {
-        let load_address = program_base_address + section_header.load_address_offset as usize;
-
-        match section_header.type_ {
-            SBXE_SECTION_TYPE_TEXT | SBXE_SECTION_TYPE_DATA => {
-                // Dosyadan belleğe kopyala
-                if section_header.size_in_file > 0 {
-                    let file_data_ptr = unsafe { program_data.as_ptr().add(section_header.offset_in_file as usize) };
-                    unsafe {
-                        ptr::copy_nonoverlapping(file_data_ptr, load_address as *mut u8, section_header.size_in_file as usize);
-                    }
-                }
-            }
-            SBXE_SECTION_TYPE_BSS => {
-                // Bellek alanını sıfırla (0'larla doldur)
-                if section_header.size_in_memory > 0 {
-                    unsafe {
-                         // core::intrinsics::write_bytes(load_address as *mut u8, 0, section_header.size_in_memory as usize); // intrinsics kullanmak yerine ptr::write_bytes daha yaygın
-                         ptr::write_bytes(load_address as *mut u8, 0, section_header.size_in_memory as usize);
-                    }
-                }
-            }
-            _ => {
-                 printk!("Hata: Bilinmeyen bölüm türü: {}", section_header.type_);
-                // Bilinmeyen bölüm türü hata olarak kabul edilebilir veya atlanabilir.
-                 return Err(SahneError::InvalidParameter);
-            }
+    // Bölümler arasındaki boşluklar da dahil tüm blok önce sıfırlanır;
+    // böylece DATA'nın dosyada olmayan kuyruğu ve BSS sıfır olur.
+    unsafe {
+        ptr::write_bytes(program_memory, 0, total_memory_size);
+    }
+    for section_header in &section_headers {
+        if section_header.type_ == SBXE_SECTION_TYPE_BSS || section_header.size_in_file == 0 {
+            continue;
+        }
+        let source = &program_data[section_header.offset_in_file as usize..][..section_header.size_in_file as usize];
+        unsafe {
+            let load_address = program_memory.add(section_header.load_address_offset as usize);
+            ptr::copy_nonoverlapping(source.as_ptr(), load_address, source.len());
         }
         // TODO: Relocations'ı uygula (Statik linkleme kullanılıyorsa genellikle gerekmez)
     }
 
     // 5. Giriş Noktası Adresini Hesapla
-    let entry_point_address = program_base_address + file_header.entry_point_offset as usize;
-     printk!("Program belleğe yüklendi: {:#x}, Giriş noktası: {:#x}\n", program_base_address, entry_point_address);
-
+    let entry_point_address = program_memory as usize + file_header.entry_point_offset as usize;
 
     Ok(LoadedProgram {
         entry_point: entry_point_address,
-        program_memory_block: program_memory_block as Box<dyn Any>, // Box'ı dyn Any olarak sakla
         program_memory_ptr: program_memory,
         program_memory_size: total_memory_size,
-        arg_memory_block: None, // Argüman belleği henüz hazırlanmadı
     })
 }
 
 /// Program argümanlarını (argc, argv) hazırlar ve belleğe kopyalar.
 /// Programın ana fonksiyonuna geçirilecek formatı oluşturur.
-/// Dönüş değeri: (argc, argv_ptr, ayrılan bellek bloklarını tutan Box).
-pub fn prepare_program_args(args: Vec<String>) -> Result<(usize, *const *const u8, Box<dyn Any>), SahneError> {
+/// Dönüş değeri: (argc, argv_ptr, program bitince geri verilecek argüman belleği).
+pub fn prepare_program_args<A: AsRef<str>, M: ProgramMemory>(
+    args: &[A],
+    memory: &M,
+) -> Result<(usize, *const *const u8, ArgMemory), SahneError> {
     let argc = args.len();
     // argv işaretçi dizisi + argüman stringleri için toplam bellek boyutu
     // argv dizisi: argc * usize (her işaretçi için)
     // stringler: Her stringin baytları + null terminator (toplam byte sayısı)
-    let argv_array_size = argc * mem::size_of::<*const u8>();
-    let total_string_bytes: usize = args.iter().map(|s| s.len() + 1).sum(); // +1 for null terminator
-
-    let total_mem_needed = argv_array_size + total_string_bytes;
+    let argv_array_size = argc.checked_mul(mem::size_of::<*const u8>()).ok_or(SahneError::InvalidParameter)?;
+    let mut total_string_bytes: usize = 0;
+    for arg in args {
+        total_string_bytes = total_string_bytes
+            .checked_add(arg.as_ref().len() + 1) // +1 for null terminator
+            .ok_or(SahneError::InvalidParameter)?;
+    }
+    let total_mem_needed = argv_array_size.checked_add(total_string_bytes).ok_or(SahneError::InvalidParameter)?;
 
     if total_mem_needed == 0 {
         // Argüman yoksa
-        return Ok((0, ptr::null(), Box::new(()))); // Boş Box döndür
+        return Ok((0, ptr::null(), ArgMemory { ptr: ptr::null_mut(), size: 0 }));
     }
 
     // Argümanlar için tek bir bellek bloğu ayır
-    // Genellikle argv işaretçi dizisi başta, ardından string verileri gelir.
-    // 8 bayt hizalama varsayımı.
-    let arg_memory = memory::allocate(total_mem_needed)?; // sahne64::memory::allocate kullan
-
-     if arg_memory.is_null() {
-         // printk!("Hata: Argüman belleği tahsis edilemedi ({} bayt).\n", total_mem_needed);
-         return Err(SahneError::OutOfMemory);
+    // argv işaretçi dizisi başta, ardından string verileri gelir.
+    let arg_memory = memory.allocate(total_mem_needed)?;
+    if arg_memory.is_null() {
+        return Err(SahneError::OutOfMemory);
     }
-    let arg_memory_block = unsafe { Box::from_raw(arg_memory as *mut u8) }; // Belleği Box'a sarmala
 
     let argv_ptr_array = arg_memory as *mut *mut u8; // argv işaretçi dizisinin başlangıcı
     let mut current_string_ptr = unsafe { arg_memory.add(argv_array_size) }; // String verisinin başladığı yer
 
-
     // Argüman stringlerini kopyala ve argv dizisini doldur
-    for (i, arg) in args.into_iter().enumerate() {
+    for (i, arg) in args.iter().enumerate() {
         // argv dizisine geçerli stringin adresini yaz
         unsafe {
             ptr::write(argv_ptr_array.add(i), current_string_ptr);
         }
 
         // String verisini kopyala (null terminator ile)
-        let src_slice = arg.as_bytes();
+        let src_slice = arg.as_ref().as_bytes();
         let dst_slice = unsafe { slice::from_raw_parts_mut(current_string_ptr, src_slice.len() + 1) }; // +1 for null terminator
         dst_slice[0..src_slice.len()].copy_from_slice(src_slice);
         dst_slice[src_slice.len()] = 0; // Null terminator
@@ -248,7 +303,140 @@ pub fn prepare_program_args(args: Vec<String>) -> Result<(usize, *const *const u
         current_string_ptr = unsafe { current_string_ptr.add(src_slice.len() + 1) };
     }
 
-     printk!("Argümanlar hazırlandı: argc {}, argv {:#p}\n", argc, argv_ptr_array);
+    Ok((argc, argv_ptr_array as *const *const u8, ArgMemory { ptr: arg_memory, size: total_mem_needed }))
+}
 
-    Ok((argc, argv_ptr_array as *const *const u8, arg_memory_block as Box<dyn Any>)) // argc, argv pointer'ı ve bellek bloğunu dön
-}
\ No newline at end of file
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::alloc::{alloc_zeroed, dealloc, Layout};
+    use std::cell::Cell;
+    use std::ffi::CStr;
+    use std::vec;
+
+    // fixtures/make_fixtures.sh ile üretilen örnek program.
+    static HELLO: &[u8] = include_bytes!("fixtures/hello.sbxe");
+
+    // Host heap'i üzerinden çalışan, açık blokları sayan test sağlayıcısı.
+    struct HostMemory {
+        live: Cell<usize>,
+    }
+
+    impl HostMemory {
+        fn new() -> Self {
+            HostMemory { live: Cell::new(0) }
+        }
+    }
+
+    impl ProgramMemory for HostMemory {
+        fn allocate(&self, size: usize) -> Result<*mut u8, SahneError> {
+            // Cihazdaki gibi sınırlı bellek: bozuk başlıkların istediği dev blokları reddet.
+            if size > 1024 * 1024 {
+                return Err(SahneError::OutOfMemory);
+            }
+            let layout = Layout::from_size_align(size, 8).map_err(|_| SahneError::OutOfMemory)?;
+            // Sıfırlama yapılmadığını yakalamak için bloğu 0xAA ile doldur.
+            let block = unsafe { alloc_zeroed(layout) };
+            unsafe { ptr::write_bytes(block, 0xAA, size) };
+            self.live.set(self.live.get() + 1);
+            Ok(block)
+        }
+
+        fn release(&self, ptr: *mut u8, size: usize) -> Result<(), SahneError> {
+            unsafe { dealloc(ptr, Layout::from_size_align(size, 8).unwrap()) };
+            self.live.set(self.live.get() - 1);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn loads_fixture_sections() {
+        let memory = HostMemory::new();
+        let program = load_image(HELLO, &memory).unwrap();
+        assert_eq!(program.memory_size(), 0x240); // BSS 0x200 + 64
+        assert_eq!(program.entry_point, program.base_address() + 4);
+
+        let image = unsafe { slice::from_raw_parts(program.base_address() as *const u8, program.memory_size()) };
+        // TEXT dosyadan kopyalanır
+        assert_eq!(&image[..16], &HELLO[20 + 3 * 24..][..16]);
+        // DATA'nın dosyada olmayan kuyruğu, bölümler arası boşluk ve BSS sıfırdır
+        assert!(image[0x100 + 12..0x120].iter().all(|&b| b == 0));
+        assert!(image[0x120..0x200].iter().all(|&b| b == 0));
+        assert!(image[0x200..].iter().all(|&b| b == 0));
+
+        program.release(&memory).unwrap();
+        assert_eq!(memory.live.get(), 0);
+    }
+
+    #[test]
+    fn rejects_bad_headers_without_allocating() {
+        let memory = HostMemory::new();
+        let mut bad_magic = HELLO.to_vec();
+        bad_magic[0] ^= 0xFF;
+        assert_eq!(load_image(&bad_magic, &memory).err(), Some(SahneError::InvalidParameter));
+
+        let mut bad_arch = HELLO.to_vec();
+        bad_arch[4] = 7;
+        assert_eq!(load_image(&bad_arch, &memory).err(), Some(SahneError::NotSupported));
+
+        // Bölüm tablosu dosyanın dışına taşıyor
+        let mut many_sections = HELLO.to_vec();
+        many_sections[12..16].copy_from_slice(&1000u32.to_le_bytes());
+        assert_eq!(load_image(&many_sections, &memory).err(), Some(SahneError::InvalidParameter));
+
+        // Giriş noktası program belleğinin dışında
+        let mut bad_entry = HELLO.to_vec();
+        bad_entry[8..12].copy_from_slice(&0x1000u32.to_le_bytes());
+        assert_eq!(load_image(&bad_entry, &memory).err(), Some(SahneError::InvalidParameter));
+
+        assert_eq!(load_image(&HELLO[..10], &memory).err(), Some(SahneError::InvalidParameter));
+        assert_eq!(memory.live.get(), 0);
+    }
+
+    // Rastgele bayt değişiklikleriyle yükleyicinin panik etmediğini ve
+    // başarısız yüklemelerde bellek sızdırmadığını doğrular.
+    #[test]
+    fn corrupted_images_never_panic() {
+        let memory = HostMemory::new();
+        let mut state: u64 = 0x0123_4567_89AB_CDEF;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..2000 {
+            let mut image = HELLO.to_vec();
+            for _ in 0..1 + next() % 4 {
+                let position = (next() % image.len() as u64) as usize;
+                image[position] = next() as u8;
+            }
+            image.truncate(1 + (next() % (HELLO.len() as u64 + 8)) as usize);
+            if let Ok(program) = load_image(&image, &memory) {
+                program.release(&memory).unwrap();
+            }
+        }
+        assert_eq!(memory.live.get(), 0);
+    }
+
+    #[test]
+    fn prepares_null_terminated_argv() {
+        let memory = HostMemory::new();
+        let (argc, argv, arg_memory) = prepare_program_args(&["sh64", "-c", "ls"], &memory).unwrap();
+        assert_eq!(argc, 3);
+        let args: Vec<&[u8]> = (0..argc)
+            .map(|i| unsafe { CStr::from_ptr(*argv.add(i) as *const _).to_bytes() })
+            .collect();
+        assert_eq!(args, vec![&b"sh64"[..], b"-c", b"ls"]);
+        arg_memory.release(&memory).unwrap();
+
+        let no_args: [&str; 0] = [];
+        let (argc, argv, arg_memory) = prepare_program_args(&no_args, &memory).unwrap();
+        assert_eq!((argc, argv.is_null()), (0, true));
+        arg_memory.release(&memory).unwrap();
+        assert_eq!(memory.live.get(), 0);
+    }
+}
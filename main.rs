@@ -1,4 +1,8 @@
 #![no_std] // Standart kütüphaneye ihtiyaç duymuyoruz
+#![feature(allocator_api)] // Box::try_new / Arc::try_new (fallible tahsis) için
+#![feature(custom_test_frameworks)] // `cargo test` için çekirdek içi test çalıştırıcısı
+#![test_runner(crate::test_runner::run_tests)]
+#![reexport_test_harness_main = "test_main"]
 
 // 'alloc' crate'ini kullanmak için (heap tahsisi)
 #[macro_use]
@@ -17,14 +21,35 @@ use alloc::boxed::Box; // Heap kullanımı örneği
 
 
 // Çekirdek alt sistem modüllerini içeri aktar
-mod uart;       // UART sürücüsü (printk için)
+mod console;    // Çekirdek konsolu (seri port ve/veya ekran)
+mod font;       // Konsolun yerleşik bitmap yazı tipi
+mod fbcon;      // Framebuffer metin konsolu
+mod ring_buffer; // Sürücülerin RX/TX bayt halkaları
+mod tty_io;     // Konsol girdisi için satır disiplini (TTY)
+mod sbi;        // SBI firmware çağrıları (timer vb.)
 mod mm;         // Bellek yönetimi (heap, paging)
+mod free_list;  // Serbest liste tahsis edicisi (mm'nin heap'i)
 mod traps;      // Kesme ve istisna işleme
 mod sys;        // Sistem çağrısı işleme
 mod sched;      // Görev zamanlayıcı
 mod exit;       // Görev sonlandırma
 mod fork;       // Görev oluşturma (eğer fork syscall modeliyse)
+mod limits;     // Görev başına kaynak sınırları
+mod futex;      // Adres üzerinde bekleme (kullanıcı alanı kilitleri için)
+mod calendar;   // Unix zamanı <-> tarih dönüşümleri
+mod kernel_time; // Duvar saati (RTC + monoton zamanlayıcı)
+mod power;      // Kontrollü kapatma / yeniden başlatma
+mod fdt;        // Device Tree (DTB) okuyucu
+mod resource_manager; // Adla erişilen çekirdek kaynakları (resource::acquire)
+mod csprng;     // ChaCha20 entropi havuzu ve üreteç
+mod entropy;    // Çekirdek entropi havuzu ve "random" kaynağı
+mod block;      // Blok cihaz soyutlaması (BlockDevice)
+mod partition;  // MBR/GPT bölüm tablosu okuyucu
+mod block_cache; // Blok önbelleği (LRU, write-back)
+mod media_events; // Çıkarılabilir cihaz takılma/çıkarılma olayları ("media" kaynağı)
 mod drivers;    // Donanım sürücüleri (timer, storage, display vb.)
+#[cfg(test)]
+mod test_runner; // QEMU altında #[test_case] testlerini çalıştırır
 // mod loader; // Kernel-side loader mantığı (eğer ayrı bir modüldeyse)
 
 
@@ -45,12 +70,25 @@ pub extern "C" fn kernel_main(hartid: usize, dtb_address: usize) -> ! {
     // Şu an S-mode'dayız ve kesmeler etkinleştirildi.
 
     // --- 2. Konsol Başlatma ---
-    // printk! kullanabilmek için UART sürücüsü başlatılmalıdır.
-    uart::init();
+    // printk! kullanabilmek için UART sürücüsü başlatılmalıdır (kesmeler açılana kadar yoklamalı çalışır).
+    drivers::uart::init();
     printk!("\n"); // Temiz bir başlangıç
     printk!("SahneBox Kernel Başlıyor (RISC-V 64)\n");
     printk!("Hart ID: {}, DTB Adresi: {:#x}\n", hartid, dtb_address);
 
+    // Device Tree, sürücülerin cihazlarını (virtio vb.) bulması için okunur. Bulunamazsa
+    // sürücüler sabit adreslerle çalışır ve DTB gerektiren cihazlar atlanır.
+    let device_tree = match dtb_address {
+        0 => None,
+        address => match unsafe { fdt::Fdt::from_address(address) } {
+            Ok(fdt) => Some(fdt),
+            Err(err) => {
+                printk!("Uyarı: DTB okunamadı: {:?}\n", err);
+                None
+            }
+        },
+    };
+
 
     // --- 3. Bellek Yönetimi Başlatma ---
     // Kernelin kendi bellek yöneticisini ve heap'i başlat.
@@ -88,14 +126,55 @@ pub extern "C" fn kernel_main(hartid: usize, dtb_address: usize) -> ! {
     // DTB adresi bu sürücülerin başlatılması için kullanılabilir (cihaz adreslerini öğrenmek için).
     drivers::timer::init(); // Scheduler için kritik
     printk!("Timer Sürücüsü Başlatıldı.\n");
+    drivers::plic::init(device_tree.as_ref()); // Cihaz kesmeleri (UART, virtio ...)
+    drivers::uart::enable_interrupts(device_tree.as_ref()); // Seri port bundan sonra kesmelerle çalışır
+    drivers::rtc::init();
+    kernel_time::init(); // Duvar saati RTC'den okunur; timer'dan sonra olmalı
+    resource_manager::init(); // "uart"
+    console::register_tty(); // "console" (satır disiplinli konsol girdisi)
+    if let Err(err) = drivers::storage::init() {
+        printk!("Uyarı: \"media\" kaynağı kaydedilemedi: {:?}\n", err);
+    }
+    entropy::init(device_tree.as_ref()); // Timer titremesi ve virtio-rng ile tohumlanır; "random" kaynağı
+    // QEMU'da virtio-blk diskleri "emmc0"/"sdcard1" olarak kaydedilir (gerçek cihazda disk bulunmaz).
+    let virtio_disks = device_tree.as_ref().map_or(0, drivers::virtio_blk::init);
+
+    // `cargo test` ile derlendiyse: QEMU virt'te bulunmayan cihazların sürücüleri atlanır,
+    // testler çalıştırılır ve QEMU kapatılır (test_main geri dönmez).
+    #[cfg(test)]
+    {
+        sched::init();
+        test_main();
+    }
 
-    // Depolama sürücüleri (eMMC, SD) - İlk programı yüklemek için gerekli
-    drivers::storage::emmc::init();
-    drivers::storage::sd::init();
+    // Ekran erken başlatılır: UART buzdolabında dışarıya açık olmadığından açılış mesajları ve
+    // panikler ekranda da görünmeli. Konsol modu bootargs'taki "console=" ile seçilir.
+    drivers::display::init();
+    let console_mode = device_tree
+        .as_ref()
+        .and_then(|fdt| fdt.find_node("/chosen"))
+        .and_then(|chosen| chosen.property("bootargs"))
+        .and_then(|bytes| core::str::from_utf8(bytes).ok())
+        .and_then(|bootargs| console::ConsoleMode::from_bootargs(bootargs.trim_end_matches('\0')))
+        .unwrap_or(console::ConsoleMode::Both);
+    if let Err(err) = console::select(console_mode) {
+        printk!("Uyarı: Ekran konsolu açılamadı ({:?}), sadece seri port kullanılıyor.\n", err);
+    }
+
+    // Depolama sürücüleri (eMMC, SD) - İlk programı yüklemek için gerekli.
+    // virtio diskleri varsa (QEMU) gerçek denetleyicilere dokunulmaz; adresleri orada eşlenmemiştir.
+    // Cihazlar ve bölümleri "emmc0", "emmc0p1", "sdcard1" ... olarak kaydedilir.
+    if virtio_disks == 0 {
+        if let Err(err) = drivers::storage::emmc::init() {
+            printk!("Uyarı: eMMC başlatılamadı: {:?}\n", err);
+        }
+        if let Err(err) = drivers::storage::sd::init() {
+            printk!("Uyarı: SD kart başlatılamadı: {:?}\n", err);
+        }
+    }
     printk!("Depolama Sürücüleri Başlatıldı.\n");
 
-    // Diğer sürücüler (display, touchscreen, audio) - User-space tarafından kullanılacak, kernel sadece başlatır.
-    drivers::display::init();
+    // Diğer sürücüler (touchscreen, audio) - User-space tarafından kullanılacak, kernel sadece başlatır.
     drivers::touchscreen::init();
     drivers::audio::init();
     printk!("Diğer Sürücüler Başlatıldı.\n");
@@ -105,6 +184,16 @@ pub extern "C" fn kernel_main(hartid: usize, dtb_address: usize) -> ! {
     // Scheduler yapılarını kur (run queues, idle task vb.)
     sched::init();
     printk!("Görev Zamanlayıcı Başlatıldı.\n");
+    // Blok önbelleğindeki yazmalar birkaç saniyede bir diske aktarılır.
+    if let Err(err) = drivers::storage::start_flush_task() {
+        printk!("Uyarı: bflush görevi başlatılamadı: {:?}\n", err);
+    }
+    // SD kart sonradan takılabilir veya çıkarılabilir; kart algılama görevi kaydı günceller.
+    if virtio_disks == 0 {
+        if let Err(err) = drivers::storage::sd::start_card_detect() {
+            printk!("Uyarı: SD kart algılama görevi başlatılamadı: {:?}\n", err);
+        }
+    }
 
 
     // --- 8. İlk Kullanıcı Alanı Görevini (Init Prosesi) Oluşturma ve Yükleme ---
@@ -188,10 +277,21 @@ pub extern "C" fn kernel_main(hartid: usize, dtb_address: usize) -> ! {
 
 // Kernel Panik Handler'ı
 // Bir panik olduğunda burası çağrılır.
+#[cfg(not(test))]
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
+    // Heap dolu olsa bile panik mesajı biçimlendirilebilsin diye acil durum rezervini kalıcı olarak aç.
+    core::mem::forget(mm::emergency_reserve());
+    // Panik konsol kilitliyken olduysa kilit zorla açılır; mesaj yine de ekrana ve seri porta yazılır.
+    unsafe {
+        console::force_unlock();
+        drivers::uart::force_unlock();
+    }
+    // Kesmeler birazdan kapatılacak; mesajlar TX halkasında kalmasın diye seri port yoklamalı yazar.
+    drivers::uart::enter_polled_mode();
+
     // Panik mesajını konsola yazdır
-    printk!("\n*** KERNEL PANIC ***\n");
+    printk!("\n\x1b[1;97;41m*** KERNEL PANIC ***\x1b[0m\n"); // Ekranda kırmızı zemin üzerinde beyaz
     if let Some(location) = info.location() {
         printk!("Konum: {}:{}\n", location.file(), location.line());
     }
@@ -213,4 +313,11 @@ fn panic(info: &PanicInfo) -> ! {
     loop {
         asm::wfi(); // İşlemciyi uykuya al, kesmeleri kapattık, uyanmayacak
     }
-}
\ No newline at end of file
+}
+
+// Test çekirdeğinin panik handler'ı: başarısız testi raporlar ve QEMU'yu hata koduyla kapatır.
+#[cfg(test)]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    test_runner::test_panic_handler(info)
+}
@@ -0,0 +1,101 @@
+// main_kernel/media_events.rs
+// Ortam Olayı Günlüğü
+// Çıkarılabilir cihazların takılma/çıkarılma olaylarını sıra numarasıyla saklar ("media" kaynağı, bkz.
+// storage.rs). Günlük okuyucu başına durum tutmaz: her okuyucu beklediği sıra numarasını kendisi tutar
+// (sahne64::media::MediaWatcher). Son N olay saklanır; daha eskisini isteyen okuyucu en eskiden devam eder.
+// Heap kullanmaz.
+
+use crate::sahne64::media::{MediaEvent, EVENT_SIZE};
+
+pub struct EventLog<const N: usize> {
+    events: [Option<MediaEvent>; N], // Sıra numarası % N yuvasında
+    next: u64,                       // Bir sonraki olayın sıra numarası
+}
+
+impl<const N: usize> EventLog<N> {
+    pub const fn new() -> Self {
+        EventLog { events: [None; N], next: 0 }
+    }
+
+    /// Bir sonraki olayın sıra numarası (abonelik buradan başlar).
+    pub fn next_sequence(&self) -> u64 {
+        self.next
+    }
+
+    /// Olayı ekler (gerekirse en eskisinin üzerine yazar) ve sıra numarasını döndürür.
+    pub fn push(&mut self, kind: u32, device: &str) -> u64 {
+        let sequence = self.next;
+        self.events[(sequence % N as u64) as usize] = Some(MediaEvent::new(sequence, kind, device));
+        self.next += 1;
+        sequence
+    }
+
+    /// `sequence` ve sonraki olayları kayıt olarak `buffer`a yazar; yazılan bayt sayısını döndürür
+    /// (yeni olay yoksa veya tampon bir kayda yetmiyorsa 0).
+    pub fn read(&self, sequence: u64, buffer: &mut [u8]) -> usize {
+        let oldest = self.next.saturating_sub(N as u64);
+        let mut written = 0;
+        for (sequence, record) in (sequence.max(oldest)..self.next).zip(buffer.chunks_exact_mut(EVENT_SIZE)) {
+            if let Some(event) = &self.events[(sequence % N as u64) as usize] {
+                record.copy_from_slice(&event.to_bytes());
+                written += EVENT_SIZE;
+            }
+        }
+        written
+    }
+}
+
+impl<const N: usize> Default for EventLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sahne64::media::{MAX_NAME_LEN, MEDIA_INSERTED, MEDIA_REMOVED};
+
+    fn event(bytes: &[u8]) -> MediaEvent {
+        MediaEvent::from_bytes(bytes.try_into().unwrap())
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn readers_resume_by_sequence_and_skip_overwritten_events() {
+        let mut log = EventLog::<3>::new();
+        let mut buffer = [0u8; 4 * EVENT_SIZE];
+        assert_eq!(log.read(0, &mut buffer), 0);
+        assert_eq!(log.push(MEDIA_INSERTED, "sdcard1"), 0);
+        assert_eq!(log.push(MEDIA_REMOVED, "sdcard1"), 1);
+        assert_eq!(log.next_sequence(), 2);
+
+        assert_eq!(log.read(1, &mut buffer), EVENT_SIZE);
+        let removed = event(&buffer[..EVENT_SIZE]);
+        assert_eq!((removed.sequence, removed.kind, removed.device()), (1, MEDIA_REMOVED, "sdcard1"));
+        assert_eq!(log.read(2, &mut buffer), 0);
+        assert_eq!(log.read(0, &mut buffer[..EVENT_SIZE + 1]), EVENT_SIZE); // Tampon kadar kayıt
+
+        // Üzerine yazılan olaylar atlanır; okuyucu sıra numarasındaki boşluğu görür.
+        for _ in 0..3 {
+            log.push(MEDIA_INSERTED, "sdcard1");
+        }
+        assert_eq!(log.read(0, &mut buffer), 3 * EVENT_SIZE);
+        assert_eq!(event(&buffer[..EVENT_SIZE]).sequence, 2);
+        assert_eq!(event(&buffer[2 * EVENT_SIZE..3 * EVENT_SIZE]).sequence, 4);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn event_records_round_trip_and_truncate_long_names() {
+        let event = MediaEvent::new(7, MEDIA_INSERTED, "sdcard1");
+        assert_eq!(MediaEvent::from_bytes(&event.to_bytes()), event);
+        let long = MediaEvent::new(0, MEDIA_REMOVED, "a-very-long-device-name");
+        assert_eq!(long.device().len(), MAX_NAME_LEN);
+
+        let mut bytes = event.to_bytes();
+        bytes[12] = 0xFF; // Bozuk uzunluk ad alanının dışına taşmaz
+        assert_eq!(MediaEvent::from_bytes(&bytes).device(), "sdcard1\0\0\0\0\0\0\0\0\0");
+    }
+}
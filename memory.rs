@@ -3,12 +3,23 @@
 
 use core::alloc::{GlobalAlloc, Layout};
 use core::ptr;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use spin::Mutex; // spin crate'i
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use crate::sahne64::SahneError;
+use crate::limits::{self, SharedResources};
+use crate::sched;
+use crate::free_list::{FreeListAllocator, FreeListNode};
 
 // TODO: Fiziksel RAM'in başlangıç adresini ve boyutunu belirleyin.
 // Bu bilgiler linker scriptinizden veya donanım belgelerinden gelmelidir.
 const PHYS_RAM_START: usize = 0x8000_0000; // Varsayımsal RAM başlangıç adresi (RISC-V'de yaygın)
+#[cfg(not(test))]
 const PHYS_RAM_SIZE: usize = 2 * 1024 * 1024; // 2 MB
+// Test çekirdeği QEMU virt üzerinde çalışır; OpenSBI RAM'in başını kullandığı için daha geniş RAM verilir (Makefile: QEMU_TEST_MEM).
+#[cfg(test)]
+const PHYS_RAM_SIZE: usize = 16 * 1024 * 1024; // 16 MB
 
 // TODO: Çekirdek kodunuzun, verilerinizin ve yığınınızın linker script tarafından
 // RAM'de nereye yerleştirildiğini belirleyin.
@@ -25,182 +36,355 @@ const HEAP_START: usize = unsafe { &__kernel_end as *const u8 as usize };
 // Heap'in boyutu. Toplam RAM boyutundan çekirdeğin kullandığı alanı çıkarın.
 const HEAP_SIZE: usize = PHYS_RAM_START + PHYS_RAM_SIZE - HEAP_START;
 
-// Serbest bellek bloğunu temsil eden yapı.
-// Her serbest blok, bir sonraki serbest bloğa işaret eder.
-struct FreeListNode {
-    size: usize,
-    next: Option<*mut FreeListNode>,
+// Düşük bellek rezervi.
+// Heap'in sonundan ayrılan bu küçük alan normal tahsislere kapalıdır; sadece
+// bir EmergencyReserve tutulurken (panik, görev sonlandırma) ve yalnızca izni tutan görevin
+// tahsislerinde kullanılabilir. Diğer görevler heap dolduğunda rezervi tüketemez.
+// Böylece heap tamamen dolduğunda bile çekirdek panik mesajını basabilir ve görevi temizleyebilir.
+const EMERGENCY_RESERVE_SIZE: usize = 8 * 1024;
+
+// Çekirdek heap'i: ana serbest liste + acil durum rezervi.
+struct KernelHeap {
+    main: Mutex<FreeListAllocator>,
+    reserve: Mutex<FreeListAllocator>,
+    reserve_start: AtomicUsize,
+    reserve_end: AtomicUsize,
+    reserve_holder: AtomicUsize, // Rezervi kullanabilecek görevin ID'si, RESERVE_CLOSED veya RESERVE_ANY
 }
 
-// Basit Serbest Liste Tahsis Edici Yapısı.
-// Bir Mutex içinde tutulur çünkü GlobalAlloc trait'i Sync gerektirir.
-struct FreeListAllocator {
-    head: Option<*mut FreeListNode>, // Serbest listesinin başı
-}
+const RESERVE_CLOSED: usize = usize::MAX;   // Rezerv kapalı
+const RESERVE_ANY: usize = usize::MAX - 1;  // Çalışan görev yokken (erken açılış) alınan izin
 
-unsafe impl Sync for FreeListAllocator {} // Bu basit tahsis edici Sync'tir (Mutex ile korunur)
+impl KernelHeap {
+    fn owns_reserve(&self, ptr: *mut u8) -> bool {
+        let addr = ptr as usize;
+        addr >= self.reserve_start.load(Ordering::Relaxed) && addr < self.reserve_end.load(Ordering::Relaxed)
+    }
 
-impl FreeListAllocator {
-    // Boş bir tahsis edici oluşturur. init() ile bellek eklenmelidir.
-    const fn new() -> Self {
-        FreeListAllocator { head: None }
+    // Şu anki tahsis rezervden karşılanabilir mi? (izin var ve izni tutan görev çalışıyor)
+    fn reserve_open(&self) -> bool {
+        match self.reserve_holder.load(Ordering::Acquire) {
+            RESERVE_CLOSED => false,
+            RESERVE_ANY => true,
+            holder => sched::running_task_id() == Some(holder),
+        }
     }
+}
 
-    // Tahsis ediciye bellek ekler (heap alanını başlatır).
-    // İlk başta tüm boş alanı tek bir blok olarak listeye ekler.
-    pub unsafe fn init(&mut self, heap_start_addr: usize, heap_size: usize) {
-        if heap_size == 0 {
-            return; // Boş heap
+// Global Tahsis Edici örneği (Mutex ile korunur)
+#[global_allocator]
+static GLOBAL_ALLOCATOR: KernelHeap = KernelHeap {
+    main: Mutex::new(FreeListAllocator::new()),
+    reserve: Mutex::new(FreeListAllocator::new()),
+    reserve_start: AtomicUsize::new(0),
+    reserve_end: AtomicUsize::new(0),
+    reserve_holder: AtomicUsize::new(RESERVE_CLOSED),
+};
+
+// GlobalAlloc trait implementasyonu (GlobalAllocator'ımız için)
+unsafe impl GlobalAlloc for KernelHeap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.main.lock().allocate(layout); // Kilitli allocator üzerinden tahsis et
+        if ptr.is_null() && self.reserve_open() {
+            return self.reserve.lock().allocate(layout); // Heap dolu: acil durum rezervine düş
         }
+        ptr
+    }
 
-        // Bellek alanının layout gereksinimlerini karşıladığından emin olun.
-        // Serbest liste düğümü kadar veya daha büyük ve hizalanmış olmalı.
-        if heap_size < core::mem::size_of::<FreeListNode>() || heap_start_addr % core::mem::align_of::<FreeListNode>() != 0 {
-              printk!("Bellek alanı tahsis edici için uygun değil! Başlangıç: {:#x}, Boyut: {}\n", heap_start_addr, heap_size);
-             // Hata durumunda init yapılamaz.
-             return;
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if self.owns_reserve(ptr) {
+            self.reserve.lock().deallocate(ptr, layout);
+        } else {
+            self.main.lock().deallocate(ptr, layout); // Kilitli allocator üzerinden serbest bırak
         }
+    }
+}
 
+// Acil durum rezervine erişim izni. Değer yaşadığı sürece heap dolduğunda izni alan görevin
+// tahsisleri rezervden karşılanır. Panik işleyicisi izni hiç bırakmaz (mem::forget),
+// sys_exit ise temizlik süresince tutar. İç içe izinlerde bırakılan izin öncekini geri yükler.
+pub struct EmergencyReserve {
+    previous_holder: usize,
+}
 
-        // Tüm alanı tek bir serbest blok olarak ekle
-        let initial_node = heap_start_addr as *mut FreeListNode;
-        ptr::write_volatile(initial_node, FreeListNode { size: heap_size, next: None });
-        self.head = Some(initial_node);
-
-         printk!("Heap başlatıldı: Başlangıç {:#x}, Boyut {}\n", heap_start_addr, heap_size);
-    }
-
-    // Bellek tahsis etme (Layout'a uygun boyutta ve hizalamada)
-    unsafe fn allocate(&mut self, layout: Layout) -> *mut u8 {
-         printk!("Bellek tahsis isteği: Boyut {}, Hizalama {}\n", layout.size(), layout.align());
-
-        let mut current = &mut self.head;
-        while let Some(node_ptr) = *current {
-            let node = &mut *node_ptr;
-            let node_start_addr = node_ptr as usize;
-
-            // Bu blok, istenen Layout'u karşılamak için kullanılabilir mi kontrol et.
-            // Hem boyut (layout.size() kadar yer olmalı) hem de hizalama (blok içinde hizalanmış bir adres bulunmalı) kontrol edilir.
-            let allocation_start_offset_in_node = node_start_addr.align_offset(layout.align());
-            let allocation_start_addr = node_start_addr + allocation_start_offset_in_node;
-            let allocation_end_addr = allocation_start_addr + layout.size();
-
-            if allocation_end_addr <= node_start_addr + node.size {
-                // Bu blok yeterli alana sahip ve içinde hizalanmış bir yer var.
-                // Bloğu kullanacağız.
-
-                // Bloğun başından tahsis edilen yerden önceki kısım (varsa)
-                let prev_free_size = allocation_start_addr - node_start_addr;
-                // Tahsis edilen yerden sonraki kısım (varsa)
-                let next_free_size = (node_start_addr + node.size) - allocation_end_addr;
-
-                if prev_free_size > 0 && next_free_size > 0 {
-                    // Blok üç parçaya ayrılıyor: önceki serbest, tahsis edilen, sonraki serbest
-                    // Önceki serbest bloğu güncelle
-                    node.size = prev_free_size;
-                    // Sonraki serbest bloğu listeye ekle
-                    let next_free_node_ptr = allocation_end_addr as *mut FreeListNode;
-                     if next_free_size >= core::mem::size_of::<FreeListNode>() && allocation_end_addr % core::mem::align_of::<FreeListNode>() == 0 {
-                         ptr::write_volatile(next_free_node_ptr, FreeListNode { size: next_free_size, next: node.next });
-                         node.next = Some(next_free_node_ptr); // Önceki bloğun next'i yeni bloğa işaret etsin
-                     } else {
-                           printk!("Hata: Kalan küçük blok FreeListNode için yeterli değil/hizalanmış değil. Kayıp bellek.\n");
-                          // Bu küçük kalan alan kaybolur (fragmentasyon).
-                          // node.next değişmez.
-                     }
-
-
-                } else if prev_free_size > 0 {
-                    // Blok ikiye ayrılıyor: önceki serbest, tahsis edilen
-                    // Önceki serbest bloğun boyutunu güncelle. next'i aynı kalır.
-                    node.size = prev_free_size;
-                } else if next_free_size > 0 {
-                    // Blok ikiye ayrılıyor: tahsis edilen, sonraki serbest
-                    // Bu durumda mevcut düğüm tahsis edildiği için listeden çıkarılmalı.
-                    // Sonraki serbest blok düğümü, tahsis edilen alanın hemen arkasından başlar.
-                    let next_free_node_ptr = allocation_end_addr as *mut FreeListNode;
-                     if next_free_size >= core::mem::size_of::<FreeListNode>() && allocation_end_addr % core::mem::align_of::<FreeListNode>() == 0 {
-                        ptr::write_volatile(next_free_node_ptr, FreeListNode { size: next_free_size, next: node.next });
-                        // Mevcut düğümü listeden çıkar
-                        *current = Some(next_free_node_ptr); // current.head veya current.next bir sonraki bloğa işaret etsin
-                     } else {
-                         // printk!("Hata: Kalan küçük blok FreeListNode için yeterli değil/hizalanmış değil. Kayıp bellek.\n");
-                         // Mevcut düğümü listeden çıkar. next'i aynı kalır.
-                         *current = node.next;
-                     }
-
-                } else {
-                    // Tüm blok tahsis ediliyor. Bu düğüm listeden çıkarılmalı.
-                    *current = node.next;
-                }
-
-                // Tahsis edilen alanın başlangıç adresini döndür.
-                 printk!("Tahsis edildi: {:#x}, Boyut: {}\n", allocation_start_addr, layout.size());
-                return allocation_start_addr as *mut u8;
-            } else {
-                // Bu blok uygun değil, bir sonraki bloğa geç
-                current = &mut node.next;
-            }
-        }
+pub fn emergency_reserve() -> EmergencyReserve {
+    let holder = sched::running_task_id().unwrap_or(RESERVE_ANY);
+    let previous_holder = GLOBAL_ALLOCATOR.reserve_holder.swap(holder, Ordering::AcqRel);
+    EmergencyReserve { previous_holder }
+}
 
-        // Uygun serbest blok bulunamadı
-         printk!("Tahsis hatası: Yeterli serbest bellek yok.\n");
-        ptr::null_mut() // Başarısızlık durumunda null pointer dön
+impl Drop for EmergencyReserve {
+    fn drop(&mut self) {
+        GLOBAL_ALLOCATOR.reserve_holder.store(self.previous_holder, Ordering::Release);
     }
+}
 
-    // Belleği serbest bırakma
-    // TODO: Birleştirme (coalescing) implementasyonu eklenmeli.
-    // Serbest bırakılan bloğu, serbest listedeki bitişik bloklarla birleştirmek fragmentasyonu azaltır.
-    unsafe fn deallocate(&mut self, ptr: *mut u8, layout: Layout) {
-         printk!("Bellek serbest bırakma: {:#p}, Boyut: {}\n", ptr, layout.size());
+// Bellek yönetimini başlatır. Heap alanını tahsis ediciye ekler.
+pub fn init() {
+    // Rezerv heap'in sonundan, FreeListNode hizasında ayrılır.
+    let reserve_start = (HEAP_START + HEAP_SIZE - EMERGENCY_RESERVE_SIZE) & !(core::mem::align_of::<FreeListNode>() - 1);
+    let reserve_size = HEAP_START + HEAP_SIZE - reserve_start;
+    unsafe {
+        // Global tahsis ediciyi başlat.
+        // Heap'in başlangıç adresini ve boyutunu geçir.
+        GLOBAL_ALLOCATOR.main.lock().init(HEAP_START, reserve_start - HEAP_START);
+        GLOBAL_ALLOCATOR.reserve.lock().init(reserve_start, reserve_size);
+    }
+    GLOBAL_ALLOCATOR.reserve_start.store(reserve_start, Ordering::Relaxed);
+    GLOBAL_ALLOCATOR.reserve_end.store(reserve_start + reserve_size, Ordering::Relaxed);
+    // printk!("Bellek yönetimi başlatıldı. Heap boyutu: {}\n", HEAP_SIZE);
+}
 
-        if ptr.is_null() { return; } // Null pointer serbest bırakılamaz
+// Kullanıcı alanı tahsisleri için hizalama ve en küçük blok boyutu.
+// Serbest bırakılan blok bir FreeListNode'a dönüştüğü için ondan küçük olamaz.
+const USER_ALLOC_ALIGN: usize = 16;
 
-        // Serbest bırakılan bloğun bilgisi (geçici olarak FreeListNode gibi davranır)
-        // Layout'tan gelen boyut, tahsis edilen gerçek boyutu temsil etmeyebilir
-        // Tahsis edicide kaydedilen gerçek boyutu bilmek gerekir (karmaşık)
-        // Basitlik için, Layout.size()'ı kullanıyoruz, ama bu doğru birleştirme için yeterli değil.
-        // Doğru birleştirme için tahsis edilen bloğun boyutunu bir şekilde takip etmek gerekir.
+fn user_layout(size: usize) -> Result<Layout, SahneError> {
+    if size == 0 {
+        return Err(SahneError::InvalidParameter);
+    }
+    let size = size.max(core::mem::size_of::<FreeListNode>());
+    Layout::from_size_align(size, USER_ALLOC_ALIGN).map_err(|_| SahneError::InvalidParameter)
+}
 
-        // Serbest bırakılan bloğu FreeListNode olarak yaz (geçici olarak)
-        let freed_node_ptr = ptr as *mut FreeListNode;
-        // Boyut bilgisi burada doğru olmayabilir!
-        ptr::write_volatile(freed_node_ptr, FreeListNode { size: layout.size(), next: self.head }); // Listenin başına ekle (basit)
+// sys_allocate ile verilmiş bir blok. Boyut ve sahip hesap tahsis anında kaydedilir;
+// iade ve işaretçi doğrulaması çağıranın bildirdiği boyuta değil bu kayda dayanır.
+struct UserAllocation {
+    base: *mut u8,
+    size: usize,
+    owner: SharedResources, // Aynı görevin iş parçacıkları aynı hesabı paylaşır
+}
+
+unsafe impl Send for UserAllocation {}
 
-        self.head = Some(freed_node_ptr);
+static USER_ALLOCATIONS: Mutex<Vec<UserAllocation>> = Mutex::new(Vec::new());
 
-        // TODO: Burada bitişik serbest blokları birleştirme (coalescing) mantığı eklenmeli.
-        // Serbest bırakılan bloğun hem önceki hem de sonraki bellek adreslerindeki serbest bloklarla
-        // adreslerine göre sıralı listede tutularak birleştirilmesi gerekir.
+// Geçerli görevin kaynak hesabı (tahsislerin sahibi).
+fn current_account() -> Result<SharedResources, SahneError> {
+    let task = sched::current_task().ok_or(SahneError::InvalidOperation)?;
+    let resources = task.lock().resources();
+    Ok(resources)
+}
+
+// SYSCALL_MEMORY_ALLOCATE: Geçerli görev adına bellek tahsis eder.
+// Önce görevin bellek sınırından düşülür; sınır aşılırsa heap'e hiç dokunulmaz.
+pub fn sys_allocate(size: usize) -> Result<*mut u8, SahneError> {
+    let layout = user_layout(size)?;
+    let owner = current_account()?;
+    limits::charge_memory(size)?;
+
+    let ptr = unsafe { GLOBAL_ALLOCATOR.alloc(layout) };
+    if ptr.is_null() {
+        limits::release_memory(size);
+        return Err(SahneError::OutOfMemory);
+    }
+    let mut allocations = USER_ALLOCATIONS.lock();
+    if allocations.try_reserve(1).is_err() {
+        drop(allocations);
+        unsafe { GLOBAL_ALLOCATOR.dealloc(ptr, layout); }
+        limits::release_memory(size);
+        return Err(SahneError::OutOfMemory);
     }
+    allocations.push(UserAllocation { base: ptr, size, owner });
+    Ok(ptr)
 }
 
-// Global Tahsis Edici örneği (Mutex ile korunur)
-#[global_allocator]
-static GLOBAL_ALLOCATOR: Mutex<FreeListAllocator> = Mutex::new(FreeListAllocator::new());
+// SYSCALL_MEMORY_RELEASE: sys_allocate ile alınmış belleği iade eder.
+// Sadece çağıran görevin hesabına yazılmış tahsisler iade edilebilir. Heap'e ve kotaya iade edilen
+// boyut tahsis kaydından alınır; çağıranın bildirdiği boyut (ABI'de hâlâ geçilir) kullanılmaz.
+pub fn sys_deallocate(ptr: *mut u8, _size: usize) -> Result<(), SahneError> {
+    if ptr.is_null() {
+        return Err(SahneError::InvalidAddress);
+    }
+    let owner = current_account()?;
+    let allocation = {
+        let mut allocations = USER_ALLOCATIONS.lock();
+        let index = allocations
+            .iter()
+            .position(|allocation| allocation.base == ptr && Arc::ptr_eq(&allocation.owner, &owner))
+            .ok_or(SahneError::InvalidAddress)?;
+        allocations.swap_remove(index)
+    };
+    let layout = user_layout(allocation.size)?;
+    unsafe { GLOBAL_ALLOCATOR.dealloc(ptr, layout); }
+    limits::release_memory(allocation.size);
+    Ok(())
+}
 
-// GlobalAlloc trait implementasyonu (GlobalAllocator'ımız için)
-unsafe impl GlobalAlloc for Mutex<FreeListAllocator> {
-    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        self.lock().allocate(layout) // Kilitli allocator üzerinden tahsis et
+// Görevin son iş parçacığı çıkarken hesabına kalan tahsisleri heap'e iade eder (sys_exit).
+pub fn release_task_allocations(owner: &SharedResources) {
+    USER_ALLOCATIONS.lock().retain(|allocation| {
+        if !Arc::ptr_eq(&allocation.owner, owner) {
+            return true;
+        }
+        if let Ok(layout) = user_layout(allocation.size) {
+            unsafe { GLOBAL_ALLOCATOR.dealloc(allocation.base, layout); }
+        }
+        false
+    });
+}
+
+// Kullanıcı işaretçisini doğrular: [addr, addr + len) tamamen çağıran görevin belleğinde olmalı.
+// MMU olmadığı için görevin adres alanı kendi yığını ve TLS bloğu, hesabına yazılmış sys_allocate
+// tahsisleri ve paylaşımlı bölgelerden oluşur; çekirdek belleğini veya başka bir görevin belleğini
+// gösteren işaretçiler InvalidAddress ile reddedilir.
+// Çalışan görev yoksa (erken açılış, çekirdek içi çağrı) işaretçi çekirdeğindir ve kabul edilir.
+pub fn check_user_range(addr: usize, len: usize) -> Result<(), SahneError> {
+    let end = addr.checked_add(len).ok_or(SahneError::InvalidAddress)?;
+    if addr == 0 {
+        return Err(SahneError::InvalidAddress);
+    }
+    let task = match sched::current_task() {
+        Some(task) => task,
+        None => return Ok(()),
+    };
+    let owner = {
+        let task = task.lock();
+        if task.owns_range(addr, end) {
+            return Ok(());
+        }
+        task.resources()
+    };
+    let contains = |base: usize, size: usize| addr >= base && end <= base + size;
+    let allocated = USER_ALLOCATIONS
+        .lock()
+        .iter()
+        .any(|allocation| Arc::ptr_eq(&allocation.owner, &owner) && contains(allocation.base as usize, allocation.size));
+    if allocated || SHARED_REGIONS.lock().iter().flatten().any(|region| contains(region.base as usize, region.size)) {
+        Ok(())
+    } else {
+        Err(SahneError::InvalidAddress)
     }
+}
 
-    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
-         self.lock().deallocate(ptr, layout); // Kilitli allocator üzerinden serbest bırak
+// Paylaşımlı bellek bölgesi.
+// MMU kullanılmadığı için bölgeler tüm görevlere aynı adreste görünür;
+// "eşleme" bölgenin adresini döndürmekten ibarettir.
+struct SharedRegion {
+    base: *mut u8,
+    size: usize,
+}
+
+unsafe impl Send for SharedRegion {}
+
+// Paylaşımlı bellek Handle'ları diğer kaynak Handle'larıyla çakışmasın diye bu değerden başlar.
+pub const SHARED_MEM_HANDLE_BASE: u64 = 0x1000;
+// Handle'ın tablo yuvasını gösteren alt bitleri; kaynak Handle'larının üst bitleri yuvanın kuşağını taşır.
+pub const HANDLE_SLOT_MASK: u64 = 0xFFFF_FFFF;
+
+static SHARED_REGIONS: Mutex<Vec<Option<SharedRegion>>> = Mutex::new(Vec::new());
+
+pub fn is_shared_handle(handle: u64) -> bool {
+    (handle & HANDLE_SLOT_MASK) >= SHARED_MEM_HANDLE_BASE
+}
+
+// SYSCALL_SHARED_MEM_CREATE: Sıfırlanmış bir paylaşımlı bölge oluşturur ve Handle'ını döndürür.
+// Hem paylaşımlı bellek hem de Handle sınırı uygulanır.
+pub fn sys_create_shared(size: usize) -> Result<u64, SahneError> {
+    let layout = user_layout(size)?;
+    limits::charge_shared_mem(size)?;
+    if let Err(err) = limits::charge_handle() {
+        limits::release_shared_mem(size);
+        return Err(err);
+    }
+
+    let base = unsafe { GLOBAL_ALLOCATOR.alloc(layout) };
+    if base.is_null() {
+        limits::release_handle();
+        limits::release_shared_mem(size);
+        return Err(SahneError::OutOfMemory);
     }
+    unsafe { ptr::write_bytes(base, 0, layout.size()); }
+
+    let mut regions = SHARED_REGIONS.lock();
+    let region = SharedRegion { base, size };
+    let index = match regions.iter().position(|slot| slot.is_none()) {
+        Some(index) => {
+            regions[index] = Some(region);
+            index
+        }
+        None => {
+            if regions.try_reserve(1).is_err() {
+                drop(regions);
+                unsafe { GLOBAL_ALLOCATOR.dealloc(base, layout); }
+                limits::release_handle();
+                limits::release_shared_mem(size);
+                return Err(SahneError::OutOfMemory);
+            }
+            regions.push(Some(region));
+            regions.len() - 1
+        }
+    };
+    Ok(SHARED_MEM_HANDLE_BASE + index as u64)
 }
 
-// Bellek yönetimini başlatır. Heap alanını tahsis ediciye ekler.
-pub fn init() {
-    unsafe {
-        // Global tahsis ediciyi başlat.
-        // Heap'in başlangıç adresini ve boyutunu geçir.
-        GLOBAL_ALLOCATOR.lock().init(HEAP_START, HEAP_SIZE);
+// SYSCALL_SHARED_MEM_MAP: Bölgenin [offset, offset + size) aralığının adresini döndürür.
+pub fn sys_map_shared(handle: u64, offset: usize, size: usize) -> Result<*mut u8, SahneError> {
+    if !is_shared_handle(handle) {
+        return Err(SahneError::InvalidHandle);
+    }
+    let regions = SHARED_REGIONS.lock();
+    let region = regions
+        .get((handle - SHARED_MEM_HANDLE_BASE) as usize)
+        .and_then(|slot| slot.as_ref())
+        .ok_or(SahneError::InvalidHandle)?;
+    match offset.checked_add(size) {
+        Some(end) if end <= region.size => Ok(unsafe { region.base.add(offset) }),
+        _ => Err(SahneError::InvalidParameter),
     }
-    // printk!("Bellek yönetimi başlatıldı. Heap boyutu: {}\n", HEAP_SIZE);
+}
+
+// SYSCALL_SHARED_MEM_UNMAP: MMU olmadığı için sadece adresin bir bölgeye ait olduğu doğrulanır.
+pub fn sys_unmap_shared(addr: *mut u8, size: usize) -> Result<(), SahneError> {
+    let addr = addr as usize;
+    let regions = SHARED_REGIONS.lock();
+    let mapped = regions.iter().flatten().any(|region| {
+        let base = region.base as usize;
+        addr >= base && addr.saturating_add(size) <= base + region.size
+    });
+    if mapped { Ok(()) } else { Err(SahneError::InvalidAddress) }
+}
+
+// Paylaşımlı bellek Handle'ı serbest bırakıldığında bölgeyi iade eder (SYSCALL_RESOURCE_RELEASE).
+pub fn sys_release_shared(handle: u64) -> Result<(), SahneError> {
+    if !is_shared_handle(handle) {
+        return Err(SahneError::InvalidHandle);
+    }
+    let region = SHARED_REGIONS
+        .lock()
+        .get_mut((handle - SHARED_MEM_HANDLE_BASE) as usize)
+        .and_then(|slot| slot.take())
+        .ok_or(SahneError::InvalidHandle)?;
+
+    let layout = user_layout(region.size)?;
+    unsafe { GLOBAL_ALLOCATOR.dealloc(region.base, layout); }
+    limits::release_shared_mem(region.size);
+    limits::release_handle();
+    Ok(())
 }
 
 // TODO: Sayfalama (paging) ile ilgili fonksiyonlar buraya eklenebilir
 // eğer basit kimlik eşlemesi veya MMU kontrolü yapılacaksa.
  fn enable_paging(...)
- fn create_page_table(...)
\ No newline at end of file
+ fn create_page_table(...)
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_case]
+    fn emergency_reserve_is_closed_by_default() {
+        assert_eq!(GLOBAL_ALLOCATOR.reserve_holder.load(Ordering::SeqCst), RESERVE_CLOSED);
+        assert!(!GLOBAL_ALLOCATOR.reserve_open());
+        let guard = emergency_reserve();
+        assert!(GLOBAL_ALLOCATOR.reserve_open());
+        drop(guard);
+        assert_eq!(GLOBAL_ALLOCATOR.reserve_holder.load(Ordering::SeqCst), RESERVE_CLOSED);
+    }
+
+    #[test_case]
+    fn user_range_rejects_null_and_wrapping_pointers() {
+        assert_eq!(check_user_range(0, 8), Err(SahneError::InvalidAddress));
+        assert_eq!(check_user_range(usize::MAX - 3, 8), Err(SahneError::InvalidAddress));
+    }
+}
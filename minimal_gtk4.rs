@@ -24,6 +24,10 @@ use crate::sahne64::{self, memory, task, SahneError};
 // Minimal Pencereleme Sistemi Kütüphanesi
 use crate::windows_system::libsaheneui_minimal::{self, DisplayConnection, Window as SahneWindow, InputEvent, TouchEventKind};
 
+// Yerleşim hesapları (layout.rs); host üzerinde de test edilebilen saf kod
+pub mod layout;
+use layout::Rect;
+
 
 // TODO: Temel Çizim Yardımcıları (Framebuffer üzerine çizim için)
 // Doğrudan paylaşımlı pencere tamponuna çizim yapacaklar.
@@ -159,6 +163,15 @@ impl Button {
     pub fn connect_clicked<F>(&mut self, callback: F) where F: Fn() + 'static {
         self.on_click = Some(Box::new(callback));
     }
+
+    // Butonun kapladığı alan
+    fn bounds(&self) -> Rect {
+        Rect::new(self.x, self.y, self.width, self.height)
+    }
+
+    fn contains_point(&self, x: i32, y: i32) -> bool {
+        self.bounds().contains(x, y)
+    }
 }
 
 impl Widget for Button {
@@ -171,9 +184,8 @@ impl Widget for Button {
 
         // Etiketi çiz (merkezlemeye dikkat et)
         let (label_w, label_h) = self.label.get_preferred_size();
-        let label_draw_x = draw_x + (self.width as i32 - label_w as i32) / 2;
-        let label_draw_y = draw_y + (self.height as i32 - label_h as i32) / 2;
-        self.label.set_position(label_draw_x - draw_x, label_draw_y - draw_y); // Label pozisyonu parent'a göre
+        let label_rect = layout::center_in(Rect::new(draw_x, draw_y, self.width, self.height), label_w, label_h);
+        self.label.set_position(label_rect.x - draw_x, label_rect.y - draw_y); // Label pozisyonu parent'a göre
         self.label.draw(painter, offset_x, offset_y); // Label'ı çiz
          printk!("DEBUG: Button çizildi: @ ({},{}) size ({},{})\n", draw_x, draw_y, self.width, self.height);
     }
@@ -181,9 +193,7 @@ impl Widget for Button {
     fn handle_event(&mut self, event: &InputEvent, event_x: i32, event_y: i32) -> bool {
         let handled = false;
         // Olay koordinatları widget'ın içinde mi?
-        if event_x >= self.x && event_x < (self.x + self.width as i32) &&
-           event_y >= self.y && event_y < (self.y + self.height as i32)
-        {
+        if self.contains_point(event_x, event_y) {
             match event {
                 InputEvent::Touch { kind, x, y } => {
                     match kind {
@@ -265,24 +275,18 @@ impl VBox {
 
     // Çocukları dikey olarak düzenler ve kendi boyutunu hesaplar.
     fn perform_layout(&mut self) {
-        let mut current_y = self.y;
-        let mut max_width = 0;
-        let mut total_height = 0;
-
-        for child in &mut self.children {
-            let (pref_w, pref_h) = child.get_preferred_size();
-            max_width = core::cmp::max(max_width, pref_w);
-            // Çocuğun pozisyonunu ayarla
-            child.set_position(self.x, current_y); // VBox'ın kendi pozisyonuna göre
-            current_y += pref_h as i32 + self.padding as i32;
-            total_height += pref_h + self.padding;
-        }
+        let child_sizes: Vec<(u32, u32)> = self.children.iter().map(|child| child.get_preferred_size()).collect();
+        let rects = layout::vbox_layout(self.x, self.y, child_sizes.iter().copied(), self.padding);
 
-        if total_height > 0 { total_height -= self.padding; } // Son elemandan sonra padding olmamalı
+        for (child, rect) in self.children.iter_mut().zip(rects.iter()) {
+            // Çocuğun pozisyonunu ayarla (VBox'ın kendi pozisyonuna göre)
+            child.set_position(rect.x, rect.y);
+        }
 
-        // VBox'ın boyutunu ayarla
-        self.width = max_width; // En geniş çocuğun genişliği
-        self.height = total_height;
+        // VBox'ın boyutunu ayarla: en geniş çocuğun genişliği, son elemandan sonra padding yok
+        let (width, height) = layout::vbox_preferred_size(child_sizes, self.padding);
+        self.width = width;
+        self.height = height;
     }
 }
 
@@ -309,9 +313,7 @@ impl Widget for VBox {
             let (child_x, child_y) = child.get_position();
             // Olay bu çocuğun bounds'u içinde mi?
             let (child_w, child_h) = child.get_preferred_size(); // Veya layout tarafından belirlenen boyut
-             if child_event_x >= child_x && child_event_x < (child_x + child_w as i32) &&
-                child_event_y >= child_y && child_event_y < (child_y + child_h as i32)
-             {
+             if Rect::new(child_x, child_y, child_w, child_h).contains(child_event_x, child_event_y) {
                 if child.handle_event(event, child_event_x, child_event_y) {
                     handled = true; // Olay işlendi
                     // Eğer çocuk işlediyse, olayı başka çocuklara göndermeyi durdurabiliriz (genellikle böyledir)
@@ -323,20 +325,8 @@ impl Widget for VBox {
     }
 
     fn get_preferred_size(&self) -> (u32, u32) {
-        // Çocukları düzenlemeden boyut hesaplamak zor.
-        // Basitlik için burada da layout yapabilir veya cached boyutu dönebilir.
-        // Performans_layout'u çağırmak mutable borrow sorununa yol açabilir.
-        // Şimdilik 0 dönelim, VBox boyutu ebeveyni tarafından ayarlanır.
-        // Daha iyi bir yöntem: calculate_preferred_size() gibi ayrı bir fonksiyon.
-         // Geçici: Layout yapıp boyutu dönelim
-        let mut temp_vbox = VBox::new(self.padding);
-        for child in self.children.iter() {
-             // Çocukları clone veya Box<dyn Widget> tekrar Box<dyn Widget> yapmak zor.
-             // Widget trait'ine clone eklemek her widget'ın clone edilebilir olmasını gerektirir.
-             // Daha iyi bir yöntem: Layout hesaplamasını ayrı bir fonksiyonda yapmak.
-        }
-        // Şimdilik tahmini/sabit boyut dönelim
-        (200, 300) // Varsayımsal boyut
+        // Layout yapmadan, çocukların tercih ettiği boyutlardan hesaplanır (perform_layout ile aynı sonuç).
+        layout::vbox_preferred_size(self.children.iter().map(|child| child.get_preferred_size()), self.padding)
     }
 
      fn set_position(&mut self, x: i32, y: i32) {
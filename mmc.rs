@@ -0,0 +1,503 @@
+// drivers/mmc.rs
+// SD ve eMMC Kart Protokolü
+// Kart tanıma (CMD0 -> ACMD41/CMD1 -> CMD2 -> CMD3 -> CMD9 -> CMD7), CID/CSD çözümleme ve blok
+// okuma/yazma. SD Physical Layer Simplified Specification (v6.00) ve JEDEC eMMC (JESD84) esas alınmıştır.
+//
+// Adresleme:
+// - SDSC (v1 ve v2 standart kapasite) ve 2 GB'a kadar eMMC kartlarda veri komutlarının argümanı bayt
+//   adresidir; blok uzunluğu CMD16 ile 512'ye sabitlenir.
+// - SDHC/SDXC ve sektör adresli eMMC kartlarda argüman blok numarasıdır (blok her zaman 512 bayttır).
+//
+// Kapasite CSD'den (SD CSD v1/v2, MMC CSD) veya yüksek kapasiteli eMMC'lerde EXT_CSD SEC_COUNT'tan okunur.
+// Denetleyiciye sdhci.rs üzerinden erişilir; testler oradaki denetleyici/kart modelini kullanır.
+
+use core::fmt;
+
+use crate::sahne64::SahneError;
+use super::sdhci::{HostError, ResponseType, Sdhci, SdhciBus};
+
+/// Veri komutlarının blok boyutu.
+pub const BLOCK_SIZE: usize = 512;
+
+// Kart tanıma sırasında saat 400 kHz'i geçmemeli; sonrasında SD "default speed", eMMC "backward compatible".
+const IDENTIFICATION_CLOCK_HZ: u32 = 400_000;
+const SD_CLOCK_HZ: u32 = 25_000_000;
+const MMC_CLOCK_HZ: u32 = 26_000_000;
+
+// Kartın güç açılışını (OCR busy biti) en fazla bu kadar bekleriz.
+const POWER_UP_TIMEOUT_MS: u64 = 1000;
+
+// Komutlar (ACMD'ler CMD55'ten sonra gönderilir)
+const CMD_GO_IDLE_STATE: u8 = 0;
+const CMD_SEND_OP_COND: u8 = 1; // Sadece eMMC
+const CMD_ALL_SEND_CID: u8 = 2;
+const CMD_SET_RELATIVE_ADDR: u8 = 3;
+const CMD_SELECT_CARD: u8 = 7;
+const CMD_SEND_IF_COND: u8 = 8; // SD
+const CMD_SEND_EXT_CSD: u8 = 8; // eMMC
+const CMD_SEND_CSD: u8 = 9;
+const CMD_SET_BLOCKLEN: u8 = 16;
+const CMD_READ_SINGLE_BLOCK: u8 = 17;
+const CMD_READ_MULTIPLE_BLOCK: u8 = 18;
+const CMD_WRITE_BLOCK: u8 = 24;
+const CMD_WRITE_MULTIPLE_BLOCK: u8 = 25;
+const CMD_APP_CMD: u8 = 55;
+const ACMD_SET_BUS_WIDTH: u8 = 6;
+const ACMD_SD_SEND_OP_COND: u8 = 41;
+
+// CMD8 argümanı: 2.7-3.6V + kontrol deseni (kart aynısını geri gönderir)
+const IF_COND_ARGUMENT: u32 = 0x1AA;
+
+// OCR
+const OCR_VOLTAGE_WINDOW: u32 = 0x00FF_8000; // 2.7-3.6V
+const OCR_LOW_VOLTAGE: u32 = 1 << 7;         // eMMC 1.70-1.95V
+const OCR_HCS: u32 = 1 << 30;                // SD: yüksek kapasite (host destekliyor / kart SDHC-SDXC)
+const OCR_ACCESS_MODE_MASK: u32 = 3 << 29;   // eMMC erişim modu
+const OCR_ACCESS_MODE_SECTOR: u32 = 2 << 29;
+const OCR_POWER_UP_DONE: u32 = 1 << 31;
+
+// R1 kart durumu
+const R1_OUT_OF_RANGE: u32 = 1 << 31;
+const R1_ADDRESS_ERROR: u32 = 1 << 30;
+const R1_ERRORS: u32 = 0xFDF8_0000; // Hata bitleri (kilitli kart ve durum alanı hariç)
+const R1_APP_CMD: u32 = 1 << 5;
+
+// eMMC'de CMD3 ile karta verilen adres (host seçer).
+const MMC_RCA: u16 = 1;
+
+// EXT_CSD: sektör sayısı (küçük uçlu 32 bit)
+const EXT_CSD_SEC_COUNT: usize = 212;
+
+/// Kart tanıma veya aktarım hatası.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardError {
+    Host(HostError),    // Denetleyici hatası (komuta yanıt yok, CRC, zaman aşımı ...)
+    NoCard,             // Yuvada kart yok
+    UnsupportedVoltage, // Kart 3.3V'u desteklemiyor
+    NotReady,           // Güç açılışı zamanında bitmedi
+    BadResponse,        // Beklenmeyen yanıt veya kart durumunda hata biti
+    UnsupportedCsd,     // Bilinmeyen CSD yapısı
+    AddressOutOfRange,  // Blok kartın kapasitesi dışında
+}
+
+impl From<HostError> for CardError {
+    fn from(err: HostError) -> Self {
+        CardError::Host(err)
+    }
+}
+
+impl From<CardError> for SahneError {
+    fn from(err: CardError) -> Self {
+        match err {
+            CardError::Host(err) => err.into(),
+            CardError::NoCard => SahneError::ResourceNotFound,
+            CardError::UnsupportedVoltage | CardError::UnsupportedCsd => SahneError::NotSupported,
+            CardError::NotReady => SahneError::TimedOut,
+            CardError::BadResponse => SahneError::InvalidOperation,
+            CardError::AddressOutOfRange => SahneError::InvalidParameter,
+        }
+    }
+}
+
+/// Kart türü ve adresleme biçimi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardType {
+    SdStandard,      // SDSC (v1 veya v2), bayt adresli
+    SdHighCapacity,  // SDHC/SDXC, blok adresli
+    Mmc,             // eMMC <= 2 GB, bayt adresli
+    MmcHighCapacity, // eMMC > 2 GB, sektör adresli
+}
+
+/// Kart kimliği (CID registerı).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cid {
+    pub manufacturer: u8,
+    pub oem: u16,
+    pub name: [u8; 6], // SD'de 5, eMMC'de 6 karakter
+    pub revision: u8,  // BCD: üst 4 bit ana, alt 4 bit alt sürüm
+    pub serial: u32,
+    pub year: u16,
+    pub month: u8,
+}
+
+impl Cid {
+    // SD CID: MID [127:120], OID [119:104], PNM [103:64], PRV [63:56], PSN [55:24], MDT [19:8]
+    fn from_sd(cid: u128) -> Self {
+        let mut name = [0; 6];
+        for (i, byte) in name.iter_mut().take(5).enumerate() {
+            *byte = field(cid, 103 - 8 * i as u32, 96 - 8 * i as u32) as u8;
+        }
+        Cid {
+            manufacturer: field(cid, 127, 120) as u8,
+            oem: field(cid, 119, 104) as u16,
+            name,
+            revision: field(cid, 63, 56) as u8,
+            serial: field(cid, 55, 24) as u32,
+            year: 2000 + field(cid, 19, 12) as u16,
+            month: field(cid, 11, 8) as u8,
+        }
+    }
+
+    // eMMC CID: MID [127:120], OID [111:104], PNM [103:56], PRV [55:48], PSN [47:16], MDT [15:8].
+    // Yıl 1997'den sayılır (EXT_CSD_REV > 4 kartlar 2013'ten sayar; EXT_CSD her kartta okunmaz).
+    fn from_mmc(cid: u128) -> Self {
+        let mut name = [0; 6];
+        for (i, byte) in name.iter_mut().enumerate() {
+            *byte = field(cid, 103 - 8 * i as u32, 96 - 8 * i as u32) as u8;
+        }
+        Cid {
+            manufacturer: field(cid, 127, 120) as u8,
+            oem: field(cid, 111, 104) as u16,
+            name,
+            revision: field(cid, 55, 48) as u8,
+            serial: field(cid, 47, 16) as u32,
+            year: 1997 + field(cid, 11, 8) as u16,
+            month: field(cid, 15, 12) as u8,
+        }
+    }
+
+    /// Ürün adı (sondaki boşluklar ve sıfırlar atılır; geçersiz UTF-8 ise boş).
+    pub fn name(&self) -> &str {
+        let len = self.name.iter().position(|&byte| byte == 0).unwrap_or(self.name.len());
+        core::str::from_utf8(&self.name[..len]).unwrap_or("").trim_end()
+    }
+}
+
+/// Tanınmış ve seçilmiş (aktarım durumundaki) bir kart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    pub card_type: CardType,
+    pub rca: u16,
+    pub cid: Cid,
+    pub blocks: u64, // BLOCK_SIZE baytlık blok sayısı
+}
+
+impl Card {
+    /// Veri komutlarının argümanı blok numarası mı (değilse bayt adresi)?
+    pub fn block_addressed(&self) -> bool {
+        matches!(self.card_type, CardType::SdHighCapacity | CardType::MmcHighCapacity)
+    }
+
+    // `block`un komut argümanı; kapasiteyi aşan veya 32 bite sığmayan adresler reddedilir.
+    fn address(&self, block: u64, blocks: usize) -> Result<u32, CardError> {
+        if block.checked_add(blocks as u64).is_none_or(|end| end > self.blocks) {
+            return Err(CardError::AddressOutOfRange);
+        }
+        let address = if self.block_addressed() { block } else { block * BLOCK_SIZE as u64 };
+        u32::try_from(address).map_err(|_| CardError::AddressOutOfRange)
+    }
+
+    /// `first` bloğundan başlayan blokları tek komutla okur (tek blok için CMD17, fazlası için CMD18).
+    pub fn read<B: SdhciBus>(&self, host: &mut Sdhci<B>, first: u64, segments: &mut [&mut [u8]]) -> Result<(), CardError> {
+        let blocks = segments.iter().map(|segment| segment.len()).sum::<usize>() / BLOCK_SIZE;
+        if blocks == 0 {
+            return Ok(());
+        }
+        let address = self.address(first, blocks)?;
+        let command = if blocks == 1 { CMD_READ_SINGLE_BLOCK } else { CMD_READ_MULTIPLE_BLOCK };
+        Ok(host.read_data(command, address, BLOCK_SIZE, segments)?)
+    }
+
+    /// `first` bloğundan başlayarak blokları tek komutla yazar (CMD24 / CMD25).
+    pub fn write<B: SdhciBus>(&self, host: &mut Sdhci<B>, first: u64, segments: &[&[u8]]) -> Result<(), CardError> {
+        let blocks = segments.iter().map(|segment| segment.len()).sum::<usize>() / BLOCK_SIZE;
+        if blocks == 0 {
+            return Ok(());
+        }
+        let address = self.address(first, blocks)?;
+        let command = if blocks == 1 { CMD_WRITE_BLOCK } else { CMD_WRITE_MULTIPLE_BLOCK };
+        Ok(host.write_data(command, address, BLOCK_SIZE, segments)?)
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} MB ({:?}, {:02}/{})", self.cid.name(), self.blocks / 2048, self.card_type, self.cid.month, self.cid.year)
+    }
+}
+
+/// Yuvadaki SD kartı tanır, 4 bit veri yoluna ve aktarım hızına geçirir. Denetleyici sıfırlanmış olmalı.
+pub fn init_sd<B: SdhciBus>(host: &mut Sdhci<B>) -> Result<Card, CardError> {
+    if !host.card_present() {
+        return Err(CardError::NoCard);
+    }
+    host.set_clock(IDENTIFICATION_CLOCK_HZ)?;
+    host.command(CMD_GO_IDLE_STATE, 0, ResponseType::None)?;
+
+    // v2 kartlar CMD8'e voltaj aralığı ve deseni geri göndererek yanıt verir; v1 kartlar komutu tanımaz.
+    let version2 = match host.command(CMD_SEND_IF_COND, IF_COND_ARGUMENT, ResponseType::R1) {
+        Ok(response) if response[0] & 0xFFF == IF_COND_ARGUMENT => true,
+        Ok(response) if response[0] & 0xF00 != IF_COND_ARGUMENT & 0xF00 => return Err(CardError::UnsupportedVoltage),
+        Ok(_) => return Err(CardError::BadResponse),
+        Err(HostError::CommandTimeout) => false,
+        Err(err) => return Err(err.into()),
+    };
+
+    // Yüksek kapasiteyi yalnızca v2 kartlara bildiririz (v1 kartlar HCS'li ACMD41'i reddedebilir).
+    let argument = OCR_VOLTAGE_WINDOW | if version2 { OCR_HCS } else { 0 };
+    let ocr = power_up(host, |host| Ok(app_command(host, 0, ACMD_SD_SEND_OP_COND, argument, ResponseType::R3)?[0]))?;
+    let card_type = if version2 && ocr & OCR_HCS != 0 { CardType::SdHighCapacity } else { CardType::SdStandard };
+
+    let cid = Cid::from_sd(register(host.command(CMD_ALL_SEND_CID, 0, ResponseType::R2)?));
+    // R6: üst 16 bit kartın seçtiği adres.
+    let rca = (host.command(CMD_SET_RELATIVE_ADDR, 0, ResponseType::R1)?[0] >> 16) as u16;
+    let blocks = sd_blocks(register(host.command(CMD_SEND_CSD, (rca as u32) << 16, ResponseType::R2)?))?;
+    card_status(host.command(CMD_SELECT_CARD, (rca as u32) << 16, ResponseType::R1b)?[0])?;
+    if card_type == CardType::SdStandard {
+        card_status(host.command(CMD_SET_BLOCKLEN, BLOCK_SIZE as u32, ResponseType::R1)?[0])?;
+    }
+    // Tüm SD kartlar 4 bit veri yolunu destekler (ACMD6 argümanı 2 = 4 bit).
+    card_status(app_command(host, rca, ACMD_SET_BUS_WIDTH, 2, ResponseType::R1)?[0])?;
+    host.set_bus_width(true);
+    host.set_clock(SD_CLOCK_HZ)?;
+    Ok(Card { card_type, rca, cid, blocks })
+}
+
+/// Dahili eMMC'yi tanır ve aktarım hızına geçirir (veri yolu 1 bit kalır). Denetleyici sıfırlanmış olmalı.
+pub fn init_mmc<B: SdhciBus>(host: &mut Sdhci<B>) -> Result<Card, CardError> {
+    host.set_clock(IDENTIFICATION_CLOCK_HZ)?;
+    host.command(CMD_GO_IDLE_STATE, 0, ResponseType::None)?;
+
+    // Sektör adreslemeyi desteklediğimizi bildiririz; 2 GB üstü kartlar OCR'de sektör modunu döndürür.
+    let argument = OCR_ACCESS_MODE_SECTOR | OCR_VOLTAGE_WINDOW | OCR_LOW_VOLTAGE;
+    let ocr = power_up(host, |host| Ok(host.command(CMD_SEND_OP_COND, argument, ResponseType::R3)?[0]))?;
+    let high_capacity = ocr & OCR_ACCESS_MODE_MASK == OCR_ACCESS_MODE_SECTOR;
+
+    let cid = Cid::from_mmc(register(host.command(CMD_ALL_SEND_CID, 0, ResponseType::R2)?));
+    let rca = MMC_RCA;
+    card_status(host.command(CMD_SET_RELATIVE_ADDR, (rca as u32) << 16, ResponseType::R1)?[0])?;
+    let csd = register(host.command(CMD_SEND_CSD, (rca as u32) << 16, ResponseType::R2)?);
+    card_status(host.command(CMD_SELECT_CARD, (rca as u32) << 16, ResponseType::R1b)?[0])?;
+
+    let (card_type, blocks) = if high_capacity {
+        // CSD'deki C_SIZE 2 GB'ı gösteremez; gerçek kapasite EXT_CSD'dedir.
+        let mut ext_csd = [0u8; 512];
+        host.read_data(CMD_SEND_EXT_CSD, 0, BLOCK_SIZE, &mut [&mut ext_csd])?;
+        let sectors = &ext_csd[EXT_CSD_SEC_COUNT..EXT_CSD_SEC_COUNT + 4];
+        (CardType::MmcHighCapacity, u32::from_le_bytes([sectors[0], sectors[1], sectors[2], sectors[3]]) as u64)
+    } else {
+        card_status(host.command(CMD_SET_BLOCKLEN, BLOCK_SIZE as u32, ResponseType::R1)?[0])?;
+        (CardType::Mmc, csd_v1_blocks(csd))
+    };
+    if blocks == 0 {
+        return Err(CardError::BadResponse);
+    }
+    host.set_clock(MMC_CLOCK_HZ)?;
+    Ok(Card { card_type, rca, cid, blocks })
+}
+
+// ACMD41/CMD1'i OCR'deki güç açılışı biti kalkana kadar tekrarlar ve son OCR'yi döndürür.
+fn power_up<B: SdhciBus>(
+    host: &mut Sdhci<B>,
+    mut send: impl FnMut(&mut Sdhci<B>) -> Result<u32, CardError>,
+) -> Result<u32, CardError> {
+    let deadline = host.bus().now_ms() + POWER_UP_TIMEOUT_MS;
+    loop {
+        let ocr = send(host)?;
+        if ocr & OCR_VOLTAGE_WINDOW == 0 {
+            return Err(CardError::UnsupportedVoltage);
+        }
+        if ocr & OCR_POWER_UP_DONE != 0 {
+            return Ok(ocr);
+        }
+        if host.bus().now_ms() >= deadline {
+            return Err(CardError::NotReady);
+        }
+    }
+}
+
+// CMD55 ardından uygulama komutu (ACMD) gönderir. `rca` tanımadan önce 0'dır.
+fn app_command<B: SdhciBus>(
+    host: &mut Sdhci<B>,
+    rca: u16,
+    index: u8,
+    argument: u32,
+    response: ResponseType,
+) -> Result<[u32; 4], CardError> {
+    let status = card_status(host.command(CMD_APP_CMD, (rca as u32) << 16, ResponseType::R1)?[0])?;
+    if status & R1_APP_CMD == 0 {
+        return Err(CardError::BadResponse);
+    }
+    Ok(host.command(index, argument, response)?)
+}
+
+// R1 kart durumundaki hata bitlerini denetler.
+fn card_status(status: u32) -> Result<u32, CardError> {
+    if status & (R1_OUT_OF_RANGE | R1_ADDRESS_ERROR) != 0 {
+        Err(CardError::AddressOutOfRange)
+    } else if status & R1_ERRORS != 0 {
+        Err(CardError::BadResponse)
+    } else {
+        Ok(status)
+    }
+}
+
+// R2 yanıtını (CID/CSD) 128 bitlik registera çevirir. Denetleyici CRC baytını atıp kalan 120 biti
+// yanıt registerlarına yerleştirir; bit numaraları spesifikasyondaki gibi olsun diye 8 bit kaydırılır.
+fn register(response: [u32; 4]) -> u128 {
+    let value = response.iter().rev().fold(0u128, |value, &word| (value << 32) | word as u128);
+    value << 8
+}
+
+// Registerın [high:low] bit alanı.
+fn field(register: u128, high: u32, low: u32) -> u64 {
+    ((register >> low) & ((1u128 << (high - low + 1)) - 1)) as u64
+}
+
+// SD CSD'den blok sayısı. v1 (SDSC): C_SIZE/C_SIZE_MULT/READ_BL_LEN; v2 (SDHC/SDXC): (C_SIZE + 1) * 512 KB.
+fn sd_blocks(csd: u128) -> Result<u64, CardError> {
+    match field(csd, 127, 126) {
+        0 => Ok(csd_v1_blocks(csd)),
+        1 => Ok((field(csd, 69, 48) + 1) * 1024),
+        _ => Err(CardError::UnsupportedCsd),
+    }
+}
+
+// SD CSD v1 ve MMC CSD: kapasite = (C_SIZE + 1) * 2^(C_SIZE_MULT + 2) * 2^READ_BL_LEN bayt.
+fn csd_v1_blocks(csd: u128) -> u64 {
+    let read_bl_len = field(csd, 83, 80);
+    let c_size = field(csd, 73, 62);
+    let c_size_mult = field(csd, 49, 47);
+    ((c_size + 1) << (c_size_mult + 2 + read_bl_len)) / BLOCK_SIZE as u64
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use crate::drivers::sdhci::tests::{ModelBus, ModelCard, ModelKind};
+
+    fn set(register: &mut u128, high: u32, low: u32, value: u128) {
+        *register |= value << low;
+        assert!(value < 1 << (high - low + 1));
+    }
+
+    fn sd_cid() -> u128 {
+        let mut cid = 0;
+        set(&mut cid, 127, 120, 0x03);
+        set(&mut cid, 119, 104, u16::from_be_bytes(*b"SD") as u128);
+        set(&mut cid, 103, 64, u64::from_be_bytes(*b"\0\0\0SU08G") as u128);
+        set(&mut cid, 63, 56, 0x80);
+        set(&mut cid, 55, 24, 0x1234_5678);
+        set(&mut cid, 19, 12, 24);
+        set(&mut cid, 11, 8, 5);
+        cid
+    }
+
+    // SD CSD v2: C_SIZE = 1 -> 2 * 512 KB
+    fn sdhc_csd() -> u128 {
+        let mut csd = 0;
+        set(&mut csd, 127, 126, 1);
+        set(&mut csd, 69, 48, 1);
+        csd
+    }
+
+    // SD CSD v1 / MMC CSD: 256 * 2^(0 + 2) * 512 bayt = 512 KB
+    fn v1_csd() -> u128 {
+        let mut csd = 0;
+        set(&mut csd, 83, 80, 9);
+        set(&mut csd, 73, 62, 255);
+        csd
+    }
+
+    fn host(card: ModelCard) -> Sdhci<ModelBus> {
+        let mut host = Sdhci::new(ModelBus::new(Some(card)));
+        host.reset().unwrap();
+        host
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn sdhc_card_is_identified_and_block_addressed() {
+        let mut host = host(ModelCard::new(ModelKind::SdHigh, sd_cid(), sdhc_csd(), 2048 * 512));
+        let card = init_sd(&mut host).unwrap();
+        assert_eq!((card.card_type, card.rca, card.blocks), (CardType::SdHighCapacity, 0x1234, 2048));
+        assert_eq!((card.cid.manufacturer, card.cid.oem, card.cid.name()), (0x03, 0x5344, "SU08G"));
+        assert_eq!((card.cid.revision, card.cid.serial, card.cid.year, card.cid.month), (0x80, 0x1234_5678, 2024, 5));
+        // ACMD41 üçüncü denemede hazır; SDHC kartlara CMD16 gönderilmez.
+        assert_eq!(host.bus().commands, [0, 8, 55, 0xA9, 55, 0xA9, 55, 0xA9, 2, 3, 9, 7, 55, 0x86]);
+        assert!(host.bus().four_bit() && host.bus().card().bus_width == 2);
+
+        let data: Vec<u8> = (0..3 * 512).map(|i| (i / 7) as u8).collect();
+        card.write(&mut host, 5, &[&data[..512], &data[512..]]).unwrap();
+        assert_eq!(host.bus().data_argument, 5);
+        assert_eq!(&host.bus().card().data[5 * 512..8 * 512], &data[..]);
+        let mut first = [0u8; 1024];
+        let mut second = [0u8; 512];
+        card.read(&mut host, 5, &mut [&mut first, &mut second]).unwrap();
+        assert_eq!((&first[..], &second[..]), (&data[..1024], &data[1024..]));
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn standard_capacity_cards_use_byte_addresses() {
+        let mut host = host(ModelCard::new(ModelKind::SdV1, sd_cid(), v1_csd(), 1024 * 512));
+        let card = init_sd(&mut host).unwrap();
+        assert_eq!((card.card_type, card.blocks), (CardType::SdStandard, 1024));
+        assert!(host.bus().commands.contains(&16) && host.bus().card().block_len == 512);
+        card.write(&mut host, 3, &[&[0xAB; 512]]).unwrap();
+        assert_eq!(host.bus().data_argument, 3 * 512);
+        assert_eq!(host.bus().card().data[3 * 512], 0xAB);
+
+        let mut host = self::host(ModelCard::new(ModelKind::SdStandard, sd_cid(), v1_csd(), 1024 * 512));
+        assert_eq!(init_sd(&mut host).unwrap().card_type, CardType::SdStandard);
+
+        let mut host = self::host(ModelCard::new(ModelKind::Mmc, 0, v1_csd(), 1024 * 512));
+        let card = init_mmc(&mut host).unwrap();
+        assert_eq!((card.card_type, card.rca, card.blocks), (CardType::Mmc, 1, 1024));
+        card.read(&mut host, 2, &mut [&mut [0u8; 512]]).unwrap();
+        assert_eq!(host.bus().data_argument, 2 * 512);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn high_capacity_emmc_reads_sector_count_from_ext_csd() {
+        let mut cid = 0;
+        set(&mut cid, 127, 120, 0x15);
+        set(&mut cid, 111, 104, 0x01);
+        set(&mut cid, 103, 56, u64::from_be_bytes(*b"\0\08GTF4R") as u128);
+        set(&mut cid, 55, 48, 0x10);
+        set(&mut cid, 47, 16, 0xCAFE);
+        set(&mut cid, 15, 12, 3);
+        set(&mut cid, 11, 8, 13);
+        let mut csd = 0;
+        set(&mut csd, 73, 62, 0xFFF); // Kapasite EXT_CSD'de
+        let mut model = ModelCard::new(ModelKind::MmcSector, cid, csd, 4096 * 512);
+        model.ext_csd[EXT_CSD_SEC_COUNT..EXT_CSD_SEC_COUNT + 4].copy_from_slice(&4096u32.to_le_bytes());
+        let mut host = host(model);
+
+        let card = init_mmc(&mut host).unwrap();
+        assert_eq!((card.card_type, card.rca, card.blocks), (CardType::MmcHighCapacity, 1, 4096));
+        assert_eq!((card.cid.manufacturer, card.cid.oem, card.cid.name()), (0x15, 0x01, "8GTF4R"));
+        assert_eq!((card.cid.serial, card.cid.year, card.cid.month), (0xCAFE, 2010, 3));
+        assert_eq!(host.bus().commands, [0, 1, 1, 1, 2, 3, 9, 7, 8]);
+        card.write(&mut host, 4000, &[&[1; 1024]]).unwrap();
+        assert_eq!(host.bus().data_argument, 4000);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn initialization_and_transfer_errors_are_typed() {
+        let mut empty = Sdhci::new(ModelBus::new(None));
+        empty.reset().unwrap();
+        assert_eq!(init_sd(&mut empty), Err(CardError::NoCard));
+        assert_eq!(init_mmc(&mut empty), Err(CardError::Host(HostError::CommandTimeout)));
+
+        let mut model = ModelCard::new(ModelKind::SdHigh, sd_cid(), sdhc_csd(), 2048 * 512);
+        model.ready_after = 0;
+        assert_eq!(init_sd(&mut host(model)), Err(CardError::NotReady));
+
+        let mut csd = sdhc_csd();
+        set(&mut csd, 127, 126, 2);
+        let model = ModelCard::new(ModelKind::SdHigh, sd_cid(), csd, 2048 * 512);
+        assert_eq!(init_sd(&mut host(model)), Err(CardError::UnsupportedCsd));
+
+        let mut host = host(ModelCard::new(ModelKind::SdHigh, sd_cid(), sdhc_csd(), 2048 * 512));
+        let card = init_sd(&mut host).unwrap();
+        let mut buffer = [0u8; 1024];
+        assert_eq!(card.read(&mut host, 2047, &mut [&mut buffer]), Err(CardError::AddressOutOfRange));
+        assert_eq!(card.read(&mut host, u64::MAX, &mut [&mut buffer]), Err(CardError::AddressOutOfRange));
+        assert_eq!(SahneError::from(CardError::NotReady), SahneError::TimedOut);
+    }
+}
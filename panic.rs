@@ -14,6 +14,9 @@ fn panic(info: &PanicInfo) -> ! {
     // Eğer sistem zaten panik halindeyse veya çoklu işlemci yoksa bu gerekmeyebilir.
      unsafe { crate::asm::disable_interrupts(); } // Eğer disable_interrupts varsa kullanın
 
+    // Heap dolu olsa bile mesaj biçimlendirilebilsin diye acil durum rezervini kalıcı olarak aç.
+    core::mem::forget(crate::mm::emergency_reserve());
+
     // Panik bilgilerini printk! kullanarak yazdır
     printk!("\nKERNEL PANIC: ");
     if let Some(location) = info.location() {
@@ -0,0 +1,346 @@
+// main_kernel/partition.rs
+// Bölüm Tablosu Okuyucu (MBR ve GPT)
+// Bir blok cihazın bölümlerini bulur; storage.rs her bölümü ayrı bir kaynak olarak kaydeder
+// ("emmc0p1", "emmc0p2" ...). Böylece kök dosya sistemi cihazın 0. sektöründe başlamak zorunda değildir.
+//
+// - MBR: 4 birincil bölüm (1-4) ve genişletilmiş bölümdeki mantıksal bölümler (5'ten başlar).
+// - GPT: Koruyucu MBR (tür 0xEE) varsa birincil başlık, bozuksa sondaki yedek başlık okunur;
+//   başlık ve bölüm dizisi CRC32 ile doğrulanır. Bölüm numarası dizideki sıradır (1'den başlar).
+// Bölüm tablosu olmayan cihazlar (doğrudan ext2 ile biçimlendirilmiş imajlar) için boş liste döner.
+//
+// Donanımdan bağımsızdır; host testleri bellekte oluşturulan imajlarla çalışır.
+
+use alloc::vec::Vec;
+
+use crate::block::BlockDevice;
+use crate::sahne64::buffer::zeroed_buffer;
+use crate::sahne64::SahneError;
+
+/// Bir cihazda kaydedilecek en fazla bölüm sayısı (kaynak tablosunu küçük tutmak için).
+pub const MAX_PARTITIONS: usize = 16;
+
+// MBR yerleşimi
+const MBR_TABLE_OFFSET: usize = 446;
+const MBR_ENTRY_SIZE: usize = 16;
+const MBR_SIGNATURE_OFFSET: usize = 510;
+const MBR_SIGNATURE: [u8; 2] = [0x55, 0xAA];
+const MBR_TYPE_EMPTY: u8 = 0x00;
+const MBR_TYPE_GPT_PROTECTIVE: u8 = 0xEE;
+const MBR_TYPES_EXTENDED: [u8; 3] = [0x05, 0x0F, 0x85];
+const FIRST_LOGICAL_NUMBER: u32 = 5;
+
+// GPT yerleşimi (UEFI spesifikasyonu 5.3)
+const GPT_SIGNATURE: &[u8; 8] = b"EFI PART";
+const GPT_HEADER_MIN_SIZE: usize = 92;
+const GPT_ENTRY_MIN_SIZE: usize = 128;
+// Bölüm dizisinin en büyük boyutu: standart 128 girdi x 128 bayt. 2 MB RAM'de daha büyüğü reddedilir.
+const GPT_MAX_ENTRY_BYTES: usize = 128 * 128;
+
+/// Bulunan bir bölüm. Blok numaraları cihazın blok boyutu cinsindendir.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionEntry {
+    pub number: u32, // Kaynak adındaki numara ("emmc0p<number>")
+    pub first_block: u64,
+    pub block_count: u64,
+}
+
+fn le_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn le_u64(bytes: &[u8], offset: usize) -> u64 {
+    le_u32(bytes, offset) as u64 | (le_u32(bytes, offset + 4) as u64) << 32
+}
+
+/// IEEE 802.3 CRC32 (GPT başlık ve bölüm dizisi sağlaması). Tablo kullanmaz; sadece açılışta çalışır.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn read_block<D: BlockDevice + ?Sized>(device: &D, block: u64) -> Result<Vec<u8>, SahneError> {
+    let mut buffer = zeroed_buffer(device.block_size())?;
+    device.read_blocks(block, &mut buffer)?;
+    Ok(buffer)
+}
+
+// Aralık cihazın içindeyse bölümü listeye ekler (bozuk girdiler atlanır). Liste doluysa false döner.
+fn push_partition(partitions: &mut Vec<PartitionEntry>, entry: PartitionEntry, device_blocks: u64) -> Result<bool, SahneError> {
+    if partitions.len() >= MAX_PARTITIONS {
+        return Ok(false);
+    }
+    let fits = entry.first_block.checked_add(entry.block_count).is_some_and(|end| end <= device_blocks);
+    if entry.first_block > 0 && entry.block_count > 0 && fits {
+        partitions.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+        partitions.push(entry);
+    }
+    Ok(true)
+}
+
+// MBR girdisi: (tür, başlangıç LBA, sektör sayısı)
+fn mbr_entry(sector: &[u8], index: usize) -> (u8, u64, u64) {
+    let entry = &sector[MBR_TABLE_OFFSET + index * MBR_ENTRY_SIZE..];
+    (entry[4], le_u32(entry, 8) as u64, le_u32(entry, 12) as u64)
+}
+
+fn has_mbr_signature(sector: &[u8]) -> bool {
+    sector[MBR_SIGNATURE_OFFSET..MBR_SIGNATURE_OFFSET + 2] == MBR_SIGNATURE
+}
+
+/// Cihazın bölüm tablosunu okur. Tablo yoksa boş liste döner; GPT var ama iki başlık da
+/// bozuksa InvalidParameter döner.
+pub fn scan<D: BlockDevice + ?Sized>(device: &D) -> Result<Vec<PartitionEntry>, SahneError> {
+    let device_blocks = device.block_count();
+    if device.block_size() < 512 || device_blocks < 2 {
+        return Ok(Vec::new());
+    }
+    let sector = read_block(device, 0)?;
+    if !has_mbr_signature(&sector) {
+        return Ok(Vec::new());
+    }
+    // Durum baytı 0x00 veya 0x80 olmalı; değilse bu bir bölüm tablosu değil (örn. FAT önyükleme sektörü).
+    let statuses_valid = (0..4).all(|index| matches!(sector[MBR_TABLE_OFFSET + index * MBR_ENTRY_SIZE], 0x00 | 0x80));
+    if !statuses_valid {
+        return Ok(Vec::new());
+    }
+    if (0..4).any(|index| mbr_entry(&sector, index).0 == MBR_TYPE_GPT_PROTECTIVE) {
+        return scan_gpt(device);
+    }
+
+    let mut partitions = Vec::new();
+    for index in 0..4 {
+        let (kind, first_block, block_count) = mbr_entry(&sector, index);
+        if kind == MBR_TYPE_EMPTY {
+            continue;
+        }
+        if MBR_TYPES_EXTENDED.contains(&kind) {
+            scan_logical(device, first_block, block_count, &mut partitions)?;
+            continue;
+        }
+        let entry = PartitionEntry { number: index as u32 + 1, first_block, block_count };
+        if !push_partition(&mut partitions, entry, device_blocks)? {
+            break;
+        }
+    }
+    partitions.sort_unstable_by_key(|entry| entry.number);
+    Ok(partitions)
+}
+
+// Genişletilmiş bölümdeki EBR (Extended Boot Record) zincirini izler. Her EBR'nin ilk girdisi
+// EBR'ye göre bir mantıksal bölüm, ikinci girdisi genişletilmiş bölümün başına göre sonraki EBR'dir.
+fn scan_logical<D: BlockDevice + ?Sized>(
+    device: &D,
+    extended_first: u64,
+    extended_count: u64,
+    partitions: &mut Vec<PartitionEntry>,
+) -> Result<(), SahneError> {
+    let extended_end = extended_first.saturating_add(extended_count).min(device.block_count());
+    let mut ebr = extended_first;
+    // EBR'ler artan sırada olmalı; geri (veya kendini) gösteren bozuk bir zincir döngüye sokmaz.
+    for number in (FIRST_LOGICAL_NUMBER..).take(MAX_PARTITIONS) {
+        if ebr == 0 || ebr >= extended_end {
+            break;
+        }
+        let sector = read_block(device, ebr)?;
+        if !has_mbr_signature(&sector) {
+            break;
+        }
+        let (kind, relative_first, block_count) = mbr_entry(&sector, 0);
+        if kind != MBR_TYPE_EMPTY {
+            let first_block = ebr.saturating_add(relative_first);
+            let inside = first_block.saturating_add(block_count) <= extended_end;
+            let entry = PartitionEntry { number, first_block, block_count };
+            if inside && !push_partition(partitions, entry, device.block_count())? {
+                break;
+            }
+        }
+        let (next_kind, next_relative, _) = mbr_entry(&sector, 1);
+        if !MBR_TYPES_EXTENDED.contains(&next_kind) || next_relative == 0 {
+            break;
+        }
+        let next = extended_first.saturating_add(next_relative);
+        if next <= ebr {
+            break;
+        }
+        ebr = next;
+    }
+    Ok(())
+}
+
+fn scan_gpt<D: BlockDevice + ?Sized>(device: &D) -> Result<Vec<PartitionEntry>, SahneError> {
+    let last_block = device.block_count() - 1;
+    let (entries, entry_count, entry_size) = match read_gpt(device, 1) {
+        Ok(table) => table,
+        Err(SahneError::InvalidParameter) => read_gpt(device, last_block)?, // Yedek başlık
+        Err(err) => return Err(err),
+    };
+
+    let mut partitions = Vec::new();
+    for index in 0..entry_count {
+        let entry = &entries[index * entry_size..(index + 1) * entry_size];
+        if entry[..16].iter().all(|&byte| byte == 0) {
+            continue; // Kullanılmayan girdi (tür GUID'i sıfır)
+        }
+        let first_block = le_u64(entry, 32);
+        // Son blok dahildir; ters veya taşan aralıklar geçersiz girdi sayılır ve atlanır.
+        let block_count = match le_u64(entry, 40).checked_sub(first_block).and_then(|span| span.checked_add(1)) {
+            Some(count) => count,
+            None => continue,
+        };
+        let entry = PartitionEntry { number: index as u32 + 1, first_block, block_count };
+        if !push_partition(&mut partitions, entry, device.block_count())? {
+            break;
+        }
+    }
+    Ok(partitions)
+}
+
+// `lba` bloğundaki GPT başlığını ve bölüm dizisini okuyup doğrular: (dizi, girdi sayısı, girdi boyutu).
+// Doğrulama hataları InvalidParameter döner; çağıran yedek başlığı dener.
+fn read_gpt<D: BlockDevice + ?Sized>(device: &D, lba: u64) -> Result<(Vec<u8>, usize, usize), SahneError> {
+    let block_size = device.block_size();
+    let mut header = read_block(device, lba)?;
+    let header_size = le_u32(&header, 12) as usize;
+    if &header[..8] != GPT_SIGNATURE || !(GPT_HEADER_MIN_SIZE..=block_size).contains(&header_size) {
+        return Err(SahneError::InvalidParameter);
+    }
+    let header_crc = le_u32(&header, 16);
+    header[16..20].fill(0); // Sağlama, alanı sıfırken hesaplanır
+    if crc32(&header[..header_size]) != header_crc || le_u64(&header, 24) != lba {
+        return Err(SahneError::InvalidParameter);
+    }
+
+    let entries_lba = le_u64(&header, 72);
+    let entry_count = le_u32(&header, 80) as usize;
+    let entry_size = le_u32(&header, 84) as usize;
+    if entry_size < GPT_ENTRY_MIN_SIZE || !entry_size.is_multiple_of(GPT_ENTRY_MIN_SIZE) {
+        return Err(SahneError::InvalidParameter);
+    }
+    let table_bytes = entry_count.checked_mul(entry_size).ok_or(SahneError::InvalidParameter)?;
+    if table_bytes > GPT_MAX_ENTRY_BYTES {
+        return Err(SahneError::NotSupported);
+    }
+    let table_blocks = table_bytes.div_ceil(block_size);
+    let fits = entries_lba.checked_add(table_blocks as u64).is_some_and(|end| end <= device.block_count());
+    if entries_lba < 2 || !fits {
+        return Err(SahneError::InvalidParameter);
+    }
+
+    let mut entries = zeroed_buffer(table_blocks * block_size)?;
+    device.read_blocks(entries_lba, &mut entries)?;
+    if crc32(&entries[..table_bytes]) != le_u32(&header, 88) {
+        return Err(SahneError::InvalidParameter);
+    }
+    Ok((entries, entry_count, entry_size))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::tests::RamDisk;
+
+    fn put_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_u64(bytes: &mut [u8], offset: usize, value: u64) {
+        bytes[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    }
+
+    // Verilen sektöre bir MBR/EBR girdisi yazar.
+    fn put_mbr_entry(disk: &RamDisk, block: usize, index: usize, kind: u8, first: u32, count: u32) {
+        let mut data = disk.data.borrow_mut();
+        let sector = &mut data[block * 512..(block + 1) * 512];
+        let entry = MBR_TABLE_OFFSET + index * MBR_ENTRY_SIZE;
+        sector[entry + 4] = kind;
+        put_u32(sector, entry + 8, first);
+        put_u32(sector, entry + 12, count);
+        sector[MBR_SIGNATURE_OFFSET..].copy_from_slice(&MBR_SIGNATURE);
+    }
+
+    // Başlığı `lba`ya, 4 girdilik diziyi `entries_lba`ya yazar. Girdiler: (dizideki sıra, ilk, son).
+    fn put_gpt(disk: &RamDisk, lba: u64, entries_lba: u64, partitions: &[(usize, u64, u64)]) {
+        let mut entries = [0u8; 4 * 128];
+        for &(index, first, last) in partitions {
+            let entry = &mut entries[index * 128..(index + 1) * 128];
+            entry[..16].copy_from_slice(&[0x0F; 16]); // Herhangi bir sıfır olmayan tür GUID'i
+            put_u64(entry, 32, first);
+            put_u64(entry, 40, last);
+        }
+        let mut header = [0u8; 512];
+        header[..8].copy_from_slice(GPT_SIGNATURE);
+        put_u32(&mut header, 12, 92);
+        put_u64(&mut header, 24, lba);
+        put_u64(&mut header, 72, entries_lba);
+        put_u32(&mut header, 80, 4);
+        put_u32(&mut header, 84, 128);
+        put_u32(&mut header, 88, crc32(&entries));
+        let header_crc = crc32(&header[..92]);
+        put_u32(&mut header, 16, header_crc);
+
+        let mut data = disk.data.borrow_mut();
+        data[lba as usize * 512..(lba as usize + 1) * 512].copy_from_slice(&header);
+        data[entries_lba as usize * 512..entries_lba as usize * 512 + entries.len()].copy_from_slice(&entries);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn crc32_matches_reference_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(&[]), 0);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn mbr_primary_and_logical_partitions_are_found() {
+        let disk = RamDisk::new(256, 512);
+        assert_eq!(scan(&disk), Ok(Vec::new())); // İmza yok: bölüm tablosu yok
+
+        put_mbr_entry(&disk, 0, 0, 0x83, 8, 40);
+        put_mbr_entry(&disk, 0, 1, 0x05, 100, 100); // Genişletilmiş: 100..200
+        put_mbr_entry(&disk, 0, 3, 0x83, 250, 50);  // Cihazın dışına taşıyor, atlanır
+        put_mbr_entry(&disk, 100, 0, 0x83, 2, 20);  // p5: 102..122
+        put_mbr_entry(&disk, 100, 1, 0x05, 50, 50); // Sonraki EBR: 150
+        put_mbr_entry(&disk, 150, 0, 0x0B, 1, 30);  // p6: 151..181
+        assert_eq!(
+            scan(&disk),
+            Ok(alloc::vec![
+                PartitionEntry { number: 1, first_block: 8, block_count: 40 },
+                PartitionEntry { number: 5, first_block: 102, block_count: 20 },
+                PartitionEntry { number: 6, first_block: 151, block_count: 30 },
+            ])
+        );
+
+        // Kendini gösteren EBR zinciri döngüye girmez.
+        put_mbr_entry(&disk, 150, 1, 0x05, 50, 50);
+        assert_eq!(scan(&disk).unwrap().len(), 3);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn gpt_falls_back_to_backup_header() {
+        let disk = RamDisk::new(128, 512);
+        put_mbr_entry(&disk, 0, 0, MBR_TYPE_GPT_PROTECTIVE, 1, 127);
+        assert_eq!(scan(&disk), Err(SahneError::InvalidParameter)); // İki başlık da yok
+
+        // Taşan (0..u64::MAX) ve ters aralıklı girdiler atlanır.
+        put_gpt(&disk, 1, 2, &[(0, 34, 63), (1, 0, u64::MAX), (2, 64, 99), (3, 90, 80)]);
+        put_gpt(&disk, 127, 123, &[(0, 34, 63), (1, 0, u64::MAX), (2, 64, 99), (3, 90, 80)]);
+        let expected = alloc::vec![
+            PartitionEntry { number: 1, first_block: 34, block_count: 30 },
+            PartitionEntry { number: 3, first_block: 64, block_count: 36 },
+        ];
+        assert_eq!(scan(&disk), Ok(expected.clone()));
+
+        // Birincil bölüm dizisi bozulursa sağlama tutmaz ve yedek kullanılır.
+        disk.data.borrow_mut()[2 * 512 + 40] ^= 0xFF;
+        assert_eq!(scan(&disk), Ok(expected));
+    }
+}
@@ -0,0 +1,144 @@
+// drivers/plic.rs
+// Platform Seviyesi Kesme Denetleyicisi (PLIC) Sürücüsü
+// Cihaz kesmeleri (UART, virtio ...) PLIC üzerinden işlemciye harici kesme olarak gelir (scause 9).
+// Sürücüler kaynak numaraları için bir işleyici kaydeder; traps.rs harici kesmede handle_interrupt'ı
+// çağırır, o da bekleyen kaynağı talep eder (claim), işleyicisini çalıştırır ve tamamlar (complete).
+//
+// Kesme işleyicisi kilit almaz: adres, bağlam ve işleyici tablosu atomik değişkenlerde tutulur.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::fdt::Fdt;
+use crate::printk;
+use crate::rs_io;
+use crate::sahne64::SahneError;
+
+// Device Tree yoksa kullanılan değerler (QEMU virt, hart 0'ın S-mode bağlamı).
+const DEFAULT_BASE: usize = 0x0C00_0000;
+const DEFAULT_CONTEXT: usize = 1;
+
+// interrupts-extended içinde S-mode harici kesmesinin neden kodu.
+const SUPERVISOR_EXTERNAL: u32 = 9;
+
+// Register yerleşimi (RISC-V PLIC spesifikasyonu)
+const PRIORITY_BASE: usize = 0x00_0000; // + 4 * kaynak
+const ENABLE_BASE: usize = 0x00_2000;   // + 0x80 * bağlam, kaynak başına 1 bit
+const ENABLE_STRIDE: usize = 0x80;
+const CONTEXT_BASE: usize = 0x20_0000;  // + 0x1000 * bağlam
+const CONTEXT_STRIDE: usize = 0x1000;
+const CONTEXT_THRESHOLD: usize = 0x0;
+const CONTEXT_CLAIM: usize = 0x4;       // Okuma: talep (claim), yazma: tamamla (complete)
+
+/// Desteklenen en yüksek kaynak numarası + 1 (QEMU virt 53 kaynak kullanır).
+pub const MAX_SOURCES: usize = 64;
+
+// Kaynaklara verilen öncelik; eşik 0 olduğu için 0'dan büyük her öncelik kesme üretir.
+const DEFAULT_PRIORITY: u32 = 1;
+
+static BASE: AtomicUsize = AtomicUsize::new(0); // 0: başlatılmadı
+static CONTEXT: AtomicUsize = AtomicUsize::new(DEFAULT_CONTEXT);
+
+// Kaynak başına işleyici (fn() işaretçisi usize olarak; 0 = yok).
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_HANDLER: AtomicUsize = AtomicUsize::new(0);
+static HANDLERS: [AtomicUsize; MAX_SOURCES] = [NO_HANDLER; MAX_SOURCES];
+
+fn read(offset: usize) -> u32 {
+    unsafe { rs_io::mmio_read32(BASE.load(Ordering::Acquire) + offset) }
+}
+
+fn write(offset: usize, value: u32) {
+    unsafe { rs_io::mmio_write32(BASE.load(Ordering::Acquire) + offset, value) }
+}
+
+/// PLIC'i bulur, tüm kaynakları kapatır ve S-mode harici kesmelerini açar.
+pub fn init(fdt: Option<&Fdt>) {
+    let node = fdt.and_then(|fdt| {
+        fdt.compatible_nodes("riscv,plic0")
+            .next()
+            .or_else(|| fdt.compatible_nodes("sifive,plic-1.0.0").next())
+    });
+    let base = node.as_ref().and_then(|node| node.reg(0)).map_or(DEFAULT_BASE, |(base, _)| base as usize);
+    // interrupts-extended: (phandle, neden) çiftleri; çiftin sırası bağlam numarasıdır.
+    // Tek çekirdekli sistemde ilk S-mode harici kesmesi bizim bağlamımızdır.
+    let context = node
+        .as_ref()
+        .and_then(|node| node.property("interrupts-extended"))
+        .and_then(|cells| {
+            cells
+                .chunks_exact(8)
+                .position(|pair| u32::from_be_bytes([pair[4], pair[5], pair[6], pair[7]]) == SUPERVISOR_EXTERNAL)
+        })
+        .unwrap_or(DEFAULT_CONTEXT);
+
+    BASE.store(base, Ordering::Release);
+    CONTEXT.store(context, Ordering::Release);
+    for word in 0..MAX_SOURCES / 32 {
+        write(enable_offset(word * 32), 0);
+    }
+    write(context_offset(CONTEXT_THRESHOLD), 0);
+    unsafe { riscv::register::sie::set_sext(); }
+    printk!("PLIC @ {:#x}, bağlam {}\n", base, context);
+}
+
+fn enable_offset(source: usize) -> usize {
+    ENABLE_BASE + CONTEXT.load(Ordering::Acquire) * ENABLE_STRIDE + (source / 32) * 4
+}
+
+fn context_offset(register: usize) -> usize {
+    CONTEXT_BASE + CONTEXT.load(Ordering::Acquire) * CONTEXT_STRIDE + register
+}
+
+/// `source` kaynağı için işleyiciyi kaydeder ve kaynağı etkinleştirir. İşleyici kesme bağlamında
+/// çalışır; kilit beklememeli ve cihazın kesme nedenini temizlemelidir.
+pub fn register_handler(source: u32, handler: fn()) -> Result<(), SahneError> {
+    let index = source as usize;
+    if index == 0 || index >= MAX_SOURCES {
+        return Err(SahneError::InvalidParameter);
+    }
+    if BASE.load(Ordering::Acquire) == 0 {
+        return Err(SahneError::NotSupported);
+    }
+    if HANDLERS[index]
+        .compare_exchange(0, handler as usize, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+        return Err(SahneError::ResourceBusy);
+    }
+    write(PRIORITY_BASE + index * 4, DEFAULT_PRIORITY);
+    let enable = enable_offset(index);
+    write(enable, read(enable) | (1 << (index % 32)));
+    Ok(())
+}
+
+/// Kaynağı kapatır ve işleyicisini kaldırır (kapatma kancaları için).
+pub fn unregister_handler(source: u32) {
+    let index = source as usize;
+    if index == 0 || index >= MAX_SOURCES || BASE.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    let enable = enable_offset(index);
+    write(enable, read(enable) & !(1 << (index % 32)));
+    HANDLERS[index].store(0, Ordering::Release);
+}
+
+/// Harici kesme işleyicisi (traps.rs). Bekleyen tüm kaynakları sırayla işler.
+pub fn handle_interrupt() {
+    if BASE.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    loop {
+        let source = read(context_offset(CONTEXT_CLAIM));
+        if source == 0 {
+            break;
+        }
+        match HANDLERS.get(source as usize).map(|slot| slot.load(Ordering::Acquire)) {
+            Some(raw) if raw != 0 => {
+                let handler: fn() = unsafe { core::mem::transmute::<usize, fn()>(raw) };
+                handler();
+            }
+            _ => printk!("PLIC: İşleyicisi olmayan kesme kaynağı {}\n", source),
+        }
+        write(context_offset(CONTEXT_CLAIM), source);
+    }
+}
@@ -0,0 +1,147 @@
+// main_kernel/power.rs
+// Kontrollü Kapatma ve Yeniden Başlatma
+// reboot/poweroff sistem çağrıları sistemi sırayla kapatır:
+//   1. Görevlere sonlanma bildirimi gönderilir (çağıran hariç): bekleyen çağrıları Interrupted döner ve
+//      task::termination_requested() true olur. Görevlerin dosya sistemlerini ayırıp çıkması için
+//      TERMINATION_GRACE_MS kadar beklenir; çıkmayanlar ve çekirdek görevleri zorla durdurulur.
+//   2. Kapatma kancaları aşama sırasıyla çalışır: dosya sistemleri, blok önbellekleri, sürücüler.
+//   3. Kesmeler kapatılır ve güç firmware (SBI SRST) veya PSU üzerinden kesilir.
+
+use spin::Mutex;
+use crate::printk;
+use crate::sahne64::SahneError;
+use crate::sbi;
+use crate::sched;
+use crate::drivers::{psu, timer};
+
+// Kapatma bildiriminden sonra görevlerin kendiliğinden çıkması için beklenen en uzun süre
+// ve bu sürede görevlerin çıkıp çıkmadığının kontrol aralığı.
+const TERMINATION_GRACE_MS: u64 = 2000;
+const TERMINATION_POLL_MS: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    Reboot,
+    PowerOff,
+}
+
+// Kapatma kancalarının çalışma aşamaları (tanım sırasıyla çalışırlar).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownStage {
+    Filesystems, // Açık dosyalar ve süper bloklar diske yazılır
+    BlockCaches, // Kirli (dirty) bloklar aygıtlara yazılır
+    Drivers,     // Donanım güvenli bir duruma alınır (DMA durdurulur, kesmeler kapatılır)
+}
+
+const STAGES: [ShutdownStage; 3] = [ShutdownStage::Filesystems, ShutdownStage::BlockCaches, ShutdownStage::Drivers];
+
+#[derive(Clone, Copy)]
+struct ShutdownHook {
+    stage: ShutdownStage,
+    name: &'static str,
+    hook: fn(),
+}
+
+// Kapatma sırasında heap'e güvenmemek için kancalar sabit boyutlu bir tabloda tutulur.
+const MAX_SHUTDOWN_HOOKS: usize = 16;
+static SHUTDOWN_HOOKS: Mutex<[Option<ShutdownHook>; MAX_SHUTDOWN_HOOKS]> = Mutex::new([None; MAX_SHUTDOWN_HOOKS]);
+
+// Kapatma sırasında çağrılacak bir kanca kaydeder. Aynı aşamadaki kancalar kayıt sırasıyla çalışır.
+// Tablo doluysa OutOfMemory döner.
+pub fn register_shutdown_hook(stage: ShutdownStage, name: &'static str, hook: fn()) -> Result<(), SahneError> {
+    let mut hooks = SHUTDOWN_HOOKS.lock();
+    let slot = hooks.iter_mut().find(|slot| slot.is_none()).ok_or(SahneError::OutOfMemory)?;
+    *slot = Some(ShutdownHook { stage, name, hook });
+    Ok(())
+}
+
+// Kayıtlı kancaları aşama sırasıyla çalıştırır.
+fn run_shutdown_hooks() {
+    // Kanca kendi kaydını yapmaya çalışırsa kilitlenmemek için tablo kopyalanır.
+    let hooks = *SHUTDOWN_HOOKS.lock();
+    for stage in STAGES {
+        for entry in hooks.iter().flatten().filter(|entry| entry.stage == stage) {
+            printk!("Kapatma: {:?} / {}\n", stage, entry.name);
+            (entry.hook)();
+        }
+    }
+}
+
+// Sistemi kapatır veya yeniden başlatır. Geri dönmez.
+pub fn shutdown(action: PowerAction) -> ! {
+    printk!("Sistem {}...\n", match action {
+        PowerAction::Reboot => "yeniden başlatılıyor",
+        PowerAction::PowerOff => "kapatılıyor",
+    });
+
+    let notified = sched::request_termination_of_others();
+    let deadline = timer::now_ticks().saturating_add(timer::ms_to_ticks(TERMINATION_GRACE_MS));
+    while sched::terminating_tasks() > 0 && timer::now_ticks() < deadline {
+        sched::sleep(TERMINATION_POLL_MS);
+    }
+    let stopped = sched::stop_other_tasks();
+    printk!("Kapatma: {} göreve bildirim gönderildi, {} görev zorla durduruldu\n", notified, stopped);
+    run_shutdown_hooks();
+
+    // Bu noktadan sonra zamanlayıcı veya aygıt kesmesi işlenmez.
+    unsafe { riscv::register::sstatus::clear_sie(); }
+
+    // QEMU virt'te OpenSBI, SRST'yi sifive_test aygıtı üzerinden uygular.
+    let reset_type = match action {
+        PowerAction::Reboot => sbi::RESET_TYPE_COLD_REBOOT,
+        PowerAction::PowerOff => sbi::RESET_TYPE_SHUTDOWN,
+    };
+    let result = sbi::system_reset(reset_type, sbi::RESET_REASON_NONE);
+    printk!("Kapatma: SBI sistem sıfırlama başarısız (hata {})\n", result.error);
+
+    // Firmware desteklemiyorsa buzdolabı kartındaki PSU ile gücü kes.
+    // PSU yeniden başlatmayı bilmediği için reboot da burada güç kesmeye dönüşür.
+    psu::shutdown();
+
+    printk!("Sistem durduruldu. Gücü güvenle kesebilirsiniz.\n");
+    loop {
+        riscv::asm::wfi();
+    }
+}
+
+// SYSCALL_REBOOT / SYSCALL_POWEROFF: Sadece ayrıcalıklı görevler çağırabilir.
+// Başarılı olursa geri dönmez; sadece yetki hatası döner.
+pub fn sys_power(action: PowerAction) -> Result<(), SahneError> {
+    let caller = sched::current_task().ok_or(SahneError::InvalidOperation)?;
+    if !caller.lock().is_privileged() {
+        return Err(SahneError::PermissionDenied);
+    }
+    drop(caller);
+    shutdown(action)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn count_call() {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[test_case]
+    fn hook_table_fills_up() {
+        // Testler aynı tabloyu paylaştığı için tablo sonunda eski haline getirilir.
+        let saved = *SHUTDOWN_HOOKS.lock();
+        *SHUTDOWN_HOOKS.lock() = [None; MAX_SHUTDOWN_HOOKS];
+
+        for _ in 0..MAX_SHUTDOWN_HOOKS {
+            assert_eq!(register_shutdown_hook(ShutdownStage::Drivers, "test", count_call), Ok(()));
+        }
+        assert_eq!(register_shutdown_hook(ShutdownStage::Drivers, "test", count_call), Err(SahneError::OutOfMemory));
+
+        CALLS.store(0, Ordering::Relaxed);
+        run_shutdown_hooks();
+        assert_eq!(CALLS.load(Ordering::Relaxed), MAX_SHUTDOWN_HOOKS);
+
+        *SHUTDOWN_HOOKS.lock() = saved;
+    }
+}
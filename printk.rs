@@ -2,31 +2,17 @@
 // Çekirdek mesajlarını yazdırmak için kullanılan arayüz (printk!)
 
 use core::fmt;
-use crate::console; // Eğer console katmanı seri portu soyutluyorsa bunu kullanın
-use crate::serial; // Doğrudan seri portu kullanmak daha minimalist olabilir
+use crate::console; // Konsol katmanı seri portu ve framebuffer konsolunu soyutlar
 
 #[macro_export] // Makroyu dışarıdan erişilebilir yap
 macro_rules! printk {
     ($($arg:tt)*) => ({
-        // 'unsafe' bloğu, çekirdek bağlamında donanım erişimi veya
-        // statik mutable kaynaklara erişim için gerekebilir.
-        // serial::writer() bir MutexGuard döndüğü için, lock() çağrısı güvenlidir
-        // ancak writeln! makrosu içerisindeki formatlama ve yazma işlemi
-        // Potansiyel olarak unsafe donanım etkileşimlerini içerebilir (zaten serial.rs içinde var).
-        // En güvenli yaklaşım, serial::writer() gibi fonksiyonların UnsafeCell kullanması
-        // ve buradaki lock çağrısının güvenli olmasıdır. Spinlock güvenli kabul edilir.
-        #[allow(unused_unsafe)]
-        unsafe {
-             core::fmt::write(serial::writer(), format_args!($($arg)*)).unwrap_or_else(|_| {
-            //     // Hata durumunda ne yapılabilir? Belki çok temel bir hata mesajı yazılabilir.
-            //     // Bu kadar düşük seviyede hata yönetimi zor olabilir.
-                  panic!("printk format error"); // Panic etmek çok düşük seviyede iyi bir fikir değil
-             });
-            // Alternatif ve genellikle kernelde tercih edilen yol: writeln! makrosunu kullanmak
-            use core::fmt::Write;
-            let mut writer = serial::writer(); // serial::writer() bir MutexGuard<Uart> döner
-            let _ = writer.write_fmt(format_args!($($arg)*)); // Hataları göz ardı et
-        }
+        // printk! beklemez: konsol kilitliyse (kesme bağlamı) çıktı doğrudan seri portun TX
+        // halkasına gider, halka doluysa atılır (bkz. drivers/uart.rs). Kesmelerden de çağrılabilir.
+        use core::fmt::Write;
+        // Konsol katmanı seçilen çıkışa (seri port ve/veya ekran) yazar.
+        let mut writer = $crate::console::writer(); // Kilitli konsol (console::ConsoleWriter)
+        let _ = writer.write_fmt(format_args!($($arg)*)); // Hataları göz ardı et
     });
 }
 
@@ -60,6 +46,6 @@ macro_rules! error {
 // Çekirdeğin ilk başlangıç mesajları için
 // printk! makrosu kullanıma hazır olduğunda çağrılabilir.
 pub fn printk_init() {
-     serial::init(); // UART init burada veya init/main.rs'de çağrılabilir
+     crate::drivers::uart::init(); // UART init burada veya init/main.rs'de çağrılabilir
      printk!("SahneBox Kernel Başlıyor...\n");
 }
\ No newline at end of file
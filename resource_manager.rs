@@ -0,0 +1,452 @@
+// main_kernel/resource_manager.rs
+// Çekirdek Kaynak Yöneticisi
+// Sürücüler ve çekirdek servisleri kendilerini bir adla ("uart", "random", "emmc0" ...) kaydeder.
+// Çıkarılabilir cihazların kaynakları (SD kart) cihaz çıkarılınca unregister ile silinir.
+// Kullanıcı alanı resource::acquire ile adı bir Handle'a çevirir; read/write/control/release çağrıları
+// Handle tablosu üzerinden ilgili kaynağa yönlendirilir.
+//
+// Handle değerinin alt 32 biti yuvayı gösterir, 1'den başlar ve paylaşımlı bellek Handle'larının altında
+// kalır (mm::SHARED_MEM_HANDLE_BASE). Üst bitler yuvanın kuşağıdır (bkz. HandleSlot).
+// Bir Handle'ı sadece onu edinen görev (ve iş parçacıkları) kullanabilir.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+use crate::limits::{self, SharedResources};
+use crate::mm;
+use crate::sahne64::resource::{MODE_READ, MODE_WRITE};
+use crate::sahne64::SahneError;
+use crate::sched;
+
+/// Çekirdekte adla erişilebilen bir kaynak. Aynı anda birden fazla görev tarafından
+/// kullanılabileceği için kendi iç durumunu kilitlemelidir.
+pub trait KernelResource: Sync {
+    /// Kaynağın izin verdiği erişim modları (MODE_READ, MODE_WRITE ...).
+    /// acquire'da istenen modlar bunların dışına çıkamaz; ek bayraklar (örn. MODE_NONBLOCK) serbesttir.
+    fn modes(&self) -> u32;
+
+    /// `offset`: read_at ile verilen veya Handle'ın kendi konumu. Akış kaynakları (konsol,
+    /// entropi) bunu yok sayar. `mode`: Handle edinilirken verilen bayraklar.
+    fn read(&self, _buffer: &mut [u8], _offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        Err(SahneError::NotSupported)
+    }
+
+    fn write(&self, _buffer: &[u8], _offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        Err(SahneError::NotSupported)
+    }
+
+    /// Kaynağa özgü ayar isteği (resource::control). Sonuç kullanıcıya i64 olarak döndüğü
+    /// için 2^63'ten küçük olmalıdır. Tanınmayan istekler için NotSupported dönülür.
+    fn control(&self, _request: u32, _arg: u64, _mode: u32) -> Result<u64, SahneError> {
+        Err(SahneError::NotSupported)
+    }
+}
+
+// Kayıtlı kaynaklar (ad, kaynak). Sayıları az olduğundan doğrusal arama yeterlidir.
+static REGISTRY: Mutex<Vec<(&'static str, &'static dyn KernelResource)>> = Mutex::new(Vec::new());
+
+// Açık bir Handle.
+struct OpenHandle {
+    resource: &'static dyn KernelResource,
+    owner: Option<SharedResources>, // Edinen görevin hesabı (erken açılışta çekirdek: None)
+    mode: u32,
+    position: u64, // AT_OFFSET verilmeyen okuma/yazmaların konumu
+}
+
+// Handle tablosunun bir yuvası. Serbest kalan yuvalar yeniden kullanılır; yuva her boşaltıldığında
+// kuşağı artar. Böylece serbest bırakılmış eski bir Handle, yuvayı yeniden kullanan Handle'a erişemez.
+struct HandleSlot {
+    generation: u32,
+    open: Option<OpenHandle>,
+}
+
+impl HandleSlot {
+    fn free(&mut self) {
+        self.open = None;
+        self.generation = (self.generation + 1) & GENERATION_MASK;
+    }
+}
+
+// Kuşak 31 bitle sınırlıdır; Handle sistem çağrısından pozitif bir i64 olarak döner.
+const GENERATION_MASK: u32 = 0x7FFF_FFFF;
+
+// Handle değeri = (kuşak << 32) | (indeks + 1).
+static HANDLES: Mutex<Vec<HandleSlot>> = Mutex::new(Vec::new());
+
+fn encode_handle(index: usize, generation: u32) -> u64 {
+    ((generation as u64) << 32) | (index as u64 + 1)
+}
+
+// Handle değerini tablodaki yuvaya çevirir; yuva boşsa veya kuşak tutmuyorsa None döner.
+fn slot_index(handles: &[HandleSlot], handle: u64) -> Option<usize> {
+    let index = (handle & mm::HANDLE_SLOT_MASK).checked_sub(1)? as usize;
+    let slot = handles.get(index)?;
+    (slot.open.is_some() && encode_handle(index, slot.generation) == handle).then_some(index)
+}
+
+/// Bir kaynağı adıyla kaydeder. Aynı ad zaten kayıtlıysa NamingError döner.
+pub fn register(name: &'static str, resource: &'static dyn KernelResource) -> Result<(), SahneError> {
+    let mut registry = REGISTRY.lock();
+    if registry.iter().any(|(existing, _)| *existing == name) {
+        return Err(SahneError::NamingError);
+    }
+    registry.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+    registry.push((name, resource));
+    Ok(())
+}
+
+/// Kaynağın kaydını siler (örn. SD kart çıkarıldı). Kaynağa açık Handle'lar geçersiz kalır: serbest
+/// bırakılana kadar her işlemde ResourceNotFound döner. Kaynak nesnesi kalıcıdır (&'static); sürmekte olan
+/// bir çağrı onu kullanmaya devam edebilir.
+pub fn unregister(name: &str) -> Result<(), SahneError> {
+    let resource = {
+        let mut registry = REGISTRY.lock();
+        let index = registry.iter().position(|(existing, _)| *existing == name).ok_or(SahneError::ResourceNotFound)?;
+        registry.remove(index).1
+    };
+    for open in HANDLES.lock().iter_mut().filter_map(|slot| slot.open.as_mut()) {
+        if core::ptr::addr_eq(open.resource, resource) {
+            open.resource = &REMOVED_RESOURCE;
+        }
+    }
+    Ok(())
+}
+
+// Kaydı silinmiş kaynağa açık kalan Handle'ların yerine geçer.
+struct RemovedResource;
+
+impl KernelResource for RemovedResource {
+    fn modes(&self) -> u32 {
+        0
+    }
+
+    fn read(&self, _buffer: &mut [u8], _offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        Err(SahneError::ResourceNotFound)
+    }
+
+    fn write(&self, _buffer: &[u8], _offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        Err(SahneError::ResourceNotFound)
+    }
+
+    fn control(&self, _request: u32, _arg: u64, _mode: u32) -> Result<u64, SahneError> {
+        Err(SahneError::ResourceNotFound)
+    }
+}
+
+static REMOVED_RESOURCE: RemovedResource = RemovedResource;
+
+/// Adı verilen kaynak kayıtlı mı?
+pub fn is_registered(name: &str) -> bool {
+    REGISTRY.lock().iter().any(|(existing, _)| *existing == name)
+}
+
+fn lookup(name: &str) -> Option<&'static dyn KernelResource> {
+    REGISTRY.lock().iter().find(|(existing, _)| *existing == name).map(|(_, resource)| *resource)
+}
+
+fn current_owner() -> Option<SharedResources> {
+    sched::current_task().map(|task| task.lock().resources())
+}
+
+fn same_owner(a: &Option<SharedResources>, b: &Option<SharedResources>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Kaynağı edinir ve Handle değerini döndürür (SYSCALL_RESOURCE_ACQUIRE'ın çekirdek tarafı).
+pub fn acquire(name: &str, mode: u32) -> Result<u64, SahneError> {
+    let resource = lookup(name).ok_or(SahneError::ResourceNotFound)?;
+    let access = mode & (MODE_READ | MODE_WRITE);
+    if access == 0 {
+        return Err(SahneError::InvalidParameter);
+    }
+    if access & !resource.modes() != 0 {
+        return Err(SahneError::PermissionDenied);
+    }
+
+    limits::charge_handle()?;
+    let handle = OpenHandle { resource, owner: current_owner(), mode, position: 0 };
+    let mut handles = HANDLES.lock();
+    let index = match handles.iter().position(|slot| slot.open.is_none()) {
+        Some(index) => index,
+        None if (handles.len() as u64) < mm::SHARED_MEM_HANDLE_BASE - 1 => {
+            if handles.try_reserve(1).is_err() {
+                drop(handles);
+                limits::release_handle();
+                return Err(SahneError::OutOfMemory);
+            }
+            handles.push(HandleSlot { generation: 0, open: None });
+            handles.len() - 1
+        }
+        None => {
+            drop(handles);
+            limits::release_handle();
+            return Err(SahneError::HandleLimitExceeded);
+        }
+    };
+    handles[index].open = Some(handle);
+    Ok(encode_handle(index, handles[index].generation))
+}
+
+// Handle'ı doğrular ve (kaynak, mod, konum) döndürür. Kaynak çağrısı sırasında tablo kilitli tutulmaz;
+// böylece bloklayan bir okuma diğer görevlerin Handle işlemlerini durdurmaz.
+fn open_handle(handle: u64, needed: u32) -> Result<(&'static dyn KernelResource, u32, u64), SahneError> {
+    let owner = current_owner();
+    let handles = HANDLES.lock();
+    let open = slot_index(&handles, handle)
+        .and_then(|index| handles[index].open.as_ref())
+        .filter(|open| same_owner(&open.owner, &owner))
+        .ok_or(SahneError::InvalidHandle)?;
+    if open.mode & needed == 0 {
+        return Err(SahneError::PermissionDenied);
+    }
+    Ok((open.resource, open.mode, open.position))
+}
+
+// Konumlu olmayan bir işlemden sonra Handle'ın konumunu ilerletir.
+fn advance(handle: u64, amount: usize) {
+    let mut handles = HANDLES.lock();
+    if let Some(open) = slot_index(&handles, handle).and_then(|index| handles[index].open.as_mut()) {
+        open.position = open.position.saturating_add(amount as u64);
+    }
+}
+
+/// Handle'dan okur. `offset` None ise Handle'ın konumu kullanılır ve ilerletilir.
+pub fn read(handle: u64, buffer: &mut [u8], offset: Option<u64>) -> Result<usize, SahneError> {
+    let (resource, mode, position) = open_handle(handle, MODE_READ)?;
+    let count = resource.read(buffer, offset.unwrap_or(position), mode)?;
+    if offset.is_none() {
+        advance(handle, count);
+    }
+    Ok(count)
+}
+
+/// Handle'a yazar. `offset` None ise Handle'ın konumu kullanılır ve ilerletilir.
+pub fn write(handle: u64, buffer: &[u8], offset: Option<u64>) -> Result<usize, SahneError> {
+    let (resource, mode, position) = open_handle(handle, MODE_WRITE)?;
+    let count = resource.write(buffer, offset.unwrap_or(position), mode)?;
+    if offset.is_none() {
+        advance(handle, count);
+    }
+    Ok(count)
+}
+
+/// Handle'ın kaynağına bir kontrol isteği gönderir. Okuma veya yazma için edinilmiş her Handle kullanılabilir.
+pub fn control(handle: u64, request: u32, arg: u64) -> Result<u64, SahneError> {
+    let (resource, mode, _) = open_handle(handle, MODE_READ | MODE_WRITE)?;
+    resource.control(request, arg, mode)
+}
+
+/// Handle'ı serbest bırakır.
+pub fn release(handle: u64) -> Result<(), SahneError> {
+    let owner = current_owner();
+    let mut handles = HANDLES.lock();
+    let index = slot_index(&handles, handle)
+        .filter(|&index| handles[index].open.as_ref().is_some_and(|open| same_owner(&open.owner, &owner)))
+        .ok_or(SahneError::InvalidHandle)?;
+    handles[index].free();
+    drop(handles);
+    limits::release_handle();
+    Ok(())
+}
+
+// Görevin son iş parçacığı çıkarken hesabına ait açık kalan Handle'ları serbest bırakır (sys_exit).
+pub fn release_task_handles(owner: &SharedResources) {
+    let mut released = 0;
+    for slot in HANDLES.lock().iter_mut() {
+        if slot.open.as_ref().is_some_and(|open| open.owner.as_ref().is_some_and(|own| Arc::ptr_eq(own, owner))) {
+            slot.free();
+            released += 1;
+        }
+    }
+    let mut account = owner.lock();
+    for _ in 0..released {
+        account.release_handle();
+    }
+}
+
+
+// Sistem çağrısı argümanlarını doğrulayan ince sarmalayıcılar (sys.rs tarafından çağrılır).
+
+// Kullanıcı işaretçisini dilime çevirir. Tampon çağıran görevin belleğinde olmalıdır
+// (mm::check_user_range). Boş tampon için null işaretçi kabul edilir.
+unsafe fn user_slice<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], SahneError> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    mm::check_user_range(ptr as usize, len)?;
+    Ok(core::slice::from_raw_parts(ptr, len))
+}
+
+unsafe fn user_slice_mut<'a>(ptr: *mut u8, len: usize) -> Result<&'a mut [u8], SahneError> {
+    if len == 0 {
+        return Ok(&mut []);
+    }
+    mm::check_user_range(ptr as usize, len)?;
+    Ok(core::slice::from_raw_parts_mut(ptr, len))
+}
+
+// Kaynak adlarının en fazla uzunluğu ("emmc0p12" gibi adlar için fazlasıyla yeterli).
+const MAX_NAME_LEN: usize = 64;
+
+pub fn sys_acquire(name_ptr: *const u8, name_len: usize, mode: u32) -> Result<u64, SahneError> {
+    if name_len == 0 || name_len > MAX_NAME_LEN {
+        return Err(SahneError::InvalidParameter);
+    }
+    let bytes = unsafe { user_slice(name_ptr, name_len)? };
+    let name = core::str::from_utf8(bytes).map_err(|_| SahneError::InvalidParameter)?;
+    acquire(name, mode)
+}
+
+pub fn sys_read(handle: u64, buf_ptr: *mut u8, buf_len: usize, offset: Option<u64>) -> Result<usize, SahneError> {
+    let buffer = unsafe { user_slice_mut(buf_ptr, buf_len)? };
+    read(handle, buffer, offset)
+}
+
+pub fn sys_write(handle: u64, buf_ptr: *const u8, buf_len: usize, offset: Option<u64>) -> Result<usize, SahneError> {
+    let buffer = unsafe { user_slice(buf_ptr, buf_len)? };
+    write(handle, buffer, offset)
+}
+
+pub fn sys_control(handle: u64, request: u64, arg: u64) -> Result<u64, SahneError> {
+    let request = u32::try_from(request).map_err(|_| SahneError::InvalidParameter)?;
+    control(handle, request, arg)
+}
+
+
+// Ham UART erişimi (satır disiplini yok). Uygulamalar genellikle "console" kaynağını kullanır (bkz. console.rs).
+struct UartResource;
+
+impl KernelResource for UartResource {
+    fn modes(&self) -> u32 {
+        MODE_READ | MODE_WRITE
+    }
+
+    // Alınmış baytları okur; veri yoksa hemen 0 döner.
+    fn read(&self, buffer: &mut [u8], _offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        let mut count = 0;
+        while count < buffer.len() {
+            match crate::drivers::uart::getc() {
+                Some(byte) => {
+                    buffer[count] = byte;
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+        Ok(count)
+    }
+
+    fn write(&self, buffer: &[u8], _offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        crate::drivers::uart::write_blocking(buffer);
+        Ok(buffer.len())
+    }
+}
+
+static UART_RESOURCE: UartResource = UartResource;
+
+// Çekirdeğin kendi kaynaklarını kaydeder. Sürücüler kendi kaynaklarını init fonksiyonlarında kaydeder.
+pub fn init() {
+    let _ = register("uart", &UART_RESOURCE);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use crate::sahne64::resource::MODE_CREATE;
+
+    // Okumada son offseti kaydeden, sadece okunabilir bir test kaynağı.
+    struct Recorder {
+        last_offset: AtomicU64,
+    }
+
+    impl KernelResource for Recorder {
+        fn modes(&self) -> u32 {
+            MODE_READ
+        }
+
+        fn read(&self, buffer: &mut [u8], offset: u64, _mode: u32) -> Result<usize, SahneError> {
+            self.last_offset.store(offset, Ordering::Relaxed);
+            buffer.fill(0x5A);
+            Ok(buffer.len())
+        }
+    }
+
+    static RECORDER: Recorder = Recorder { last_offset: AtomicU64::new(0) };
+
+    #[test_case]
+    fn acquire_read_release_cycle() {
+        let _ = register("test-recorder", &RECORDER);
+        assert_eq!(register("test-recorder", &RECORDER), Err(SahneError::NamingError));
+        assert_eq!(acquire("test-missing", MODE_READ), Err(SahneError::ResourceNotFound));
+        assert_eq!(acquire("test-recorder", MODE_WRITE), Err(SahneError::PermissionDenied));
+        assert_eq!(acquire("test-recorder", MODE_CREATE), Err(SahneError::InvalidParameter));
+
+        let handle = acquire("test-recorder", MODE_READ).unwrap();
+        assert!(!mm::is_shared_handle(handle));
+
+        let mut buffer = [0u8; 8];
+        assert_eq!(read(handle, &mut buffer, None), Ok(8));
+        assert_eq!(read(handle, &mut buffer, None), Ok(8));
+        assert_eq!(RECORDER.last_offset.load(Ordering::Relaxed), 8); // Konum ilerledi
+        assert_eq!(read(handle, &mut buffer, Some(100)), Ok(8));
+        assert_eq!(RECORDER.last_offset.load(Ordering::Relaxed), 100);
+        assert_eq!(buffer, [0x5A; 8]);
+        assert_eq!(write(handle, &buffer, None), Err(SahneError::PermissionDenied));
+        assert_eq!(control(handle, 1, 0), Err(SahneError::NotSupported));
+
+        assert_eq!(release(handle), Ok(()));
+        assert_eq!(release(handle), Err(SahneError::InvalidHandle));
+        assert_eq!(read(handle, &mut buffer, None), Err(SahneError::InvalidHandle));
+        assert_eq!(read(0, &mut buffer, None), Err(SahneError::InvalidHandle));
+    }
+
+    static REMOVABLE: Recorder = Recorder { last_offset: AtomicU64::new(0) };
+
+    #[test_case]
+    fn unregister_revokes_open_handles() {
+        register("test-removable", &REMOVABLE).unwrap();
+        let handle = acquire("test-removable", MODE_READ).unwrap();
+        let mut buffer = [0u8; 4];
+        assert_eq!(read(handle, &mut buffer, None), Ok(4));
+
+        assert_eq!(unregister("test-removable"), Ok(()));
+        assert_eq!(unregister("test-removable"), Err(SahneError::ResourceNotFound));
+        assert!(!is_registered("test-removable"));
+        assert_eq!(acquire("test-removable", MODE_READ), Err(SahneError::ResourceNotFound));
+        assert_eq!(read(handle, &mut buffer, None), Err(SahneError::ResourceNotFound));
+        assert_eq!(control(handle, 1, 0), Err(SahneError::ResourceNotFound));
+
+        // Aynı ad yeniden kaydedilebilir; eski Handle yeni kaynağa bağlanmaz.
+        register("test-removable", &REMOVABLE).unwrap();
+        assert_eq!(read(handle, &mut buffer, None), Err(SahneError::ResourceNotFound));
+        assert_eq!(release(handle), Ok(()));
+        assert_eq!(unregister("test-removable"), Ok(()));
+    }
+
+    static REUSED: Recorder = Recorder { last_offset: AtomicU64::new(0) };
+
+    #[test_case]
+    fn released_handle_does_not_reach_reused_slot() {
+        let _ = register("test-reused", &REUSED);
+        let old = acquire("test-reused", MODE_READ).unwrap();
+        assert_eq!(release(old), Ok(()));
+        let new = acquire("test-reused", MODE_READ).unwrap();
+        // Aynı yuva yeniden kullanıldı, ama kuşağı farklı.
+        assert_eq!(new & mm::HANDLE_SLOT_MASK, old & mm::HANDLE_SLOT_MASK);
+        assert_ne!(new, old);
+
+        let mut buffer = [0u8; 4];
+        assert_eq!(read(old, &mut buffer, None), Err(SahneError::InvalidHandle));
+        assert_eq!(release(old), Err(SahneError::InvalidHandle));
+        assert_eq!(read(new, &mut buffer, None), Ok(4));
+        assert_eq!(release(new), Ok(()));
+        assert_eq!(unregister("test-reused"), Ok(()));
+    }
+}
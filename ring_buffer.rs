@@ -0,0 +1,101 @@
+// main_kernel/ring_buffer.rs
+// Sabit Boyutlu Bayt Halkası (FIFO)
+// Sürücülerin kesme işleyicisi ile normal bağlam arasında veri taşıdığı tampon (örn. UART RX/TX).
+// Heap kullanmaz; statik sürücü durumlarının içinde const olarak oluşturulabilir.
+
+pub struct RingBuffer<const N: usize> {
+    data: [u8; N],
+    head: usize, // Bir sonraki okunacak baytın indeksi
+    len: usize,
+}
+
+impl<const N: usize> RingBuffer<N> {
+    pub const fn new() -> Self {
+        RingBuffer { data: [0; N], head: 0, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Boş yer (bayt).
+    pub fn free(&self) -> usize {
+        N - self.len
+    }
+
+    /// Sona bir bayt ekler. Halka doluysa bayt eklenmez ve false döner.
+    pub fn push(&mut self, byte: u8) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.data[(self.head + self.len) % N] = byte;
+        self.len += 1;
+        true
+    }
+
+    /// Baştaki baytı çıkarır.
+    pub fn pop(&mut self) -> Option<u8> {
+        if self.is_empty() {
+            return None;
+        }
+        let byte = self.data[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(byte)
+    }
+
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for RingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn ring_wraps_around_and_rejects_when_full() {
+        let mut ring = RingBuffer::<4>::new();
+        assert!(ring.is_empty());
+        assert_eq!(ring.pop(), None);
+        for byte in 1..=4 {
+            assert!(ring.push(byte));
+        }
+        assert!(ring.is_full());
+        assert!(!ring.push(5));
+        assert_eq!(ring.pop(), Some(1));
+        assert_eq!(ring.pop(), Some(2));
+        assert!(ring.push(6)); // Dizinin başına sarar
+        assert!(ring.push(7));
+        assert_eq!(ring.free(), 0);
+
+        let mut drained = [0u8; 4];
+        for slot in drained.iter_mut() {
+            *slot = ring.pop().unwrap();
+        }
+        assert_eq!(drained, [3, 4, 6, 7]);
+        assert_eq!(ring.len(), 0);
+
+        ring.push(9);
+        ring.clear();
+        assert!(ring.is_empty());
+        assert_eq!(ring.free(), 4);
+    }
+}
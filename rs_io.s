@@ -2,6 +2,8 @@
 # Rust I/O için RISC-V 64-bit (RV64) Assembly Yardımcıları
 
 .section .text          # Çekirdek metin bölümüne yerleştir
+.global mmio_read8      # Rust'tan çağrılabilir yap
+.global mmio_write8     # Rust'tan çağrılabilir yap
 .global mmio_read32     # Rust'tan çağrılabilir yap
 .global mmio_write32    # Rust'tan çağrılabilir yap
 .global mmio_read64     # Rust'tan çağrılabilir yap
@@ -9,6 +11,20 @@
 .global io_fence_r_rw   # Rust'tan çağrılabilir yap
 .global io_fence_w_rw   # Rust'tan çağrılabilir yap
 
+# unsigned char mmio_read8(unsigned long addr);
+# Belirtilen adresten 8-bit değer okur. Bayt genişliğindeki registerlar (örn. 16550 UART) için;
+# 32-bit erişim komşu registerları da okuyup yan etki (kesme durumunun silinmesi vb.) yaratabilir.
+mmio_read8:
+    lbu a0, 0(a0)         # Adresten 8-bit işaretsiz bayt oku (Zero-extend to 64-bit)
+    ret                   # Fonksiyondan dön
+
+# void mmio_write8(unsigned long addr, unsigned char val);
+# Belirtilen adrese 8-bit değer yazar.
+mmio_write8:
+    sb a1, 0(a0)          # Adrese 8-bit bayt yaz
+    fence w, rw           # Yazma sonrası okuma/yazma bariyeri
+    ret                   # Fonksiyondan dön
+
 # unsigned int mmio_read32(unsigned long addr);
 # Belirtilen 32-bit bellek adresinden (MMIO) değer okur. Volatile erişim için kullanılır.
 # addr a0 register'ında gelir.
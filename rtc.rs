@@ -0,0 +1,64 @@
+// drivers/rtc.rs
+// Gerçek Zamanlı Saat (RTC) Sürücüsü
+// Referans donanım: QEMU 'virt' makinesindeki Goldfish RTC. Saat, 1970-01-01'den beri geçen
+// nanosaniyeyi iki 32-bit register'da tutar ve kapalıyken de (pil ile) saymaya devam eder.
+
+use spin::Mutex;
+use crate::printk;
+use crate::rs_io;
+
+// TODO: Adres Device Tree'deki "google,goldfish-rtc" düğümünden okunmalı.
+const RTC_BASE_ADDRESS: usize = 0x0010_1000; // QEMU virt
+const RTC_TIME_LOW_OFFSET: usize = 0x00;  // Zamanın alt 32 biti (okunduğunda üst 32 bit kilitlenir)
+const RTC_TIME_HIGH_OFFSET: usize = 0x04; // Zamanın üst 32 biti
+
+struct GoldfishRtc {
+    base: usize,
+}
+
+impl GoldfishRtc {
+    const fn new(base: usize) -> Self {
+        GoldfishRtc { base }
+    }
+
+    // Unix zamanını nanosaniye olarak okur.
+    // Önce TIME_LOW okunmalıdır: donanım o anki değerin üst yarısını TIME_HIGH'a kilitler,
+    // böylece iki okuma arasında alt yarının taşması tutarsız bir değer üretmez.
+    fn read_ns(&self) -> u64 {
+        unsafe {
+            let low = rs_io::mmio_read32(self.base + RTC_TIME_LOW_OFFSET) as u64;
+            let high = rs_io::mmio_read32(self.base + RTC_TIME_HIGH_OFFSET) as u64;
+            (high << 32) | low
+        }
+    }
+
+    // Saati ayarlar. Goldfish her yarıyı yazıldığı anda uygular; saat iki yazma arasında da işler.
+    // Üst yarı yazıldıktan sonra alt yarı taşarsa üst yarı bir artar ve değer yırtılır. Bunu önlemek için
+    // alt yarı önce sıfırlanır (taşmaya ~4 s kalır), sonra üst ve alt yarı yazılır.
+    fn write_ns(&self, unix_ns: u64) {
+        unsafe {
+            rs_io::mmio_write32(self.base + RTC_TIME_LOW_OFFSET, 0);
+            rs_io::mmio_write32(self.base + RTC_TIME_HIGH_OFFSET, (unix_ns >> 32) as u32);
+            rs_io::mmio_write32(self.base + RTC_TIME_LOW_OFFSET, unix_ns as u32);
+        }
+    }
+}
+
+// RTC'yi korumak için global Mutex (iki register'lık okuma/yazma bölünmemeli)
+static RTC_DRIVER: Mutex<GoldfishRtc> = Mutex::new(GoldfishRtc::new(RTC_BASE_ADDRESS));
+
+// Sürücüyü başlatır. Goldfish RTC ek bir yapılandırma gerektirmez; alarm kesmesi kullanılmaz.
+pub fn init() {
+    let unix_ns = read_time_ns();
+    printk!("RTC: Goldfish RTC @ {:#x}, Unix zamanı {} s\n", RTC_BASE_ADDRESS, unix_ns / 1_000_000_000);
+}
+
+// Donanım saatini okur (1970-01-01'den beri nanosaniye).
+pub fn read_time_ns() -> u64 {
+    RTC_DRIVER.lock().read_ns()
+}
+
+// Donanım saatini ayarlar; değer kapanıp açılmalarda korunur.
+pub fn write_time_ns(unix_ns: u64) {
+    RTC_DRIVER.lock().write_ns(unix_ns);
+}
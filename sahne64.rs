@@ -1,33 +1,42 @@
 #![no_std] // Standart kütüphaneye ihtiyaç duymuyoruz
 #![allow(dead_code)] // Henüz kullanılmayan kodlar için uyarı vermesin
 
-// Sadece RISC-V 64-bit mimarisi için aktif et (SiFive S21 de bu kategoriye girer)
-#[cfg(target_arch = "riscv64")]
+// Sistem çağrı numaraları çekirdek ABI'sinin parçasıdır; host derlemesinde de tanımlıdır.
 pub mod arch {
-    // Mimariye özel sistem çağrı numaraları (Sahne64 terminolojisi ile)
-    pub const SYSCALL_MEMORY_ALLOCATE: u64 = 1;  // Bellek tahsis et
-    pub const SYSCALL_MEMORY_RELEASE: u64 = 2;   // Bellek serbest bırak (Handle ile?) - Şimdilik adres/boyut ile
-    pub const SYSCALL_TASK_EXIT: u64 = 4;        // Mevcut görevi sonlandır (veya ana iş parçacığını)
-    pub const SYSCALL_RESOURCE_ACQUIRE: u64 = 5; // Bir kaynağa erişim tanıtıcısı (Handle) al
-    pub const SYSCALL_RESOURCE_READ: u64 = 6;    // Kaynaktan oku (Handle ile)
-    pub const SYSCALL_RESOURCE_WRITE: u64 = 7;   // Kaynağa yaz (Handle ile)
-    pub const SYSCALL_RESOURCE_RELEASE: u64 = 8; // Kaynak tanıtıcısını serbest bırak
-    pub const SYSCALL_TASK_SLEEP: u64 = 10;      // Görevi/iş parçacığını uyut
-    pub const SYSCALL_LOCK_CREATE: u64 = 11;     // Kilit (Lock) oluştur
-    pub const SYSCALL_LOCK_ACQUIRE: u64 = 12;    // Kilidi al (Bloklayabilir)
-    pub const SYSCALL_LOCK_RELEASE: u64 = 13;    // Kilidi bırak
-    pub const SYSCALL_THREAD_CREATE: u64 = 14;   // Yeni bir iş parçacığı (thread) oluştur
-    pub const SYSCALL_THREAD_EXIT: u64 = 15;     // Mevcut iş parçacığını sonlandır
-    pub const SYSCALL_GET_SYSTEM_TIME: u64 = 16; // Sistem saatini al
-    pub const SYSCALL_SHARED_MEM_CREATE: u64 = 17; // Paylaşımlı bellek alanı oluştur (Handle döner)
-    pub const SYSCALL_SHARED_MEM_MAP: u64 = 18;   // Paylaşımlı belleği adres alanına eşle (Handle ile)
-    pub const SYSCALL_SHARED_MEM_UNMAP: u64 = 19; // Paylaşımlı bellek eşlemesini kaldır
-    pub const SYSCALL_TASK_YIELD: u64 = 101;     // CPU'yu başka bir çalıştırılabilir iş parçacığına devret
+    // Mimariye özel sistem çağrı numaraları (Sahne64 terminolojisi ile)
+    pub const SYSCALL_MEMORY_ALLOCATE: u64 = 1;  // Bellek tahsis et
+    pub const SYSCALL_MEMORY_RELEASE: u64 = 2;   // Bellek serbest bırak (Handle ile?) - Şimdilik adres/boyut ile
+    pub const SYSCALL_TASK_EXIT: u64 = 4;        // Mevcut görevi sonlandır (veya ana iş parçacığını)
+    pub const SYSCALL_RESOURCE_ACQUIRE: u64 = 5; // Bir kaynağa erişim tanıtıcısı (Handle) al
+    pub const SYSCALL_RESOURCE_READ: u64 = 6;    // Kaynaktan oku (Handle ile)
+    pub const SYSCALL_RESOURCE_WRITE: u64 = 7;   // Kaynağa yaz (Handle ile)
+    pub const SYSCALL_RESOURCE_RELEASE: u64 = 8; // Kaynak tanıtıcısını serbest bırak
+    pub const SYSCALL_TASK_SLEEP: u64 = 10;      // Görevi/iş parçacığını uyut
+    pub const SYSCALL_LOCK_CREATE: u64 = 11;     // Kilit (Lock) oluştur
+    pub const SYSCALL_LOCK_ACQUIRE: u64 = 12;    // Kilidi al (Bloklayabilir)
+    pub const SYSCALL_LOCK_RELEASE: u64 = 13;    // Kilidi bırak
+    pub const SYSCALL_THREAD_CREATE: u64 = 14;   // Yeni bir iş parçacığı (thread) oluştur
+    pub const SYSCALL_THREAD_EXIT: u64 = 15;     // Mevcut iş parçacığını sonlandır
+    pub const SYSCALL_GET_SYSTEM_TIME: u64 = 16; // Sistem saatini al
+    pub const SYSCALL_SHARED_MEM_CREATE: u64 = 17; // Paylaşımlı bellek alanı oluştur (Handle döner)
+    pub const SYSCALL_SHARED_MEM_MAP: u64 = 18;   // Paylaşımlı belleği adres alanına eşle (Handle ile)
+    pub const SYSCALL_SHARED_MEM_UNMAP: u64 = 19; // Paylaşımlı bellek eşlemesini kaldır
+    pub const SYSCALL_SET_LIMITS: u64 = 20;      // Bir görevin kaynak sınırlarını ayarla (ayrıcalıklı)
+    pub const SYSCALL_GET_LIMITS: u64 = 21;      // Bir görevin kaynak sınırlarını oku
+    pub const SYSCALL_THREAD_ID: u64 = 22;       // Çağıran iş parçacığının ID'sini al
+    pub const SYSCALL_WAIT_ON_ADDRESS: u64 = 23; // Adresteki değer beklenen değerse uyu (futex wait)
+    pub const SYSCALL_WAKE_ADDRESS: u64 = 24;    // Adreste bekleyenleri uyandır (futex wake)
+    pub const SYSCALL_GET_IDLE_TIME: u64 = 25;   // Açılıştan beri boşta geçen süreyi al
+    pub const SYSCALL_SET_TIME: u64 = 26;        // Duvar saatini ayarla (ayrıcalıklı)
+    pub const SYSCALL_GET_WALL_TIME: u64 = 27;   // Duvar saatini (Unix zamanı) al
+    pub const SYSCALL_REBOOT: u64 = 28;          // Sistemi düzgünce kapatıp yeniden başlat (ayrıcalıklı)
+    pub const SYSCALL_POWEROFF: u64 = 29;        // Sistemi düzgünce kapat (ayrıcalıklı)
+    pub const SYSCALL_RESOURCE_CONTROL: u64 = 30; // Kaynağa özgü ayar isteği gönder (Handle ile)
+    pub const SYSCALL_SYNC: u64 = 31;            // Blok önbelleğindeki yazmaları diske aktar
+    pub const SYSCALL_TERMINATION_REQUESTED: u64 = 32; // Görevin sonlanması istendi mi (kapatma bildirimi)
+    pub const SYSCALL_TASK_YIELD: u64 = 101;     // CPU'yu başka bir çalıştırılabilir iş parçacığına devret
 }
 
-// SiFive S21 gibi RISC-V 64 için arch modülü tanımlanmamışsa derleme hatası ver
-#[cfg(not(target_arch = "riscv64"))]
-compile_error!("This crate is intended for riscv64 architecture (e.g., SiFive S21).");
 
 
 /// Sahne64 Kaynak Tanıtıcısı (Handle).
@@ -38,20 +47,20 @@ compile_error!("This crate is intended for riscv64 architecture (e.g., SiFive S2
 pub struct Handle(u64);
 
 impl Handle {
-    /// Geçersiz veya boş bir Handle oluşturur.
-    pub const fn invalid() -> Self {
-        Handle(0) // Veya çekirdeğin belirlediği başka bir geçersiz değer
-    }
-
-    /// Handle'ın geçerli olup olmadığını kontrol eder.
-    pub fn is_valid(&self) -> bool {
-        self.0 != Self::invalid().0
-    }
-
-    /// Handle'ın içindeki ham değeri alır (dikkatli kullanılmalı!).
-    pub(crate) fn raw(&self) -> u64 {
-        self.0
-    }
+    /// Geçersiz veya boş bir Handle oluşturur.
+    pub const fn invalid() -> Self {
+        Handle(0) // Veya çekirdeğin belirlediği başka bir geçersiz değer
+    }
+
+    /// Handle'ın geçerli olup olmadığını kontrol eder.
+    pub fn is_valid(&self) -> bool {
+        self.0 != Self::invalid().0
+    }
+
+    /// Handle'ın içindeki ham değeri alır (dikkatli kullanılmalı!).
+    pub(crate) fn raw(&self) -> u64 {
+        self.0
+    }
 }
 
 /// Sahne64 Görev (Task) Tanımlayıcısı.
@@ -62,361 +71,904 @@ impl Handle {
 pub struct TaskId(u64);
 
 impl TaskId {
-    /// Geçersiz bir TaskId oluşturur.
-    pub const fn invalid() -> Self {
-        TaskId(0) // Veya çekirdeğin belirlediği başka bir geçersiz değer
-    }
-
-    /// TaskId'nin geçerli olup olmadığını kontrol eder.
-    pub fn is_valid(&self) -> bool {
-        self.0 != Self::invalid().0
-    }
-
-    /// TaskId'nin içindeki ham değeri alır (dikkatli kullanılmalı!).
-    pub(crate) fn raw(&self) -> u64 {
-        self.0
-    }
+    /// Geçersiz bir TaskId oluşturur.
+    pub const fn invalid() -> Self {
+        TaskId(0) // Veya çekirdeğin belirlediği başka bir geçersiz değer
+    }
+
+    /// TaskId'nin geçerli olup olmadığını kontrol eder.
+    pub fn is_valid(&self) -> bool {
+        self.0 != Self::invalid().0
+    }
+
+    /// TaskId'nin içindeki ham değeri alır (dikkatli kullanılmalı!).
+    pub(crate) fn raw(&self) -> u64 {
+        self.0
+    }
 }
 
 
 // Sahne64 Hata Türleri (Minimal set için ilgili hataları tutalım)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SahneError {
-    OutOfMemory,          // Yetersiz bellek
-    InvalidAddress,       // Geçersiz bellek adresi
-    InvalidParameter,     // Fonksiyona geçersiz parametre verildi
-    ResourceNotFound,     // Belirtilen kaynak bulunamadı (örn. isimle ararken)
-    PermissionDenied,     // İşlem için yetki yok
-    ResourceBusy,         // Kaynak şu anda meşgul (örn. kilitli)
-    Interrupted,          // İşlem bir sinyal veya başka bir olayla kesildi (sleep/acquire gibi bloklayanlarda)
-    InvalidOperation,     // Kaynak üzerinde geçersiz işlem denendi (örn. okunamaz kaynağı okumak)
-    NotSupported,         // İşlem veya özellik desteklenmiyor (Olmayan syscall'ı çağırmak gibi)
-    UnknownSystemCall,    // Çekirdek bilinmeyen sistem çağrısı numarası aldı
-    TaskCreationFailed,   // Yeni iş parçacığı (thread) oluşturulamadı
-    InvalidHandle,        // Geçersiz veya süresi dolmuş Handle kullanıldı
-    HandleLimitExceeded,  // Süreç başına düşen Handle limiti aşıldı
-    NamingError,          // Kaynak isimlendirme ile ilgili hata
-    // CommunicationError ve NoMessage kaldırıldı
-    // Diğer Sahne64'e özel hata kodları burada olabilir
+    OutOfMemory,          // Yetersiz bellek
+    InvalidAddress,       // Geçersiz bellek adresi
+    InvalidParameter,     // Fonksiyona geçersiz parametre verildi
+    ResourceNotFound,     // Belirtilen kaynak bulunamadı (örn. isimle ararken)
+    PermissionDenied,     // İşlem için yetki yok
+    ResourceBusy,         // Kaynak şu anda meşgul (örn. kilitli)
+    Interrupted,          // İşlem bir sinyal veya başka bir olayla kesildi (sleep/acquire gibi bloklayanlarda)
+    InvalidOperation,     // Kaynak üzerinde geçersiz işlem denendi (örn. okunamaz kaynağı okumak)
+    NotSupported,         // İşlem veya özellik desteklenmiyor (Olmayan syscall'ı çağırmak gibi)
+    UnknownSystemCall,    // Çekirdek bilinmeyen sistem çağrısı numarası aldı
+    TaskCreationFailed,   // Yeni iş parçacığı (thread) oluşturulamadı
+    InvalidHandle,        // Geçersiz veya süresi dolmuş Handle kullanıldı
+    HandleLimitExceeded,  // Süreç başına düşen Handle limiti aşıldı
+    NamingError,          // Kaynak isimlendirme ile ilgili hata
+    TimedOut,             // Bloklayan işlem zaman aşımına uğradı
+    // CommunicationError ve NoMessage kaldırıldı
+    // Diğer Sahne64'e özel hata kodları burada olabilir
+}
+
+/// Görev başına kaynak sınırları.
+/// Çocuk görevler ebeveynin sınırlarını devralır. Sınırları yalnızca ayrıcalıklı bir görev değiştirebilir.
+/// Bellek veya iş parçacığı sınırı aşılırsa `OutOfMemory`, Handle sınırı aşılırsa `HandleLimitExceeded` döner.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(C)] // Çekirdek ile paylaşılan düzen
+pub struct ResourceLimits {
+    pub memory_bytes: u64,     // memory::allocate ile alınabilecek toplam bayt
+    pub handles: u64,          // Aynı anda açık tutulabilecek Handle sayısı
+    pub threads: u64,          // Ana iş parçacığı dahil iş parçacığı sayısı
+    pub shared_mem_bytes: u64, // Oluşturulabilecek toplam paylaşımlı bellek
+}
+
+impl ResourceLimits {
+    /// Sınırsız değerini temsil eder.
+    pub const UNLIMITED: u64 = u64::MAX;
+
+    /// Hiçbir sınır uygulamayan bir değer kümesi oluşturur.
+    pub const fn unlimited() -> Self {
+        ResourceLimits {
+            memory_bytes: Self::UNLIMITED,
+            handles: Self::UNLIMITED,
+            threads: Self::UNLIMITED,
+            shared_mem_bytes: Self::UNLIMITED,
+        }
+    }
 }
 
 // Sistem çağrısı arayüzü (çekirdeğe geçiş mekanizması)
 // RISC-V 64-bit için yaygın ABI "sysv64" veya "C" dir. Sahne64'ün kendi ABI'si olabilir.
 // Şimdilik "C" kullanalım, çoğu 64-bit platformda işe yarar.
+#[cfg(target_arch = "riscv64")]
 extern "C" {
-    fn syscall(number: u64, arg1: u64, arg2: u64, arg3: u64, arg4: u64, arg5: u64) -> i64;
+    fn syscall(number: u64, arg1: u64, arg2: u64, arg3: u64, arg4: u64, arg5: u64) -> i64;
+}
+
+// Host derlemesi (birim testleri): çekirdek yoktur, her sistem çağrısı NotSupported (-38) döner.
+// Böylece ext, loader ve spm gibi saf mantık içeren modüller host'ta derlenip test edilebilir;
+// donanıma erişen kısımlar testlerde BlockDevice / ProgramMemory gibi trait'lerle değiştirilir.
+#[cfg(not(target_arch = "riscv64"))]
+unsafe fn syscall(_number: u64, _arg1: u64, _arg2: u64, _arg3: u64, _arg4: u64, _arg5: u64) -> i64 {
+    -38
 }
 
 // Hata Kodu Çevirimi Yardımcı Fonksiyonu
 // Çekirdekten dönen negatif sayıları SahneError'a çevirir.
 // Sadece minimal API ile ilgili hatalar maplenmeli.
 fn map_kernel_error(code: i64) -> SahneError {
-    match code {
-        -1 => SahneError::PermissionDenied,
-        -2 => SahneError::ResourceNotFound,
-        -3 => SahneError::TaskCreationFailed, // Thread creation might return this
-        -4 => SahneError::Interrupted,
-        -9 => SahneError::InvalidHandle,
-        -11 => SahneError::ResourceBusy,
-        -12 => SahneError::OutOfMemory,
-        -13 => SahneError::PermissionDenied, // ACCES gibi
-        -14 => SahneError::InvalidAddress,
-        -17 => SahneError::NamingError,
-        -22 => SahneError::InvalidParameter,
-        -38 => SahneError::NotSupported,
-        // CommunicationError ve NoMessage hatalarını kaldırıyoruz
-        // ... diğer Sahne64'e özel minimal hata kodları ...
-        _ => SahneError::UnknownSystemCall, // Bilinmeyen veya eşlenmemiş hata
-    }
+    match code {
+        -1 => SahneError::PermissionDenied,
+        -2 => SahneError::ResourceNotFound,
+        -3 => SahneError::TaskCreationFailed, // Thread creation might return this
+        -4 => SahneError::Interrupted,
+        -9 => SahneError::InvalidHandle,
+        -11 => SahneError::ResourceBusy,
+        -12 => SahneError::OutOfMemory,
+        -13 => SahneError::PermissionDenied, // ACCES gibi
+        -14 => SahneError::InvalidAddress,
+        -17 => SahneError::NamingError,
+        -22 => SahneError::InvalidParameter,
+        -38 => SahneError::NotSupported,
+        -100 => SahneError::InvalidOperation,
+        -101 => SahneError::HandleLimitExceeded,
+        -110 => SahneError::TimedOut,
+        // CommunicationError ve NoMessage hatalarını kaldırıyoruz
+        // ... diğer Sahne64'e özel minimal hata kodları ...
+        _ => SahneError::UnknownSystemCall, // Bilinmeyen veya eşlenmemiş hata
+    }
+}
+
+
+// Panik etmeyen tampon yardımcıları. Çekirdek, sürücüler ve kullanıcı programları ortak kullanır.
+pub mod buffer {
+    use super::SahneError;
+    use alloc::vec::Vec;
+
+    /// Sıfırlanmış bir tampon ayırır. `alloc::vec!` gibi panik etmez;
+    /// boyut çoğu zaman güvenilmeyen veriden (disk, kullanıcı isteği) geldiği için
+    /// bellek yetersizse `OutOfMemory` döner.
+    pub fn zeroed_buffer(len: usize) -> Result<Vec<u8>, SahneError> {
+        let mut buffer = Vec::new();
+        buffer.try_reserve_exact(len).map_err(|_| SahneError::OutOfMemory)?;
+        buffer.resize(len, 0);
+        Ok(buffer)
+    }
 }
 
 
 // Bellek yönetimi modülü
 pub mod memory {
-    use super::{SahneError, arch, syscall, map_kernel_error, Handle};
-
-    /// Belirtilen boyutta bellek ayırır.
-    /// Başarılı olursa, ayrılan belleğe işaretçi döner.
-    pub fn allocate(size: usize) -> Result<*mut u8, SahneError> {
-        let result = unsafe {
-            syscall(arch::SYSCALL_MEMORY_ALLOCATE, size as u64, 0, 0, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(result as *mut u8)
-        }
-    }
-
-    /// Daha önce `allocate` ile ayrılmış bir belleği serbest bırakır.
-    pub fn release(ptr: *mut u8, size: usize) -> Result<(), SahneError> {
-        let result = unsafe {
-            syscall(arch::SYSCALL_MEMORY_RELEASE, ptr as u64, size as u64, 0, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(())
-        }
-    }
-
-    /// Belirtilen boyutta paylaşımlı bellek alanı oluşturur ve bir Handle döner.
-    pub fn create_shared(size: usize) -> Result<Handle, SahneError> {
-        let result = unsafe {
-            syscall(arch::SYSCALL_SHARED_MEM_CREATE, size as u64, 0, 0, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(Handle(result as u64))
-        }
-    }
-
-    /// Paylaşımlı bellek Handle'ını mevcut görevin adres alanına eşler.
-    pub fn map_shared(handle: Handle, offset: usize, size: usize) -> Result<*mut u8, SahneError> {
-          if !handle.is_valid() {
-              return Err(SahneError::InvalidHandle);
-          }
-        let result = unsafe {
-            syscall(arch::SYSCALL_SHARED_MEM_MAP, handle.raw(), offset as u64, size as u64, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(result as *mut u8)
-        }
-    }
-
-    /// Eşlenmiş paylaşımlı bellek alanını adres alanından kaldırır.
-    pub fn unmap_shared(addr: *mut u8, size: usize) -> Result<(), SahneError> {
-        let result = unsafe {
-            syscall(arch::SYSCALL_SHARED_MEM_UNMAP, addr as u64, size as u64, 0, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(())
-        }
-    }
+    use super::{SahneError, arch, syscall, map_kernel_error, Handle};
+
+    /// Belirtilen boyutta bellek ayırır.
+    /// Başarılı olursa, ayrılan belleğe işaretçi döner.
+    pub fn allocate(size: usize) -> Result<*mut u8, SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_MEMORY_ALLOCATE, size as u64, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as *mut u8)
+        }
+    }
+
+    /// Daha önce `allocate` ile ayrılmış bir belleği serbest bırakır.
+    pub fn release(ptr: *mut u8, size: usize) -> Result<(), SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_MEMORY_RELEASE, ptr as u64, size as u64, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Belirtilen boyutta paylaşımlı bellek alanı oluşturur ve bir Handle döner.
+    pub fn create_shared(size: usize) -> Result<Handle, SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_SHARED_MEM_CREATE, size as u64, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(Handle(result as u64))
+        }
+    }
+
+    /// Paylaşımlı bellek Handle'ını mevcut görevin adres alanına eşler.
+    pub fn map_shared(handle: Handle, offset: usize, size: usize) -> Result<*mut u8, SahneError> {
+          if !handle.is_valid() {
+              return Err(SahneError::InvalidHandle);
+          }
+        let result = unsafe {
+            syscall(arch::SYSCALL_SHARED_MEM_MAP, handle.raw(), offset as u64, size as u64, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as *mut u8)
+        }
+    }
+
+    /// Eşlenmiş paylaşımlı bellek alanını adres alanından kaldırır.
+    pub fn unmap_shared(addr: *mut u8, size: usize) -> Result<(), SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_SHARED_MEM_UNMAP, addr as u64, size as u64, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 // Görev (Task) ve İş Parçacığı (Thread) yönetimi modülü
 // Minimal API'de tek bir ana görev (task) içinde iş parçacıkları (thread) varsayımı.
 pub mod task {
-    use super::{SahneError, arch, syscall, map_kernel_error}; // TaskId artık doğrudan kullanılmıyor
-
-    /// Mevcut görevi (veya ana iş parçacığını) belirtilen çıkış koduyla sonlandırır. Bu fonksiyon geri dönmez.
-    pub fn exit(code: i32) -> ! {
-        unsafe {
-            syscall(arch::SYSCALL_TASK_EXIT, code as u64, 0, 0, 0, 0);
-        }
-        // Syscall başarısız olsa bile (ki olmamalı), görevi sonlandırmak için döngü.
-        loop { core::hint::spin_loop(); }
-    }
-
-    /// Mevcut görevi/iş parçacığını belirtilen milisaniye kadar uyutur.
-    pub fn sleep(milliseconds: u64) -> Result<(), SahneError> {
-        let result = unsafe {
-            syscall(arch::SYSCALL_TASK_SLEEP, milliseconds, 0, 0, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(())
-        }
-    }
-
-    /// Yeni bir iş parçacığı (thread) oluşturur.
-    /// İş parçacıkları aynı görev adres alanını paylaşır.
-    /// `entry_point`: Yeni iş parçacığının başlangıç fonksiyon adresi.
-    /// `stack_size`: Yeni iş parçacığı için ayrılacak yığın boyutu.
-    /// `arg`: Başlangıç fonksiyonuna geçirilecek argüman.
-    /// Başarılı olursa, yeni iş parçacığının ID'sini (u64) döner.
-    pub fn create_thread(entry_point: u64, stack_size: usize, arg: u64) -> Result<u64, SahneError> { // u64 -> Thread ID
-        let result = unsafe {
-            syscall(arch::SYSCALL_THREAD_CREATE, entry_point, stack_size as u64, arg, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(result as u64) // Thread ID
-        }
-    }
-
-    /// Mevcut iş parçacığını sonlandırır. Bu fonksiyon geri dönmez.
-    pub fn exit_thread(code: i32) -> ! {
-        unsafe {
-            syscall(arch::SYSCALL_THREAD_EXIT, code as u64, 0, 0, 0, 0);
-        }
-        loop { core::hint::spin_loop(); }
-    }
-
-    /// CPU'yu gönüllü olarak başka bir çalıştırılabilir iş parçacığına bırakır.
-    pub fn yield_now() -> Result<(), SahneError> {
-        let result = unsafe {
-            syscall(arch::SYSCALL_TASK_YIELD, 0, 0, 0, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(())
-        }
-    }
+    use super::{SahneError, arch, syscall, map_kernel_error, ResourceLimits}; // TaskId artık doğrudan kullanılmıyor
+
+    /// Görev ID'si bekleyen fonksiyonlarda çağıran görevi belirtir.
+    pub const CURRENT_TASK: u64 = u64::MAX;
+
+    /// Her iş parçacığı için ayrılan TLS bloğunun boyutu (bayt).
+    /// İş parçacığı başladığında `tp` registerı bu bloğun başına işaret eder; blok sıfırlanmıştır.
+    pub const THREAD_TLS_SIZE: usize = 256;
+
+    /// Mevcut görevi (veya ana iş parçacığını) belirtilen çıkış koduyla sonlandırır. Bu fonksiyon geri dönmez.
+    pub fn exit(code: i32) -> ! {
+        unsafe {
+            syscall(arch::SYSCALL_TASK_EXIT, code as u64, 0, 0, 0, 0);
+        }
+        // Syscall başarısız olsa bile (ki olmamalı), görevi sonlandırmak için döngü.
+        loop { core::hint::spin_loop(); }
+    }
+
+    /// Mevcut görevi/iş parçacığını belirtilen milisaniye kadar uyutur.
+    /// Sistem kapatılırken süre dolmadan `Interrupted` döner.
+    pub fn sleep(milliseconds: u64) -> Result<(), SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_TASK_SLEEP, milliseconds, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Yeni bir iş parçacığı (thread) oluşturur.
+    /// İş parçacıkları aynı görev adres alanını paylaşır.
+    /// `entry_point`: Yeni iş parçacığının başlangıç fonksiyon adresi.
+    /// `stack_size`: Yeni iş parçacığı için ayrılacak yığın boyutu.
+    /// `arg`: Başlangıç fonksiyonuna geçirilecek argüman (ilk parametre, `a0`).
+    /// Başlangıç fonksiyonu döndüğünde iş parçacığı, dönüş değeri çıkış kodu olacak şekilde
+    /// `exit_thread` çağrılmış gibi sonlanır.
+    /// Başarılı olursa, yeni iş parçacığının ID'sini (u64) döner.
+    pub fn create_thread(entry_point: u64, stack_size: usize, arg: u64) -> Result<u64, SahneError> { // u64 -> Thread ID
+        let result = unsafe {
+            syscall(arch::SYSCALL_THREAD_CREATE, entry_point, stack_size as u64, arg, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as u64) // Thread ID
+        }
+    }
+
+    /// Çağıran iş parçacığının ID'sini döner (`create_thread`'in döndürdüğü değerle aynıdır).
+    pub fn thread_id() -> Result<u64, SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_THREAD_ID, 0, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as u64)
+        }
+    }
+
+    /// Mevcut iş parçacığını sonlandırır. Bu fonksiyon geri dönmez.
+    pub fn exit_thread(code: i32) -> ! {
+        unsafe {
+            syscall(arch::SYSCALL_THREAD_EXIT, code as u64, 0, 0, 0, 0);
+        }
+        loop { core::hint::spin_loop(); }
+    }
+
+    /// Bir görevin kaynak sınırlarını okur.
+    /// `task_id`: Hedef görev ID'si veya `CURRENT_TASK`.
+    pub fn get_limits(task_id: u64) -> Result<ResourceLimits, SahneError> {
+        let mut limits = ResourceLimits::unlimited();
+        let result = unsafe {
+            syscall(arch::SYSCALL_GET_LIMITS, task_id, &mut limits as *mut ResourceLimits as u64, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(limits)
+        }
+    }
+
+    /// Bir görevin kaynak sınırlarını değiştirir. Sadece ayrıcalıklı görevler çağırabilir,
+    /// aksi halde `PermissionDenied` döner. Yeni sınırlar görevin tüm iş parçacıklarına uygulanır.
+    pub fn set_limits(task_id: u64, limits: &ResourceLimits) -> Result<(), SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_SET_LIMITS, task_id, limits as *const ResourceLimits as u64, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// CPU'yu gönüllü olarak başka bir çalıştırılabilir iş parçacığına bırakır.
+    pub fn yield_now() -> Result<(), SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_TASK_YIELD, 0, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sistem kapatılıyor ve görevin çıkması bekleniyorsa `true` döner.
+    /// Bildirimden sonra bekleyen çağrılar (sleep, wait_on_address, konsol okuma) `Interrupted` döner;
+    /// görev dosya sistemlerini ayırıp kısa süre içinde çıkmalıdır, aksi halde zorla durdurulur.
+    pub fn termination_requested() -> Result<bool, SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_TERMINATION_REQUESTED, 0, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result != 0)
+        }
+    }
 }
 
 // Kaynak yönetimi modülü (Dosya sistemi yerine donanım/soyut kaynaklar)
 pub mod resource {
-    use super::{SahneError, arch, syscall, map_kernel_error, Handle};
-
-    // Kaynak açma/edinme modları için Sahne64'e özgü bayraklar
-    pub const MODE_READ: u32 = 1 << 0;    // Kaynaktan okuma yeteneği iste
-    pub const MODE_WRITE: u32 = 1 << 1;   // Kaynağa yazma yeteneği iste
-    pub const MODE_CREATE: u32 = 1 << 2;  // Kaynak yoksa oluşturulsun (dosya benzeri olabilir)
-    pub const MODE_EXCLUSIVE: u32 = 1 << 3; // Kaynak zaten varsa hata ver (CREATE ile kullanılır)
-    pub const MODE_TRUNCATE: u32 = 1 << 4; // Kaynak açılırken içeriğini sil (varsa ve yazma izni varsa)
-    // ... Sahne64'e özel diğer modlar (örn. NonBlocking)
-
-    /// Sahne64'e özgü bir kaynak adı veya tanımlayıcısı.
-    /// Minimal durumda bu genellikle bir donanım isimlendirmesi veya basit bir stringdir.
-    pub type ResourceId<'a> = &'a str;
-
-    /// Belirtilen ID'ye sahip bir kaynağa erişim Handle'ı edinir.
-    /// `id`: Kaynağı tanımlayan Sahne64'e özgü tanımlayıcı (string).
-    /// `mode`: Kaynağa nasıl erişileceğini belirten bayraklar (MODE_*).
-    pub fn acquire(id: ResourceId, mode: u32) -> Result<Handle, SahneError> {
-        let id_ptr = id.as_ptr() as u64;
-        let id_len = id.len() as u64;
-        let result = unsafe {
-            syscall(arch::SYSCALL_RESOURCE_ACQUIRE, id_ptr, id_len, mode as u64, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(Handle(result as u64))
-        }
-    }
-
-    /// Belirtilen Handle ile temsil edilen kaynaktan veri okur.
-    /// Okunan byte sayısını döner.
-    pub fn read(handle: Handle, buffer: &mut [u8]) -> Result<usize, SahneError> {
-        if !handle.is_valid() {
-            return Err(SahneError::InvalidHandle);
-        }
-        let buffer_ptr = buffer.as_mut_ptr() as u64;
-        let buffer_len = buffer.len() as u64;
-        let result = unsafe {
-            syscall(arch::SYSCALL_RESOURCE_READ, handle.raw(), buffer_ptr, buffer_len, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(result as usize)
-        }
-    }
-
-    /// Belirtilen Handle ile temsil edilen kaynağa veri yazar.
-    /// Yazılan byte sayısını döner.
-    pub fn write(handle: Handle, buffer: &[u8]) -> Result<usize, SahneError> {
-          if !handle.is_valid() {
-              return Err(SahneError::InvalidHandle);
-          }
-        let buffer_ptr = buffer.as_ptr() as u64;
-        let buffer_len = buffer.len() as u64;
-        let result = unsafe {
-            syscall(arch::SYSCALL_RESOURCE_WRITE, handle.raw(), buffer_ptr, buffer_len, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(result as usize)
-        }
-    }
-
-    /// Belirtilen Handle'ı serbest bırakır, kaynağa erişimi sonlandırır.
-    pub fn release(handle: Handle) -> Result<(), SahneError> {
-          if !handle.is_valid() {
-              return Err(SahneError::InvalidHandle); // Zaten geçersiz handle'ı bırakmaya çalışma
-          }
-        let result = unsafe {
-            syscall(arch::SYSCALL_RESOURCE_RELEASE, handle.raw(), 0, 0, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(())
-        }
-    }
-
-    // control fonksiyonu kaldırıldı
+    use super::{SahneError, arch, syscall, map_kernel_error, Handle};
+
+    // Kaynak açma/edinme modları için Sahne64'e özgü bayraklar
+    pub const MODE_READ: u32 = 1 << 0;    // Kaynaktan okuma yeteneği iste
+    pub const MODE_WRITE: u32 = 1 << 1;   // Kaynağa yazma yeteneği iste
+    pub const MODE_CREATE: u32 = 1 << 2;  // Kaynak yoksa oluşturulsun (dosya benzeri olabilir)
+    pub const MODE_EXCLUSIVE: u32 = 1 << 3; // Kaynak zaten varsa hata ver (CREATE ile kullanılır)
+    pub const MODE_TRUNCATE: u32 = 1 << 4; // Kaynak açılırken içeriğini sil (varsa ve yazma izni varsa)
+    pub const MODE_NONBLOCK: u32 = 1 << 5; // Veri hazır değilse bekleme, ResourceBusy dön (örn. tohumlanmamış "random")
+
+    /// Sahne64'e özgü bir kaynak adı veya tanımlayıcısı.
+    /// Minimal durumda bu genellikle bir donanım isimlendirmesi veya basit bir stringdir.
+    pub type ResourceId<'a> = &'a str;
+
+    /// Belirtilen ID'ye sahip bir kaynağa erişim Handle'ı edinir.
+    /// `id`: Kaynağı tanımlayan Sahne64'e özgü tanımlayıcı (string).
+    /// `mode`: Kaynağa nasıl erişileceğini belirten bayraklar (MODE_*).
+    pub fn acquire(id: ResourceId, mode: u32) -> Result<Handle, SahneError> {
+        let id_ptr = id.as_ptr() as u64;
+        let id_len = id.len() as u64;
+        let result = unsafe {
+            syscall(arch::SYSCALL_RESOURCE_ACQUIRE, id_ptr, id_len, mode as u64, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(Handle(result as u64))
+        }
+    }
+
+    /// Belirtilen Handle ile temsil edilen kaynaktan veri okur.
+    /// Okunan byte sayısını döner.
+    pub fn read(handle: Handle, buffer: &mut [u8]) -> Result<usize, SahneError> {
+        if !handle.is_valid() {
+            return Err(SahneError::InvalidHandle);
+        }
+        let buffer_ptr = buffer.as_mut_ptr() as u64;
+        let buffer_len = buffer.len() as u64;
+        let result = unsafe {
+            syscall(arch::SYSCALL_RESOURCE_READ, handle.raw(), buffer_ptr, buffer_len, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as usize)
+        }
+    }
+
+    /// Belirtilen Handle ile temsil edilen kaynağa veri yazar.
+    /// Yazılan byte sayısını döner.
+    pub fn write(handle: Handle, buffer: &[u8]) -> Result<usize, SahneError> {
+          if !handle.is_valid() {
+              return Err(SahneError::InvalidHandle);
+          }
+        let buffer_ptr = buffer.as_ptr() as u64;
+        let buffer_len = buffer.len() as u64;
+        let result = unsafe {
+            syscall(arch::SYSCALL_RESOURCE_WRITE, handle.raw(), buffer_ptr, buffer_len, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as usize)
+        }
+    }
+
+    /// `read_at` / `write_at` çağrılarında 5. argüman: offset 4. argümanda verilmiştir.
+    /// Bu bayrak yoksa çekirdek kaynağın kendi konumunu kullanır (konsol gibi akış kaynakları).
+    pub const AT_OFFSET: u64 = 1;
+
+    /// Kaynaktan, başlangıcına göre `offset` bayt ilerisinden veri okur (blok cihazlar için).
+    /// Okunan byte sayısını döner.
+    pub fn read_at(handle: Handle, buffer: &mut [u8], offset: u64) -> Result<usize, SahneError> {
+        if !handle.is_valid() {
+            return Err(SahneError::InvalidHandle);
+        }
+        let buffer_ptr = buffer.as_mut_ptr() as u64;
+        let buffer_len = buffer.len() as u64;
+        let result = unsafe {
+            syscall(arch::SYSCALL_RESOURCE_READ, handle.raw(), buffer_ptr, buffer_len, offset, AT_OFFSET)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as usize)
+        }
+    }
+
+    /// Kaynağa, başlangıcına göre `offset` bayt ilerisine veri yazar (blok cihazlar için).
+    /// Yazılan byte sayısını döner.
+    pub fn write_at(handle: Handle, buffer: &[u8], offset: u64) -> Result<usize, SahneError> {
+        if !handle.is_valid() {
+            return Err(SahneError::InvalidHandle);
+        }
+        let buffer_ptr = buffer.as_ptr() as u64;
+        let buffer_len = buffer.len() as u64;
+        let result = unsafe {
+            syscall(arch::SYSCALL_RESOURCE_WRITE, handle.raw(), buffer_ptr, buffer_len, offset, AT_OFFSET)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as usize)
+        }
+    }
+
+    /// Belirtilen Handle'ı serbest bırakır, kaynağa erişimi sonlandırır.
+    pub fn release(handle: Handle) -> Result<(), SahneError> {
+          if !handle.is_valid() {
+              return Err(SahneError::InvalidHandle); // Zaten geçersiz handle'ı bırakmaya çalışma
+          }
+        let result = unsafe {
+            syscall(arch::SYSCALL_RESOURCE_RELEASE, handle.raw(), 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Konsol (TTY) kontrol istekleri. Mod, TTY_* bayraklarının birleşimidir.
+    pub const CONTROL_TTY_GET_MODE: u32 = 1; // Geçerli modu döndürür
+    pub const CONTROL_TTY_SET_MODE: u32 = 2; // `arg` yeni moddur
+
+    pub const TTY_CANONICAL: u32 = 1 << 0; // Satır düzenleme; okuma Enter'a kadar bekler ve bir satır döner
+    pub const TTY_ECHO: u32 = 1 << 1;      // Girilen karakterler ekrana yansıtılır
+    pub const TTY_SIGNALS: u32 = 1 << 2;   // Ctrl-C girdiyi atar ve bekleyen okuma Interrupted döner
+
+    // Blok cihaz kontrol istekleri ("emmc0", "sdcard1", "emmc0p2" ...).
+    pub const CONTROL_BLOCK_GET_SIZE: u32 = 16;       // Cihazın boyutunu bayt olarak döndürür
+    pub const CONTROL_BLOCK_FLUSH: u32 = 17;          // Cihazın yazma önbelleğini boşaltır (yazma izni gerekir)
+    pub const CONTROL_BLOCK_GET_BLOCK_SIZE: u32 = 18; // Cihazın blok boyutunu (bayt) döndürür
+    pub const CONTROL_BLOCK_CACHE_HITS: u32 = 19;     // Blok önbelleğinden karşılanan okuma sayısı (cihaz geneli)
+    pub const CONTROL_BLOCK_CACHE_MISSES: u32 = 20;   // Aygıttan okunan blok sayısı (cihaz geneli)
+    pub const CONTROL_BLOCK_SET_MOUNTED: u32 = 21;    // `arg` 1: ext2 yazılabilir bağlandı (kirli), 0: ayrıldı (yazma izni gerekir)
+
+    /// Kaynağa özgü bir kontrol isteği gönderir (örn. konsol modu) ve isteğin sonucunu döner.
+    /// Kaynak isteği tanımıyorsa NotSupported döner.
+    pub fn control(handle: Handle, request: u32, arg: u64) -> Result<u64, SahneError> {
+        if !handle.is_valid() {
+            return Err(SahneError::InvalidHandle);
+        }
+        let result = unsafe {
+            syscall(arch::SYSCALL_RESOURCE_CONTROL, handle.raw(), request as u64, arg, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as u64)
+        }
+    }
+}
+
+// Çıkarılabilir ortam olayları (SD kart takıldı/çıkarıldı)
+pub mod media {
+    use super::{resource, SahneError, Handle};
+
+    /// Olayların okunduğu kaynak. Her kayıt EVENT_SIZE bayttır; okuma konumu (bayt) / EVENT_SIZE olayın
+    /// sıra numarasıdır. Yeni olay yoksa okuma bekler (MODE_NONBLOCK ile ResourceBusy döner).
+    /// Çekirdek son olayları saklar; çok geride kalan okuyucu saklanan en eski olaydan devam eder
+    /// (kayıtlardaki sıra numarası atlar).
+    pub const RESOURCE_NAME: &str = "media";
+
+    /// Sıradaki olayın (henüz olmamış) sıra numarasını döndürür. Abonelik buradan başlar.
+    pub const CONTROL_MEDIA_GET_SEQUENCE: u32 = 32;
+
+    pub const MEDIA_INSERTED: u32 = 1; // Cihaz ve bölümleri kaynak olarak kaydedildi
+    pub const MEDIA_REMOVED: u32 = 2;  // Cihaz çıkarıldı; kaynakları ve açık Handle'ları geçersiz
+
+    /// Kayıt boyutu (bayt).
+    pub const EVENT_SIZE: usize = 32;
+    /// Olaydaki cihaz adının en fazla uzunluğu.
+    pub const MAX_NAME_LEN: usize = 16;
+
+    /// Bir ortam olayı. Kayıt düzeni (küçük uçlu): sıra (u64), tür (u32), ad uzunluğu (u32), ad (16 bayt).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct MediaEvent {
+        pub sequence: u64,
+        pub kind: u32,
+        name_len: u32,
+        name: [u8; MAX_NAME_LEN],
+    }
+
+    impl MediaEvent {
+        /// Uzun adlar MAX_NAME_LEN bayta kısaltılır.
+        pub fn new(sequence: u64, kind: u32, device: &str) -> Self {
+            let len = device.len().min(MAX_NAME_LEN);
+            let mut name = [0; MAX_NAME_LEN];
+            name[..len].copy_from_slice(&device.as_bytes()[..len]);
+            MediaEvent { sequence, kind, name_len: len as u32, name }
+        }
+
+        /// Cihazın kaynak adı ("sdcard1").
+        pub fn device(&self) -> &str {
+            let len = (self.name_len as usize).min(MAX_NAME_LEN);
+            core::str::from_utf8(&self.name[..len]).unwrap_or("")
+        }
+
+        pub fn to_bytes(&self) -> [u8; EVENT_SIZE] {
+            let mut bytes = [0; EVENT_SIZE];
+            bytes[0..8].copy_from_slice(&self.sequence.to_le_bytes());
+            bytes[8..12].copy_from_slice(&self.kind.to_le_bytes());
+            bytes[12..16].copy_from_slice(&self.name_len.to_le_bytes());
+            bytes[16..].copy_from_slice(&self.name);
+            bytes
+        }
+
+        pub fn from_bytes(bytes: &[u8; EVENT_SIZE]) -> Self {
+            let mut name = [0; MAX_NAME_LEN];
+            name.copy_from_slice(&bytes[16..]);
+            MediaEvent {
+                sequence: u64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]),
+                kind: u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+                name_len: u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+                name,
+            }
+        }
+    }
+
+    /// Ortam olaylarına abonelik. Sadece abone olunduktan sonraki olaylar alınır.
+    pub struct MediaWatcher {
+        handle: Handle,
+        next: u64, // Beklenen olayın sıra numarası
+    }
+
+    impl MediaWatcher {
+        /// `nonblock`: true ise next_event olay yokken beklemeden ResourceBusy döner.
+        pub fn new(nonblock: bool) -> Result<Self, SahneError> {
+            let mode = resource::MODE_READ | if nonblock { resource::MODE_NONBLOCK } else { 0 };
+            let handle = resource::acquire(RESOURCE_NAME, mode)?;
+            match resource::control(handle, CONTROL_MEDIA_GET_SEQUENCE, 0) {
+                Ok(next) => Ok(MediaWatcher { handle, next }),
+                Err(err) => {
+                    let _ = resource::release(handle);
+                    Err(err)
+                }
+            }
+        }
+
+        /// Sıradaki olayı döndürür (gerekirse bekler).
+        pub fn next_event(&mut self) -> Result<MediaEvent, SahneError> {
+            let mut bytes = [0u8; EVENT_SIZE];
+            let offset = self.next.checked_mul(EVENT_SIZE as u64).ok_or(SahneError::InvalidParameter)?;
+            if resource::read_at(self.handle, &mut bytes, offset)? != EVENT_SIZE {
+                return Err(SahneError::InvalidOperation);
+            }
+            let event = MediaEvent::from_bytes(&bytes);
+            self.next = event.sequence + 1;
+            Ok(event)
+        }
+    }
+
+    impl Drop for MediaWatcher {
+        fn drop(&mut self) {
+            let _ = resource::release(self.handle);
+        }
+    }
 }
 
 // Çekirdek ile zaman etkileşim modülü
 pub mod kernel {
-    use super::{SahneError, arch, syscall, map_kernel_error};
-
-    // KERNEL_INFO_ tipleri kaldırıldı
-
-    // get_info fonksiyonu kaldırıldı
-
-    /// Sistem saatini (örneğin, epoch'tan beri geçen nanosaniye olarak) alır.
-    pub fn get_time() -> Result<u64, SahneError> {
-        let result = unsafe {
-            syscall(arch::SYSCALL_GET_SYSTEM_TIME, 0, 0, 0, 0, 0)
-        };
-          if result < 0 {
-              Err(map_kernel_error(result))
-          } else {
-              Ok(result as u64)
-          }
-    }
+    use super::{SahneError, arch, syscall, map_kernel_error};
+
+    // KERNEL_INFO_ tipleri kaldırıldı
+
+    // get_info fonksiyonu kaldırıldı
+
+    /// Açılıştan beri geçen monoton süreyi nanosaniye olarak alır.
+    pub fn get_time() -> Result<u64, SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_GET_SYSTEM_TIME, 0, 0, 0, 0, 0)
+        };
+          if result < 0 {
+              Err(map_kernel_error(result))
+          } else {
+              Ok(result as u64)
+          }
+    }
+
+    /// Açılıştan beri işlemcinin boşta (wfi ile uyurken) geçirdiği süreyi nanosaniye olarak alır.
+    /// get_time() ile birlikte kullanılarak işlemci kullanım oranı hesaplanabilir.
+    pub fn get_idle_time() -> Result<u64, SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_GET_IDLE_TIME, 0, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as u64)
+        }
+    }
+
+    /// Duvar saatini, 1970-01-01 00:00:00 UTC'den beri geçen nanosaniye olarak alır.
+    /// get_time()'ın aksine saat ayarlandığında ileri veya geri atlayabilir; süre ölçmek için get_time() kullanın.
+    pub fn get_wall_time() -> Result<u64, SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_GET_WALL_TIME, 0, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as u64)
+        }
+    }
+
+    /// Duvar saatini ayarlar (Unix zamanı, nanosaniye). Değer RTC'ye de yazılır ve yeniden
+    /// başlatmalarda korunur. Sadece ayrıcalıklı görevler çağırabilir (aksi halde PermissionDenied).
+    pub fn set_time(unix_ns: u64) -> Result<(), SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_SET_TIME, unix_ns, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Çekirdeğin blok önbelleğindeki tüm yazmaları kalıcı depolamaya aktarır ve tamamlanınca döner.
+    /// Önbellek ayrıca birkaç saniyede bir kendiliğinden boşaltılır; sync bunu beklemeden yapar.
+    pub fn sync() -> Result<(), SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_SYNC, 0, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sistemi yeniden başlatır. Çekirdek diğer görevleri durdurur, dosya sistemlerini ve blok
+    /// önbelleklerini diske yazar, sürücüleri durdurur ve ardından cihazı sıfırlar.
+    /// Başarılı olursa geri dönmez. Sadece ayrıcalıklı görevler çağırabilir (aksi halde PermissionDenied).
+    pub fn reboot() -> Result<(), SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_REBOOT, 0, 0, 0, 0, 0)
+        };
+        Err(map_kernel_error(result))
+    }
+
+    /// Sistemi reboot() ile aynı sırayla kapatır ve gücü keser. Başarılı olursa geri dönmez.
+    pub fn poweroff() -> Result<(), SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_POWEROFF, 0, 0, 0, 0, 0)
+        };
+        Err(map_kernel_error(result))
+    }
 }
 
 // Senkronizasyon araçları modülü (Mutex -> Lock)
 pub mod sync {
-    use super::{SahneError, arch, syscall, map_kernel_error, Handle};
-
-    /// Yeni bir kilit (Lock) kaynağı oluşturur ve bunun için bir Handle döner.
-    /// Başlangıçta kilit serbesttir.
-    pub fn lock_create() -> Result<Handle, SahneError> {
-        let result = unsafe {
-            syscall(arch::SYSCALL_LOCK_CREATE, 0, 0, 0, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(Handle(result as u64))
-        }
-    }
-
-    /// Belirtilen Handle'a sahip kilidi almaya çalışır.
-    /// Kilit başka bir thread/task tarafından tutuluyorsa, çağıran bloke olur.
-    pub fn lock_acquire(lock_handle: Handle) -> Result<(), SahneError> {
-          if !lock_handle.is_valid() {
-              return Err(SahneError::InvalidHandle);
-          }
-        let result = unsafe {
-            syscall(arch::SYSCALL_LOCK_ACQUIRE, lock_handle.raw(), 0, 0, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(())
-        }
-    }
-
-    /// Belirtilen Handle'a sahip kilidi serbest bırakır.
-    /// Kilidin çağıran thread/task tarafından tutuluyor olması gerekir.
-    pub fn lock_release(lock_handle: Handle) -> Result<(), SahneError> {
-          if !lock_handle.is_valid() {
-              return Err(SahneError::InvalidHandle);
-          }
-        let result = unsafe {
-            syscall(arch::SYSCALL_LOCK_RELEASE, lock_handle.raw(), 0, 0, 0, 0)
-        };
-        if result < 0 {
-            Err(map_kernel_error(result))
-        } else {
-            Ok(())
-        }
-    }
-}
\ No newline at end of file
+    use super::{SahneError, arch, syscall, map_kernel_error, Handle};
+    use core::cell::UnsafeCell;
+    use core::ops::{Deref, DerefMut};
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    /// Yeni bir kilit (Lock) kaynağı oluşturur ve bunun için bir Handle döner.
+    /// Başlangıçta kilit serbesttir.
+    pub fn lock_create() -> Result<Handle, SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_LOCK_CREATE, 0, 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(Handle(result as u64))
+        }
+    }
+
+    /// Belirtilen Handle'a sahip kilidi almaya çalışır.
+    /// Kilit başka bir thread/task tarafından tutuluyorsa, çağıran bloke olur.
+    pub fn lock_acquire(lock_handle: Handle) -> Result<(), SahneError> {
+          if !lock_handle.is_valid() {
+              return Err(SahneError::InvalidHandle);
+          }
+        let result = unsafe {
+            syscall(arch::SYSCALL_LOCK_ACQUIRE, lock_handle.raw(), 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Belirtilen Handle'a sahip kilidi serbest bırakır.
+    /// Kilidin çağıran thread/task tarafından tutuluyor olması gerekir.
+    pub fn lock_release(lock_handle: Handle) -> Result<(), SahneError> {
+          if !lock_handle.is_valid() {
+              return Err(SahneError::InvalidHandle);
+          }
+        let result = unsafe {
+            syscall(arch::SYSCALL_LOCK_RELEASE, lock_handle.raw(), 0, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Zaman aşımı olmadan beklemek için `wait_on_address`'e verilecek değer.
+    pub const NO_TIMEOUT: u64 = u64::MAX;
+
+    /// `addr`'deki değer hâlâ `expected` ise çağıran iş parçacığını, `wake_address` ile
+    /// uyandırılana veya `timeout_ms` dolana kadar uyutur.
+    /// Değer farklıysa hemen `ResourceBusy`, süre dolarsa `TimedOut` döner.
+    /// Kendiliğinden (sahte) uyanmalar olabilir; çağıran koşulunu yeniden kontrol etmelidir.
+    pub fn wait_on_address(addr: &AtomicU32, expected: u32, timeout_ms: u64) -> Result<(), SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_WAIT_ON_ADDRESS, addr as *const AtomicU32 as u64, expected as u64, timeout_ms, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// `addr` üzerinde bekleyen en fazla `count` iş parçacığını uyandırır.
+    /// Uyandırılan iş parçacığı sayısını döner.
+    pub fn wake_address(addr: &AtomicU32, count: usize) -> Result<usize, SahneError> {
+        let result = unsafe {
+            syscall(arch::SYSCALL_WAKE_ADDRESS, addr as *const AtomicU32 as u64, count as u64, 0, 0, 0)
+        };
+        if result < 0 {
+            Err(map_kernel_error(result))
+        } else {
+            Ok(result as usize)
+        }
+    }
+
+    // Mutex durumları
+    const UNLOCKED: u32 = 0;
+    const LOCKED: u32 = 1;    // Kilitli, bekleyen yok
+    const CONTENDED: u32 = 2; // Kilitli ve en az bir iş parçacığı bekliyor olabilir
+
+    // Çekirdeğe gitmeden önce kısa süre dönerek denenecek sayı.
+    const SPIN_LIMIT: u32 = 100;
+
+    /// Kullanıcı alanı Mutex'i.
+    /// Çekişme yokken kilitleme/bırakma tek bir atomik işlemdir, sistem çağrısı yapılmaz.
+    /// Çekişme olduğunda `wait_on_address`/`wake_address` ile çekirdekte beklenir.
+    pub struct Mutex<T> {
+        state: AtomicU32,
+        data: UnsafeCell<T>,
+    }
+
+    unsafe impl<T: Send> Send for Mutex<T> {}
+    unsafe impl<T: Send> Sync for Mutex<T> {}
+
+    impl<T> Mutex<T> {
+        /// Kilitsiz durumda yeni bir Mutex oluşturur.
+        pub const fn new(value: T) -> Self {
+            Mutex { state: AtomicU32::new(UNLOCKED), data: UnsafeCell::new(value) }
+        }
+
+        /// Kilidi alır, gerekirse bekler.
+        pub fn lock(&self) -> MutexGuard<'_, T> {
+            if self.state.compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed).is_err() {
+                self.lock_contended();
+            }
+            MutexGuard { mutex: self }
+        }
+
+        /// Kilit serbestse alır, değilse beklemeden `None` döner.
+        pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+            self.state
+                .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+                .ok()
+                .map(|_| MutexGuard { mutex: self })
+        }
+
+        /// İçteki değeri Mutex'i tüketerek döner.
+        pub fn into_inner(self) -> T {
+            self.data.into_inner()
+        }
+
+        #[cold]
+        fn lock_contended(&self) {
+            // Kilit kısa süre içinde bırakılabilir; çekirdeğe gitmeden önce biraz dön.
+            for _ in 0..SPIN_LIMIT {
+                if self.state.load(Ordering::Relaxed) == UNLOCKED
+                    && self.state.compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed).is_ok()
+                {
+                    return;
+                }
+                core::hint::spin_loop();
+            }
+            // Bekleyen olduğunu işaretle; kilidi bırakan wake_address çağıracak.
+            // Kilidi CONTENDED olarak almak, arkamızda bekleyen varsa onların da uyandırılmasını sağlar.
+            while self.state.swap(CONTENDED, Ordering::Acquire) != UNLOCKED {
+                let _ = wait_on_address(&self.state, CONTENDED, NO_TIMEOUT);
+            }
+        }
+
+        fn unlock(&self) {
+            if self.state.swap(UNLOCKED, Ordering::Release) == CONTENDED {
+                let _ = wake_address(&self.state, 1);
+            }
+        }
+    }
+
+    /// `Mutex::lock` tarafından dönen kilit bekçisi. Düşürüldüğünde kilit bırakılır.
+    pub struct MutexGuard<'a, T> {
+        mutex: &'a Mutex<T>,
+    }
+
+    impl<T> Deref for MutexGuard<'_, T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            unsafe { &*self.mutex.data.get() }
+        }
+    }
+
+    impl<T> DerefMut for MutexGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            unsafe { &mut *self.mutex.data.get() }
+        }
+    }
+
+    impl<T> Drop for MutexGuard<'_, T> {
+        fn drop(&mut self) {
+            self.mutex.unlock();
+        }
+    }
+
+    /// Koşul değişkeni. `Mutex` ile birlikte kullanılır.
+    /// Her bildirimde artan bir sayaç üzerinde bekler; böylece kilidi bırakıp beklemeye
+    /// geçme arasında gelen bildirim kaybolmaz.
+    pub struct Condvar {
+        seq: AtomicU32,
+    }
+
+    impl Condvar {
+        pub const fn new() -> Self {
+            Condvar { seq: AtomicU32::new(0) }
+        }
+
+        /// Kilidi bırakır, bildirim gelene kadar bekler ve kilidi yeniden alır.
+        /// Sahte uyanmalar olabilir; koşul bir döngü içinde kontrol edilmelidir.
+        pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+            let (guard, _) = self.wait_timeout(guard, NO_TIMEOUT);
+            guard
+        }
+
+        /// `wait` gibidir ama en fazla `timeout_ms` bekler.
+        /// İkinci değer, bekleme zaman aşımıyla bittiyse `true` olur.
+        pub fn wait_timeout<'a, T>(&self, guard: MutexGuard<'a, T>, timeout_ms: u64) -> (MutexGuard<'a, T>, bool) {
+            let seq = self.seq.load(Ordering::Relaxed);
+            let mutex = guard.mutex;
+            drop(guard);
+            let timed_out = wait_on_address(&self.seq, seq, timeout_ms) == Err(SahneError::TimedOut);
+            (mutex.lock(), timed_out)
+        }
+
+        /// Bekleyen bir iş parçacığını uyandırır.
+        pub fn notify_one(&self) {
+            self.seq.fetch_add(1, Ordering::Release);
+            let _ = wake_address(&self.seq, 1);
+        }
+
+        /// Bekleyen tüm iş parçacıklarını uyandırır.
+        pub fn notify_all(&self) {
+            self.seq.fetch_add(1, Ordering::Release);
+            let _ = wake_address(&self.seq, usize::MAX);
+        }
+    }
+
+    impl Default for Condvar {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
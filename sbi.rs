@@ -0,0 +1,51 @@
+// main_kernel/sbi.rs
+// SBI (Supervisor Binary Interface) Çağrıları
+// S-mode çekirdek, zamanlayıcı gibi M-mode kaynaklarına firmware (OpenSBI vb.) üzerinden erişir.
+
+use core::arch::asm;
+
+// SBI uzantı kimlikleri (EID)
+const SBI_EXT_TIME: usize = 0x5449_4D45; // "TIME"
+
+// SBI çağrısının dönüş değeri (a0: hata, a1: değer)
+pub struct SbiRet {
+    pub error: isize,
+    pub value: usize,
+}
+
+// Genel SBI çağrısı: a7 = EID, a6 = FID, argümanlar a0-a2.
+#[inline(always)]
+fn sbi_call(eid: usize, fid: usize, arg0: usize, arg1: usize, arg2: usize) -> SbiRet {
+    let error: isize;
+    let value: usize;
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") arg0 => error,
+            inlateout("a1") arg1 => value,
+            in("a2") arg2,
+            in("a6") fid,
+            in("a7") eid,
+        );
+    }
+    SbiRet { error, value }
+}
+
+// Zamanlayıcı kesmesini 'time' değeri stime_value'ya ulaştığında tetiklenecek şekilde programlar.
+// Bekleyen zamanlayıcı kesmesini de temizler. u64::MAX vermek zamanlayıcıyı fiilen kapatır.
+pub fn set_timer(stime_value: u64) {
+    let _ = sbi_call(SBI_EXT_TIME, 0, stime_value as usize, 0, 0);
+}
+
+// Sistem sıfırlama uzantısı (SRST) sabitleri
+const SBI_EXT_SRST: usize = 0x5352_5354; // "SRST"
+pub const RESET_TYPE_SHUTDOWN: usize = 0;
+pub const RESET_TYPE_COLD_REBOOT: usize = 1;
+pub const RESET_REASON_NONE: usize = 0;
+pub const RESET_REASON_SYSTEM_FAILURE: usize = 1;
+
+// Sistemi kapatır veya yeniden başlatır. Başarılı olursa geri dönmez;
+// firmware SRST uzantısını desteklemiyorsa hata ile döner.
+pub fn system_reset(reset_type: usize, reason: usize) -> SbiRet {
+    sbi_call(SBI_EXT_SRST, 0, reset_type, reason, 0)
+}
@@ -2,10 +2,16 @@
 // Görev (Task/Process) Yönetimi ve Zamanlayıcı
 
 use core::fmt;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use spin::Mutex; // spin crate'i
 use alloc::boxed::Box; // Heap tahsisi için alloc crate'i
 use alloc::vec::Vec; // Dinamik boyutlu liste için alloc crate'i
 use alloc::sync::Arc; // Birden fazla yerden referans vermek için (isteğe bağlı)
+use crate::sahne64::SahneError;
+use crate::sahne64::buffer::zeroed_buffer;
+use crate::sahne64::task::THREAD_TLS_SIZE;
+use crate::limits::{self, SharedResources, TaskResources};
+use crate::drivers::timer;
 
 // TODO: Context Switch Assembly fonksiyonunun imzası.
 // Bu fonksiyon mevcut bağlamı old_context_ptr'a kaydeder,
@@ -13,6 +19,8 @@ use alloc::sync::Arc; // Birden fazla yerden referans vermek için (isteğe bağ
 // Rust'ta 'extern "C"' ile tanımlanmalıdır.
 extern "C" {
     fn context_switch(old_context_ptr: *mut TaskContext, new_context_ptr: *const TaskContext);
+    // asm.s: Giriş fonksiyonundan dönen görevi SYSCALL_THREAD_EXIT ile sonlandırır.
+    fn thread_exit_trampoline();
 }
 
 // Görev durumu enum'u
@@ -51,6 +59,9 @@ pub struct TaskContext {
     // Stack Pointer
     sp: usize, // x2
 
+    // Thread Pointer (iş parçacığının TLS bloğu)
+    tp: usize, // x4
+
     // Program Counter (Return from trap/context switch)
     mepc: usize, // Machine Exception Program Counter - Trap'ten/Geçişten dönülecek adres
 
@@ -68,35 +79,73 @@ impl TaskContext {
         TaskContext {
             ra: 0, t0: 0, t1: 0, t2: 0, a0: 0, a1: 0, a2: 0, a3: 0, a4: 0, a5: 0, a6: 0, a7: 0,
             t3: 0, t4: 0, t5: 0, t6: 0, s0: 0, s1: 0, s2: 0, s3: 0, s4: 0, s5: 0, s6: 0, s7: 0,
-            s8: 0, s9: 0, s10: 0, s11: 0, sp: 0, mepc: 0, mstatus: 0,
+            s8: 0, s9: 0, s10: 0, s11: 0, sp: 0, tp: 0, mepc: 0, mstatus: 0,
         }
     }
 }
 
+// Yığın taşması tespiti.
+// Henüz sayfalama (MMU) olmadığı için yığının altına eşlenmemiş bir koruma sayfası konamaz.
+// Bunun yerine her yığının en alttaki STACK_GUARD_SIZE baytı bilinen bir desenle (kanarya)
+// doldurulur ve her bağlam değişiminde kontrol edilir. Koruma alanı yığın tahsisinin içinde
+// olduğundan, bu alanı aşmayan bir taşma heap'teki başka nesneleri bozmaz.
+// TODO: Sayfalama geldiğinde koruma alanı eşlenmemiş bir sayfa ile değiştirilmeli.
+pub const STACK_GUARD_SIZE: usize = 256;
+const STACK_CANARY: u64 = 0x5AFE_57AC_C0DE_CA11;
+
+// Görev adının en fazla uzunluğu (bayt). Hata mesajlarında görevi tanımak için kullanılır.
+pub const TASK_NAME_LEN: usize = 16;
+
 // Görev Yapısı
 pub struct Task {
     id: usize,
+    // Görev adı (UTF-8, name_len bayt geçerli)
+    name: [u8; TASK_NAME_LEN],
+    name_len: usize,
     state: TaskState,
     context: TaskContext,
     // TODO: Yığın (stack) için tahsis edilen belleği tutmak gerek.
     // Box<[u8]> veya başka bir pointer türü olabilir.
     // Bu bellek Task silindiğinde serbest bırakılmalıdır.
     stack: Option<Box<[u8]>>,
+    // İş parçacığına özel depolama (TLS) bloğu. tp registerı bu bloğun başına işaret eder.
+    tls: Option<Box<[u8]>>,
+    // Kaynak hesabı ve sınırları. Aynı görevin iş parçacıkları aynı hesabı paylaşır.
+    resources: SharedResources,
+    // Ayrıcalıklı görevler başka görevlerin sınırlarını değiştirebilir.
+    privileged: bool,
+    // Blocked durumdaki görevin en geç uyandırılacağı zaman (timer tiki). None: süresiz bekleme.
+    wake_at: Option<u64>,
+    // Kapatma bildirimi alındı: kesilebilir beklemeler Interrupted döner, görev çıkmalıdır.
+    termination_requested: bool,
+    // Görev kendisi çıkmadı, çekirdek tarafından sonlandırıldı (kapatma, yığın taşması).
+    killed: bool,
+    // spawn_kernel_task ile başlatılan çekirdek görevi; kapatma bildirimi almaz.
+    kernel: bool,
     // Diğer görev bilgileri eklenebilir (öncelik, isim vb.)
 }
 
 impl Task {
     // Yeni bir görev oluşturur.
     // entry_point: Görevin başlayacağı fonksiyonun adresi.
-    // stack_size: Görev için ayrılacak yığın boyutu.
-    // TODO: Bellek tahsisi (alloc) burada kullanılır. mm/memory.rs'nin çalışıyor olması gerekir.
-    pub fn new(id: usize, entry_point: usize, stack_size: usize) -> Result<Self, &'static str> {
-        // TODO: Yığın için bellek tahsis et.
-        let stack = alloc::vec![0u8; stack_size].into_boxed_slice(); // Basit vektör tahsisi
-        // TODO: Bellek tahsisi başarısız olursa hata döndür.
-        // alloc::vec! başarısız olursa panic eder, daha sağlam bir alloc mekanizması gerekebilir.
-
-        let stack_top = stack.as_ptr() as usize + stack_size;
+    // stack_size: Görev için ayrılacak kullanılabilir yığın boyutu (koruma alanı buna eklenir).
+    // Tahsisler fallible yapılır: bellek yetersizse çekirdek panik etmez, OutOfMemory döner.
+    pub fn new(id: usize, entry_point: usize, stack_size: usize) -> Result<Self, SahneError> {
+        if stack_size == 0 {
+            return Err(SahneError::InvalidParameter);
+        }
+        let total_stack_size = stack_size.checked_add(STACK_GUARD_SIZE).ok_or(SahneError::InvalidParameter)?;
+        // Yığın ve TLS bloğu için bellek tahsis et.
+        let mut stack = zeroed_buffer(total_stack_size)?.into_boxed_slice();
+        let tls = zeroed_buffer(THREAD_TLS_SIZE)?.into_boxed_slice();
+        let resources = Arc::try_new(Mutex::new(TaskResources::new(limits::DEFAULT_LIMITS)))
+            .map_err(|_| SahneError::OutOfMemory)?;
+
+        // Yığın aşağı doğru büyür; koruma alanı tahsisin en altındadır.
+        for word in stack[..STACK_GUARD_SIZE].chunks_exact_mut(8) {
+            word.copy_from_slice(&STACK_CANARY.to_ne_bytes());
+        }
+        let stack_top = stack.as_ptr() as usize + total_stack_size;
 
         let mut context = TaskContext::empty();
         // Görevin başlayacağı adres (entry_point) mepc registerına yazılır.
@@ -115,20 +164,102 @@ impl Task {
         const MSTATUS_MPP_MACHINE: usize = 3 << 11;
         context.mstatus = MSTATUS_MPIE | MSTATUS_MPP_MACHINE; // Örnek ayar
 
-        // Görev bağlamı için ra (return address) ayarlanabilir.
-        // Görev fonksiyonu bittiğinde ne olacağını belirler. Genellikle bir "exit" fonksiyonuna döner.
-        // TODO: Bu adresi, görev tamamlandığında çağrılacak bir task_exit_wrapper fonksiyonunun adresi yapın.
-        // Bu wrapper fonksiyonu, görevden gelen dönüş değerini alıp sys_exit'i çağırmalıdır.
-        // Şimdilik 0 yapalım, ama bu görev bitince crash olmasına neden olur.
-        context.ra = 0; // Görev bitince dönülecek adres (geçici olarak 0)
+        // Görev fonksiyonu bittiğinde çekirdeğin çıkış trampolinine döner.
+        // Trampolin, a0'daki dönüş değeriyle SYSCALL_THREAD_EXIT çağırır.
+        context.ra = thread_exit_trampoline as usize;
+
+        // RISC-V TLS modelinde tp, iş parçacığının TLS bloğunun başına işaret eder.
+        context.tp = tls.as_ptr() as usize;
 
         Ok(Task {
             id,
+            name: [0; TASK_NAME_LEN],
+            name_len: 0,
             state: TaskState::Runnable,
             context,
             stack: Some(stack), // Yığın belleğini sakla
+            tls: Some(tls),
+            resources,
+            privileged: false,
+            wake_at: None,
+            termination_requested: false,
+            killed: false,
+            kernel: false,
         })
     }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    // Görev adı. Ad verilmemişse boş döner.
+    pub fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len]).unwrap_or("")
+    }
+
+    // Görev adını ayarlar. TASK_NAME_LEN bayttan uzun adlar karakter sınırında kısaltılır.
+    pub fn set_name(&mut self, name: &str) {
+        let mut len = name.len().min(TASK_NAME_LEN);
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.name[..len].copy_from_slice(&name.as_bytes()[..len]);
+        self.name_len = len;
+    }
+
+    // Yığın taşmış mı? Koruma alanındaki kanarya bozulmuşsa veya sp (görev bu yığında
+    // çalışıyorsa) koruma alanına inmişse true döner.
+    fn stack_overflowed(&self, sp: Option<usize>) -> bool {
+        let stack = match self.stack.as_ref() {
+            Some(stack) => stack,
+            None => return false,
+        };
+        let guard = &stack[..STACK_GUARD_SIZE];
+        let canary_intact = guard.chunks_exact(8).all(|word| word == STACK_CANARY.to_ne_bytes());
+        let stack_top = stack.as_ptr() as usize + stack.len();
+        let guard_top = stack.as_ptr() as usize + STACK_GUARD_SIZE;
+        let sp_in_guard = sp.is_some_and(|sp| sp <= stack_top && sp < guard_top);
+        !canary_intact || sp_in_guard
+    }
+
+    // [start, end) aralığı görevin kendi yığınında (koruma alanı hariç) veya TLS bloğunda mı?
+    // Kullanıcı işaretçisi doğrulamasında kullanılır (mm::check_user_range).
+    pub fn owns_range(&self, start: usize, end: usize) -> bool {
+        let stack = self.stack.as_deref().map(|stack| &stack[STACK_GUARD_SIZE..]);
+        [stack, self.tls.as_deref()].into_iter().flatten().any(|block| {
+            let base = block.as_ptr() as usize;
+            start >= base && end <= base + block.len()
+        })
+    }
+
+    // Bağlamı başka bir görevden kopyalar (fork). tp bu görevin kendi TLS bloğunda kalır;
+    // TLS içeriği ise ebeveynden kopyalanır.
+    pub fn copy_context_from(&mut self, parent: &Task) {
+        let own_tp = self.context.tp;
+        self.context = parent.context;
+        self.context.tp = own_tp;
+        if let (Some(own_tls), Some(parent_tls)) = (self.tls.as_mut(), parent.tls.as_ref()) {
+            own_tls.copy_from_slice(parent_tls);
+        }
+    }
+
+    // Görevin kaynak hesabını döndürür (Arc clone edilir).
+    pub fn resources(&self) -> SharedResources {
+        self.resources.clone()
+    }
+
+    // Görevi başka bir hesaba bağlar (iş parçacıkları ve fork ile oluşan çocuklar için).
+    pub fn set_resources(&mut self, resources: SharedResources) {
+        self.resources = resources;
+    }
+
+    pub fn is_privileged(&self) -> bool {
+        self.privileged
+    }
+
+    pub fn set_privileged(&mut self, privileged: bool) {
+        self.privileged = privileged;
+    }
 }
 
 // Çekirdekteki tüm görevleri tutan global liste.
@@ -140,22 +271,109 @@ static TASKS: Mutex<Vec<Arc<Mutex<Task>>>> = Mutex::new(Vec::new());
 // Mutex ile korunmalı.
 static CURRENT_TASK_ID: Mutex<Option<usize>> = Mutex::new(None);
 
-// Zamanlayıcıyı (scheduler) başlatır. İlk görevi çalıştırır.
+// CURRENT_TASK_ID'nin kilitsiz kopyası (NO_TASK: görev yok). Tahsis edici gibi
+// CURRENT_TASK_ID kilidi tutulurken de çağrılabilen yerler bunu okur.
+const NO_TASK: usize = usize::MAX;
+static RUNNING_TASK: AtomicUsize = AtomicUsize::new(NO_TASK);
+
+// Boşta (idle) görevinin ID'si. init() onu her zaman ilk görev olarak ekler.
+pub const IDLE_TASK_ID: usize = 0;
+
+// Boşta görevinin yığını. Sadece kesme çerçevesi ve schedule() için yer gerekir.
+const IDLE_STACK_SIZE: usize = 2048;
+
+// Toplam boşta geçen süre (tik) ve boşta görevine son geçiş anı.
+static IDLE_TICKS: AtomicU64 = AtomicU64::new(0);
+static IDLE_SINCE: AtomicU64 = AtomicU64::new(0);
+
+// Boşta görevi: çalışacak başka görev yokken işlemciyi bir sonraki kesmeye kadar uyutur (wfi).
+// Buzdolabı 7/24 çalıştığı için boşta güç tüketimi önemlidir; meşgul döngü yerine hep wfi kullanılır.
+extern "C" fn idle_task_main() -> ! {
+    loop {
+        // Kesme işleyicisi bir görevi uyandırmış olabilir; varsa ona geç.
+        schedule();
+        // schedule() ile wfi arasında gelen bir kesmenin uyandırdığı görev kaçırılmasın diye kuyruk
+        // kesmeler kapalıyken yeniden kontrol edilir. wfi, SIE kapalıyken de bekleyen bir kesmeyle
+        // uyanır; kesme SIE yeniden açılınca işlenir.
+        unsafe { riscv::register::sstatus::clear_sie(); }
+        if !has_runnable_task() {
+            riscv::asm::wfi();
+        }
+        unsafe { riscv::register::sstatus::set_sie(); }
+    }
+}
+
+// Boşta görevi dışında çalıştırılabilir bir görev var mı?
+fn has_runnable_task() -> bool {
+    let tasks = TASKS.lock();
+    select_next_task(&tasks, IDLE_TASK_ID, false) != IDLE_TASK_ID
+}
+
+// Zamanlayıcıyı (scheduler) başlatır. Boşta görevini oluşturur.
 // İlk görev genellikle init/main.rs'deki çekirdek ana döngüsü olur.
 pub fn init() {
+    let mut idle_task = Task::new(IDLE_TASK_ID, idle_task_main as usize, IDLE_STACK_SIZE)
+        .expect("Boşta görevi oluşturulamadı");
+    idle_task.set_name("idle");
+    add_task(idle_task).expect("Boşta görevi eklenemedi");
+
     // TASKS vektörünü ve CURRENT_TASK_ID'yi başlatır.
     // İlk görevi (çekirdek ana döngüsü) burada oluşturup kuyruğa ekleyin.
      let initial_task_id = add_task(...); // İlk görevi ekle
-     *CURRENT_TASK_ID.lock() = Some(initial_task_id);
+    // İlk görev (init) ayrıcalıklı olmalıdır: initial_task.set_privileged(true).
+    // Diğer görevler ayrıcalığı ve kaynak sınırlarını fork ile ondan devralır.
+    // CURRENT_TASK_ID run_scheduler() tarafından ayarlanır.
      printk!("Zamanlayıcı başlatıldı.\n");
 }
 
+// Çekirdek başlatmasını bitirir ve CPU'yu görevlere devreder. Asla geri dönmez.
+// Önce boşta görevine geçilir; o da hemen schedule() çağırarak ilk çalıştırılabilir görevi seçer.
+pub fn run_scheduler() -> ! {
+    let idle_arc = task_by_id(IDLE_TASK_ID).expect("run_scheduler: sched::init çağrılmadı");
+    idle_arc.lock().state = TaskState::Running;
+    IDLE_SINCE.store(timer::now_ticks(), Ordering::Relaxed);
+    *CURRENT_TASK_ID.lock() = Some(IDLE_TASK_ID);
+    RUNNING_TASK.store(IDLE_TASK_ID, Ordering::Release);
+
+    // Açılış yığınının bağlamı buraya kaydedilir ve bir daha yüklenmez.
+    let mut boot_context = TaskContext::empty();
+    unsafe {
+        let idle_context_ptr = &idle_arc.lock().context as *const TaskContext;
+        context_switch(&mut boot_context as *mut TaskContext, idle_context_ptr);
+    }
+    unreachable!("run_scheduler: açılış bağlamına geri dönüldü");
+}
+
+// Açılıştan beri boşta (wfi içinde) geçen toplam süre (nanosaniye).
+pub fn idle_time_ns() -> u64 {
+    let mut ticks = IDLE_TICKS.load(Ordering::Relaxed);
+    if current_task_id() == Some(IDLE_TASK_ID) {
+        // Süren boşta dönemini de say.
+        ticks += timer::now_ticks().saturating_sub(IDLE_SINCE.load(Ordering::Relaxed));
+    }
+    timer::ticks_to_ns(ticks)
+}
+
 // Yeni bir görevi görev kuyruğuna ekler.
-pub fn add_task(task: Task) -> usize {
+// Liste büyütülemezse görev düşürülür (yığını serbest kalır) ve OutOfMemory döner.
+pub fn add_task(task: Task) -> Result<usize, SahneError> {
     let task_id = task.id;
-    let task_arc = Arc::new(Mutex::new(task));
-    TASKS.lock().push(task_arc);
-    task_id
+    let task_arc = Arc::try_new(Mutex::new(task)).map_err(|_| SahneError::OutOfMemory)?;
+    let mut tasks_lock = TASKS.lock();
+    tasks_lock.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+    tasks_lock.push(task_arc);
+    Ok(task_id)
+}
+
+// Çekirdek içinde çalışan bir görev oluşturur (periyodik işler için; örn. blok önbelleğini boşaltma).
+// Görev ayrıcalıklıdır ve sched::init'ten sonra oluşturulmalıdır. Görevin ID'sini döndürür.
+pub fn spawn_kernel_task(name: &str, entry: extern "C" fn() -> !, stack_size: usize) -> Result<usize, SahneError> {
+    let task_id = TASKS.lock().len();
+    let mut task = Task::new(task_id, entry as usize, stack_size)?;
+    task.set_name(name);
+    task.set_privileged(true);
+    task.kernel = true;
+    add_task(task)
 }
 
 // Şu anda çalışan görevi döndürür (Arc<Mutex<Task>> olarak).
@@ -173,44 +391,105 @@ pub fn current_task() -> Option<Arc<Mutex<Task>>> {
     })
 }
 
+// Şu anda çalışan görevin ID'sini döndürür (SYSCALL_THREAD_ID).
+pub fn current_task_id() -> Option<usize> {
+    *CURRENT_TASK_ID.lock()
+}
+
+// current_task_id'nin kilit almayan sürümü (tahsis edici içinden kullanılır).
+pub fn running_task_id() -> Option<usize> {
+    match RUNNING_TASK.load(Ordering::Acquire) {
+        NO_TASK => None,
+        id => Some(id),
+    }
+}
+
+// ID'si verilen görevi döndürür (Arc clone edilir).
+pub fn task_by_id(id: usize) -> Option<Arc<Mutex<Task>>> {
+    TASKS.lock().get(id).cloned()
+}
+
+// SYSCALL_THREAD_CREATE: Geçerli görevin adres alanını ve kaynak hesabını paylaşan yeni bir iş parçacığı oluşturur.
+// İş parçacığı sınırı aşılmışsa hiçbir şey tahsis edilmeden OutOfMemory döner.
+pub fn sys_create_thread(entry_point: usize, stack_size: usize, arg: u64) -> Result<usize, SahneError> {
+    let parent_arc = current_task().ok_or(SahneError::InvalidOperation)?;
+    let (resources, privileged, name, name_len) = {
+        let parent = parent_arc.lock();
+        (parent.resources(), parent.is_privileged(), parent.name, parent.name_len)
+    };
+
+    resources.lock().charge_thread()?;
+
+    let new_task_id = TASKS.lock().len();
+    let mut thread = match Task::new(new_task_id, entry_point, stack_size) {
+        Ok(task) => task,
+        Err(err) => {
+            resources.lock().release_thread();
+            return Err(err);
+        }
+    };
+    // Argüman, giriş fonksiyonunun ilk parametresi olarak a0'da geçirilir.
+    thread.context.a0 = arg as usize;
+
+    thread.set_resources(resources.clone());
+    thread.set_privileged(privileged);
+    // İş parçacığı görevin adını taşır; taşma raporlarında hangi göreve ait olduğu görülür.
+    thread.name = name;
+    thread.name_len = name_len;
+    add_task(thread).map_err(|err| {
+        resources.lock().release_thread();
+        err
+    })
+}
+
 // Zamanlama fonksiyonu. Çalışmaya hazır bir sonraki görevi seçer ve bağlam değiştirir.
 // Bu fonksiyon ya periyodik olarak (örn. timer kesmesiyle) ya da bir görev beklemeye geçtiğinde çağrılır.
 #[no_mangle] // Kesme işleyicisi veya sistem çağrısından çağrılabilir
 pub fn schedule() {
+    // Bekleme süresi dolan görevleri önce çalıştırılabilir yap.
+    wake_expired_tasks();
+
     let mut tasks_lock = TASKS.lock();
     let mut current_task_id_lock = CURRENT_TASK_ID.lock();
 
-    let old_task_id = current_task_id_lock.unwrap(); // Varsayım: Her zaman çalışan bir görev var
-
-    // Bir sonraki runnable görevi bul (basit round-robin)
-    let total_tasks = tasks_lock.len();
-    let start_index = (old_task_id + 1) % total_tasks;
-    let mut next_task_id = old_task_id;
-
-    for i in 0..total_tasks {
-        let candidate_id = (start_index + i) % total_tasks;
-        let candidate_task_arc = &tasks_lock[candidate_id]; // Arc clone etmeden referans al
-        let candidate_task = candidate_task_arc.lock(); // Görev Mutex'ini kilitle
-
-        if candidate_task.state == TaskState::Runnable {
-            next_task_id = candidate_id;
-            break; // İlk runnable görevi bulduk
+    // run_scheduler() öncesinde (erken açılış) değiştirilecek bir görev yoktur.
+    // Timer'ı kapatmak bekleyen kesmeyi de temizler.
+    let old_task_id = match *current_task_id_lock {
+        Some(id) => id,
+        None => {
+            timer::set_next_event(None);
+            return;
         }
-        // Kiliti serbest bırak
-        drop(candidate_task);
-    }
+    };
+    let old_still_running = {
+        // Çalışan görevin yığını, ona geri dönülmeden önce her bağlam değişiminde kontrol edilir.
+        let mut old_task = tasks_lock[old_task_id].lock();
+        fault_if_overflowed(&mut old_task);
+        old_task.state == TaskState::Running
+    };
+    let next_task_id = select_next_task(&tasks_lock, old_task_id, old_still_running);
+
+    // Tickless: bir sonraki timer kesmesini sadece gerçekten gereken an için programla.
+    // Aynı görev devam etse bile yeniden programlanır; bu, bekleyen kesmeyi de temizler.
+    program_next_timer(&tasks_lock, next_task_id);
 
-    // Eğer çalıştırılabilir başka görev bulamadıysak (sadece geçerli görev runnable ise),
-    // geçerli görevi çalıştırmaya devam et.
     if next_task_id == old_task_id {
         return;
     }
 
+    account_idle_switch(old_task_id, next_task_id);
+
     let old_task_arc = tasks_lock[old_task_id].clone(); // Eski görevin Arc'ını al
     let new_task_arc = tasks_lock[next_task_id].clone(); // Yeni görevin Arc'ını al
 
     // Geçerli görevin durumunu güncelle
-    old_task_arc.lock().state = TaskState::Runnable;
+    // Bloklanmış veya sonlanmış görev durumunu korur; sadece çalışan görev kuyruğa geri döner.
+    {
+        let mut old_task = old_task_arc.lock();
+        if old_task.state == TaskState::Running {
+            old_task.state = TaskState::Runnable;
+        }
+    }
     new_task_arc.lock().state = TaskState::Running;
 
     // CURRENT_TASK_ID'yi güncellemeden kilitleri serbest bırak!
@@ -223,6 +502,7 @@ pub fn schedule() {
     // Basitlik için, Rust'ta ID'yi güncelleyip kilitleri serbest bırakalım.
     // Kilitler, context_switch çağrılmadan önce serbest bırakılmalıdır, aksi takdirde deadlock olur.
     *current_task_id_lock = Some(next_task_id);
+    RUNNING_TASK.store(next_task_id, Ordering::Release);
 
     // Kilitleri serbest bırak
     drop(current_task_id_lock);
@@ -246,6 +526,266 @@ pub fn schedule() {
     // Ancak genellikle context_switch doğrudan zamanlayıcının çağrıldığı noktaya döner.
 }
 
+// Şu anki yığın göstericisi.
+#[inline(always)]
+fn current_sp() -> usize {
+    let sp: usize;
+    unsafe { core::arch::asm!("mv {}, sp", out(reg) sp) };
+    sp
+}
+
+// Trap girişinde (her kesme ve sistem çağrısında) çalışan görevin yığınını kontrol eder.
+// Tickless zamanlayıcıda bağlam değişimi seyrek olabileceği için taşma schedule()'ı beklemeden yakalanır;
+// taşan görev sonlandırılır ve başka bir göreve geçilir.
+pub fn check_current_stack() {
+    let task_arc = match current_task() {
+        Some(task) => task,
+        None => return,
+    };
+    let overflowed = fault_if_overflowed(&mut task_arc.lock());
+    drop(task_arc);
+    if overflowed {
+        schedule();
+    }
+}
+
+// Çalışan görevin yığını (kanarya ve sp) taşmışsa görevi sonlandırır; sonlandırıldıysa true döner.
+fn fault_if_overflowed(task: &mut Task) -> bool {
+    if task.state == TaskState::Exited || !task.stack_overflowed(Some(current_sp())) {
+        return false;
+    }
+    stack_overflow_fault(task);
+    true
+}
+
+// Yığını taşan görevi sonlandırır. Görev bir daha zamanlanmaz; yığını kendi tahsisinde kaldığı
+// için başka nesneleri bozmaz. Boşta görevi olmadan çekirdek devam edemeyeceği için o durumda panik edilir.
+fn stack_overflow_fault(task: &mut Task) {
+    if task.id == IDLE_TASK_ID {
+        panic!("Boşta görevinin yığını taştı");
+    }
+    printk!("GÖREV HATASI: '{}' (ID {}) yığın taşması, görev sonlandırıldı\n", task.name(), task.id);
+    task.state = TaskState::Exited;
+    task.killed = true;
+    task.wake_at = None;
+    // Kilit sırası: Task -> TaskResources.
+    task.resources().lock().release_thread();
+}
+
+// Bir sonraki runnable görevi seçer (basit round-robin).
+// Boşta görevi bu aramaya katılmaz; sadece başka seçenek kalmadığında çalışır.
+// Çalıştırılabilir başka görev yoksa geçerli görev (hâlâ çalışabiliyorsa) devam eder,
+// aksi halde boşta görevine geçilir.
+fn select_next_task(tasks: &[Arc<Mutex<Task>>], old_task_id: usize, old_still_running: bool) -> usize {
+    let total_tasks = tasks.len();
+    let start_index = (old_task_id + 1) % total_tasks;
+
+    for i in 0..total_tasks {
+        let candidate_id = (start_index + i) % total_tasks;
+        if candidate_id == IDLE_TASK_ID {
+            continue;
+        }
+        if tasks[candidate_id].lock().state == TaskState::Runnable {
+            return candidate_id; // İlk runnable görevi bulduk
+        }
+    }
+
+    if old_still_running { old_task_id } else { IDLE_TASK_ID }
+}
+
+// Bir sonraki timer kesmesini programlar: Blocked görevlerin en yakın uyanma anı ve,
+// başka bir görev CPU için bekliyorsa, çalışan görevin zaman diliminin sonu.
+// Hiçbiri yoksa timer kapatılır; işlemci bir cihaz kesmesine kadar wfi'da kalabilir.
+fn program_next_timer(tasks: &[Arc<Mutex<Task>>], running_id: usize) {
+    let mut deadline: Option<u64> = None;
+    let mut contended = false;
+
+    for (id, task_arc) in tasks.iter().enumerate() {
+        if id == running_id || id == IDLE_TASK_ID {
+            continue;
+        }
+        let task = task_arc.lock();
+        match task.state {
+            TaskState::Blocked => {
+                if let Some(wake_at) = task.wake_at {
+                    deadline = Some(deadline.map_or(wake_at, |d| d.min(wake_at)));
+                }
+            }
+            TaskState::Runnable => contended = true,
+            _ => {}
+        }
+    }
+
+    if contended && running_id != IDLE_TASK_ID {
+        let slice_end = timer::now_ticks().saturating_add(timer::TIME_SLICE_TICKS);
+        deadline = Some(deadline.map_or(slice_end, |d| d.min(slice_end)));
+    }
+
+    timer::set_next_event(deadline);
+}
+
+// Boşta görevine giriş/çıkışta boşta süresini günceller.
+fn account_idle_switch(old_task_id: usize, next_task_id: usize) {
+    let now = timer::now_ticks();
+    if old_task_id == IDLE_TASK_ID {
+        let since = IDLE_SINCE.load(Ordering::Relaxed);
+        IDLE_TICKS.fetch_add(now.saturating_sub(since), Ordering::Relaxed);
+    }
+    if next_task_id == IDLE_TASK_ID {
+        IDLE_SINCE.store(now, Ordering::Relaxed);
+    }
+}
+
+// Süresi dolan Blocked görevleri Runnable yapar.
+fn wake_expired_tasks() {
+    let now = timer::now_ticks();
+    let tasks_lock = TASKS.lock();
+    for task_arc in tasks_lock.iter() {
+        let mut task = task_arc.lock();
+        if task.state == TaskState::Blocked && task.wake_at.map_or(false, |deadline| deadline <= now) {
+            task.state = TaskState::Runnable;
+            task.wake_at = None;
+        }
+    }
+}
+
+// Geçerli görevi bloklanmış olarak işaretler ama henüz CPU'yu bırakmaz.
+// Bekleme kuyruğuna ekleme ile bloklanma arasında kaybolan uyandırmaları önlemek için
+// çağıran, kendi kuyruk kilidini tutarken bunu çağırmalı, kilidi bıraktıktan sonra
+// wait_while_blocked() ile beklemelidir. wake_task() arada çağrılırsa bekleme hiç yapılmaz.
+pub fn prepare_to_block(deadline: Option<u64>) {
+    if let Some(task_arc) = current_task() {
+        let mut task = task_arc.lock();
+        task.state = TaskState::Blocked;
+        task.wake_at = deadline;
+    }
+}
+
+// prepare_to_block gibidir ama kapatma bildirimi almış görev bloklanmaz, Interrupted döner.
+// Bildirim görev kilidi altında kontrol edildiği için kontrol ile bloklanma arasında kaybolmaz.
+pub fn prepare_to_block_interruptible(deadline: Option<u64>) -> Result<(), SahneError> {
+    if let Some(task_arc) = current_task() {
+        let mut task = task_arc.lock();
+        if task.termination_requested {
+            return Err(SahneError::Interrupted);
+        }
+        task.state = TaskState::Blocked;
+        task.wake_at = deadline;
+    }
+    Ok(())
+}
+
+// Geçerli görev Blocked olduğu sürece CPU'yu başka görevlere bırakır.
+pub fn wait_while_blocked() {
+    let task_arc = match current_task() {
+        Some(task) => task,
+        None => return,
+    };
+    loop {
+        if task_arc.lock().state != TaskState::Blocked {
+            break;
+        }
+        schedule();
+        // Normalde schedule() boşta görevine geçer. Sadece run_scheduler() öncesinde
+        // (boşta görevi henüz çalışmıyorken) buraya bloklanmış olarak dönülür;
+        // bir kesme durumu değiştirene kadar işlemciyi beklet.
+        if task_arc.lock().state == TaskState::Blocked {
+            riscv::asm::wfi();
+        }
+    }
+    task_arc.lock().state = TaskState::Running;
+}
+
+// Bloklanmış bir görevi uyandırır. Görev bloklanmamışsa bir şey yapmaz.
+pub fn wake_task(id: usize) {
+    if let Some(task_arc) = task_by_id(id) {
+        let mut task = task_arc.lock();
+        if task.state == TaskState::Blocked {
+            task.state = TaskState::Runnable;
+            task.wake_at = None;
+        }
+    }
+}
+
+// Çağıran görev ve boşta görevi dışındaki tüm görevleri durdurur (kapatma sırasında kullanılır).
+// Durdurulan görevler Exited olarak işaretlenir ve bir daha zamanlanmaz. Durdurulan görev sayısı döner.
+pub fn stop_other_tasks() -> usize {
+    let caller_id = current_task_id();
+    let tasks_lock = TASKS.lock();
+    let mut stopped = 0;
+    for task_arc in tasks_lock.iter() {
+        let mut task = task_arc.lock();
+        if task.id == IDLE_TASK_ID || Some(task.id) == caller_id || task.state == TaskState::Exited {
+            continue;
+        }
+        task.state = TaskState::Exited;
+        task.wake_at = None;
+        task.killed = true;
+        // Kilit sırası: Task -> TaskResources.
+        task.resources().lock().release_thread();
+        stopped += 1;
+    }
+    stopped
+}
+
+// Kapatma bildirimi: çağıran, boşta görevi ve çekirdek görevleri dışındaki canlı görevlerin sonlanması
+// istenir. Bloklu görevler uyandırılır; kesilebilir beklemeleri Interrupted döner. Bildirilen görev sayısı döner.
+pub fn request_termination_of_others() -> usize {
+    let caller_id = current_task_id();
+    let tasks_lock = TASKS.lock();
+    let mut notified = 0;
+    for task_arc in tasks_lock.iter() {
+        let mut task = task_arc.lock();
+        if task.id == IDLE_TASK_ID || Some(task.id) == caller_id || task.kernel || task.state == TaskState::Exited {
+            continue;
+        }
+        task.termination_requested = true;
+        if task.state == TaskState::Blocked {
+            task.state = TaskState::Runnable;
+            task.wake_at = None;
+        }
+        notified += 1;
+    }
+    notified
+}
+
+// Sonlanması istenmiş ama henüz çıkmamış görev sayısı.
+pub fn terminating_tasks() -> usize {
+    TASKS.lock().iter().filter(|task_arc| {
+        let task = task_arc.lock();
+        task.termination_requested && task.state != TaskState::Exited
+    }).count()
+}
+
+// SYSCALL_TERMINATION_REQUESTED: Geçerli görevin sonlanması istendi mi?
+pub fn termination_requested() -> bool {
+    current_task().is_some_and(|task| task.lock().termination_requested)
+}
+
+// Görev çekirdek tarafından mı sonlandırıldı? (kendisi çıktıysa veya bilinmiyorsa false)
+pub fn task_was_killed(id: usize) -> bool {
+    task_by_id(id).is_some_and(|task| task.lock().killed)
+}
+
+// Geçerli görevi en az verilen süre kadar uyutur.
+pub fn sleep(milliseconds: u64) {
+    let deadline = timer::now_ticks().saturating_add(timer::ms_to_ticks(milliseconds));
+    while timer::now_ticks() < deadline {
+        prepare_to_block(Some(deadline));
+        wait_while_blocked();
+    }
+}
+
+// SYSCALL_TASK_SLEEP: sleep gibidir ama kapatma bildirimi gelirse süre dolmadan Interrupted döner.
+pub fn sleep_interruptible(milliseconds: u64) -> Result<(), SahneError> {
+    let deadline = timer::now_ticks().saturating_add(timer::ms_to_ticks(milliseconds));
+    while timer::now_ticks() < deadline {
+        prepare_to_block_interruptible(Some(deadline))?;
+        wait_while_blocked();
+    }
+    Ok(())
+}
+
 // Bir görev kendiliğinden (cooperatively) zamanlayıcıyı çağırabilir.
 #[allow(dead_code)] // Eğer preemptive scheduling kullanılıyorsa bu kullanılmayabilir.
 pub fn task_yield() {
@@ -263,8 +803,117 @@ pub fn debug_print_tasks() {
     for task_arc in tasks_lock.iter() {
         let task = task_arc.lock();
         let current_marker = if task.id == current_id { "*" } else { "" };
-        printk!("ID: {} Durum: {:?} {}\n", task.id, task.state, current_marker);
+        printk!("ID: {} Ad: {} Durum: {:?} {}\n", task.id, task.name(), task.state, current_marker);
         // printk!("  Context: {:?}\n", task.context); // Çok detaylı olabilir
     }
     printk!("---------------------\n");
-}
\ No newline at end of file
+}
+
+// Testler zamanlayıcı başlamadan çalışır; çalışan görev yoktur. Bu yardımcı `f`'i geçici bir
+// görev çalışıyormuş gibi çalıştırır (örn. kullanıcı işaretçisi doğrulamasını denemek için).
+#[cfg(test)]
+pub fn run_as_test_task<R>(f: impl FnOnce() -> R) -> R {
+    let task_id = TASKS.lock().len();
+    add_task(Task::new(task_id, 0, 4096).unwrap()).unwrap();
+    let previous = CURRENT_TASK_ID.lock().replace(task_id);
+    RUNNING_TASK.store(task_id, Ordering::Release);
+    let result = f();
+    *CURRENT_TASK_ID.lock() = previous;
+    RUNNING_TASK.store(previous.unwrap_or(NO_TASK), Ordering::Release);
+    TASKS.lock().pop();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test için görev listesi: ID'ler indekslerle aynıdır, 0 boşta görevidir.
+    fn task_list(states: &[TaskState]) -> Vec<Arc<Mutex<Task>>> {
+        let mut tasks = Vec::new();
+        for (id, state) in states.iter().enumerate() {
+            let mut task = Task::new(id, 0, 512).expect("test görevi oluşturulamadı");
+            task.state = *state;
+            tasks.push(Arc::new(Mutex::new(task)));
+        }
+        tasks
+    }
+
+    #[test_case]
+    fn zero_stack_task_is_rejected() {
+        assert!(matches!(Task::new(1, 0, 0), Err(SahneError::InvalidParameter)));
+    }
+
+    #[test_case]
+    fn fresh_stack_guard_is_intact() {
+        let task = Task::new(1, 0, 512).unwrap();
+        assert!(!task.stack_overflowed(None));
+        // Yığının tepesinde çalışmak taşma değildir.
+        assert!(!task.stack_overflowed(Some(task.context.sp)));
+    }
+
+    #[test_case]
+    fn overwritten_canary_is_detected() {
+        let mut task = Task::new(1, 0, 512).unwrap();
+        task.stack.as_mut().unwrap()[STACK_GUARD_SIZE - 1] ^= 0xFF;
+        assert!(task.stack_overflowed(None));
+    }
+
+    #[test_case]
+    fn stack_pointer_in_guard_is_detected() {
+        let task = Task::new(1, 0, 512).unwrap();
+        let base = task.stack.as_ref().unwrap().as_ptr() as usize;
+        assert!(task.stack_overflowed(Some(base + STACK_GUARD_SIZE - 8)));
+        assert!(!task.stack_overflowed(Some(base + STACK_GUARD_SIZE)));
+    }
+
+    #[test_case]
+    fn overflowing_task_is_terminated() {
+        let mut task = Task::new(1, 0, 512).unwrap();
+        task.set_name("sh64");
+        task.state = TaskState::Running;
+        stack_overflow_fault(&mut task);
+        assert_eq!(task.state, TaskState::Exited);
+    }
+
+    #[test_case]
+    fn long_task_names_are_truncated_on_char_boundary() {
+        let mut task = Task::new(1, 0, 512).unwrap();
+        assert_eq!(task.name(), "");
+        task.set_name("sahnedesktop");
+        assert_eq!(task.name(), "sahnedesktop");
+        // 15 ASCII bayt + 2 baytlık 'ş': 16. baytta kesilirse karakter bölünürdü.
+        task.set_name("aaaaaaaaaaaaaaaşbbb");
+        assert_eq!(task.name(), "aaaaaaaaaaaaaaa");
+    }
+
+    #[test_case]
+    fn round_robin_picks_next_runnable_task() {
+        use TaskState::*;
+        let tasks = task_list(&[Runnable, Running, Runnable, Runnable]);
+        assert_eq!(select_next_task(&tasks, 1, true), 2);
+        // Listenin sonundan başa sarar (boşta görevi atlanır).
+        assert_eq!(select_next_task(&tasks, 3, true), 2);
+    }
+
+    #[test_case]
+    fn running_task_continues_when_alone() {
+        use TaskState::*;
+        let tasks = task_list(&[Runnable, Running, Blocked]);
+        assert_eq!(select_next_task(&tasks, 1, true), 1);
+    }
+
+    #[test_case]
+    fn blocked_task_switches_to_idle() {
+        use TaskState::*;
+        let tasks = task_list(&[Runnable, Blocked, Exited]);
+        assert_eq!(select_next_task(&tasks, 1, false), IDLE_TASK_ID);
+    }
+
+    #[test_case]
+    fn idle_is_skipped_while_work_exists() {
+        use TaskState::*;
+        let tasks = task_list(&[Running, Blocked, Runnable]);
+        assert_eq!(select_next_task(&tasks, IDLE_TASK_ID, true), 2);
+    }
+}
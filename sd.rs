@@ -1,158 +1,209 @@
 // drivers/sd.rs
 // SD ve Micro SD Kart Sürücüsü (Blok Tabanlı)
+// Denetleyici SDHCI uyumludur (sdhci.rs), kart tanıma ve adresleme (SDSC bayt, SDHC/SDXC blok adresi)
+// mmc.rs'tedir. Ardışık bloklar tek komutla (CMD18/CMD25) aktarılır; denetleyici ADMA2 destekliyorsa
+// veri DMA ile doğrudan çağıranın tamponlarına gider.
+//
+// Kart takılıp çıkarılabilir: "sdcd" görevi denetleyicinin kart algılama durumunu yoklar, takılan kartı
+// tanıyıp "sdcard1" olarak kaydeder, çıkarılanı kaldırır (storage::unregister_disk; önbellek atılır,
+// açık Handle'lar geçersiz olur, "media" kaynağına olay yazılır).
 
 use spin::Mutex;
+use crate::block::{self, BlockDevice};
 use crate::printk;
-use crate::rs_io;
-use alloc::boxed::Box; // Buffer için heap tahsisi gerekebilir
-use core::slice;
-
-// TODO: SD kart denetleyicisinin gerçek MMIO adresini ve register offsetlerini belirleyin.
+use crate::sahne64::SahneError;
+use crate::sched;
+use crate::drivers::storage;
+use crate::drivers::mmc::{self, Card, CardError};
+use crate::drivers::sdhci::Sdhci;
+use crate::drivers::sdhci_mmio::MmioBus;
+
+// TODO: SD kart denetleyicisinin gerçek MMIO adresini ve kesme numarasını belirleyin.
 const SD_CONTROLLER_BASE_ADDRESS: usize = 0xDDAA_0000; // Varsayımsal
-const SD_BLOCK_SIZE: usize = 512; // SD kart blok boyutu genellikle 512 bayttır
+const SD_IRQ: u32 = 41; // Varsayımsal PLIC kaynağı
+const SD_HOST_SLOT: usize = 1; // sdhci_mmio kesme tablosundaki yeri
+const SD_DISK_NAME: &str = "sdcard1";
 
-// TODO: SD Kart Register offsetleri (Tamamen Varsayımsal - Gerçek Donanıma Bakılmalı!)
-const SD_CMD_REG_OFFSET: usize = 0x00; // Command Register
-const SD_ARG_REG_OFFSET: usize = 0x04; // Argument Register
-const SD_RSP_REG_OFFSET: usize = 0x08; // Response Register
-const SD_STATUS_REG_OFFSET: usize = 0x0C; // Status Register
-const SD_DATA_REG_OFFSET: usize = 0x10; // Data Register (PIO için)
-const SD_STATUS_CMD_DONE_BIT: u32 = 1 << 0; // Command Done bit
-const SD_STATUS_DATA_READY_BIT: u32 = 1 << 1; // Data Ready bit
-const SD_STATUS_ERROR_BIT: u32 = 1 << 7; // Error bit
+// Kart algılama yoklama aralığı. Kartı çıkaran kullanıcı en fazla bu kadar süre eski cihazı görür.
+const CARD_DETECT_INTERVAL_MS: u64 = 500;
+const CARD_DETECT_STACK_SIZE: usize = 4096;
 
 
 struct SdCardReader {
-    controller_base: usize,
-    card_inserted: bool,
-    card_initialized: bool,
-    // Diğer durum bilgileri (kart boyutu, tip, RCA vb.) eklenebilir
+    host: Sdhci<MmioBus>,
+    card: Option<Card>, // Tanınmış kart (tür, CID, kapasite); None ise kart yok veya tanınmadı
 }
 
 impl SdCardReader {
     const fn new(controller_base: usize) -> Self {
         SdCardReader {
-            controller_base,
-            card_inserted: false, // Başlangıçta kart yok varsayalım
-            card_initialized: false,
+            host: Sdhci::new(MmioBus::new(controller_base, SD_HOST_SLOT)),
+            card: None,
         }
     }
 
     // SD kart denetleyicisini ve kartı başlatır.
-    // TODO: SD kart protokolüne göre doldurun (Power Up, Reset, CMD0, CMD8, ACMD41 vb.)
-    // Bu karmaşık bir protokoldür. Kartın takılı olup olmadığını kontrol etmelidir.
-    pub fn init(&mut self) -> Result<(), &'static str> {
+    // Kart tanınır (CMD8, ACMD41, CMD2, CMD3 ...), türü (SDSC, SDHC/SDXC) ve kapasitesi CSD'den okunur.
+    pub fn init(&mut self) -> Result<(), SahneError> {
         printk!("SD kart sürücüsü başlatılıyor...\n");
+        self.card = None;
+        self.host.reset()?;
+
+        let card = match mmc::init_sd(&mut self.host) {
+            Ok(card) => card,
+            Err(CardError::NoCard) => {
+                printk!("SD kart takılı değil.\n");
+                return Err(SahneError::ResourceNotFound); // SD kart takılı değil
+            }
+            Err(err) => {
+                printk!("SD kart tanınamadı: {:?}\n", err);
+                return Err(err.into());
+            }
+        };
+        let dma = self.host.enable_adma();
+        printk!("SD kart sürücüsü başlatıldı ve kart bulundu: {} ({}).\n", card, if dma { "ADMA2" } else { "PIO" });
+        self.card = Some(card);
+        Ok(())
+    }
 
-        // TODO: Kartın takılı olup olmadığını kontrol et (donanıma özel pin veya register)
-         self.card_inserted = unsafe { (rs_io::mmio_read32(self.controller_base + CARD_DETECT_REG) & INSERTED_BIT) != 0 };
-        self.card_inserted = true; // Şimdilik takılı varsayalım
-
-        if !self.card_inserted {
-            printk!("SD kart takılı değil.\n");
-            self.card_initialized = false;
-            return Err("SD kart takılı değil");
-        }
-
-        // TODO: Temel SD kart initsiyalizasyon komutlarını gönder (CMD0, CMD8, ACMD41, CMD2, CMD3 vb.)
-        // Bu adım kart tipini (SDHC, SDXC, SDSC) ve kapasiteyi belirler.
-         unsafe { ... }
-
-        self.card_initialized = true;
-        printk!("SD kart sürücüsü başlatıldı ve kart bulundu.\n");
-        Ok(()) // Başarılı varsayalım
-    }
-
-    // Belirtilen blok numarasından veri okur.
-    // buffer, en az SD_BLOCK_SIZE bayt boyutunda olmalıdır.
-    // TODO: SD kart protokolüne göre doldurun (CMD17 Single Block Read veya CMD18 Multiple Block Read).
-    pub fn read_block(&self, block_address: u32, buffer: &mut [u8]) -> Result<(), &'static str> {
-         if !self.card_initialized { return Err("SD kart initsiyalize edilmedi"); }
-         if buffer.len() < SD_BLOCK_SIZE { return Err("Okuma buffer boyutu yetersiz"); }
-
-          printk!("SD: Blok {} okunuyor...\n", block_address);
-
-        // TODO: SD okuma komutlarını gönder (CMD17 veya CMD18)
-        // Argüman olarak blok adresini kullan (SDHC/SDXC için blok adresi, SDSC için bayt adresi)
-        // Data Register (PIO) veya DMA kullanarak veriyi oku
-        // Durum registerlarını kontrol ederek işlemin tamamlanmasını bekle
-        unsafe {
-             // Örnek (PIO - basitleştirilmiş):
-              rs_io::mmio_write32(self.controller_base + SD_ARG_REG_OFFSET, block_address);
-              rs_io::mmio_write32(self.controller_base + SD_CMD_REG_OFFSET, 17); // CMD17
-             // ... Veriyi oku ve buffera kopyala ...
-        }
-
-        Ok(()) // Başarılı varsayalım
-    }
-
-    // Belirtilen blok numarasına veri yazar.
-    // data, en az SD_BLOCK_SIZE bayt boyutunda olmalıdır.
-    // TODO: SD kart protokolüne göre doldurun (CMD24 Single Block Write veya CMD25 Multiple Block Write).
-    pub fn write_block(&self, block_address: u32, data: &[u8]) -> Result<(), &'static str> {
-        if !self.card_initialized { return Err("SD kart initsiyalize edilmedi"); }
-        if data.len() < SD_BLOCK_SIZE { return Err("Yazma data boyutu yetersiz"); }
-        // printk!("SD: Blok {} yazılıyor...\n", block_address);
-
-        // TODO: SD yazma komutlarını gönder (CMD24 veya CMD25)
-        // Argüman olarak blok adresini kullan
-        // Data Register (PIO) veya DMA kullanarak veriyi yaz
-        // Durum registerlarını kontrol ederek işlemin tamamlanmasını bekle
-         unsafe {
-            // Örnek (PIO - basitleştirilmiş):
-              rs_io::mmio_write32(self.controller_base + SD_ARG_REG_OFFSET, block_address);
-              rs_io::mmio_write32(self.controller_base + SD_CMD_REG_OFFSET, 24); // CMD24
-             // ... Veriyi data registerına yaz ...
-        }
+    fn read(&mut self, first: u64, segments: &mut [&mut [u8]]) -> Result<(), SahneError> {
+        let card = self.card.ok_or(SahneError::ResourceNotFound)?; // SD kart initsiyalize edilmedi
+        Ok(card.read(&mut self.host, first, segments)?)
+    }
 
-        Ok(()) // Başarılı varsayalım
+    fn write(&mut self, first: u64, segments: &[&[u8]]) -> Result<(), SahneError> {
+        let card = self.card.ok_or(SahneError::ResourceNotFound)?; // SD kart initsiyalize edilmedi
+        Ok(card.write(&mut self.host, first, segments)?)
     }
 
-    // SD kartın toplam blok sayısını döndürür.
-    // TODO: Gerçek kapasiteyi kartın CSD registerından okuyarak bulun.
-    #[allow(dead_code)]
-    pub fn block_count(&self) -> Option<u32> {
-         if !self.card_initialized { return None; }
-         // Örnek sabit değer dönelim, gerçekte CSD'den hesaplanmalı
-         Some(50000000 / SD_BLOCK_SIZE as u32) // Örnek: 50MB varsayımsal kart boyutu
+    // SD kartın toplam blok sayısını döndürür (CSD'den; kart yoksa 0).
+    pub fn block_count(&self) -> u64 {
+        self.card.map_or(0, |card| card.blocks)
     }
 
     // SD kart blok boyutunu döndürür.
-    #[allow(dead_code)]
     pub fn block_size(&self) -> usize {
-        SD_BLOCK_SIZE
+        mmc::BLOCK_SIZE
     }
 
-    // Kartın takılı olup olmadığını kontrol eder.
-     #[allow(dead_code)]
+    // Kart takılı mı? Denetleyicinin kart algılama durumu okunur.
     pub fn is_inserted(&self) -> bool {
-        // TODO: Kart takılı pini/registerını gerçek zamanlı olarak oku.
-        self.card_inserted // Init sırasındaki durumu dönelim (basitlik)
+        self.host.card_present()
+    }
+
+    // Yazma koruma anahtarı açık mı?
+    pub fn is_write_protected(&self) -> bool {
+        self.host.write_protected()
     }
 }
 
 // SD sürücüsünü korumak için global Mutex
 static SD_DRIVER: Mutex<SdCardReader> = Mutex::new(SdCardReader::new(SD_CONTROLLER_BASE_ADDRESS));
 
-// Sürücüyü başlatmak için dışarıdan çağrılacak fonksiyon
-pub fn init() -> Result<(), &'static str> {
-    SD_DRIVER.lock().init()
+// SD kartın BlockDevice yüzü. Scatter list'ler tek komutla aktarılır.
+struct SdDevice;
+
+static SD_DEVICE: SdDevice = SdDevice;
+
+impl BlockDevice for SdDevice {
+    fn block_size(&self) -> usize {
+        SD_DRIVER.lock().block_size()
+    }
+
+    fn block_count(&self) -> u64 {
+        SD_DRIVER.lock().block_count()
+    }
+
+    fn read_blocks(&self, first: u64, buffer: &mut [u8]) -> Result<(), SahneError> {
+        self.read_blocks_scatter(first, &mut [buffer])
+    }
+
+    fn write_blocks(&self, first: u64, buffer: &[u8]) -> Result<(), SahneError> {
+        self.write_blocks_gather(first, &[buffer])
+    }
+
+    fn read_blocks_scatter(&self, first: u64, segments: &mut [&mut [u8]]) -> Result<(), SahneError> {
+        block::check_segments(self, first, segments.iter().map(|segment| segment.len()))?;
+        SD_DRIVER.lock().read(first, segments)
+    }
+
+    fn write_blocks_gather(&self, first: u64, segments: &[&[u8]]) -> Result<(), SahneError> {
+        if self.is_read_only() {
+            return Err(SahneError::PermissionDenied);
+        }
+        block::check_segments(self, first, segments.iter().map(|segment| segment.len()))?;
+        SD_DRIVER.lock().write(first, segments)
+    }
+
+    fn is_read_only(&self) -> bool {
+        SD_DRIVER.lock().is_write_protected()
+    }
 }
 
-// Blok okumak için dışarıdan çağrılacak fonksiyon
-#[allow(dead_code)]
-pub fn read_block(block_address: u32, buffer: &mut [u8]) -> Result<(), &'static str> {
-    SD_DRIVER.lock().read_block(block_address, buffer)
+// Kart tanınırsa "sdcard1" ve bölümlerini ("sdcard1p<n>") kaydeder.
+fn attach_card() -> Result<(), SahneError> {
+    SD_DRIVER.lock().init()?;
+    storage::register_disk(SD_DISK_NAME, &SD_DEVICE)
 }
 
-// Blok yazmak için dışarıdan çağrılacak fonksiyon
-#[allow(dead_code)]
-pub fn write_block(block_address: u32, data: &[u8]) -> Result<(), &'static str> {
-    SD_DRIVER.lock().write_block(block_address, data)
+// Çıkarılan kartın kaynaklarını kaldırır. Sürücü kilidi tutulmaz: unregister_disk önbelleği kilitler.
+fn detach_card() {
+    let _ = storage::unregister_disk(SD_DISK_NAME);
+    SD_DRIVER.lock().card = None;
+    printk!("SD kart çıkarıldı.\n");
+}
+
+extern "C" fn card_detect_main() -> ! {
+    let mut attached = SD_DRIVER.lock().card.is_some();
+    let mut failed = false; // Tanınamayan kart, değiştirilene kadar yeniden denenmez
+    loop {
+        sched::sleep(CARD_DETECT_INTERVAL_MS);
+        let (changed, present) = {
+            let mut driver = SD_DRIVER.lock();
+            (driver.host.take_card_change(), driver.is_inserted())
+        };
+        if changed {
+            failed = false;
+        }
+        if attached && (changed || !present) {
+            detach_card();
+            attached = false;
+        }
+        if !attached && present && !failed {
+            match attach_card() {
+                Ok(()) => attached = true,
+                Err(err) => {
+                    printk!("SD kart eklenemedi: {:?}\n", err);
+                    failed = true;
+                }
+            }
+        }
+    }
+}
+
+// Sürücüyü başlatmak için dışarıdan çağrılacak fonksiyon (plic::init ve storage::init'ten sonra).
+// Kart bulunursa "sdcard1", bölümleri "sdcard1p<n>" kaynakları olarak kaydedilir. Kart takılı değilse
+// de başarılı döner; kart sonradan takılınca card-detect görevi kaydeder.
+pub fn init() -> Result<(), SahneError> {
+    {
+        let mut driver = SD_DRIVER.lock();
+        if let Err(err) = driver.host.bus_mut().attach_interrupt(SD_IRQ) {
+            printk!("SD: Kesme bağlanamadı ({:?}), durum yoklanacak.\n", err);
+        }
+    }
+    match attach_card() {
+        Err(SahneError::ResourceNotFound) => Ok(()), // Kart yok
+        result => result,
+    }
+}
+
+/// Kart takılma/çıkarılmasını izleyen "sdcd" görevini başlatır (sd::init ve sched::init'ten sonra).
+pub fn start_card_detect() -> Result<(), SahneError> {
+    sched::spawn_kernel_task("sdcd", card_detect_main, CARD_DETECT_STACK_SIZE).map(|_| ())
 }
 
 // Kart takılı mı kontrolü
 #[allow(dead_code)]
 pub fn is_inserted() -> bool {
     SD_DRIVER.lock().is_inserted()
-}
\ No newline at end of file
+}
@@ -0,0 +1,87 @@
+// drivers/sdhci_mmio.rs
+// SDHCI Denetleyicilerinin MMIO Erişimi ve Kesmeleri
+// sdhci.rs'teki denetleyici mantığını gerçek registerlara bağlar. Komut veya veri beklerken görev
+// kesme sinyalini açar ve uyur; kesme işleyicisi sinyali kapatıp (seviye kesmesi tekrar gelmesin)
+// görevi uyandırır. Kesme bağlanmamışsa veya zamanlayıcı henüz çalışmıyorsa (açılış) durum yoklanır.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::rs_io;
+use crate::sahne64::SahneError;
+use crate::sched;
+use super::plic;
+use super::sdhci::{SdhciBus, REG_INT_SIGNAL};
+use super::timer;
+
+/// Desteklenen denetleyici sayısı (eMMC ve SD kart).
+pub const MAX_HOSTS: usize = 2;
+
+// Kesme işleyicisinin kullandığı denetleyici adresleri ve bekleyen görev (ID + 1; 0 = yok).
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY: AtomicUsize = AtomicUsize::new(0);
+static BASES: [AtomicUsize; MAX_HOSTS] = [EMPTY; MAX_HOSTS];
+static WAITERS: [AtomicUsize; MAX_HOSTS] = [EMPTY; MAX_HOSTS];
+const HANDLERS: [fn(); MAX_HOSTS] = [handle_interrupt::<0>, handle_interrupt::<1>];
+
+fn handle_interrupt<const HOST: usize>() {
+    let base = BASES[HOST].load(Ordering::Acquire);
+    unsafe { rs_io::mmio_write32(base + REG_INT_SIGNAL, 0) };
+    let waiter = WAITERS[HOST].swap(0, Ordering::AcqRel);
+    if waiter != 0 {
+        sched::wake_task(waiter - 1);
+    }
+}
+
+/// Bir denetleyicinin register penceresi. `host` kesme tablosundaki yuvadır (0..MAX_HOSTS).
+pub struct MmioBus {
+    base: usize,
+    host: usize,
+    irq: u32, // 0: kesme bağlı değil
+}
+
+impl MmioBus {
+    pub const fn new(base: usize, host: usize) -> Self {
+        MmioBus { base, host, irq: 0 }
+    }
+
+    /// Tamamlanma kesmesini PLIC kaynağı `irq`a bağlar. Başarısız olursa sürücü yoklamaya devam eder.
+    pub fn attach_interrupt(&mut self, irq: u32) -> Result<(), SahneError> {
+        if self.host >= MAX_HOSTS {
+            return Err(SahneError::InvalidParameter);
+        }
+        BASES[self.host].store(self.base, Ordering::Release);
+        plic::register_handler(irq, HANDLERS[self.host])?;
+        self.irq = irq;
+        Ok(())
+    }
+}
+
+impl SdhciBus for MmioBus {
+    fn read(&self, offset: usize) -> u32 {
+        unsafe { rs_io::mmio_read32(self.base + offset) }
+    }
+
+    fn write(&mut self, offset: usize, value: u32) {
+        unsafe { rs_io::mmio_write32(self.base + offset, value) }
+    }
+
+    fn now_ms(&self) -> u64 {
+        timer::now_ticks() / timer::ms_to_ticks(1)
+    }
+
+    fn wait_for_interrupt(&mut self, mask: u32, deadline_ms: u64) {
+        let Some(task_id) = sched::current_task_id() else {
+            return; // Açılış: zamanlayıcı çalışmıyor, yoklanır
+        };
+        if self.irq == 0 {
+            return;
+        }
+        sched::prepare_to_block(Some(deadline_ms.saturating_mul(timer::ms_to_ticks(1))));
+        WAITERS[self.host].store(task_id + 1, Ordering::Release);
+        // Durum zaten oluşmuşsa kesme hemen gelir ve görevi uyandırır; uyandırma kaybolmaz.
+        self.write(REG_INT_SIGNAL, mask);
+        sched::wait_while_blocked();
+        WAITERS[self.host].store(0, Ordering::Release);
+        self.write(REG_INT_SIGNAL, 0);
+    }
+}
@@ -21,7 +21,8 @@ use core::ptr;
 use crate::sahne64::{self, resource, memory, task, SahneError, Handle};
 
 // Minimal EXT2 dosya sistemi kütüphanesi (Kullanıcı alanı kütüphanesi)
-use crate::filesystem::ext::ExtFilesystem; // ext.rs dosyasını filesystem modülü altında varsayalım
+use crate::filesystem::ext::{ExtFilesystem, EXT2_ROOT_INODE}; // ext.rs dosyasını filesystem modülü altında varsayalım
+use crate::filesystem::ext::fsck;
 
 
 // Komut Satırı Argümanları Pars Etmek İçin Basit Yardımcı (Installer'dan kopyalandı)
@@ -75,78 +76,31 @@ impl core::fmt::Write for ConsoleWriter {
     }
 }
 
-// Resource::read kullanarak temel konsol okuyucu
+// Çekirdeğin kanonik konsol satırı en fazla 256 bayt + satır sonudur (tty_io.rs LINE_MAX);
+// bu boyuttaki bir tampona tek okumada bütün bir satır sığar.
+const LINE_BUFFER_SIZE: usize = 257;
+
+// Konsoldan satır okuyucu. Satır düzenleme (backspace, Ctrl-U), yankı ve Ctrl-C / Ctrl-D
+// çekirdeğin TTY katmanında yapılır; burada sadece tamamlanan satır alınır.
 struct ConsoleReader {
      handle: Handle,
-     buffer: Vec<u8>, // Satır tamponu
-     buffer_pos: usize, // Tampon içindeki mevcut pozisyon
+     buffer: [u8; LINE_BUFFER_SIZE],
 }
 
 impl ConsoleReader {
-    fn new(handle: Handle, buffer_capacity: usize) -> Self {
-        ConsoleReader {
-            handle,
-            buffer: Vec::with_capacity(buffer_capacity),
-            buffer_pos: 0,
-        }
-    }
-
-    // Tek bir karakter okumaya çalışır (pollemeye dayalı).
-    fn read_char(&mut self) -> Option<u8> {
-        let mut byte = [0u8; 1];
-        // resource::read syscall'ı burada polling veya bloklama yapabilir.
-        // Blocking syscall, scheduler tarafından ele alınmalıdır.
-        match resource::read(self.handle, &mut byte, 0, 1) { // resource::read(handle, buf, offset, len)
-            Ok(1) => Some(byte[0]),
-            _ => None, // Veri yok veya hata
-        }
+    fn new(handle: Handle) -> Self {
+        ConsoleReader { handle, buffer: [0; LINE_BUFFER_SIZE] }
     }
 
-    // Bir satır okur (Enter'a kadar). Temel satır düzenleme (backspace) yapar.
-    fn read_line(&mut self, console: &mut ConsoleWriter) -> Result<String, SahneError> {
-        self.buffer.clear();
-        self.buffer_pos = 0;
-
-        loop {
-            // Karakter gelene kadar bekle (polling veya blocking read)
-            let byte = loop {
-                if let Some(b) = self.read_char() {
-                    break b;
-                }
-                // Eğer okuma blocking değilse, işlemciyi serbest bırak
-                task::yield_now().unwrap_or_else(|_| { core::hint::spin_loop(); }); // Scheduler varsa yield
-            };
-
-
-            match byte {
-                b'\n' | b'\r' => { // Enter tuşu
-                    // Yeni satır yazdır ve döngüden çık
-                    writeln!(console, "").unwrap();
-                    let line = String::from_utf8(self.buffer.clone()).unwrap_or(String::new()); // Geçersiz UTF8'i boş string yap
-                    return Ok(line);
-                }
-                0x7f | b'\x08' => { // Backspace (ASCII 127 veya 8)
-                    if self.buffer_pos > 0 {
-                        // Tampondan son karakteri sil
-                        self.buffer_pos -= 1;
-                        self.buffer.pop();
-                        // Konsoldan silme: Geri git, boşluk yaz, geri git
-                        write!(console, "\x08 \x08").unwrap();
-                    }
-                }
-                _ => { // Diğer karakterler
-                    // Ekrana yazdır
-                    write!(console, "{}", byte as char).unwrap();
-                    // Tampona ekle (kapasiteyi aşmamaya dikkat et)
-                    if self.buffer.len() < self.buffer.capacity() {
-                        self.buffer.push(byte);
-                        self.buffer_pos += 1;
-                    } else {
-                        // printk!("WARN: Console buffer dolu.\n"); // Buffer dolduysa uyarı
-                    }
-                }
-            }
+    // Bir satır okur (Enter'a kadar bekler), satır sonu olmadan döndürür.
+    // Ctrl-D ile dosya sonunda None, Ctrl-C'de Err(SahneError::Interrupted) döner.
+    fn read_line(&mut self) -> Result<Option<String>, SahneError> {
+        let count = resource::read(self.handle, &mut self.buffer)?;
+        if count == 0 {
+            return Ok(None);
         }
+        let line = self.buffer[..count].strip_suffix(b"\n").unwrap_or(&self.buffer[..count]);
+        Ok(Some(String::from_utf8(line.to_vec()).unwrap_or(String::new()))) // Geçersiz UTF8'i boş string yap
     }
 }
 
@@ -159,42 +113,27 @@ fn parse_command_line(line: &str) -> Vec<String> {
 }
 
 // Çalıştırılabilir Dosyayı Bulma Fonksiyonu
-// Dosya sisteminde (örn. /bin) komut adını arar.
+// Eğik çizgi içeren komutlar yol olarak çözülür ("/bin/hello", "./hello"); diğerleri /bin'de aranır.
+// Yoldaki sembolik bağlantılar izlenir (örn. /bin/sh -> hello.sbxe).
 // Dönüş değeri: Çalıştırılabilir dosyanın i-node numarası (varsa) veya hata.
 fn find_executable(command: &str, fs: &ExtFilesystem) -> Result<Option<u32>, SahneError> {
     // TODO: Çalıştırılabilir yolları (örn. /bin) bir listede tutmak ve sırayla aramak gerekir (PATH gibi).
     let search_path = "/bin"; // Şimdilik sadece /bin'de arayalım
+    let path = if command.contains('/') { command.to_string() } else { format!("{}/{}", search_path, command) };
 
-    // /bin dizininin i-node'unu bul
-    let root_inode = fs.root_directory()?;
-    let bin_dir_inode_num = fs.lookup(root_inode.inode, search_path).unwrap_or(0); // "/bin" i-node'unu ara
-
-    if bin_dir_inode_num == 0 {
-         printk!("WARN: /bin dizini bulunamadı.\n");
-        return Ok(None); // /bin dizini yok
-    }
+    // Kabuğun henüz çalışma dizini yok (cd); göreli yollar kökten çözülür.
+    let inode_number = match fs.resolve(&path, EXT2_ROOT_INODE) {
+        Ok(inode_number) => inode_number,
+        Err(SahneError::ResourceNotFound) | Err(SahneError::InvalidOperation) => return Ok(None),
+        Err(err) => return Err(err),
+    };
 
-    let bin_dir_inode = fs.read_inode(bin_dir_inode_num)?;
-
-    // /bin dizini içinde komut adını ara
-    let entries = fs.list_directory(&bin_dir_inode)?;
-    for entry in entries {
-        if entry.inode != 0 { // Geçersiz olmayan girişler için
-            // Dizin girdisindeki dosya adını al
-            // Entry'nin name alanı sabit boyutlu [u8; 255].
-            // name_len'e göre slice alıp str'ye çevir.
-            let entry_name = str::from_utf8(&entry.name[0..entry.name_len as usize]).unwrap_or("<geçersiz>");
-
-            if entry_name == command {
-                // printk!("DEBUG: Çalıştırılabilir bulundu: {} -> i-node {}\n", command, entry.inode);
-                // Dosyanın gerçekten çalıştırılabilir olup olmadığını kontrol etmek gerek (i-node i_mode ve izinler)
-                // Şimdilik sadece ada bakıyoruz.
-                return Ok(Some(entry.inode)); // Bulundu, i-node numarasını döndür
-            }
-        }
+    // Dosyanın gerçekten çalıştırılabilir olup olmadığını kontrol etmek gerek (i-node i_mode ve izinler)
+    // Şimdilik sadece normal dosya olmasına bakıyoruz.
+    if !fs.read_inode(inode_number)?.is_file() {
+        return Ok(None);
     }
-
-    Ok(None) // Bulunamadı
+    Ok(Some(inode_number)) // Bulundu, i-node numarasını döndür
 }
 
 
@@ -259,6 +198,55 @@ fn execute_program(
 }
 
 
+// Denetim raporunu konsola yazar
+fn print_fsck_report(report: &fsck::Report, console: &mut ConsoleWriter) {
+    for finding in &report.findings {
+        let status = if finding.fixed { "düzeltildi" } else { "düzeltilmedi" };
+        writeln!(console, "fsck: {} ({})", finding.problem, status).unwrap();
+    }
+    writeln!(console, "fsck: {} i-node, {} blok kullanımda; {} sorun, {} düzeltilmedi.",
+        report.used_inodes, report.used_blocks, report.findings.len(), report.unfixed()).unwrap();
+}
+
+// emmc0 üzerindeki dosya sistemini denetler (dahili "fsck" komutu).
+// Kabuğun kendi (salt okunur) bağlaması önbellekte sadece grup tanımlayıcılarını tutar; onarımdan sonra
+// değişen serbest sayılarını görmemesi program bulup çalıştırmayı etkilemez.
+fn run_fsck(mode: fsck::Mode, console: &mut ConsoleWriter) -> Result<(), SahneError> {
+    let access = match mode {
+        fsck::Mode::Report => resource::MODE_READ,
+        fsck::Mode::Repair => resource::MODE_READ | resource::MODE_WRITE,
+    };
+    let device_handle = resource::acquire("emmc0", access)?;
+    let result = ExtFilesystem::mount(device_handle).and_then(|mut fs| {
+        let report = fsck::check(&mut fs, mode)?;
+        print_fsck_report(&report, console);
+        fs.unmount().map(|_| ())
+    });
+    let _ = resource::release(device_handle);
+    result
+}
+
+// Açılış denetimi: dosya sistemi temiz kapatılmadıysa (örn. elektrik kesintisi), hatalı işaretliyse
+// veya bağlama sayısı/denetim aralığı dolduysa onarım kipinde denetler.
+fn check_root_filesystem(console: &mut ConsoleWriter) {
+    let device_handle = match resource::acquire("emmc0", resource::MODE_READ | resource::MODE_WRITE) {
+        Ok(handle) => handle,
+        Err(_) => return, // Hata aşağıdaki bağlamada raporlanır
+    };
+    let result = ExtFilesystem::mount(device_handle).and_then(|mut fs| {
+        if let Some(report) = fsck::check_at_boot(&mut fs)? {
+            writeln!(console, "Dosya sistemi temiz kapatılmamış, denetleniyor...").unwrap();
+            print_fsck_report(&report, console);
+        }
+        fs.unmount().map(|_| ())
+    });
+    if let Err(err) = result {
+        writeln!(console, "Uyarı: Açılış dosya sistemi denetimi yapılamadı: {:?}", err).unwrap();
+    }
+    let _ = resource::release(device_handle);
+}
+
+
 // Dahili Komutları İşleme Fonksiyonu
 fn handle_builtin_command(
     command: &str,
@@ -294,6 +282,17 @@ fn handle_builtin_command(
              writeln!(console, "DEBUG: Built-in list implemente edilmedi.").unwrap();
              Ok(())
         }
+        "fsck" => { // Dosya sistemi denetimi: varsayılan sadece rapor, "-y" ile onarım
+            let mode = match args.get(1).map(|arg| arg.as_str()) {
+                None => fsck::Mode::Report,
+                Some("-y") => fsck::Mode::Repair,
+                Some(_) => {
+                    writeln!(console, "Kullanım: fsck [-y]").unwrap();
+                    return Ok(());
+                }
+            };
+            run_fsck(mode, console)
+        }
         // TODO: Diğer dahili komutları ekle (cd, pwd, help vb.)
         _ => Err(SahneError::NotSupported), // Bilinmeyen dahili komut (bu durum find_executable'a düşmemeli)
     }
@@ -310,10 +309,13 @@ pub extern "C" fn main(argc: usize, argv: *const *const u8) -> ! {
     // Konsol kaynağını edin (Okuma ve Yazma için)
     let console_handle = resource::acquire("console", resource::MODE_READ | resource::MODE_WRITE).unwrap_or_else(|_| { loop { core::hint::spin_loop(); } });
     let mut console_writer = ConsoleWriter { handle: console_handle };
-    let mut console_reader = ConsoleReader::new(console_handle, 256); // 256 bayt buffer
+    let mut console_reader = ConsoleReader::new(console_handle);
 
     writeln!(console_writer, "SahneBox Komut Satırı Kabuğu (sh64) Başlıyor.").unwrap();
 
+    // Bağlamadan önce gerekiyorsa dosya sistemini denetle ve onar
+    check_root_filesystem(&mut console_writer);
+
     // Dosya sistemini bağla (Çalıştırılabilirleri bulmak için)
      let target_device_handle = resource::acquire("emmc0", resource::MODE_READ).unwrap_or_else(|_| {
         writeln!(console_writer, "Hata: Hedef cihaz (emmc0) kaynağına erişilemedi.").unwrap();
@@ -343,8 +345,14 @@ pub extern "C" fn main(argc: usize, argv: *const *const u8) -> ! {
         write!(console_writer, "# ").unwrap();
 
         // Kullanıcıdan bir satır komut oku
-        let command_line = match console_reader.read_line(&mut console_writer) {
-            Ok(line) => line,
+        let command_line = match console_reader.read_line() {
+            Ok(Some(line)) => line,
+            Ok(None) => {
+                 // Ctrl-D: kabuk kapanmaz (sistemin tek kabuğu), yeni satırda istemi yeniden göster.
+                 writeln!(console_writer, "").unwrap();
+                 continue;
+            }
+            Err(SahneError::Interrupted) => continue, // Ctrl-C: satır çekirdekte atıldı, "^C" yazıldı
             Err(_) => {
                  writeln!(console_writer, "Hata: Girdi okunamadı.").unwrap();
                  continue; // Döngüye devam et
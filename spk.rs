@@ -0,0 +1,232 @@
+// package_manager/spm/src/spk.rs
+// .spk Paket Formatı
+// Paket dosyası bellekte bir bayt dizisi olarak parse edilir; çekirdek API'sine bağımlı değildir.
+//
+// Disk üzerindeki format (little-endian):
+//   Başlık : magic "SSPK" (u32), format sürümü (u16), dosya sayısı (u16),
+//            isim uzunluğu (u8), sürüm uzunluğu (u8), isim, sürüm
+//   Giriş  : yol uzunluğu (u16), veri offseti (u32), veri boyutu (u32), yol   (dosya sayısı kadar)
+//   Veri   : dosya içerikleri (offsetler dosyanın başına göredir)
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str;
+
+use crate::sahne64::SahneError;
+
+// .spk sihirli sayısı ("SSPK" ASCII)
+pub const SPK_MAGIC: u32 = 0x4B505353;
+// Desteklenen format sürümü
+pub const SPK_VERSION: u16 = 1;
+
+const HEADER_SIZE: usize = 10; // magic + sürüm + dosya sayısı + iki uzunluk
+const ENTRY_HEADER_SIZE: usize = 10; // yol uzunluğu + offset + boyut
+
+// Parse edilmiş paket (disk üzerindeki formatı temsil etmez, parse edildikten sonraki hali)
+pub struct SahneBoxPackage {
+    pub name: String,
+    pub version: String,
+    // ... diğer metadata (örn. description)
+    pub files: Vec<PackageFile>, // Paketin içindeki dosyaların listesi
+}
+
+pub struct PackageFile {
+    pub path: String, // Kurulacağı mutlak yol (örn. /bin/my_program)
+    pub offset: usize, // Dosya verisinin .spk dosyasındaki offseti
+    pub size: usize, // Dosya verisinin boyutu
+    // TODO: Checksum eklenebilir
+}
+
+impl PackageFile {
+    /// Dosyanın verisini paket içeriğinden döndürür.
+    /// Aralık parse sırasında doğrulandığı için aynı paket verisiyle çağrıldığında panik etmez.
+    pub fn data<'a>(&self, package_data: &'a [u8]) -> &'a [u8] {
+        &package_data[self.offset..self.offset + self.size]
+    }
+}
+
+// Paket verisi üzerinde sınır kontrollü okuyucu.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SahneError> {
+        let end = self.position.checked_add(len).ok_or(SahneError::InvalidParameter)?;
+        let bytes = self.data.get(self.position..end).ok_or(SahneError::InvalidParameter)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, SahneError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SahneError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, SahneError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self, len: usize) -> Result<String, SahneError> {
+        let text = str::from_utf8(self.take(len)?).map_err(|_| SahneError::InvalidParameter)?;
+        let mut owned = String::new();
+        owned.try_reserve_exact(text.len()).map_err(|_| SahneError::OutOfMemory)?;
+        owned.push_str(text);
+        Ok(owned)
+    }
+}
+
+// Kurulum yolu mutlak olmalı ve hedef dosya sisteminin dışına çıkamamalı.
+fn is_valid_install_path(path: &str) -> bool {
+    path.starts_with('/')
+        && path.len() > 1
+        && !path.ends_with('/')
+        && !path.contains('\0')
+        && path.split('/').skip(1).all(|component| !component.is_empty() && component != "." && component != "..")
+}
+
+/// .spk paketini parse eder. Paket verisi güvenilmeyen kaynaktan (kurulum medyası) geldiği için
+/// tüm uzunluk ve offsetler doğrulanır; bozuk bir paket panik yerine InvalidParameter ile reddedilir.
+pub fn parse(data: &[u8]) -> Result<SahneBoxPackage, SahneError> {
+    if data.len() < HEADER_SIZE {
+        return Err(SahneError::InvalidParameter);
+    }
+    let mut reader = Reader { data, position: 0 };
+
+    if reader.u32()? != SPK_MAGIC {
+        return Err(SahneError::InvalidParameter); // Geçersiz format
+    }
+    if reader.u16()? != SPK_VERSION {
+        return Err(SahneError::NotSupported); // Bilinmeyen format sürümü
+    }
+    let file_count = reader.u16()? as usize;
+    let name_len = reader.u8()? as usize;
+    let version_len = reader.u8()? as usize;
+    let name = reader.string(name_len)?;
+    let version = reader.string(version_len)?;
+    if name.is_empty() || name.contains('/') {
+        return Err(SahneError::InvalidParameter);
+    }
+
+    // Her giriş en az ENTRY_HEADER_SIZE bayt olduğundan dosya sayısı, tahsisten önce sınırlanabilir.
+    if file_count > (data.len() - reader.position) / ENTRY_HEADER_SIZE {
+        return Err(SahneError::InvalidParameter);
+    }
+    let mut files = Vec::new();
+    files.try_reserve_exact(file_count).map_err(|_| SahneError::OutOfMemory)?;
+
+    for _ in 0..file_count {
+        let path_len = reader.u16()? as usize;
+        let offset = reader.u32()? as usize;
+        let size = reader.u32()? as usize;
+        let path = reader.string(path_len)?;
+
+        if !is_valid_install_path(&path) {
+            return Err(SahneError::InvalidParameter);
+        }
+        match offset.checked_add(size) {
+            Some(end) if end <= data.len() => {}
+            _ => return Err(SahneError::InvalidParameter), // Veri paketin dışında
+        }
+        if files.iter().any(|file: &PackageFile| file.path == path) {
+            return Err(SahneError::InvalidParameter); // Aynı yol iki kez
+        }
+
+        files.push(PackageFile { path, offset, size });
+    }
+
+    Ok(SahneBoxPackage { name, version, files })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // fixtures/make_fixtures.sh ile üretilen iki dosyalı örnek paket.
+    static HELLO: &[u8] = include_bytes!("fixtures/hello.spk");
+
+    // Başlıktan sonraki ilk girişin offseti: 10 bayt başlık + "hello" + "1.0.0"
+    const FIRST_ENTRY: usize = HEADER_SIZE + 5 + 5;
+
+    #[test]
+    fn parses_fixture_package() {
+        let package = parse(HELLO).unwrap();
+        assert_eq!(package.name, "hello");
+        assert_eq!(package.version, "1.0.0");
+        assert_eq!(package.files.len(), 2);
+        assert_eq!(package.files[0].path, "/bin/hello");
+        assert_eq!(package.files[0].size, 32);
+        assert_eq!(package.files[1].path, "/etc/hello.conf");
+        assert_eq!(package.files[1].data(HELLO), b"greeting=Merhaba\n");
+    }
+
+    #[test]
+    fn rejects_bad_header() {
+        let mut bad_magic = HELLO.to_vec();
+        bad_magic[0] = b'X';
+        assert_eq!(parse(&bad_magic).err(), Some(SahneError::InvalidParameter));
+
+        let mut bad_version = HELLO.to_vec();
+        bad_version[4] = 9;
+        assert_eq!(parse(&bad_version).err(), Some(SahneError::NotSupported));
+
+        assert_eq!(parse(&HELLO[..HEADER_SIZE + 3]).err(), Some(SahneError::InvalidParameter));
+    }
+
+    #[test]
+    fn rejects_escaping_paths() {
+        // İlk girişin yolunu ("/bin/hello") aynı uzunlukta geçersiz yollarla değiştir
+        let mut escaping = HELLO.to_vec();
+        let path_start = FIRST_ENTRY + ENTRY_HEADER_SIZE;
+        escaping[path_start..path_start + 10].copy_from_slice(b"/../../aaa");
+        assert_eq!(parse(&escaping).err(), Some(SahneError::InvalidParameter));
+
+        escaping[path_start..path_start + 10].copy_from_slice(b"bin/hello/");
+        assert_eq!(parse(&escaping).err(), Some(SahneError::InvalidParameter));
+    }
+
+    #[test]
+    fn rejects_data_outside_package() {
+        let mut truncated = HELLO.to_vec();
+        truncated.pop();
+        assert_eq!(parse(&truncated).err(), Some(SahneError::InvalidParameter));
+
+        let mut huge_size = HELLO.to_vec();
+        huge_size[FIRST_ENTRY + 6..FIRST_ENTRY + 10].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(parse(&huge_size).err(), Some(SahneError::InvalidParameter));
+    }
+
+    // Rastgele bozulmuş paketlerde parse'ın panik etmediğini ve kabul edilen her
+    // dosyanın verisine güvenle erişilebildiğini doğrular.
+    #[test]
+    fn corrupted_packages_never_panic() {
+        let mut state: u64 = 0x5350_4B5F_4655_5A5A;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..5000 {
+            let mut package_data = HELLO.to_vec();
+            for _ in 0..1 + next() % 3 {
+                let position = (next() % package_data.len() as u64) as usize;
+                package_data[position] = next() as u8;
+            }
+            package_data.truncate((next() % (HELLO.len() as u64 + 1)) as usize);
+            if let Ok(package) = parse(&package_data) {
+                for file in &package.files {
+                    assert!(is_valid_install_path(&file.path));
+                    let _ = file.data(&package_data);
+                }
+            }
+        }
+    }
+}
@@ -20,7 +20,11 @@ use crate::sahne64::{self, resource, memory, task, SahneError, Handle};
 
 // Minimal EXT2 dosya sistemi kütüphanesi (Kullanıcı alanı kütüphanesi)
 // Bu kütüphane, çekirdeğin resource::read/write sistem çağrılarını kullanarak çalışacaktır.
-use crate::filesystem::ext::ExtFilesystem; // ext.rs dosyasını filesystem modülü altında varsayalım
+use crate::filesystem::ext::{ExtFilesystem, EXT2_ROOT_INODE}; // ext.rs dosyasını filesystem modülü altında varsayalım
+
+// .spk paket formatı (spk.rs); host üzerinde de test edilebilen saf parse kodu
+mod spk;
+use spk::SahneBoxPackage;
 
 
 // Komut Satırı Argümanları Pars Etmek İçin Basit Yardımcı
@@ -78,91 +82,74 @@ impl core::fmt::Write for ConsoleWriter {
 }
 
 
-// Basit Paket Formatı (.spk) yapısı (Disk üzerindeki formatı temsil etmez, parse edildikten sonraki hali)
-struct SahneBoxPackage {
-    name: String,
-    version: String,
-    // ... diğer metadata (örn. description)
-    files: Vec<PackageFile>, // Paketin içindeki dosyaların listesi
-}
+// Kurulu paketlerin listesi: her satırda "<ad> v<sürüm>".
+const INSTALLED_LIST_DIR: &str = "/etc/spm";
+const INSTALLED_LIST_PATH: &str = "/etc/spm/installed.list";
 
-struct PackageFile {
-    path: String, // Paketin içinde bulunduğu yol (örn. /bin/my_program)
-    offset: usize, // Dosya verisinin .spk dosyasındaki offseti
-    size: usize, // Dosya verisinin boyutu
-    // TODO: Checksum eklenebilir
+// Mutlak `path` dizinini (ara dizinler dahil) gerekirse oluşturur ve i-node numarasını döndürür.
+fn create_directory_all(fs: &mut ExtFilesystem, path: &str) -> Result<u32, SahneError> {
+    let mut dir = EXT2_ROOT_INODE;
+    for component in path.split('/').filter(|component| !component.is_empty()) {
+        dir = match fs.resolve(component, dir) {
+            Ok(existing) => existing,
+            Err(SahneError::ResourceNotFound) => fs.mkdir(dir, component)?,
+            Err(err) => return Err(err),
+        };
+    }
+    Ok(dir)
 }
 
-// .spk Paketini okuma ve parse etme fonksiyonu
-// Basitlik için, package_file_handle'ın tüm .spk dosyasını temsil ettiği varsayılır.
-// Karmaşık parse işlemleri burada yapılmalıdır.
-fn parse_spk_package(package_file_handle: Handle) -> Result<SahneBoxPackage, SahneError> {
-    // TODO: .spk dosyasını okuyun ve parse edin.
-    // Örnek:
-    // 1. Dosyanın başından metadata okuyun (isim, versiyon, dosya sayısı).
-    // 2. Dosya listesini okuyun (yol, offset, boyut).
-    // 3. PackageFile structlarını oluşturun.
-    // Bu çok basitleştirilmiş bir örnektir. Gerçek parse karmaşıktır.
-
-    // Şimdilik yer tutucu implementasyon: Sabit bir paket döndür
-    // Bu, sadece yapının nasıl olacağını gösterir. Gerçekte dosyadan okunmalıdır.
-    let dummy_package = SahneBoxPackage {
-        name: "my_dummy_app".to_string(),
-        version: "1.0.0".to_string(),
-        files: vec![
-            PackageFile {
-                path: "/bin/my_dummy_app".to_string(),
-                offset: 1024, // Verinin başladığı varsayımsal offset
-                size: 5120,  // Varsayımsal boyut (5KB)
-            },
-            PackageFile {
-                path: "/etc/my_dummy_app.conf".to_string(),
-                offset: 1024 + 5120, // Bir önceki dosyanın bittiği yerden sonra
-                size: 256,
-            },
-        ],
-    };
-
-    Ok(dummy_package) // Başarılı varsayalım
+// `dir` içindeki `name` dosyasını açar; yoksa oluşturur. `truncate` ise var olan içerik silinir.
+fn open_or_create_file(fs: &mut ExtFilesystem, dir: u32, name: &str, truncate: bool) -> Result<u32, SahneError> {
+    match fs.lookup(dir, name) {
+        Ok(existing) => {
+            if truncate {
+                fs.truncate_file(existing, 0)?;
+            }
+            Ok(existing)
+        }
+        Err(SahneError::ResourceNotFound) => fs.create_file(dir, name),
+        Err(err) => Err(err),
+    }
 }
 
-
 // Paketi Kurma Fonksiyonu
 // package: Parse edilmiş paket yapısı.
-// source_fs: Paketin bulunduğu dosya sistemi (genellikle kurulum medyası).
+// package_data: .spk dosyasının bellekteki içeriği (package bundan parse edilmiş olmalı).
 // target_fs: Kurulumun yapılacağı dosya sistemi (genellikle eMMC).
 fn install_package(
     package: &SahneBoxPackage,
-    package_file_handle: Handle, // .spk dosyasının handle'ı
+    package_data: &[u8],
     target_fs: &mut ExtFilesystem,
     console: &mut ConsoleWriter,
 ) -> Result<(), SahneError> {
     writeln!(console, "Paket Kuruluyor: {} (v{})", package.name, package.version).unwrap();
 
-    // Kurulum veritabanına paket adını ekle (Basit dosya)
-    // TODO: "/etc/spm/installed.list" dosyasını aç/oluştur ve paket adını ekle.
-    // Bu, ExtFilesystem'in yazma yeteneği gerektirir.
-    // let mut installed_list_file = target_fs.open_file("/etc/spm/installed.list", resource::MODE_WRITE | resource::MODE_CREATE | resource::MODE_APPEND)?; // open_file fonksiyonu yok
-
     // Paketin içindeki dosyaları hedef dosya sistemine kopyala
     for file in &package.files {
         writeln!(console, "  Dosya Kopyalanıyor: {}", file.path).unwrap();
 
-        // TODO: Hedef dizinleri oluştur (ExtFilesystem::create_directory gerektirir)
-        // Örn: target_fs.create_directory_all(parent_dir_of(file.path))?;
+        // Yollar spk::parse tarafından doğrulandı: mutlak, "." / ".." bileşeni yok, '/' ile bitmiyor.
+        let (dir_path, name) = file.path.rsplit_once('/').ok_or(SahneError::InvalidParameter)?;
+        let dir = create_directory_all(target_fs, dir_path)?;
 
-        // Hedef dosyayı oluştur/aç (ExtFilesystem::open_file/create_file gerektirir)
-        // let mut target_file_handle = target_fs.create_file(&file.path)?; // create_file fonksiyonu yok
+        // Var olan dosyanın üzerine yazılır (paket güncellemesi).
+        let target = open_or_create_file(target_fs, dir, name, true)?;
 
-        // Dosya verisini kaynaktan oku (.spk dosyasından)
-        let mut file_buffer = alloc::vec![0u8; file.size];
-        // resource::read(package_file_handle, &mut file_buffer, file.offset, file.size)?; // resource::read'in offsetli versiyonu
-
-        // Dosya verisini hedefe yaz (ExtFilesystem::write_file gerektirir)
-        // target_fs.write_file(target_file_handle, &file_buffer)?; // write_file fonksiyonu yok
+        // Dosya verisi .spk içeriğinden alınır (aralık spk::parse tarafından doğrulandı)
+        let file_data = file.data(package_data);
+        if target_fs.write_file(target, 0, file_data)? != file_data.len() {
+            return Err(SahneError::OutOfMemory); // Kısa yazma: disk doldu
+        }
+    }
 
-        // Handle'ları serbest bırak
-        // resource::release(target_file_handle)?;
+    // Kurulum veritabanına paketi ekle
+    let list_dir = create_directory_all(target_fs, INSTALLED_LIST_DIR)?;
+    let list_name = &INSTALLED_LIST_PATH[INSTALLED_LIST_DIR.len() + 1..];
+    let list = open_or_create_file(target_fs, list_dir, list_name, false)?;
+    let line = format!("{} v{}\n", package.name, package.version);
+    if target_fs.append_file(list, line.as_bytes())? != line.len() {
+        return Err(SahneError::OutOfMemory);
     }
 
     writeln!(console, "{} paketi başarıyla kuruldu.", package.name).unwrap();
@@ -171,21 +158,27 @@ fn install_package(
 }
 
 // Yüklü Paketleri Listeleme Fonksiyonu
-// TODO: "/etc/spm/installed.list" dosyasını oku ve içeriğini konsola yazdır.
+// "/etc/spm/installed.list" dosyasını okur ve her satırı konsola yazdırır.
 fn list_installed_packages(target_fs: &mut ExtFilesystem, console: &mut ConsoleWriter) -> Result<(), SahneError> {
      writeln!(console, "Yüklü Paketler:").unwrap();
 
-     // TODO: "/etc/spm/installed.list" dosyasını bul ve oku.
-     // let installed_file_inode = target_fs.lookup(root_dir.inode, "/etc/spm/installed.list")?;
-     // let installed_file_inode = target_fs.read_inode(installed_file_inode_num)?;
-     // let mut content_buffer = alloc::vec![0u8; installed_file_inode.i_size as usize];
-     // target_fs.read_file(&installed_file_inode, &mut content_buffer, 0)?;
-     // let content_str = str::from_utf8(&content_buffer).unwrap_or("<geçersiz içerik>");
-
-     // writeln!(console, "{}", content_str).unwrap();
-
-     writeln!(console, "- ÖrnekPaket1 v1.0.0").unwrap(); // Yer tutucu
-     writeln!(console, "- ÖrnekPaket2 v2.1.0").unwrap(); // Yer tutucu
+     let list = match target_fs.resolve(INSTALLED_LIST_PATH, EXT2_ROOT_INODE) {
+         Ok(list) => list,
+         Err(SahneError::ResourceNotFound) => {
+             writeln!(console, "(yüklü paket yok)").unwrap();
+             return Ok(());
+         }
+         Err(err) => return Err(err),
+     };
+     let inode = target_fs.read_inode(list)?;
+     // Boyut diskten geldiği için tahsis panik etmemeli.
+     let mut content = sahne64::buffer::zeroed_buffer(inode.i_size as usize)?;
+     let read = target_fs.read_file(&inode, &mut content, 0)?;
+     let content = str::from_utf8(&content[..read]).map_err(|_| SahneError::InvalidParameter)?;
+
+     for line in content.lines().filter(|line| !line.is_empty()) {
+         writeln!(console, "- {}", line).unwrap();
+     }
 
      Ok(())
 }
@@ -245,26 +238,33 @@ pub extern "C" fn main(argc: usize, argv: *const *const u8) -> ! {
                  writeln!(console_writer, "Kurulum medyası bağlandı.").unwrap();
 
 
-                 // TODO: Kurulum medyasında paketi (.spk dosyasını) bul (örn. /packages/my_package.spk)
+                 // Kurulum medyasında paketi (.spk dosyasını) bul (örn. /packages/my_package.spk)
                  let package_file_path = format!("/packages/{}.spk", name); // Paket yolu
-                 let packages_dir_inode = installer_fs.root_directory().unwrap(); // Basitlik için root'taki /packages varsayımı
-                 let package_inode_num = installer_fs.lookup(packages_dir_inode.inode, &package_file_path).unwrap_or(0); // lookup("/packages", "my_package.spk")
+                 let package_inode_num = installer_fs.resolve(&package_file_path, EXT2_ROOT_INODE).unwrap_or(0);
 
                  if package_inode_num == 0 {
                       writeln!(console_writer, "Hata: Paket dosyası {} kurulum medyasında bulunamadı.", package_file_path).unwrap();
                       task::exit(-5);
                  }
 
-                 // TODO: Paket dosyasını aç (ExtFilesystem::open_file gerektirir)
-                 // let package_file_handle = installer_fs.open_file(&package_file_path, resource::MODE_READ)?; // open_file yok
+                 // Paket dosyasını belleğe oku. Boyut diskten geldiği için tahsis panik etmemeli.
+                 let package_data = match installer_fs.read_inode(package_inode_num).and_then(|inode| {
+                     let mut data = sahne64::buffer::zeroed_buffer(inode.i_size as usize)?;
+                     let read = installer_fs.read_file(&inode, &mut data, 0)?;
+                     data.truncate(read);
+                     Ok(data)
+                 }) {
+                     Ok(data) => data,
+                     Err(err) => {
+                         writeln!(console_writer, "Hata: Paket dosyası okunamadı: {:?}", err).unwrap();
+                         task::exit(-5);
+                     }
+                 };
 
-                 // TODO: Paket dosyasını oku ve parse et
-                 // parse_spk_package(package_file_handle) fonksiyonu çağrılacak.
-                 let dummy_package_handle = Handle(100); // Yer tutucu paket handle'ı
-                 match parse_spk_package(dummy_package_handle) { // Gerçekte dosya handle'ı geçmeli
+                 // Paket dosyasını parse et ve kur
+                 match spk::parse(&package_data) {
                       Ok(package) => {
-                         // Paketi kur
-                         match install_package(&package, dummy_package_handle, &mut target_fs, &mut console_writer) {
+                         match install_package(&package, &package_data, &mut target_fs, &mut console_writer) {
                              Ok(_) => {}, // Başarılı
                              Err(err) => {
                                  writeln!(console_writer, "Hata: Paket kurulumu başarısız: {:?}", err).unwrap();
@@ -304,7 +304,13 @@ pub extern "C" fn main(argc: usize, argv: *const *const u8) -> ! {
     }
 
 
+    // Hedef dosya sistemini ayır: önbellekteki yazmalar diske iner ve birim temiz işaretlenir.
+    if let Err(err) = target_fs.unmount() {
+        writeln!(console_writer, "Hata: Hedef dosya sistemi ayrılamadı: {:?}", err).unwrap();
+        task::exit(-9);
+    }
+
     // Uygulama tamamlandı, çık
     writeln!(console_writer, "SPM Tamamlandı.").unwrap();
     task::exit(0); // Başarıyla çık
-}
\ No newline at end of file
+}
\ No newline at end of file
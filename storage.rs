@@ -0,0 +1,457 @@
+// drivers/storage.rs
+// Depolama Cihazı Kaydı
+// Blok sürücüleri (virtio-blk, eMMC, SD) cihazlarını register_disk ile kaydeder. Cihaz kendi adıyla
+// ("emmc0"), bölüm tablosundaki her bölüm de ayrı bir kaynak olarak ("emmc0p1", "emmc0p2" ...) sunulur.
+// Kaynak okuma/yazmaları bayt konumludur; block::read_at/write_at ile bloklara çevrilir.
+//
+// Kaynak yöneticisi &'static nesneler tuttuğu için kaynaklar ve bölüm adları kalıcı olarak ayrılır.
+// Çıkarılabilir cihazlar (SD kart) unregister_disk ile kaldırılır; aynı cihaz yeniden kaydedildiğinde
+// kaynak nesneleri (ve değişmeyen bölümlerinkiler) yeniden kullanılır.
+//
+// Tüm cihazlar ortak bir blok önbelleğinden (block_cache) geçer; bölümler de cihazlarının önbelleğini
+// kullanır. Kirli bloklar sync sistem çağrısı, CONTROL_BLOCK_FLUSH, periyodik boşaltma görevi ("bflush")
+// ve kapatma kancası ile aygıta yazılır.
+//
+// Cihaz kaydedilince ve kaldırılınca "media" kaynağına olay yazılır (sahne64::media); abone görevler
+// kartın takıldığını veya çıkarıldığını buradan öğrenir.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use spin::Mutex;
+
+use crate::block::{self, BlockDevice, Partition};
+use crate::block_cache::BlockCache;
+use crate::media_events::EventLog;
+use crate::partition;
+use crate::power::{self, ShutdownStage};
+use crate::printk;
+use crate::resource_manager::{self, KernelResource};
+use crate::sahne64::media::{self, CONTROL_MEDIA_GET_SEQUENCE, EVENT_SIZE, MEDIA_INSERTED, MEDIA_REMOVED};
+use crate::sahne64::resource::{
+    CONTROL_BLOCK_CACHE_HITS, CONTROL_BLOCK_CACHE_MISSES, CONTROL_BLOCK_FLUSH, CONTROL_BLOCK_GET_BLOCK_SIZE,
+    CONTROL_BLOCK_GET_SIZE, CONTROL_BLOCK_SET_MOUNTED, MODE_NONBLOCK, MODE_READ, MODE_WRITE,
+};
+use crate::sahne64::SahneError;
+use crate::sched;
+
+/// Kaynak olarak sunulabilen, görevler arasında paylaşılan blok cihaz.
+pub type SharedBlockDevice = &'static (dyn BlockDevice + Sync);
+
+// Önbelleğin blok verileri için bellek bütçesi. 2 MB RAM'de dosya sisteminin sık okunan meta verisine
+// (süper blok, grup tanımlayıcıları, i-node tabloları, dizinler) yeter.
+const CACHE_BYTES: usize = 64 * 1024;
+
+// Periyodik boşaltma aralığı: elektrik kesilirse en fazla bu kadar süredir yazılan veri kaybolur.
+const FLUSH_INTERVAL_MS: u64 = 5000;
+const FLUSH_TASK_STACK_SIZE: usize = 4096;
+
+// "media" kaynağının sakladığı son olay sayısı.
+const MEDIA_EVENT_HISTORY: usize = 16;
+
+// Kilit sırası: DISKS -> CACHE -> sürücünün kendi kilitleri. Sürücüler önbelleği çağırmaz.
+static CACHE: Mutex<BlockCache<SharedBlockDevice>> = Mutex::new(BlockCache::new(CACHE_BYTES));
+
+// Sürücünün cihazını önbellek üzerinden sunar. Kaynaklar ve bölümler bu nesneyi kullanır.
+struct CachedDisk {
+    id: AtomicUsize, // Önbellekteki cihaz kimliği (cihaz yeniden kaydedilince değişir)
+    device: SharedBlockDevice,
+}
+
+impl CachedDisk {
+    fn id(&self) -> usize {
+        self.id.load(Ordering::Acquire)
+    }
+}
+
+impl BlockDevice for CachedDisk {
+    fn block_size(&self) -> usize {
+        self.device.block_size()
+    }
+
+    fn block_count(&self) -> u64 {
+        self.device.block_count()
+    }
+
+    fn read_blocks(&self, first: u64, buffer: &mut [u8]) -> Result<(), SahneError> {
+        CACHE.lock().read(self.id(), first, buffer)
+    }
+
+    fn write_blocks(&self, first: u64, buffer: &[u8]) -> Result<(), SahneError> {
+        CACHE.lock().write(self.id(), first, buffer)
+    }
+
+    fn read_blocks_scatter(&self, first: u64, segments: &mut [&mut [u8]]) -> Result<(), SahneError> {
+        CACHE.lock().read_scatter(self.id(), first, segments)
+    }
+
+    fn write_blocks_gather(&self, first: u64, segments: &[&[u8]]) -> Result<(), SahneError> {
+        CACHE.lock().write_gather(self.id(), first, segments)
+    }
+
+    fn flush(&self) -> Result<(), SahneError> {
+        CACHE.lock().flush(self.id())
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.device.is_read_only()
+    }
+}
+
+// Bir blok cihazı (veya bölümü) bayt konumlu bir kaynak olarak sunar.
+struct BlockResource<D: BlockDevice + Sync + 'static> {
+    device: &'static D,        // Bağlama kayıtları cihazı kaynaktan bağımsız tutabilsin diye kalıcı
+    disk: &'static CachedDisk, // Bölümler cihazlarının önbellek sayaçlarını paylaşır
+}
+
+impl<D: BlockDevice + Sync + 'static> KernelResource for BlockResource<D> {
+    fn modes(&self) -> u32 {
+        if self.device.is_read_only() { MODE_READ } else { MODE_READ | MODE_WRITE }
+    }
+
+    fn read(&self, buffer: &mut [u8], offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        block::read_at(&self.device, offset, buffer)
+    }
+
+    fn write(&self, buffer: &[u8], offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        block::write_at(&self.device, offset, buffer)
+    }
+
+    fn control(&self, request: u32, arg: u64, mode: u32) -> Result<u64, SahneError> {
+        match request {
+            CONTROL_BLOCK_GET_SIZE => Ok(block::size_bytes(&self.device)),
+            CONTROL_BLOCK_GET_BLOCK_SIZE => Ok(self.device.block_size() as u64),
+            CONTROL_BLOCK_FLUSH if mode & MODE_WRITE == 0 => Err(SahneError::PermissionDenied),
+            CONTROL_BLOCK_FLUSH => self.device.flush().map(|_| 0),
+            CONTROL_BLOCK_CACHE_HITS => CACHE.lock().stats(self.disk.id()).map(|stats| stats.hits),
+            CONTROL_BLOCK_CACHE_MISSES => CACHE.lock().stats(self.disk.id()).map(|stats| stats.misses),
+            CONTROL_BLOCK_SET_MOUNTED if mode & MODE_WRITE == 0 => Err(SahneError::PermissionDenied),
+            CONTROL_BLOCK_SET_MOUNTED => set_mounted(self.device, arg != 0).map(|_| 0),
+            _ => Err(SahneError::NotSupported),
+        }
+    }
+}
+
+fn leak_resource<D: BlockDevice + Sync + 'static>(device: D, disk: &'static CachedDisk) -> Result<&'static dyn KernelResource, SahneError> {
+    let device: &'static D = Box::leak(Box::try_new(device).map_err(|_| SahneError::OutOfMemory)?);
+    let resource = Box::try_new(BlockResource { device, disk }).map_err(|_| SahneError::OutOfMemory)?;
+    Ok(Box::leak(resource))
+}
+
+// "<disk>p<numara>" adını kalıcı olarak ayırır.
+fn partition_name(disk: &str, number: u32) -> Result<&'static str, SahneError> {
+    let mut name = String::new();
+    name.try_reserve_exact(disk.len() + 4).map_err(|_| SahneError::OutOfMemory)?;
+    let _ = write!(name, "{}p{}", disk, number);
+    Ok(name.leak())
+}
+
+// Kaydedilmiş bir bölümün kaynağı. Bölüm tablosu değişmediyse kart yeniden takıldığında yeniden kullanılır.
+struct PartitionSlot {
+    number: u32,
+    first_block: u64,
+    block_count: u64,
+    name: &'static str,
+    resource: &'static dyn KernelResource,
+    registered: bool,
+}
+
+// Kaydedilmiş bir cihaz. Kayıtlar silinmez; çıkarılan cihaz `present = false` olarak kalır.
+struct Disk {
+    name: &'static str,
+    cached: &'static CachedDisk,
+    resource: &'static dyn KernelResource,
+    partitions: Vec<PartitionSlot>, // Şimdiye kadar görülen bölümler
+    present: bool,
+}
+
+static DISKS: Mutex<Vec<Disk>> = Mutex::new(Vec::new());
+
+/// Cihazı `name` adıyla kaydeder ve bölüm tablosundaki bölümleri "<name>p<n>" olarak ekler.
+/// Bölüm tablosu okunamazsa sadece cihazın kendisi kaydedilir. Çıkarılmış bir cihaz aynı adla yeniden
+/// kaydedilebilir (sürücü aynı BlockDevice'ı vermelidir).
+pub fn register_disk(name: &'static str, device: SharedBlockDevice) -> Result<(), SahneError> {
+    let mut disks = DISKS.lock();
+    let index = match disks.iter().position(|disk| disk.name == name) {
+        Some(index) if disks[index].present || !core::ptr::addr_eq(disks[index].cached.device, device) => {
+            return Err(SahneError::NamingError);
+        }
+        Some(index) => index,
+        None => {
+            if resource_manager::is_registered(name) {
+                return Err(SahneError::NamingError);
+            }
+            disks.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+            let cached = Box::try_new(CachedDisk { id: AtomicUsize::new(usize::MAX), device }).map_err(|_| SahneError::OutOfMemory)?;
+            let cached: &'static CachedDisk = Box::leak(cached);
+            let resource = leak_resource(cached, cached)?;
+            if disks.is_empty() {
+                // İlk cihazla birlikte kapatma kancaları kurulur; sürücüler durdurulmadan önce çalışır.
+                let _ = power::register_shutdown_hook(ShutdownStage::Filesystems, "ext2", unmount_filesystems);
+                let _ = power::register_shutdown_hook(ShutdownStage::BlockCaches, "block-cache", shutdown);
+            }
+            disks.push(Disk { name, cached, resource, partitions: Vec::new(), present: false });
+            disks.len() - 1
+        }
+    };
+    let disk = &mut disks[index];
+
+    let id = CACHE.lock().add_device(device)?;
+    disk.cached.id.store(id, Ordering::Release);
+    if let Err(err) = resource_manager::register(name, disk.resource) {
+        CACHE.lock().remove_device(id);
+        return Err(err);
+    }
+    disk.present = true;
+    post_media_event(MEDIA_INSERTED, name);
+    register_partitions(disk);
+    Ok(())
+}
+
+// Cihazın bölüm tablosunu okur ve bölümleri kaydeder. Hatalar bildirilir ama cihazın kaydını bozmaz.
+fn register_partitions(disk: &mut Disk) {
+    let partitions = match partition::scan(disk.cached) {
+        Ok(partitions) => partitions,
+        Err(err) => {
+            printk!("{}: Bölüm tablosu okunamadı: {:?}\n", disk.name, err);
+            return;
+        }
+    };
+    for entry in partitions {
+        let existing = disk.partitions.iter().position(|slot| {
+            !slot.registered
+                && (slot.number, slot.first_block, slot.block_count) == (entry.number, entry.first_block, entry.block_count)
+        });
+        let index = match existing {
+            Some(index) => index,
+            None => match new_partition_slot(disk, entry.number, entry.first_block, entry.block_count) {
+                Ok(slot) => {
+                    disk.partitions.push(slot);
+                    disk.partitions.len() - 1
+                }
+                Err(err) => {
+                    printk!("{}: Bölüm {} eklenemedi: {:?}\n", disk.name, entry.number, err);
+                    continue;
+                }
+            },
+        };
+        let slot = &mut disk.partitions[index];
+        match resource_manager::register(slot.name, slot.resource) {
+            Ok(()) => {
+                slot.registered = true;
+                printk!(
+                    "{}: blok {}, {} KB\n",
+                    slot.name,
+                    entry.first_block,
+                    entry.block_count * disk.cached.block_size() as u64 / 1024
+                );
+            }
+            Err(err) => printk!("{}: Kaydedilemedi: {:?}\n", slot.name, err),
+        }
+    }
+}
+
+fn new_partition_slot(disk: &mut Disk, number: u32, first_block: u64, block_count: u64) -> Result<PartitionSlot, SahneError> {
+    disk.partitions.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+    // Ad, aynı numaralı eski bir bölümün adıyla paylaşılır.
+    let name = match disk.partitions.iter().find(|slot| slot.number == number) {
+        Some(slot) => slot.name,
+        None => partition_name(disk.name, number)?,
+    };
+    let partition = Partition::new(disk.cached, first_block, block_count)?;
+    let resource = leak_resource(partition, disk.cached)?;
+    Ok(PartitionSlot { number, first_block, block_count, name, resource, registered: false })
+}
+
+/// Çıkarılan cihazı ve bölümlerini kaldırır: kaynak kayıtları silinir (açık Handle'lar geçersiz kalır)
+/// ve cihazın önbellekteki blokları atılır. Aygıta yazılamayan kirli blok sayısını döndürür.
+pub fn unregister_disk(name: &str) -> Result<usize, SahneError> {
+    let mut disks = DISKS.lock();
+    let disk = disks.iter_mut().find(|disk| disk.name == name && disk.present).ok_or(SahneError::ResourceNotFound)?;
+    for slot in disk.partitions.iter_mut().filter(|slot| slot.registered) {
+        let _ = resource_manager::unregister(slot.name);
+        slot.registered = false;
+    }
+    let _ = resource_manager::unregister(disk.name);
+    disk.present = false;
+    let lost = CACHE.lock().remove_device(disk.cached.id());
+    if lost > 0 {
+        printk!("{}: Çıkarıldı, {} blok yazılamadan kayboldu.\n", disk.name, lost);
+    }
+    post_media_event(MEDIA_REMOVED, disk.name);
+    Ok(lost)
+}
+
+// "media" kaynağının olay günlüğü ve yeni olay bekleyen görevler.
+struct MediaQueue {
+    log: EventLog<MEDIA_EVENT_HISTORY>,
+    waiters: Vec<usize>, // Görev kimlikleri
+}
+
+static MEDIA: Mutex<MediaQueue> = Mutex::new(MediaQueue { log: EventLog::new(), waiters: Vec::new() });
+
+fn post_media_event(kind: u32, device: &str) {
+    let mut media = MEDIA.lock();
+    media.log.push(kind, device);
+    for task_id in media.waiters.drain(..) {
+        sched::wake_task(task_id);
+    }
+}
+
+// Takılma/çıkarılma olaylarını sunar. Konum, istenen olayın sıra numarası * EVENT_SIZE'dır; okuma o olaydan
+// itibaren tampona sığan kadar kaydı döndürür. Yeni olay yoksa MODE_NONBLOCK ile ResourceBusy döner,
+// aksi halde olay gelene kadar beklenir.
+struct MediaResource;
+
+impl KernelResource for MediaResource {
+    fn modes(&self) -> u32 {
+        MODE_READ
+    }
+
+    fn read(&self, buffer: &mut [u8], offset: u64, mode: u32) -> Result<usize, SahneError> {
+        if buffer.len() < EVENT_SIZE || !offset.is_multiple_of(EVENT_SIZE as u64) {
+            return Err(SahneError::InvalidParameter);
+        }
+        let sequence = offset / EVENT_SIZE as u64;
+        loop {
+            {
+                let mut media = MEDIA.lock();
+                let read = media.log.read(sequence, buffer);
+                if read > 0 {
+                    return Ok(read);
+                }
+                if mode & MODE_NONBLOCK != 0 {
+                    return Err(SahneError::ResourceBusy);
+                }
+                // Kuyruğa ekleme ve bloklanma aynı kilit altında yapılır; arada gelen olay kaybolmaz.
+                let task_id = sched::current_task_id().ok_or(SahneError::InvalidOperation)?;
+                if !media.waiters.contains(&task_id) {
+                    media.waiters.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+                    media.waiters.push(task_id);
+                }
+                sched::prepare_to_block(None);
+            }
+            sched::wait_while_blocked();
+        }
+    }
+
+    fn write(&self, _buffer: &[u8], _offset: u64, _mode: u32) -> Result<usize, SahneError> {
+        Err(SahneError::PermissionDenied)
+    }
+
+    fn control(&self, request: u32, _arg: u64, _mode: u32) -> Result<u64, SahneError> {
+        match request {
+            CONTROL_MEDIA_GET_SEQUENCE => Ok(MEDIA.lock().log.next_sequence()),
+            _ => Err(SahneError::NotSupported),
+        }
+    }
+}
+
+static MEDIA_RESOURCE: MediaResource = MediaResource;
+
+/// "media" kaynağını kaydeder (blok sürücülerinden önce).
+pub fn init() -> Result<(), SahneError> {
+    resource_manager::register(media::RESOURCE_NAME, &MEDIA_RESOURCE)
+}
+
+/// Tüm cihazların kirli bloklarını aygıta yazar. Hata olsa da diğer cihazlara devam edilir; ilk hata döner.
+pub fn sync() -> Result<(), SahneError> {
+    CACHE.lock().flush_all()
+}
+
+// SYSCALL_SYNC: Önbellekteki tüm yazmaları kalıcı depolamaya aktarır.
+pub fn sys_sync() -> Result<(), SahneError> {
+    sync()
+}
+
+// Yazılabilir bağlanmış bir ext2 birimi (CONTROL_BLOCK_SET_MOUNTED). Dosya sistemi kullanıcı alanında
+// çalışır; çekirdek sadece hangi birimin hangi görev tarafından "temiz değil" bırakıldığını bilir.
+struct MountedVolume {
+    device: SharedBlockDevice, // Cihaz veya bölüm
+    owner: usize,              // Bağlayan görev
+}
+
+static MOUNTS: Mutex<Vec<MountedVolume>> = Mutex::new(Vec::new());
+
+// ext2 süper bloğunun konumu ve kapatma kancasının dokunduğu alanlar (ext.rs ile aynı).
+const EXT2_SUPERBLOCK_OFFSET: u64 = 1024;
+const EXT2_MAGIC_OFFSET: u64 = EXT2_SUPERBLOCK_OFFSET + 56; // s_magic, hemen ardından s_state
+const EXT2_STATE_OFFSET: u64 = EXT2_SUPERBLOCK_OFFSET + 58;
+const EXT2_MAGIC: u16 = 0xEF53;
+const EXT2_VALID_FS: u16 = 1;
+
+fn set_mounted(device: SharedBlockDevice, mounted: bool) -> Result<(), SahneError> {
+    let owner = sched::current_task_id().ok_or(SahneError::InvalidOperation)?;
+    let mut mounts = MOUNTS.lock();
+    if mounted {
+        mounts.try_reserve(1).map_err(|_| SahneError::OutOfMemory)?;
+        mounts.push(MountedVolume { device, owner });
+    } else if let Some(index) = mounts.iter().position(|volume| volume.owner == owner && core::ptr::addr_eq(volume.device, device)) {
+        mounts.swap_remove(index);
+    }
+    Ok(())
+}
+
+// Kapatma kancası (ShutdownStage::Filesystems). Görevler bildirim üzerine dosya sistemlerini kendileri
+// ayırır; ayırmadan çıkan görevlerin birimleri burada "temiz" işaretlenir. Görev kendi isteğiyle bir işlemin
+// ortasında çıkamayacağı için meta veri tutarlıdır. Sahibi zorla durdurulan birimler bir işlemin ortasında
+// kalmış olabileceğinden kirli bırakılır; bir sonraki açılışta fsck ile denetlenir.
+fn unmount_filesystems() {
+    let mounts = core::mem::take(&mut *MOUNTS.lock());
+    let killed = |device: SharedBlockDevice| {
+        mounts.iter().any(|volume| core::ptr::addr_eq(volume.device, device) && sched::task_was_killed(volume.owner))
+    };
+    for (index, volume) in mounts.iter().enumerate() {
+        if mounts[..index].iter().any(|earlier| core::ptr::addr_eq(earlier.device, volume.device)) {
+            continue; // Aynı birim birden fazla kez bağlanmış
+        }
+        if killed(volume.device) {
+            printk!("ext2: Bağlayan görev durduruldu, dosya sistemi kirli bırakıldı\n");
+            continue;
+        }
+        if let Err(err) = mark_ext2_clean(volume.device) {
+            printk!("ext2: Süper blok yazılamadı: {:?}\n", err);
+        }
+    }
+}
+
+// Süper bloğun s_state alanına EXT2_VALID_FS ekler (ExtFilesystem::unmount ile aynı etki).
+fn mark_ext2_clean(device: SharedBlockDevice) -> Result<(), SahneError> {
+    let mut fields = [0u8; 4];
+    if block::read_at(&device, EXT2_MAGIC_OFFSET, &mut fields)? != fields.len() {
+        return Err(SahneError::InvalidOperation);
+    }
+    if u16::from_le_bytes([fields[0], fields[1]]) != EXT2_MAGIC {
+        return Err(SahneError::InvalidParameter);
+    }
+    let state = u16::from_le_bytes([fields[2], fields[3]]) | EXT2_VALID_FS;
+    block::write_at(&device, EXT2_STATE_OFFSET, &state.to_le_bytes())?;
+    Ok(())
+}
+
+// Kapatma kancası (ShutdownStage::BlockCaches).
+fn shutdown() {
+    if let Err(err) = sync() {
+        printk!("Blok önbelleği boşaltılamadı: {:?}\n", err);
+    }
+}
+
+extern "C" fn flush_task_main() -> ! {
+    loop {
+        sched::sleep(FLUSH_INTERVAL_MS);
+        if CACHE.lock().usage().1 == 0 {
+            continue;
+        }
+        if let Err(err) = sync() {
+            printk!("bflush: Blok önbelleği boşaltılamadı: {:?}\n", err);
+        }
+    }
+}
+
+/// Kirli blokları periyodik olarak aygıta yazan "bflush" görevini başlatır (sched::init'ten sonra).
+pub fn start_flush_task() -> Result<(), SahneError> {
+    sched::spawn_kernel_task("bflush", flush_task_main, FLUSH_TASK_STACK_SIZE).map(|_| ())
+}
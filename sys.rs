@@ -6,14 +6,19 @@ use crate::printk;             // printk! makrosunu içeri aktar
 use crate::traps::TrapFrame;    // TrapFrame yapısını içeri aktar (kaydedilmiş registerları içerir)
 use crate::sahne64::arch;       // sahne64.rs'deki sistem çağrısı numaralarını içeri aktar
 use crate::sahne64::SahneError; // sahne64.rs'deki hata enumunu içeri aktar
+use crate::sahne64::ResourceLimits; // Görev kaynak sınırları
+use crate::sahne64::resource::AT_OFFSET; // read_at / write_at bayrağı
 
 // TODO: İlgili çekirdek modüllerini içeri aktarın
 use crate::exit;  // task::exit, thread::exit için
 use crate::sched; // task::sleep, task::yield_now, thread::create için
 use crate::mm;    // memory::allocate, memory::release, shared_mem_* için
-use crate::resource_manager; // Resource syscallları için (şimdilik yok, sys.rs içinde placeholder)
+use crate::limits; // Görev başına kaynak sınırları (Handle sayısı vb.)
+use crate::futex;  // wait_on_address / wake_address için
+use crate::resource_manager; // Resource syscallları için (adla kayıtlı çekirdek kaynakları)
 use crate::sync_manager;     // Lock syscallları için (şimdilik yok, sys.rs içinde placeholder)
-use crate::kernel_time;     // get_system_time için (şimdilik yok, sys.rs içinde placeholder)
+use crate::kernel_time;     // Duvar saati (set_time / get_wall_time)
+use crate::power::{self, PowerAction}; // reboot / poweroff
 
 
 // SahneError'ı ABI uyumlu negatif i64 hata koduna çeviren yardımcı fonksiyon (Kernel tarafı)
@@ -34,6 +39,7 @@ fn kernel_error_to_i64(err: SahneError) -> i64 {
         SahneError::InvalidOperation => -100, // Rastgele bir kod
         SahneError::HandleLimitExceeded => -101, // Rastgele bir kod
         SahneError::UnknownSystemCall => -102, // Rastgele bir kod
+        SahneError::TimedOut => -110, // ETIMEDOUT
         // TODO: SahneError enumuna eklenen diğer hataları buraya mapleyin.
     }
 }
@@ -47,7 +53,7 @@ pub extern "C" fn sys_call_handler(trap_frame: *mut TrapFrame) {
     // 'unsafe' çünkü raw pointer (trap_frame) kullanılıyor ve registerlara yazılıyor.
     unsafe {
         // Sistem çağrısı numarasını a7 registerından al
-        let syscall_num = (*trap_frame).a7 as usize; // usize kullanmak match için daha uygun
+        let syscall_num = (*trap_frame).a7 as u64; // arch::SYSCALL_* sabitleri u64
 
         // Argümanları a0-a5 registerlarından al
         // Argüman sayısı sistem çağrısına göre değişir.
@@ -92,125 +98,67 @@ pub extern "C" fn sys_call_handler(trap_frame: *mut TrapFrame) {
                 loop { core::hint::spin_loop(); } // Görev bitmezse burada dönsün
             }
             arch::SYSCALL_RESOURCE_ACQUIRE => {
-                 acquire(id_ptr: u64, id_len: u64, mode: u32) -> Result<Handle, SahneError>
-                let id_ptr = arg0 as *const u8;
-                let id_len = arg1 as usize;
-                let mode = arg2 as u32;
-
-                // TODO: Kernel-internal resource management logic here or in resource_manager.rs
-                // 1. id_ptr ve id_len ile gelen string'i doğrula (geçerli adres, çekirdek belleğinde değil vb.)
-                // 2. Kaynak adını kernel'in tanıdığı kaynaklarla eşle (örn. "uart", "emmc0", "sdcard1", "display", "touchscreen", "refrigerator")
-                // 3. İzinleri kontrol et (mode).
-                // 4. İlgili sürücüyü veya kaynağı al/aç.
-                // 5. Yeni bir Handle oluştur ve bunu geçerli göreve ata.
-                // 6. Handle değerini veya hata kodunu döndür.
-
-                // Şimdilik yer tutucu: Sadece "uart" kaynağını tanıyormuş gibi yapalım
-                let resource_id_slice = core::slice::from_raw_parts(id_ptr, id_len);
-                 let resource_name = core::str::from_utf8(resource_id_slice);
-
-                 match resource_name {
-                     Ok("uart") => {
-                         // Başarılı bir Handle değeri döndür (örneğin 1)
-                         // Gerçekte, çekirdek handle'ları yönetmeli.
-                          1i64 // Varsayımsal "uart" handle'ı
-                     }
-                     Ok(_) | Err(_) => {
-                          // Bilinmeyen kaynak veya geçersiz isim
-                          kernel_error_to_i64(SahneError::ResourceNotFound)
-                     }
+                 // acquire(id_ptr: u64, id_len: u64, mode: u32) -> Result<Handle, SahneError>
+                 // Kaynak adları resource_manager'a kayıtlıdır ("console", "random", ...).
+                 match resource_manager::sys_acquire(arg0 as *const u8, arg1 as usize, arg2 as u32) {
+                     Ok(handle) => handle as i64,
+                     Err(err) => kernel_error_to_i64(err),
                  }
-                // Bu kısım ciddi implementasyon gerektirir.
             }
             arch::SYSCALL_RESOURCE_READ => {
                  // read(handle: u64, buf_ptr: u64, buf_len: u64) -> Result<usize, SahneError>
-                 let handle_val = arg0;
-                 let buf_ptr = arg1 as *mut u8;
-                 let buf_len = arg2 as usize;
-
-                 // TODO: Kernel-internal resource management logic
-                 // 1. Handle'ı doğrula ve geçerli göreve ait mi kontrol et.
-                 // 2. Handle'a karşılık gelen kaynağı (ve sürücüyü) bul.
-                 // 3. Kaynağın okunabilir olup olmadığını kontrol et.
-                 // 4. Sürücünün okuma fonksiyonunu çağır (örn. drivers::uart::getc veya drivers::emmc::read_block).
-                 // 5. Okunan bayt sayısını veya hata kodunu döndür.
-
-                 // Şimdilik yer tutucu: Sadece "uart" handle'ından okuyormuş gibi yapalım (varsayımsal handle 1)
-                 if handle_val == 1 { // Varsayımsal "uart" handle'ı
-                     // TODO: drivers::uart::getc() veya buffer okuma mantığını kullan
-                     // drivers::uart::getc() tek bayt okur, syscall buffer bekler.
-                     // Burası karmaşıklaşır. Basit polling okuma örneği:
-                     let mut bytes_read = 0;
-                     for i in 0..buf_len {
-                         if let Some(byte) = crate::drivers::uart::getc() { // drivers::uart::getc()'i kullan
-                             ptr::write_volatile(buf_ptr.add(i), byte);
-                             bytes_read += 1;
-                         } else {
-                             // Veri yoksa hemen dön veya bir süre bekle (polling)
-                             break; // Pollingde veri yoksa hemen dön
-                         }
-                     }
-                     bytes_read as i64 // Okunan bayt sayısını döndür
-                 } else {
-                      kernel_error_to_i64(SahneError::InvalidHandle) // Geçersiz handle
+                 // resource::read_at: arg3 = offset, arg4 = AT_OFFSET. Bayrak yoksa kaynağın kendi konumu kullanılır.
+                 let offset = if arg4 as u64 & AT_OFFSET != 0 { Some(arg3 as u64) } else { None };
+                 match resource_manager::sys_read(arg0 as u64, arg1 as *mut u8, arg2 as usize, offset) {
+                     Ok(count) => count as i64,
+                     Err(err) => kernel_error_to_i64(err),
                  }
-                 // Bu kısım ciddi implementasyon gerektirir.
             }
             arch::SYSCALL_RESOURCE_WRITE => {
-                 write(handle: u64, buf_ptr: u64, buf_len: u64) -> Result<usize, SahneError>
-                 let handle_val = arg0;
-                 let buf_ptr = arg1 as *const u8;
-                 let buf_len = arg2 as usize;
-
-                 // TODO: Kernel-internal resource management logic
-                 // 1. Handle'ı doğrula ve geçerli göreve ait mi kontrol et.
-                 // 2. Handle'a karşılık gelen kaynağı (ve sürücüyü) bul.
-                 // 3. Kaynağın yazılabilir olup olmadığını kontrol et.
-                 // 4. Sürücünün yazma fonksiyonunu çağır (örn. drivers::uart::putc veya drivers::emmc::write_block).
-                 // 5. Yazılan bayt sayısını veya hata kodunu döndür.
-
-                 // Şimdilik yer tutucu: Sadece "uart" handle'ına yazıyormuş gibi yapalım (varsayımsal handle 1)
-                 if handle_val == 1 { // Varsayımsal "uart" handle'ı
-                     // TODO: drivers::uart::putc() veya buffer yazma mantığını kullan
-                     let slice = core::slice::from_raw_parts(buf_ptr, buf_len);
-                     for &byte in slice {
-                         crate::drivers::uart::putc(byte); // drivers::uart::putc() kullan
-                     }
-                     buf_len as i64 // Yazılan bayt sayısını döndür (basitlik)
-                 } else {
-                      kernel_error_to_i64(SahneError::InvalidHandle) // Geçersiz handle
+                 // write(handle: u64, buf_ptr: u64, buf_len: u64) -> Result<usize, SahneError>
+                 let offset = if arg4 as u64 & AT_OFFSET != 0 { Some(arg3 as u64) } else { None };
+                 match resource_manager::sys_write(arg0 as u64, arg1 as *const u8, arg2 as usize, offset) {
+                     Ok(count) => count as i64,
+                     Err(err) => kernel_error_to_i64(err),
+                 }
+            }
+            arch::SYSCALL_RESOURCE_CONTROL => {
+                 // control(handle: u64, request: u32, arg: u64) -> Result<u64, SahneError>
+                 match resource_manager::sys_control(arg0 as u64, arg1 as u64, arg2 as u64) {
+                     Ok(value) => value as i64,
+                     Err(err) => kernel_error_to_i64(err),
+                 }
+            }
+            arch::SYSCALL_SYNC => {
+                 // sync() -> Result<(), SahneError>
+                 match crate::drivers::storage::sys_sync() {
+                     Ok(()) => 0i64,
+                     Err(err) => kernel_error_to_i64(err),
                  }
-                 // Bu kısım ciddi implementasyon gerektirir.
             }
             arch::SYSCALL_RESOURCE_RELEASE => {
-                 release(handle: u64) -> Result<(), SahneError>
-                let handle_val = arg0;
-
-                 // TODO: Kernel-internal resource management logic
-                 // 1. Handle'ı doğrula ve geçerli göreve ait mi kontrol et.
-                 // 2. Handle'a karşılık gelen kaynağı serbest bırak / referans sayısını azalt.
-                 // 3. Handle'ı geçersiz olarak işaretle.
-                 // 4. Başarı veya hata kodunu döndür.
-
-                 // Şimdilik yer tutucu: Sadece handle 1'i serbest bırakıyormuş gibi yapalım
-                 if handle_val == 1 { // Varsayımsal "uart" handle'ı
-                     // Gerçekte Handle tablosundan kaldırılmalı
-                     0i64 // Başarılı
+                 // release(handle: u64) -> Result<(), SahneError>
+                let handle_val = arg0 as u64;
+
+                 // Paylaşımlı bellek Handle'ları bölgeyi de iade eder.
+                 let result = if mm::is_shared_handle(handle_val) {
+                     mm::sys_release_shared(handle_val)
                  } else {
-                     kernel_error_to_i64(SahneError::InvalidHandle) // Geçersiz handle
+                     resource_manager::release(handle_val)
+                 };
+                 match result {
+                     Ok(()) => 0i64,
+                     Err(err) => kernel_error_to_i64(err),
                  }
-                // Bu kısım ciddi implementasyon gerektirir.
             }
             arch::SYSCALL_TASK_SLEEP => {
                   sleep(milliseconds: u64) -> Result<(), SahneError>
                  let milliseconds = arg0;
-                 // TODO: scheduler ve timer ile etkileşime girerek görevi uyut
-                 sched::sleep(milliseconds); // scheduler'da sleep fonksiyonunuz olmalı
-
-                 // Uyku başarılıysa 0 döndür (kesilmezse)
-                 // sched::sleep Result döndürmeli
-                 0i64 // Başarılı varsayalım
-                 // Bu kısım ciddi implementasyon gerektirir (Timer kesmeleri, Blocked görev yönetimi).
+                 // Kapatma bildirimi gelirse uyku yarıda kalır ve Interrupted döner.
+                 match sched::sleep_interruptible(milliseconds) {
+                     Ok(()) => 0i64,
+                     Err(err) => kernel_error_to_i64(err),
+                 }
             }
             arch::SYSCALL_LOCK_CREATE => {
                  lock_create() -> Result<Handle, SahneError>
@@ -270,64 +218,110 @@ pub extern "C" fn sys_call_handler(trap_frame: *mut TrapFrame) {
                  /// sys_exit schedule() çağırır ve bu thread sonlanır. Buraya asla ulaşılmaz.
                 loop { core::hint::spin_loop(); }
             }
+            arch::SYSCALL_WAIT_ON_ADDRESS => {
+                // wait_on_address(addr: *const AtomicU32, expected: u32, timeout_ms: u64) -> Result<(), SahneError>
+                match futex::sys_wait_on_address(arg0 as usize, arg1 as u32, arg2) {
+                    Ok(()) => 0i64,
+                    Err(err) => kernel_error_to_i64(err),
+                }
+            }
+            arch::SYSCALL_WAKE_ADDRESS => {
+                // wake_address(addr: *const AtomicU32, count: usize) -> Result<usize, SahneError>
+                match futex::sys_wake_address(arg0 as usize, arg1 as usize) {
+                    Ok(woken) => woken as i64,
+                    Err(err) => kernel_error_to_i64(err),
+                }
+            }
+            arch::SYSCALL_THREAD_ID => {
+                // thread_id() -> Result<u64, SahneError>
+                match sched::current_task_id() {
+                    Some(id) => id as i64,
+                    None => kernel_error_to_i64(SahneError::InvalidOperation),
+                }
+            }
+            arch::SYSCALL_TERMINATION_REQUESTED => {
+                // termination_requested() -> Result<bool, SahneError>
+                sched::termination_requested() as i64
+            }
             arch::SYSCALL_GET_SYSTEM_TIME => {
-                  get_time() -> Result<u64, SahneError>
-                 // TODO: Kernelin zaman kaynağından (timer driver) mevcut zamanı al.
-                 // kernel_time::get_time_nanos() gibi bir fonksiyon olmalı.
-
-                 // Şimdilik yer tutucu: Sabit bir değer veya basit bir sayıcı döndürelim.
-                  printk!("WARN: get_system_time implemente edilmedi, 0 döndürülüyor.\n");
-                 0i64 // Başarılı varsayalım, zaman 0
-
-                 // Gerçek implementasyon:
-                  match kernel_time::get_time_nanos() {
-                     Ok(time) => time as i64,
-                     Err(err) => kernel_error_to_i64(err),
-                  }
-                 // Bu kısım ciddi implementasyon gerektirir (Timer donanımı ve zaman takibi).
+                // get_time() -> Result<u64, SahneError>
+                // Açılıştan beri geçen monoton süre (nanosaniye).
+                crate::drivers::timer::now_ns() as i64
+            }
+            arch::SYSCALL_GET_IDLE_TIME => {
+                // get_idle_time() -> Result<u64, SahneError>
+                // Açılıştan beri boşta görevinde (wfi) geçen süre (nanosaniye).
+                sched::idle_time_ns() as i64
+            }
+            arch::SYSCALL_SET_TIME => {
+                // set_time(unix_ns: u64) -> Result<(), SahneError>
+                match kernel_time::sys_set_time(arg0) {
+                    Ok(()) => 0i64,
+                    Err(err) => kernel_error_to_i64(err),
+                }
+            }
+            arch::SYSCALL_GET_WALL_TIME => {
+                // get_wall_time() -> Result<u64, SahneError>
+                // 1970-01-01 UTC'den beri geçen süre (nanosaniye); i64 aralığında kalacak şekilde sınırlıdır.
+                kernel_time::sys_get_wall_time() as i64
+            }
+            arch::SYSCALL_REBOOT => {
+                // reboot() -> Result<(), SahneError>; başarılı olursa geri dönmez.
+                match power::sys_power(PowerAction::Reboot) {
+                    Ok(()) => 0i64,
+                    Err(err) => kernel_error_to_i64(err),
+                }
+            }
+            arch::SYSCALL_POWEROFF => {
+                // poweroff() -> Result<(), SahneError>; başarılı olursa geri dönmez.
+                match power::sys_power(PowerAction::PowerOff) {
+                    Ok(()) => 0i64,
+                    Err(err) => kernel_error_to_i64(err),
+                }
             }
              arch::SYSCALL_SHARED_MEM_CREATE => {
                   create_shared(size: usize) -> Result<Handle, SahneError>
                  let size = arg0 as usize;
-                 // TODO: Kernel-internal shared memory management logic (MMU/Paging ile ilgili)
-                 // 1. Shared bellek alanı için fiziksel bellek tahsis et.
-                 // 2. Bu alan için bir SharedMemory nesnesi/tanıtıcısı oluştur.
-                 // 3. Bir Handle ata ve kaydet.
-                 // 4. Handle değerini döndür.
-
-                 // Şimdilik yer tutucu
-                 kernel_error_to_i64(SahneError::NotSupported) // Henüz desteklenmiyor
-                 // Bu kısım ciddi implementasyon gerektirir (MMU/Paging, bellek tahsisi).
+                 // Görevin paylaşımlı bellek ve Handle sınırları mm::sys_create_shared içinde uygulanır.
+                 match mm::sys_create_shared(size) {
+                     Ok(handle) => handle as i64,
+                     Err(err) => kernel_error_to_i64(err),
+                 }
+                 // TODO: MMU/Paging eklendiğinde bölgeler görev adres alanlarına ayrı ayrı eşlenmeli.
             }
              arch::SYSCALL_SHARED_MEM_MAP => {
                   map_shared(handle: u64, offset: usize, size: usize) -> Result<*mut u8, SahneError>
                  let handle_val = arg0;
                  let offset = arg1 as usize;
                  let size = arg2 as usize;
-                 // TODO: Kernel-internal shared memory management logic (MMU/Paging ile ilgili)
-                 // 1. Handle'ı doğrula ve SharedMemory nesnesini bul.
-                 // 2. Geçerli görevin adres alanında boş bir sanal adres aralığı bul.
-                 // 3. SharedMemory'nin fiziksel sayfalarını bu sanal adres aralığına eşle (sayfa tablolarını güncelle).
-                 // 4. TLB'yi flush et (sfence.vma).
-                 // 5. Atanan sanal adresin başlangıcını döndür.
-
-                 // Şimdilik yer tutucu
-                 kernel_error_to_i64(SahneError::NotSupported) // Henüz desteklenmiyor
-                 // Bu kısım ciddi implementasyon gerektirir (MMU/Paging, sanal bellek yönetimi).
+                 // MMU olmadığı için bölgenin adresi doğrudan döndürülür.
+                 match mm::sys_map_shared(handle_val, offset, size) {
+                     Ok(addr) => addr as i64,
+                     Err(err) => kernel_error_to_i64(err),
+                 }
             }
              arch::SYSCALL_SHARED_MEM_UNMAP => {
                 // unmap_shared(addr: *mut u8, size: usize) -> Result<(), SahneError>
                  let addr = arg0 as *mut u8;
                  let size = arg1 as usize;
-                 // TODO: Kernel-internal shared memory management logic (MMU/Paging ile ilgili)
-                 // 1. Belirtilen sanal adres aralığının geçerli görevin adres alanında eşlenmiş SharedMemory olup olmadığını doğrula.
-                 // 2. Sayfa tablosundaki eşlemeleri kaldır.
-                 // 3. TLB'yi flush et (sfence.vma).
-                 // 4. Başarı veya hata kodunu döndür.
-
-                 // Şimdilik yer tutucu
-                 kernel_error_to_i64(SahneError::NotSupported) // Henüz desteklenmiyor
-                 // Bu kısım ciddi implementasyon gerektirir (MMU/Paging, sanal bellek yönetimi).
+                 match mm::sys_unmap_shared(addr, size) {
+                     Ok(()) => 0i64,
+                     Err(err) => kernel_error_to_i64(err),
+                 }
+            }
+            arch::SYSCALL_SET_LIMITS => {
+                // set_limits(task_id: u64, limits: *const ResourceLimits) -> Result<(), SahneError>
+                match limits::sys_set_limits(arg0, arg1 as *const ResourceLimits) {
+                    Ok(()) => 0i64,
+                    Err(err) => kernel_error_to_i64(err),
+                }
+            }
+            arch::SYSCALL_GET_LIMITS => {
+                // get_limits(task_id: u64, out: *mut ResourceLimits) -> Result<(), SahneError>
+                match limits::sys_get_limits(arg0, arg1 as *mut ResourceLimits) {
+                    Ok(()) => 0i64,
+                    Err(err) => kernel_error_to_i64(err),
+                }
             }
             arch::SYSCALL_TASK_YIELD => {
                   yield_now() -> Result<(), SahneError>
@@ -346,7 +340,7 @@ pub extern "C" fn sys_call_handler(trap_frame: *mut TrapFrame) {
         };
 
         // Dönüş değerini a0 registerına yaz
-        (*trap_frame).a0 = return_value as u64;
+        (*trap_frame).a0 = return_value as usize;
 
         // Hata kodu ABI'de a1'de de dönüyorsa, onu da ayarlayın.
         // Sahne64'ün map_kernel_error fonksiyonu sadece a0'daki negatif değere bakıyor,
@@ -363,11 +357,6 @@ pub extern "C" fn sys_call_handler(trap_frame: *mut TrapFrame) {
 // Bu karmaşık logic de ayrı bir modülde (örn. main_kernel/sync_manager.rs) yer alabilir.
 // Syscall handler'lar bu modülün fonksiyonlarını çağırır.
 
-// TODO: Kernel-internal time source logic (Sistem saatini takip etme)
-// Bu logic de ayrı bir modülde (örn. main_kernel/kernel_time.rs) yer alabilir.
-// Syscall handler'lar bu modülün fonksiyonlarını çağırır.
-
-
 // TODO: mm/memory.rs'de syscall'lar için public allocate/deallocate fonksiyonları
  #[no_mangle] pub extern "C" fn sys_allocate(size: usize) -> Result<*mut u8, SahneError> { ... }
  #[no_mangle] pub extern "C" fn sys_deallocate(ptr: *mut u8, size: usize) -> Result<(), SahneError> { ... }
@@ -384,4 +373,65 @@ pub extern "C" fn sys_call_handler(trap_frame: *mut TrapFrame) {
 
 // TODO: exit.rs'de thread exit için public fonksiyon
  #[no_mangle] pub extern "C" fn sys_exit_thread(code: i32) -> ! { ... }
-// Şu an Task Exit ile aynı (exit::sys_exit) kullanılabilir.
\ No newline at end of file
+// Şu an Task Exit ile aynı (exit::sys_exit) kullanılabilir.
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::AtomicU32;
+
+    // Sistem çağrısını trap yolundan geçmeden, sahte bir TrapFrame ile çalıştırır.
+    fn call(syscall_num: u64, arg0: usize, arg1: usize) -> i64 {
+        let mut frame = TrapFrame::zeroed();
+        frame.a7 = syscall_num as usize;
+        frame.a0 = arg0;
+        frame.a1 = arg1;
+        sys_call_handler(&mut frame);
+        frame.a0 as i64
+    }
+
+    #[test_case]
+    fn unknown_syscall_returns_error_code() {
+        assert_eq!(call(9999, 0, 0), kernel_error_to_i64(SahneError::UnknownSystemCall));
+    }
+
+    #[test_case]
+    fn system_time_is_monotonic() {
+        let first = call(arch::SYSCALL_GET_SYSTEM_TIME, 0, 0);
+        let second = call(arch::SYSCALL_GET_SYSTEM_TIME, 0, 0);
+        assert!(first >= 0);
+        assert!(second >= first);
+    }
+
+    #[test_case]
+    fn wake_address_without_waiters_wakes_nobody() {
+        static WORD: AtomicU32 = AtomicU32::new(0);
+        let addr = &WORD as *const AtomicU32 as usize;
+        assert_eq!(call(arch::SYSCALL_WAKE_ADDRESS, addr, 1), 0);
+    }
+
+    #[test_case]
+    fn wake_address_rejects_unaligned_address() {
+        static WORDS: [AtomicU32; 2] = [AtomicU32::new(0), AtomicU32::new(0)];
+        let addr = &WORDS[0] as *const AtomicU32 as usize + 1;
+        assert_eq!(call(arch::SYSCALL_WAKE_ADDRESS, addr, 1), kernel_error_to_i64(SahneError::InvalidAddress));
+    }
+
+    #[test_case]
+    fn error_codes_are_negative_and_distinct() {
+        let errors = [
+            SahneError::PermissionDenied, SahneError::ResourceNotFound, SahneError::TaskCreationFailed,
+            SahneError::Interrupted, SahneError::InvalidHandle, SahneError::ResourceBusy,
+            SahneError::OutOfMemory, SahneError::InvalidAddress, SahneError::NamingError,
+            SahneError::InvalidParameter, SahneError::NotSupported, SahneError::InvalidOperation,
+            SahneError::HandleLimitExceeded, SahneError::UnknownSystemCall, SahneError::TimedOut,
+        ];
+        for (i, a) in errors.iter().enumerate() {
+            assert!(kernel_error_to_i64(*a) < 0);
+            for b in &errors[i + 1..] {
+                assert_ne!(kernel_error_to_i64(*a), kernel_error_to_i64(*b));
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// main_kernel/test_runner.rs
+// Çekirdek Test Çalıştırıcısı (custom_test_frameworks)
+// `cargo test` ile derlenen çekirdek qemu-system-riscv64 -machine virt altında açılır,
+// #[test_case] fonksiyonlarını sırayla çalıştırır, sonuçları UART'a yazar ve
+// QEMU'yu bir çıkış koduyla kapatır. Böylece testler ekransız (headless) çalışabilir.
+
+use core::panic::PanicInfo;
+use crate::printk;
+use crate::sbi;
+
+// QEMU virt makinesindeki sifive_test cihazı (test finisher).
+// Buraya yazılan değer QEMU'yu kapatır; üst 16 bit QEMU'nun çıkış kodudur.
+const SIFIVE_TEST_BASE: usize = 0x10_0000;
+const FINISHER_PASS: u32 = 0x5555;
+const FINISHER_FAIL: u32 = 0x3333;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QemuExitCode {
+    Success, // QEMU 0 ile çıkar
+    Failed,  // QEMU 1 ile çıkar
+}
+
+// Her #[test_case] fonksiyonu için adını yazıp çalıştıran sarmalayıcı.
+pub trait Testable {
+    fn run(&self);
+}
+
+impl<T: Fn()> Testable for T {
+    fn run(&self) {
+        printk!("{} ... ", core::any::type_name::<T>());
+        self();
+        printk!("[ok]\n");
+    }
+}
+
+// #![test_runner] olarak main.rs'de kayıtlıdır. Tüm testler geçerse QEMU'yu başarıyla kapatır.
+// Başarısız bir test panik oluşturur; test_panic_handler QEMU'yu hata koduyla kapatır.
+pub fn run_tests(tests: &[&dyn Testable]) {
+    printk!("{} çekirdek testi çalıştırılıyor\n", tests.len());
+    for test in tests {
+        test.run();
+    }
+    printk!("Tüm testler geçti.\n");
+    exit_qemu(QemuExitCode::Success);
+}
+
+// Test çekirdeğinin panik işleyicisi (main.rs'deki #[cfg(test)] panic_handler'dan çağrılır).
+pub fn test_panic_handler(info: &PanicInfo) -> ! {
+    printk!("[başarısız]\n");
+    if let Some(location) = info.location() {
+        printk!("Konum: {}:{}\n", location.file(), location.line());
+    }
+    if let Some(message) = info.message() {
+        printk!("Mesaj: {}\n", message);
+    }
+    exit_qemu(QemuExitCode::Failed);
+}
+
+// QEMU'yu verilen kodla kapatır.
+pub fn exit_qemu(code: QemuExitCode) -> ! {
+    let value = match code {
+        QemuExitCode::Success => FINISHER_PASS,
+        QemuExitCode::Failed => (1 << 16) | FINISHER_FAIL,
+    };
+    unsafe {
+        core::ptr::write_volatile(SIFIVE_TEST_BASE as *mut u32, value);
+    }
+
+    // sifive_test yoksa (farklı makine tipi) SBI üzerinden kapatmayı dene.
+    let reason = match code {
+        QemuExitCode::Success => sbi::RESET_REASON_NONE,
+        QemuExitCode::Failed => sbi::RESET_REASON_SYSTEM_FAILURE,
+    };
+    let _ = sbi::system_reset(sbi::RESET_TYPE_SHUTDOWN, reason);
+
+    loop {
+        riscv::asm::wfi();
+    }
+}
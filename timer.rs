@@ -0,0 +1,48 @@
+// drivers/timer.rs
+// Zamanlayıcı (Timer) Sürücüsü
+// RISC-V 'time' CSR'ı üzerinden monoton zaman sağlar.
+
+use crate::printk;
+use crate::sbi;
+
+// 'time' CSR'ının saniyedeki tik sayısı.
+// TODO: Device Tree'deki /cpus/timebase-frequency değerinden okunmalı.
+pub const TIMEBASE_FREQ: u64 = 10_000_000; // QEMU virt ve SiFive çekirdekleri için 10 MHz
+
+// Açılıştan beri geçen tik sayısı (monoton, taşmaz).
+pub fn now_ticks() -> u64 {
+    riscv::register::time::read() as u64
+}
+
+// Zamanlayıcı tabanlı görev değişimi için zaman dilimi.
+// Sadece birden fazla görev CPU için yarışırken kullanılır (tickless çalışma).
+pub const TIME_SLICE_TICKS: u64 = TIMEBASE_FREQ / 100; // 10 ms
+
+// Milisaniyeyi tik sayısına çevirir (taşmada doyar).
+pub fn ms_to_ticks(milliseconds: u64) -> u64 {
+    milliseconds.saturating_mul(TIMEBASE_FREQ / 1000)
+}
+
+// Tik sayısını nanosaniyeye çevirir.
+pub fn ticks_to_ns(ticks: u64) -> u64 {
+    // 10 MHz için 1 tik = 100 ns; ara çarpımda taşmayı önlemek için u128 kullanılır.
+    ((ticks as u128 * 1_000_000_000) / TIMEBASE_FREQ as u128) as u64
+}
+
+// Açılıştan beri geçen süre (nanosaniye).
+pub fn now_ns() -> u64 {
+    ticks_to_ns(now_ticks())
+}
+
+// Bir sonraki zamanlayıcı kesmesini programlar. None: beklenen olay yok, periyodik tik de yok.
+// Bekleyen bir zamanlayıcı kesmesi varsa temizlenir.
+pub fn set_next_event(deadline: Option<u64>) {
+    sbi::set_timer(deadline.unwrap_or(u64::MAX));
+}
+
+// Zamanlayıcıyı başlatır.
+pub fn init() {
+    // Periyodik tik yok: zamanlayıcı (scheduler) ihtiyaç duydukça programlar.
+    set_next_event(None);
+    printk!("Timer: Zaman tabanı {} Hz\n", TIMEBASE_FREQ);
+}
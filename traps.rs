@@ -5,6 +5,8 @@ use core::arch::global_asm; // Assembly kodu gömmek için (isteğe bağlı, ayr
 use crate::printk; // printk! makrosunu içeri aktar
 use crate::sys;    // Sistem çağrısı işleyicisini içeri aktar
 use crate::sched;  // Zamanlayıcıyı içeri aktar (eğer timer kesmesi kullanılıyorsa)
+use crate::entropy; // Kesme zamanlaması entropi kaynağıdır
+use crate::drivers::plic; // Harici (cihaz) kesmeleri
 use crate::asm::read_csr; // CSR okuma fonksiyonunu içeri aktar
 
 // TODO: Trap Entry Assembly Kodu
@@ -69,18 +71,37 @@ use crate::asm::read_csr; // CSR okuma fonksiyonunu içeri aktar
 pub struct TrapFrame {
      // sched::TaskContext içindeki register alanlarını buraya kopyalayın
      // Assembly kodu bu sıraya göre kaydetmeli/yüklemelidir!
-    ra: usize,
-    t0: usize, t1: usize, t2: usize,
-    a0: usize, a1: usize, a2: usize, a3: usize, a4: usize, a5: usize, a6: usize, a7: usize,
-    t3: usize, t4: usize, t5: usize, t6: usize,
-    s0: usize, s1: usize, s2: usize, s3: usize, s4: usize, s5: usize, s6: usize, s7: usize,
-    s8: usize, s9: usize, s10: usize, s11: usize,
-    sp: usize,
-    mepc: usize,
-    mstatus: usize,
+    pub ra: usize,
+    pub t0: usize, pub t1: usize, pub t2: usize,
+    pub a0: usize, pub a1: usize, pub a2: usize, pub a3: usize, pub a4: usize, pub a5: usize, pub a6: usize, pub a7: usize,
+    pub t3: usize, pub t4: usize, pub t5: usize, pub t6: usize,
+    pub s0: usize, pub s1: usize, pub s2: usize, pub s3: usize, pub s4: usize, pub s5: usize, pub s6: usize, pub s7: usize,
+    pub s8: usize, pub s9: usize, pub s10: usize, pub s11: usize,
+    pub sp: usize,
+    pub tp: usize,
+    pub mepc: usize,
+    pub mstatus: usize,
     // Diğer CSR'lar veya durumlar
 }
 
+impl TrapFrame {
+    // Tüm registerları sıfır olan bir çerçeve (testler ve çekirdek içi sistem çağrıları için).
+    pub const fn zeroed() -> Self {
+        TrapFrame {
+            ra: 0,
+            t0: 0, t1: 0, t2: 0,
+            a0: 0, a1: 0, a2: 0, a3: 0, a4: 0, a5: 0, a6: 0, a7: 0,
+            t3: 0, t4: 0, t5: 0, t6: 0,
+            s0: 0, s1: 0, s2: 0, s3: 0, s4: 0, s5: 0, s6: 0, s7: 0,
+            s8: 0, s9: 0, s10: 0, s11: 0,
+            sp: 0,
+            tp: 0,
+            mepc: 0,
+            mstatus: 0,
+        }
+    }
+}
+
 
 // Trap işleyici fonksiyonu. Assembly'den çağrılır.
 // Kaydedilmiş TrapFrame'in mutable bir işaretçisini alır.
@@ -90,61 +111,74 @@ pub extern "C" fn handle_trap(trap_frame: *mut TrapFrame) {
     unsafe {
         // Trap nedenini (mcause) oku
         let mcause = read_csr(0x342); // RISC-V mcause CSR adresi 0x342
+        dispatch_trap(trap_frame, mcause);
+    }
+}
 
-        // Trap tipini belirle: Kesme mi (mcause MSB 1) yoksa İstisna mı (mcause MSB 0)?
-        let is_interrupt = (mcause >> 63) & 1 == 1; // 63. bit (MSB for signed 64-bit)
-        let trap_code = mcause & (!(1usize << 63)); // Neden kodu (işaretsiz)
-
-        // Kaydedilmiş mepc'yi al (trap'in olduğu adres)
-        let mepc_val = (*trap_frame).mepc;
-
-        if is_interrupt {
-            // Kesme (Interrupt)
-            match trap_code {
-                7 => { // Machine Timer Interrupt (MTIMER)
-                     printk!("."); // Timer kesmesinin sık çalıştığını görmek için
-                    // TODO: Timer donanımını bir sonraki kesme için programla.
-                    // TODO: Zamanlayıcıyı çağır (eğer preemptive scheduling kullanılıyorsa)
-                     sched::schedule();
-                }
-                // TODO: Diğer kesmeleri (harici, yazılım vb.) burada ele alın.
-                _ => {
-                    printk!("Bilinmeyen Kesme! Kod: {} MEPC: {:#x}\n", trap_code, mepc_val);
-                    // Bilinmeyen kesmede panik veya sistemi durdur.
-                    panic!("Bilinmeyen Kesme");
-                }
+// Trap'i nedenine (mcause) göre işler.
+// CSR okumadan ayrı tutuldu; testler sahte bir mcause ile doğrudan çağırabilir.
+pub(crate) unsafe fn dispatch_trap(trap_frame: *mut TrapFrame, mcause: usize) {
+    // Trap tipini belirle: Kesme mi (mcause MSB 1) yoksa İstisna mı (mcause MSB 0)?
+    let is_interrupt = (mcause >> 63) & 1 == 1; // 63. bit (MSB for signed 64-bit)
+    let trap_code = mcause & (!(1usize << 63)); // Neden kodu (işaretsiz)
+
+    // Kaydedilmiş mepc'yi al (trap'in olduğu adres)
+    let mepc_val = (*trap_frame).mepc;
+
+    // Kesilen görevin yığın kanaryası her trap girişinde kontrol edilir.
+    sched::check_current_stack();
+
+    if is_interrupt {
+        // Kesme (Interrupt)
+        // Kesmelerin geliş anı entropi havuzuna eklenir (öngörülemeyen zamanlama).
+        entropy::add_interrupt_timing(trap_code);
+        match trap_code {
+            5 | 7 => { // Supervisor (STIMER) / Machine Timer Interrupt (MTIMER)
+                 printk!("."); // Timer kesmesinin sık çalıştığını görmek için
+                // Tickless: kesme sadece bir uyku süresi veya zaman dilimi dolduğunda gelir.
+                // schedule() süresi dolanları uyandırır ve bir sonraki kesmeyi yeniden programlar.
+                sched::schedule();
+            }
+            9 | 11 => { // Supervisor / Machine External Interrupt (PLIC üzerinden cihazlar)
+                plic::handle_interrupt();
             }
-        } else {
-            // İstisna (Exception)
-            match trap_code {
-                8 | 9 => { // Environment Call from U-mode (8) or S-mode (9) (Sistem Çağrısı)
-                     printk!("Sistem Çağrısı MEPC: {:#x}\n", mepc_val);
-                    // Sistem çağrısı işleyicisini çağır
-                    sys::sys_call_handler(trap_frame);
-
-                    // Sistem çağrısı tamamlandıktan sonra, yönergeyi atlamak için mepc'yi 4 artır.
-                    // Aksi halde aynı sistem çağrısı tekrar çalışır.
-                    (*trap_frame).mepc = mepc_val.wrapping_add(4); // Yönerge 4 bayt (RV64)
-                }
-                // TODO: Diğer istisnaları (örn. Page Fault, Illegal Instruction, Bus Error) ele alın.
-                // Page Fault'lar (13, 15) bellek yönetimi (mm) için kritiktir.
-                // Illegal Instruction (2) veya Access Fault (1, 3, 5, 7) gibi hatalarda genellikle görev sonlandırılır veya sistem panikler.
-                2 => { // Illegal Instruction
-                     printk!("Illegal Yönerge! MEPC: {:#x}\n", mepc_val);
-                     // Görev sonlandırılabilir veya panik edilebilir.
-                     panic!("Illegal Yönerge");
-                }
-                _ => {
-                    printk!("Bilinmeyen İstisna! Kod: {} MEPC: {:#x}\n", trap_code, mepc_val);
-                    // Bilinmeyen istisnada panik veya sistemi durdur.
-                    panic!("Bilinmeyen İstisna");
-                }
+            // TODO: Diğer kesmeleri (yazılım vb.) burada ele alın.
+            _ => {
+                printk!("Bilinmeyen Kesme! Kod: {} MEPC: {:#x}\n", trap_code, mepc_val);
+                // Bilinmeyen kesmede panik veya sistemi durdur.
+                panic!("Bilinmeyen Kesme");
+            }
+        }
+    } else {
+        // İstisna (Exception)
+        match trap_code {
+            8 | 9 => { // Environment Call from U-mode (8) or S-mode (9) (Sistem Çağrısı)
+                 printk!("Sistem Çağrısı MEPC: {:#x}\n", mepc_val);
+                // Sistem çağrısı işleyicisini çağır
+                sys::sys_call_handler(trap_frame);
+
+                // Sistem çağrısı tamamlandıktan sonra, yönergeyi atlamak için mepc'yi 4 artır.
+                // Aksi halde aynı sistem çağrısı tekrar çalışır.
+                (*trap_frame).mepc = mepc_val.wrapping_add(4); // Yönerge 4 bayt (RV64)
+            }
+            // TODO: Diğer istisnaları (örn. Page Fault, Illegal Instruction, Bus Error) ele alın.
+            // Page Fault'lar (13, 15) bellek yönetimi (mm) için kritiktir.
+            // Illegal Instruction (2) veya Access Fault (1, 3, 5, 7) gibi hatalarda genellikle görev sonlandırılır veya sistem panikler.
+            2 => { // Illegal Instruction
+                 printk!("Illegal Yönerge! MEPC: {:#x}\n", mepc_val);
+                 // Görev sonlandırılabilir veya panik edilebilir.
+                 panic!("Illegal Yönerge");
+            }
+            _ => {
+                printk!("Bilinmeyen İstisna! Kod: {} MEPC: {:#x}\n", trap_code, mepc_val);
+                // Bilinmeyen istisnada panik veya sistemi durdur.
+                panic!("Bilinmeyen İstisna");
             }
         }
-
-        // TODO: Trap'ten dönmeden önce (Assembly'ye dönmeden önce) yapılması gerekenler.
-        // Örneğin, kaydedilmiş TaskContext'teki mepc veya a0 gibi dönüş değerleri güncellendi mi kontrol et.
     }
+
+    // TODO: Trap'ten dönmeden önce (Assembly'ye dönmeden önce) yapılması gerekenler.
+    // Örneğin, kaydedilmiş TaskContext'teki mepc veya a0 gibi dönüş değerleri güncellendi mi kontrol et.
 }
 
 // Çekirdek başlatılırken trap handler'ı ayarlanır.
@@ -170,4 +204,32 @@ pub fn init() {
      unsafe { crate::asm::enable_interrupts(); } // Eğer enable_interrupts varsa kullanın.
     // TODO: Spesifik kesmeleri (timer, external vb.) etkinleştirmek için mie CSR'ını ayarlayın.
      unsafe { crate::asm::write_csr(0x304, some_interrupt_mask); } // RISC-V mie CSR adresi 0x304
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sahne64::arch;
+
+    const INTERRUPT_BIT: usize = 1 << 63;
+    const ECALL_FROM_U_MODE: usize = 8;
+    const SUPERVISOR_TIMER: usize = 5;
+
+    #[test_case]
+    fn ecall_runs_syscall_and_skips_instruction() {
+        let mut frame = TrapFrame::zeroed();
+        frame.a7 = arch::SYSCALL_GET_SYSTEM_TIME as usize;
+        frame.mepc = 0x8000_1000;
+        unsafe { dispatch_trap(&mut frame, ECALL_FROM_U_MODE); }
+        assert_eq!(frame.mepc, 0x8000_1004);
+        assert!(frame.a0 as i64 >= 0);
+    }
+
+    #[test_case]
+    fn timer_interrupt_returns_to_same_instruction() {
+        let mut frame = TrapFrame::zeroed();
+        frame.mepc = 0x8000_2000;
+        unsafe { dispatch_trap(&mut frame, INTERRUPT_BIT | SUPERVISOR_TIMER); }
+        assert_eq!(frame.mepc, 0x8000_2000);
+    }
+}
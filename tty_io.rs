@@ -1,36 +1,387 @@
 // main_kernel/tty_io.rs
-// Terminal (TTY) giriş/çıkış katmanı.
-// Tam teşekküllü bir TTY implementasyonu bu proje için çok karmaşık olabilir
-// ve 'Floppy Disk'e sığmalı' hedefini aşabilir.
-// Şu an için ya boş kalır ya da çok temel, karakter geçişi yapar.
-
- use core::fmt;
- use crate::console; // Veya doğrudan serial kullanabilirsiniz
-
-// Çok temel bir TTY yapısı (sadece geçiş yapan)
- #[allow(dead_code)] // Kullanılmıyorsa uyarı vermemesi için
- struct Tty {
-      wrapped_console: console::ConsoleWriter, // Konsol yazıcısını tutabilir
-     // Diğer TTY durumları (buffer, mod vb.) buraya eklenebilir
- }
-
- #[allow(dead_code)]
- impl Tty {
-//     // Yeni bir Tty oluşturur
-      const fn new() -> Self {
-          Tty {
-               // wrapped_console: console::writer(),
-          }
-      }
-
-//     // Bir karakter yazar
-      pub fn putc(&mut self, byte: u8) {
-          self.wrapped_console.putc(byte);
-      }
-
-//     // Bir karakter okur
-      pub fn getc(&mut self) -> Option<u8> {
-          // self.wrapped_console.getc()
-           None // Şimdilik girdi yok
-      }
- }
\ No newline at end of file
+// Terminal (TTY) Satır Disiplini
+// Konsoldan gelen baytları uygulamalara verilmeden önce işler: kanonik modda satır düzenleme
+// (silme, satır silme, UTF-8 karakter bazında backspace), yankı (echo), Ctrl-C / Ctrl-D; ham
+// (raw) modda baytlar olduğu gibi geçer. Mod, "console" kaynağına resource::control ile
+// CONTROL_TTY_SET_MODE gönderilerek değiştirilir (bkz. console.rs).
+//
+// Bu modül donanıma dokunmaz: girdi baytları input() ile verilir, yankı bir geri çağırma ile
+// yazılır. Bu sayede host testlerinde de çalışır.
+
+use alloc::collections::VecDeque;
+
+use crate::sahne64::resource::{TTY_CANONICAL, TTY_ECHO, TTY_SIGNALS};
+use crate::sahne64::SahneError;
+
+/// Düzenlenen satırın en fazla bayt uzunluğu (satır sonu hariç).
+pub const LINE_MAX: usize = 256;
+/// Okunmayı bekleyen girdinin en fazla bayt sayısı. Dolarsa yeni satırlar kabul edilmez.
+pub const INPUT_MAX: usize = 1024;
+
+/// Açılıştaki mod: kanonik, yankılı, Ctrl-C etkin.
+pub const DEFAULT_MODE: u32 = TTY_CANONICAL | TTY_ECHO | TTY_SIGNALS;
+const ALL_MODES: u32 = TTY_CANONICAL | TTY_ECHO | TTY_SIGNALS;
+
+const CTRL_C: u8 = 0x03;
+const CTRL_D: u8 = 0x04;
+const BELL: u8 = 0x07;
+const BACKSPACE: u8 = 0x08;
+const CTRL_U: u8 = 0x15;
+const ESCAPE: u8 = 0x1B;
+const DELETE: u8 = 0x7F;
+
+// Kanonik modda yutulan terminal kaçış dizileri (ok tuşları vb. satıra girmesin).
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    None,
+    Escape, // ESC alındı
+    Csi,    // ESC [ ... son bayt bekleniyor
+    Ss3,    // ESC O x (bazı terminallerde F1-F4 ve ok tuşları)
+}
+
+pub struct LineDiscipline {
+    mode: u32,
+    line: [u8; LINE_MAX], // Düzenlenmekte olan satır
+    line_len: usize,
+    ready: VecDeque<u8>,           // Okunmaya hazır girdi
+    line_lengths: VecDeque<usize>, // Kanonik modda `ready` içindeki satırların uzunlukları; 0 = dosya sonu (Ctrl-D)
+    escape: EscapeState,
+    dropping_continuation: bool, // Sığmayan çok baytlı karakterin devam baytları atılıyor
+    interrupted: bool,           // Ctrl-C alındı, bir sonraki okuma Interrupted döner
+}
+
+impl LineDiscipline {
+    pub const fn new() -> Self {
+        LineDiscipline {
+            mode: DEFAULT_MODE,
+            line: [0; LINE_MAX],
+            line_len: 0,
+            ready: VecDeque::new(),
+            line_lengths: VecDeque::new(),
+            escape: EscapeState::None,
+            dropping_continuation: false,
+            interrupted: false,
+        }
+    }
+
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    /// Modu değiştirir. Kanonikten ham moda geçerken yarım satır okunabilir hale gelir;
+    /// ham moddan kanoniğe geçerken bekleyen baytlar tek bir satır sayılır.
+    pub fn set_mode(&mut self, mode: u32) -> Result<(), SahneError> {
+        if mode & !ALL_MODES != 0 {
+            return Err(SahneError::InvalidParameter);
+        }
+        let was_canonical = self.mode & TTY_CANONICAL != 0;
+        let canonical = mode & TTY_CANONICAL != 0;
+        if was_canonical && !canonical {
+            let partial = self.line_len.min(INPUT_MAX - self.ready.len());
+            self.ready.extend(&self.line[..partial]);
+            self.line_len = 0;
+            self.line_lengths.clear(); // Ham modda satır sınırı ve dosya sonu yoktur
+        } else if !was_canonical && canonical {
+            self.line_lengths.clear();
+            if !self.ready.is_empty() {
+                self.line_lengths.push_back(self.ready.len());
+            }
+        }
+        self.escape = EscapeState::None;
+        self.dropping_continuation = false;
+        self.mode = mode;
+        Ok(())
+    }
+
+    /// Okunabilir girdi (veya bekleyen Ctrl-C) var mı?
+    pub fn has_input(&self) -> bool {
+        self.interrupted || !self.ready.is_empty() || !self.line_lengths.is_empty()
+    }
+
+    /// Konsoldan gelen bir baytı işler. `output` terminale geri yazılacak yankıyı alır (TTY_ECHO kapalıysa çağrılmaz).
+    pub fn input(&mut self, byte: u8, mut output: impl FnMut(&[u8])) {
+        let echoing = self.mode & TTY_ECHO != 0;
+        let mut echo = |bytes: &[u8]| {
+            if echoing {
+                output(bytes)
+            }
+        };
+
+        if byte == CTRL_C && self.mode & TTY_SIGNALS != 0 {
+            self.line_len = 0;
+            self.ready.clear();
+            self.line_lengths.clear();
+            self.escape = EscapeState::None;
+            self.interrupted = true;
+            echo(b"^C\r\n");
+            return;
+        }
+
+        if self.mode & TTY_CANONICAL == 0 {
+            if self.ready.len() < INPUT_MAX {
+                self.ready.push_back(byte);
+                echo(&[byte]);
+            }
+            return;
+        }
+
+        match self.escape {
+            EscapeState::None => {}
+            EscapeState::Escape => {
+                self.escape = match byte {
+                    b'[' => EscapeState::Csi,
+                    b'O' => EscapeState::Ss3,
+                    _ => EscapeState::None,
+                };
+                return;
+            }
+            EscapeState::Csi => {
+                if (0x40..=0x7E).contains(&byte) {
+                    self.escape = EscapeState::None;
+                }
+                return;
+            }
+            EscapeState::Ss3 => {
+                self.escape = EscapeState::None;
+                return;
+            }
+        }
+
+        match byte {
+            b'\r' | b'\n' => {
+                if self.commit_line(true) {
+                    echo(b"\r\n");
+                } else {
+                    echo(&[BELL]);
+                }
+            }
+            DELETE | BACKSPACE => {
+                if self.erase_char() {
+                    echo(b"\x08 \x08");
+                }
+            }
+            CTRL_U => {
+                while self.erase_char() {
+                    echo(b"\x08 \x08");
+                }
+            }
+            CTRL_D => {
+                // Boş satırda dosya sonu; doluysa satır sonu eklemeden satırı gönderir.
+                if self.line_len == 0 {
+                    if self.line_lengths.len() < INPUT_MAX {
+                        self.line_lengths.push_back(0);
+                    }
+                } else if !self.commit_line(false) {
+                    echo(&[BELL]);
+                }
+            }
+            ESCAPE => self.escape = EscapeState::Escape,
+            b'\t' => self.insert(byte, &mut echo),
+            0x00..=0x1F => {} // Diğer kontrol karakterleri yok sayılır
+            _ => self.insert(byte, &mut echo),
+        }
+    }
+
+    // Satıra bir bayt ekler. Çok baytlı bir UTF-8 karakteri ya bütün olarak sığar ya da hiç eklenmez.
+    fn insert(&mut self, byte: u8, echo: &mut impl FnMut(&[u8])) {
+        let is_continuation = byte & 0xC0 == 0x80;
+        if is_continuation && self.dropping_continuation {
+            return;
+        }
+        self.dropping_continuation = false;
+        let needed = if is_continuation { 1 } else { utf8_sequence_length(byte) };
+        if self.line_len + needed > LINE_MAX {
+            self.dropping_continuation = !is_continuation;
+            echo(&[BELL]);
+            return;
+        }
+        self.line[self.line_len] = byte;
+        self.line_len += 1;
+        echo(&[byte]);
+    }
+
+    // Satırdaki son karakteri (UTF-8 devam baytlarıyla birlikte) siler. Satır boşsa false döner.
+    fn erase_char(&mut self) -> bool {
+        if self.line_len == 0 {
+            return false;
+        }
+        self.line_len -= 1;
+        while self.line_len > 0 && self.line[self.line_len] & 0xC0 == 0x80 {
+            self.line_len -= 1;
+        }
+        true
+    }
+
+    // Düzenlenen satırı okunabilir girdiye taşır. Yer yoksa satır düzenlemede kalır ve false döner.
+    fn commit_line(&mut self, newline: bool) -> bool {
+        let length = self.line_len + newline as usize;
+        if self.ready.len() + length > INPUT_MAX {
+            return false;
+        }
+        self.ready.extend(&self.line[..self.line_len]);
+        if newline {
+            self.ready.push_back(b'\n');
+        }
+        self.line_lengths.push_back(length);
+        self.line_len = 0;
+        true
+    }
+
+    /// Hazır girdiyi okur. Kanonik modda tek çağrı en fazla bir satır döndürür (satır tampona
+    /// sığmazsa kalanı sonraki okumaya kalır); dosya sonunda Ok(0) döner.
+    /// Okunacak bir şey yoksa ResourceBusy, Ctrl-C alındıysa bir kez Interrupted döner.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize, SahneError> {
+        if self.interrupted {
+            self.interrupted = false;
+            return Err(SahneError::Interrupted);
+        }
+
+        let available = if self.mode & TTY_CANONICAL != 0 {
+            match self.line_lengths.front_mut() {
+                Some(0) => {
+                    self.line_lengths.pop_front();
+                    return Ok(0);
+                }
+                Some(length) => {
+                    let count = (*length).min(buffer.len());
+                    *length -= count;
+                    if *length == 0 {
+                        self.line_lengths.pop_front();
+                    }
+                    count
+                }
+                None => return Err(SahneError::ResourceBusy),
+            }
+        } else if self.ready.is_empty() {
+            return Err(SahneError::ResourceBusy);
+        } else {
+            self.ready.len().min(buffer.len())
+        };
+
+        for (slot, byte) in buffer.iter_mut().zip(self.ready.drain(..available)) {
+            *slot = byte;
+        }
+        Ok(available)
+    }
+}
+
+impl Default for LineDiscipline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// UTF-8 baş baytından karakterin toplam bayt sayısı. Geçersiz baytlar tek bayt sayılır.
+fn utf8_sequence_length(byte: u8) -> usize {
+    match byte {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => 1,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    // Baytları sırayla verir, yankıyı toplar.
+    fn feed(tty: &mut LineDiscipline, input: &[u8]) -> Vec<u8> {
+        let mut echoed = Vec::new();
+        for &byte in input {
+            tty.input(byte, |bytes| echoed.extend_from_slice(bytes));
+        }
+        echoed
+    }
+
+    fn read_all(tty: &mut LineDiscipline) -> Result<Vec<u8>, SahneError> {
+        let mut buffer = [0u8; 64];
+        tty.read(&mut buffer).map(|count| buffer[..count].to_vec())
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn canonical_mode_buffers_lines_and_echoes() {
+        let mut tty = LineDiscipline::new();
+        assert_eq!(feed(&mut tty, b"ls"), b"ls");
+        assert_eq!(tty.read(&mut [0u8; 8]), Err(SahneError::ResourceBusy)); // Satır bitmedi
+
+        assert_eq!(feed(&mut tty, b" -l\rcd /\n"), b" -l\r\ncd /\r\n");
+        assert_eq!(read_all(&mut tty).unwrap(), b"ls -l\n");
+        // Küçük tampon: satırın kalanı sonraki okumaya kalır, bir sonraki satıra taşmaz.
+        let mut small = [0u8; 3];
+        assert_eq!(tty.read(&mut small), Ok(3));
+        assert_eq!(&small, b"cd ");
+        assert_eq!(read_all(&mut tty).unwrap(), b"/\n");
+        assert_eq!(tty.read(&mut small), Err(SahneError::ResourceBusy));
+
+        // Ok tuşu (ESC [ A) ve diğer kontrol karakterleri satıra girmez.
+        assert_eq!(feed(&mut tty, b"a\x1b[Ab\x01\n"), b"ab\r\n");
+        assert_eq!(read_all(&mut tty).unwrap(), b"ab\n");
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn backspace_erases_whole_utf8_characters() {
+        let mut tty = LineDiscipline::new();
+        // "Çığ" + iki backspace -> "Ç"
+        let echoed = feed(&mut tty, "Çığ\x7f\x08".as_bytes());
+        assert!(echoed.ends_with(b"\x08 \x08\x08 \x08"));
+        feed(&mut tty, b"\n");
+        assert_eq!(read_all(&mut tty).unwrap(), "Ç\n".as_bytes());
+
+        // Ctrl-U tüm satırı siler; boş satırda backspace bir şey yazmaz.
+        feed(&mut tty, "şöyle".as_bytes());
+        assert_eq!(feed(&mut tty, &[CTRL_U]), b"\x08 \x08".repeat(5));
+        assert_eq!(feed(&mut tty, &[DELETE]), b"");
+        feed(&mut tty, b"\n");
+        assert_eq!(read_all(&mut tty).unwrap(), b"\n");
+
+        // Satır dolarken sığmayan iki baytlı karakter bütünüyle reddedilir.
+        feed(&mut tty, &[b'x'; LINE_MAX - 1]);
+        assert_eq!(feed(&mut tty, "ü".as_bytes()), [BELL]);
+        assert_eq!(feed(&mut tty, b"y\n"), b"y\r\n");
+        let mut line = [0u8; LINE_MAX + 1];
+        assert_eq!(tty.read(&mut line), Ok(LINE_MAX + 1));
+        assert_eq!(line[LINE_MAX - 1], b'y');
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn control_characters_interrupt_and_end_input() {
+        let mut tty = LineDiscipline::new();
+        feed(&mut tty, b"tamam\n");
+        assert_eq!(feed(&mut tty, b"yarim\x03"), b"yarim^C\r\n");
+        assert_eq!(tty.read(&mut [0u8; 8]), Err(SahneError::Interrupted));
+        assert_eq!(tty.read(&mut [0u8; 8]), Err(SahneError::ResourceBusy)); // Bekleyen girdi de atıldı
+
+        // Ctrl-D: dolu satırı satır sonu olmadan gönderir, boş satırda dosya sonu verir.
+        feed(&mut tty, b"son\x04\x04");
+        assert_eq!(read_all(&mut tty).unwrap(), b"son");
+        assert_eq!(tty.read(&mut [0u8; 8]), Ok(0));
+
+        // TTY_SIGNALS kapalıyken Ctrl-C sıradan bir kontrol karakteridir; yankı kapalıyken hiçbir şey yazılmaz.
+        tty.set_mode(TTY_CANONICAL).unwrap();
+        assert_eq!(feed(&mut tty, b"gizli\x03\n"), b"");
+        assert_eq!(read_all(&mut tty).unwrap(), b"gizli\n");
+        assert_eq!(tty.set_mode(1 << 7), Err(SahneError::InvalidParameter));
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn raw_mode_passes_bytes_through() {
+        let mut tty = LineDiscipline::new();
+        feed(&mut tty, b"ab");
+        tty.set_mode(TTY_SIGNALS).unwrap(); // Yarım satır okunabilir hale gelir
+        assert_eq!(feed(&mut tty, b"\x1b[A\x7f\r"), b""); // Yankı yok, düzenleme yok
+        assert_eq!(read_all(&mut tty).unwrap(), b"ab\x1b[A\x7f\r");
+        assert!(!tty.has_input());
+
+        feed(&mut tty, b"xyz");
+        tty.set_mode(DEFAULT_MODE).unwrap(); // Bekleyen baytlar tek satır olur
+        assert_eq!(read_all(&mut tty).unwrap(), b"xyz");
+        assert_eq!(tty.mode(), DEFAULT_MODE);
+    }
+}
@@ -1,77 +1,410 @@
 // drivers/uart.rs
-// UART (Seri Port) Donanım Sürücüsü
+// NS16550A UART (Seri Port) Sürücüsü
+// QEMU virt ve PacketBox'taki 16550 uyumlu UART'ı sürer: bölen (baud) ve hat ayarı (8N1), FIFO'lar,
+// kesmelerle dolan/boşalan RX ve TX halkaları, hat hatası istatistikleri.
+//
+// Açılışta init() UART'ı varsayılan adreste yoklamalı (polling) modda başlatır, böylece printk! hemen
+// çalışır. PLIC hazır olunca enable_interrupts() alımı ve gönderimi kesmelere geçirir.
+// Yazma hiçbir zaman beklemez: bayt TX halkasına konur, kesme işleyicisi donanım FIFO'sunu doldurur;
+// halka doluysa (veya sürücü kesilen kod tarafından kilitliyse) bayt atılır ve sayılır. Böylece kesme
+// bağlamından printk! takılmaz. Beklenebilen yerler (kullanıcı yazmaları) write_blocking kullanır.
 
-use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use spin::Mutex;
+
+use crate::fdt::Fdt;
+use crate::power::{self, ShutdownStage};
+use crate::printk;
+use crate::ring_buffer::RingBuffer;
 use crate::rs_io; // rs_io.S'deki Assembly MMIO fonksiyonları
+use crate::sched;
+use super::plic;
+
+// Device Tree yoksa kullanılan değerler (QEMU virt; PacketBox'ta da UART0 bu adreste varsayılır).
+const DEFAULT_BASE: usize = 0x1000_0000;
+const DEFAULT_CLOCK_HZ: u32 = 3_686_400;
+const DEFAULT_IRQ: u32 = 10;
+const BAUD_RATE: u32 = 115_200;
+
+// Register offsetleri (bayt genişliğinde)
+const REG_RBR: usize = 0; // Okuma: alınan bayt
+const REG_THR: usize = 0; // Yazma: gönderilecek bayt
+const REG_DLL: usize = 0; // LCR_DLAB=1 iken: bölen düşük baytı
+const REG_IER: usize = 1; // Kesme etkinleştirme
+const REG_DLM: usize = 1; // LCR_DLAB=1 iken: bölen yüksek baytı
+const REG_FCR: usize = 2; // Yazma: FIFO denetimi
+const REG_LCR: usize = 3; // Hat denetimi
+const REG_MCR: usize = 4; // Modem denetimi
+const REG_LSR: usize = 5; // Hat durumu
+
+const IER_RX_AVAILABLE: u8 = 0x01; // Alınan veri (ve FIFO zaman aşımı)
+const IER_TX_EMPTY: u8 = 0x02;     // THR boşaldı
+const IER_LINE_STATUS: u8 = 0x04;  // Taşma, çerçeve/eşlik hatası, break
+
+const FCR_ENABLE: u8 = 0x01;
+const FCR_CLEAR_RX: u8 = 0x02;
+const FCR_CLEAR_TX: u8 = 0x04;
+const FCR_RX_TRIGGER_8: u8 = 0x80; // 8 bayt birikince kesme; daha azı için zaman aşımı kesmesi gelir
 
-// TODO: PacketBox UART donanımının gerçek adresini ve register offsetlerini belirleyin.
-const UART_BASE_ADDRESS: usize = 0x1000_0000; // Varsayımsal
-const UART_TX_REG_OFFSET: usize = 0x00; // Transmit Data Register offset (Varsayımsal)
-const UART_RX_REG_OFFSET: usize = 0x00; // Receive Data Register offset (Varsayımsal)
-const UART_LSR_REG_OFFSET: usize = 0x05; // Line Status Register offset (Varsayımsal)
-const UART_LSR_TX_EMPTY_BIT: u8 = 0x20; // LSR'deki TX Empty biti (Varsayımsal)
-const UART_LSR_RX_DATA_READY_BIT: u8 = 0x01; // LSR'deki RX Data Ready biti (Varsayımsal)
+const LCR_8N1: u8 = 0x03; // 8 veri biti, eşlik yok, 1 durdurma biti
+const LCR_DLAB: u8 = 0x80; // Bölen registerlarına erişim
+
+const MCR_DTR: u8 = 0x01;
+const MCR_RTS: u8 = 0x02;
+const MCR_OUT2: u8 = 0x08; // Bazı kartlarda kesme hattını işlemciye bağlar
+const MCR_LOOPBACK: u8 = 0x10;
+
+const LSR_DATA_READY: u8 = 0x01;
+const LSR_OVERRUN: u8 = 0x02;
+const LSR_PARITY_ERROR: u8 = 0x04;
+const LSR_FRAMING_ERROR: u8 = 0x08;
+const LSR_BREAK: u8 = 0x10;
+const LSR_THR_EMPTY: u8 = 0x20;
+
+// THR boşaldığında FIFO'ya beklemeden yazılabilecek bayt sayısı.
+const FIFO_SIZE: usize = 16;
+const RX_BUFFER_SIZE: usize = 256;
+const TX_BUFFER_SIZE: usize = 2048;
+// write_blocking TX halkasında yer beklerken bu aralıkla yoklar.
+const TX_WAIT_POLL_MS: u64 = 1;
+
+/// Sürücü sayaçları. Hatalı baytlar yine de alınır; sayaçlar hattın sağlığını izlemek içindir.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UartStats {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_overruns: u64, // Donanım FIFO'su taştı (LSR OE): en az bir bayt kayboldu
+    pub rx_dropped: u64,  // RX halkası doluydu, bayt atıldı
+    pub tx_dropped: u64,  // TX halkası doluydu veya sürücü kilitliydi, bayt atıldı
+    pub framing_errors: u64,
+    pub parity_errors: u64,
+    pub breaks: u64,
+}
 
+impl UartStats {
+    const fn new() -> Self {
+        UartStats {
+            rx_bytes: 0,
+            tx_bytes: 0,
+            rx_overruns: 0,
+            rx_dropped: 0,
+            tx_dropped: 0,
+            framing_errors: 0,
+            parity_errors: 0,
+            breaks: 0,
+        }
+    }
+}
 
 struct Uart {
-    base_address: usize,
-    // Diğer yapılandırma alanları (baudrate, vb.) eklenebilir
+    base: usize,
+    ier: u8,          // IER'in olması gereken değeri (kesme işleyicisi geçici olarak 0 yazabilir)
+    interrupts: bool, // false: yoklamalı mod (açılış, panik, kapatma)
+    rx: RingBuffer<RX_BUFFER_SIZE>,
+    tx: RingBuffer<TX_BUFFER_SIZE>,
+    stats: UartStats,
 }
 
 impl Uart {
-    const fn new(base_address: usize) -> Self {
-        Uart { base_address }
+    const fn new(base: usize) -> Self {
+        Uart {
+            base,
+            ier: 0,
+            interrupts: false,
+            rx: RingBuffer::new(),
+            tx: RingBuffer::new(),
+            stats: UartStats::new(),
+        }
+    }
+
+    fn read(&self, register: usize) -> u8 {
+        unsafe { rs_io::mmio_read8(self.base + register) }
+    }
+
+    fn write(&self, register: usize, value: u8) {
+        unsafe { rs_io::mmio_write8(self.base + register, value) }
+    }
+
+    // Bölen, hat formatı ve FIFO'ları programlar. IER korunur.
+    fn configure(&mut self, clock_hz: u32) {
+        self.write(REG_IER, 0);
+        // En yakın bölen: QEMU virt'te 3.6864 MHz / (16 * 115200) = 2
+        let divisor = ((clock_hz + 8 * BAUD_RATE) / (16 * BAUD_RATE)).clamp(1, u16::MAX as u32);
+        self.write(REG_LCR, LCR_DLAB);
+        self.write(REG_DLL, divisor as u8);
+        self.write(REG_DLM, (divisor >> 8) as u8);
+        self.write(REG_LCR, LCR_8N1);
+        self.write(REG_FCR, FCR_ENABLE | FCR_CLEAR_RX | FCR_CLEAR_TX | FCR_RX_TRIGGER_8);
+        self.write(REG_MCR, MCR_DTR | MCR_RTS | MCR_OUT2);
+        self.write(REG_IER, self.ier);
+    }
+
+    // LSR'yi okur ve hata bitlerini sayar (hata bitleri okununca temizlenir).
+    fn line_status(&mut self) -> u8 {
+        let lsr = self.read(REG_LSR);
+        if lsr & LSR_OVERRUN != 0 {
+            self.stats.rx_overruns += 1;
+        }
+        if lsr & LSR_PARITY_ERROR != 0 {
+            self.stats.parity_errors += 1;
+        }
+        if lsr & LSR_FRAMING_ERROR != 0 {
+            self.stats.framing_errors += 1;
+        }
+        if lsr & LSR_BREAK != 0 {
+            self.stats.breaks += 1;
+        }
+        lsr
+    }
+
+    // Donanım FIFO'sundaki tüm baytları RX halkasına taşır.
+    fn receive(&mut self) {
+        while self.line_status() & LSR_DATA_READY != 0 {
+            let byte = self.read(REG_RBR);
+            self.stats.rx_bytes += 1;
+            if !self.rx.push(byte) {
+                self.stats.rx_dropped += 1;
+            }
+        }
+    }
+
+    // THR boşsa TX halkasından FIFO'ya en fazla FIFO_SIZE bayt yazar. Kesmelerle çalışırken halkada
+    // bayt kaldıysa THR boşalma kesmesi açılır, kalmadıysa kapatılır.
+    fn transmit(&mut self) {
+        if self.line_status() & LSR_THR_EMPTY != 0 {
+            for _ in 0..FIFO_SIZE {
+                let Some(byte) = self.tx.pop() else {
+                    break;
+                };
+                self.write(REG_THR, byte);
+                self.stats.tx_bytes += 1;
+            }
+        }
+        if self.interrupts {
+            self.ier = if self.tx.is_empty() { self.ier & !IER_TX_EMPTY } else { self.ier | IER_TX_EMPTY };
+            self.write(REG_IER, self.ier);
+        }
+    }
+
+    // Kesme nedenlerini işler; kesme işleyicisi IER'i maskelediyse geri yükler.
+    fn service(&mut self) {
+        self.receive();
+        self.transmit();
+        self.write(REG_IER, self.ier);
+    }
+
+    fn put(&mut self, byte: u8) {
+        if !self.interrupts {
+            // Yoklamalı mod: önce halkada kalanlar, sonra bayt doğrudan gönderilir.
+            self.flush_polled();
+            self.write_polled(byte);
+            return;
+        }
+        if !self.tx.push(byte) {
+            self.stats.tx_dropped += 1;
+        }
+        // Boşalma kesmesi açıksa FIFO'yu kesme işleyicisi doldurur; değilse verici boştadır.
+        if self.ier & IER_TX_EMPTY == 0 {
+            self.transmit();
+        }
     }
 
-    // UART donanımını başlatır (baudrate, format vb. ayarları).
-    // TODO: Gerçek donanıma göre doldurun.
-    pub fn init(&self) {
-        // Örnek: Varsayımsal bazı ayarlar
-         unsafe { rs_io::mmio_write32(self.base_address + SOME_CONFIG_REG, SOME_VALUE); }
-         printk!("UART donanım sürücüsü başlatıldı.\n"); // Bu çıktı için serial/printk'in çalışıyor olması gerekir.
+    fn write_polled(&mut self, byte: u8) {
+        while self.line_status() & LSR_THR_EMPTY == 0 {
+            core::hint::spin_loop();
+        }
+        self.write(REG_THR, byte);
+        self.stats.tx_bytes += 1;
     }
 
-    // Bir bayt (karakter) gönderir. Göndermeden önce hattın boşalmasını bekler (polling).
-    pub fn putc(&self, byte: u8) {
-        // Transmit buffer boşalana kadar bekle (Line Status Register'ı kontrol et)
-        while unsafe { (rs_io::mmio_read32(self.base_address + UART_LSR_REG_OFFSET) as u8 & UART_LSR_TX_EMPTY_BIT) == 0 } {
-            // Bekle...
+    // TX halkasını kesme beklemeden boşaltır.
+    fn flush_polled(&mut self) {
+        while let Some(byte) = self.tx.pop() {
+            self.write_polled(byte);
         }
-        // Veri kaydına baytı yaz
-        unsafe { rs_io::mmio_write32(self.base_address + UART_TX_REG_OFFSET, byte as u32); }
     }
+}
+
+static UART: Mutex<Uart> = Mutex::new(Uart::new(DEFAULT_BASE));
 
-    // Bir bayt okumaya çalışır. Veri varsa Some(bayt), yoksa None döner (polling).
-    pub fn getc(&self) -> Option<u8> {
-        // Receive buffer'da veri var mı kontrol et (Line Status Register'ı kontrol et)
-        if unsafe { (rs_io::mmio_read32(self.base_address + UART_LSR_REG_OFFSET) as u8 & UART_LSR_RX_DATA_READY_BIT) != 0 } {
-            // Veri kaydından baytı oku
-            Some(unsafe { rs_io::mmio_read32(self.base_address + UART_RX_REG_OFFSET) as u8 })
-        } else {
-            None // Veri yok
+// Kesme işleyicisi sürücüyü kilitli bulursa (kesilen kod kilidi tutuyorsa) UART kesmelerini cihazda
+// maskeler ve işi kilidi bırakan koda devreder. Maskelemek için kilitsiz erişilen adres tutulur.
+static INTERRUPT_DEFERRED: AtomicBool = AtomicBool::new(false);
+static BASE: AtomicUsize = AtomicUsize::new(DEFAULT_BASE);
+// Kayıtlı PLIC kaynağı (0: yoklamalı mod).
+static IRQ: AtomicU32 = AtomicU32::new(0);
+// Sürücü kilitliyken putc'nin attığı baytlar (istatistikler de kilit altında olduğu için ayrı sayılır).
+static BUSY_DROPS: AtomicU64 = AtomicU64::new(0);
+
+// Ertelenmiş kesme işini yapar. Sürücü kilidini bırakan her normal bağlam yolu çağırır.
+fn run_deferred() {
+    if INTERRUPT_DEFERRED.swap(false, Ordering::AcqRel) {
+        match UART.try_lock() {
+            Some(mut uart) => uart.service(),
+            None => INTERRUPT_DEFERRED.store(true, Ordering::Release),
         }
     }
 }
 
-// UART sürücüsünü korumak için global Mutex
-static UART_DRIVER: Mutex<Uart> = Mutex::new(Uart::new(UART_BASE_ADDRESS));
+fn with_uart<R>(f: impl FnOnce(&mut Uart) -> R) -> R {
+    let result = f(&mut UART.lock());
+    run_deferred();
+    result
+}
+
+// PLIC'ten çağrılır (kesme bağlamı).
+fn handle_interrupt() {
+    match UART.try_lock() {
+        Some(mut uart) => uart.service(),
+        None => {
+            // Maskelenmezse seviye tetiklemeli kesme işlenene kadar tekrar tekrar gelir.
+            unsafe { rs_io::mmio_write8(BASE.load(Ordering::Acquire) + REG_IER, 0) };
+            INTERRUPT_DEFERRED.store(true, Ordering::Release);
+        }
+    }
+}
 
-// Sürücüyü başlatmak için dışarıdan çağrılacak fonksiyon
+/// UART'ı varsayılan adreste 115200 8N1 olarak yoklamalı modda başlatır. printk!'ten önce çağrılmalıdır.
 pub fn init() {
-    UART_DRIVER.lock().init();
+    UART.lock().configure(DEFAULT_CLOCK_HZ);
+}
+
+/// UART'ı kesmelerle çalışmaya geçirir; plic::init'ten sonra çağrılmalıdır. Device Tree'de "ns16550a"
+/// düğümü varsa adres, saat frekansı ve kesme numarası oradan alınır.
+pub fn enable_interrupts(fdt: Option<&Fdt>) {
+    let node = fdt.and_then(|fdt| fdt.compatible_nodes("ns16550a").next());
+    let irq = node.as_ref().and_then(|node| node.interrupt()).unwrap_or(DEFAULT_IRQ);
+    if let Some(node) = node.as_ref() {
+        let base = node.reg(0).map_or(DEFAULT_BASE, |(base, _)| base as usize);
+        let clock_hz = node.property_u32("clock-frequency").unwrap_or(DEFAULT_CLOCK_HZ);
+        with_uart(|uart| {
+            uart.flush_polled();
+            uart.base = base;
+            BASE.store(base, Ordering::Release);
+            uart.configure(clock_hz);
+        });
+    }
+
+    if let Err(err) = plic::register_handler(irq, handle_interrupt) {
+        printk!("UART: Kesme {} kaydedilemedi ({:?}), yoklamalı modda kalıyor.\n", irq, err);
+        return;
+    }
+    IRQ.store(irq, Ordering::Release);
+    with_uart(|uart| {
+        uart.interrupts = true;
+        uart.ier = IER_RX_AVAILABLE | IER_LINE_STATUS;
+        uart.service();
+    });
+    let _ = power::register_shutdown_hook(ShutdownStage::Drivers, "uart", enter_polled_mode);
+    printk!("UART kesmelerle çalışıyor (kaynak {}).\n", irq);
+}
+
+/// Kesmeleri kapatır ve bekleyen çıktıyı gönderir; sonraki yazmalar doğrudan donanıma gider.
+/// Kapatma sırasında ve panik işleyicisinde (kesmeler kapanmadan önce) kullanılır.
+pub fn enter_polled_mode() {
+    let irq = IRQ.swap(0, Ordering::AcqRel);
+    if irq != 0 {
+        plic::unregister_handler(irq);
+    }
+    let mut uart = UART.lock();
+    uart.interrupts = false;
+    uart.ier = 0;
+    uart.write(REG_IER, 0);
+    uart.flush_polled();
+}
+
+/// Panik işleyicisi için: sürücüyü tutan kod paniklediyse kilidi zorla açar.
+///
+/// # Safety
+/// Sadece panik sırasında, başka hiçbir yürütme bağlamı UART'ı kullanmayacakken çağrılmalıdır.
+pub unsafe fn force_unlock() {
+    if UART.is_locked() {
+        UART.force_unlock();
+    }
 }
 
-// Bir karakter yazmak için dışarıdan çağrılacak fonksiyon
+/// Bir bayt gönderir; beklemez (kesme bağlamından da çağrılabilir). Yer yoksa bayt atılır ve sayılır.
 pub fn putc(byte: u8) {
-    UART_DRIVER.lock().putc(byte);
+    match UART.try_lock() {
+        Some(mut uart) => uart.put(byte),
+        None => {
+            BUSY_DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    run_deferred();
+}
+
+/// Baytları gönderir; TX halkası doluysa yer açılmasını bekler. Sadece görev bağlamından çağrılmalıdır.
+pub fn write_blocking(bytes: &[u8]) {
+    for chunk in bytes.chunks(TX_BUFFER_SIZE / 2) {
+        wait_for_tx_space(chunk.len());
+        for &byte in chunk {
+            putc(byte);
+        }
+    }
 }
 
-// Bir karakter okumak için dışarıdan çağrılacak fonksiyon
-#[allow(dead_code)] // Eğer şimdilik input kullanılmıyorsa
+/// TX halkasında `needed` bayt (en fazla halka boyutu) yer açılana kadar bekler.
+/// Yoklamalı modda yazmalar zaten beklediği için hemen döner.
+pub fn wait_for_tx_space(needed: usize) {
+    let needed = needed.min(TX_BUFFER_SIZE);
+    while with_uart(|uart| uart.interrupts && uart.tx.free() < needed) {
+        sched::sleep(TX_WAIT_POLL_MS);
+    }
+}
+
+/// Alınmış bir bayt varsa döndürür (beklemez).
 pub fn getc() -> Option<u8> {
-    UART_DRIVER.lock().getc()
+    with_uart(|uart| {
+        // Kesme henüz gelmediyse (veya yoklamalı moddaysak) FIFO'daki baytlar da alınır.
+        if uart.rx.is_empty() {
+            uart.receive();
+        }
+        uart.rx.pop()
+    })
 }
 
-// main_kernel/serial.rs veya console.rs bu putc/getc fonksiyonlarını kullanarak
-// fmt::Write traitini implemente edebilir.
\ No newline at end of file
+/// Sürücü sayaçlarının anlık kopyası.
+pub fn stats() -> UartStats {
+    let mut stats = with_uart(|uart| uart.stats);
+    stats.tx_dropped += BUSY_DROPS.load(Ordering::Relaxed);
+    stats
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::timer;
+
+    // Döngü (loopback) modunda gönderilen baytlar hatta çıkmadan alıcıya döner.
+    #[test_case]
+    fn loopback_bytes_arrive_in_rx_ring() {
+        with_uart(|uart| {
+            uart.flush_polled();
+            uart.rx.clear();
+            uart.write(REG_MCR, MCR_LOOPBACK | MCR_OUT2);
+        });
+        let before = stats();
+        for &byte in b"Sahne" {
+            putc(byte);
+        }
+
+        let mut received = [0u8; 5];
+        let mut count = 0;
+        let deadline = timer::now_ticks() + timer::ms_to_ticks(100);
+        while count < received.len() && timer::now_ticks() < deadline {
+            if let Some(byte) = getc() {
+                received[count] = byte;
+                count += 1;
+            }
+        }
+        with_uart(|uart| uart.write(REG_MCR, MCR_DTR | MCR_RTS | MCR_OUT2));
+
+        assert_eq!(&received[..count], b"Sahne");
+        let after = stats();
+        assert_eq!(after.rx_bytes - before.rx_bytes, 5);
+        assert!(after.tx_bytes - before.tx_bytes >= 5);
+        assert_eq!(after.rx_dropped, before.rx_dropped);
+    }
+}
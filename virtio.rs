@@ -0,0 +1,204 @@
+// drivers/virtio.rs
+// Virtio MMIO Taşıma Katmanı (Transport)
+// QEMU 'virt' makinesindeki virtio cihazları (entropi, blok vb.) Device Tree'de "virtio,mmio"
+// düğümleri olarak görünür. Bu modül cihaz başlatma sırasını (reset, özellik anlaşması, kuyruk kurulumu)
+// uygular; cihaza özgü protokoller virtio_rng.rs gibi sürücülerdedir.
+//
+// Sadece modern (sürüm 2) arayüz desteklenir. QEMU varsayılan olarak eski (legacy) arayüzü sunar;
+// '-global virtio-mmio.force-legacy=false' ile çalıştırılmalıdır (bkz. Makefile).
+
+use crate::fdt::Fdt;
+use crate::rs_io;
+use crate::sahne64::SahneError;
+use super::virtqueue::{VirtQueue, QUEUE_SIZE};
+
+const VIRTIO_MMIO_MAGIC: u32 = 0x7472_6976; // "virt" (little-endian)
+const VIRTIO_MMIO_VERSION_MODERN: u32 = 2;
+
+// Register offsetleri (virtio 1.x, bölüm 4.2.2)
+const REG_MAGIC: usize = 0x000;
+const REG_VERSION: usize = 0x004;
+const REG_DEVICE_ID: usize = 0x008;
+const REG_DEVICE_FEATURES: usize = 0x010;
+const REG_DEVICE_FEATURES_SEL: usize = 0x014;
+const REG_DRIVER_FEATURES: usize = 0x020;
+const REG_DRIVER_FEATURES_SEL: usize = 0x024;
+const REG_QUEUE_SEL: usize = 0x030;
+const REG_QUEUE_NUM_MAX: usize = 0x034;
+const REG_QUEUE_NUM: usize = 0x038;
+const REG_QUEUE_READY: usize = 0x044;
+const REG_QUEUE_NOTIFY: usize = 0x050;
+const REG_INTERRUPT_STATUS: usize = 0x060;
+const REG_INTERRUPT_ACK: usize = 0x064;
+const REG_STATUS: usize = 0x070;
+const REG_QUEUE_DESC_LOW: usize = 0x080;
+const REG_QUEUE_DESC_HIGH: usize = 0x084;
+const REG_QUEUE_DRIVER_LOW: usize = 0x090;
+const REG_QUEUE_DRIVER_HIGH: usize = 0x094;
+const REG_QUEUE_DEVICE_LOW: usize = 0x0A0;
+const REG_QUEUE_DEVICE_HIGH: usize = 0x0A4;
+const REG_CONFIG: usize = 0x100;
+
+// Cihaz durumu bitleri
+const STATUS_ACKNOWLEDGE: u32 = 1;
+const STATUS_DRIVER: u32 = 2;
+const STATUS_DRIVER_OK: u32 = 4;
+const STATUS_FEATURES_OK: u32 = 8;
+const STATUS_FAILED: u32 = 128;
+
+// Modern cihazlarda zorunlu özellik biti
+pub const VIRTIO_F_VERSION_1: u64 = 1 << 32;
+
+// Cihaz kimlikleri
+pub const DEVICE_ID_BLOCK: u32 = 2;
+pub const DEVICE_ID_ENTROPY: u32 = 4;
+
+/// Bir virtio-mmio cihazının register bloğu.
+pub struct VirtioMmio {
+    base: usize,
+    interrupt: Option<u32>, // PLIC kaynak numarası (Device Tree'den)
+}
+
+impl VirtioMmio {
+    /// Adresteki cihazı tanır. Boş yuvalar (cihaz kimliği 0) ResourceNotFound,
+    /// eski arayüz NotSupported döner.
+    ///
+    /// # Safety
+    /// `base` bir virtio-mmio register bloğunu göstermelidir.
+    pub unsafe fn probe(base: usize, interrupt: Option<u32>) -> Result<VirtioMmio, SahneError> {
+        let device = VirtioMmio { base, interrupt };
+        if device.read(REG_MAGIC) != VIRTIO_MMIO_MAGIC {
+            return Err(SahneError::ResourceNotFound);
+        }
+        if device.read(REG_VERSION) != VIRTIO_MMIO_VERSION_MODERN {
+            return Err(SahneError::NotSupported);
+        }
+        if device.device_id() == 0 {
+            return Err(SahneError::ResourceNotFound); // QEMU boş yuvaları da listeler
+        }
+        Ok(device)
+    }
+
+    /// Device Tree'deki ilk `device_id` türündeki cihazı bulur.
+    pub fn find(fdt: &Fdt, device_id: u32) -> Option<VirtioMmio> {
+        VirtioMmio::find_all(fdt, device_id).next()
+    }
+
+    /// Device Tree'deki tüm `device_id` türündeki cihazları düğüm sırasıyla döndürür (örn. birden fazla disk).
+    pub fn find_all<'a>(fdt: &Fdt<'a>, device_id: u32) -> impl Iterator<Item = VirtioMmio> + 'a {
+        fdt.compatible_nodes("virtio,mmio").filter_map(move |node| {
+            let (base, _) = node.reg(0)?;
+            let device = unsafe { VirtioMmio::probe(base as usize, node.interrupt()) }.ok()?;
+            (device.device_id() == device_id).then_some(device)
+        })
+    }
+
+    fn read(&self, offset: usize) -> u32 {
+        unsafe { rs_io::mmio_read32(self.base + offset) }
+    }
+
+    fn write(&self, offset: usize, value: u32) {
+        unsafe { rs_io::mmio_write32(self.base + offset, value) }
+    }
+
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    pub fn interrupt(&self) -> Option<u32> {
+        self.interrupt
+    }
+
+    pub fn device_id(&self) -> u32 {
+        self.read(REG_DEVICE_ID)
+    }
+
+    /// Cihazı sıfırlar ve özellikleri anlaşır. `wanted` sürücünün anladığı özelliklerdir;
+    /// cihazın da desteklediği alt küme döner. Ardından kuyruklar kurulup driver_ok() çağrılmalıdır.
+    pub fn begin_init(&self, wanted: u64) -> Result<u64, SahneError> {
+        self.write(REG_STATUS, 0); // Sıfırla
+        self.write(REG_STATUS, STATUS_ACKNOWLEDGE);
+        self.write(REG_STATUS, STATUS_ACKNOWLEDGE | STATUS_DRIVER);
+
+        self.write(REG_DEVICE_FEATURES_SEL, 0);
+        let low = self.read(REG_DEVICE_FEATURES) as u64;
+        self.write(REG_DEVICE_FEATURES_SEL, 1);
+        let high = self.read(REG_DEVICE_FEATURES) as u64;
+        let features = ((high << 32) | low) & (wanted | VIRTIO_F_VERSION_1);
+        if features & VIRTIO_F_VERSION_1 == 0 {
+            self.fail();
+            return Err(SahneError::NotSupported);
+        }
+
+        self.write(REG_DRIVER_FEATURES_SEL, 0);
+        self.write(REG_DRIVER_FEATURES, features as u32);
+        self.write(REG_DRIVER_FEATURES_SEL, 1);
+        self.write(REG_DRIVER_FEATURES, (features >> 32) as u32);
+
+        self.write(REG_STATUS, STATUS_ACKNOWLEDGE | STATUS_DRIVER | STATUS_FEATURES_OK);
+        if self.read(REG_STATUS) & STATUS_FEATURES_OK == 0 {
+            self.fail(); // Cihaz seçilen özellik kümesini kabul etmedi
+            return Err(SahneError::NotSupported);
+        }
+        Ok(features)
+    }
+
+    /// `index` numaralı kuyruğu verilen halkalarla kurar. Kuyruk bu cihaz kullanıldığı sürece yaşamalıdır.
+    pub fn setup_queue(&self, index: u32, queue: &VirtQueue) -> Result<(), SahneError> {
+        self.write(REG_QUEUE_SEL, index);
+        if self.read(REG_QUEUE_READY) != 0 {
+            return Err(SahneError::ResourceBusy);
+        }
+        let max = self.read(REG_QUEUE_NUM_MAX);
+        if max == 0 {
+            return Err(SahneError::ResourceNotFound);
+        }
+        if (max as usize) < QUEUE_SIZE {
+            return Err(SahneError::NotSupported);
+        }
+        self.write(REG_QUEUE_NUM, QUEUE_SIZE as u32);
+        let write_address = |low: usize, high: usize, address: u64| {
+            self.write(low, address as u32);
+            self.write(high, (address >> 32) as u32);
+        };
+        write_address(REG_QUEUE_DESC_LOW, REG_QUEUE_DESC_HIGH, queue.desc_address());
+        write_address(REG_QUEUE_DRIVER_LOW, REG_QUEUE_DRIVER_HIGH, queue.avail_address());
+        write_address(REG_QUEUE_DEVICE_LOW, REG_QUEUE_DEVICE_HIGH, queue.used_address());
+        self.write(REG_QUEUE_READY, 1);
+        Ok(())
+    }
+
+    /// Başlatmayı tamamlar; cihaz bundan sonra kuyrukları işler.
+    pub fn driver_ok(&self) {
+        let status = self.read(REG_STATUS);
+        self.write(REG_STATUS, status | STATUS_DRIVER_OK);
+    }
+
+    /// Cihazı kullanılamaz olarak işaretler.
+    pub fn fail(&self) {
+        let status = self.read(REG_STATUS);
+        self.write(REG_STATUS, status | STATUS_FAILED);
+    }
+
+    /// Cihaza kuyruğa yeni istek eklendiğini bildirir.
+    pub fn notify(&self, queue_index: u32) {
+        self.write(REG_QUEUE_NOTIFY, queue_index);
+    }
+
+    /// Bekleyen kesme nedenlerini okur ve onaylar.
+    pub fn ack_interrupt(&self) -> u32 {
+        let status = self.read(REG_INTERRUPT_STATUS);
+        self.write(REG_INTERRUPT_ACK, status);
+        status
+    }
+
+    /// Cihaza özgü yapılandırma alanından okur (offset yapılandırma alanına göredir).
+    pub fn config_read32(&self, offset: usize) -> u32 {
+        self.read(REG_CONFIG + offset)
+    }
+
+    /// Kapatma sırasında cihazı sıfırlar; DMA durur ve kuyruklar serbest bırakılabilir.
+    pub fn reset(&self) {
+        self.write(REG_STATUS, 0);
+    }
+}
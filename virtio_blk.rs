@@ -0,0 +1,298 @@
+// drivers/virtio_blk.rs
+// Virtio Blok Cihazı Sürücüsü (virtio-blk)
+// QEMU'da '-drive if=none,id=d0,file=disk.img -device virtio-blk-device,drive=d0' ile eklenen diskler.
+// Device Tree'deki sıraya göre ilk disk "emmc0", ikincisi "sdcard1" kaynağı olarak kaydedilir; böylece
+// ext2 katmanı, spm ve kurulum sihirbazı geliştirme makinesinde disk imajlarıyla çalıştırılabilir.
+// Gerçek PacketBox donanımında bu cihaz yoktur; orada emmc.rs ve sd.rs kullanılır.
+//
+// Her istek üç tampondan oluşur: istek başlığı (tür + sektör), veri ve cihazın yazdığı durum baytı.
+// Veri, sürücünün kendi sektör tamponundan geçer. Diskler storage.rs ile kaydedilir; bölümleri de
+// ("emmc0p1" ...) ayrı kaynaklar olarak görünür.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+use crate::block::{self, BlockDevice};
+use crate::fdt::Fdt;
+use crate::power::{self, ShutdownStage};
+use crate::printk;
+use crate::sahne64::SahneError;
+use super::storage;
+use super::timer;
+use super::virtio::{VirtioMmio, DEVICE_ID_BLOCK};
+use super::virtqueue::{Buffer, VirtQueue};
+
+/// virtio-blk adreslemesi her zaman 512 baytlık sektörlerle yapılır.
+pub const SECTOR_SIZE: usize = 512;
+
+// Tek istekte aktarılan en fazla sektör (sürücü tamponunun boyutu). 2 MB RAM için küçük tutulur.
+const BOUNCE_SECTORS: usize = 8;
+
+// Özellik bitleri (virtio spesifikasyonu 5.2.3)
+const VIRTIO_BLK_F_RO: u64 = 1 << 5;    // Cihaz sadece okunabilir
+const VIRTIO_BLK_F_FLUSH: u64 = 1 << 9; // Cihazın yazma önbelleği var; FLUSH isteği desteklenir
+
+// İstek türleri
+const REQUEST_IN: u32 = 0;    // Okuma
+const REQUEST_OUT: u32 = 1;   // Yazma
+const REQUEST_FLUSH: u32 = 4; // Yazma önbelleğini boşalt
+
+// Durum baytı
+const STATUS_OK: u8 = 0;
+const STATUS_UNSUPPORTED: u8 = 2;
+const STATUS_PENDING: u8 = 0xFF; // Cihaz yazmadan önce tampona konan değer
+
+// Yapılandırma alanı: capacity (u64, sektör cinsinden)
+const CONFIG_CAPACITY: usize = 0;
+
+// Disk istekleri sektör sayısına göre uzayabilir; yavaş bir imaj dosyasında da zaman aşımı olmasın.
+const REQUEST_TIMEOUT_MS: u64 = 1000;
+
+/// Kaydedilen disk sayısı ve kaynak adları (Device Tree sırasıyla).
+pub const MAX_DISKS: usize = 2;
+const DISK_NAMES: [&str; MAX_DISKS] = ["emmc0", "sdcard1"];
+
+struct VirtioBlk {
+    device: VirtioMmio,
+    queue: Box<VirtQueue>, // Cihaz halkaların adresini bildiği için yeri değişmemeli
+    bounce: Vec<u8>,       // BOUNCE_SECTORS * SECTOR_SIZE bayt; boyutu değişmez (DMA adresi sabit kalır)
+    capacity: u64,         // Sektör sayısı
+    read_only: bool,
+    has_flush: bool,
+}
+
+impl VirtioBlk {
+    // Bir isteği gönderir ve tamamlanmasını bekler. Veri tamponun ilk `sectors` sektörüdür.
+    fn request(&mut self, kind: u32, sector: u64, sectors: usize) -> Result<(), SahneError> {
+        let mut header = [0u8; 16]; // type: u32, reserved: u32, sector: u64 (little-endian)
+        header[0..4].copy_from_slice(&kind.to_le_bytes());
+        header[8..16].copy_from_slice(&sector.to_le_bytes());
+        let mut status = [STATUS_PENDING];
+
+        let data = &mut self.bounce[..sectors * SECTOR_SIZE];
+        let header_buffer = Buffer::readable(&header);
+        let status_buffer = Buffer::writable(&mut status);
+        // Kilit tutulduğu için kuyrukta aynı anda tek istek bulunur.
+        if sectors == 0 {
+            self.queue.submit(&[header_buffer, status_buffer])?;
+        } else if kind == REQUEST_IN {
+            self.queue.submit(&[header_buffer, Buffer::writable(data), status_buffer])?;
+        } else {
+            self.queue.submit(&[header_buffer, Buffer::readable(data), status_buffer])?;
+        }
+        self.device.notify(0);
+
+        // Kesme yerine sorgulama (polling): header ve status yığında olduğundan istek bitmeden dönülmez.
+        let deadline = timer::now_ticks() + timer::ms_to_ticks(REQUEST_TIMEOUT_MS);
+        loop {
+            if self.queue.pop_used().is_some() {
+                self.device.ack_interrupt();
+                break;
+            }
+            if timer::now_ticks() >= deadline {
+                return Err(SahneError::TimedOut); // Çağıran cihazı durdurur
+            }
+            core::hint::spin_loop();
+        }
+        match unsafe { core::ptr::read_volatile(&status[0]) } {
+            STATUS_OK => Ok(()),
+            STATUS_UNSUPPORTED => Err(SahneError::NotSupported),
+            _ => Err(SahneError::InvalidOperation), // Cihaz G/Ç hatası
+        }
+    }
+
+    // `first` sektöründen itibaren `buffer`ı okur; tampondan büyük aktarımlar parçalara bölünür.
+    fn read_sectors(&mut self, first: u64, buffer: &mut [u8]) -> Result<(), SahneError> {
+        for (index, chunk) in buffer.chunks_mut(self.bounce.len()).enumerate() {
+            let sectors = chunk.len() / SECTOR_SIZE;
+            self.request(REQUEST_IN, first + (index * BOUNCE_SECTORS) as u64, sectors)?;
+            chunk.copy_from_slice(&self.bounce[..chunk.len()]);
+        }
+        Ok(())
+    }
+
+    fn write_sectors(&mut self, first: u64, buffer: &[u8]) -> Result<(), SahneError> {
+        if self.read_only {
+            return Err(SahneError::PermissionDenied);
+        }
+        for (index, chunk) in buffer.chunks(self.bounce.len()).enumerate() {
+            let sectors = chunk.len() / SECTOR_SIZE;
+            self.bounce[..chunk.len()].copy_from_slice(chunk);
+            self.request(REQUEST_OUT, first + (index * BOUNCE_SECTORS) as u64, sectors)?;
+        }
+        Ok(())
+    }
+
+    // Cihazın yazma önbelleğini kalıcı depolamaya yazdırır. Önbelleksiz cihazlarda bir şey yapmaz.
+    fn flush(&mut self) -> Result<(), SahneError> {
+        if !self.has_flush {
+            return Ok(());
+        }
+        self.request(REQUEST_FLUSH, 0, 0)
+    }
+}
+
+static DISKS: [Mutex<Option<VirtioBlk>>; MAX_DISKS] = [Mutex::new(None), Mutex::new(None)];
+
+// Diskin kilidini alıp işlemi çalıştırır. Cihaz yanıt vermezse sıfırlanır ve disk kaldırılır.
+fn with_disk<T>(index: usize, f: impl FnOnce(&mut VirtioBlk) -> Result<T, SahneError>) -> Result<T, SahneError> {
+    let mut guard = DISKS[index].lock();
+    let disk = guard.as_mut().ok_or(SahneError::ResourceNotFound)?;
+    let result = f(disk);
+    if matches!(result, Err(SahneError::TimedOut)) {
+        // Cihaz tamponları sonradan doldurabilir; güvenli olması için cihaz durdurulur.
+        printk!("virtio-blk {} yanıt vermiyor, devre dışı bırakılıyor.\n", DISK_NAMES[index]);
+        disk.device.reset();
+        *guard = None;
+    }
+    result
+}
+
+// DISKS tablosundaki bir diskin BlockDevice yüzü; storage katmanına &'static olarak verilir.
+struct VirtioDisk {
+    index: usize,
+}
+
+static VIRTIO_DISKS: [VirtioDisk; MAX_DISKS] = [VirtioDisk { index: 0 }, VirtioDisk { index: 1 }];
+
+impl BlockDevice for VirtioDisk {
+    fn block_size(&self) -> usize {
+        SECTOR_SIZE
+    }
+
+    fn block_count(&self) -> u64 {
+        DISKS[self.index].lock().as_ref().map_or(0, |disk| disk.capacity)
+    }
+
+    fn read_blocks(&self, first: u64, buffer: &mut [u8]) -> Result<(), SahneError> {
+        block::check_range(self, first, buffer.len())?;
+        with_disk(self.index, |disk| disk.read_sectors(first, buffer))
+    }
+
+    fn write_blocks(&self, first: u64, buffer: &[u8]) -> Result<(), SahneError> {
+        block::check_range(self, first, buffer.len())?;
+        with_disk(self.index, |disk| disk.write_sectors(first, buffer))
+    }
+
+    fn flush(&self) -> Result<(), SahneError> {
+        with_disk(self.index, |disk| disk.flush())
+    }
+
+    fn is_read_only(&self) -> bool {
+        DISKS[self.index].lock().as_ref().is_some_and(|disk| disk.read_only)
+    }
+}
+
+// Bir cihazı başlatır; hata durumunda cihaz FAILED olarak işaretlenir.
+fn probe(device: VirtioMmio) -> Option<VirtioBlk> {
+    let queue = Box::try_new(VirtQueue::new()).ok()?;
+    let mut bounce = Vec::new();
+    bounce.try_reserve_exact(BOUNCE_SECTORS * SECTOR_SIZE).ok()?;
+    bounce.resize(BOUNCE_SECTORS * SECTOR_SIZE, 0);
+
+    let features = device
+        .begin_init(VIRTIO_BLK_F_RO | VIRTIO_BLK_F_FLUSH)
+        .and_then(|features| device.setup_queue(0, &queue).map(|_| features));
+    let features = match features {
+        Ok(features) => features,
+        Err(err) => {
+            printk!("virtio-blk {:#x} başlatılamadı: {:?}\n", device.base(), err);
+            device.fail();
+            return None;
+        }
+    };
+    let capacity = device.config_read32(CONFIG_CAPACITY) as u64
+        | (device.config_read32(CONFIG_CAPACITY + 4) as u64) << 32;
+    device.driver_ok();
+    Some(VirtioBlk {
+        device,
+        queue,
+        bounce,
+        capacity,
+        read_only: features & VIRTIO_BLK_F_RO != 0,
+        has_flush: features & VIRTIO_BLK_F_FLUSH != 0,
+    })
+}
+
+/// Device Tree'deki virtio-blk cihazlarını başlatır ve kaynak olarak kaydeder.
+/// Kaydedilen disk sayısını döndürür (0: QEMU dışında veya disk verilmemiş).
+pub fn init(fdt: &Fdt) -> usize {
+    let mut count = 0;
+    for device in VirtioMmio::find_all(fdt, DEVICE_ID_BLOCK) {
+        if count == MAX_DISKS {
+            printk!("virtio-blk {:#x}: en fazla {} disk desteklenir, atlandı.\n", device.base(), MAX_DISKS);
+            continue;
+        }
+        let base = device.base();
+        let Some(disk) = probe(device) else {
+            continue;
+        };
+        printk!(
+            "virtio-blk {} @ {:#x}: {} KB{}\n",
+            DISK_NAMES[count],
+            base,
+            disk.capacity * SECTOR_SIZE as u64 / 1024,
+            if disk.read_only { " (salt okunur)" } else { "" }
+        );
+        *DISKS[count].lock() = Some(disk);
+        if let Err(err) = storage::register_disk(DISK_NAMES[count], &VIRTIO_DISKS[count]) {
+            printk!("Uyarı: '{}' kaynağı kaydedilemedi: {:?}\n", DISK_NAMES[count], err);
+        }
+        count += 1;
+    }
+    if count > 0 {
+        let _ = power::register_shutdown_hook(ShutdownStage::Drivers, "virtio-blk", shutdown);
+    }
+    count
+}
+
+// Kapatma kancası: yazma önbelleğini boşaltır ve cihazları sıfırlar (yeniden başlatmada DMA yapmasınlar).
+fn shutdown() {
+    for slot in DISKS.iter() {
+        if let Some(mut disk) = slot.lock().take() {
+            if let Err(err) = disk.flush() {
+                printk!("virtio-blk: Önbellek boşaltılamadı: {:?}\n", err);
+            }
+            disk.device.reset();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource_manager;
+    use crate::sahne64::resource::{CONTROL_BLOCK_FLUSH, CONTROL_BLOCK_GET_SIZE, MODE_NONBLOCK, MODE_READ, MODE_WRITE};
+
+    // Test çekirdeği QEMU virt'te 1 MB'lık boş bir test diskiyle çalışır (bkz. Makefile, TEST_DISK).
+    #[test_case]
+    fn test_disk_reads_back_unaligned_writes() {
+        assert!(resource_manager::is_registered("emmc0"));
+        let handle = resource_manager::acquire("emmc0", MODE_READ | MODE_WRITE | MODE_NONBLOCK).unwrap();
+        assert_eq!(resource_manager::control(handle, CONTROL_BLOCK_GET_SIZE, 0), Ok(1024 * 1024));
+
+        // Sektör sınırlarını ve sürücü tamponunun boyutunu aşan, hizasız bir yazma.
+        let mut pattern = alloc::vec![0u8; BOUNCE_SECTORS * SECTOR_SIZE + 700];
+        for (i, byte) in pattern.iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+        let offset = 3 * SECTOR_SIZE as u64 + 100;
+        assert_eq!(resource_manager::write(handle, &pattern, Some(offset)), Ok(pattern.len()));
+
+        let mut read_back = alloc::vec![0u8; pattern.len() + 2];
+        assert_eq!(resource_manager::read(handle, &mut read_back, Some(offset - 1)), Ok(read_back.len()));
+        assert_eq!(read_back[0], 0); // Yazılan alanın öncesi bozulmamalı
+        assert_eq!(&read_back[1..pattern.len() + 1], &pattern[..]);
+        assert_eq!(read_back[pattern.len() + 1], 0);
+
+        // Diskin sonunda kısa okuma, sonrasında 0; sonrasına yazma reddedilir.
+        let mut tail = [0u8; 16];
+        assert_eq!(resource_manager::read(handle, &mut tail, Some(1024 * 1024 - 8)), Ok(8));
+        assert_eq!(resource_manager::read(handle, &mut tail, Some(1024 * 1024)), Ok(0));
+        assert_eq!(resource_manager::write(handle, &tail, Some(1024 * 1024)), Err(SahneError::InvalidParameter));
+        assert_eq!(resource_manager::control(handle, CONTROL_BLOCK_FLUSH, 0), Ok(0));
+        assert_eq!(resource_manager::release(handle), Ok(()));
+    }
+}
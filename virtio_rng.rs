@@ -0,0 +1,92 @@
+// drivers/virtio_rng.rs
+// Virtio Entropi Cihazı Sürücüsü (virtio-rng)
+// QEMU'da '-device virtio-rng-device' ile eklenen donanım rastgele sayı kaynağı. Cihazın tek bir
+// kuyruğu vardır: sürücü yazılabilir bir tampon verir, cihaz onu rastgele baytlarla doldurur.
+// Gerçek PacketBox donanımında bu cihaz yoktur; entropi havuzu diğer kaynaklarla çalışmaya devam eder.
+
+use alloc::boxed::Box;
+use spin::Mutex;
+
+use crate::fdt::Fdt;
+use crate::power::{self, ShutdownStage};
+use crate::printk;
+use crate::sahne64::SahneError;
+use super::timer;
+use super::virtio::{VirtioMmio, DEVICE_ID_ENTROPY};
+use super::virtqueue::{Buffer, VirtQueue};
+
+// Cihaz istekleri genellikle mikro saniyeler içinde tamamlar; yanıt vermeyen cihazda çekirdek takılmasın.
+const REQUEST_TIMEOUT_MS: u64 = 100;
+
+struct VirtioRng {
+    device: VirtioMmio,
+    queue: Box<VirtQueue>, // Cihaz halkaların adresini bildiği için yeri değişmemeli
+}
+
+static RNG_DEVICE: Mutex<Option<VirtioRng>> = Mutex::new(None);
+
+/// Device Tree'de bir virtio-rng cihazı arar ve başlatır. Cihaz bulunup kullanılabilir hale gelirse true döner.
+pub fn init(fdt: &Fdt) -> bool {
+    let Some(device) = VirtioMmio::find(fdt, DEVICE_ID_ENTROPY) else {
+        return false;
+    };
+    let queue = match Box::try_new(VirtQueue::new()) {
+        Ok(queue) => queue,
+        Err(_) => return false,
+    };
+    let ready = device.begin_init(0).and_then(|_| device.setup_queue(0, &queue));
+    if let Err(err) = ready {
+        printk!("virtio-rng {:#x} başlatılamadı: {:?}\n", device.base(), err);
+        device.fail();
+        return false;
+    }
+    device.driver_ok();
+    printk!("virtio-rng bulundu @ {:#x}\n", device.base());
+
+    *RNG_DEVICE.lock() = Some(VirtioRng { device, queue });
+    let _ = power::register_shutdown_hook(ShutdownStage::Drivers, "virtio-rng", shutdown);
+    true
+}
+
+/// Cihazdan rastgele bayt okur ve okunan bayt sayısını döndürür (cihaz daha az verebilir).
+/// Cihaz yoksa NotSupported, cihaz zamanında yanıt vermezse TimedOut döner.
+pub fn read(buffer: &mut [u8]) -> Result<usize, SahneError> {
+    if buffer.is_empty() {
+        return Ok(0);
+    }
+    let mut guard = RNG_DEVICE.lock();
+    let rng = guard.as_mut().ok_or(SahneError::NotSupported)?;
+
+    // Kilit tutulduğu için kuyrukta aynı anda tek istek bulunur.
+    rng.queue.submit(&[Buffer::writable(buffer)])?;
+    rng.device.notify(0);
+
+    // Kesme yerine sorgulama (polling): istek kısa sürer ve sadece tohumlama sırasında yapılır.
+    let deadline = timer::now_ticks() + timer::ms_to_ticks(REQUEST_TIMEOUT_MS);
+    loop {
+        if let Some((_, written)) = rng.queue.pop_used() {
+            rng.device.ack_interrupt();
+            return Ok((written as usize).min(buffer.len()));
+        }
+        if timer::now_ticks() >= deadline {
+            // Cihaz tamponu sonradan doldurabilir; güvenli olması için cihaz durdurulur.
+            printk!("virtio-rng yanıt vermiyor, devre dışı bırakılıyor.\n");
+            rng.device.reset();
+            *guard = None;
+            return Err(SahneError::TimedOut);
+        }
+        core::hint::spin_loop();
+    }
+}
+
+/// virtio-rng cihazı kullanılabilir mi?
+pub fn is_present() -> bool {
+    RNG_DEVICE.lock().is_some()
+}
+
+// Kapatma kancası: cihazı sıfırlar, böylece yeniden başlatma sırasında belleğe DMA yapmaz.
+fn shutdown() {
+    if let Some(rng) = RNG_DEVICE.lock().take() {
+        rng.device.reset();
+    }
+}
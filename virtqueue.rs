@@ -0,0 +1,293 @@
+// drivers/virtqueue.rs
+// Virtio Bölünmüş Kuyruk (Split Virtqueue)
+// Sürücü ile virtio cihazı arasındaki paylaşılan bellek halkaları: tanımlayıcı tablosu (descriptor table),
+// sürücünün doldurduğu "available" halka ve cihazın doldurduğu "used" halka (virtio 1.x, bölüm 2.7).
+// Sadece bellek işlemleri içerir; register erişimi virtio.rs'dedir. MMU olmadığı için
+// sanal adres = fiziksel adres kabul edilir.
+
+use core::ptr;
+use core::sync::atomic::{fence, Ordering};
+
+use crate::sahne64::SahneError;
+
+/// Kuyruk başına tanımlayıcı sayısı. 2 MB'lık sistemde küçük tutulur;
+/// bir blok isteği 3, bir entropi isteği 1 tanımlayıcı kullanır.
+pub const QUEUE_SIZE: usize = 8;
+
+const VIRTQ_DESC_F_NEXT: u16 = 1;  // Zincir bir sonraki tanımlayıcıyla devam eder
+const VIRTQ_DESC_F_WRITE: u16 = 2; // Tampon cihaz tarafından yazılır
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Descriptor {
+    addr: u64,
+    len: u32,
+    flags: u16,
+    next: u16,
+}
+
+#[repr(C)]
+struct AvailRing {
+    flags: u16,
+    idx: u16,
+    ring: [u16; QUEUE_SIZE],
+    used_event: u16,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UsedElem {
+    id: u32,
+    len: u32,
+}
+
+#[repr(C)]
+struct UsedRing {
+    flags: u16,
+    idx: u16,
+    ring: [UsedElem; QUEUE_SIZE],
+    avail_event: u16,
+}
+
+/// Cihaza verilecek bir tampon. Bir istek birden fazla tampondan (scatter-gather) oluşabilir.
+#[derive(Clone, Copy, Debug)]
+pub struct Buffer {
+    pub addr: u64,
+    pub len: u32,
+    pub device_writable: bool, // true: cihaz bu tampona yazar (cevap), false: cihaz okur (istek)
+}
+
+impl Buffer {
+    /// Cihazın okuyacağı tampon.
+    pub fn readable(data: &[u8]) -> Buffer {
+        Buffer { addr: data.as_ptr() as u64, len: data.len() as u32, device_writable: false }
+    }
+
+    /// Cihazın dolduracağı tampon.
+    pub fn writable(data: &mut [u8]) -> Buffer {
+        Buffer { addr: data.as_mut_ptr() as u64, len: data.len() as u32, device_writable: true }
+    }
+}
+
+/// Bir kuyruğun tüm halkaları. Cihaz adresleri bu yapının içini gösterdiği için
+/// kuyruk oluşturulduktan sonra taşınmamalıdır (Box içinde veya static tutulur).
+/// Hizalama gereksinimleri: tanımlayıcılar 16, available 2, used 4 bayt.
+#[repr(C, align(16))]
+pub struct VirtQueue {
+    desc: [Descriptor; QUEUE_SIZE],
+    avail: AvailRing,
+    used: UsedRing,
+    free_head: u16, // Boş tanımlayıcı zincirinin başı
+    num_free: u16,
+    last_used: u16, // Sürücünün işlediği son used.idx
+}
+
+impl VirtQueue {
+    pub const fn new() -> Self {
+        let mut desc = [Descriptor { addr: 0, len: 0, flags: 0, next: 0 }; QUEUE_SIZE];
+        let mut index = 0;
+        while index < QUEUE_SIZE {
+            desc[index].next = (index + 1) as u16; // Boş tanımlayıcılar tek zincirde
+            index += 1;
+        }
+        VirtQueue {
+            desc,
+            avail: AvailRing { flags: 0, idx: 0, ring: [0; QUEUE_SIZE], used_event: 0 },
+            used: UsedRing { flags: 0, idx: 0, ring: [UsedElem { id: 0, len: 0 }; QUEUE_SIZE], avail_event: 0 },
+            free_head: 0,
+            num_free: QUEUE_SIZE as u16,
+            last_used: 0,
+        }
+    }
+
+    pub fn desc_address(&self) -> u64 {
+        self.desc.as_ptr() as u64
+    }
+
+    pub fn avail_address(&self) -> u64 {
+        &self.avail as *const AvailRing as u64
+    }
+
+    pub fn used_address(&self) -> u64 {
+        &self.used as *const UsedRing as u64
+    }
+
+    /// Boş tanımlayıcı sayısı.
+    pub fn free_descriptors(&self) -> usize {
+        self.num_free as usize
+    }
+
+    /// Tamponları tek bir istek olarak kuyruğa ekler ve isteğin kimliğini (ilk tanımlayıcı) döndürür.
+    /// Cihaza haber vermek (notify) çağıranın işidir. Yeterli boş tanımlayıcı yoksa ResourceBusy döner.
+    /// Tamponlar, istek pop_used ile geri alınana kadar geçerli kalmalıdır.
+    pub fn submit(&mut self, buffers: &[Buffer]) -> Result<u16, SahneError> {
+        if buffers.is_empty() || buffers.len() > QUEUE_SIZE {
+            return Err(SahneError::InvalidParameter);
+        }
+        if buffers.len() > self.num_free as usize {
+            return Err(SahneError::ResourceBusy);
+        }
+
+        let head = self.free_head;
+        let mut index = head;
+        for (position, buffer) in buffers.iter().enumerate() {
+            let descriptor = &mut self.desc[index as usize];
+            let next_free = descriptor.next;
+            descriptor.addr = buffer.addr;
+            descriptor.len = buffer.len;
+            descriptor.flags = if buffer.device_writable { VIRTQ_DESC_F_WRITE } else { 0 };
+            if position + 1 < buffers.len() {
+                descriptor.flags |= VIRTQ_DESC_F_NEXT;
+            } else {
+                self.free_head = next_free;
+            }
+            index = next_free;
+        }
+        self.num_free -= buffers.len() as u16;
+
+        let slot = self.avail.idx as usize % QUEUE_SIZE;
+        self.avail.ring[slot] = head;
+        // Cihaz, idx'i gördüğünde tanımlayıcıların ve halka girdisinin yazılmış olduğunu görmeli.
+        fence(Ordering::SeqCst);
+        unsafe { ptr::write_volatile(&mut self.avail.idx, self.avail.idx.wrapping_add(1)) };
+        fence(Ordering::SeqCst);
+        Ok(head)
+    }
+
+    /// Cihazın tamamladığı bir isteği alır: (istek kimliği, cihazın yazdığı bayt sayısı).
+    /// İsteğin tanımlayıcıları boş listeye döner.
+    pub fn pop_used(&mut self) -> Option<(u16, u32)> {
+        fence(Ordering::SeqCst);
+        let used_idx = unsafe { ptr::read_volatile(&self.used.idx) };
+        if used_idx == self.last_used {
+            return None;
+        }
+        fence(Ordering::SeqCst); // idx'ten önce yazılan girdiyi oku
+        let slot = self.last_used as usize % QUEUE_SIZE;
+        let elem = unsafe { ptr::read_volatile(&self.used.ring[slot]) };
+        self.last_used = self.last_used.wrapping_add(1);
+
+        // Cihaz geçersiz bir kimlik verirse tanımlayıcılar kaybedilir ama bellek bozulmaz.
+        let head = elem.id as u16;
+        if head as usize >= QUEUE_SIZE {
+            return Some((head, elem.len));
+        }
+        let mut index = head;
+        let mut freed = 1;
+        while self.desc[index as usize].flags & VIRTQ_DESC_F_NEXT != 0 && freed < QUEUE_SIZE {
+            index = self.desc[index as usize].next % QUEUE_SIZE as u16;
+            freed += 1;
+        }
+        self.desc[index as usize].flags = 0;
+        self.desc[index as usize].next = self.free_head;
+        self.free_head = head;
+        self.num_free = (self.num_free + freed as u16).min(QUEUE_SIZE as u16);
+        Some((head, elem.len))
+    }
+}
+
+impl Default for VirtQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+
+    // Cihaz tarafını taklit eder: yeni istekleri alır, yazılabilir tamponları `fill` ile doldurur
+    // ve used halkasına ekler. Her istek için okunabilir tamponların içeriğini döndürür.
+    fn run_device(queue: &mut VirtQueue, device_seen: &mut u16, fill: u8) -> Vec<Vec<u8>> {
+        let mut requests = Vec::new();
+        while *device_seen != queue.avail.idx {
+            let head = queue.avail.ring[*device_seen as usize % QUEUE_SIZE];
+            let mut index = head as usize;
+            let mut readable = Vec::new();
+            let mut written = 0;
+            loop {
+                let descriptor = queue.desc[index];
+                let data = unsafe { core::slice::from_raw_parts_mut(descriptor.addr as *mut u8, descriptor.len as usize) };
+                if descriptor.flags & VIRTQ_DESC_F_WRITE != 0 {
+                    data.fill(fill);
+                    written += descriptor.len;
+                } else {
+                    readable.extend_from_slice(data);
+                }
+                if descriptor.flags & VIRTQ_DESC_F_NEXT == 0 {
+                    break;
+                }
+                index = descriptor.next as usize;
+            }
+            let slot = queue.used.idx as usize % QUEUE_SIZE;
+            queue.used.ring[slot] = UsedElem { id: head as u32, len: written };
+            queue.used.idx = queue.used.idx.wrapping_add(1);
+            *device_seen = device_seen.wrapping_add(1);
+            requests.push(readable);
+        }
+        requests
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn ring_layout_meets_alignment() {
+        let queue = Box::new(VirtQueue::new());
+        assert_eq!(queue.desc_address() % 16, 0);
+        assert_eq!(queue.avail_address() % 2, 0);
+        assert_eq!(queue.used_address() % 4, 0);
+        assert_eq!(queue.avail_address() - queue.desc_address(), 16 * QUEUE_SIZE as u64);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn chained_request_round_trip() {
+        let mut queue = Box::new(VirtQueue::new());
+        let mut device_seen = 0;
+        let header = [1u8, 2, 3, 4];
+        let mut data = [0u8; 16];
+        let mut status = [0xFFu8; 1];
+
+        let id = queue
+            .submit(&[Buffer::readable(&header), Buffer::writable(&mut data), Buffer::writable(&mut status)])
+            .unwrap();
+        assert_eq!(queue.free_descriptors(), QUEUE_SIZE - 3);
+        assert_eq!(queue.pop_used(), None);
+
+        let requests = run_device(&mut queue, &mut device_seen, 0xAB);
+        assert_eq!(requests, [header.to_vec()]);
+        assert_eq!(queue.pop_used(), Some((id, 17)));
+        assert_eq!(queue.free_descriptors(), QUEUE_SIZE);
+        assert_eq!(data, [0xAB; 16]);
+        assert_eq!(status, [0xAB]);
+    }
+
+    #[cfg_attr(target_os = "none", test_case)]
+    #[cfg_attr(not(target_os = "none"), test)]
+    fn full_queue_reports_busy_and_recovers() {
+        let mut queue = Box::new(VirtQueue::new());
+        let mut device_seen = 0;
+        let mut buffers = [[0u8; 4]; QUEUE_SIZE];
+
+        // İndeksler taşsın diye kuyruk birçok kez doldurulup boşaltılır.
+        for round in 0..100u8 {
+            let mut ids = Vec::new();
+            for buffer in buffers.iter_mut() {
+                ids.push(queue.submit(&[Buffer::writable(buffer)]).unwrap());
+            }
+            let mut extra = [0u8; 1];
+            assert_eq!(queue.submit(&[Buffer::writable(&mut extra)]), Err(SahneError::ResourceBusy));
+
+            run_device(&mut queue, &mut device_seen, round);
+            for id in ids {
+                assert_eq!(queue.pop_used(), Some((id, 4)));
+            }
+            assert_eq!(queue.pop_used(), None);
+            assert_eq!(queue.free_descriptors(), QUEUE_SIZE);
+            assert!(buffers.iter().all(|buffer| *buffer == [round; 4]));
+        }
+        assert_eq!(queue.submit(&[]), Err(SahneError::InvalidParameter));
+    }
+}